@@ -3,4 +3,34 @@ fn main() {
     if target_os == "windows" {
         embed_resource::compile("res/icon.rc", embed_resource::NONE);
     }
+
+    #[cfg(feature = "wgpu")]
+    compile_wgpu_shaders();
+}
+
+// The wgpu backend loads its shaders as SPIR-V, so compile the GLSL sources with `WGPU` defined.
+#[cfg(feature = "wgpu")]
+fn compile_wgpu_shaders() {
+    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    let compiler = shaderc::Compiler::new().expect("Failed to create shader compiler.");
+    let mut options = shaderc::CompileOptions::new().expect("Failed to create shader options.");
+    options.add_macro_definition("WGPU", None);
+
+    for (file_name, kind) in [
+        ("checkerboard_resolve.frag", shaderc::ShaderKind::Fragment),
+        ("entire_view.vert", shaderc::ShaderKind::Vertex),
+        ("octree_map.frag", shaderc::ShaderKind::Fragment),
+        ("ray_march_voxels.frag", shaderc::ShaderKind::Fragment),
+        ("speed_lines.frag", shaderc::ShaderKind::Fragment),
+    ] {
+        let source = std::fs::read_to_string(format!("src/shaders/{file_name}")).unwrap();
+        let artifact = compiler
+            .compile_into_spirv(&source, kind, file_name, "main", Some(&options))
+            .unwrap_or_else(|e| panic!("Failed to compile {file_name}: {e}"));
+        std::fs::write(
+            out_dir.join(format!("{file_name}.spv")),
+            artifact.as_binary_u8(),
+        )
+        .unwrap();
+    }
 }
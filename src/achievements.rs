@@ -0,0 +1,202 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{collections::BTreeSet, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use crate::game::Run;
+use crate::i18n::{Language, Text};
+use crate::persistence;
+use crate::stats::Stats;
+use crate::voxels;
+
+// Achievement constants.
+const ACHIEVEMENTS_FILE_NAME: &str = "achievements.toml";
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+const STEADY_HAND_SECONDS: f32 = 5. * 60.;
+const MARATHON_LEVEL: u32 = 10;
+const HIGH_ROLLER_SCORE: u32 = 50;
+const FREQUENT_FLYER_SECONDS: f64 = 60. * 60.;
+const CRASH_TEST_DUMMY_CRASHES: u32 = 100;
+
+// Every achievement that can be unlocked.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Achievement {
+    FirstPortal,
+    DeepDive,
+    Marathon,
+    SteadyHand,
+    HighRoller,
+    FrequentFlyer,
+    CrashTestDummy,
+}
+
+// Snapshot of the game used to evaluate unlock conditions.
+pub struct Progress<'a> {
+    pub run: &'a Run,
+    pub stats: &'a Stats,
+    pub boosting: bool,
+    pub delta_time: f32,
+    // Whether crashing ends the run. Runs that survive crashes make reaching a level or score trivial.
+    pub ends_on_crash: bool,
+}
+
+// Unlocked achievements and the notifications for newly unlocked ones.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Achievements {
+    unlocked: BTreeSet<Achievement>,
+
+    #[serde(skip)]
+    toasts: Vec<(Achievement, Instant)>,
+    #[serde(skip)]
+    unboosted_time: f32,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 7] = [
+        Achievement::FirstPortal,
+        Achievement::DeepDive,
+        Achievement::Marathon,
+        Achievement::SteadyHand,
+        Achievement::HighRoller,
+        Achievement::FrequentFlyer,
+        Achievement::CrashTestDummy,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            Achievement::FirstPortal => Text::AchievementFirstPortal,
+            Achievement::DeepDive => Text::AchievementDeepDive,
+            Achievement::Marathon => Text::AchievementMarathon,
+            Achievement::SteadyHand => Text::AchievementSteadyHand,
+            Achievement::HighRoller => Text::AchievementHighRoller,
+            Achievement::FrequentFlyer => Text::AchievementFrequentFlyer,
+            Achievement::CrashTestDummy => Text::AchievementCrashTestDummy,
+        }
+    }
+
+    pub fn description(self) -> Text {
+        match self {
+            Achievement::FirstPortal => Text::AchievementFirstPortalDescription,
+            Achievement::DeepDive => Text::AchievementDeepDiveDescription,
+            Achievement::Marathon => Text::AchievementMarathonDescription,
+            Achievement::SteadyHand => Text::AchievementSteadyHandDescription,
+            Achievement::HighRoller => Text::AchievementHighRollerDescription,
+            Achievement::FrequentFlyer => Text::AchievementFrequentFlyerDescription,
+            Achievement::CrashTestDummy => Text::AchievementCrashTestDummyDescription,
+        }
+    }
+
+    // Whether the unlock condition of this achievement is met.
+    fn is_met(self, progress: &Progress, unboosted_time: f32) -> bool {
+        match self {
+            Achievement::FirstPortal => progress.stats.portals_taken > 0,
+            Achievement::DeepDive => {
+                progress.run.deepest_portal == Some(voxels::MAXIMUM_GOAL_DEPTH)
+            }
+            Achievement::Marathon => progress.ends_on_crash && progress.run.level >= MARATHON_LEVEL,
+            Achievement::SteadyHand => unboosted_time >= STEADY_HAND_SECONDS,
+            Achievement::HighRoller => {
+                progress.ends_on_crash && progress.run.points >= HIGH_ROLLER_SCORE
+            }
+            Achievement::FrequentFlyer => progress.stats.flight_time >= FREQUENT_FLYER_SECONDS,
+            Achievement::CrashTestDummy => progress.stats.crashes >= CRASH_TEST_DUMMY_CRASHES,
+        }
+    }
+}
+
+impl Achievements {
+    // Load the unlocked achievements from the app directory.
+    pub fn load() -> Self {
+        persistence::load(&persistence::profile_file(ACHIEVEMENTS_FILE_NAME))
+    }
+
+    // Evaluate the unlock conditions against the current frame, saving any newly unlocked achievements.
+    pub fn update(&mut self, progress: &Progress) {
+        if progress.run.start.is_some() && !progress.boosting {
+            self.unboosted_time += progress.delta_time;
+        } else {
+            self.unboosted_time = 0.;
+        }
+
+        let mut unlocked_any = false;
+        for achievement in Achievement::ALL {
+            if !self.unlocked.contains(&achievement)
+                && achievement.is_met(progress, self.unboosted_time)
+            {
+                log::info!(target: "achievements", "Unlocked achievement={achievement:?}");
+                self.unlocked.insert(achievement);
+                self.toasts.push((achievement, Instant::now()));
+                unlocked_any = true;
+            }
+        }
+        if unlocked_any {
+            persistence::save(&persistence::profile_file(ACHIEVEMENTS_FILE_NAME), self);
+        }
+
+        self.toasts
+            .retain(|(_, unlocked_at)| unlocked_at.elapsed() < TOAST_DURATION);
+    }
+
+    // Draw a notification in the corner of the screen for each recently unlocked achievement.
+    pub fn show_toasts(&self, ctx: &egui::Context, language: Language) {
+        if self.toasts.is_empty() {
+            return;
+        }
+        egui::Area::new("achievement_toasts")
+            .anchor(egui::Align2::RIGHT_TOP, [-8., 8.])
+            .interactable(false)
+            .show(ctx, |ui| {
+                for (achievement, _) in &self.toasts {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.label(
+                            egui::RichText::new(language.tr(Text::AchievementUnlocked)).small(),
+                        );
+                        ui.strong(language.tr(achievement.name()));
+                        ui.label(language.tr(achievement.description()));
+                    });
+                }
+            });
+    }
+
+    // List every achievement and whether it has been unlocked.
+    pub fn show_list(&self, ui: &mut egui::Ui, language: Language) {
+        egui::Grid::new("achievements_grid").show(ui, |ui| {
+            for achievement in Achievement::ALL {
+                let unlocked = self.is_unlocked(achievement);
+                ui.label(if unlocked { "✔" } else { "✖" });
+                let description = language.tr(achievement.description());
+                ui.add_enabled(unlocked, egui::Label::new(language.tr(achievement.name())))
+                    .on_disabled_hover_text(description)
+                    .on_hover_text(description);
+                ui.end_row();
+            }
+        });
+    }
+
+    // Getters.
+    pub fn has_toasts(&self) -> bool {
+        !self.toasts.is_empty()
+    }
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+}
@@ -0,0 +1,229 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::{InnerSpace, Rotation, Vector3};
+use egui::{Color32, Context};
+
+use crate::game::State;
+use crate::voxels::{self, VoxelCompact};
+
+// Seconds of flight ahead within which a drone turns away from obstacles.
+const LOOKAHEAD_SECONDS: f32 = 1.;
+
+// How sharply drones turn towards where they want to go.
+const TURN_RATE: f32 = 4.;
+
+// Where the wingman keeps formation relative to the camera, in seconds of the camera's flight.
+const WINGMAN_OFFSET: Vector3<f32> = Vector3::new(0.25, 0.05, -0.3);
+
+// How far the wingman can fall behind, in seconds of the camera's flight, before it rejoins the formation.
+const WINGMAN_MAXIMUM_DISTANCE: f32 = 4.;
+const WINGMAN_COLOUR: Color32 = Color32::from_rgb(90, 200, 255);
+
+// How far behind the camera the pursuer starts, in seconds of the camera's flight.
+const PURSUER_START_DISTANCE: f32 = 2.;
+
+// The pursuer flies a little faster than the camera, so that only boosting, sharp flying, or a portal escapes it.
+const PURSUER_SPEED: f32 = 1.1;
+
+// How far the pursuer can fall behind, in seconds of the camera's flight, before it starts the chase again.
+const PURSUER_MAXIMUM_DISTANCE: f32 = 6.;
+
+// How close the pursuer must come to catch the camera, in seconds of the camera's flight.
+const CATCH_DISTANCE: f32 = 0.08;
+const PURSUER_COLOUR: Color32 = Color32::from_rgb(255, 70, 60);
+
+// Radius of the markers drawn at drones, in seconds of the camera's flight.
+const MARKER_RADIUS: f32 = 0.015;
+
+// A drone flying through the octree, steering clear of voxels with the ray-cast API.
+struct Drone {
+    position: Vector3<f32>,
+    heading: Vector3<f32>,
+}
+
+impl Drone {
+    // Place a drone along an offset from an origin, short of any voxel in the way.
+    fn spawn(
+        origin: Vector3<f32>,
+        offset: Vector3<f32>,
+        heading: Vector3<f32>,
+        octree: &[VoxelCompact],
+    ) -> Self {
+        let distance = offset.magnitude();
+        let direction = offset / distance;
+        let clear = voxels::raycast(origin, direction, octree)
+            .map_or(distance, |hit| distance.min(0.5 * hit.distance));
+        Self {
+            position: origin + direction * clear,
+            heading,
+        }
+    }
+
+    // Turn towards a target while steering clear of voxels ahead, then fly on without entering any.
+    fn fly(&mut self, target: Vector3<f32>, speed: f32, delta_time: f32, octree: &[VoxelCompact]) {
+        let lookahead = LOOKAHEAD_SECONDS * speed;
+        let mut desired = normalize_or(target - self.position, self.heading);
+        if let Some(hit) = voxels::raycast(self.position, self.heading, octree) {
+            if hit.distance < lookahead {
+                desired += 2. * (1. - hit.distance / lookahead) * hit.normal;
+            }
+        }
+        let turn = (TURN_RATE * delta_time).min(1.);
+        let desired = normalize_or(desired, self.heading);
+        self.heading = normalize_or(self.heading + (desired - self.heading) * turn, self.heading);
+
+        // Glance off any surface that would be reached this frame, rather than flying into it.
+        let step = speed * delta_time;
+        match voxels::raycast(self.position, self.heading, octree) {
+            Some(hit) if hit.distance <= step => {
+                self.heading -= 2. * self.heading.dot(hit.normal) * hit.normal;
+            }
+            _ => self.position += self.heading * step,
+        }
+    }
+}
+
+// The unit vector along a vector, or the fallback if the vector is too short to have a direction.
+fn normalize_or(vector: Vector3<f32>, fallback: Vector3<f32>) -> Vector3<f32> {
+    if vector.magnitude2() > f32::EPSILON {
+        vector.normalize()
+    } else {
+        fallback
+    }
+}
+
+// Drones flying with the camera: a friendly wingman keeping formation, and a pursuer chasing the camera
+// through runs in chase mode.
+#[derive(Default)]
+pub struct Drones {
+    wingman: Option<Drone>,
+    pursuer: Option<Drone>,
+}
+
+impl Drones {
+    // Fly the drones enabled in the options after the camera, starting any which are newly needed.
+    pub fn update(
+        &mut self,
+        game: &State,
+        scoring: bool,
+        delta_time: f32,
+        octree: &[VoxelCompact],
+    ) {
+        let speed = game.camera_speed;
+        let forward = game.camera_quaternion.rotate_vector(Vector3::unit_z());
+
+        // The wingman speeds up the further it is from its place in formation, rejoining it if left far behind.
+        if game.options.wingman {
+            let offset = game.camera_quaternion.rotate_vector(WINGMAN_OFFSET * speed);
+            let target = game.camera_position + offset;
+            let spawn = || Drone::spawn(game.camera_position, offset, forward, octree);
+            let wingman = self.wingman.get_or_insert_with(spawn);
+            let distance = (target - wingman.position).magnitude();
+            if distance > WINGMAN_MAXIMUM_DISTANCE * speed {
+                *wingman = spawn();
+            } else {
+                let catch_up = (0.8 + 2. * distance / speed).min(3.);
+                wingman.fly(target, catch_up * speed, delta_time, octree);
+            }
+        } else {
+            self.wingman = None;
+        }
+
+        // The pursuer only chases during runs which keep score, starting again behind the camera if left far behind.
+        let chasing = game.options.chase && game.run.start.is_some() && scoring;
+        if chasing {
+            let offset = -PURSUER_START_DISTANCE * speed * forward;
+            let spawn = || Drone::spawn(game.camera_position, offset, forward, octree);
+            let pursuer = self.pursuer.get_or_insert_with(spawn);
+            if (game.camera_position - pursuer.position).magnitude()
+                > PURSUER_MAXIMUM_DISTANCE * speed
+            {
+                *pursuer = spawn();
+            } else {
+                pursuer.fly(
+                    game.camera_position,
+                    PURSUER_SPEED * speed,
+                    delta_time,
+                    octree,
+                );
+            }
+        } else {
+            self.pursuer = None;
+        }
+    }
+
+    // Whether the pursuer has caught the camera.
+    pub fn caught(&self, camera_position: Vector3<f32>, camera_speed: f32) -> bool {
+        self.pursuer.as_ref().is_some_and(|pursuer| {
+            (pursuer.position - camera_position).magnitude() < CATCH_DISTANCE * camera_speed
+        })
+    }
+
+    // Remove the drones so that they start again from the camera, such as when the world changes or after a crash.
+    pub fn reset(&mut self) {
+        self.wingman = None;
+        self.pursuer = None;
+    }
+
+    // Whether there are no drones to draw.
+    pub fn is_empty(&self) -> bool {
+        self.wingman.is_none() && self.pursuer.is_none()
+    }
+
+    // Draw a glowing marker at each drone in view of the camera.
+    pub fn draw(&self, ctx: &Context, game: &State, time: f32, octree: &[VoxelCompact]) {
+        let camera_position = game.camera_position;
+        let camera_quaternion = game.shaken_camera_quaternion(time);
+        let screen = ctx.screen_rect();
+        let lens = game.lens(screen.aspect_ratio());
+        let inverse = camera_quaternion.invert();
+        let painter = ctx.layer_painter(egui::LayerId::background());
+        let drones = [
+            (&self.wingman, WINGMAN_COLOUR),
+            (&self.pursuer, PURSUER_COLOUR),
+        ];
+        for (drone, colour) in drones {
+            let Some(drone) = drone else {
+                continue;
+            };
+            let view = inverse.rotate_vector(drone.position - camera_position);
+            let Some((centre, depth)) = lens.to_screen(screen, view, 0.) else {
+                continue;
+            };
+
+            // Drones behind voxels are hidden.
+            let distance = view.magnitude();
+            let occluded = voxels::raycast(
+                camera_position,
+                (drone.position - camera_position) / distance,
+                octree,
+            )
+            .is_some_and(|hit| hit.distance < distance);
+            if occluded {
+                continue;
+            }
+            let radius = lens
+                .pixels(screen, MARKER_RADIUS * game.camera_speed, depth)
+                .max(3.);
+            painter.circle_filled(centre, 3. * radius, colour.gamma_multiply(0.12));
+            painter.circle_filled(centre, 2. * radius, colour.gamma_multiply(0.3));
+            painter.circle_filled(centre, radius, colour);
+        }
+    }
+}
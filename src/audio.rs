@@ -0,0 +1,696 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::f32::consts::TAU;
+use std::fs::File;
+use std::io::BufReader;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use rodio::source::{Source, UniformSourceIterator};
+use web_time::Instant;
+
+use crate::game::DEFAULT_CAMERA_SPEED;
+use crate::i18n::{Language, Text};
+use crate::voxel_flight_simulator::app_data_dir;
+
+// Scales a soundtrack can be built on, as semitones above the root: major and minor pentatonic, dorian, lydian,
+// and aeolian.
+const SCALES: [&[u8]; 5] = [
+    &[0, 2, 4, 7, 9],
+    &[0, 3, 5, 7, 10],
+    &[0, 2, 3, 5, 7, 9, 10],
+    &[0, 2, 4, 6, 7, 9, 11],
+    &[0, 2, 3, 5, 7, 8, 10],
+];
+
+// MIDI notes the root of a soundtrack can be, from A2 to A3.
+const ROOT_NOTES: RangeInclusive<u8> = 45..=57;
+
+// Beats per minute of a soundtrack, raised a little by each level up to a limit.
+const TEMPO_RANGE: RangeInclusive<f32> = 72.0..=104.;
+const LEVEL_TEMPO: f32 = 2.;
+const MAXIMUM_LEVEL_TEMPO: f32 = 24.;
+
+// Notes in an arpeggio pattern, and the most octaves it spans, reached after a few levels.
+const PATTERN_LENGTH: usize = 8;
+const MAXIMUM_PATTERN_OCTAVES: u32 = 3;
+
+// Intensity above which the arpeggio plays sixteenth notes rather than eighth notes.
+const DOUBLE_TIME_INTENSITY: f32 = 0.6;
+
+// How quickly the intensity follows the camera's speed, so that the music swells rather than jumps.
+const INTENSITY_SMOOTHING: f32 = 1.5;
+
+// A slow swell in the pad's loudness, in cycles per second.
+const PAD_SWELL_RATE: f32 = 0.1;
+
+// An echo giving the arpeggio some space.
+const ECHO_SECONDS: f32 = 0.3;
+const ECHO_FEEDBACK: f32 = 0.35;
+
+// Kinds of files played from the music folder.
+const SONG_EXTENSIONS: [&str; 2] = ["mp3", "ogg"];
+
+// Seconds over which one song fades into the next, or songs fade in over the generated soundtrack.
+const CROSSFADE_SECONDS: f32 = 3.;
+
+// How often an empty music folder is checked for songs again.
+const PLAYLIST_SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+// Most sound effects played at once. More replace the oldest.
+const MAXIMUM_EFFECTS: usize = 16;
+
+// How long the name of a new song is shown.
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+// The soundtrack of a world: a scale over a root note, a tempo, and an arpeggio pattern climbing it.
+struct Track {
+    root: f32,
+    scale: &'static [u8],
+    tempo: f32,
+    pattern: [u32; PATTERN_LENGTH],
+}
+
+impl Track {
+    // Derive the soundtrack of a world from its seed. Later levels play faster over a wider range.
+    #[allow(clippy::cast_precision_loss)]
+    fn new(seed: u64, level: u32) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let scale = SCALES[rng.gen_range(0..SCALES.len())];
+        let root = midi_frequency(f32::from(rng.gen_range(ROOT_NOTES)));
+        let tempo =
+            rng.gen_range(TEMPO_RANGE) + (LEVEL_TEMPO * level as f32).min(MAXIMUM_LEVEL_TEMPO);
+        let octaves = (1 + level / 2).min(MAXIMUM_PATTERN_OCTAVES);
+        #[allow(clippy::cast_possible_truncation)]
+        let notes = octaves * scale.len() as u32;
+        let mut pattern = [0; PATTERN_LENGTH];
+        for note in &mut pattern {
+            *note = rng.gen_range(0..notes);
+        }
+        Self {
+            root,
+            scale,
+            tempo,
+            pattern,
+        }
+    }
+
+    // The frequency of the given note of the pattern, counting up the scale from the root.
+    #[allow(clippy::cast_precision_loss)]
+    fn frequency(&self, step: usize) -> f32 {
+        let note = self.pattern[step % PATTERN_LENGTH] as usize;
+        let octave = note / self.scale.len();
+        let semitones = 12 * octave + usize::from(self.scale[note % self.scale.len()]);
+        self.root * 2_f32.powf(semitones as f32 / 12.)
+    }
+}
+
+// The frequency in hertz of a MIDI note.
+fn midi_frequency(note: f32) -> f32 {
+    440. * 2_f32.powf((note - 69.) / 12.)
+}
+
+// Sound effects played over the music.
+#[derive(Clone, Copy)]
+pub enum Effect {
+    Fire,
+    Portal,
+    Crash,
+}
+
+impl Effect {
+    // Seconds until the effect has faded away.
+    fn duration(self) -> f32 {
+        match self {
+            Effect::Fire => 0.15,
+            Effect::Portal => 0.8,
+            Effect::Crash => 0.6,
+        }
+    }
+
+    // The pitch of the effect the given number of seconds after it started: a falling zap for firing, a rising
+    // chime for portals, and a low thump for crashes.
+    fn frequency(self, time: f32) -> f32 {
+        match self {
+            Effect::Fire => 1400. * (1. - 0.8 * time / self.duration()),
+            Effect::Portal => 520. * 2_f32.powf(1.5 * time),
+            Effect::Crash => 70.,
+        }
+    }
+
+    // The effect's sample at a time since it started, given the phase of its pitch and some white noise.
+    fn sample(self, time: f32, phase: f32, noise: f32) -> f32 {
+        match self {
+            Effect::Fire => (-25. * time).exp() * phase.sin(),
+            Effect::Portal => {
+                (40. * time).min(1.)
+                    * (-4. * time).exp()
+                    * 0.5
+                    * (phase.sin() + (1.5 * phase).sin())
+            }
+            Effect::Crash => (-8. * time).exp() * phase.sin() + 0.6 * (-6. * time).exp() * noise,
+        }
+    }
+}
+
+// A sound effect being played.
+struct Voice {
+    effect: Effect,
+    time: f32,
+    phase: f32,
+}
+
+// A song decoded from the music folder, as samples for each output channel in turn.
+type Song = Box<dyn Iterator<Item = f32> + Send>;
+
+// Changes sent to the synthesizer on the audio thread.
+enum Message {
+    Track(Track),
+    Song(Song),
+    Silence,
+    Effect(Effect),
+    Intensity(f32),
+    Volume(f32),
+    EffectsVolume(f32),
+}
+
+// Synthesizes the soundtrack one sample at a time on the audio thread: a drone of the root and its fifth under an
+// arpeggio that plays faster, louder, and brighter with intensity. Songs from the music folder are mixed in
+// instead, crossfading from one to the next, and sound effects are played over the music.
+struct Synth {
+    receiver: Receiver<Message>,
+    finished: Sender<()>,
+    sample_rate: f32,
+    song: Option<Song>,
+    fading_song: Option<Song>,
+    crossfade: f32,
+    soundtrack_gain: f32,
+    track: Option<Track>,
+    intensity: f32,
+    target_intensity: f32,
+    volume: f32,
+    swell_phase: f32,
+    step: usize,
+    step_time: f32,
+    note_phase: f32,
+    note_frequency: f32,
+    note_time: f32,
+    pad_phases: [f32; 2],
+    echo: Vec<f32>,
+    echo_index: usize,
+    voices: Vec<Voice>,
+    effects_volume: f32,
+    noise_state: u32,
+}
+
+impl Synth {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn new(receiver: Receiver<Message>, finished: Sender<()>, sample_rate: f32) -> Self {
+        Self {
+            receiver,
+            finished,
+            sample_rate,
+            song: None,
+            fading_song: None,
+            crossfade: 1.,
+            soundtrack_gain: 1.,
+            track: None,
+            intensity: 0.,
+            target_intensity: 0.,
+            volume: 0.,
+            swell_phase: 0.,
+            step: 0,
+            step_time: 0.,
+            note_phase: 0.,
+            note_frequency: 0.,
+            note_time: f32::INFINITY,
+            pad_phases: [0.; 2],
+            echo: vec![0.; (ECHO_SECONDS * sample_rate) as usize],
+            echo_index: 0,
+            voices: Vec::new(),
+            effects_volume: 0.,
+            noise_state: 0x9E37_79B9,
+        }
+    }
+
+    // Apply the changes sent since the last buffer was filled.
+    fn receive(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                Message::Track(track) => {
+                    self.step = 0;
+                    self.step_time = f32::INFINITY;
+                    self.track = Some(track);
+                }
+                Message::Song(song) => {
+                    self.fading_song = self.song.replace(song);
+                    self.crossfade = 0.;
+                }
+                Message::Silence => {
+                    self.track = None;
+                    self.song = None;
+                    self.fading_song = None;
+                }
+                Message::Effect(effect) => {
+                    if self.voices.len() == MAXIMUM_EFFECTS {
+                        self.voices.remove(0);
+                    }
+                    self.voices.push(Voice {
+                        effect,
+                        time: 0.,
+                        phase: 0.,
+                    });
+                }
+                Message::Intensity(intensity) => self.target_intensity = intensity,
+                Message::Volume(volume) => self.volume = volume,
+                Message::EffectsVolume(volume) => self.effects_volume = volume,
+            }
+        }
+    }
+
+    // Move the crossfades along by one frame. The generated soundtrack fades out while songs play.
+    fn advance_crossfade(&mut self) {
+        let step = 1. / (CROSSFADE_SECONDS * self.sample_rate);
+        self.crossfade = (self.crossfade + step).min(1.);
+        if self.crossfade >= 1. {
+            self.fading_song = None;
+        }
+        let playing = self.song.is_some() || self.fading_song.is_some();
+        self.soundtrack_gain = if playing {
+            (self.soundtrack_gain - step).max(0.)
+        } else {
+            (self.soundtrack_gain + step).min(1.)
+        };
+    }
+
+    // The next sample of the songs playing, for one channel. The main thread is told when a song runs out.
+    fn next_song_sample(&mut self) -> f32 {
+        let mut sample = 0.;
+        if let Some(song) = &mut self.song {
+            match song.next() {
+                Some(value) => sample += self.crossfade * value,
+                None => {
+                    self.song = None;
+                    let _ = self.finished.send(());
+                }
+            }
+        }
+        if let Some(song) = &mut self.fading_song {
+            match song.next() {
+                Some(value) => sample += (1. - self.crossfade) * value,
+                None => self.fading_song = None,
+            }
+        }
+        self.volume * sample
+    }
+
+    // The next sample of the sound effects playing.
+    fn next_effects_sample(&mut self) -> f32 {
+        if self.voices.is_empty() {
+            return 0.;
+        }
+
+        // White noise from a xorshift generator, which is cheap enough for the audio thread.
+        self.noise_state ^= self.noise_state << 13;
+        self.noise_state ^= self.noise_state >> 17;
+        self.noise_state ^= self.noise_state << 5;
+        #[allow(clippy::cast_precision_loss)]
+        let noise = self.noise_state as f32 / u32::MAX as f32 * 2. - 1.;
+
+        let delta_time = 1. / self.sample_rate;
+        let mut sample = 0.;
+        for voice in &mut self.voices {
+            voice.phase =
+                (voice.phase + TAU * voice.effect.frequency(voice.time) * delta_time) % TAU;
+            sample += voice.effect.sample(voice.time, voice.phase, noise);
+            voice.time += delta_time;
+        }
+        self.voices
+            .retain(|voice| voice.time < voice.effect.duration());
+        0.3 * self.effects_volume * sample
+    }
+
+    // The next sample of the generated soundtrack.
+    fn next_sample(&mut self) -> f32 {
+        let Some(track) = &self.track else {
+            return 0.;
+        };
+        if self.soundtrack_gain <= 0. {
+            return 0.;
+        }
+        let delta_time = 1. / self.sample_rate;
+        self.intensity +=
+            (self.target_intensity - self.intensity) * (INTENSITY_SMOOTHING * delta_time).min(1.);
+
+        // Start the next note of the arpeggio when one is due.
+        let subdivision = if self.intensity > DOUBLE_TIME_INTENSITY {
+            4.
+        } else {
+            2.
+        };
+        let step_length = 60. / (track.tempo * subdivision);
+        self.step_time += delta_time;
+        if self.step_time >= step_length {
+            self.step_time = 0.;
+            self.note_frequency = track.frequency(self.step);
+            self.note_time = 0.;
+            self.step += 1;
+        }
+
+        // The arpeggio gains overtones as the intensity rises, and each note fades away.
+        self.note_time += delta_time;
+        self.note_phase = (self.note_phase + TAU * self.note_frequency * delta_time) % TAU;
+        let brightness = self.intensity;
+        let envelope = (-self.note_time * (8. - 4. * self.intensity)).exp();
+        let note = envelope
+            * (self.note_phase.sin()
+                + 0.3 * brightness * (2. * self.note_phase).sin()
+                + 0.15 * brightness * (3. * self.note_phase).sin());
+
+        // Pass the arpeggio through the echo.
+        let echoed = note + ECHO_FEEDBACK * self.echo[self.echo_index];
+        self.echo[self.echo_index] = echoed;
+        self.echo_index = (self.echo_index + 1) % self.echo.len();
+
+        // A drone of the root and fifth an octave below, slowly swelling.
+        let mut pad = 0.;
+        for (phase, ratio) in self.pad_phases.iter_mut().zip([0.5, 0.75]) {
+            *phase = (*phase + TAU * ratio * track.root * delta_time) % TAU;
+            pad += phase.sin();
+        }
+        self.swell_phase = (self.swell_phase + TAU * PAD_SWELL_RATE * delta_time) % TAU;
+        let swell = 0.75 + 0.25 * self.swell_phase.sin();
+
+        self.volume
+            * self.soundtrack_gain
+            * (0.12 * swell * pad + (0.12 + 0.18 * self.intensity) * echoed)
+    }
+}
+
+// The folder that songs are played from, creating it if necessary so that it can be opened.
+pub fn music_dir() -> PathBuf {
+    let dir = app_data_dir().join("music");
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log::warn!(target: "audio", "Failed to create the music folder error=\"{e}\"");
+    }
+    dir
+}
+
+// The songs in the music folder, played in a shuffled order which is dealt again each time through.
+#[derive(Default)]
+struct Playlist {
+    songs: Vec<PathBuf>,
+    position: Option<usize>,
+    started: Option<Instant>,
+    duration: Option<Duration>,
+    last_scan: Option<Instant>,
+}
+
+impl Playlist {
+    // Find the songs in the music folder and shuffle them.
+    fn scan(&mut self) {
+        self.last_scan = Some(Instant::now());
+        self.position = None;
+        self.songs = match std::fs::read_dir(music_dir()) {
+            Ok(entries) => entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| {
+                    path.extension()
+                        .and_then(|extension| extension.to_str())
+                        .is_some_and(|extension| {
+                            SONG_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+                        })
+                })
+                .collect(),
+            Err(e) => {
+                log::warn!(target: "audio", "Failed to read the music folder error=\"{e}\"");
+                Vec::new()
+            }
+        };
+        self.songs.shuffle(&mut rand::thread_rng());
+    }
+
+    // Whether the song playing is close enough to its end to crossfade into the next.
+    fn is_ending(&self) -> bool {
+        match (self.started, self.duration) {
+            (Some(started), Some(duration)) => {
+                started.elapsed() + Duration::from_secs_f32(CROSSFADE_SECONDS) >= duration
+            }
+            _ => false,
+        }
+    }
+}
+
+// Plays sound effects on an audio output stream, over either a soundtrack generated for each world or the songs
+// of the music folder when there are any.
+pub struct Audio {
+    sender: Sender<Message>,
+    finished: Receiver<()>,
+    channels: u16,
+    sample_rate: u32,
+    music: bool,
+    track: Option<(u64, u32)>,
+    intensity: f32,
+    volume: f32,
+    effects_volume: f32,
+    playlist: Playlist,
+    toast: Option<(String, Instant)>,
+    _stream: cpal::Stream,
+}
+
+impl Audio {
+    // Start a silent output stream on the default audio device, to play music and sound effects on.
+    pub fn new() -> Result<Self, String> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or("No audio output device")?;
+        let config = device.default_output_config().map_err(|e| e.to_string())?;
+        let (sender, receiver) = mpsc::channel();
+        let (finished_sender, finished) = mpsc::channel();
+        let channels = config.channels();
+        let sample_rate = config.sample_rate().0;
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => {
+                build_stream::<f32>(&device, &config.into(), receiver, finished_sender)
+            }
+            cpal::SampleFormat::I16 => {
+                build_stream::<i16>(&device, &config.into(), receiver, finished_sender)
+            }
+            cpal::SampleFormat::U16 => {
+                build_stream::<u16>(&device, &config.into(), receiver, finished_sender)
+            }
+            format => return Err(format!("Unsupported sample format {format}")),
+        }?;
+        stream.play().map_err(|e| e.to_string())?;
+        Ok(Self {
+            sender,
+            finished,
+            channels,
+            sample_rate,
+            music: false,
+            track: None,
+            intensity: -1.,
+            volume: -1.,
+            effects_volume: -1.,
+            playlist: Playlist::default(),
+            toast: None,
+            _stream: stream,
+        })
+    }
+
+    // Play the songs of the music folder, or else the soundtrack of the given world and level, intensifying with
+    // the camera's speed. Without a music volume, only sound effects are played.
+    pub fn update(
+        &mut self,
+        seed: u64,
+        level: u32,
+        camera_speed: f32,
+        music_volume: Option<f32>,
+        effects_volume: f32,
+    ) {
+        if effects_volume != self.effects_volume {
+            self.effects_volume = effects_volume;
+            self.send(Message::EffectsVolume(effects_volume));
+        }
+        let Some(volume) = music_volume else {
+            if self.music {
+                self.music = false;
+                self.track = None;
+                self.playlist.position = None;
+                self.playlist.started = None;
+                self.toast = None;
+                self.send(Message::Silence);
+            }
+            return;
+        };
+        self.music = true;
+        if self.playlist.songs.is_empty()
+            && self
+                .playlist
+                .last_scan
+                .is_none_or(|scan| scan.elapsed() >= PLAYLIST_SCAN_INTERVAL)
+        {
+            self.playlist.scan();
+        }
+        let finished = self.finished.try_iter().count() > 0;
+        if !self.playlist.songs.is_empty() {
+            if self.playlist.position.is_none() || finished || self.playlist.is_ending() {
+                self.skip(1);
+            }
+        } else if self.track != Some((seed, level)) {
+            self.track = Some((seed, level));
+            self.send(Message::Track(Track::new(seed, level)));
+        }
+        let intensity = (0.5 * camera_speed / DEFAULT_CAMERA_SPEED).clamp(0., 1.);
+        if (intensity - self.intensity).abs() > 0.01 {
+            self.intensity = intensity;
+            self.send(Message::Intensity(intensity));
+        }
+        if volume != self.volume {
+            self.volume = volume;
+            self.send(Message::Volume(volume));
+        }
+        if self
+            .toast
+            .as_ref()
+            .is_some_and(|(_, time)| time.elapsed() >= TOAST_DURATION)
+        {
+            self.toast = None;
+        }
+    }
+
+    // Crossfade into the song the given number of places along the playlist, dealing the songs again on reaching
+    // the end. Songs which can't be played are dropped from the playlist.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    pub fn skip(&mut self, offset: isize) {
+        while self.music && !self.playlist.songs.is_empty() {
+            let count = self.playlist.songs.len() as isize;
+            let position = match self.playlist.position {
+                Some(position) => position as isize + offset,
+                None => 0,
+            };
+            if position >= count {
+                self.playlist.scan();
+                continue;
+            }
+            let position = position.rem_euclid(count) as usize;
+            let path = self.playlist.songs[position].clone();
+            match open_song(&path, self.channels, self.sample_rate) {
+                Ok((song, duration)) => {
+                    let name = path
+                        .file_stem()
+                        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+                    log::info!(target: "audio", "Playing song name=\"{name}\"");
+                    self.playlist.position = Some(position);
+                    self.playlist.started = Some(Instant::now());
+                    self.playlist.duration = duration;
+                    self.toast = Some((name, Instant::now()));
+                    self.track = None;
+                    self.send(Message::Song(song));
+                    return;
+                }
+                Err(e) => {
+                    log::warn!(target: "audio", "Failed to play song path={path:?} error=\"{e}\"");
+                    self.playlist.songs.remove(position);
+                    self.playlist.position = position.checked_sub(1);
+                }
+            }
+        }
+    }
+
+    // Play a sound effect, unless effects are silent.
+    pub fn play_effect(&self, effect: Effect) {
+        if self.effects_volume > 0. {
+            self.send(Message::Effect(effect));
+        }
+    }
+
+    // Draw the name of the song which just started at the bottom of the screen.
+    pub fn show_toast(&self, ctx: &egui::Context, language: Language) {
+        let Some((name, _)) = &self.toast else {
+            return;
+        };
+        egui::Area::new("song_toast")
+            .anchor(egui::Align2::CENTER_BOTTOM, [0., -64.])
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new(language.tr(Text::MusicNowPlaying)).small());
+                    ui.strong(name);
+                });
+            });
+    }
+
+    // Getters.
+    pub fn has_toast(&self) -> bool {
+        self.toast.is_some()
+    }
+
+    fn send(&self, message: Message) {
+        // The audio thread only stops with the stream, which lives as long as the sender.
+        let _ = self.sender.send(message);
+    }
+}
+
+// Decode a song for the output's channels and sample rate, along with its length when it is known.
+fn open_song(
+    path: &Path,
+    channels: u16,
+    sample_rate: u32,
+) -> Result<(Song, Option<Duration>), String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let decoder = rodio::Decoder::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+    let duration = decoder.total_duration();
+    let song = UniformSourceIterator::<_, f32>::new(decoder, channels, sample_rate);
+    Ok((Box::new(song), duration))
+}
+
+// Build an output stream which fills each channel with the soundtrack, songs, and sound effects.
+fn build_stream<T: SizedSample + FromSample<f32>>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    receiver: Receiver<Message>,
+    finished: Sender<()>,
+) -> Result<cpal::Stream, String> {
+    #[allow(clippy::cast_precision_loss)]
+    let mut synth = Synth::new(receiver, finished, config.sample_rate.0 as f32);
+    let channels = usize::from(config.channels);
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                synth.receive();
+                for frame in data.chunks_mut(channels) {
+                    synth.advance_crossfade();
+                    let soundtrack = synth.next_sample() + synth.next_effects_sample();
+                    for sample in frame {
+                        *sample = T::from_sample(soundtrack + synth.next_song_sample());
+                    }
+                }
+            },
+            |e| log::warn!(target: "audio", "Audio stream error error=\"{e}\""),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
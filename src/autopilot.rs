@@ -0,0 +1,150 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::{InnerSpace, Quaternion, Rotation, Vector3};
+
+use crate::voxels::{self, Intersection, TraversalCache, VoxelCompact};
+
+// How often the surroundings are probed for a new heading, in seconds.
+const PROBE_INTERVAL: f32 = 0.15;
+
+// How far ahead to probe, as seconds of flight at the current speed.
+const PROBE_SECONDS: f32 = 2.5;
+
+// Angles from the camera's forward direction of the rings of probe rays, in radians.
+const PROBE_RINGS: [f32; 2] = [0.35, 0.8];
+const PROBE_RING_COUNT: u32 = 8;
+
+// How sharply to turn towards the chosen heading.
+const STEERING_GAIN: f32 = 3.;
+
+// Flies the camera through open space towards any portals that are in view, for the demo shown
+// before a run starts and when the player hands over control.
+#[derive(Default)]
+pub struct Autopilot {
+    pub enabled: bool,
+    cache: TraversalCache,
+    heading: Option<Vector3<f32>>,
+    time_until_probe: f32,
+}
+
+// Rotation inputs chosen by the autopilot, each in the range [-1, 1].
+pub struct Steering {
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+impl Autopilot {
+    // Forget the current heading, which must be done whenever the octree changes.
+    pub fn reset(&mut self) {
+        self.cache.clear();
+        self.heading = None;
+        self.time_until_probe = 0.;
+    }
+
+    // Choose the rotation inputs for this frame, probing for a new heading when one is due.
+    pub fn steer(
+        &mut self,
+        position: Vector3<f32>,
+        orientation: Quaternion<f32>,
+        speed: f32,
+        delta_time: f32,
+        octree: &[VoxelCompact],
+    ) -> Steering {
+        self.time_until_probe -= delta_time;
+        if self.heading.is_none() || self.time_until_probe <= 0. {
+            self.time_until_probe = PROBE_INTERVAL;
+            self.heading = Some(self.probe(position, orientation, speed, octree));
+        }
+
+        // Turn towards the heading in camera space, where the camera looks along +Z with +X right and +Y up.
+        let local = orientation
+            .invert()
+            .rotate_vector(self.heading.unwrap_or(Vector3::unit_z()));
+        let turn = |offset: f32| {
+            if local.z < 0. {
+                // Turn as hard as possible when the heading is behind the camera.
+                offset.signum()
+            } else {
+                (offset * STEERING_GAIN).clamp(-1., 1.)
+            }
+        };
+        Steering {
+            pitch: -turn(local.y),
+            yaw: turn(local.x),
+        }
+    }
+
+    // Greedily pick the probe ray with the best score, returning its world-space direction.
+    // Rays reaching a portal are preferred, nearer ones first, followed by rays with the most open space.
+    fn probe(
+        &mut self,
+        position: Vector3<f32>,
+        orientation: Quaternion<f32>,
+        speed: f32,
+        octree: &[VoxelCompact],
+    ) -> Vector3<f32> {
+        let length = speed * PROBE_SECONDS;
+        let mut best = (
+            f32::NEG_INFINITY,
+            orientation.rotate_vector(Vector3::unit_z()),
+        );
+        for local in probe_directions() {
+            let direction = orientation.rotate_vector(local);
+            let end = position + direction * length;
+            let score =
+                match voxels::first_hit_along_segment(position, end, octree, &mut self.cache) {
+                    None => 1.,
+                    Some(hit) => {
+                        let open = (hit - position).magnitude() / length;
+                        match voxels::octree_scale_and_collision_of_point(
+                            hit,
+                            octree,
+                            &mut self.cache,
+                        ) {
+                            Intersection::Portal { .. } => 3. - open,
+                            _ => open,
+                        }
+                    }
+                };
+
+            // Slightly favour the current direction so that similar rays don't cause the heading to wobble.
+            let score = score + 0.1 * local.z;
+            if score > best.0 {
+                best = (score, direction);
+            }
+        }
+        best.1
+    }
+}
+
+// Camera-space directions of the probe rays: straight ahead and rings around it.
+fn probe_directions() -> impl Iterator<Item = Vector3<f32>> {
+    #[allow(clippy::cast_precision_loss)]
+    let rings = PROBE_RINGS.into_iter().flat_map(|angle: f32| {
+        (0..PROBE_RING_COUNT).map(move |i| {
+            let around = std::f32::consts::TAU * i as f32 / PROBE_RING_COUNT as f32;
+            Vector3::new(
+                angle.sin() * around.cos(),
+                angle.sin() * around.sin(),
+                angle.cos(),
+            )
+        })
+    });
+    std::iter::once(Vector3::unit_z()).chain(rings)
+}
@@ -0,0 +1,141 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+    fs,
+    io::{self, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
+
+use crate::persistence;
+use crate::voxel_flight_simulator::app_data_dir;
+
+// Every archive starts with a manifest of the data version its files were written with.
+const MANIFEST_FILE_NAME: &str = "manifest.toml";
+const DATA_EXTENSION: &str = "toml";
+
+#[derive(Deserialize, Serialize)]
+struct Manifest {
+    data_version: u32,
+    game_version: String,
+}
+
+// Bundle every `.toml` data file in the app directory, including the custom modes and the files of each profile, into
+// a zip archive.
+// Returns the number of files exported.
+pub fn export(path: &Path) -> io::Result<usize> {
+    let root = app_data_dir();
+    let mut files = Vec::new();
+    collect_data_files(&root, &mut files)?;
+
+    let mut zip = ZipWriter::new(fs::File::create(path)?);
+    let options = FileOptions::default();
+    let manifest = Manifest {
+        data_version: persistence::DATA_VERSION,
+        game_version: env!("CARGO_PKG_VERSION").to_owned(),
+    };
+    zip.start_file(MANIFEST_FILE_NAME, options)?;
+    zip.write_all(
+        toml::to_string_pretty(&manifest)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?
+            .as_bytes(),
+    )?;
+    for file in &files {
+        let Ok(relative) = file.strip_prefix(&root) else {
+            continue;
+        };
+        let name: Vec<_> = relative.iter().map(|part| part.to_string_lossy()).collect();
+        zip.start_file(name.join("/"), options)?;
+        zip.write_all(&fs::read(file)?)?;
+    }
+    zip.finish()?;
+    Ok(files.len())
+}
+
+// Replace the data files in the app directory with those of an exported archive, migrating them from older data
+// versions. Returns the number of files imported.
+pub fn import(path: &Path) -> io::Result<usize> {
+    let invalid = |e: String| io::Error::new(ErrorKind::InvalidData, e);
+    let mut zip = ZipArchive::new(fs::File::open(path)?).map_err(|e| invalid(e.to_string()))?;
+
+    let manifest: Manifest = {
+        let mut contents = String::new();
+        zip.by_name(MANIFEST_FILE_NAME)
+            .map_err(|e| invalid(e.to_string()))?
+            .read_to_string(&mut contents)?;
+        toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?
+    };
+    log::info!(
+        target: "backup",
+        "Importing data data_version={} game_version={}",
+        manifest.data_version,
+        manifest.game_version
+    );
+
+    // Read and migrate every file before writing any, so that a bad archive leaves the existing data alone.
+    let mut files = Vec::new();
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index).map_err(|e| invalid(e.to_string()))?;
+        let Some(relative) = file.enclosed_name().map(Path::to_path_buf) else {
+            continue;
+        };
+        if relative == Path::new(MANIFEST_FILE_NAME)
+            || relative.extension().and_then(|e| e.to_str()) != Some(DATA_EXTENSION)
+        {
+            continue;
+        }
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let contents =
+            persistence::migrate(manifest.data_version, file.name(), contents).map_err(invalid)?;
+        files.push((relative, contents));
+    }
+
+    // The archive replaces the data rather than being merged over it, so data files it doesn't have, such as those of
+    // profiles added since it was exported, are removed.
+    let root = app_data_dir();
+    let mut existing = Vec::new();
+    collect_data_files(&root, &mut existing)?;
+    for file in existing {
+        fs::remove_file(file)?;
+    }
+    for (relative, contents) in &files {
+        let path = root.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+    }
+    Ok(files.len())
+}
+
+// Find the data files in a directory and its subdirectories.
+fn collect_data_files(directory: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_data_files(&path, files)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some(DATA_EXTENSION) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,203 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::game::Run;
+use crate::i18n::{Language, Text};
+use crate::stats::Stats;
+use crate::theme::WorldTheme;
+use crate::voxels::{Symmetry, WorldGenerator};
+
+// A level of the campaign: a curated world, and the score to reach in a run starting from it.
+pub struct Level {
+    pub seed: u64,
+    pub theme: WorldTheme,
+    pub generator: WorldGenerator,
+    pub symmetry: Symmetry,
+    pub goal: u32,
+    // Seconds, after which the run ends and the level is tried again.
+    pub time_limit: Option<f32>,
+}
+
+// The levels of the campaign, in the order they are unlocked.
+pub const LEVELS: [Level; 8] = [
+    Level {
+        seed: 1_902_734_113,
+        theme: WorldTheme::Classic,
+        generator: WorldGenerator::Recursive,
+        symmetry: Symmetry::Off,
+        goal: 5,
+        time_limit: None,
+    },
+    Level {
+        seed: 2_384_117_509,
+        theme: WorldTheme::Canyon,
+        generator: WorldGenerator::Recursive,
+        symmetry: Symmetry::Off,
+        goal: 8,
+        time_limit: None,
+    },
+    Level {
+        seed: 845_216_377,
+        theme: WorldTheme::City,
+        generator: WorldGenerator::Tiles,
+        symmetry: Symmetry::Off,
+        goal: 12,
+        time_limit: Some(180.),
+    },
+    Level {
+        seed: 3_117_004_862,
+        theme: WorldTheme::Nebula,
+        generator: WorldGenerator::Recursive,
+        symmetry: Symmetry::Mirror,
+        goal: 15,
+        time_limit: Some(180.),
+    },
+    Level {
+        seed: 77_409_215,
+        theme: WorldTheme::Classic,
+        generator: WorldGenerator::Recursive,
+        symmetry: Symmetry::Rotational,
+        goal: 20,
+        time_limit: Some(210.),
+    },
+    Level {
+        seed: 2_960_331_148,
+        theme: WorldTheme::Canyon,
+        generator: WorldGenerator::Tiles,
+        symmetry: Symmetry::Off,
+        goal: 25,
+        time_limit: Some(240.),
+    },
+    Level {
+        seed: 1_408_552_790,
+        theme: WorldTheme::City,
+        generator: WorldGenerator::Recursive,
+        symmetry: Symmetry::Mirror,
+        goal: 30,
+        time_limit: Some(240.),
+    },
+    Level {
+        seed: 3_999_120_451,
+        theme: WorldTheme::Nebula,
+        generator: WorldGenerator::Recursive,
+        symmetry: Symmetry::Rotational,
+        goal: 40,
+        time_limit: Some(300.),
+    },
+];
+
+// What became of the level being played.
+pub enum Outcome {
+    Completed(usize),
+    Failed(usize),
+}
+
+// The campaign level being played, if any, and the last level completed.
+#[derive(Default)]
+pub struct Campaign {
+    level: Option<usize>,
+    started: bool,
+    completed: Option<usize>,
+}
+
+impl Campaign {
+    // Begin playing the given level, once the world it starts in has been entered.
+    pub fn start(&mut self, level: usize) {
+        self.level = Some(level);
+        self.started = false;
+        self.completed = None;
+    }
+
+    // Stop playing the campaign, such as when another mode is chosen.
+    pub fn stop(&mut self) {
+        self.level = None;
+    }
+
+    // Follow the run of the level being played, reporting when its goal is reached or the run ends short of it.
+    pub fn update(&mut self, run: &Run) -> Option<Outcome> {
+        let level = self.level?;
+        if run.start.is_none() {
+            return std::mem::take(&mut self.started).then_some(Outcome::Failed(level));
+        }
+        self.started = true;
+        if run.points < LEVELS[level].goal {
+            return None;
+        }
+        self.level = None;
+        self.completed = Some(level);
+        Some(Outcome::Completed(level))
+    }
+
+    // Show the levels, which are unlocked by completing the one before. Returns the level chosen to be played.
+    pub fn show_window(
+        &self,
+        ctx: &egui::Context,
+        is_visible: &mut bool,
+        stats: &Stats,
+        lang: Language,
+    ) -> Option<usize> {
+        let mut chosen = None;
+        egui::Window::new(lang.tr(Text::CampaignTitle))
+            .id(egui::Id::new("campaign_window"))
+            .open(is_visible)
+            .show(ctx, |ui| {
+                if let Some(level) = self.completed {
+                    ui.heading(lang.tr_args(Text::CampaignLevelComplete, &[&(level + 1)]));
+                    ui.separator();
+                }
+                let completed_levels = stats.campaign_levels as usize;
+                egui::Grid::new("campaign_grid").show(ui, |ui| {
+                    for (i, level) in LEVELS.iter().enumerate() {
+                        let completed = i < completed_levels;
+                        let unlocked = i <= completed_levels;
+                        ui.label(if completed { "✔" } else { " " });
+                        ui.add_enabled(
+                            unlocked,
+                            egui::Label::new(lang.tr_args(Text::CampaignLevel, &[&(i + 1)])),
+                        );
+                        ui.label(match level.time_limit {
+                            Some(limit) => lang.tr_args(
+                                Text::CampaignGoalTimed,
+                                &[&level.goal, &format!("{limit:.0}")],
+                            ),
+                            None => lang.tr_args(Text::CampaignGoal, &[&level.goal]),
+                        });
+                        let play = if self.level == Some(i) {
+                            Text::CampaignRestart
+                        } else {
+                            Text::CampaignPlay
+                        };
+                        if ui
+                            .add_enabled(unlocked, egui::Button::new(lang.tr(play)))
+                            .on_disabled_hover_text(lang.tr(Text::CampaignLocked))
+                            .clicked()
+                        {
+                            chosen = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        chosen
+    }
+
+    // The time limit of the level being played, if it has one.
+    pub fn time_limit(&self) -> Option<f32> {
+        self.level.and_then(|level| LEVELS[level].time_limit)
+    }
+}
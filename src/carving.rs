@@ -0,0 +1,101 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::BTreeMap;
+
+use cgmath::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+use crate::voxels::{self, VoxelCompact, NULL_VOXEL_INDEX};
+
+// Carving constants.
+const CARVINGS_FILE_NAME: &str = "carvings.toml";
+
+// A voxel carved out of a world, by the index of its parent and which of the parent's children it was. The index of
+// the carved voxel is kept so that carvings made with other world options aren't applied to a different layout.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct Carve {
+    parent: u32,
+    cell: usize,
+    voxel: u32,
+}
+
+// The voxels carved out of each world, by the world's seed. Seeds are kept as text since TOML keys must be strings.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Carvings {
+    worlds: BTreeMap<String, Vec<Carve>>,
+}
+
+impl Carve {
+    // Carve the colour or mirror voxel containing a point out of the octree, if there is one.
+    pub fn at(position: Vector3<f32>, octree: &mut [VoxelCompact]) -> Option<Self> {
+        let (parent, cell, voxel) = voxels::carve_leaf(position, octree)?;
+        Some(Self {
+            parent,
+            cell,
+            voxel,
+        })
+    }
+
+    // The index of the voxel whose children were changed by the carve.
+    pub fn parent(&self) -> usize {
+        self.parent as usize
+    }
+}
+
+impl Carvings {
+    // Load the current profile's carvings, starting with none if there are none.
+    pub fn load() -> Self {
+        persistence::load(&persistence::profile_file(CARVINGS_FILE_NAME))
+    }
+
+    // Carve the voxels carved before out of a newly generated world of the given seed.
+    pub fn apply(&self, seed: u64, octree: &mut [VoxelCompact]) {
+        let Some(carves) = self.worlds.get(&seed.to_string()) else {
+            return;
+        };
+        for carve in carves {
+            let child = octree
+                .get_mut(carve.parent as usize)
+                .and_then(|parent| parent.children.get_mut(carve.cell));
+            if let Some(child) = child.filter(|child| **child == carve.voxel) {
+                *child = NULL_VOXEL_INDEX;
+            }
+        }
+    }
+
+    // Remember a voxel carved out of the world of the given seed.
+    pub fn record(&mut self, seed: u64, carve: Carve) {
+        self.worlds.entry(seed.to_string()).or_default().push(carve);
+        persistence::save(&persistence::profile_file(CARVINGS_FILE_NAME), self);
+    }
+
+    // Whether any voxels have been carved out of the world of the given seed.
+    pub fn any(&self, seed: u64) -> bool {
+        self.worlds.contains_key(&seed.to_string())
+    }
+
+    // Forget the voxels carved out of the world of the given seed, so that it's generated whole again.
+    pub fn remove(&mut self, seed: u64) {
+        if self.worlds.remove(&seed.to_string()).is_some() {
+            persistence::save(&persistence::profile_file(CARVINGS_FILE_NAME), self);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::{InnerSpace, Quaternion, Vector3, Vector4};
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+// Cinematic constants.
+const CAMERA_PATH_FILE_NAME: &str = "camera_path.toml";
+const DEFAULT_KEYFRAME_DURATION: f32 = 2.;
+pub const KEYFRAME_DURATION_RANGE: std::ops::RangeInclusive<f32> = 0.1..=30.;
+
+// A camera pose along the path, and how long the camera takes to travel from it to the next keyframe.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct Keyframe {
+    pub position: [f32; 3],
+    pub orientation: [f32; 4],
+    pub duration: f32,
+}
+
+// A camera path through a single world, and the state of its playback.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CameraPath {
+    pub seed: Option<u64>,
+    pub keyframes: Vec<Keyframe>,
+    pub hide_overlay: bool,
+    pub looping: bool,
+
+    #[serde(skip)]
+    playback_time: Option<f32>,
+}
+
+impl Keyframe {
+    fn position(&self) -> Vector3<f32> {
+        self.position.into()
+    }
+
+    // The orientation as a vector, so that it can be interpolated component-wise.
+    fn orientation(&self) -> Vector4<f32> {
+        self.orientation.into()
+    }
+}
+
+impl CameraPath {
+    // Load the current profile's exported camera path, starting with an empty path if there is none.
+    pub fn load() -> Self {
+        let mut path: Self = persistence::load(&persistence::profile_file(CAMERA_PATH_FILE_NAME));
+        for keyframe in &mut path.keyframes {
+            keyframe.duration = keyframe.duration.clamp(
+                *KEYFRAME_DURATION_RANGE.start(),
+                *KEYFRAME_DURATION_RANGE.end(),
+            );
+        }
+        path
+    }
+
+    // Export the camera path to the current profile's directory.
+    pub fn save(&self) {
+        persistence::save(&persistence::profile_file(CAMERA_PATH_FILE_NAME), self);
+    }
+
+    // Add a keyframe at the given camera pose. A path belongs to a single world, so keyframes dropped in
+    // a different world start a new path.
+    pub fn add_keyframe(
+        &mut self,
+        position: Vector3<f32>,
+        orientation: Quaternion<f32>,
+        seed: u64,
+    ) {
+        if self.seed != Some(seed) {
+            self.keyframes.clear();
+            self.seed = Some(seed);
+        }
+        self.keyframes.push(Keyframe {
+            position: position.into(),
+            orientation: orientation.into(),
+            duration: DEFAULT_KEYFRAME_DURATION,
+        });
+    }
+
+    // Whether the path has enough keyframes to be played.
+    pub fn can_play(&self) -> bool {
+        self.keyframes.len() > 1
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playback_time.is_some()
+    }
+
+    pub fn play(&mut self) {
+        if self.can_play() {
+            self.playback_time = Some(0.);
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.playback_time = None;
+    }
+
+    // Total time to play the path, which ends at the last keyframe.
+    pub fn total_duration(&self) -> f32 {
+        let count = self.keyframes.len().saturating_sub(1);
+        self.keyframes[..count].iter().map(|k| k.duration).sum()
+    }
+
+    // Advance playback and return the camera pose to show, or `None` once playback has finished.
+    pub fn advance(&mut self, delta_time: f32) -> Option<(Vector3<f32>, Quaternion<f32>)> {
+        let total = self.total_duration();
+        let mut time = self.playback_time? + delta_time;
+        if time > total {
+            if self.looping && total > 0. {
+                time %= total;
+            } else {
+                self.stop();
+                return None;
+            }
+        }
+        self.playback_time = Some(time);
+        Some(self.sample(time))
+    }
+
+    // The camera pose at a time along the path, interpolated with Catmull-Rom splines.
+    fn sample(&self, mut time: f32) -> (Vector3<f32>, Quaternion<f32>) {
+        let last = self.keyframes.len() - 1;
+        let mut segment = 0;
+        while segment < last - 1 && time > self.keyframes[segment].duration {
+            time -= self.keyframes[segment].duration;
+            segment += 1;
+        }
+        let t = (time / self.keyframes[segment].duration).clamp(0., 1.);
+
+        // The keyframes around the segment, repeating the first and last keyframes at the ends.
+        let k = |i: usize| &self.keyframes[i.min(last)];
+        let (k0, k1, k2, k3) = (
+            k(segment.saturating_sub(1)),
+            k(segment),
+            k(segment + 1),
+            k(segment + 2),
+        );
+
+        let position = catmull_rom(
+            k0.position(),
+            k1.position(),
+            k2.position(),
+            k3.position(),
+            t,
+        );
+
+        // Quaternions `q` and `-q` are the same rotation, so flip each to the same side as its neighbour
+        // to avoid turning the long way around.
+        let align = |previous: Vector4<f32>, q: Vector4<f32>| {
+            if previous.dot(q) < 0. {
+                -q
+            } else {
+                q
+            }
+        };
+        let q1 = k1.orientation();
+        let q0 = align(q1, k0.orientation());
+        let q2 = align(q1, k2.orientation());
+        let q3 = align(q2, k3.orientation());
+        let orientation: [f32; 4] = catmull_rom(q0, q1, q2, q3, t).normalize().into();
+
+        (position, orientation.into())
+    }
+}
+
+// Uniform Catmull-Rom interpolation between `p1` and `p2`.
+fn catmull_rom<V>(p0: V, p1: V, p2: V, p3: V, t: f32) -> V
+where
+    V: InnerSpace<Scalar = f32>,
+{
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.
+        + (p2 - p0) * t
+        + (p0 * 2. - p1 * 5. + p2 * 4. - p3) * t2
+        + (p1 * 3. - p0 - p2 * 3. + p3) * t3)
+        * 0.5
+}
@@ -0,0 +1,185 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::path::PathBuf;
+
+use crate::renderer::Backend;
+use crate::theme::WorldTheme;
+
+#[cfg(not(target_arch = "wasm32"))]
+const USAGE: &str = "Usage: voxel_flight_simulator [OPTIONS]
+
+Options:
+  -b, --backend <vulkan|wgpu>  Choose the graphics API, instead of preferring Vulkan when available
+  -g, --gpu <name>             Use the named GPU, instead of the one chosen in the options
+  -m, --model <path>           Start by flying through an OBJ or glTF model, voxelized into a world
+      --model-depth <7-9>      Voxelize the model in finer detail, at the cost of memory (default 8)
+  -p, --profile <name>         Play as the named profile, instead of the one played last
+  -s, --seed <text>            Start in the world generated from the given seed, a number or any phrase
+  -t, --theme <name>           Generate worlds in the classic, canyon, city, or nebula theme
+  -v, --verbose                Include debug messages in the log
+      --vr                     Also render to a VR headset through OpenXR, in builds with the xr feature
+  -w, --world <path>           Start in a hand-authored world described by a TOML file
+  -h, --help                   Print this help message";
+
+// Command-line arguments accepted by the app.
+#[derive(Default)]
+pub struct Args {
+    pub backend: Option<Backend>,
+    pub gpu: Option<String>,
+    pub model: Option<PathBuf>,
+    pub model_depth: Option<u32>,
+    pub profile: Option<String>,
+    pub seed: Option<String>,
+    pub theme: Option<WorldTheme>,
+    pub verbose: bool,
+    pub vr: bool,
+    pub world: Option<PathBuf>,
+}
+
+impl Args {
+    // Parse the arguments given to the process, exiting on `--help` or unknown arguments.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn parse() -> Self {
+        let mut args = Self::default();
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-b" | "--backend" => {
+                    let backend = iter.next().and_then(|name| Backend::from_name(&name));
+                    if backend.is_none() {
+                        eprintln!("Expected vulkan or wgpu after {arg}\n\n{USAGE}");
+                        std::process::exit(2);
+                    }
+                    args.backend = backend;
+                }
+                "-g" | "--gpu" => {
+                    args.gpu = iter.next();
+                    if args.gpu.is_none() {
+                        eprintln!("Expected a GPU name after {arg}\n\n{USAGE}");
+                        std::process::exit(2);
+                    }
+                }
+                "-m" | "--model" => {
+                    args.model = iter.next().map(PathBuf::from);
+                    if args.model.is_none() {
+                        eprintln!("Expected a file path after {arg}\n\n{USAGE}");
+                        std::process::exit(2);
+                    }
+                }
+                "--model-depth" => {
+                    let depth = iter.next().and_then(|depth| depth.parse().ok());
+                    if depth.is_none() {
+                        eprintln!("Expected a number after {arg}\n\n{USAGE}");
+                        std::process::exit(2);
+                    }
+                    args.model_depth = depth;
+                }
+                "-p" | "--profile" => {
+                    args.profile = iter.next();
+                    if args.profile.is_none() {
+                        eprintln!("Expected a profile name after {arg}\n\n{USAGE}");
+                        std::process::exit(2);
+                    }
+                }
+                "-s" | "--seed" => {
+                    args.seed = iter.next();
+                    if args.seed.is_none() {
+                        eprintln!("Expected a seed after {arg}\n\n{USAGE}");
+                        std::process::exit(2);
+                    }
+                }
+                "-t" | "--theme" => {
+                    let theme = iter.next().and_then(|name| WorldTheme::from_name(&name));
+                    if theme.is_none() {
+                        eprintln!(
+                            "Expected classic, canyon, city, or nebula after {arg}\n\n{USAGE}"
+                        );
+                        std::process::exit(2);
+                    }
+                    args.theme = theme;
+                }
+                "-v" | "--verbose" => args.verbose = true,
+                "--vr" => args.vr = true,
+                "-w" | "--world" => {
+                    args.world = iter.next().map(PathBuf::from);
+                    if args.world.is_none() {
+                        eprintln!("Expected a file path after {arg}\n\n{USAGE}");
+                        std::process::exit(2);
+                    }
+                }
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                _ => {
+                    eprintln!("Unknown argument: {arg}\n\n{USAGE}");
+                    std::process::exit(2);
+                }
+            }
+        }
+        args
+    }
+    // Web builds read the same options from the page's query string, e.g. `?seed=purple+dragon&theme=canyon&verbose`.
+    // Unknown or malformed parameters are ignored so that shared links keep working.
+    #[cfg(target_arch = "wasm32")]
+    pub fn parse() -> Self {
+        let query = web_sys::window()
+            .and_then(|window| window.location().search().ok())
+            .unwrap_or_default();
+        let mut args = Self::default();
+        for parameter in query.trim_start_matches('?').split('&') {
+            let (key, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+            match key {
+                "profile" => args.profile = Some(decode_query_value(value)),
+                "seed" => args.seed = Some(decode_query_value(value)),
+                "theme" => args.theme = WorldTheme::from_name(value),
+                "verbose" => args.verbose = true,
+                _ => (),
+            }
+        }
+        args
+    }
+}
+
+// Decode a value from a query string, where spaces may be written as `+` and any byte as `%` and two hex digits.
+#[cfg(target_arch = "wasm32")]
+fn decode_query_value(value: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut iter = value.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = iter.by_ref().take(2).collect();
+                match std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(decoded) => bytes.push(decoded),
+                    None => {
+                        bytes.push(b'%');
+                        bytes.extend(hex);
+                    }
+                }
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -0,0 +1,99 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::time::Duration;
+
+use gilrs::{GamepadId, Gilrs};
+use web_time::Instant;
+
+use crate::i18n::{Language, Text};
+
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+// A controller being connected or disconnected, and when.
+struct Toast {
+    name: String,
+    connected: bool,
+    time: Instant,
+}
+
+// Notifications shown when controllers are connected or disconnected.
+#[derive(Default)]
+pub struct ControllerToasts {
+    toasts: Vec<Toast>,
+}
+
+impl ControllerToasts {
+    // Notify the player that the named controller was connected or disconnected.
+    pub fn push(&mut self, name: &str, connected: bool) {
+        log::info!(target: "controllers", "Controller name={name} connected={connected}");
+        self.toasts.push(Toast {
+            name: name.to_owned(),
+            connected,
+            time: Instant::now(),
+        });
+    }
+
+    // Remove the notifications which have been shown long enough.
+    pub fn update(&mut self) {
+        self.toasts
+            .retain(|toast| toast.time.elapsed() < TOAST_DURATION);
+    }
+
+    // Draw a notification at the bottom of the screen for each recent change of controller.
+    pub fn show(&self, ctx: &egui::Context, language: Language) {
+        if self.toasts.is_empty() {
+            return;
+        }
+        egui::Area::new("controller_toasts")
+            .anchor(egui::Align2::CENTER_BOTTOM, [0., -8.])
+            .interactable(false)
+            .show(ctx, |ui| {
+                for toast in &self.toasts {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.label(
+                            egui::RichText::new(language.tr(if toast.connected {
+                                Text::ControllerConnected
+                            } else {
+                                Text::ControllerDisconnected
+                            }))
+                            .small(),
+                        );
+                        ui.strong(&toast.name);
+                    });
+                }
+            });
+    }
+
+    // Getters.
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+}
+
+// Whether events from the given gamepad are read, which is every gamepad when none is chosen or the chosen one
+// isn't connected.
+pub fn accepts(gilrs: &Gilrs, id: GamepadId, chosen: Option<&str>) -> bool {
+    let Some(chosen) = chosen else {
+        return true;
+    };
+    gilrs.gamepad(id).name() == chosen
+        || !gilrs
+            .gamepads()
+            .any(|(_, gamepad)| gamepad.name() == chosen)
+}
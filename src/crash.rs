@@ -0,0 +1,126 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{fs, path::PathBuf, sync::Mutex};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{backtrace::Backtrace, panic::PanicHookInfo};
+#[cfg(not(target_arch = "wasm32"))]
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::voxel_flight_simulator::app_data_dir;
+
+// Crash report constants.
+const CRASH_REPORT_DIRECTORY: &str = "crash_reports";
+const PENDING_REPORT_FILE_NAME: &str = "pending_crash_report.txt";
+
+// App state worth including in a crash report, updated as the app runs.
+struct CrashContext {
+    seed: Option<u64>,
+    device_name: Option<String>,
+}
+
+static CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    seed: None,
+    device_name: None,
+});
+
+// Install a panic hook which writes a crash report before the default hook runs.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_report(info) {
+            Ok(path) => eprintln!("Crash report saved to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {e}"),
+        }
+        default_hook(info);
+    }));
+}
+
+// Browsers have no file system to write crash reports to, so print the panic to the console instead.
+#[cfg(target_arch = "wasm32")]
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        web_sys::console::error_1(&info.to_string().into());
+    }));
+}
+
+// Record the current world seed for future crash reports.
+pub fn set_seed(seed: u64) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.seed = Some(seed);
+    }
+}
+
+// Record the name of the GPU in use for future crash reports.
+pub fn set_device_name(name: &str) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.device_name = Some(name.to_string());
+    }
+}
+
+// Get the crash report left by the previous launch, if it hasn't been dismissed yet.
+pub fn take_pending_report() -> Option<PathBuf> {
+    let pending_file = app_data_dir().join(PENDING_REPORT_FILE_NAME);
+    let path = fs::read_to_string(&pending_file).ok()?;
+    if let Err(e) = fs::remove_file(&pending_file) {
+        log::warn!("Failed to clear pending crash report: {e}");
+    }
+
+    let path = PathBuf::from(path.trim());
+    path.exists().then_some(path)
+}
+
+// Write a crash report describing the panic and mark it to be shown on the next launch.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_report(info: &PanicHookInfo) -> std::io::Result<PathBuf> {
+    let now = SystemTime::now();
+
+    // Avoid blocking in case the panic happened while the context was locked.
+    let (seed, device_name) = match CONTEXT.try_lock() {
+        Ok(context) => (context.seed, context.device_name.clone()),
+        Err(_) => (None, None),
+    };
+
+    let mut report = format!(
+        "voxel_flight_simulator {}\nTime: {}\nOS: {} {}\nGPU: {}\nSeed: {}\n\n{info}\n\nBacktrace:\n{}\nRecent log:\n",
+        env!("CARGO_PKG_VERSION"),
+        crate::logging::format_timestamp(now),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        device_name.as_deref().unwrap_or("Unknown"),
+        seed.map_or_else(|| "Unknown".to_string(), |s| s.to_string()),
+        Backtrace::force_capture(),
+    );
+    for line in crate::logging::recent_lines() {
+        report.push_str(&line);
+    }
+
+    let directory = app_data_dir().join(CRASH_REPORT_DIRECTORY);
+    fs::create_dir_all(&directory)?;
+    let seconds = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let path = directory.join(format!("crash_{seconds}.txt"));
+    fs::write(&path, report)?;
+
+    fs::write(
+        app_data_dir().join(PENDING_REPORT_FILE_NAME),
+        path.to_string_lossy().as_bytes(),
+    )?;
+    Ok(path)
+}
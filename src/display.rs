@@ -0,0 +1,127 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+use winit::monitor::{MonitorHandle, VideoMode};
+use winit::window::{Fullscreen, Window};
+
+// How the window fills the screen in fullscreen.
+#[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+pub enum FullscreenMode {
+    // A borderless window covering the whole monitor.
+    #[default]
+    Borderless,
+    // Exclusive control of a monitor, switched to the given video mode.
+    Exclusive(DisplayMode),
+}
+
+// A video mode, stored so that it can be found again on its monitor after a restart.
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+pub struct DisplayMode {
+    pub size: [u32; 2],
+    pub refresh_millihertz: u32,
+    pub bit_depth: u16,
+}
+
+impl DisplayMode {
+    fn from_video_mode(mode: &VideoMode) -> Self {
+        Self {
+            size: mode.size().into(),
+            refresh_millihertz: mode.refresh_rate_millihertz(),
+            bit_depth: mode.bit_depth(),
+        }
+    }
+
+    // A short description of the resolution, refresh rate, and colour depth.
+    pub fn label(&self) -> String {
+        #[allow(clippy::cast_precision_loss)]
+        let hertz = self.refresh_millihertz as f32 / 1000.;
+        format!(
+            "{}×{} @ {hertz:.0} Hz ({}-bit)",
+            self.size[0], self.size[1], self.bit_depth
+        )
+    }
+}
+
+// The names of the monitors connected, in the order the platform lists them.
+pub fn monitor_names(window: &Window) -> Vec<String> {
+    window
+        .available_monitors()
+        .filter_map(|monitor| monitor.name())
+        .collect()
+}
+
+// The video modes of the named monitor, or of the window's current monitor, from largest and fastest down.
+pub fn display_modes(window: &Window, monitor: Option<&str>) -> Vec<DisplayMode> {
+    let Some(monitor) = find_monitor(window, monitor) else {
+        return Vec::new();
+    };
+    let mut modes: Vec<_> = monitor
+        .video_modes()
+        .map(|mode| DisplayMode::from_video_mode(&mode))
+        .collect();
+    modes.sort_by(|a, b| {
+        (b.size[0] * b.size[1], b.refresh_millihertz, b.bit_depth).cmp(&(
+            a.size[0] * a.size[1],
+            a.refresh_millihertz,
+            a.bit_depth,
+        ))
+    });
+    modes
+}
+
+// The fullscreen state for the given mode on the named monitor, or on the monitor the window is on when none is named.
+// A monitor which is no longer connected falls back to the current one, and an exclusive mode which can no longer be
+// found falls back to borderless.
+pub fn fullscreen(window: &Window, monitor: Option<&str>, mode: &FullscreenMode) -> Fullscreen {
+    let monitor = find_monitor(window, monitor);
+    match mode {
+        FullscreenMode::Borderless => Fullscreen::Borderless(monitor),
+        FullscreenMode::Exclusive(display_mode) => {
+            let video_mode = monitor.as_ref().and_then(|monitor| {
+                monitor
+                    .video_modes()
+                    .find(|mode| DisplayMode::from_video_mode(mode) == *display_mode)
+            });
+            if let Some(video_mode) = video_mode {
+                Fullscreen::Exclusive(video_mode)
+            } else {
+                log::warn!(
+                    target: "display",
+                    "Video mode is unavailable, using borderless fullscreen mode=\"{}\"",
+                    display_mode.label(),
+                );
+                Fullscreen::Borderless(monitor)
+            }
+        }
+    }
+}
+
+// The monitor with the given name, or the window's current monitor when no name is given or it isn't connected.
+fn find_monitor(window: &Window, name: Option<&str>) -> Option<MonitorHandle> {
+    if let Some(name) = name {
+        let monitor = window
+            .available_monitors()
+            .find(|monitor| monitor.name().as_deref() == Some(name));
+        if monitor.is_some() {
+            return monitor;
+        }
+        log::warn!(target: "display", "Monitor is not connected, using the current monitor name=\"{name}\"");
+    }
+    window.current_monitor()
+}
@@ -0,0 +1,142 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::sync::Arc;
+
+use vulkano::{
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, AutoCommandBufferBuilder, CommandBufferUsage,
+        PrimaryAutoCommandBuffer, RenderPassBeginInfo, SecondaryAutoCommandBuffer,
+        SubpassBeginInfo, SubpassContents, SubpassEndInfo,
+    },
+    device::Queue,
+    image::view::ImageView,
+    render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass},
+};
+
+// The render passes of a frame, in the order they run. Each pass clears the images it draws into, and each of
+// its subpasses runs the secondary command buffers registered for it. Passes later in the list may sample the
+// images drawn by earlier ones.
+#[derive(Default)]
+pub struct FrameGraph {
+    passes: Vec<FramePass>,
+}
+
+// A render pass, the images it draws into, and the draws registered for each of its subpasses.
+struct FramePass {
+    label: &'static str,
+    render_pass: Arc<RenderPass>,
+    attachments: Vec<Arc<ImageView>>,
+    subpasses: Vec<Vec<Arc<SecondaryAutoCommandBuffer>>>,
+}
+
+impl FrameGraph {
+    // Add a render pass drawing into the given images, which must match the attachments of the render pass.
+    // Draws are registered with `draw`, which targets the last pass added.
+    pub fn pass(
+        &mut self,
+        label: &'static str,
+        render_pass: Arc<RenderPass>,
+        attachments: Vec<Arc<ImageView>>,
+    ) -> &mut Self {
+        let subpasses = vec![Vec::new(); render_pass.subpasses().len()];
+        self.passes.push(FramePass {
+            label,
+            render_pass,
+            attachments,
+            subpasses,
+        });
+        self
+    }
+
+    // Register a draw in a subpass of the last pass added.
+    pub fn draw(
+        &mut self,
+        subpass: usize,
+        command_buffer: Arc<SecondaryAutoCommandBuffer>,
+    ) -> &mut Self {
+        let pass = self
+            .passes
+            .last_mut()
+            .expect("A pass is added before drawing in it.");
+        pass.subpasses[subpass].push(command_buffer);
+        self
+    }
+
+    // Record every pass, in order, into a new primary command buffer.
+    pub fn record(
+        self,
+        allocator: &StandardCommandBufferAllocator,
+        queue: &Arc<Queue>,
+    ) -> AutoCommandBufferBuilder<PrimaryAutoCommandBuffer> {
+        let mut builder = AutoCommandBufferBuilder::primary(
+            allocator,
+            queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        for pass in self.passes {
+            pass.record(&mut builder);
+        }
+        builder
+    }
+}
+
+impl FramePass {
+    fn record(self, builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>) {
+        let clear_values = vec![Some([0.0; 4].into()); self.attachments.len()];
+        let framebuffer = Framebuffer::new(
+            self.render_pass,
+            FramebufferCreateInfo {
+                attachments: self.attachments,
+                ..FramebufferCreateInfo::default()
+            },
+        )
+        .unwrap_or_else(|e| {
+            panic!(
+                "Failed to create framebuffer for the {} pass: {e}",
+                self.label
+            )
+        });
+
+        let subpass_begin_info = || SubpassBeginInfo {
+            contents: SubpassContents::SecondaryCommandBuffers,
+            ..SubpassBeginInfo::default()
+        };
+        builder
+            .begin_render_pass(
+                RenderPassBeginInfo {
+                    clear_values,
+                    ..RenderPassBeginInfo::framebuffer(framebuffer)
+                },
+                subpass_begin_info(),
+            )
+            .unwrap();
+        for (i, command_buffers) in self.subpasses.into_iter().enumerate() {
+            if i > 0 {
+                builder
+                    .next_subpass(SubpassEndInfo::default(), subpass_begin_info())
+                    .unwrap();
+            }
+            for command_buffer in command_buffers {
+                builder.execute_commands(command_buffer).unwrap();
+            }
+        }
+        builder.end_render_pass(SubpassEndInfo::default()).unwrap();
+    }
+}
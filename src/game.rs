@@ -16,84 +16,618 @@
     along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
 */
 
-use std::time::Instant;
+use std::f32::consts::TAU;
+use web_time::Instant;
 
-use cgmath::{Quaternion, Vector3};
+use cgmath::{Deg, InnerSpace, Quaternion, Rad, Rotation, Rotation3, Vector3};
 use gilrs::Gilrs;
+use serde::{Deserialize, Serialize};
+
+use crate::display::FullscreenMode;
+use crate::graphics::GraphicsSettings;
+use crate::haptics::HapticOptions;
+use crate::i18n::{Language, Text};
+use crate::input::{Action, GamepadState, Input, Keyboard};
+use crate::layout::WindowPositions;
+use crate::mixer::AudioMixer;
+use crate::overlay_theme::OverlayTheme;
+use crate::palette::ColorblindMode;
+use crate::persistence;
+use crate::practice::{SpawnPoint, SpawnPoints};
+use crate::projection::{Lens, Projection};
+use crate::rings::RING_POINTS;
+use crate::theme::WorldTheme;
+use crate::voxels::{self, DifficultyBand, Symmetry, VoxelCompact, WorldGenerator};
 
 // Game constants.
 pub const DEFAULT_CAMERA_POSITION: Vector3<f32> = Vector3::new(0.01, 0.2, -2.);
 pub const DEFAULT_CAMERA_ORIENTATION: Quaternion<f32> = Quaternion::new(1., 0., 0., 0.);
 pub const DEFAULT_CAMERA_SPEED: f32 = 0.175;
+pub const SPEED_PORTAL_FACTOR: f32 = 1.15;
+
+// Zen mode flies at this fraction of the usual speed, under a sun circling at this fraction of its usual rate.
+pub const ZEN_SPEED_FACTOR: f32 = 0.6;
+pub const ZEN_SUN_RATE: f32 = 0.25;
+
+// Seconds of boosting that a full fuel meter lasts, and the fraction of the meter refilled by each pickup.
+pub const FUEL_BOOST_SECONDS: f32 = 4.;
+pub const PORTAL_FUEL: f32 = 0.5;
+pub const RING_FUEL: f32 = 0.25;
+
+// A level's par time is this many seconds, plus more for each point of the world's difficulty rating.
+const PAR_SECONDS: f32 = 20.;
+const PAR_SECONDS_PER_DIFFICULTY: f32 = 4.;
+
+// Finishing a level under par scores a bonus point for each of this many seconds to spare.
+const PAR_BONUS_SECONDS: f32 = 5.;
+pub const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.75..=2.;
+pub const OVERLAY_AUTO_HIDE_RANGE: std::ops::RangeInclusive<f32> = 1.0..=30.;
+pub const DEFAULT_OVERLAY_AUTO_HIDE: f32 = 5.;
+pub const FPS_LIMIT_RANGE: std::ops::RangeInclusive<u32> = 20..=240;
+pub const DEFAULT_FPS_LIMIT: u32 = 60;
+pub const FOV_RANGE: std::ops::RangeInclusive<f32> = 60.0..=120.;
+pub const DEFAULT_FOV: f32 = 76.;
+pub const HORIZONTAL_FOV_CAP_RANGE: std::ops::RangeInclusive<f32> = 90.0..=360.;
+pub const DEFAULT_HORIZONTAL_FOV_CAP: f32 = 150.;
+pub const SPEED_LINES_RANGE: std::ops::RangeInclusive<f32> = 0.0..=1.;
+pub const DEFAULT_SPEED_LINES: f32 = 0.5;
+pub const CRASH_FEEDBACK_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.;
+pub const STEREO_CONVERGENCE_RANGE: std::ops::RangeInclusive<f32> = 0.25..=4.;
+pub const DEFAULT_STEREO_CONVERGENCE: f32 = 1.;
+pub const DEADZONE_RANGE: std::ops::RangeInclusive<f32> = 0.0..=0.5;
+pub const SENSITIVITY_RANGE: std::ops::RangeInclusive<f32> = 0.25..=2.;
+pub const RESPONSE_EXPONENT_RANGE: std::ops::RangeInclusive<f32> = 0.5..=3.;
+const CRASH_FEEDBACK_DURATION: f32 = 0.6;
+const SCORE_FLASH_DURATION: f32 = 0.5;
+const MAXIMUM_SHAKE_ANGLE: f32 = 0.05;
+const FOV_BOOST_KICK: f32 = 12.;
+const BOOST_EASING_RATE: f32 = 6.;
+const ROLL_SPEED: f32 = 2.;
+const PITCH_SPEED: f32 = 1.25;
+const YAW_SPEED: f32 = 0.5;
+
+// Forces of the advanced flight model, as accelerations relative to the cruising speed per second. Lift and drag
+// grow with the square of the speed relative to cruising, so that level flight settles near the cruising speed.
+const THRUST: f32 = 0.6;
+const GRAVITY: f32 = 1.;
+const LIFT: f32 = 4.;
+const PARASITIC_DRAG: f32 = 0.6;
+const INDUCED_DRAG: f32 = 0.1;
+
+// Angle of attack in radians past which the wings stall, losing their lift by twice this angle.
+const STALL_ANGLE: f32 = 0.26;
+
+// How quickly the nose turns to follow the direction of flight, per unit of relative speed.
+const WEATHERVANE_RATE: f32 = 1.5;
+
+// Range of the camera speed scaling set by a H.O.T.A.S. throttle, from idle to full.
+pub const THROTTLE_MINIMUM: f32 = 0.5;
+pub const THROTTLE_MAXIMUM: f32 = 1.5;
+const SETTINGS_FILE_NAME: &str = "settings.toml";
 
 // Game state.
 pub struct State {
     pub camera_position: Vector3<f32>,
     pub camera_quaternion: Quaternion<f32>,
+    pub boost_blend: f32,
+    pub boosting: bool,
     pub camera_speed: f32,
+    pub crash_feedback: f32,
+    pub events: Vec<RunEvent>,
     pub gamepad: GamepadState,
     pub gilrs: Gilrs,
+    pub input: Input,
     pub keyboard: Keyboard,
+    pub open_position: Vector3<f32>,
     pub options: Options,
     pub run: Run,
+    pub score_flash: f32,
+    pub spawn_points: SpawnPoints,
+    pub start_orientation: Quaternion<f32>,
+    pub start_position: Vector3<f32>,
+    pub velocity: Vector3<f32>,
 }
 
 // Game options.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Options {
+    pub audio: AudioMixer,
     pub camera_boost: HoldOrToggle,
+    pub chase: bool,
+    pub check_for_updates: bool,
+    pub checkerboard_rendering: bool,
+    pub collision_hull: CollisionHull,
+    pub colorblind_mode: ColorblindMode,
+    pub controller: Option<String>,
+    pub crash_feedback: f32,
+    pub custom_mode: Option<String>,
+    pub destructible_voxels: bool,
+    pub difficulty_band: Option<DifficultyBand>,
+    pub dynamic_fov: bool,
+    pub dynamic_resolution: Option<u32>,
+    pub fov: f32,
+    pub flight_model: FlightModel,
+    pub fps_limit: Option<u32>,
+    pub fuel_boost: bool,
+    pub fullscreen: FullscreenMode,
+    pub gamepad_response: GamepadResponse,
+    pub generator: WorldGenerator,
+    pub gpu: Option<String>,
+    #[serde(flatten)]
+    pub graphics: GraphicsSettings,
+    pub haptics: HapticOptions,
+    pub horizontal_fov_cap: Option<f32>,
+    pub hotas_axes: HotasAxes,
     pub hotas_mode: bool,
     pub invert_y: bool,
+    pub language: Language,
+    pub minimap: bool,
+    pub mode: GameMode,
+    pub monitor: Option<String>,
+    pub multisampling: Multisampling,
+    pub offline: bool,
+    pub overlay_accent: Option<[u8; 3]>,
+    pub overlay_auto_hide: Option<f32>,
+    pub overlay_theme: OverlayTheme,
+    pub particles: bool,
+    pub pause_on_focus_loss: bool,
+    pub plugins: bool,
+    pub portal_values: bool,
+    pub projection: Projection,
+    pub remote_control: Option<u16>,
+    pub speed_lines: Option<f32>,
+    pub speedrun_timer: bool,
+    pub stereo: StereoMode,
+    pub stereo_convergence: f32,
+    pub stream_files: bool,
+    pub symmetry: Symmetry,
+    pub temporal_reprojection: bool,
+    pub theme: WorldTheme,
+    pub trail: bool,
+    pub tutorial_complete: bool,
+    pub ui_scale: f32,
+    pub video_format: VideoFormat,
+    pub wind: Option<f32>,
+    pub window_positions: WindowPositions,
+    pub wingman: bool,
 }
 
 // Run state.
 #[derive(Default)]
 pub struct Run {
+    pub deepest_portal: Option<u32>,
+    pub fuel_spent: f32,
     pub level: u32,
     pub points: u32,
+    pub scoring: ScoringRules,
+    pub speed_portals: u32,
     pub start: Option<Instant>,
+    // The time taken to finish each level of the run, and its par.
+    pub level_times: Vec<LevelTime>,
 }
 
-// State of the keyboard inputs relevant to the game.
-#[allow(clippy::struct_excessive_bools)]
-#[derive(Default)]
-pub struct Keyboard {
-    pub up: bool,
-    pub down: bool,
-    pub left: bool,
-    pub right: bool,
-    pub space: bool,
-    pub a: bool,
-    pub d: bool,
+// How long a level of a run took to finish, in seconds, against its par time.
+#[derive(Clone, Copy)]
+pub struct LevelTime {
+    pub time: f32,
+    pub par: f32,
 }
 
-// State of the gamepad inputs relevant to the game.
-#[derive(Default)]
-pub struct GamepadState {
-    pub left_stick: [f32; 2],
-    pub yaw: SharedAxis,
-    pub south_button: bool,
+// How points are scored during a run, which follows the mode and difficulty until the run starts.
+#[derive(Clone, Copy)]
+pub struct ScoringRules {
+    // Portals at this depth score the points per depth, and each level deeper scores that much more.
+    pub minimum_depth: u32,
+    pub points_per_depth: u32,
+    // Points added to every scoring portal, and to the deepest portals there can be.
+    pub portal_bonus: u32,
+    pub deepest_bonus: u32,
+    pub ring_points: u32,
+    // Seconds under par for each bonus point when a level is finished.
+    pub par_bonus_seconds: f32,
+    // Every point scored is multiplied by this.
+    pub multiplier: u32,
 }
 
-// Helper type for tracking an axis value when one or two buttons control the result.
-pub enum SharedAxis {
-    Single(f32),
-    Split(f32, f32),
+// The raw event codes of the H.O.T.A.S. axes assigned to each control, as reported by gilrs.
+// Unassigned pitch and roll fall back to the left stick, and unassigned yaw to the right trigger.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct HotasAxes {
+    pub pitch: Option<u32>,
+    pub roll: Option<u32>,
+    pub yaw: Option<u32>,
+    pub throttle: Option<u32>,
+    pub invert_throttle: bool,
 }
 
-// Helper type for tracking activation that comes from either holding or toggling an input.
+// The controls which a H.O.T.A.S. axis can be assigned to.
 #[derive(Clone, Copy, PartialEq)]
+pub enum HotasControl {
+    Pitch,
+    Roll,
+    Yaw,
+    Throttle,
+}
+
+// Direction to look relative to the flight direction, held with the hat switch.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum LookDirection {
+    #[default]
+    Forward,
+    Left,
+    Right,
+    Back,
+    Up,
+}
+
+// How a gamepad axis responds to the stick. Movement within the deadzone is ignored, and the rest is raised to the
+// exponent then scaled by the sensitivity, so that exponents above one give finer control near the centre.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct AxisResponse {
+    pub deadzone: f32,
+    pub sensitivity: f32,
+    pub exponent: f32,
+}
+
+// The response of each gamepad axis which steers the camera. Yaw is only an axis in H.O.T.A.S. mode.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct GamepadResponse {
+    pub roll: AxisResponse,
+    pub pitch: AxisResponse,
+    pub yaw: AxisResponse,
+}
+
+// Helper type for tracking activation that comes from either holding or toggling an input.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
 pub enum HoldOrToggle {
     Hold,
     Toggle(bool),
 }
 
+// Points around the camera which are tested for collisions, in addition to the camera itself.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+pub enum CollisionHull {
+    Point,
+    #[default]
+    Wingtips,
+    Full,
+}
+
+// Something which happened during a run, for the overlay and effects to react to.
+#[derive(Clone, Copy)]
+pub enum RunEvent {
+    // A portal was taken, gaining the given points, which are none for speed portals, and reaching the given level.
+    PortalTaken { points: u32, level: u32 },
+    // The camera crashed, ending the run.
+    Crashed,
+    // Boosting began.
+    BoostStarted,
+    // An obstacle lies ahead within the warning distance, with closeness from zero at its edge to one on contact.
+    ObstacleAhead { closeness: f32 },
+}
+
+// How crashes and scoring are treated.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+pub enum GameMode {
+    // Crashing ends the run.
+    #[default]
+    Standard,
+
+    // Crashing returns the camera to the world's saved spawn point, keeping the run going.
+    Practice,
+
+    // A relaxed flight without crashes or scoring, at a gentler speed under a slower sun.
+    Zen,
+
+    // Curated worlds played in order, each with a score to reach.
+    Campaign,
+}
+
+// How the camera moves through the world.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+pub enum FlightModel {
+    // The camera flies where it points, at a speed which follows the size of the surrounding voxels.
+    #[default]
+    Arcade,
+
+    // The camera has momentum, and is pulled down by gravity and held up by the lift of its wings, which stall
+    // when the nose points too far from the direction of flight.
+    Advanced,
+}
+
+// How shadows are cast from the sun. The value is passed to the ray-marching shader.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+#[repr(u32)]
+pub enum ShadowQuality {
+    Off,
+    Hard,
+    #[default]
+    Soft,
+}
+
+// How the view is split between two eyes for 3D displays and glasses, short of a VR headset. The value is passed to
+// the ray-marching shader.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+#[repr(u32)]
+pub enum StereoMode {
+    #[default]
+    Off,
+
+    // Both eyes are blended into one image, to be seen through red and cyan glasses.
+    Anaglyph,
+
+    // Each eye is squeezed into half of the view, left then right, for 3D televisions and monitors.
+    SideBySide,
+}
+
+// What the ray-marching shader shows in place of the lit world, to help diagnose generation and traversal.
+// The value is passed to the ray-marching shader.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[repr(u32)]
+pub enum DebugView {
+    #[default]
+    Off,
+    Steps,
+    Depth,
+    Normals,
+    VoxelType,
+}
+
+// How many samples are taken of each pixel of the frame's rasterized passes, smoothing the edges of the overlay,
+// particles, and trail.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+pub enum Multisampling {
+    #[default]
+    Off,
+    X2,
+    X4,
+    X8,
+}
+
+// Video containers that recordings can be saved as.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+pub enum VideoFormat {
+    #[default]
+    Mp4,
+    WebM,
+}
+
+// Where the camera starts in a world. The default start outside the world is kept when the way in is clear, and
+// otherwise the camera starts in a roomy open space inside the world, facing a portal if one can be seen.
+pub fn world_start(octree: &[VoxelCompact]) -> (Vector3<f32>, Quaternion<f32>) {
+    let forward = DEFAULT_CAMERA_ORIENTATION.rotate_vector(Vector3::unit_z());
+    if voxels::approach_is_clear(DEFAULT_CAMERA_POSITION, forward, octree) {
+        return (DEFAULT_CAMERA_POSITION, DEFAULT_CAMERA_ORIENTATION);
+    }
+    let Some((position, direction)) = voxels::find_spawn(octree) else {
+        return (DEFAULT_CAMERA_POSITION, DEFAULT_CAMERA_ORIENTATION);
+    };
+
+    // Turn to face the direction while keeping the wings level.
+    let yaw = Quaternion::from_angle_y(Rad(direction.x.atan2(direction.z)));
+    let pitch = Quaternion::from_angle_x(Rad(-direction.y.clamp(-1., 1.).asin()));
+    (position, yaw * pitch)
+}
+
 // Manipulate the game state.
 impl State {
-    // Helper to reset the camera to the default position and orientation.
+    // Start new runs in this world from its start, as found by `world_start`.
+    pub fn place_start(&mut self, octree: &[VoxelCompact]) {
+        (self.start_position, self.start_orientation) = world_start(octree);
+    }
+
+    // Helper to reset the camera to the world's start position and orientation.
     pub fn reset_camera(&mut self) {
-        self.camera_position = DEFAULT_CAMERA_POSITION;
-        self.camera_quaternion = DEFAULT_CAMERA_ORIENTATION;
-        self.camera_speed = DEFAULT_CAMERA_SPEED;
+        self.camera_position = self.start_position;
+        self.camera_quaternion = self.start_orientation;
+        self.camera_speed = self.run.camera_speed();
+        self.align_velocity();
+    }
+
+    // Return the camera to a practice spawn point at the run's speed, keeping the run going.
+    pub fn respawn(&mut self, point: SpawnPoint) {
+        self.camera_position = point.position();
+        self.camera_quaternion = point.orientation();
+        self.camera_speed = self.run.camera_speed();
+        self.align_velocity();
+    }
+
+    // Return to the last position found clear of obstacles and turn to face the other way, which zen mode does
+    // instead of crashing.
+    pub fn turn_around(&mut self) {
+        self.camera_position = self.open_position;
+        self.camera_quaternion = self.camera_quaternion * Quaternion::from_angle_y(Deg(180.));
+        self.align_velocity();
+    }
+
+    // Point the velocity along the nose at the current speed, as arcade flight always does.
+    pub fn align_velocity(&mut self) {
+        self.velocity =
+            self.camera_quaternion
+                .rotate_vector(Vector3::new(0., 0., self.camera_speed));
+    }
+
+    // Apply thrust, gravity, lift, and drag over the elapsed time, then move the camera by the new velocity.
+    // Forces are relative to the given cruising speed, and thrust is scaled by the given factor.
+    // Returns the distance moved.
+    pub fn fly_advanced(&mut self, delta_time: f32, cruise_speed: f32, thrust_factor: f32) -> f32 {
+        let forward = self.camera_quaternion.rotate_vector(Vector3::unit_z());
+        let up = self.camera_quaternion.rotate_vector(Vector3::unit_y());
+        let relative_velocity = self.velocity / cruise_speed;
+        let speed = relative_velocity.magnitude();
+
+        let mut acceleration = THRUST * thrust_factor * forward - GRAVITY * Vector3::unit_y();
+        if speed > f32::EPSILON {
+            let direction = relative_velocity / speed;
+
+            // The angle of attack is measured in the plane of the nose and the wings' up direction, positive when
+            // the nose is above the direction of flight.
+            let angle_of_attack = (-direction.dot(up)).atan2(direction.dot(forward));
+            let lift_coefficient = if angle_of_attack.abs() <= STALL_ANGLE {
+                TAU * angle_of_attack
+            } else {
+                let stalled = ((angle_of_attack.abs() - STALL_ANGLE) / STALL_ANGLE).min(1.);
+                TAU * STALL_ANGLE.copysign(angle_of_attack) * (1. - stalled)
+            };
+
+            // Lift acts perpendicular to the direction of flight, drag against it.
+            let lift_direction = up - direction * direction.dot(up);
+            if lift_direction.magnitude2() > f32::EPSILON {
+                acceleration +=
+                    LIFT * lift_coefficient * speed * speed * lift_direction.normalize();
+            }
+            let drag = (PARASITIC_DRAG + INDUCED_DRAG * lift_coefficient * lift_coefficient)
+                * speed
+                * speed;
+
+            // Drag can't do more than stop the camera within a frame.
+            acceleration -= drag.min(speed / delta_time.max(f32::EPSILON)) * direction;
+
+            // The tail turns the nose toward the direction of flight.
+            let axis = forward.cross(direction);
+            let angle = axis.magnitude().asin();
+            if angle > f32::EPSILON {
+                let turn = (WEATHERVANE_RATE * speed * delta_time).min(angle);
+                self.camera_quaternion = Quaternion::from_axis_angle(axis.normalize(), Rad(turn))
+                    * self.camera_quaternion;
+            }
+        }
+
+        self.velocity += cruise_speed * delta_time * acceleration;
+        self.camera_speed = self.velocity.magnitude();
+        let displacement = self.velocity * delta_time;
+        self.camera_position += displacement;
+        displacement.magnitude()
+    }
+
+    // Whether any input is currently requesting a boost, and there is fuel for it when boosting uses fuel.
+    pub fn is_boosting(&self) -> bool {
+        let requested = self.input.boost_held() || self.options.camera_boost.into();
+        requested && (!self.options.fuel_boost || self.run.fuel() > 0.)
+    }
+
+    // Use up fuel while boosting during a run, when boosting uses fuel.
+    pub fn burn_fuel(&mut self, delta_time: f32) {
+        if self.options.fuel_boost && self.run.start.is_some() && self.is_boosting() {
+            self.run.fuel_spent = (self.run.fuel_spent + delta_time / FUEL_BOOST_SECONDS).min(1.);
+        }
+    }
+
+    // Ease the blend between flying normally and boosting, which drives the effects of boosting.
+    pub fn update_boost_blend(&mut self, delta_time: f32) {
+        let target = f32::from(self.is_boosting());
+        self.boost_blend +=
+            (target - self.boost_blend) * (1. - (-BOOST_EASING_RATE * delta_time).exp());
+    }
+
+    // The tangent of half the vertical field of view, which is what the shader expects.
+    // The view is optionally widened while boosting.
+    pub fn fov_y(&self) -> f32 {
+        let kick = if self.options.dynamic_fov {
+            FOV_BOOST_KICK * self.boost_blend
+        } else {
+            0.
+        };
+        (0.5 * (self.options.fov + kick).to_radians()).tan()
+    }
+
+    // How the view is projected onto a window of the given aspect ratio, keeping within the horizontal field of
+    // view's cap.
+    pub fn lens(&self, aspect_ratio: f32) -> Lens {
+        Lens::new(
+            self.options.projection,
+            self.fov_y(),
+            aspect_ratio,
+            self.options.horizontal_fov_cap,
+        )
+    }
+
+    // Strength of the speed lines drawn while boosting, which fade as the camera slows down.
+    pub fn speed_lines(&self) -> f32 {
+        self.options.speed_lines.map_or(0., |intensity| {
+            intensity * self.boost_blend * (self.camera_speed / DEFAULT_CAMERA_SPEED).min(1.)
+        })
+    }
+
+    // Start the shake and flash which follow a crash, which fade out over a moment.
+    pub fn begin_crash_feedback(&mut self) {
+        self.crash_feedback = 1.;
+    }
+
+    pub fn update_crash_feedback(&mut self, delta_time: f32) {
+        self.crash_feedback = (self.crash_feedback - delta_time / CRASH_FEEDBACK_DURATION).max(0.);
+    }
+
+    // Start the chromatic flash which follows taking a portal during a run.
+    pub fn begin_score_flash(&mut self) {
+        self.score_flash = 1.;
+    }
+
+    pub fn update_score_flash(&mut self, delta_time: f32) {
+        self.score_flash = (self.score_flash - delta_time / SCORE_FLASH_DURATION).max(0.);
+    }
+
+    // The orientation to render the camera with, turned by the hat switch and shaken after a crash.
+    // Neither affects flight.
+    pub fn shaken_camera_quaternion(&self, time: f32) -> Quaternion<f32> {
+        let view_quaternion = self.camera_quaternion * self.input.look.rotation();
+        let angle = MAXIMUM_SHAKE_ANGLE * self.options.crash_feedback * self.crash_feedback.powi(2);
+        if angle <= 0. {
+            return view_quaternion;
+        }
+
+        // Sums of sines at unrelated frequencies give smooth noise, offset for each axis.
+        let noise = |offset: f32| {
+            0.6 * (37. * time + offset).sin() + 0.4 * (59. * time + 2.3 * offset).sin()
+        };
+        view_quaternion
+            * Quaternion::from_angle_z(Rad(angle * noise(0.)))
+            * Quaternion::from_angle_x(Rad(angle * noise(1.7)))
+            * Quaternion::from_angle_y(Rad(angle * noise(4.1)))
+    }
+
+    // Scale of the camera speed set by the throttle, which is only read in H.O.T.A.S. mode.
+    pub fn throttle_factor(&self) -> f32 {
+        match self.input.throttle {
+            Some(throttle) if self.options.hotas_mode => {
+                THROTTLE_MINIMUM + (THROTTLE_MAXIMUM - THROTTLE_MINIMUM) * throttle
+            }
+            _ => 1.,
+        }
+    }
+
+    // Opacity of the red flash after a crash.
+    pub fn crash_flash(&self) -> f32 {
+        (0.4 * self.options.crash_feedback * self.crash_feedback.powi(2)).min(1.)
+    }
+
+    // Apply an input action, returning whether it was the player steering or boosting.
+    pub fn apply_action(&mut self, action: Action) -> bool {
+        if action == Action::ToggleBoost {
+            if let HoldOrToggle::Toggle(toggled) = &mut self.options.camera_boost {
+                *toggled = !*toggled;
+            }
+            return false;
+        }
+        self.input.apply(action)
+    }
+
+    // Apply the actions queued since the last frame, returning whether any were the player steering or boosting.
+    pub fn apply_queued_actions(&mut self) -> bool {
+        let mut started = false;
+        for action in self.input.take_queue() {
+            started |= self.apply_action(action);
+        }
+        started
+    }
+
+    // Rotate the camera by the given roll, pitch, and yaw over the elapsed time.
+    pub fn turn_camera(&mut self, delta_time: f32, inputs: (f32, f32, f32)) {
+        self.camera_quaternion = turned(self.camera_quaternion, delta_time, inputs);
     }
 }
 
@@ -103,12 +637,27 @@ impl Default for State {
         Self {
             camera_position: DEFAULT_CAMERA_POSITION,
             camera_quaternion: DEFAULT_CAMERA_ORIENTATION,
+            boost_blend: 0.,
+            boosting: false,
             camera_speed: DEFAULT_CAMERA_SPEED,
+            crash_feedback: 0.,
+            events: Vec::new(),
             gamepad: GamepadState::default(),
             gilrs: Gilrs::new().unwrap(),
+            input: Input::default(),
             keyboard: Keyboard::default(),
+            open_position: DEFAULT_CAMERA_POSITION,
             options: Options::default(),
             run: Run::default(),
+            score_flash: 0.,
+            spawn_points: SpawnPoints::default(),
+            start_orientation: DEFAULT_CAMERA_ORIENTATION,
+            start_position: DEFAULT_CAMERA_POSITION,
+            velocity: DEFAULT_CAMERA_ORIENTATION.rotate_vector(Vector3::new(
+                0.,
+                0.,
+                DEFAULT_CAMERA_SPEED,
+            )),
         }
     }
 }
@@ -117,13 +666,184 @@ impl Default for State {
 impl Default for Options {
     fn default() -> Self {
         Self {
+            audio: AudioMixer::default(),
             camera_boost: HoldOrToggle::Hold,
+            chase: false,
+            check_for_updates: false,
+            checkerboard_rendering: false,
+            collision_hull: CollisionHull::default(),
+            colorblind_mode: ColorblindMode::default(),
+            controller: None,
+            crash_feedback: 1.,
+            custom_mode: None,
+            destructible_voxels: false,
+            difficulty_band: None,
+            dynamic_fov: true,
+            dynamic_resolution: None,
+            fov: DEFAULT_FOV,
+            flight_model: FlightModel::default(),
+            fps_limit: None,
+            fuel_boost: false,
+            fullscreen: FullscreenMode::default(),
+            gamepad_response: GamepadResponse::default(),
+            generator: WorldGenerator::default(),
+            gpu: None,
+            graphics: GraphicsSettings::default(),
+            haptics: HapticOptions::default(),
+            horizontal_fov_cap: None,
+            hotas_axes: HotasAxes::default(),
             hotas_mode: false,
             invert_y: true,
+            language: Language::default(),
+            minimap: false,
+            mode: GameMode::default(),
+            monitor: None,
+            multisampling: Multisampling::default(),
+            offline: false,
+            overlay_accent: None,
+            overlay_auto_hide: None,
+            overlay_theme: OverlayTheme::default(),
+            particles: true,
+            pause_on_focus_loss: true,
+            plugins: false,
+            portal_values: true,
+            projection: Projection::default(),
+            remote_control: None,
+            speed_lines: Some(DEFAULT_SPEED_LINES),
+            speedrun_timer: false,
+            stereo: StereoMode::default(),
+            stereo_convergence: DEFAULT_STEREO_CONVERGENCE,
+            stream_files: false,
+            symmetry: Symmetry::default(),
+            temporal_reprojection: false,
+            theme: WorldTheme::default(),
+            trail: true,
+            tutorial_complete: false,
+            ui_scale: 1.,
+            video_format: VideoFormat::default(),
+            wind: None,
+            window_positions: WindowPositions::default(),
+            wingman: false,
         }
     }
 }
 
+// Load and store the game options.
+impl Options {
+    // Load the stored options, using defaults for any that are missing.
+    pub fn load() -> Self {
+        let mut options: Self = persistence::load(&persistence::profile_file(SETTINGS_FILE_NAME));
+        options.ui_scale = options
+            .ui_scale
+            .clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end());
+        options.fov = options.fov.clamp(*FOV_RANGE.start(), *FOV_RANGE.end());
+        options.horizontal_fov_cap = options.horizontal_fov_cap.map(|cap| {
+            cap.clamp(
+                *HORIZONTAL_FOV_CAP_RANGE.start(),
+                *HORIZONTAL_FOV_CAP_RANGE.end(),
+            )
+        });
+        options.crash_feedback = options
+            .crash_feedback
+            .clamp(*CRASH_FEEDBACK_RANGE.start(), *CRASH_FEEDBACK_RANGE.end());
+        options.graphics = options.graphics.clamped();
+        options.stereo_convergence = options.stereo_convergence.clamp(
+            *STEREO_CONVERGENCE_RANGE.start(),
+            *STEREO_CONVERGENCE_RANGE.end(),
+        );
+        options.speed_lines = options
+            .speed_lines
+            .map(|intensity| intensity.clamp(*SPEED_LINES_RANGE.start(), *SPEED_LINES_RANGE.end()));
+        options.fps_limit = options
+            .fps_limit
+            .map(|limit| limit.clamp(*FPS_LIMIT_RANGE.start(), *FPS_LIMIT_RANGE.end()));
+        options.dynamic_resolution = options
+            .dynamic_resolution
+            .map(|target| target.clamp(*FPS_LIMIT_RANGE.start(), *FPS_LIMIT_RANGE.end()));
+        options.overlay_auto_hide = options.overlay_auto_hide.map(|delay| {
+            delay.clamp(
+                *OVERLAY_AUTO_HIDE_RANGE.start(),
+                *OVERLAY_AUTO_HIDE_RANGE.end(),
+            )
+        });
+        options
+    }
+
+    // Store the options in the app directory. An active boost toggle is not carried over to the next launch.
+    pub fn save(&self) {
+        let camera_boost = match self.camera_boost {
+            HoldOrToggle::Toggle(_) => HoldOrToggle::Toggle(false),
+            HoldOrToggle::Hold => HoldOrToggle::Hold,
+        };
+        persistence::save(
+            &persistence::profile_file(SETTINGS_FILE_NAME),
+            &Options {
+                camera_boost,
+                ..self.clone()
+            },
+        );
+    }
+}
+
+impl ScoringRules {
+    // The points scored by taking a portal at the given depth, counted as `voxels::Intersection::Portal` counts it.
+    pub fn portal_points(&self, depth: u32) -> u32 {
+        let levels = (depth + 1).saturating_sub(self.minimum_depth);
+        let deepest = if depth == voxels::MAXIMUM_GOAL_DEPTH {
+            self.deepest_bonus
+        } else {
+            0
+        };
+        (levels * self.points_per_depth + self.portal_bonus + deepest) * self.multiplier
+    }
+
+    // The points scored by collecting the given number of rings.
+    pub fn ring_points(&self, count: u32) -> u32 {
+        count * self.ring_points * self.multiplier
+    }
+
+    // The bonus points scored by finishing a level in the given time.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn time_bonus(&self, level_time: LevelTime) -> u32 {
+        if self.par_bonus_seconds <= 0. {
+            return 0;
+        }
+        let spare = (level_time.par - level_time.time).max(0.);
+        (spare / self.par_bonus_seconds) as u32 * self.multiplier
+    }
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        Self {
+            minimum_depth: voxels::MINIMUM_GOAL_DEPTH,
+            points_per_depth: 1,
+            portal_bonus: 0,
+            deepest_bonus: 1,
+            ring_points: RING_POINTS,
+            par_bonus_seconds: PAR_BONUS_SECONDS,
+            multiplier: 1,
+        }
+    }
+}
+
+// An orientation rotated by the given roll, pitch, and yaw over the elapsed time, at the speeds the camera turns.
+pub fn turned(
+    orientation: Quaternion<f32>,
+    delta_time: f32,
+    (roll, pitch, yaw): (f32, f32, f32),
+) -> Quaternion<f32> {
+    orientation
+        * Quaternion::from_angle_z(Rad(delta_time * ROLL_SPEED * roll))
+        * Quaternion::from_angle_x(Rad(delta_time * PITCH_SPEED * pitch))
+        * Quaternion::from_angle_y(Rad(delta_time * YAW_SPEED * yaw))
+}
+
+// The par time of a level in a world of the given difficulty rating.
+pub fn par_time(difficulty: f32) -> f32 {
+    PAR_SECONDS + PAR_SECONDS_PER_DIFFICULTY * difficulty
+}
+
 // Manipulate the run state.
 impl Run {
     // Helper function to ensure a run has started if conditions are met.
@@ -134,12 +854,282 @@ impl Run {
             _ => (),
         }
     }
+
+    // Seconds since the run started, if it has.
+    pub fn elapsed(&self) -> Option<f32> {
+        self.start.map(|start| start.elapsed().as_secs_f32())
+    }
+
+    // Seconds spent on the current level, since the last level was finished.
+    pub fn level_elapsed(&self) -> Option<f32> {
+        let finished: f32 = self.level_times.iter().map(|level| level.time).sum();
+        self.elapsed().map(|elapsed| elapsed - finished)
+    }
+
+    // Record the current level as finished, returning its time against the given par.
+    pub fn finish_level(&mut self, par: f32) -> LevelTime {
+        let level_time = LevelTime {
+            time: self.level_elapsed().unwrap_or_default(),
+            par,
+        };
+        self.level_times.push(level_time);
+        level_time
+    }
+
+    // The fraction of the fuel meter left, which starts each run full.
+    pub fn fuel(&self) -> f32 {
+        1. - self.fuel_spent
+    }
+
+    // Refill the given fraction of the fuel meter.
+    pub fn refuel(&mut self, amount: f32) {
+        self.fuel_spent = (self.fuel_spent - amount).max(0.);
+    }
+
+    // The camera's speed in the largest voxels, raised by each speed portal taken during the run.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn camera_speed(&self) -> f32 {
+        DEFAULT_CAMERA_SPEED * SPEED_PORTAL_FACTOR.powi(self.speed_portals as i32)
+    }
+}
+
+impl HotasAxes {
+    pub const CONTROLS: [HotasControl; 4] = [
+        HotasControl::Pitch,
+        HotasControl::Roll,
+        HotasControl::Yaw,
+        HotasControl::Throttle,
+    ];
+
+    // Mutable access to the axis assigned to the given control.
+    pub fn get_mut(&mut self, control: HotasControl) -> &mut Option<u32> {
+        match control {
+            HotasControl::Pitch => &mut self.pitch,
+            HotasControl::Roll => &mut self.roll,
+            HotasControl::Yaw => &mut self.yaw,
+            HotasControl::Throttle => &mut self.throttle,
+        }
+    }
+
+    // The code of the axis assigned to the given control, if any.
+    pub fn get(&self, control: HotasControl) -> Option<u32> {
+        match control {
+            HotasControl::Pitch => self.pitch,
+            HotasControl::Roll => self.roll,
+            HotasControl::Yaw => self.yaw,
+            HotasControl::Throttle => self.throttle,
+        }
+    }
+
+    // The control the axis with the given code is assigned to, if any.
+    pub fn control(&self, code: u32) -> Option<HotasControl> {
+        Self::CONTROLS
+            .into_iter()
+            .find(|&control| self.get(control) == Some(code))
+    }
 }
 
-// Make managaing the gamepad state easier with default axis value and type.
-impl Default for SharedAxis {
+impl LookDirection {
+    // The direction held on a hat switch which reports as a pair of axes, with the centre looking forward.
+    pub fn from_hat_axis(axis: gilrs::Axis, value: f32) -> Self {
+        match axis {
+            gilrs::Axis::DPadX if value > 0.5 => LookDirection::Right,
+            gilrs::Axis::DPadX if value < -0.5 => LookDirection::Left,
+            gilrs::Axis::DPadY if value > 0.5 => LookDirection::Up,
+            gilrs::Axis::DPadY if value < -0.5 => LookDirection::Back,
+            _ => LookDirection::Forward,
+        }
+    }
+
+    // The rotation from the flight direction to the view.
+    pub fn rotation(self) -> Quaternion<f32> {
+        match self {
+            LookDirection::Forward => Quaternion::new(1., 0., 0., 0.),
+            LookDirection::Left => Quaternion::from_angle_y(Deg(-90.)),
+            LookDirection::Right => Quaternion::from_angle_y(Deg(90.)),
+            LookDirection::Back => Quaternion::from_angle_y(Deg(180.)),
+            LookDirection::Up => Quaternion::from_angle_x(Deg(-90.)),
+        }
+    }
+}
+
+// The raw stick values pass through unchanged by default.
+impl Default for AxisResponse {
     fn default() -> Self {
-        Self::Single(0.)
+        Self {
+            deadzone: 0.,
+            sensitivity: 1.,
+            exponent: 1.,
+        }
+    }
+}
+
+impl AxisResponse {
+    // Shape a raw axis value in the range [-1, 1], keeping its sign.
+    pub fn apply(self, value: f32) -> f32 {
+        let magnitude = value.abs();
+        if magnitude <= self.deadzone {
+            return 0.;
+        }
+        let outside = ((magnitude - self.deadzone) / (1. - self.deadzone)).min(1.);
+        (self.sensitivity * outside.powf(self.exponent))
+            .min(1.)
+            .copysign(value)
+    }
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Standard,
+        GameMode::Practice,
+        GameMode::Zen,
+        GameMode::Campaign,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            GameMode::Standard => Text::ModeStandard,
+            GameMode::Practice => Text::ModePractice,
+            GameMode::Zen => Text::ModeZen,
+            GameMode::Campaign => Text::ModeCampaign,
+        }
+    }
+}
+
+impl FlightModel {
+    pub const ALL: [FlightModel; 2] = [FlightModel::Arcade, FlightModel::Advanced];
+
+    pub fn name(self) -> Text {
+        match self {
+            FlightModel::Arcade => Text::FlightArcade,
+            FlightModel::Advanced => Text::FlightAdvanced,
+        }
+    }
+}
+
+impl CollisionHull {
+    pub const ALL: [CollisionHull; 3] = [
+        CollisionHull::Point,
+        CollisionHull::Wingtips,
+        CollisionHull::Full,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            CollisionHull::Point => Text::HullPoint,
+            CollisionHull::Wingtips => Text::HullWingtips,
+            CollisionHull::Full => Text::HullFull,
+        }
+    }
+
+    // Sample points in camera space, as a fraction of the hull size. The camera looks along +Z.
+    pub fn points(self) -> &'static [Vector3<f32>] {
+        const WINGTIPS: [Vector3<f32>; 2] = [Vector3::new(-1., 0., 0.), Vector3::new(1., 0., 0.)];
+        const FULL: [Vector3<f32>; 6] = [
+            Vector3::new(-1., 0., 0.),
+            Vector3::new(1., 0., 0.),
+            Vector3::new(0., 0., 0.6),
+            Vector3::new(0., 0., -0.6),
+            Vector3::new(0., 0.3, 0.),
+            Vector3::new(0., -0.3, 0.),
+        ];
+        match self {
+            CollisionHull::Point => &[],
+            CollisionHull::Wingtips => &WINGTIPS,
+            CollisionHull::Full => &FULL,
+        }
+    }
+}
+
+impl ShadowQuality {
+    pub const ALL: [ShadowQuality; 3] =
+        [ShadowQuality::Off, ShadowQuality::Hard, ShadowQuality::Soft];
+
+    pub fn name(self) -> Text {
+        match self {
+            ShadowQuality::Off => Text::ShadowsOff,
+            ShadowQuality::Hard => Text::ShadowsHard,
+            ShadowQuality::Soft => Text::ShadowsSoft,
+        }
+    }
+}
+
+impl StereoMode {
+    pub const ALL: [StereoMode; 3] = [
+        StereoMode::Off,
+        StereoMode::Anaglyph,
+        StereoMode::SideBySide,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            StereoMode::Off => Text::StereoOff,
+            StereoMode::Anaglyph => Text::StereoAnaglyph,
+            StereoMode::SideBySide => Text::StereoSideBySide,
+        }
+    }
+}
+
+impl DebugView {
+    pub const ALL: [DebugView; 5] = [
+        DebugView::Off,
+        DebugView::Steps,
+        DebugView::Depth,
+        DebugView::Normals,
+        DebugView::VoxelType,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            DebugView::Off => Text::DebugViewOff,
+            DebugView::Steps => Text::DebugViewSteps,
+            DebugView::Depth => Text::DebugViewDepth,
+            DebugView::Normals => Text::DebugViewNormals,
+            DebugView::VoxelType => Text::DebugViewVoxelType,
+        }
+    }
+}
+
+impl Multisampling {
+    pub const ALL: [Multisampling; 4] = [
+        Multisampling::Off,
+        Multisampling::X2,
+        Multisampling::X4,
+        Multisampling::X8,
+    ];
+
+    pub fn samples(self) -> u32 {
+        match self {
+            Multisampling::Off => 1,
+            Multisampling::X2 => 2,
+            Multisampling::X4 => 4,
+            Multisampling::X8 => 8,
+        }
+    }
+
+    pub fn label(self, lang: Language) -> String {
+        match self {
+            Multisampling::Off => lang.tr(Text::MultisamplingOff).to_owned(),
+            _ => format!("{}×", self.samples()),
+        }
+    }
+}
+
+impl VideoFormat {
+    pub const ALL: [VideoFormat; 2] = [VideoFormat::Mp4, VideoFormat::WebM];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VideoFormat::Mp4 => "MP4",
+            VideoFormat::WebM => "WebM",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            VideoFormat::Mp4 => "mp4",
+            VideoFormat::WebM => "webm",
+        }
     }
 }
 
@@ -0,0 +1,157 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::ShadowQuality;
+use crate::i18n::Text;
+use crate::renderer;
+
+pub const RENDER_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=1.;
+pub const REFLECTION_BOUNCES_RANGE: std::ops::RangeInclusive<u32> = 0..=4;
+pub const FOG_DISTANCE_RANGE: std::ops::RangeInclusive<u32> = 20..=60;
+pub const MAXIMUM_DEPTH_RANGE: std::ops::RangeInclusive<u32> = 8..=15;
+
+// How the world is rendered, trading its detail for performance. The render scale is applied by the renderer and
+// the rest is passed to the ray-marching shader.
+// The fields are stored alongside the other game options, so that settings saved before they were grouped still load.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    // The fraction of the window's resolution that the world is rendered at, before any dynamic resolution.
+    pub render_scale: f32,
+
+    // How many times rays bounce off mirror voxels. Each bounce continues the march and casts another shadow ray.
+    pub reflection_bounces: u32,
+
+    pub shadow_quality: ShadowQuality,
+    pub ambient_occlusion: bool,
+
+    // The steps a ray marches before the fog hides it completely. Fewer steps bring the fog closer and cost less.
+    pub fog_distance: u32,
+
+    // The deepest level of the octree that rays march into, limiting the finest detail drawn up close.
+    pub maximum_depth: u32,
+}
+
+// Sets of graphics settings chosen together from a single list.
+#[derive(Clone, Copy, PartialEq)]
+pub enum GraphicsPreset {
+    Low,
+    Medium,
+    High,
+    Ultra,
+}
+
+// The settings of the high preset, which the game has always rendered with.
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        GraphicsPreset::High.settings()
+    }
+}
+
+impl GraphicsSettings {
+    // Keep stored settings within their ranges.
+    pub fn clamped(self) -> Self {
+        Self {
+            render_scale: self
+                .render_scale
+                .clamp(*RENDER_SCALE_RANGE.start(), *RENDER_SCALE_RANGE.end()),
+            reflection_bounces: self.reflection_bounces.clamp(
+                *REFLECTION_BOUNCES_RANGE.start(),
+                *REFLECTION_BOUNCES_RANGE.end(),
+            ),
+            fog_distance: self
+                .fog_distance
+                .clamp(*FOG_DISTANCE_RANGE.start(), *FOG_DISTANCE_RANGE.end()),
+            maximum_depth: self
+                .maximum_depth
+                .clamp(*MAXIMUM_DEPTH_RANGE.start(), *MAXIMUM_DEPTH_RANGE.end()),
+            ..self
+        }
+    }
+
+    // The preset these settings match, if any.
+    pub fn preset(&self) -> Option<GraphicsPreset> {
+        GraphicsPreset::ALL
+            .into_iter()
+            .find(|preset| preset.settings() == *self)
+    }
+
+    // The value of `FrameUniforms::traversal`, which packs how far rays go for the shader.
+    pub fn traversal(&self) -> u32 {
+        self.reflection_bounces
+            | self.maximum_depth << renderer::TRAVERSAL_DEPTH_SHIFT
+            | self.fog_distance << renderer::TRAVERSAL_STEPS_SHIFT
+    }
+}
+
+impl GraphicsPreset {
+    pub const ALL: [GraphicsPreset; 4] = [
+        GraphicsPreset::Low,
+        GraphicsPreset::Medium,
+        GraphicsPreset::High,
+        GraphicsPreset::Ultra,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            GraphicsPreset::Low => Text::GraphicsLow,
+            GraphicsPreset::Medium => Text::GraphicsMedium,
+            GraphicsPreset::High => Text::GraphicsHigh,
+            GraphicsPreset::Ultra => Text::GraphicsUltra,
+        }
+    }
+
+    pub fn settings(self) -> GraphicsSettings {
+        match self {
+            GraphicsPreset::Low => GraphicsSettings {
+                render_scale: 0.7,
+                reflection_bounces: 0,
+                shadow_quality: ShadowQuality::Off,
+                ambient_occlusion: false,
+                fog_distance: 24,
+                maximum_depth: 11,
+            },
+            GraphicsPreset::Medium => GraphicsSettings {
+                render_scale: 0.85,
+                reflection_bounces: 1,
+                shadow_quality: ShadowQuality::Hard,
+                ambient_occlusion: false,
+                fog_distance: 30,
+                maximum_depth: 13,
+            },
+            GraphicsPreset::High => GraphicsSettings {
+                render_scale: 1.,
+                reflection_bounces: 2,
+                shadow_quality: ShadowQuality::Soft,
+                ambient_occlusion: true,
+                fog_distance: 35,
+                maximum_depth: 15,
+            },
+            GraphicsPreset::Ultra => GraphicsSettings {
+                render_scale: 1.,
+                reflection_bounces: 4,
+                shadow_quality: ShadowQuality::Soft,
+                ambient_occlusion: true,
+                fog_distance: 48,
+                maximum_depth: 15,
+            },
+        }
+    }
+}
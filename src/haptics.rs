@@ -0,0 +1,149 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Replay, Ticks};
+use serde::{Deserialize, Serialize};
+use web_time::{Duration, Instant};
+
+use crate::controllers;
+use crate::game::State;
+
+pub const INTENSITY_RANGE: std::ops::RangeInclusive<f32> = 0.0..=1.;
+
+// Seconds between rumbles warning of an obstacle ahead, so that a steady approach pulses rather than buzzes.
+const PROXIMITY_INTERVAL: f32 = 0.35;
+
+// Strength of the rumble for each kind of event, scaled together by the controller.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct HapticOptions {
+    pub enabled: bool,
+    pub collision: f32,
+    pub portal: f32,
+    pub boost: f32,
+    pub proximity: f32,
+}
+
+// Events which rumble the controller.
+#[derive(Clone, Copy)]
+pub enum Rumble {
+    Collision,
+    Portal,
+    Boost,
+    // How close the obstacle ahead is, from zero at the edge of the warning distance to one on contact.
+    Proximity(f32),
+}
+
+// Plays rumble effects on the controllers that support them, keeping each effect alive until it finishes.
+#[derive(Default)]
+pub struct Haptics {
+    effects: Vec<(Effect, Instant)>,
+    proximity_cooldown: f32,
+}
+
+impl Default for HapticOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            collision: 1.,
+            portal: 0.6,
+            boost: 0.3,
+            proximity: 0.4,
+        }
+    }
+}
+
+impl Haptics {
+    // Rumble the controllers being read for the given event, scaled by its intensity setting.
+    pub fn play(&mut self, game: &mut State, rumble: Rumble) {
+        let options = game.options.haptics;
+        if !options.enabled {
+            return;
+        }
+
+        // Strong and weak motor strengths, and how long to rumble for in milliseconds.
+        let (strong, weak, milliseconds) = match rumble {
+            Rumble::Collision => (options.collision, options.collision, 320),
+            Rumble::Portal => (0.3 * options.portal, options.portal, 180),
+            Rumble::Boost => (0., options.boost, 120),
+            Rumble::Proximity(closeness) => {
+                if self.proximity_cooldown > 0. {
+                    return;
+                }
+                self.proximity_cooldown = PROXIMITY_INTERVAL;
+                (0., closeness.clamp(0., 1.) * options.proximity, 60)
+            }
+        };
+        if strong <= 0. && weak <= 0. {
+            return;
+        }
+
+        let chosen = game.options.controller.as_deref();
+        let gamepads: Vec<_> = game
+            .gilrs
+            .gamepads()
+            .filter(|(id, gamepad)| {
+                gamepad.is_ff_supported() && controllers::accepts(&game.gilrs, *id, chosen)
+            })
+            .map(|(id, _)| id)
+            .collect();
+        if gamepads.is_empty() {
+            return;
+        }
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let magnitude = |strength: f32| (f32::from(u16::MAX) * strength.min(1.)).round() as u16;
+        let scheduling = Replay {
+            play_for: Ticks::from_ms(milliseconds),
+            ..Default::default()
+        };
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong {
+                    magnitude: magnitude(strong),
+                },
+                scheduling,
+                envelope: Default::default(),
+            })
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Weak {
+                    magnitude: magnitude(weak),
+                },
+                scheduling,
+                envelope: Default::default(),
+            })
+            .gamepads(&gamepads)
+            .repeat(Repeat::For(Ticks::from_ms(milliseconds)))
+            .finish(&mut game.gilrs)
+            .and_then(|effect| effect.play().map(|()| effect));
+        match effect {
+            Ok(effect) => self.effects.push((
+                effect,
+                Instant::now() + Duration::from_millis(milliseconds.into()),
+            )),
+            Err(e) => log::error!(target: "haptics", "Failed to play rumble: {e}"),
+        }
+    }
+
+    // Release the effects which have finished playing.
+    pub fn update(&mut self, delta_time: f32) {
+        self.proximity_cooldown = (self.proximity_cooldown - delta_time).max(0.);
+        let now = Instant::now();
+        self.effects.retain(|(_, end)| *end > now);
+    }
+}
@@ -0,0 +1,158 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bytemuck::{Pod, Zeroable};
+use cgmath::{InnerSpace, Vector3};
+
+use crate::voxels::{self, RandomOctreeHelper, TraversalCache, VoxelCompact};
+
+// The number of hazards the shader reads. Must match `maximumHazards` in `ray_march_voxels.frag`.
+pub const MAXIMUM_HAZARDS: usize = 6;
+
+// Points sampled when probing the world for open space for hazards to move through.
+const PLACEMENT_SAMPLES: u32 = 256;
+
+// Only empty voxels at least this large, relative to the root voxel, are open enough for a hazard.
+const MINIMUM_OPEN_HALF_SIZE: f32 = 1. / 16.;
+
+// Sizes relative to the half-size of the empty voxel that a hazard moves through. The path stays inside the voxel.
+const HAZARD_SIZE: f32 = 0.08;
+const PATH_RADIUS: f32 = 0.6;
+
+// Range of angular speeds along a hazard's path, in radians per second.
+const MINIMUM_SPEED: f32 = 0.4;
+const MAXIMUM_SPEED: f32 = 1.2;
+
+// A voxel-sized cube moving through open space, either orbiting the centre of an empty voxel or patrolling back and
+// forth through it. Its centre is `centre + cos(angle) * a + sin(angle) * b`, where the angle grows with time at
+// the speed in the `w` of `a` from the phase in the `w` of `b`. The `w` of `centre` is the cube's half-size.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+pub struct Hazard {
+    pub centre: [f32; 4],
+    pub a: [f32; 4],
+    pub b: [f32; 4],
+}
+
+// The hazards of a world, laid out as the `Hazards` uniform block in `ray_march_voxels.frag`. They are placed once
+// each time a world is generated, and both the game and the shader move them along their paths by the time.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct WorldHazards {
+    pub hazards: [Hazard; MAXIMUM_HAZARDS],
+    pub count: u32,
+    _padding: [u32; 3],
+}
+
+impl Hazard {
+    // The centre of the hazard at the given time.
+    pub fn position(&self, time: f32) -> Vector3<f32> {
+        let angle = self.a[3] * time + self.b[3];
+        let [c, a, b] = [self.centre, self.a, self.b].map(|v| Vector3::new(v[0], v[1], v[2]));
+        c + a * angle.cos() + b * angle.sin()
+    }
+
+    // Whether a point is inside the hazard's cube at the given time.
+    pub fn contains(&self, point: Vector3<f32>, time: f32) -> bool {
+        let d = point - self.position(time);
+        let half_size = self.centre[3];
+        d.x.abs() <= half_size && d.y.abs() <= half_size && d.z.abs() <= half_size
+    }
+}
+
+impl WorldHazards {
+    // Place hazards in large empty voxels found by probing the world at random points, each given a random path,
+    // speed, and phase. The same seed places the same hazards.
+    pub fn place(random: &mut RandomOctreeHelper, octree: &[VoxelCompact]) -> Self {
+        let mut hazards = WorldHazards::zeroed();
+        let mut cache = TraversalCache::default();
+        for _ in 0..PLACEMENT_SAMPLES {
+            if hazards.count as usize == MAXIMUM_HAZARDS {
+                break;
+            }
+            hazards.try_place(random, octree, &mut cache);
+        }
+        hazards
+    }
+
+    // Add one more hazard to the world, such as when chat votes for one. Returns false if the world already has as
+    // many hazards as the shader reads, or no open space was found for another.
+    pub fn add(&mut self, random: &mut RandomOctreeHelper, octree: &[VoxelCompact]) -> bool {
+        let mut cache = TraversalCache::default();
+        (0..PLACEMENT_SAMPLES).any(|_| {
+            (self.count as usize) < MAXIMUM_HAZARDS && self.try_place(random, octree, &mut cache)
+        })
+    }
+
+    // Probe the world at a random point, placing a hazard if it is in a large enough empty voxel without one.
+    fn try_place(
+        &mut self,
+        random: &mut RandomOctreeHelper,
+        octree: &[VoxelCompact],
+        cache: &mut TraversalCache,
+    ) -> bool {
+        let mut point = || 2. * random.samplef() - 1.;
+        let position = Vector3::new(point(), point(), point());
+        let Some((center, half_size)) = voxels::empty_voxel_at(position, octree, cache) else {
+            return false;
+        };
+        let occupied = self.hazards[..self.count as usize]
+            .iter()
+            .any(|hazard| hazard.centre[..3] == [center.x, center.y, center.z]);
+        if half_size < MINIMUM_OPEN_HALF_SIZE || occupied {
+            return false;
+        }
+
+        // A random axis to orbit about or patrol along.
+        let mut axis = Vector3::new(point(), point(), point());
+        if axis.magnitude2() < 1e-4 {
+            axis = Vector3::unit_y();
+        }
+        let axis = axis.normalize();
+        let radius = PATH_RADIUS * half_size;
+        let (a, b) = if random.samplef() < 0.5 {
+            // Orbit in the plane perpendicular to the axis.
+            let helper = if axis.x.abs() < 0.9 {
+                Vector3::unit_x()
+            } else {
+                Vector3::unit_z()
+            };
+            let a = axis.cross(helper).normalize();
+            (a * radius, axis.cross(a) * radius)
+        } else {
+            // Patrol back and forth along the axis.
+            (Vector3::new(0., 0., 0.), axis * radius)
+        };
+        let speed = MINIMUM_SPEED + (MAXIMUM_SPEED - MINIMUM_SPEED) * random.samplef();
+        let phase = std::f32::consts::TAU * random.samplef();
+        self.hazards[self.count as usize] = Hazard {
+            centre: [center.x, center.y, center.z, HAZARD_SIZE * half_size],
+            a: [a.x, a.y, a.z, speed],
+            b: [b.x, b.y, b.z, phase],
+        };
+        self.count += 1;
+        true
+    }
+
+    // Whether a point is inside any hazard at the given time.
+    pub fn collides(&self, point: Vector3<f32>, time: f32) -> bool {
+        self.hazards[..self.count as usize]
+            .iter()
+            .any(|hazard| hazard.contains(point, time))
+    }
+}
@@ -18,25 +18,34 @@
 
 use std::sync::Arc;
 
-use smallvec::smallvec;
+use egui_winit_vulkano::{Gui, GuiConfig};
+use smallvec::{smallvec, SmallVec};
 use vulkano::{
+    buffer::{
+        allocator::{SubbufferAllocator, SubbufferAllocatorCreateInfo},
+        Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer,
+    },
     command_buffer::{
         allocator::{StandardCommandBufferAllocator, StandardCommandBufferAllocatorCreateInfo},
-        AutoCommandBufferBuilder, CommandBufferInheritanceInfo, CommandBufferUsage,
-        RenderPassBeginInfo, SecondaryAutoCommandBuffer, SubpassBeginInfo, SubpassContents,
-        SubpassEndInfo,
+        AutoCommandBufferBuilder, CommandBufferInheritanceInfo, CommandBufferUsage, CopyBufferInfo,
+        CopyImageToBufferInfo, SecondaryAutoCommandBuffer,
     },
     descriptor_set::{
         allocator::{StandardDescriptorSetAllocator, StandardDescriptorSetAllocatorCreateInfo},
-        PersistentDescriptorSet,
+        PersistentDescriptorSet, WriteDescriptorSet,
     },
-    device::{Device, Queue},
+    device::{Device, DeviceOwned, Queue},
     format::Format,
-    image::{view::ImageView, SampleCount},
-    memory::allocator::StandardMemoryAllocator,
+    image::{
+        sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+        view::ImageView,
+        Image, ImageCreateInfo, ImageType, ImageUsage, SampleCount,
+    },
+    memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator},
     pipeline::{
+        cache::{PipelineCache, PipelineCacheCreateInfo},
         graphics::{
-            color_blend::{ColorBlendAttachmentState, ColorBlendState},
+            color_blend::{AttachmentBlend, ColorBlendAttachmentState, ColorBlendState},
             input_assembly::{InputAssemblyState, PrimitiveTopology},
             multisample::MultisampleState,
             rasterization::RasterizationState,
@@ -48,26 +57,638 @@ use vulkano::{
         GraphicsPipeline, Pipeline, PipelineBindPoint, PipelineLayout,
         PipelineShaderStageCreateInfo,
     },
-    render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass},
-    sync::GpuFuture,
+    render_pass::{RenderPass, Subpass},
+    shader::EntryPoint,
+    sync::{self, future::FenceSignalFuture, GpuFuture, Sharing},
+    DeviceSize,
+};
+#[cfg(feature = "xr")]
+use vulkano::{
+    device::DeviceExtensions, image::sys::RawImage, instance::InstanceExtensions, Handle,
+    VulkanObject,
+};
+use vulkano_util::{
+    context::{VulkanoConfig, VulkanoContext},
+    renderer::VulkanoWindowRenderer,
+    window::{VulkanoWindows, WindowDescriptor},
+};
+use winit::{
+    event::WindowEvent,
+    event_loop::{EventLoop, EventLoopWindowTarget},
+    window::{Window, WindowId},
+};
+
+use crate::frame_graph::FrameGraph;
+use crate::hazards::WorldHazards;
+use crate::lighting::WorldLights;
+use crate::particles::ParticleInstance;
+use crate::renderer::{
+    self, CapturedFrame, EyeView, FrameUniforms, MapUniforms, PixelOrder, Renderer, SceneHistory,
 };
-use vulkano_util::renderer::VulkanoWindowRenderer;
+use crate::trail::TrailVertex;
+use crate::voxels::VoxelCompact;
+
+const VOXEL_BUFFER_COUNT: usize = 2;
+const CAPTURE_BUFFER_COUNT: usize = 3;
+const PIPELINE_CACHE_FILE_NAME: &str = "pipeline_cache.bin";
 
 pub struct Allocators {
     pub memory: Arc<StandardMemoryAllocator>,
     pub command_buffer: StandardCommandBufferAllocator,
     pub descriptor_set: StandardDescriptorSetAllocator,
+    pub storage_buffer: SubbufferAllocator,
+    pub voxel_buffer: VoxelBufferPool,
+}
+
+// Device-local buffers for the voxel-octree which are reused by each new world, and only reallocated
+// when a world doesn't fit. Worlds are copied in from a staging buffer on the transfer queue.
+// Two buffers are kept so that a new world can be uploaded while frames in flight still read the old one.
+pub struct VoxelBufferPool {
+    current: usize,
+    queue_family_indices: SmallVec<[u32; 4]>,
+    slots: [Option<VoxelBufferSlot>; VOXEL_BUFFER_COUNT],
+    transfer_queue: Arc<Queue>,
+}
+
+// A voxel-octree buffer, the lights and hazards of its world, their descriptor set, and the most recent frame to
+// read them.
+struct VoxelBufferSlot {
+    buffer: Subbuffer<[VoxelCompact]>,
+    lights: Subbuffer<WorldLights>,
+    hazards: Subbuffer<WorldHazards>,
+    descriptor_set: Arc<PersistentDescriptorSet>,
+    last_frame: Option<Arc<FenceSignalFuture<Box<dyn GpuFuture>>>>,
+}
+
+// Host-visible buffers that rendered frames are copied into when capturing. Each frame is read back once
+// its copy has finished, so that capturing doesn't stall rendering.
+struct FrameCapture {
+    enabled: bool,
+    next: usize,
+    order: Option<PixelOrder>,
+    ready: Vec<CapturedFrame>,
+    slots: [Option<CaptureSlot>; CAPTURE_BUFFER_COUNT],
+}
+
+// A capture buffer, the size of the frame copied into it, and the frame waiting to be read back.
+struct CaptureSlot {
+    buffer: Subbuffer<[u8]>,
+    extent: [u32; 2],
+    frame: Option<Arc<FenceSignalFuture<Box<dyn GpuFuture>>>>,
+}
+
+// The image that the scene is rendered to before the speed-lines pass samples it, and the descriptor set
+// binding it. The image may be smaller than the window when rendering at a reduced resolution.
+// The distance to the surface seen by each pixel is rendered alongside, and both are bound by the history
+// descriptor set for the next frame to reproject.
+#[derive(Clone)]
+struct SceneTarget {
+    view: Arc<ImageView>,
+    distance_view: Arc<ImageView>,
+    descriptor_set: Arc<PersistentDescriptorSet>,
+    history_descriptor_set: Arc<PersistentDescriptorSet>,
+}
+
+// The images that half of the scene is ray-marched into when rendering in a checkerboard pattern, and the
+// descriptor set binding them for the resolve pass to fill the scene from.
+#[derive(Clone)]
+struct CheckerboardTarget {
+    view: Arc<ImageView>,
+    distance_view: Arc<ImageView>,
+    descriptor_set: Arc<PersistentDescriptorSet>,
+}
+
+// The render pass and pipeline drawing a top-down map of the octree, into the map window or the minimap, and the
+// descriptor set binding the voxel-octree it reads, which is recreated for each new world.
+struct MapRenderer {
+    render_pass: Arc<RenderPass>,
+    pipeline: AppPipeline,
+    descriptor_set: Option<Arc<PersistentDescriptorSet>>,
+}
+
+// The image the minimap is ray-marched into for the overlay to show, and whether it is due to be redrawn.
+struct Minimap {
+    map: MapRenderer,
+    view: Arc<ImageView>,
+    redraw: bool,
+}
+
+// The session with a VR headset, and the targets each eye is rendered with.
+#[cfg(feature = "xr")]
+struct HeadsetRenderer {
+    session: crate::xr::Session,
+    eyes: [EyeTarget; 2],
+}
+
+// The images of a headset eye's swapchain, and the pipelines and scene images which render into them at the eye's
+// size. Eyes are ray-marched in full without reprojecting, so their history is a placeholder.
+#[cfg(feature = "xr")]
+struct EyeTarget {
+    images: Vec<Arc<ImageView>>,
+    app_pipeline: AppPipeline,
+    speed_lines_pipeline: AppPipeline,
+    scene: SceneTarget,
+    history: SceneTarget,
+    multisampled: Option<Arc<ImageView>>,
 }
 
 pub struct Engine {
     allocators: Allocators,
     app_renderer: RenderAppWithOverlay,
+    capture: FrameCapture,
+    checkerboard: bool,
+    checkerboard_target: Option<CheckerboardTarget>,
+    frame: u32,
+    history: Option<SceneHistory>,
+    map: Option<MapRenderer>,
+    minimap: Option<Minimap>,
+    multisample_target: Option<Arc<ImageView>>,
+    particles: Option<Subbuffer<[ParticleInstance]>>,
+    pending_upload: Option<Box<dyn GpuFuture>>,
+    pipeline_cache: Arc<PipelineCache>,
+    render_scale: f32,
+    scenes: [Option<SceneTarget>; 2],
+    trail: Option<Subbuffer<[TrailVertex]>>,
+    viewport: Viewport,
+}
+
+// Vulkan implementation of the app renderer, drawing the overlay with `egui_winit_vulkano`.
+pub struct VulkanRenderer {
+    context: VulkanoContext,
+    engine: Engine,
+    gpu_names: Vec<String>,
+    gui: Gui,
+    gui_active: bool,
+    #[cfg(feature = "xr")]
+    headset: Option<HeadsetRenderer>,
+    map_window: Option<WindowId>,
+    minimap_texture: Option<egui::TextureId>,
+    windows: VulkanoWindows,
+}
+
+impl VulkanRenderer {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        event_loop: &EventLoop<()>,
+        title: &str,
+        octree: &[VoxelCompact],
+        lights: &WorldLights,
+        hazards: &WorldHazards,
+        gpu: Option<&str>,
+        samples: u32,
+        vr: bool,
+    ) -> Self {
+        // Rank the requested GPU above all others, otherwise keep Vulkano's preference for discrete GPUs.
+        let mut config = VulkanoConfig::default();
+        if let Some(gpu) = gpu {
+            let gpu = gpu.to_string();
+            let default_priority = config.device_priority_fn.clone();
+            config.device_priority_fn = Arc::new(move |device| {
+                if device.properties().device_name == gpu {
+                    0
+                } else {
+                    default_priority(device) + 1
+                }
+            });
+        }
+        let device_filter = config.device_filter_fn.clone();
+
+        // Find the VR headset before creating Vulkan, which must have the extensions the headset needs and run on the
+        // GPU it is plugged into.
+        #[cfg(feature = "xr")]
+        let headset = vr.then(crate::xr::Headset::new).and_then(|headset| {
+            headset
+                .map_err(|e| log::error!(target: "xr", "Failed to find a VR headset: {e}"))
+                .ok()
+        });
+        #[cfg(feature = "xr")]
+        if let Some(headset) = &headset {
+            let instance_extensions = headset.instance_extensions();
+            config.instance_create_info.enabled_extensions =
+                config.instance_create_info.enabled_extensions.union(
+                    &InstanceExtensions::from_iter(instance_extensions.iter().map(String::as_str)),
+                );
+            let device_extensions = headset.device_extensions();
+            config.device_extensions = config.device_extensions.union(
+                &DeviceExtensions::from_iter(device_extensions.iter().map(String::as_str)),
+            );
+            let headset = headset.clone();
+            let default_filter = config.device_filter_fn.clone();
+            config.device_filter_fn = Arc::new(move |device| {
+                default_filter(device)
+                    && headset.is_graphics_device(
+                        device.instance().handle().as_raw(),
+                        device.handle().as_raw(),
+                    )
+            });
+        }
+        #[cfg(not(feature = "xr"))]
+        if vr {
+            log::error!(target: "xr", "This build doesn't include VR, build with the xr feature to use a headset");
+        }
+
+        // Get Vulkano context.
+        let context = VulkanoContext::new(config);
+        log::info!(target: "gpu", "Using device name=\"{}\"", context.device_name());
+        crate::crash::set_device_name(context.device_name());
+        if let Some(gpu) = gpu.filter(|&gpu| gpu != context.device_name()) {
+            log::warn!(target: "gpu", "Requested GPU is unavailable name=\"{gpu}\"");
+        }
+
+        // List the devices that could have been chosen.
+        let gpu_names = context
+            .instance()
+            .enumerate_physical_devices()
+            .map(|devices| {
+                devices
+                    .filter(|device| device_filter(device))
+                    .map(|device| device.properties().device_name.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Vulkano windows (create one).
+        let mut windows = VulkanoWindows::default();
+        windows.create_window(
+            event_loop,
+            &context,
+            &WindowDescriptor {
+                title: title.to_string(),
+                present_mode: vulkano::swapchain::PresentMode::Mailbox,
+                ..WindowDescriptor::default()
+            },
+            // Allow frames to be copied out of the swapchain for capture.
+            |info| info.image_usage |= ImageUsage::TRANSFER_SRC,
+        );
+        let renderer = windows.get_primary_renderer().unwrap();
+
+        // Get the image format that will be used by the swapchain and is acceptable for the window surface.
+        let image_format = renderer.swapchain_format();
+
+        // Initialize standalone engine.
+        // Vulkano's context doesn't create a dedicated transfer queue, so uploads use its compute queue,
+        // which runs asynchronously to rendering on devices with a separate compute family.
+        let mut engine = Engine::new(
+            renderer.graphics_queue(),
+            context.compute_queue().clone(),
+            image_format,
+            supported_sample_count(context.device(), samples),
+            Viewport {
+                offset: [0.; 2],
+                extent: renderer.window_size(),
+                depth_range: 0.0..=1.,
+            },
+        );
+
+        // Create GUI manager that will render as a subpass of our render pass, with as many samples as the frame.
+        let gui = Gui::new_with_subpass(
+            event_loop,
+            renderer.surface(),
+            renderer.graphics_queue(),
+            engine.gui_pass(),
+            image_format,
+            GuiConfig::default(),
+        );
+
+        // Initialize storage buffer with the voxel-octree data.
+        engine.upload_voxels(octree, lights, hazards);
+
+        // Start the headset's session, rendering each eye in the window's format so that the same passes draw both.
+        #[cfg(feature = "xr")]
+        let headset = headset.and_then(|headset| {
+            HeadsetRenderer::new(
+                headset,
+                &context,
+                &engine,
+                &renderer.graphics_queue(),
+                image_format,
+            )
+        });
+
+        VulkanRenderer {
+            context,
+            engine,
+            gpu_names,
+            gui,
+            gui_active: false,
+            #[cfg(feature = "xr")]
+            headset,
+            map_window: None,
+            minimap_texture: None,
+            windows,
+        }
+    }
+}
+
+impl Renderer for VulkanRenderer {
+    fn window(&self) -> &Window {
+        self.windows.get_primary_window().unwrap()
+    }
+
+    fn gpu_names(&self) -> &[String] {
+        &self.gpu_names
+    }
+
+    fn window_size(&self) -> [f32; 2] {
+        self.windows.get_primary_renderer().unwrap().window_size()
+    }
+
+    fn resize(&mut self) {
+        // Notify the window manager to recreate the swapchain next draw.
+        let renderer = self.windows.get_primary_renderer_mut().unwrap();
+        renderer.resize();
+
+        // Recreate the pipeline with the new viewport.
+        self.engine.recreate_pipeline(Viewport {
+            offset: [0.; 2],
+            extent: renderer.window_size(),
+            depth_range: 0.0..=1.,
+        });
+    }
+
+    fn update_gui(&mut self, event: &WindowEvent) -> bool {
+        self.gui.update(event)
+    }
+
+    fn begin_gui(&mut self) -> egui::Context {
+        self.gui.immediate_ui(|_| {});
+        self.gui_active = true;
+        self.gui.context()
+    }
+
+    fn upload_world(
+        &mut self,
+        octree: &[VoxelCompact],
+        lights: &WorldLights,
+        hazards: &WorldHazards,
+    ) {
+        self.engine.upload_voxels(octree, lights, hazards);
+    }
+
+    fn update_voxels(&mut self, first: usize, voxels: &[VoxelCompact]) {
+        self.engine.update_voxels(first, voxels);
+    }
+
+    fn upload_particles(&mut self, particles: &[ParticleInstance]) {
+        self.engine.upload_particles(particles);
+    }
+
+    fn upload_trail(&mut self, trail: &[TrailVertex]) {
+        self.engine.upload_trail(trail);
+    }
+
+    fn set_render_scale(&mut self, scale: f32) {
+        self.engine.set_render_scale(scale);
+    }
+
+    fn set_frame_capture(&mut self, enabled: bool) -> bool {
+        self.engine.set_frame_capture(enabled)
+    }
+
+    fn take_captured_frames(&mut self) -> Vec<CapturedFrame> {
+        self.engine.capture.take()
+    }
+
+    fn save_pipeline_cache(&self) {
+        self.engine.save_pipeline_cache();
+    }
+
+    fn render_offscreen(
+        &mut self,
+        uniforms: &FrameUniforms,
+        size: [u32; 2],
+    ) -> Option<CapturedFrame> {
+        self.engine.render_offscreen(uniforms, size)
+    }
+
+    fn render_frame(&mut self, uniforms: &FrameUniforms) {
+        let renderer = self.windows.get_primary_renderer_mut().unwrap();
+
+        // Get secondary command buffer for rendering GUI, if the overlay was updated this frame.
+        let gui_command_buffer = std::mem::take(&mut self.gui_active).then(|| {
+            self.gui
+                .draw_on_subpass_image(renderer.swapchain_image_size())
+        });
+
+        // Render main app with overlay from GUI.
+        let after_future = self
+            .engine
+            .render_frame(renderer, gui_command_buffer, uniforms);
+
+        // Present swapchain without waiting, so that the CPU can prepare the next frame while this one renders.
+        renderer.present(after_future, false);
+
+        // Draw the map after the frame, which has waited on any new world.
+        if let Some(renderer) = self
+            .map_window
+            .and_then(|id| self.windows.get_renderer_mut(id))
+        {
+            self.engine.render_map(renderer, uniforms);
+        }
+    }
+
+    fn set_map_window(
+        &mut self,
+        window_target: &EventLoopWindowTarget<()>,
+        title: Option<&str>,
+    ) -> bool {
+        match (title, self.map_window) {
+            (Some(title), None) => {
+                let id = self.windows.create_window(
+                    window_target,
+                    &self.context,
+                    &WindowDescriptor {
+                        title: title.to_string(),
+                        width: 512.,
+                        height: 512.,
+                        present_mode: vulkano::swapchain::PresentMode::Mailbox,
+                        ..WindowDescriptor::default()
+                    },
+                    |_| {},
+                );
+                let renderer = self.windows.get_renderer(id).unwrap();
+                self.engine.open_map(
+                    renderer.swapchain_format(),
+                    Viewport {
+                        offset: [0.; 2],
+                        extent: renderer.window_size(),
+                        depth_range: 0.0..=1.,
+                    },
+                );
+                self.map_window = Some(id);
+            }
+            (None, Some(id)) => {
+                self.engine.close_map();
+                self.windows.remove_renderer(id);
+                self.map_window = None;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn set_multisampling(
+        &mut self,
+        window_target: &EventLoopWindowTarget<()>,
+        samples: u32,
+    ) -> bool {
+        let samples = supported_sample_count(self.context.device(), samples);
+        if samples == self.engine.app_renderer.samples {
+            return true;
+        }
+        self.engine.set_samples(samples);
+
+        // The overlay's pipeline is created for the render pass, so the overlay is created again, keeping its memory
+        // of windows and widgets. Its textures are registered again as they are next needed.
+        let renderer = self.windows.get_primary_renderer().unwrap();
+        let gui = Gui::new_with_subpass(
+            window_target,
+            renderer.surface(),
+            renderer.graphics_queue(),
+            self.engine.gui_pass(),
+            renderer.swapchain_format(),
+            GuiConfig::default(),
+        );
+        let memory = self.gui.context().memory(Clone::clone);
+        gui.context().memory_mut(|m| *m = memory);
+        self.gui = gui;
+        self.minimap_texture = None;
+
+        // Headset eyes draw with their own pipelines and multisampled images.
+        #[cfg(feature = "xr")]
+        if let Some(headset) = &mut self.headset {
+            let extent = headset.session.extent();
+            for eye in &mut headset.eyes {
+                *eye = self.engine.eye_target(extent, eye.images.clone());
+            }
+        }
+        true
+    }
+
+    fn is_map_window(&self, id: WindowId) -> bool {
+        self.map_window == Some(id)
+    }
+
+    fn minimap(&mut self, size: u32) -> Option<egui::TextureId> {
+        if let Some(view) = self.engine.prepare_minimap(size) {
+            // The minimap was recreated at a new size, so its texture is replaced.
+            if let Some(texture) = self.minimap_texture.take() {
+                self.gui.unregister_user_image(texture);
+            }
+            self.minimap_texture = Some(self.gui.register_user_image_view(
+                view,
+                SamplerCreateInfo {
+                    mag_filter: Filter::Linear,
+                    min_filter: Filter::Linear,
+                    address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                    ..SamplerCreateInfo::default()
+                },
+            ));
+        }
+        self.minimap_texture
+    }
+
+    fn resize_map(&mut self) {
+        let Some(renderer) = self
+            .map_window
+            .and_then(|id| self.windows.get_renderer_mut(id))
+        else {
+            return;
+        };
+        renderer.resize();
+        self.engine.resize_map(Viewport {
+            offset: [0.; 2],
+            extent: renderer.window_size(),
+            depth_range: 0.0..=1.,
+        });
+    }
+
+    #[cfg(feature = "xr")]
+    fn begin_xr_frame(&mut self) -> Option<[EyeView; 2]> {
+        let headset = self.headset.as_mut()?;
+        let views = headset.session.begin_frame();
+        if headset.session.has_ended() {
+            log::info!(target: "xr", "The VR session has ended");
+            self.headset = None;
+        }
+        views
+    }
+
+    #[cfg(not(feature = "xr"))]
+    fn begin_xr_frame(&mut self) -> Option<[EyeView; 2]> {
+        None
+    }
+
+    // Render each eye into the next image of its swapchain. The frame is still ended when an image can't be had, so
+    // that the headset can move on to the next.
+    #[cfg(feature = "xr")]
+    fn end_xr_frame(&mut self, eyes: &[FrameUniforms; 2]) {
+        let Some(headset) = &mut self.headset else {
+            return;
+        };
+        for (index, (target, uniforms)) in headset.eyes.iter().zip(eyes).enumerate() {
+            let Some(image) = headset.session.acquire_image(index) else {
+                continue;
+            };
+            self.engine
+                .render_eye(target, target.images[image].clone(), uniforms);
+            headset.session.release_image(index);
+        }
+        headset.session.end_frame();
+    }
+
+    #[cfg(not(feature = "xr"))]
+    fn end_xr_frame(&mut self, _eyes: &[FrameUniforms; 2]) {}
+}
+
+#[cfg(feature = "xr")]
+impl HeadsetRenderer {
+    // Start a session with the headset on the window's device and graphics queue, wrapping the images of each eye's
+    // swapchain to be rendered into.
+    fn new(
+        headset: crate::xr::Headset,
+        context: &VulkanoContext,
+        engine: &Engine,
+        queue: &Arc<Queue>,
+        format: Format,
+    ) -> Option<Self> {
+        let device = context.device();
+        let handles = crate::xr::VulkanHandles {
+            instance: context.instance().handle().as_raw(),
+            physical_device: device.physical_device().handle().as_raw(),
+            device: device.handle().as_raw(),
+            queue_family_index: queue.queue_family_index(),
+            queue_index: queue.id_within_family(),
+        };
+        // Safety: the session is dropped with the renderer, before the Vulkano context it renders with.
+        let session = unsafe { headset.start(&handles, format as u32) }
+            .map_err(|e| log::error!(target: "xr", "Failed to start the VR session: {e}"))
+            .ok()?;
+        let extent = session.extent();
+        let eyes = [0, 1].map(|eye| {
+            let images = session
+                .images(eye)
+                .iter()
+                .map(|&handle| headset_image(device, handle, format, extent))
+                .collect();
+            engine.eye_target(extent, images)
+        });
+        log::info!(target: "xr", "Rendering to VR headset width={} height={}", extent[0], extent[1]);
+        Some(Self { session, eyes })
+    }
 }
 
 impl Engine {
-    pub fn new(queue: Arc<Queue>, image_format: Format, viewport: Viewport) -> Self {
+    pub fn new(
+        queue: Arc<Queue>,
+        transfer_queue: Arc<Queue>,
+        image_format: Format,
+        samples: SampleCount,
+        viewport: Viewport,
+    ) -> Self {
+        let memory = Arc::new(StandardMemoryAllocator::new_default(queue.device().clone()));
         let allocators = Allocators {
-            memory: Arc::new(StandardMemoryAllocator::new_default(queue.device().clone())),
+            memory: memory.clone(),
             command_buffer: StandardCommandBufferAllocator::new(
                 queue.device().clone(),
                 StandardCommandBufferAllocatorCreateInfo {
@@ -79,34 +700,768 @@ impl Engine {
                 queue.device().clone(),
                 StandardDescriptorSetAllocatorCreateInfo::default(),
             ),
+
+            // Particles and the trail are written to new buffers each frame, carved out of arenas which are reused
+            // once the frames reading them have finished.
+            storage_buffer: SubbufferAllocator::new(
+                memory,
+                SubbufferAllocatorCreateInfo {
+                    buffer_usage: BufferUsage::STORAGE_BUFFER,
+                    memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                        | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                    ..SubbufferAllocatorCreateInfo::default()
+                },
+            ),
+            voxel_buffer: VoxelBufferPool::new(&queue, transfer_queue),
         };
 
-        let render_pass = RenderAppWithOverlay::new(queue, image_format, viewport);
+        let pipeline_cache = load_pipeline_cache(queue.device());
+        let render_pass = RenderAppWithOverlay::new(
+            queue,
+            &pipeline_cache,
+            image_format,
+            samples,
+            viewport.clone(),
+        );
 
         Engine {
             allocators,
             app_renderer: render_pass,
+            capture: FrameCapture::new(image_format),
+            checkerboard: false,
+            checkerboard_target: None,
+            frame: 0,
+            history: None,
+            map: None,
+            minimap: None,
+            multisample_target: None,
+            particles: None,
+            pending_upload: None,
+            pipeline_cache,
+            render_scale: 1.,
+            scenes: [None, None],
+            trail: None,
+            viewport,
         }
     }
 
+    // Write the compiled pipelines to the app data directory, so the next launch can skip compiling them again.
+    pub fn save_pipeline_cache(&self) {
+        let path = crate::voxel_flight_simulator::app_data_dir().join(PIPELINE_CACHE_FILE_NAME);
+        let temp_path = path.with_extension("bin.tmp");
+        let result = self
+            .pipeline_cache
+            .get_data()
+            .map_err(std::io::Error::other)
+            .and_then(|data| std::fs::write(&temp_path, data))
+            .and_then(|()| std::fs::rename(&temp_path, &path));
+        if let Err(e) = result {
+            log::warn!(target: "gpu", "Failed to save pipeline cache path=\"{}\": {e}", path.display());
+        }
+    }
+
+    // Start or stop copying rendered frames for capture, which needs a swapchain format of 8-bit pixels.
+    pub fn set_frame_capture(&mut self, enabled: bool) -> bool {
+        if enabled && self.capture.order.is_none() {
+            return false;
+        }
+        self.capture.enabled = enabled;
+        true
+    }
+
     pub fn render_frame(
         &mut self,
         renderer: &mut VulkanoWindowRenderer,
         gui_command_buffer: Option<Arc<SecondaryAutoCommandBuffer>>,
-        push_constants: ray_march_voxels_fs::Push,
-        descriptor_set: Arc<PersistentDescriptorSet>,
+        uniforms: &FrameUniforms,
     ) -> Box<dyn GpuFuture> {
-        // Acquire swapchain future.
-        let before_future = renderer.acquire().unwrap();
+        // Acquire swapchain future, waiting on any voxel upload before rendering.
+        let mut before_future = renderer.acquire().unwrap();
+        if let Some(upload) = self.pending_upload.take() {
+            before_future = before_future.join(upload).boxed();
+        }
+
+        let capture_buffer = self
+            .capture
+            .begin(&self.allocators.memory, renderer.swapchain_image_size());
+        let [scene, previous] = self.scene_targets(self.scene_extent());
+        let uniforms = uniforms.reprojecting(self.history, self.frame);
+        self.history = Some(uniforms.history());
+        self.frame = self.frame.wrapping_add(1);
+
+        // Ray-march half as many pixels when rendering in a checkerboard pattern.
+        if uniforms.checkerboard() != self.checkerboard {
+            self.checkerboard = uniforms.checkerboard();
+            self.recreate_app_pipeline();
+        }
+        let checkerboard = self
+            .checkerboard
+            .then(|| self.checkerboard_target(self.march_extent()));
+
+        // Draw this frame's trail and particles over the scene, if any were uploaded.
+        let trail_command_buffer = self.trail.take().map(|buffer| {
+            self.app_renderer.trail_pipeline.draw(
+                &self.allocators.command_buffer,
+                &self.allocators.descriptor_set,
+                &self.app_renderer.queue,
+                trail_vs::Push::from(&uniforms),
+                buffer,
+            )
+        });
+        let particles_command_buffer = self.particles.take().map(|buffer| {
+            self.app_renderer.particles_pipeline.draw(
+                &self.allocators.command_buffer,
+                &self.allocators.descriptor_set,
+                &self.app_renderer.queue,
+                particles_vs::Push::from(&uniforms),
+                buffer,
+            )
+        });
 
-        self.app_renderer.render(
+        let slot = self.allocators.voxel_buffer.current_slot();
+
+        // Ray-march the minimap before the scene when it is due, so that the overlay over this frame shows it.
+        let mut graph = FrameGraph::default();
+        if let Some(minimap) = self.minimap.as_mut().filter(|minimap| minimap.redraw) {
+            minimap.redraw = false;
+            let command_buffer = minimap.map.draw(
+                &self.allocators.command_buffer,
+                &self.allocators.descriptor_set,
+                &self.app_renderer.queue,
+                slot.buffer.clone(),
+                &MapUniforms::minimap(&uniforms),
+            );
+            graph
+                .pass(
+                    "minimap",
+                    minimap.map.render_pass.clone(),
+                    vec![minimap.view.clone()],
+                )
+                .draw(0, command_buffer);
+        }
+
+        let multisampled = self.multisample_target(renderer.swapchain_image_size());
+        let after_future = self.app_renderer.render(
+            [
+                &self.app_renderer.app_pipeline,
+                &self.app_renderer.speed_lines_pipeline,
+            ],
             &self.allocators.command_buffer,
             before_future,
+            graph,
             renderer.swapchain_image_view(),
+            multisampled,
+            [&scene, &previous],
+            checkerboard
+                .as_ref()
+                .map(|target| (&self.app_renderer.resolve_pipeline, target)),
+            trail_command_buffer
+                .into_iter()
+                .chain(particles_command_buffer)
+                .collect(),
             gui_command_buffer,
-            push_constants,
-            descriptor_set,
+            &uniforms,
+            slot.descriptor_set.clone(),
+            capture_buffer.clone(),
+        );
+
+        // Remember when the frame finishes so that its voxel buffer isn't overwritten while being read.
+        let frame = Arc::new(
+            after_future
+                .then_signal_fence_and_flush()
+                .expect("Failed to submit frame."),
+        );
+        slot.last_frame = Some(frame.clone());
+        if capture_buffer.is_some() {
+            self.capture.end(frame.clone());
+        }
+        frame.boxed()
+    }
+
+    // Create the pipeline drawing the octree map into a window with the given image format and viewport.
+    pub fn open_map(&mut self, image_format: Format, viewport: Viewport) {
+        self.map = Some(MapRenderer::new(
+            &self.pipeline_cache,
+            image_format,
+            viewport,
+        ));
+    }
+
+    pub fn close_map(&mut self) {
+        self.map = None;
+    }
+
+    // Recreate the map pipeline given the map window's new viewport.
+    pub fn resize_map(&mut self, viewport: Viewport) {
+        if let Some(map) = &mut self.map {
+            map.pipeline = AppPipeline::octree_map(
+                &self.pipeline_cache,
+                map.pipeline.subpass.clone(),
+                viewport,
+            );
+        }
+    }
+
+    // Get the view of the minimap's image, unless it already exists at the given size, and schedule it to be
+    // ray-marched with the next frame when it is new or due to be redrawn.
+    fn prepare_minimap(&mut self, size: u32) -> Option<Arc<ImageView>> {
+        if let Some(minimap) = &mut self.minimap {
+            if minimap.view.image().extent()[..2] == [size; 2] {
+                minimap.redraw |= self.frame % renderer::MINIMAP_FRAME_INTERVAL == 0;
+                return None;
+            }
+        }
+        let image = Image::new(
+            self.allocators.memory.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: self.app_renderer.image_format,
+                extent: [size, size, 1],
+                usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED,
+                ..ImageCreateInfo::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .expect("Failed to create minimap image.");
+        let view = ImageView::new_default(image).unwrap();
+        self.minimap = Some(Minimap {
+            map: MapRenderer::new(
+                &self.pipeline_cache,
+                self.app_renderer.image_format,
+                extent_viewport([size; 2]),
+            ),
+            view: view.clone(),
+            redraw: true,
+        });
+        Some(view)
+    }
+
+    // Draw and present the octree map with the camera on it, after the latest frame to read the voxel buffer.
+    pub fn render_map(&mut self, renderer: &mut VulkanoWindowRenderer, uniforms: &FrameUniforms) {
+        let Some(map) = &mut self.map else {
+            return;
+        };
+
+        // Skip drawing while the window is minimized.
+        let [width, height] = renderer.window_size();
+        if renderer.window_size().contains(&0.0f32) {
+            return;
+        }
+        let Ok(mut before_future) = renderer.acquire() else {
+            return;
+        };
+        let slot = self.allocators.voxel_buffer.current_slot();
+        if let Some(frame) = &slot.last_frame {
+            before_future = before_future.join(frame.clone()).boxed();
+        }
+
+        let queue = &self.app_renderer.queue;
+        let command_buffer = map.draw(
+            &self.allocators.command_buffer,
+            &self.allocators.descriptor_set,
+            queue,
+            slot.buffer.clone(),
+            &MapUniforms::window(uniforms, width / height),
+        );
+        let mut graph = FrameGraph::default();
+        graph
+            .pass(
+                "map",
+                map.render_pass.clone(),
+                vec![renderer.swapchain_image_view()],
+            )
+            .draw(0, command_buffer);
+        let command_buffer = graph
+            .record(&self.allocators.command_buffer, queue)
+            .build()
+            .unwrap();
+
+        // The map also reads the voxel buffer, so it must finish before the buffer is overwritten.
+        let frame = Arc::new(
+            before_future
+                .then_execute(queue.clone(), command_buffer)
+                .unwrap()
+                .boxed()
+                .then_signal_fence_and_flush()
+                .expect("Failed to submit map frame."),
+        );
+        slot.last_frame = Some(frame.clone());
+        renderer.present(frame.boxed(), false);
+    }
+
+    // Render a frame without the overlay to a new image, using pipelines with a viewport of that size,
+    // and wait for it to be copied back to host memory.
+    pub fn render_offscreen(
+        &mut self,
+        uniforms: &FrameUniforms,
+        size: [u32; 2],
+    ) -> Option<CapturedFrame> {
+        let order = self.capture.order?;
+        let queue = self.app_renderer.queue.clone();
+        let image = Image::new(
+            self.allocators.memory.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: self.app_renderer.image_format,
+                extent: [size[0], size[1], 1],
+                usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_SRC,
+                ..ImageCreateInfo::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .map_err(|e| log::error!(target: "gpu", "Failed to create offscreen image: {e}"))
+        .ok()?;
+        let buffer = Buffer::new_slice::<u8>(
+            self.allocators.memory.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..BufferCreateInfo::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+                ..AllocationCreateInfo::default()
+            },
+            DeviceSize::from(size[0]) * DeviceSize::from(size[1]) * 4,
+        )
+        .map_err(|e| log::error!(target: "gpu", "Failed to allocate offscreen buffer: {e}"))
+        .ok()?;
+
+        let viewport = extent_viewport(size);
+        let app_pipeline = AppPipeline::ray_march(
+            &self.pipeline_cache,
+            self.app_renderer.app_pipeline.subpass.clone(),
+            viewport.clone(),
+        );
+        let speed_lines_pipeline = AppPipeline::speed_lines(
+            &self.pipeline_cache,
+            self.app_renderer.speed_lines_pipeline.subpass.clone(),
+            viewport,
+        );
+        // Offscreen frames ray-march every pixel without reprojecting, so their history is a placeholder.
+        let pipelines = [&app_pipeline, &speed_lines_pipeline];
+        let scene = SceneTarget::new(&self.allocators, &self.app_renderer, pipelines, size);
+        let history = SceneTarget::new(&self.allocators, &self.app_renderer, pipelines, [1, 1]);
+        let uniforms = FrameUniforms {
+            temporal: 0,
+            ..*uniforms
+        };
+
+        // Wait on any voxel upload before rendering.
+        let mut before_future = sync::now(queue.device().clone()).boxed();
+        if let Some(upload) = self.pending_upload.take() {
+            before_future = before_future.join(upload).boxed();
+        }
+        let multisampled = self
+            .app_renderer
+            .multisample_view(&self.allocators.memory, size);
+        let slot = self.allocators.voxel_buffer.current_slot();
+        let after_future = self.app_renderer.render(
+            [&app_pipeline, &speed_lines_pipeline],
+            &self.allocators.command_buffer,
+            before_future,
+            FrameGraph::default(),
+            ImageView::new_default(image).unwrap(),
+            multisampled,
+            [&scene, &history],
+            None,
+            Vec::new(),
+            None,
+            &uniforms,
+            slot.descriptor_set.clone(),
+            Some(buffer.clone()),
+        );
+        let finished = after_future
+            .then_signal_fence_and_flush()
+            .map_err(|e| log::error!(target: "gpu", "Failed to submit offscreen frame: {e}"))
+            .ok()?
+            .wait(None);
+        if let Err(e) = finished {
+            log::error!(target: "gpu", "Failed to render offscreen frame: {e}");
+            return None;
+        }
+
+        let pixels = buffer.read().ok()?.to_vec();
+        Some(CapturedFrame {
+            width: size[0],
+            height: size[1],
+            order,
+            pixels,
+        })
+    }
+
+    // Create the pipelines and scene images which render a headset eye into the given images of its swapchain.
+    #[cfg(feature = "xr")]
+    fn eye_target(&self, extent: [u32; 2], images: Vec<Arc<ImageView>>) -> EyeTarget {
+        let viewport = extent_viewport(extent);
+        let app_pipeline = AppPipeline::ray_march(
+            &self.pipeline_cache,
+            self.app_renderer.app_pipeline.subpass.clone(),
+            viewport.clone(),
+        );
+        let speed_lines_pipeline = AppPipeline::speed_lines(
+            &self.pipeline_cache,
+            self.app_renderer.speed_lines_pipeline.subpass.clone(),
+            viewport,
+        );
+        let pipelines = [&app_pipeline, &speed_lines_pipeline];
+        let scene = SceneTarget::new(&self.allocators, &self.app_renderer, pipelines, extent);
+        let history = SceneTarget::new(&self.allocators, &self.app_renderer, pipelines, [1, 1]);
+        let multisampled = self
+            .app_renderer
+            .multisample_view(&self.allocators.memory, extent);
+        EyeTarget {
+            images,
+            app_pipeline,
+            speed_lines_pipeline,
+            scene,
+            history,
+            multisampled,
+        }
+    }
+
+    // Render a headset eye into one of its swapchain images, waiting for it to finish so that the image can be handed
+    // back to the headset. The trail and particles are only drawn in the window.
+    #[cfg(feature = "xr")]
+    fn render_eye(&mut self, eye: &EyeTarget, image: Arc<ImageView>, uniforms: &FrameUniforms) {
+        let queue = self.app_renderer.queue.clone();
+        let mut before_future = sync::now(queue.device().clone()).boxed();
+        if let Some(upload) = self.pending_upload.take() {
+            before_future = before_future.join(upload).boxed();
+        }
+        let slot = self.allocators.voxel_buffer.current_slot();
+        let after_future = self.app_renderer.render(
+            [&eye.app_pipeline, &eye.speed_lines_pipeline],
+            &self.allocators.command_buffer,
+            before_future,
+            FrameGraph::default(),
+            image,
+            eye.multisampled.clone(),
+            [&eye.scene, &eye.history],
+            None,
+            Vec::new(),
+            None,
+            uniforms,
+            slot.descriptor_set.clone(),
+            None,
+        );
+        let future = match after_future.then_signal_fence_and_flush() {
+            Ok(future) => future,
+            Err(e) => {
+                log::error!(target: "xr", "Failed to submit a VR eye: {e}");
+                return;
+            }
+        };
+        if let Err(e) = future.wait(None) {
+            log::error!(target: "xr", "Failed to render a VR eye: {e}");
+        }
+    }
+
+    // Copy a voxel-octree into the voxel buffer not used by the latest frames, which later frames then read.
+    // The buffer's descriptor set is only recreated when the buffer had to be reallocated to fit the octree.
+    // The world's lights and hazards are written directly to the slot's host-visible uniform buffers.
+    pub fn upload_voxels(
+        &mut self,
+        octree: &[VoxelCompact],
+        lights: &WorldLights,
+        hazards: &WorldHazards,
+    ) {
+        let voxel_count = octree.len() as DeviceSize;
+        let index = self.allocators.voxel_buffer.next_slot_index();
+        let buffer = match self.allocators.voxel_buffer.buffer(index, voxel_count) {
+            Some(buffer) => buffer,
+            None => {
+                let buffer = self
+                    .allocators
+                    .voxel_buffer
+                    .allocate(self.allocators.memory.clone(), voxel_count);
+                let lights = self.create_uniform_buffer();
+                let hazards = self.create_uniform_buffer();
+                let descriptor_set = self.create_voxel_descriptor_set(
+                    buffer.clone(),
+                    lights.clone(),
+                    hazards.clone(),
+                );
+                self.allocators.voxel_buffer.slots[index] = Some(VoxelBufferSlot {
+                    buffer: buffer.clone(),
+                    lights,
+                    hazards,
+                    descriptor_set,
+                    last_frame: None,
+                });
+                buffer
+            }
+        };
+        if let Some(slot) = &self.allocators.voxel_buffer.slots[index] {
+            *slot.lights.write().expect("Failed to write world lights.") = *lights;
+            *slot
+                .hazards
+                .write()
+                .expect("Failed to write world hazards.") = *hazards;
+        }
+
+        // Stage the octree in host-visible memory.
+        let staging_buffer = Buffer::from_iter(
+            self.allocators.memory.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            octree.iter().copied(),
         )
+        .expect("Failed to create voxel staging buffer.");
+
+        // Copy the octree on the transfer queue, after any upload the GPU hasn't used yet.
+        let transfer_queue = self.allocators.voxel_buffer.transfer_queue.clone();
+        let mut builder = AutoCommandBufferBuilder::primary(
+            &self.allocators.command_buffer,
+            transfer_queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        builder
+            .copy_buffer(CopyBufferInfo::buffers(
+                staging_buffer,
+                buffer.slice(..voxel_count),
+            ))
+            .unwrap();
+        let command_buffer = builder.build().unwrap();
+
+        // The next frame waits for the copy on the GPU instead of stalling here.
+        let previous_upload = self
+            .pending_upload
+            .take()
+            .unwrap_or_else(|| sync::now(transfer_queue.device().clone()).boxed());
+        let upload = previous_upload
+            .then_execute(transfer_queue, command_buffer)
+            .unwrap()
+            .then_signal_semaphore_and_flush()
+            .expect("Failed to upload voxel buffer.");
+        self.pending_upload = Some(upload.boxed());
+        self.allocators.voxel_buffer.current = index;
+
+        // The previous frame shows a different world, and the maps must read the new buffer.
+        self.history = None;
+        if let Some(map) = &mut self.map {
+            map.descriptor_set = None;
+        }
+        if let Some(minimap) = &mut self.minimap {
+            minimap.map.descriptor_set = None;
+        }
+    }
+
+    // Overwrite part of the voxel buffer that the latest frames read. Waits for the last frame that read it, since
+    // the change is too small to be worth writing the whole world to the other buffer.
+    pub fn update_voxels(&mut self, first: usize, voxels: &[VoxelCompact]) {
+        let slot = self.allocators.voxel_buffer.current_slot();
+        if let Some(frame) = slot.last_frame.take() {
+            frame.wait(None).expect("Failed to wait for frame.");
+        }
+        let buffer = slot.buffer.clone();
+
+        // Stage the voxels in host-visible memory.
+        let staging_buffer = Buffer::from_iter(
+            self.allocators.memory.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            voxels.iter().copied(),
+        )
+        .expect("Failed to create voxel staging buffer.");
+
+        // Copy the voxels on the transfer queue, after any upload the GPU hasn't used yet.
+        let transfer_queue = self.allocators.voxel_buffer.transfer_queue.clone();
+        let mut builder = AutoCommandBufferBuilder::primary(
+            &self.allocators.command_buffer,
+            transfer_queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        let first = first as DeviceSize;
+        builder
+            .copy_buffer(CopyBufferInfo::buffers(
+                staging_buffer,
+                buffer.slice(first..first + voxels.len() as DeviceSize),
+            ))
+            .unwrap();
+        let command_buffer = builder.build().unwrap();
+        let previous_upload = self
+            .pending_upload
+            .take()
+            .unwrap_or_else(|| sync::now(transfer_queue.device().clone()).boxed());
+        let upload = previous_upload
+            .then_execute(transfer_queue, command_buffer)
+            .unwrap()
+            .then_signal_semaphore_and_flush()
+            .expect("Failed to upload voxels.");
+        self.pending_upload = Some(upload.boxed());
+
+        // The minimap shows the change without waiting for its next redraw.
+        if let Some(minimap) = &mut self.minimap {
+            minimap.redraw = true;
+        }
+    }
+
+    // Write the particles to draw with the next frame to a new buffer. No particles are drawn when there are none.
+    pub fn upload_particles(&mut self, particles: &[ParticleInstance]) {
+        self.particles = self.write_storage_buffer(particles);
+    }
+
+    // Write the trail to draw with the next frame to a new buffer. It takes at least two points to draw a trail.
+    pub fn upload_trail(&mut self, trail: &[TrailVertex]) {
+        self.trail = if trail.len() < 2 {
+            None
+        } else {
+            self.write_storage_buffer(trail)
+        };
+    }
+
+    // Copy values into a new storage buffer for the next frame, or none if there are no values to copy.
+    fn write_storage_buffer<T: BufferContents + Copy>(
+        &self,
+        values: &[T],
+    ) -> Option<Subbuffer<[T]>> {
+        if values.is_empty() {
+            return None;
+        }
+        let buffer = self
+            .allocators
+            .storage_buffer
+            .allocate_slice(values.len() as DeviceSize)
+            .map_err(|e| log::error!(target: "gpu", "Failed to allocate storage buffer: {e}"))
+            .ok()?;
+        buffer
+            .write()
+            .expect("Failed to write storage buffer.")
+            .copy_from_slice(values);
+        Some(buffer)
+    }
+
+    // Create a host-visible uniform buffer for values of a world, such as its lights.
+    fn create_uniform_buffer<T: BufferContents>(&self) -> Subbuffer<T> {
+        Buffer::new_sized(
+            self.allocators.memory.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::UNIFORM_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+        )
+        .expect("Failed to create world uniform buffer.")
+    }
+
+    // Create a descriptor set binding the voxel-octree buffer and the lights and hazards of its world.
+    fn create_voxel_descriptor_set(
+        &self,
+        buffer: Subbuffer<[VoxelCompact]>,
+        lights: Subbuffer<WorldLights>,
+        hazards: Subbuffer<WorldHazards>,
+    ) -> Arc<PersistentDescriptorSet> {
+        PersistentDescriptorSet::new(
+            &self.allocators.descriptor_set,
+            self.pipeline()
+                .layout()
+                .set_layouts()
+                .get(0)
+                .unwrap()
+                .clone(),
+            [
+                WriteDescriptorSet::buffer(0, buffer),
+                WriteDescriptorSet::buffer(2, lights),
+                WriteDescriptorSet::buffer(3, hazards),
+            ],
+            [],
+        )
+        .expect("Failed to create voxel buffer descriptor set.")
+    }
+
+    // Get the image to render the scene to and the image holding the previous frame, which swap each frame.
+    // Both are recreated when the size of the frame changes, losing the history.
+    fn scene_targets(&mut self, extent: [u32; 2]) -> [SceneTarget; 2] {
+        self.scenes.swap(0, 1);
+        for target in &mut self.scenes {
+            if target
+                .as_ref()
+                .map_or(true, |scene| scene.view.image().extent()[..2] != extent)
+            {
+                self.history = None;
+                *target = Some(SceneTarget::new(
+                    &self.allocators,
+                    &self.app_renderer,
+                    [
+                        &self.app_renderer.app_pipeline,
+                        &self.app_renderer.speed_lines_pipeline,
+                    ],
+                    extent,
+                ));
+            }
+        }
+        self.scenes
+            .clone()
+            .map(|target| target.expect("Scene targets were just created."))
+    }
+
+    // Get the images half of the scene is ray-marched into, recreating them when the size of the frame changes.
+    fn checkerboard_target(&mut self, extent: [u32; 2]) -> CheckerboardTarget {
+        match &self.checkerboard_target {
+            Some(target) if target.view.image().extent()[..2] == extent => target.clone(),
+            _ => {
+                let target = CheckerboardTarget::new(
+                    &self.allocators,
+                    &self.app_renderer.resolve_pipeline,
+                    self.app_renderer.image_format,
+                    extent,
+                );
+                self.checkerboard_target = Some(target.clone());
+                target
+            }
+        }
+    }
+
+    // Get the multisampled image the frame is drawn into before it is resolved into the window's image,
+    // recreating it when the size of the frame changes. There is none without multisampling.
+    fn multisample_target(&mut self, extent: [u32; 2]) -> Option<Arc<ImageView>> {
+        match &self.multisample_target {
+            Some(view) if view.image().extent()[..2] == extent => Some(view.clone()),
+            _ => {
+                self.multisample_target = self
+                    .app_renderer
+                    .multisample_view(&self.allocators.memory, extent);
+                self.multisample_target.clone()
+            }
+        }
+    }
+
+    // Draw frames with a new number of samples, recreating the render pass and the pipelines drawn in it.
+    pub fn set_samples(&mut self, samples: SampleCount) {
+        let renderer = &mut self.app_renderer;
+        renderer.samples = samples;
+        renderer.render_pass = RenderAppWithOverlay::create_render_pass(
+            renderer.queue.device().clone(),
+            renderer.image_format,
+            samples,
+        );
+        let subpass = Subpass::from(renderer.render_pass.clone(), 0).unwrap();
+        renderer.speed_lines_pipeline.subpass = subpass.clone();
+        renderer.particles_pipeline.subpass = subpass.clone();
+        renderer.trail_pipeline.subpass = subpass;
+        self.multisample_target = None;
+        self.recreate_pipeline(self.viewport.clone());
     }
 
     // Get subpass for the GUI overlay.
@@ -114,19 +1469,69 @@ impl Engine {
         Subpass::from(self.render_pass().clone(), 1).unwrap()
     }
 
-    // Recreate the graphics pipeline given a new viewport.
+    // Recreate the graphics pipelines given a new viewport.
     pub fn recreate_pipeline(&mut self, viewport: Viewport) {
-        self.app_renderer.app_pipeline = AppPipeline::new(
-            self.app_renderer.queue.device(),
+        self.viewport = viewport;
+        self.recreate_app_pipeline();
+        self.app_renderer.speed_lines_pipeline = AppPipeline::speed_lines(
+            &self.pipeline_cache,
+            self.app_renderer.speed_lines_pipeline.subpass.clone(),
+            self.viewport.clone(),
+        );
+        self.app_renderer.particles_pipeline = StoragePipeline::particles(
+            &self.pipeline_cache,
+            self.app_renderer.particles_pipeline.subpass.clone(),
+            self.viewport.clone(),
+        );
+        self.app_renderer.trail_pipeline = StoragePipeline::trail(
+            &self.pipeline_cache,
+            self.app_renderer.trail_pipeline.subpass.clone(),
+            self.viewport.clone(),
+        );
+    }
+
+    // Ray-march the scene at a fraction of the window's resolution. The pipeline is only recreated when
+    // the scene's size in pixels changes.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        let previous = self.scene_extent();
+        self.render_scale = scale;
+        if self.scene_extent() != previous {
+            self.recreate_app_pipeline();
+        }
+    }
+
+    // Recreate the ray-marching pipeline with a viewport the size of the scene.
+    fn recreate_app_pipeline(&mut self) {
+        let cache = &self.pipeline_cache;
+        self.app_renderer.app_pipeline = AppPipeline::ray_march(
+            cache,
             self.app_renderer.app_pipeline.subpass.clone(),
-            viewport,
+            extent_viewport(self.march_extent()),
+        );
+        self.app_renderer.resolve_pipeline = AppPipeline::checkerboard_resolve(
+            cache,
+            self.app_renderer.resolve_pipeline.subpass.clone(),
+            extent_viewport(self.scene_extent()),
         );
     }
 
-    // Getters
-    pub fn allocators(&self) -> &Allocators {
-        &self.allocators
+    // The size in pixels of the scene.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn scene_extent(&self) -> [u32; 2] {
+        renderer::scaled_extent(self.viewport.extent.map(|x| x as u32), self.render_scale)
+    }
+
+    // The size in pixels that the scene is ray-marched at, which is half as wide when rendering in a checkerboard
+    // pattern.
+    fn march_extent(&self) -> [u32; 2] {
+        if self.checkerboard {
+            renderer::checkerboard_extent(self.scene_extent())
+        } else {
+            self.scene_extent()
+        }
     }
+
+    // Getters
     pub fn pipeline(&self) -> &Arc<GraphicsPipeline> {
         self.app_renderer.app_pipeline.pipeline()
     }
@@ -135,29 +1540,494 @@ impl Engine {
     }
 }
 
-/// A render pass which places an incoming image over frame filling it.
+impl MapRenderer {
+    fn new(cache: &Arc<PipelineCache>, format: Format, viewport: Viewport) -> Self {
+        let render_pass = vulkano::single_pass_renderpass!(
+            cache.device().clone(),
+            attachments: {
+                color: {
+                    format: format,
+                    samples: SampleCount::Sample1,
+                    load_op: Clear,
+                    store_op: Store,
+                }
+            },
+            pass: { color: [color], depth_stencil: {} }
+        )
+        .unwrap();
+        let subpass = Subpass::from(render_pass.clone(), 0).unwrap();
+        MapRenderer {
+            render_pass,
+            pipeline: AppPipeline::octree_map(cache, subpass, viewport),
+            descriptor_set: None,
+        }
+    }
+
+    // Record drawing the map of the voxel-octree in the given buffer, binding the buffer after a new world.
+    fn draw(
+        &mut self,
+        command_buffer_allocator: &StandardCommandBufferAllocator,
+        descriptor_set_allocator: &StandardDescriptorSetAllocator,
+        queue: &Arc<Queue>,
+        voxel_buffer: Subbuffer<[VoxelCompact]>,
+        uniforms: &MapUniforms,
+    ) -> Arc<SecondaryAutoCommandBuffer> {
+        let pipeline = &self.pipeline;
+        let descriptor_set = self.descriptor_set.get_or_insert_with(|| {
+            PersistentDescriptorSet::new(
+                descriptor_set_allocator,
+                pipeline
+                    .pipeline()
+                    .layout()
+                    .set_layouts()
+                    .get(0)
+                    .unwrap()
+                    .clone(),
+                [WriteDescriptorSet::buffer(0, voxel_buffer)],
+                [],
+            )
+            .expect("Failed to create map descriptor set.")
+        });
+        pipeline.draw(
+            command_buffer_allocator,
+            queue,
+            octree_map_fs::Push::from(uniforms),
+            vec![descriptor_set.clone()],
+        )
+    }
+}
+
+impl CheckerboardTarget {
+    fn new(
+        allocators: &Allocators,
+        resolve_pipeline: &AppPipeline,
+        format: Format,
+        extent: [u32; 2],
+    ) -> Self {
+        let create_view = |format| {
+            let image = Image::new(
+                allocators.memory.clone(),
+                ImageCreateInfo {
+                    image_type: ImageType::Dim2d,
+                    format,
+                    extent: [extent[0], extent[1], 1],
+                    usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED,
+                    ..ImageCreateInfo::default()
+                },
+                AllocationCreateInfo::default(),
+            )
+            .expect("Failed to create checkerboard image.");
+            ImageView::new_default(image).unwrap()
+        };
+        let view = create_view(format);
+        let distance_view = create_view(Format::R32_SFLOAT);
+        let descriptor_set = PersistentDescriptorSet::new(
+            &allocators.descriptor_set,
+            resolve_pipeline
+                .pipeline()
+                .layout()
+                .set_layouts()
+                .get(0)
+                .unwrap()
+                .clone(),
+            [
+                WriteDescriptorSet::image_view(0, view.clone()),
+                WriteDescriptorSet::image_view(1, distance_view.clone()),
+            ],
+            [],
+        )
+        .expect("Failed to create checkerboard descriptor set.");
+        CheckerboardTarget {
+            view,
+            distance_view,
+            descriptor_set,
+        }
+    }
+}
+
+impl SceneTarget {
+    fn new(
+        allocators: &Allocators,
+        app_renderer: &RenderAppWithOverlay,
+        [app_pipeline, speed_lines_pipeline]: [&AppPipeline; 2],
+        extent: [u32; 2],
+    ) -> Self {
+        let image = Image::new(
+            allocators.memory.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: app_renderer.image_format,
+                extent: [extent[0], extent[1], 1],
+                usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED,
+                ..ImageCreateInfo::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .expect("Failed to create scene image.");
+        let view = ImageView::new_default(image).unwrap();
+        let descriptor_set = PersistentDescriptorSet::new(
+            &allocators.descriptor_set,
+            speed_lines_pipeline
+                .pipeline()
+                .layout()
+                .set_layouts()
+                .get(0)
+                .unwrap()
+                .clone(),
+            [WriteDescriptorSet::image_view_sampler(
+                0,
+                view.clone(),
+                app_renderer.sampler.clone(),
+            )],
+            [],
+        )
+        .expect("Failed to create scene descriptor set.");
+
+        let distance_image = Image::new(
+            allocators.memory.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: Format::R32_SFLOAT,
+                extent: [extent[0], extent[1], 1],
+                usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED,
+                ..ImageCreateInfo::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .expect("Failed to create scene distance image.");
+        let distance_view = ImageView::new_default(distance_image).unwrap();
+        let history_descriptor_set = PersistentDescriptorSet::new(
+            &allocators.descriptor_set,
+            app_pipeline
+                .pipeline()
+                .layout()
+                .set_layouts()
+                .get(1)
+                .unwrap()
+                .clone(),
+            [
+                WriteDescriptorSet::image_view_sampler(
+                    0,
+                    view.clone(),
+                    app_renderer.history_sampler.clone(),
+                ),
+                WriteDescriptorSet::image_view_sampler(
+                    1,
+                    distance_view.clone(),
+                    app_renderer.history_sampler.clone(),
+                ),
+            ],
+            [],
+        )
+        .expect("Failed to create scene history descriptor set.");
+
+        SceneTarget {
+            view,
+            distance_view,
+            descriptor_set,
+            history_descriptor_set,
+        }
+    }
+}
+
+impl VoxelBufferPool {
+    fn new(graphics_queue: &Arc<Queue>, transfer_queue: Arc<Queue>) -> Self {
+        // The buffer is shared between queue families when transfers don't use the graphics family.
+        let mut queue_family_indices = smallvec![graphics_queue.queue_family_index()];
+        if transfer_queue.queue_family_index() != graphics_queue.queue_family_index() {
+            queue_family_indices.push(transfer_queue.queue_family_index());
+        }
+
+        VoxelBufferPool {
+            current: 0,
+            queue_family_indices,
+            slots: Default::default(),
+            transfer_queue,
+        }
+    }
+
+    // The slot read by the latest frames.
+    fn current_slot(&mut self) -> &mut VoxelBufferSlot {
+        self.slots[self.current]
+            .as_mut()
+            .expect("A world is uploaded before the first frame.")
+    }
+
+    // Get the index of the slot the next world should be written to, which the latest frames aren't reading.
+    // Waits for the last frame that read it, which has almost always finished already.
+    fn next_slot_index(&mut self) -> usize {
+        let index = (self.current + 1) % VOXEL_BUFFER_COUNT;
+        if let Some(frame) = self.slots[index]
+            .as_mut()
+            .and_then(|slot| slot.last_frame.take())
+        {
+            frame.wait(None).expect("Failed to wait for frame.");
+        }
+        index
+    }
+
+    // Get the buffer of a slot, if it exists and has room for `voxel_count` voxels.
+    fn buffer(&self, index: usize, voxel_count: DeviceSize) -> Option<Subbuffer<[VoxelCompact]>> {
+        self.slots[index]
+            .as_ref()
+            .map(|slot| slot.buffer.clone())
+            .filter(|buffer| buffer.len() >= voxel_count)
+    }
+
+    // Allocate a buffer with room for `voxel_count` voxels.
+    // Capacity grows in powers of two so that slightly larger worlds can reuse the buffer.
+    fn allocate(
+        &self,
+        memory: Arc<StandardMemoryAllocator>,
+        voxel_count: DeviceSize,
+    ) -> Subbuffer<[VoxelCompact]> {
+        let sharing = if self.queue_family_indices.len() > 1 {
+            Sharing::Concurrent(self.queue_family_indices.clone())
+        } else {
+            Sharing::Exclusive
+        };
+        let buffer = Buffer::new_slice(
+            memory,
+            BufferCreateInfo {
+                sharing,
+                usage: BufferUsage::STORAGE_BUFFER | BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                ..Default::default()
+            },
+            voxel_count.next_power_of_two(),
+        )
+        .expect("Failed to create voxel buffer.");
+        log::debug!(target: "gpu", "Allocated voxel buffer capacity={}", buffer.len());
+        buffer
+    }
+}
+
+impl FrameCapture {
+    fn new(image_format: Format) -> Self {
+        let order = match image_format {
+            Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB => Some(PixelOrder::Rgba),
+            Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB => Some(PixelOrder::Bgra),
+            _ => None,
+        };
+        FrameCapture {
+            enabled: false,
+            next: 0,
+            order,
+            ready: Vec::new(),
+            slots: Default::default(),
+        }
+    }
+
+    // Get the buffer to copy the next frame into, if capturing. A frame still waiting in the buffer is read
+    // back first, and the buffer is reallocated when the frame size has changed.
+    fn begin(
+        &mut self,
+        memory: &Arc<StandardMemoryAllocator>,
+        extent: [u32; 2],
+    ) -> Option<Subbuffer<[u8]>> {
+        if !self.enabled {
+            return None;
+        }
+        let order = self.order?;
+        let slot = &mut self.slots[self.next];
+        if let Some(frame) = slot.as_mut().and_then(|slot| slot.read(order, true)) {
+            self.ready.push(frame);
+        }
+        if slot.as_ref().map_or(true, |slot| slot.extent != extent) {
+            let buffer = Buffer::new_slice::<u8>(
+                memory.clone(),
+                BufferCreateInfo {
+                    usage: BufferUsage::TRANSFER_DST,
+                    ..BufferCreateInfo::default()
+                },
+                AllocationCreateInfo {
+                    memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                        | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+                    ..AllocationCreateInfo::default()
+                },
+                DeviceSize::from(extent[0]) * DeviceSize::from(extent[1]) * 4,
+            )
+            .expect("Failed to allocate capture buffer.");
+            *slot = Some(CaptureSlot {
+                buffer,
+                extent,
+                frame: None,
+            });
+        }
+        slot.as_ref().map(|slot| slot.buffer.clone())
+    }
+
+    // Record the frame copying into the buffer from `begin`.
+    fn end(&mut self, frame: Arc<FenceSignalFuture<Box<dyn GpuFuture>>>) {
+        if let Some(slot) = &mut self.slots[self.next] {
+            slot.frame = Some(frame);
+        }
+        self.next = (self.next + 1) % CAPTURE_BUFFER_COUNT;
+    }
+
+    // Read back the frames whose copies have finished, stopping at the first unfinished one to keep them in order.
+    fn take(&mut self) -> Vec<CapturedFrame> {
+        let mut frames = std::mem::take(&mut self.ready);
+        let Some(order) = self.order else {
+            return frames;
+        };
+        for i in 0..CAPTURE_BUFFER_COUNT {
+            let Some(slot) = &mut self.slots[(self.next + i) % CAPTURE_BUFFER_COUNT] else {
+                continue;
+            };
+            if slot.frame.is_none() {
+                continue;
+            }
+            match slot.read(order, false) {
+                Some(frame) => frames.push(frame),
+                None => break,
+            }
+        }
+        frames
+    }
+}
+
+impl CaptureSlot {
+    // Read back the frame copied into the buffer, optionally waiting for the copy to finish.
+    fn read(&mut self, order: PixelOrder, wait: bool) -> Option<CapturedFrame> {
+        let frame = self.frame.as_ref()?;
+        if !wait && !frame.is_signaled().unwrap_or(true) {
+            return None;
+        }
+        let finished = frame.wait(None);
+        self.frame = None;
+        if let Err(e) = finished {
+            log::warn!(target: "gpu", "Failed to capture frame: {e}");
+            return None;
+        }
+        let pixels = match self.buffer.read() {
+            Ok(pixels) => pixels.to_vec(),
+            Err(e) => {
+                log::warn!(target: "gpu", "Failed to read captured frame: {e}");
+                return None;
+            }
+        };
+        Some(CapturedFrame {
+            width: self.extent[0],
+            height: self.extent[1],
+            order,
+            pixels,
+        })
+    }
+}
+
+/// A render pass which ray-marches the scene into its own image, followed by a render pass which scales
+/// that image up over the frame with speed lines and places the overlay on top.
 struct RenderAppWithOverlay {
     pub queue: Arc<Queue>,
     render_pass: Arc<RenderPass>,
+    scene_render_pass: Arc<RenderPass>,
     pub app_pipeline: AppPipeline,
+    pub resolve_pipeline: AppPipeline,
+    pub speed_lines_pipeline: AppPipeline,
+    pub particles_pipeline: StoragePipeline,
+    pub trail_pipeline: StoragePipeline,
+    pub image_format: Format,
+    pub samples: SampleCount,
+    pub sampler: Arc<Sampler>,
+    pub history_sampler: Arc<Sampler>,
 }
 
 impl RenderAppWithOverlay {
-    pub fn new(queue: Arc<Queue>, image_format: Format, viewport: Viewport) -> Self {
-        let render_pass = Self::create_render_pass(queue.device().clone(), image_format);
+    pub fn new(
+        queue: Arc<Queue>,
+        cache: &Arc<PipelineCache>,
+        image_format: Format,
+        samples: SampleCount,
+        viewport: Viewport,
+    ) -> Self {
+        let render_pass = Self::create_render_pass(queue.device().clone(), image_format, samples);
+        let scene_render_pass =
+            Self::create_scene_render_pass(queue.device().clone(), image_format);
 
-        // Create a graphics pipeline for the app's render pass.
+        // Create graphics pipelines for the app's scene, the pass resolving it when ray-marched in a checkerboard
+        // pattern, and the speed lines, trail, and particles drawn over it.
+        let subpass = Subpass::from(scene_render_pass.clone(), 0).unwrap();
+        let app_pipeline = AppPipeline::ray_march(cache, subpass.clone(), viewport.clone());
+        let resolve_pipeline = AppPipeline::checkerboard_resolve(cache, subpass, viewport.clone());
         let subpass = Subpass::from(render_pass.clone(), 0).unwrap();
-        let app_pipeline = AppPipeline::new(queue.device(), subpass, viewport);
+        let speed_lines_pipeline =
+            AppPipeline::speed_lines(cache, subpass.clone(), viewport.clone());
+        let particles_pipeline =
+            StoragePipeline::particles(cache, subpass.clone(), viewport.clone());
+        let trail_pipeline = StoragePipeline::trail(cache, subpass, viewport);
+
+        // The scene is filtered when it is scaled up to the size of the frame.
+        let sampler = Sampler::new(
+            queue.device().clone(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Linear,
+                min_filter: Filter::Linear,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                ..SamplerCreateInfo::default()
+            },
+        )
+        .expect("Failed to create scene sampler.");
+
+        // The previous frame is reprojected pixel by pixel, so it isn't filtered.
+        let history_sampler = Sampler::new(
+            queue.device().clone(),
+            SamplerCreateInfo {
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                ..SamplerCreateInfo::default()
+            },
+        )
+        .expect("Failed to create history sampler.");
 
         RenderAppWithOverlay {
             queue,
             render_pass,
+            scene_render_pass,
             app_pipeline,
+            resolve_pipeline,
+            speed_lines_pipeline,
+            particles_pipeline,
+            trail_pipeline,
+            image_format,
+            samples,
+            sampler,
+            history_sampler,
+        }
+    }
+
+    // With multisampling, both passes draw into a multisampled image which the GUI pass resolves into the frame.
+    fn create_render_pass(
+        device: Arc<Device>,
+        format: Format,
+        samples: SampleCount,
+    ) -> Arc<RenderPass> {
+        if samples != SampleCount::Sample1 {
+            return vulkano::ordered_passes_renderpass!(
+                device,
+                attachments: {
+                    multisampled: {
+                        format: format,
+                        samples: samples,
+                        load_op: Clear,
+                        store_op: DontCare,
+                    },
+                    color: {
+                        format: format,
+                        samples: SampleCount::Sample1,
+                        load_op: Clear,
+                        store_op: Store,
+                    }
+                },
+                passes: [
+                    { color: [multisampled], depth_stencil: {}, input: [] },
+                    { color: [multisampled], color_resolve: [color], depth_stencil: {}, input: [] }
+                ]
+            )
+            .unwrap();
         }
-    }
-
-    fn create_render_pass(device: Arc<Device>, format: Format) -> Arc<RenderPass> {
         vulkano::ordered_passes_renderpass!(
             device,
             attachments: {
@@ -169,7 +2039,7 @@ impl RenderAppWithOverlay {
                 }
             },
             passes: [
-                // Main app pass.
+                // Speed-lines pass, which draws the scene over the frame.
                 { color: [color], depth_stencil: {}, input: [] },
 
                 // GUI pass.
@@ -179,71 +2049,121 @@ impl RenderAppWithOverlay {
         .unwrap()
     }
 
+    // Main app pass, which renders the scene for the speed-lines pass to sample, and the distance to the
+    // surface seen by each pixel for the next frame to reproject.
+    fn create_scene_render_pass(device: Arc<Device>, format: Format) -> Arc<RenderPass> {
+        vulkano::single_pass_renderpass!(
+            device,
+            attachments: {
+                scene: {
+                    format: format,
+                    samples: SampleCount::Sample1,
+                    load_op: Clear,
+                    store_op: Store,
+                },
+                distance: {
+                    format: Format::R32_SFLOAT,
+                    samples: SampleCount::Sample1,
+                    load_op: Clear,
+                    store_op: Store,
+                }
+            },
+            pass: { color: [scene, distance], depth_stencil: {} }
+        )
+        .unwrap()
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         &self,
+        [app_pipeline, speed_lines_pipeline]: [&AppPipeline; 2],
         allocator: &StandardCommandBufferAllocator,
         before_future: Box<dyn GpuFuture>,
+        mut graph: FrameGraph,
         image: Arc<ImageView>,
+        multisampled: Option<Arc<ImageView>>,
+        [scene, previous]: [&SceneTarget; 2],
+        checkerboard: Option<(&AppPipeline, &CheckerboardTarget)>,
+        effect_command_buffers: Vec<Arc<SecondaryAutoCommandBuffer>>,
         gui_command_buffer: Option<Arc<SecondaryAutoCommandBuffer>>,
-        push_constants: ray_march_voxels_fs::Push,
+        uniforms: &FrameUniforms,
         descriptor_set: Arc<PersistentDescriptorSet>,
+        capture_buffer: Option<Subbuffer<[u8]>>,
     ) -> Box<dyn GpuFuture> {
-        // Create a primary command buffer builder with intent for one-time submission.
-        let mut builder = AutoCommandBufferBuilder::primary(
+        // Create secondary command buffer to run main app pipeline
+        let app_command_buffer = app_pipeline.draw(
             allocator,
-            self.queue.queue_family_index(),
-            CommandBufferUsage::OneTimeSubmit,
-        )
-        .unwrap();
-
-        // Create framebuffer. Only one attachment is needed for this app.
-        let framebuffer = Framebuffer::new(
-            self.render_pass.clone(),
-            FramebufferCreateInfo {
-                attachments: vec![image],
-                ..FramebufferCreateInfo::default()
-            },
-        )
-        .unwrap();
-
-        // Begin render pass.
-        builder
-            .begin_render_pass(
-                RenderPassBeginInfo {
-                    clear_values: vec![Some([0.0; 4].into())],
-                    ..RenderPassBeginInfo::framebuffer(framebuffer)
-                },
-                SubpassBeginInfo {
-                    contents: SubpassContents::SecondaryCommandBuffers,
-                    ..SubpassBeginInfo::default()
-                },
-            )
-            .unwrap();
+            &self.queue,
+            ray_march_voxels_fs::Push::from(uniforms),
+            vec![descriptor_set, previous.history_descriptor_set.clone()],
+        );
 
-        // Create secondary command buffer to run main app pipeline
-        let app_command_buffer =
-            self.app_pipeline
-                .draw(allocator, &self.queue, push_constants, descriptor_set);
+        // Render the scene into its image, after any passes already in the graph. In a checkerboard pattern, half
+        // of the scene is ray-marched into images half as wide, then resolved to fill the scene.
+        let scene_attachments = vec![scene.view.clone(), scene.distance_view.clone()];
+        match checkerboard {
+            Some((resolve_pipeline, target)) => {
+                let resolve_command_buffer = resolve_pipeline.draw(
+                    allocator,
+                    &self.queue,
+                    checkerboard_resolve_fs::Push::from(uniforms),
+                    vec![target.descriptor_set.clone()],
+                );
+                graph
+                    .pass(
+                        "checkerboard",
+                        self.scene_render_pass.clone(),
+                        vec![target.view.clone(), target.distance_view.clone()],
+                    )
+                    .draw(0, app_command_buffer)
+                    .pass(
+                        "checkerboard_resolve",
+                        self.scene_render_pass.clone(),
+                        scene_attachments,
+                    )
+                    .draw(0, resolve_command_buffer);
+            }
+            None => {
+                graph
+                    .pass("scene", self.scene_render_pass.clone(), scene_attachments)
+                    .draw(0, app_command_buffer);
+            }
+        }
 
-        // Add app commands to primary command buffer and move to next subpass.
-        builder.execute_commands(app_command_buffer).unwrap();
-        builder
-            .next_subpass(
-                SubpassEndInfo::default(),
-                SubpassBeginInfo {
-                    contents: SubpassContents::SecondaryCommandBuffers,
-                    ..SubpassBeginInfo::default()
-                },
+        // Draw the scene into the image being presented with speed lines and any effects such as particles, then
+        // the optional GUI over it.
+        let swapchain_image = image.image().clone();
+        let attachments = match multisampled {
+            Some(view) => vec![view, image],
+            None => vec![image],
+        };
+        let speed_lines_command_buffer = speed_lines_pipeline.draw(
+            allocator,
+            &self.queue,
+            speed_lines_fs::Push::from(uniforms),
+            vec![scene.descriptor_set.clone()],
+        );
+        graph
+            .pass(
+                "speed_lines_with_overlay",
+                self.render_pass.clone(),
+                attachments,
             )
-            .unwrap();
-
-        // Add optional GUI command buffer to primary command buffer.
+            .draw(0, speed_lines_command_buffer);
+        for command_buffer in effect_command_buffers {
+            graph.draw(0, command_buffer);
+        }
         if let Some(command_buffer) = gui_command_buffer {
-            builder.execute_commands(command_buffer).unwrap();
+            graph.draw(1, command_buffer);
         }
 
-        // End render pass and execute primary command buffer.
-        builder.end_render_pass(SubpassEndInfo::default()).unwrap();
+        // Record the passes, copy the finished frame out if capturing, and execute primary command buffer.
+        let mut builder = graph.record(allocator, &self.queue);
+        if let Some(buffer) = capture_buffer {
+            builder
+                .copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(swapchain_image, buffer))
+                .unwrap();
+        }
         let command_buffer = builder.build().unwrap();
         let after_future = before_future
             .then_execute(self.queue.clone(), command_buffer)
@@ -252,6 +2172,32 @@ impl RenderAppWithOverlay {
         after_future.boxed()
     }
 
+    // Create the multisampled image which frames of the given size are drawn into, if multisampling.
+    // It is only ever resolved, so it needn't be kept in memory after the pass.
+    fn multisample_view(
+        &self,
+        memory: &Arc<StandardMemoryAllocator>,
+        extent: [u32; 2],
+    ) -> Option<Arc<ImageView>> {
+        if self.samples == SampleCount::Sample1 {
+            return None;
+        }
+        let image = Image::new(
+            memory.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: self.image_format,
+                extent: [extent[0], extent[1], 1],
+                samples: self.samples,
+                usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSIENT_ATTACHMENT,
+                ..ImageCreateInfo::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .expect("Failed to create multisampled image.");
+        Some(ImageView::new_default(image).unwrap())
+    }
+
     // Getters
     pub fn render_pass(&self) -> &Arc<RenderPass> {
         &self.render_pass
@@ -264,14 +2210,55 @@ struct AppPipeline {
 }
 
 impl AppPipeline {
-    // Create a graphics pipeline for the main app render pass.
-    pub fn new(device: &Arc<Device>, subpass: Subpass, viewport: Viewport) -> Self {
-        // Setup relevant context for creating the pipeline from these shaders.
-        let vs = entire_view_vs::load(device.clone())
+    // Create a graphics pipeline which ray-marches the voxel-octree.
+    pub fn ray_march(cache: &Arc<PipelineCache>, subpass: Subpass, viewport: Viewport) -> Self {
+        let fs = ray_march_voxels_fs::load(cache.device().clone())
+            .expect("Failed to create shader module.")
+            .entry_point("main")
+            .unwrap();
+        Self::new(cache, subpass, viewport, fs)
+    }
+
+    // Create a graphics pipeline which fills the pixels skipped when ray-marching in a checkerboard pattern.
+    pub fn checkerboard_resolve(
+        cache: &Arc<PipelineCache>,
+        subpass: Subpass,
+        viewport: Viewport,
+    ) -> Self {
+        let fs = checkerboard_resolve_fs::load(cache.device().clone())
+            .expect("Failed to create shader module.")
+            .entry_point("main")
+            .unwrap();
+        Self::new(cache, subpass, viewport, fs)
+    }
+
+    // Create a graphics pipeline which draws a top-down slice of the voxel-octree with the player's position.
+    pub fn octree_map(cache: &Arc<PipelineCache>, subpass: Subpass, viewport: Viewport) -> Self {
+        let fs = octree_map_fs::load(cache.device().clone())
+            .expect("Failed to create shader module.")
+            .entry_point("main")
+            .unwrap();
+        Self::new(cache, subpass, viewport, fs)
+    }
+
+    // Create a graphics pipeline which draws the scene rendered by the previous render pass, with speed lines.
+    pub fn speed_lines(cache: &Arc<PipelineCache>, subpass: Subpass, viewport: Viewport) -> Self {
+        let fs = speed_lines_fs::load(cache.device().clone())
             .expect("Failed to create shader module.")
             .entry_point("main")
             .unwrap();
-        let fs = ray_march_voxels_fs::load(device.clone())
+        Self::new(cache, subpass, viewport, fs)
+    }
+
+    // Create a graphics pipeline which draws a quad over the entire view with the given fragment shader.
+    fn new(
+        cache: &Arc<PipelineCache>,
+        subpass: Subpass,
+        viewport: Viewport,
+        fs: EntryPoint,
+    ) -> Self {
+        // Setup relevant context for creating the pipeline from these shaders.
+        let vs = entire_view_vs::load(cache.device().clone())
             .expect("Failed to create shader module.")
             .entry_point("main")
             .unwrap();
@@ -280,16 +2267,16 @@ impl AppPipeline {
             PipelineShaderStageCreateInfo::new(fs),
         ];
         let layout = PipelineLayout::new(
-            device.clone(),
+            cache.device().clone(),
             PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
-                .into_pipeline_layout_create_info(device.clone())
+                .into_pipeline_layout_create_info(cache.device().clone())
                 .unwrap(),
         )
         .unwrap();
 
         let pipeline = GraphicsPipeline::new(
-            device.clone(),
-            None,
+            cache.device().clone(),
+            Some(cache.clone()),
             GraphicsPipelineCreateInfo {
                 stages,
                 vertex_input_state: Some(VertexInputState::default()),
@@ -307,7 +2294,7 @@ impl AppPipeline {
 
                 // Necessary defaults.
                 rasterization_state: Some(RasterizationState::default()),
-                multisample_state: Some(MultisampleState::default()),
+                multisample_state: Some(subpass_multisample_state(&subpass)),
                 color_blend_state: Some(ColorBlendState {
                     attachments: (0..subpass.num_color_attachments())
                         .map(|_| ColorBlendAttachmentState::default())
@@ -324,12 +2311,12 @@ impl AppPipeline {
         AppPipeline { subpass, pipeline }
     }
 
-    pub fn draw(
+    pub fn draw<Pc: BufferContents>(
         &self,
         allocator: &StandardCommandBufferAllocator,
         queue: &Arc<Queue>,
-        push_constants: ray_march_voxels_fs::Push,
-        descriptor_set: Arc<PersistentDescriptorSet>,
+        push_constants: Pc,
+        descriptor_sets: Vec<Arc<PersistentDescriptorSet>>,
     ) -> Arc<SecondaryAutoCommandBuffer> {
         let mut builder = AutoCommandBufferBuilder::secondary(
             allocator,
@@ -351,7 +2338,7 @@ impl AppPipeline {
                 PipelineBindPoint::Graphics,
                 self.pipeline.layout().clone(),
                 0,
-                descriptor_set,
+                descriptor_sets,
             )
             .unwrap()
             .draw(4, 1, 0, 0)
@@ -366,23 +2353,297 @@ impl AppPipeline {
     }
 }
 
+// A pipeline drawing over the view from values in a storage buffer rather than vertex buffers, such as the
+// particles, which are additively blended streaks with one instance of a quad for each, and the trail, which is a
+// single alpha-blended ribbon with two vertices for each point.
+struct StoragePipeline {
+    pub subpass: Subpass,
+    pipeline: Arc<GraphicsPipeline>,
+    instanced: bool,
+}
+
+impl StoragePipeline {
+    // Create a graphics pipeline which draws particles as streaks.
+    pub fn particles(cache: &Arc<PipelineCache>, subpass: Subpass, viewport: Viewport) -> Self {
+        let vs = particles_vs::load(cache.device().clone())
+            .expect("Failed to create shader module.")
+            .entry_point("main")
+            .unwrap();
+        let fs = particles_fs::load(cache.device().clone())
+            .expect("Failed to create shader module.")
+            .entry_point("main")
+            .unwrap();
+        Self::new(
+            cache,
+            subpass,
+            viewport,
+            [vs, fs],
+            AttachmentBlend::additive(),
+            true,
+        )
+    }
+
+    // Create a graphics pipeline which draws the trail behind the camera as a ribbon.
+    pub fn trail(cache: &Arc<PipelineCache>, subpass: Subpass, viewport: Viewport) -> Self {
+        let vs = trail_vs::load(cache.device().clone())
+            .expect("Failed to create shader module.")
+            .entry_point("main")
+            .unwrap();
+        let fs = trail_fs::load(cache.device().clone())
+            .expect("Failed to create shader module.")
+            .entry_point("main")
+            .unwrap();
+        Self::new(
+            cache,
+            subpass,
+            viewport,
+            [vs, fs],
+            AttachmentBlend::alpha(),
+            false,
+        )
+    }
+
+    fn new(
+        cache: &Arc<PipelineCache>,
+        subpass: Subpass,
+        viewport: Viewport,
+        [vs, fs]: [EntryPoint; 2],
+        blend: AttachmentBlend,
+        instanced: bool,
+    ) -> Self {
+        let stages = smallvec![
+            PipelineShaderStageCreateInfo::new(vs),
+            PipelineShaderStageCreateInfo::new(fs),
+        ];
+        let layout = PipelineLayout::new(
+            cache.device().clone(),
+            PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+                .into_pipeline_layout_create_info(cache.device().clone())
+                .unwrap(),
+        )
+        .unwrap();
+
+        let pipeline = GraphicsPipeline::new(
+            cache.device().clone(),
+            Some(cache.clone()),
+            GraphicsPipelineCreateInfo {
+                stages,
+                vertex_input_state: Some(VertexInputState::default()),
+                input_assembly_state: Some(InputAssemblyState {
+                    topology: PrimitiveTopology::TriangleStrip,
+                    ..InputAssemblyState::default()
+                }),
+                viewport_state: Some(ViewportState {
+                    viewports: smallvec![viewport],
+                    ..ViewportState::default()
+                }),
+                rasterization_state: Some(RasterizationState::default()),
+                multisample_state: Some(subpass_multisample_state(&subpass)),
+
+                // Blend over the scene beneath.
+                color_blend_state: Some(ColorBlendState {
+                    attachments: (0..subpass.num_color_attachments())
+                        .map(|_| ColorBlendAttachmentState {
+                            blend: Some(blend),
+                            ..ColorBlendAttachmentState::default()
+                        })
+                        .collect(),
+                    ..Default::default()
+                }),
+                subpass: Some(subpass.clone().into()),
+                ..GraphicsPipelineCreateInfo::layout(layout)
+            },
+        )
+        .expect("Failed to create graphics pipeline");
+
+        StoragePipeline {
+            subpass,
+            pipeline,
+            instanced,
+        }
+    }
+
+    // Record a draw of every value in the buffer, which is bound by a new descriptor set.
+    pub fn draw<T: BufferContents, Pc: BufferContents>(
+        &self,
+        allocator: &StandardCommandBufferAllocator,
+        descriptor_set_allocator: &StandardDescriptorSetAllocator,
+        queue: &Arc<Queue>,
+        push_constants: Pc,
+        values: Subbuffer<[T]>,
+    ) -> Arc<SecondaryAutoCommandBuffer> {
+        #[allow(clippy::cast_possible_truncation)]
+        let count = values.len() as u32;
+        let (vertex_count, instance_count) = if self.instanced {
+            (4, count)
+        } else {
+            (2 * count, 1)
+        };
+        let descriptor_set = PersistentDescriptorSet::new(
+            descriptor_set_allocator,
+            self.pipeline.layout().set_layouts()[0].clone(),
+            [WriteDescriptorSet::buffer(0, values)],
+            [],
+        )
+        .expect("Failed to create storage buffer descriptor set.");
+
+        let mut builder = AutoCommandBufferBuilder::secondary(
+            allocator,
+            queue.queue_family_index(),
+            CommandBufferUsage::MultipleSubmit,
+            CommandBufferInheritanceInfo {
+                render_pass: Some(self.subpass.clone().into()),
+                ..CommandBufferInheritanceInfo::default()
+            },
+        )
+        .unwrap();
+
+        builder
+            .push_constants(self.pipeline.layout().clone(), 0, push_constants)
+            .unwrap()
+            .bind_pipeline_graphics(self.pipeline.clone())
+            .unwrap()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                self.pipeline.layout().clone(),
+                0,
+                descriptor_set,
+            )
+            .unwrap()
+            .draw(vertex_count, instance_count, 0, 0)
+            .expect("Failed to complete draw command");
+
+        builder.build().unwrap()
+    }
+}
+
+// Create a pipeline cache from the one saved by the last launch, if any. The driver ignores saved data which
+// doesn't match the device or driver version, so a stale file only costs recompiling the pipelines.
+fn load_pipeline_cache(device: &Arc<Device>) -> Arc<PipelineCache> {
+    let path = crate::voxel_flight_simulator::app_data_dir().join(PIPELINE_CACHE_FILE_NAME);
+    let initial_data = std::fs::read(&path).unwrap_or_else(|e| {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::warn!(target: "gpu", "Failed to read pipeline cache path=\"{}\": {e}", path.display());
+        }
+        Vec::new()
+    });
+    let initial_data = if initial_data.is_empty() || pipeline_cache_matches(device, &initial_data) {
+        initial_data
+    } else {
+        log::warn!(target: "gpu", "Ignoring pipeline cache from another device or driver path=\"{}\"", path.display());
+        Vec::new()
+    };
+
+    // SAFETY: Vulkan requires the initial data to have been retrieved from a pipeline cache on a compatible device.
+    // Data read from disk may come from another GPU or driver, so its header was checked against this device above,
+    // leaving the driver to reject anything else it finds stale.
+    let cache = unsafe {
+        PipelineCache::new(
+            device.clone(),
+            PipelineCacheCreateInfo {
+                initial_data,
+                ..PipelineCacheCreateInfo::default()
+            },
+        )
+    };
+    cache.unwrap_or_else(|e| {
+        log::warn!(target: "gpu", "Failed to load pipeline cache path=\"{}\": {e}", path.display());
+        unsafe { PipelineCache::new(device.clone(), PipelineCacheCreateInfo::default()) }
+            .expect("Failed to create pipeline cache.")
+    })
+}
+
+// Whether pipeline cache data has a header written by this device, following `VkPipelineCacheHeaderVersionOne`:
+// its length, version, vendor, device, and cache UUID.
+fn pipeline_cache_matches(device: &Device, data: &[u8]) -> bool {
+    const HEADER_LENGTH: usize = 32;
+    const HEADER_VERSION_ONE: u32 = 1;
+    let Some(header) = data.get(..HEADER_LENGTH) else {
+        return false;
+    };
+    let word = |i: usize| u32::from_ne_bytes(header[i * 4..i * 4 + 4].try_into().unwrap());
+    let properties = device.physical_device().properties();
+    word(0) as usize >= HEADER_LENGTH
+        && word(0) as usize <= data.len()
+        && word(1) == HEADER_VERSION_ONE
+        && word(2) == properties.vendor_id
+        && word(3) == properties.device_id
+        && header[16..] == properties.pipeline_cache_uuid
+}
+
+// Rasterize with as many samples as the attachments of the subpass have.
+fn subpass_multisample_state(subpass: &Subpass) -> MultisampleState {
+    MultisampleState {
+        rasterization_samples: subpass.num_samples().unwrap_or(SampleCount::Sample1),
+        ..MultisampleState::default()
+    }
+}
+
+// The largest sample count, up to the requested one, that the device can render colour attachments with.
+fn supported_sample_count(device: &Device, requested: u32) -> SampleCount {
+    let supported = device
+        .physical_device()
+        .properties()
+        .framebuffer_color_sample_counts;
+    let samples = [
+        SampleCount::Sample8,
+        SampleCount::Sample4,
+        SampleCount::Sample2,
+    ]
+    .into_iter()
+    .find(|&samples| samples as u32 <= requested && supported.contains_enum(samples))
+    .unwrap_or(SampleCount::Sample1);
+    if samples as u32 != requested {
+        log::warn!(
+            target: "gpu",
+            "Multisampling is limited by the device requested={requested} samples={}",
+            samples as u32,
+        );
+    }
+    samples
+}
+
+// Wrap an image of a headset eye's swapchain, which the headset owns, so that frames can be rendered into it.
+#[cfg(feature = "xr")]
+fn headset_image(
+    device: &Arc<Device>,
+    handle: u64,
+    format: Format,
+    extent: [u32; 2],
+) -> Arc<ImageView> {
+    let image = unsafe {
+        RawImage::from_handle_borrowed(
+            device.clone(),
+            Handle::from_raw(handle),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format,
+                extent: [extent[0], extent[1], 1],
+                usage: ImageUsage::COLOR_ATTACHMENT,
+                ..ImageCreateInfo::default()
+            },
+        )
+        .expect("Failed to wrap headset image.")
+        .assume_bound()
+    };
+    ImageView::new_default(Arc::new(image)).unwrap()
+}
+
+// A viewport covering an image of the given size.
+#[allow(clippy::cast_precision_loss)]
+fn extent_viewport([width, height]: [u32; 2]) -> Viewport {
+    Viewport {
+        offset: [0.; 2],
+        extent: [width as f32, height as f32],
+        depth_range: 0.0..=1.,
+    }
+}
+
 /// Minimal vertex shader which draws a quad over the entire viewport.
 mod entire_view_vs {
     vulkano_shaders::shader! {
         ty: "vertex",
-        src: "
-#version 450
-layout (location = 0) out vec2 coord;
-vec2 quad[4] = vec2[] (
-        vec2(-1.0, -1.0),
-        vec2(-1.0,  1.0),
-        vec2( 1.0, -1.0),
-        vec2( 1.0,  1.0)
-);
-void main() {
-        gl_Position = vec4(quad[gl_VertexIndex], 0.0, 1.0);
-        coord = quad[gl_VertexIndex];
-}",
+        path: "src/shaders/entire_view.vert",
     }
 }
 
@@ -393,3 +2654,144 @@ pub mod ray_march_voxels_fs {
         path: "src/shaders/ray_march_voxels.frag",
     }
 }
+
+/// Import the checkerboard-resolve fragment shader by file path.
+mod checkerboard_resolve_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/checkerboard_resolve.frag",
+    }
+}
+
+/// Import the octree-map fragment shader by file path.
+mod octree_map_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/octree_map.frag",
+    }
+}
+
+/// Import the speed-lines fragment shader by file path.
+mod speed_lines_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/speed_lines.frag",
+    }
+}
+
+/// Import the particle shaders by file path.
+mod particles_vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/particles.vert",
+    }
+}
+
+mod particles_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/particles.frag",
+    }
+}
+
+/// Import the trail shaders by file path.
+mod trail_vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/trail.vert",
+    }
+}
+
+mod trail_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/trail.frag",
+    }
+}
+
+// Copy the map's values into the push constants of the octree-map shader.
+impl From<&MapUniforms> for octree_map_fs::Push {
+    fn from(uniforms: &MapUniforms) -> Self {
+        Self {
+            camera_quaternion: uniforms.camera_quaternion,
+            camera_position: uniforms.camera_position,
+            aspect_ratio: uniforms.aspect_ratio,
+            centre: uniforms.centre,
+            scale: uniforms.scale,
+            ray_march: uniforms.ray_march,
+        }
+    }
+}
+
+// Copy the per-frame values into the push constants of the fragment shader.
+impl From<&FrameUniforms> for ray_march_voxels_fs::Push {
+    fn from(uniforms: &FrameUniforms) -> Self {
+        Self {
+            camera_position: uniforms.camera_position,
+            time: uniforms.time,
+            camera_quaternion: uniforms.camera_quaternion,
+            light_dir: uniforms.light_dir,
+            aspect_ratio: uniforms.aspect_ratio,
+            portal_highlight: uniforms.portal_highlight,
+            fov_y: uniforms.fov_y,
+            exposure: uniforms.exposure,
+            focus_distance: uniforms.focus_distance,
+            aperture: uniforms.aperture,
+            shadow_quality: uniforms.shadow_quality,
+            ambient_occlusion: uniforms.ambient_occlusion,
+            traversal: uniforms.traversal,
+            temporal: uniforms.temporal,
+            previous_camera_quaternion: uniforms.previous_camera_quaternion,
+            previous_camera_position: uniforms.previous_camera_position,
+            view: uniforms.view,
+        }
+    }
+}
+
+// Copy the frame count choosing which pixels were ray-marched into the push constants of the resolve pass.
+impl From<&FrameUniforms> for checkerboard_resolve_fs::Push {
+    fn from(uniforms: &FrameUniforms) -> Self {
+        Self {
+            frame: uniforms.temporal >> renderer::TEMPORAL_FRAME_SHIFT,
+        }
+    }
+}
+
+// Copy the camera and projection of the frame into the push constants of the particle shader.
+impl From<&FrameUniforms> for particles_vs::Push {
+    fn from(uniforms: &FrameUniforms) -> Self {
+        Self {
+            camera_quaternion: uniforms.camera_quaternion,
+            camera_position: uniforms.camera_position,
+            aspect_ratio: uniforms.aspect_ratio,
+            fov_y: uniforms.fov_y,
+            projection: uniforms.view >> renderer::VIEW_PROJECTION_SHIFT,
+        }
+    }
+}
+
+// Copy the camera and projection of the frame into the push constants of the trail shader.
+impl From<&FrameUniforms> for trail_vs::Push {
+    fn from(uniforms: &FrameUniforms) -> Self {
+        Self {
+            camera_quaternion: uniforms.camera_quaternion,
+            camera_position: uniforms.camera_position,
+            aspect_ratio: uniforms.aspect_ratio,
+            fov_y: uniforms.fov_y,
+            projection: uniforms.view >> renderer::VIEW_PROJECTION_SHIFT,
+        }
+    }
+}
+
+// Copy the per-frame values used by the speed lines into their push constants.
+impl From<&FrameUniforms> for speed_lines_fs::Push {
+    fn from(uniforms: &FrameUniforms) -> Self {
+        Self {
+            time: uniforms.time,
+            aspect_ratio: uniforms.aspect_ratio,
+            strength: uniforms.speed_lines,
+            flash: uniforms.crash_flash,
+            score_flash: uniforms.score_flash,
+        }
+    }
+}
@@ -0,0 +1,102 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::VecDeque;
+
+use crate::game;
+use crate::theme::WorldTheme;
+use crate::voxels::{Symmetry, WorldGenerator};
+
+// The most worlds that can be returned to.
+const HISTORY_LENGTH: usize = 32;
+
+// The seed and options that a world was generated from, which are enough to generate it again.
+#[derive(Clone, Copy, PartialEq)]
+pub struct WorldRecord {
+    pub seed: u64,
+    pub generator: WorldGenerator,
+    pub symmetry: Symmetry,
+    pub theme: WorldTheme,
+}
+
+// The worlds generated before the current one, and those left by going back, so that an interesting world isn't
+// lost to an accidental regeneration.
+#[derive(Default)]
+pub struct WorldHistory {
+    current: Option<WorldRecord>,
+    back: VecDeque<WorldRecord>,
+    forward: Vec<WorldRecord>,
+}
+
+impl WorldRecord {
+    // The record of a world generated from the given seed with the given options.
+    pub fn new(seed: u64, options: &game::Options) -> Self {
+        WorldRecord {
+            seed,
+            generator: options.generator,
+            symmetry: options.symmetry,
+            theme: options.theme,
+        }
+    }
+
+    // Change the options to those the world was generated with.
+    pub fn apply(&self, options: &mut game::Options) {
+        options.generator = self.generator;
+        options.symmetry = self.symmetry;
+        options.theme = self.theme;
+    }
+}
+
+impl WorldHistory {
+    // Record that the given world was generated. A different world from the current one pushes the current one
+    // onto the history and forgets the worlds that could be gone forward to.
+    pub fn visit(&mut self, record: WorldRecord) {
+        match self.current.replace(record) {
+            Some(previous) if previous != record => {
+                if self.back.len() == HISTORY_LENGTH {
+                    self.back.pop_front();
+                }
+                self.back.push_back(previous);
+                self.forward.clear();
+            }
+            _ => (),
+        }
+    }
+
+    // Go back to the previous world, if there is one.
+    pub fn back(&mut self) -> Option<WorldRecord> {
+        let previous = self.back.pop_back()?;
+        self.forward.extend(self.current.replace(previous));
+        Some(previous)
+    }
+
+    // Go forward to the world most recently left by going back, if there is one.
+    pub fn forward(&mut self) -> Option<WorldRecord> {
+        let next = self.forward.pop()?;
+        self.back.extend(self.current.replace(next));
+        Some(next)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+}
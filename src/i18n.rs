@@ -0,0 +1,1107 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+// Languages the overlay can be shown in.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+// Every piece of translatable overlay text.
+#[derive(Clone, Copy)]
+pub enum Text {
+    // Options window.
+    OptionsTitle,
+    OptionController,
+    ControllerAny,
+    ControllerConnected,
+    ControllerDisconnected,
+    OptionHotasMode,
+    OptionHotasAxes,
+    AxisRudder,
+    AxisThrottle,
+    AxisDefault,
+    HotasAssign,
+    HotasListening,
+    OptionInvertThrottle,
+    OptionGamepadResponse,
+    AxisRoll,
+    AxisPitch,
+    AxisYaw,
+    OptionDeadzone,
+    OptionSensitivity,
+    OptionCurve,
+    ResetDefaults,
+    TouchBoost,
+    OptionRumble,
+    OptionRumbleEnabled,
+    RumbleCollision,
+    RumblePortal,
+    RumbleBoost,
+    RumbleProximity,
+    OptionToggleBoost,
+    OptionFuelBoost,
+    OptionGameMode,
+    ModeStandard,
+    ModePractice,
+    ModeZen,
+    ModeCampaign,
+    PracticeClearSpawnPoint,
+    OptionDestructibleVoxels,
+    OptionWingman,
+    OptionChase,
+    OptionAudio,
+    OptionMasterVolume,
+    OptionMusic,
+    OptionSoundEffects,
+    OptionMuteOnFocusLoss,
+    OptionOpenMusicFolder,
+    MusicNowPlaying,
+    OptionRestoreCarvedVoxels,
+    OptionWind,
+    OptionSetSeed,
+    OptionSeedNumber,
+    WorldDifficulty,
+    OptionPreviousWorld,
+    OptionNextWorld,
+    OptionImportImage,
+    OptionImportWorld,
+    OptionReplayTutorial,
+    ChooseHeightmap,
+    ChooseColourMap,
+    ChooseWorldFile,
+    OptionInvertY,
+    OptionFov,
+    OptionDynamicFov,
+    OptionSpeedLines,
+    OptionParticles,
+    OptionTrail,
+    OptionMinimap,
+    OptionPortalValues,
+    OptionCrashFeedback,
+    OptionGraphicsPreset,
+    GraphicsLow,
+    GraphicsMedium,
+    GraphicsHigh,
+    GraphicsUltra,
+    GraphicsCustom,
+    OptionRenderScale,
+    OptionShadowQuality,
+    ShadowsOff,
+    ShadowsHard,
+    ShadowsSoft,
+    OptionAmbientOcclusion,
+    OptionReflectionBounces,
+    ReflectionBouncesCost,
+    OptionFogDistance,
+    FogDistanceHint,
+    OptionMaximumDepth,
+    MaximumDepthHint,
+    OptionStereo,
+    StereoOff,
+    StereoAnaglyph,
+    StereoSideBySide,
+    OptionStereoConvergence,
+    StereoConvergenceHint,
+    OptionProjection,
+    ProjectionRectilinear,
+    ProjectionCylindrical,
+    ProjectionPanini,
+    ProjectionHint,
+    OptionHorizontalFovCap,
+    OptionLanguage,
+    OptionColorblindMode,
+    OptionUiScale,
+    OptionOverlayTheme,
+    OverlayThemeDark,
+    OverlayThemeLight,
+    OverlayThemeHighContrast,
+    OptionAccentColour,
+    OptionOverlayAutoHide,
+    ResetWindowLayout,
+    OptionPauseOnFocusLoss,
+    OptionFpsLimit,
+    OptionDynamicResolution,
+    RenderScale,
+    OptionTemporalReprojection,
+    TemporalReprojectionHint,
+    OptionCheckerboardRendering,
+    CheckerboardRenderingHint,
+    OptionCollisionHull,
+    OptionDebugView,
+    DebugViewOff,
+    DebugViewSteps,
+    DebugViewDepth,
+    DebugViewNormals,
+    DebugViewVoxelType,
+    OptionVideoFormat,
+    OptionExclusiveFullscreen,
+    OptionMonitor,
+    MonitorCurrent,
+    OptionVideoMode,
+    HullPoint,
+    HullWingtips,
+    HullFull,
+    OptionFlightModel,
+    FlightArcade,
+    FlightAdvanced,
+    OptionMultisampling,
+    MultisamplingOff,
+    OptionGpu,
+    GpuAutomatic,
+    ColorblindOff,
+    ColorblindDeuteranopia,
+    ColorblindProtanopia,
+    ColorblindTritanopia,
+    OptionTheme,
+    ThemeClassic,
+    ThemeCanyon,
+    ThemeCity,
+    ThemeNebula,
+    OptionGenerator,
+    GeneratorRecursive,
+    GeneratorTiles,
+    OptionSymmetry,
+    SymmetryOff,
+    SymmetryMirror,
+    SymmetryRotational,
+    OptionDifficultyBand,
+    DifficultyAny,
+    DifficultyEasy,
+    DifficultyMedium,
+    DifficultyHard,
+    TwitchVoting,
+    TwitchChannelHint,
+    TwitchConnecting,
+    TwitchVotesPending,
+    TwitchWaiting,
+    TwitchDisconnected,
+    OptionCheckForUpdates,
+    OptionOffline,
+    UpdateAvailable,
+    UpdateDownload,
+    OptionProfile,
+    ProfileDefault,
+    ProfileNameHint,
+    ProfileAdd,
+    ExportData,
+    ImportData,
+    OptionStreamFiles,
+    OptionOpenStreamFolder,
+    OptionRemoteControl,
+    RemoteControlPort,
+    OptionPlugins,
+    ReloadPlugins,
+    OptionOpenModesFolder,
+    OptionOpenPluginsFolder,
+    OptionSpeedrunTimer,
+    SpeedrunExportSplits,
+    SpeedrunSplit,
+    LiveSplitServer,
+    LiveSplitAddressHint,
+    LiveSplitConnecting,
+    LiveSplitConnected,
+    LiveSplitDisconnected,
+
+    // Map window.
+    MapWindowTitle,
+
+    // Help window.
+    HelpTitle,
+    HelpAppWindow,
+    HelpToggleFullscreen,
+    HelpEscape,
+    HelpToggleMap,
+    HelpToggleConsole,
+    HelpOverlayWindow,
+    HelpToggleHelp,
+    HelpToggleStats,
+    HelpToggleOptions,
+    HelpToggleCameraPath,
+    HelpToggleInspector,
+    HelpGame,
+    HelpNewWorld,
+    HelpNextWorld,
+    HelpPreviousWorld,
+    HelpTogglePhotoMode,
+    HelpHidePhotoPanel,
+    HelpCaptureScreenshot,
+    HelpToggleAutopilot,
+    HelpAddKeyframe,
+    HelpPlayCameraPath,
+    HelpSaveSpawnPoint,
+    HelpRespawn,
+    HelpFire,
+    HelpNextSong,
+    HelpPreviousSong,
+    HelpCapturePanorama,
+    HelpToggleRecording,
+    HelpToggleSpectator,
+    HelpFlight,
+    HelpPitchDown,
+    HelpPitchUp,
+    HelpRollLeft,
+    HelpRollRight,
+    HelpYawLeft,
+    HelpYawRight,
+    HelpBoost,
+    HelpScoring,
+    HelpScoringDepth,
+    HelpScoringPortalBonus,
+    HelpScoringDeepestBonus,
+    HelpScoringRings,
+    HelpScoringPar,
+    HelpScoringMultiplier,
+    HelpScoringOff,
+
+    // Run window.
+    RunTitle,
+    RunScore,
+    RunLevel,
+    RunRings,
+    RunFuel,
+    RunTime,
+    RunTimeLeft,
+    RunPar,
+    RunLastLevel,
+
+    // Tutorial prompts.
+    TutorialTitle,
+    TutorialPitch,
+    TutorialRoll,
+    TutorialYaw,
+    TutorialBoost,
+    TutorialFindPortal,
+    TutorialSkip,
+
+    // Camera path window.
+    CameraPathTitle,
+    CameraPathSummary,
+    CameraPathOtherWorld,
+    CameraPathAddKeyframe,
+    CameraPathPlay,
+    CameraPathStop,
+    CameraPathClear,
+    CameraPathExport,
+    CameraPathImport,
+    CameraPathLoop,
+    CameraPathHideOverlay,
+    CameraPathKeyframe,
+
+    // Photo mode window.
+    PhotoTitle,
+    PhotoHint,
+    PhotoExposure,
+    PhotoAperture,
+    PhotoFocusDistance,
+    PhotoAutofocus,
+    PhotoCapture,
+
+    // Statistics window.
+    StatsTitle,
+    StatsFlightTime,
+    StatsDistanceFlown,
+    StatsCrashes,
+    StatsPortalsTaken,
+    StatsBestScore,
+    StatsFurthestLevel,
+    StatsCampaignLevels,
+    StatsAchievements,
+
+    // Inspector window.
+    InspectorTitle,
+    InspectorHint,
+    InspectorNothingHit,
+    InspectorIndex,
+    InspectorFlags,
+    InspectorDepth,
+    InspectorDistance,
+    InspectorNormal,
+    InspectorColour,
+    VoxelComplex,
+    VoxelColour,
+    VoxelPortal,
+    VoxelMirror,
+    VoxelReturnPortal,
+    VoxelSpeedPortal,
+    VoxelWormhole,
+    VoxelUnknown,
+
+    // Campaign window.
+    CampaignTitle,
+    CampaignLevel,
+    CampaignGoal,
+    CampaignGoalTimed,
+    CampaignPlay,
+    CampaignRestart,
+    CampaignLocked,
+    CampaignLevelComplete,
+
+    // Crash report window.
+    CrashTitle,
+    CrashMessage,
+    CrashOpenReport,
+    CrashDismiss,
+
+    // Achievements.
+    AchievementUnlocked,
+    AchievementFirstPortal,
+    AchievementFirstPortalDescription,
+    AchievementDeepDive,
+    AchievementDeepDiveDescription,
+    AchievementMarathon,
+    AchievementMarathonDescription,
+    AchievementSteadyHand,
+    AchievementSteadyHandDescription,
+    AchievementHighRoller,
+    AchievementHighRollerDescription,
+    AchievementFrequentFlyer,
+    AchievementFrequentFlyerDescription,
+    AchievementCrashTestDummy,
+    AchievementCrashTestDummyDescription,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    // The name of the language, written in that language.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    // Get the translation of a piece of text.
+    pub fn tr(self, text: Text) -> &'static str {
+        match self {
+            Language::English => english(text),
+            Language::Spanish => spanish(text),
+        }
+    }
+
+    // Get the translation of a piece of text, replacing each `{}` with the next argument.
+    pub fn tr_args(self, text: Text, args: &[&dyn Display]) -> String {
+        let mut args = args.iter();
+        let mut parts = self.tr(text).split("{}");
+        let mut result = parts.next().unwrap_or_default().to_string();
+        for part in parts {
+            if let Some(arg) = args.next() {
+                result.push_str(&arg.to_string());
+            }
+            result.push_str(part);
+        }
+        result
+    }
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::OptionsTitle => "Options",
+        Text::OptionController => "Controller",
+        Text::ControllerAny => "Any",
+        Text::ControllerConnected => "Controller connected",
+        Text::ControllerDisconnected => "Controller disconnected",
+        Text::OptionHotasMode => "Treat gamepad as H.O.T.A.S. stick",
+        Text::OptionHotasAxes => "H.O.T.A.S. axes",
+        Text::AxisRudder => "Rudder or twist",
+        Text::AxisThrottle => "Throttle",
+        Text::AxisDefault => "Default",
+        Text::HotasAssign => "Assign",
+        Text::HotasListening => "Move an axis...",
+        Text::OptionInvertThrottle => "Invert throttle",
+        Text::OptionGamepadResponse => "Stick response",
+        Text::AxisRoll => "Roll",
+        Text::AxisPitch => "Pitch",
+        Text::AxisYaw => "Yaw (H.O.T.A.S. only)",
+        Text::OptionDeadzone => "Deadzone",
+        Text::OptionSensitivity => "Sensitivity",
+        Text::OptionCurve => "Curve",
+        Text::ResetDefaults => "Reset to defaults",
+        Text::TouchBoost => "BOOST",
+        Text::OptionRumble => "Rumble",
+        Text::OptionRumbleEnabled => "Enable rumble",
+        Text::RumbleCollision => "Crashes",
+        Text::RumblePortal => "Portals",
+        Text::RumbleBoost => "Boosting",
+        Text::RumbleProximity => "Obstacles ahead",
+        Text::OptionToggleBoost => "Toggle boost",
+        Text::OptionFuelBoost => "Boost uses fuel",
+        Text::OptionGameMode => "Mode",
+        Text::ModeStandard => "Standard",
+        Text::ModePractice => "Practice",
+        Text::ModeZen => "Zen",
+        Text::ModeCampaign => "Campaign",
+        Text::PracticeClearSpawnPoint => "Clear spawn point",
+        Text::OptionDestructibleVoxels => "Destructible voxels",
+        Text::OptionWingman => "Wingman",
+        Text::OptionChase => "Chase mode",
+        Text::OptionAudio => "Audio",
+        Text::OptionMasterVolume => "Master volume",
+        Text::OptionMusic => "Music",
+        Text::OptionSoundEffects => "Sound effects",
+        Text::OptionMuteOnFocusLoss => "Mute while in the background",
+        Text::OptionOpenMusicFolder => "Open music folder",
+        Text::MusicNowPlaying => "Now playing",
+        Text::OptionRestoreCarvedVoxels => "Restore carved voxels",
+        Text::OptionWind => "Wind",
+        Text::OptionSetSeed => "Set seed",
+        Text::OptionSeedNumber => "Seed number: {}",
+        Text::WorldDifficulty => "Difficulty: {} ({})",
+        Text::OptionPreviousWorld => "Previous world",
+        Text::OptionNextWorld => "Next world",
+        Text::OptionImportImage => "Import image…",
+        Text::OptionImportWorld => "Import world file…",
+        Text::OptionReplayTutorial => "Replay tutorial",
+        Text::ChooseHeightmap => "Choose a grayscale heightmap",
+        Text::ChooseColourMap => "Choose a colour map, or cancel to colour by height",
+        Text::ChooseWorldFile => "Choose a world file",
+        Text::OptionInvertY => "Inverted Y-Axis",
+        Text::OptionFov => "Field of view",
+        Text::OptionDynamicFov => "Widen field of view when boosting",
+        Text::OptionSpeedLines => "Speed lines",
+        Text::OptionParticles => "Particles",
+        Text::OptionTrail => "Contrail",
+        Text::OptionMinimap => "Minimap",
+        Text::OptionPortalValues => "Portal point values",
+        Text::OptionCrashFeedback => "Crash shake and flash",
+        Text::OptionGraphicsPreset => "Graphics quality",
+        Text::GraphicsLow => "Low",
+        Text::GraphicsMedium => "Medium",
+        Text::GraphicsHigh => "High",
+        Text::GraphicsUltra => "Ultra",
+        Text::GraphicsCustom => "Custom",
+        Text::OptionRenderScale => "Render scale",
+        Text::OptionShadowQuality => "Shadows",
+        Text::ShadowsOff => "Off",
+        Text::ShadowsHard => "Hard",
+        Text::ShadowsSoft => "Soft",
+        Text::OptionAmbientOcclusion => "Ambient occlusion",
+        Text::OptionReflectionBounces => "Mirror reflections",
+        Text::ReflectionBouncesCost => "Each bounce off a mirror voxel lights and shadows another surface, so more bounces cost more where mirrors fill the view",
+        Text::OptionFogDistance => "Fog distance",
+        Text::FogDistanceHint => "Steps each ray marches before the fog hides it. Fewer steps bring the fog closer and render faster",
+        Text::OptionMaximumDepth => "Voxel detail",
+        Text::MaximumDepthHint => "The deepest level of the octree drawn up close. Lower levels render faster but show less fine detail",
+        Text::OptionStereo => "3D output",
+        Text::StereoOff => "Off",
+        Text::StereoAnaglyph => "Red/cyan anaglyph",
+        Text::StereoSideBySide => "Side-by-side",
+        Text::OptionStereoConvergence => "3D convergence (s)",
+        Text::StereoConvergenceHint => "Seconds of flight ahead where the world appears level with the screen. Nearer surfaces stand out of it, and further ones sink behind",
+        Text::OptionProjection => "Projection",
+        Text::ProjectionRectilinear => "Rectilinear",
+        Text::ProjectionCylindrical => "Cylindrical",
+        Text::ProjectionPanini => "Panini",
+        Text::ProjectionHint => "Cylindrical and Panini projections stretch the edges of ultrawide screens far less, at the cost of bending some straight lines",
+        Text::OptionHorizontalFovCap => "Horizontal FOV cap",
+        Text::OptionLanguage => "Language",
+        Text::OptionColorblindMode => "Colour-blind mode",
+        Text::OptionUiScale => "UI scale",
+        Text::OptionOverlayTheme => "Overlay theme",
+        Text::OverlayThemeDark => "Dark",
+        Text::OverlayThemeLight => "Light",
+        Text::OverlayThemeHighContrast => "High contrast",
+        Text::OptionAccentColour => "Accent colour",
+        Text::OptionOverlayAutoHide => "Hide overlay when idle",
+        Text::ResetWindowLayout => "Reset window layout",
+        Text::OptionPauseOnFocusLoss => "Pause on focus loss",
+        Text::OptionFpsLimit => "Limit frame rate",
+        Text::OptionDynamicResolution => "Adapt resolution to hold",
+        Text::RenderScale => "Rendering at {}% resolution",
+        Text::OptionTemporalReprojection => "Temporal reprojection (advanced)",
+        Text::TemporalReprojectionHint => "Reuse the last frame's colours where the same surfaces are still in view, ray-marching only newly revealed pixels and a quarter of the rest each frame. Faster, but moving lights and portals may lag slightly",
+        Text::OptionCheckerboardRendering => "Checkerboard rendering",
+        Text::CheckerboardRenderingHint => "Ray-march half of the pixels each frame in a checkerboard pattern and fill in the rest from their neighbours. Much faster on integrated GPUs, at the cost of slightly softer detail",
+        Text::OptionCollisionHull => "Collision hull",
+        Text::OptionDebugView => "Debug view",
+        Text::DebugViewOff => "Off",
+        Text::DebugViewSteps => "Traversal steps",
+        Text::DebugViewDepth => "Octree depth",
+        Text::DebugViewNormals => "Normals",
+        Text::DebugViewVoxelType => "Voxel type",
+        Text::OptionVideoFormat => "Video format",
+        Text::OptionExclusiveFullscreen => "Exclusive fullscreen",
+        Text::OptionMonitor => "Monitor",
+        Text::MonitorCurrent => "Current",
+        Text::OptionVideoMode => "Resolution",
+        Text::HullPoint => "Camera only",
+        Text::HullWingtips => "Wingtips",
+        Text::HullFull => "Full body",
+        Text::OptionFlightModel => "Flight model",
+        Text::FlightArcade => "Arcade",
+        Text::FlightAdvanced => "Advanced (lift, drag, and stalls)",
+        Text::OptionMultisampling => "Anti-aliasing",
+        Text::MultisamplingOff => "Off",
+        Text::OptionGpu => "GPU (applies after restart)",
+        Text::GpuAutomatic => "Automatic",
+        Text::ColorblindOff => "Off",
+        Text::ColorblindDeuteranopia => "Deuteranopia",
+        Text::ColorblindProtanopia => "Protanopia",
+        Text::ColorblindTritanopia => "Tritanopia",
+        Text::OptionTheme => "World theme",
+        Text::ThemeClassic => "Classic",
+        Text::ThemeCanyon => "Canyon",
+        Text::ThemeCity => "City",
+        Text::ThemeNebula => "Nebula",
+        Text::OptionGenerator => "World generator",
+        Text::GeneratorRecursive => "Recursive",
+        Text::GeneratorTiles => "Tiles",
+        Text::OptionSymmetry => "World symmetry",
+        Text::SymmetryOff => "Off",
+        Text::SymmetryMirror => "Mirror",
+        Text::SymmetryRotational => "Rotational",
+        Text::OptionDifficultyBand => "New world difficulty",
+        Text::DifficultyAny => "Any",
+        Text::DifficultyEasy => "Easy",
+        Text::DifficultyMedium => "Medium",
+        Text::DifficultyHard => "Hard",
+        Text::TwitchVoting => "Twitch chat voting",
+        Text::TwitchChannelHint => "Channel",
+        Text::TwitchConnecting => "Connecting to #{}...",
+        Text::TwitchVotesPending => "{} votes, closing in {}s",
+        Text::TwitchWaiting => "Waiting for !seed <number>, !regen, or !hazard",
+        Text::TwitchDisconnected => "Disconnected",
+        Text::OptionCheckForUpdates => "Check for updates on startup",
+        Text::OptionOffline => "Offline mode",
+        Text::UpdateAvailable => "Version {} is available",
+        Text::UpdateDownload => "Download",
+        Text::OptionProfile => "Profile",
+        Text::ProfileDefault => "Default",
+        Text::ProfileNameHint => "New profile name",
+        Text::ProfileAdd => "Add profile",
+        Text::ExportData => "Export data…",
+        Text::ImportData => "Import data…",
+        Text::OptionStreamFiles => "Write stream files",
+        Text::OptionOpenStreamFolder => "Open stream folder",
+        Text::OptionRemoteControl => "Remote control over HTTP",
+        Text::RemoteControlPort => "Port ",
+        Text::OptionPlugins => "Enable plugins",
+        Text::ReloadPlugins => "Reload",
+        Text::OptionOpenModesFolder => "Open modes folder",
+        Text::OptionOpenPluginsFolder => "Open plugins folder",
+        Text::OptionSpeedrunTimer => "Speedrun timer",
+        Text::SpeedrunExportSplits => "Export splits…",
+        Text::SpeedrunSplit => "Level {}",
+        Text::LiveSplitServer => "LiveSplit Server",
+        Text::LiveSplitAddressHint => "Address",
+        Text::LiveSplitConnecting => "Connecting to {}...",
+        Text::LiveSplitConnected => "Connected to {}",
+        Text::LiveSplitDisconnected => "Disconnected",
+
+        Text::MapWindowTitle => "Map",
+
+        Text::HelpTitle => "Help",
+        Text::HelpAppWindow => "App-Window",
+        Text::HelpToggleFullscreen => "Toggle window fullscreen",
+        Text::HelpEscape => "If fullscreen, then enter windowed mode. Else, close the application",
+        Text::HelpToggleMap => "Open or close a window with a top-down map of the world at your height",
+        Text::HelpToggleConsole => "Toggle the visibility of the output command prompt",
+        Text::HelpOverlayWindow => "Overlay-Window",
+        Text::HelpToggleHelp => "Toggle showing this Help window",
+        Text::HelpToggleStats => "Toggle showing the Statistics window",
+        Text::HelpToggleOptions => "Toggle showing the Options window",
+        Text::HelpToggleCameraPath => "Toggle showing the Camera Path window",
+        Text::HelpToggleInspector => "Toggle the Inspector, which describes the voxel clicked on",
+        Text::HelpGame => "Game",
+        Text::HelpNewWorld => "Generate a new random world and reset game",
+        Text::HelpNextWorld => "Go forward to the world left by going back",
+        Text::HelpPreviousWorld => "Go back to the previous world and reset game",
+        Text::HelpTogglePhotoMode => "Enter or leave photo mode, pausing the game",
+        Text::HelpHidePhotoPanel => "Hide or show the photo mode window",
+        Text::HelpCaptureScreenshot => "Save a high-resolution screenshot without the overlay",
+        Text::HelpToggleAutopilot => "Toggle autopilot, ending the current run",
+        Text::HelpAddKeyframe => "Add a camera path keyframe",
+        Text::HelpPlayCameraPath => "Play or stop the camera path",
+        Text::HelpSaveSpawnPoint => "Practice mode: save a spawn point here",
+        Text::HelpRespawn => "Practice mode: return to the spawn point",
+        Text::HelpFire => "Fire a projectile",
+        Text::HelpNextSong => "Play the next song of the music folder",
+        Text::HelpPreviousSong => "Play the previous song of the music folder",
+        Text::HelpCapturePanorama => "Save a 360° panorama of the surroundings",
+        Text::HelpToggleRecording => "Start or stop recording a video",
+        Text::HelpToggleSpectator => "Toggle spectator mode, flying a director camera",
+        Text::HelpFlight => "Flight",
+        Text::HelpPitchDown => "Pitch down",
+        Text::HelpPitchUp => "Pitch up",
+        Text::HelpRollLeft => "Roll left",
+        Text::HelpRollRight => "Roll right",
+        Text::HelpYawLeft => "Yaw left",
+        Text::HelpYawRight => "Yaw right",
+        Text::HelpBoost => "Boost",
+        Text::HelpScoring => "Scoring",
+        Text::HelpScoringDepth => "Portals {} levels deep score {}, and {} more for each level deeper",
+        Text::HelpScoringPortalBonus => "Every portal scores {} extra",
+        Text::HelpScoringDeepestBonus => "The deepest portals, {} levels deep, score {} extra",
+        Text::HelpScoringRings => "Each ring scores {}",
+        Text::HelpScoringPar => "Finishing a level under par scores 1 for every {} seconds to spare",
+        Text::HelpScoringMultiplier => "All points are multiplied by {}",
+        Text::HelpScoringOff => "This mode doesn't keep score",
+
+        Text::RunTitle => "Run",
+        Text::RunScore => "Score: {}",
+        Text::RunLevel => "Level: {}",
+        Text::RunRings => "Rings: {} / {}",
+        Text::RunFuel => "Fuel",
+        Text::RunTime => "Time: {}s",
+        Text::RunTimeLeft => "Time left: {}s",
+        Text::RunPar => "Par: {}s ({}s)",
+        Text::RunLastLevel => "Last level: {}s ({}s)",
+
+        Text::TutorialTitle => "Tutorial",
+        Text::TutorialPitch => "Pitch the nose up and down with UP and DOWN, or the left stick",
+        Text::TutorialRoll => "Roll left and right with LEFT and RIGHT, or the left stick",
+        Text::TutorialYaw => "Yaw left and right with {} and {}, or the shoulder buttons",
+        Text::TutorialBoost => "Hold SPACE, or the bottom face button, to boost",
+        Text::TutorialFindPortal => "Fly into the portals on top of the tower to reach your first world",
+        Text::TutorialSkip => "Skip tutorial",
+
+        Text::CameraPathTitle => "Camera Path",
+        Text::CameraPathSummary => "Keyframes: {}, duration: {}s",
+        Text::CameraPathOtherWorld => "This path was made in world {}",
+        Text::CameraPathAddKeyframe => "Add keyframe",
+        Text::CameraPathPlay => "Play",
+        Text::CameraPathStop => "Stop",
+        Text::CameraPathClear => "Clear",
+        Text::CameraPathExport => "Export",
+        Text::CameraPathImport => "Import",
+        Text::CameraPathLoop => "Loop playback",
+        Text::CameraPathHideOverlay => "Hide overlay during playback",
+        Text::CameraPathKeyframe => "#{}, seconds to next:",
+
+        Text::PhotoTitle => "Photo Mode",
+        Text::PhotoHint => "Steer with the flight controls and hold boost to move forward",
+        Text::PhotoExposure => "Exposure",
+        Text::PhotoAperture => "Aperture",
+        Text::PhotoFocusDistance => "Focus distance",
+        Text::PhotoAutofocus => "Autofocus",
+        Text::PhotoCapture => "Capture",
+
+        Text::StatsTitle => "Statistics",
+        Text::StatsFlightTime => "Flight time",
+        Text::StatsDistanceFlown => "Distance flown",
+        Text::StatsCrashes => "Crashes",
+        Text::StatsPortalsTaken => "Portals taken",
+        Text::StatsBestScore => "Best score",
+        Text::StatsFurthestLevel => "Furthest level",
+        Text::StatsCampaignLevels => "Campaign levels completed",
+        Text::StatsAchievements => "Achievements",
+
+        Text::InspectorTitle => "Inspector",
+        Text::InspectorHint => "Click the world to inspect the first voxel under the cursor",
+        Text::InspectorNothingHit => "No voxel was hit",
+        Text::InspectorIndex => "Index",
+        Text::InspectorFlags => "Flags",
+        Text::InspectorDepth => "Depth",
+        Text::InspectorDistance => "Distance",
+        Text::InspectorNormal => "Normal",
+        Text::InspectorColour => "Colour",
+        Text::VoxelComplex => "complex",
+        Text::VoxelColour => "colour",
+        Text::VoxelPortal => "portal",
+        Text::VoxelMirror => "mirror",
+        Text::VoxelReturnPortal => "return portal",
+        Text::VoxelSpeedPortal => "speed portal",
+        Text::VoxelWormhole => "wormhole",
+        Text::VoxelUnknown => "unknown",
+
+        Text::CampaignTitle => "Campaign",
+        Text::CampaignLevel => "Level {}",
+        Text::CampaignGoal => "Score {} points",
+        Text::CampaignGoalTimed => "Score {} points within {}s",
+        Text::CampaignPlay => "Play",
+        Text::CampaignRestart => "Restart",
+        Text::CampaignLocked => "Complete the level before to unlock this one",
+        Text::CampaignLevelComplete => "Level {} complete!",
+        Text::CrashTitle => "Crash Report",
+        Text::CrashMessage => "The game crashed during the last launch. A report was saved to:",
+        Text::CrashOpenReport => "Open report",
+        Text::CrashDismiss => "Dismiss",
+
+        Text::AchievementUnlocked => "Achievement unlocked!",
+        Text::AchievementFirstPortal => "Through the Looking Glass",
+        Text::AchievementFirstPortalDescription => "Fly through your first portal",
+        Text::AchievementDeepDive => "Deep Dive",
+        Text::AchievementDeepDiveDescription => "Take a portal at the deepest possible depth",
+        Text::AchievementMarathon => "Marathon",
+        Text::AchievementMarathonDescription => "Reach level 10 in a single run",
+        Text::AchievementSteadyHand => "Steady Hand",
+        Text::AchievementSteadyHandDescription => "Fly for 5 minutes in a run without boosting",
+        Text::AchievementHighRoller => "High Roller",
+        Text::AchievementHighRollerDescription => "Score 50 points in a single run",
+        Text::AchievementFrequentFlyer => "Frequent Flyer",
+        Text::AchievementFrequentFlyerDescription => "Spend an hour in flight",
+        Text::AchievementCrashTestDummy => "Crash Test Dummy",
+        Text::AchievementCrashTestDummyDescription => "Crash 100 times",
+    }
+}
+
+fn spanish(text: Text) -> &'static str {
+    match text {
+        Text::OptionsTitle => "Opciones",
+        Text::OptionController => "Mando",
+        Text::ControllerAny => "Cualquiera",
+        Text::ControllerConnected => "Mando conectado",
+        Text::ControllerDisconnected => "Mando desconectado",
+        Text::OptionHotasMode => "Tratar el mando como palanca H.O.T.A.S.",
+        Text::OptionHotasAxes => "Ejes H.O.T.A.S.",
+        Text::AxisRudder => "Timón o giro",
+        Text::AxisThrottle => "Acelerador",
+        Text::AxisDefault => "Predeterminado",
+        Text::HotasAssign => "Asignar",
+        Text::HotasListening => "Mueve un eje...",
+        Text::OptionInvertThrottle => "Invertir acelerador",
+        Text::OptionGamepadResponse => "Respuesta de la palanca",
+        Text::AxisRoll => "Alabeo",
+        Text::AxisPitch => "Cabeceo",
+        Text::AxisYaw => "Guiñada (solo H.O.T.A.S.)",
+        Text::OptionDeadzone => "Zona muerta",
+        Text::OptionSensitivity => "Sensibilidad",
+        Text::OptionCurve => "Curva",
+        Text::ResetDefaults => "Restablecer valores",
+        Text::TouchBoost => "IMPULSO",
+        Text::OptionRumble => "Vibración",
+        Text::OptionRumbleEnabled => "Activar vibración",
+        Text::RumbleCollision => "Choques",
+        Text::RumblePortal => "Portales",
+        Text::RumbleBoost => "Impulso",
+        Text::RumbleProximity => "Obstáculos delante",
+        Text::OptionToggleBoost => "Alternar impulso",
+        Text::OptionFuelBoost => "El impulso gasta combustible",
+        Text::OptionGameMode => "Modo",
+        Text::ModeStandard => "Estándar",
+        Text::ModePractice => "Práctica",
+        Text::ModeZen => "Zen",
+        Text::ModeCampaign => "Campaña",
+        Text::PracticeClearSpawnPoint => "Borrar punto de reaparición",
+        Text::OptionDestructibleVoxels => "Vóxeles destructibles",
+        Text::OptionWingman => "Compañero de ala",
+        Text::OptionChase => "Modo persecución",
+        Text::OptionAudio => "Audio",
+        Text::OptionMasterVolume => "Volumen general",
+        Text::OptionMusic => "Música",
+        Text::OptionSoundEffects => "Efectos de sonido",
+        Text::OptionMuteOnFocusLoss => "Silenciar en segundo plano",
+        Text::OptionOpenMusicFolder => "Abrir carpeta de música",
+        Text::MusicNowPlaying => "Reproduciendo",
+        Text::OptionRestoreCarvedVoxels => "Restaurar vóxeles tallados",
+        Text::OptionWind => "Viento",
+        Text::OptionSetSeed => "Usar semilla",
+        Text::OptionSeedNumber => "Número de semilla: {}",
+        Text::WorldDifficulty => "Dificultad: {} ({})",
+        Text::OptionPreviousWorld => "Mundo anterior",
+        Text::OptionNextWorld => "Mundo siguiente",
+        Text::OptionImportImage => "Importar imagen…",
+        Text::OptionImportWorld => "Importar archivo de mundo…",
+        Text::OptionReplayTutorial => "Repetir el tutorial",
+        Text::ChooseHeightmap => "Elige un mapa de alturas en escala de grises",
+        Text::ChooseColourMap => "Elige un mapa de colores, o cancela para colorear por altura",
+        Text::ChooseWorldFile => "Elige un archivo de mundo",
+        Text::OptionInvertY => "Eje Y invertido",
+        Text::OptionFov => "Campo de visión",
+        Text::OptionDynamicFov => "Ampliar el campo de visión al impulsar",
+        Text::OptionSpeedLines => "Líneas de velocidad",
+        Text::OptionParticles => "Partículas",
+        Text::OptionTrail => "Estela",
+        Text::OptionMinimap => "Minimapa",
+        Text::OptionPortalValues => "Valor en puntos de los portales",
+        Text::OptionCrashFeedback => "Sacudida y destello al chocar",
+        Text::OptionGraphicsPreset => "Calidad gráfica",
+        Text::GraphicsLow => "Baja",
+        Text::GraphicsMedium => "Media",
+        Text::GraphicsHigh => "Alta",
+        Text::GraphicsUltra => "Ultra",
+        Text::GraphicsCustom => "Personalizada",
+        Text::OptionRenderScale => "Escala de renderizado",
+        Text::OptionShadowQuality => "Sombras",
+        Text::ShadowsOff => "Desactivadas",
+        Text::ShadowsHard => "Duras",
+        Text::ShadowsSoft => "Suaves",
+        Text::OptionAmbientOcclusion => "Oclusión ambiental",
+        Text::OptionReflectionBounces => "Reflejos en espejos",
+        Text::ReflectionBouncesCost => "Cada rebote en un vóxel espejo ilumina y sombrea otra superficie, así que más rebotes cuestan más donde los espejos llenan la vista",
+        Text::OptionFogDistance => "Distancia de la niebla",
+        Text::FogDistanceHint => "Pasos que avanza cada rayo antes de que la niebla lo oculte. Menos pasos acercan la niebla y renderizan más rápido",
+        Text::OptionMaximumDepth => "Detalle de los vóxeles",
+        Text::MaximumDepthHint => "El nivel más profundo del octree que se dibuja de cerca. Los niveles más bajos renderizan más rápido pero muestran menos detalle",
+        Text::OptionStereo => "Salida 3D",
+        Text::StereoOff => "Desactivada",
+        Text::StereoAnaglyph => "Anaglifo rojo/cian",
+        Text::StereoSideBySide => "Lado a lado",
+        Text::OptionStereoConvergence => "Convergencia 3D (s)",
+        Text::StereoConvergenceHint => "Segundos de vuelo por delante donde el mundo aparece al nivel de la pantalla. Las superficies más cercanas sobresalen, y las más lejanas se hunden detrás",
+        Text::OptionProjection => "Proyección",
+        Text::ProjectionRectilinear => "Rectilínea",
+        Text::ProjectionCylindrical => "Cilíndrica",
+        Text::ProjectionPanini => "Panini",
+        Text::ProjectionHint => "Las proyecciones cilíndrica y Panini estiran mucho menos los bordes de las pantallas ultrapanorámicas, a costa de curvar algunas líneas rectas",
+        Text::OptionHorizontalFovCap => "Límite del campo de visión horizontal",
+        Text::OptionLanguage => "Idioma",
+        Text::OptionColorblindMode => "Modo para daltonismo",
+        Text::OptionUiScale => "Escala de la interfaz",
+        Text::OptionOverlayTheme => "Tema de la interfaz",
+        Text::OverlayThemeDark => "Oscuro",
+        Text::OverlayThemeLight => "Claro",
+        Text::OverlayThemeHighContrast => "Alto contraste",
+        Text::OptionAccentColour => "Color de acento",
+        Text::OptionOverlayAutoHide => "Ocultar la interfaz en reposo",
+        Text::ResetWindowLayout => "Restablecer la disposición de ventanas",
+        Text::OptionPauseOnFocusLoss => "Pausar al perder el foco",
+        Text::OptionFpsLimit => "Limitar la tasa de fotogramas",
+        Text::OptionDynamicResolution => "Adaptar la resolución para mantener",
+        Text::RenderScale => "Renderizando al {}% de la resolución",
+        Text::OptionTemporalReprojection => "Reproyección temporal (avanzado)",
+        Text::TemporalReprojectionHint => "Reutiliza los colores del último fotograma donde las mismas superficies siguen a la vista, trazando solo los píxeles recién descubiertos y una cuarta parte del resto en cada fotograma. Es más rápido, pero las luces y los portales en movimiento pueden retrasarse un poco",
+        Text::OptionCheckerboardRendering => "Renderizado en damero",
+        Text::CheckerboardRenderingHint => "Traza la mitad de los píxeles en cada fotograma con un patrón de damero y completa el resto a partir de sus vecinos. Mucho más rápido en GPU integradas, a costa de detalles algo más suaves",
+        Text::OptionCollisionHull => "Casco de colisión",
+        Text::OptionDebugView => "Vista de depuración",
+        Text::DebugViewOff => "Desactivada",
+        Text::DebugViewSteps => "Pasos de recorrido",
+        Text::DebugViewDepth => "Profundidad del octárbol",
+        Text::DebugViewNormals => "Normales",
+        Text::DebugViewVoxelType => "Tipo de vóxel",
+        Text::OptionVideoFormat => "Formato de vídeo",
+        Text::OptionExclusiveFullscreen => "Pantalla completa exclusiva",
+        Text::OptionMonitor => "Monitor",
+        Text::MonitorCurrent => "Actual",
+        Text::OptionVideoMode => "Resolución",
+        Text::HullPoint => "Solo la cámara",
+        Text::HullWingtips => "Puntas de las alas",
+        Text::HullFull => "Cuerpo completo",
+        Text::OptionFlightModel => "Modelo de vuelo",
+        Text::FlightArcade => "Arcade",
+        Text::FlightAdvanced => "Avanzado (sustentación, resistencia y pérdidas)",
+        Text::OptionMultisampling => "Antialiasing",
+        Text::MultisamplingOff => "Desactivado",
+        Text::OptionGpu => "GPU (se aplica al reiniciar)",
+        Text::GpuAutomatic => "Automática",
+        Text::ColorblindOff => "Desactivado",
+        Text::ColorblindDeuteranopia => "Deuteranopía",
+        Text::ColorblindProtanopia => "Protanopía",
+        Text::ColorblindTritanopia => "Tritanopía",
+        Text::OptionTheme => "Tema del mundo",
+        Text::ThemeClassic => "Clásico",
+        Text::ThemeCanyon => "Cañón",
+        Text::ThemeCity => "Ciudad",
+        Text::ThemeNebula => "Nebulosa",
+        Text::OptionGenerator => "Generador del mundo",
+        Text::GeneratorRecursive => "Recursivo",
+        Text::GeneratorTiles => "Baldosas",
+        Text::OptionSymmetry => "Simetría del mundo",
+        Text::SymmetryOff => "Desactivada",
+        Text::SymmetryMirror => "Especular",
+        Text::SymmetryRotational => "Rotacional",
+        Text::OptionDifficultyBand => "Dificultad de mundos nuevos",
+        Text::DifficultyAny => "Cualquiera",
+        Text::DifficultyEasy => "Fácil",
+        Text::DifficultyMedium => "Media",
+        Text::DifficultyHard => "Difícil",
+        Text::TwitchVoting => "Votación en el chat de Twitch",
+        Text::TwitchChannelHint => "Canal",
+        Text::TwitchConnecting => "Conectando a #{}...",
+        Text::TwitchVotesPending => "{} votos, cierra en {}s",
+        Text::TwitchWaiting => "Esperando !seed <número>, !regen o !hazard",
+        Text::TwitchDisconnected => "Desconectado",
+        Text::OptionCheckForUpdates => "Buscar actualizaciones al iniciar",
+        Text::OptionOffline => "Modo sin conexión",
+        Text::UpdateAvailable => "La versión {} está disponible",
+        Text::UpdateDownload => "Descargar",
+        Text::OptionProfile => "Perfil",
+        Text::ProfileDefault => "Predeterminado",
+        Text::ProfileNameHint => "Nombre del nuevo perfil",
+        Text::ProfileAdd => "Añadir perfil",
+        Text::ExportData => "Exportar datos…",
+        Text::ImportData => "Importar datos…",
+        Text::OptionStreamFiles => "Escribir archivos para streaming",
+        Text::OptionOpenStreamFolder => "Abrir carpeta de streaming",
+        Text::OptionRemoteControl => "Control remoto por HTTP",
+        Text::RemoteControlPort => "Puerto ",
+        Text::OptionPlugins => "Activar complementos",
+        Text::ReloadPlugins => "Recargar",
+        Text::OptionOpenModesFolder => "Abrir carpeta de modos",
+        Text::OptionOpenPluginsFolder => "Abrir carpeta de complementos",
+        Text::OptionSpeedrunTimer => "Cronómetro de speedrun",
+        Text::SpeedrunExportSplits => "Exportar parciales…",
+        Text::SpeedrunSplit => "Nivel {}",
+        Text::LiveSplitServer => "LiveSplit Server",
+        Text::LiveSplitAddressHint => "Dirección",
+        Text::LiveSplitConnecting => "Conectando a {}...",
+        Text::LiveSplitConnected => "Conectado a {}",
+        Text::LiveSplitDisconnected => "Desconectado",
+
+        Text::MapWindowTitle => "Mapa",
+
+        Text::HelpTitle => "Ayuda",
+        Text::HelpAppWindow => "Ventana",
+        Text::HelpToggleFullscreen => "Alternar pantalla completa",
+        Text::HelpEscape => {
+            "En pantalla completa, volver al modo ventana. Si no, cerrar la aplicación"
+        }
+        Text::HelpToggleMap => {
+            "Abrir o cerrar una ventana con un mapa cenital del mundo a tu altura"
+        }
+        Text::HelpToggleConsole => "Alternar la visibilidad de la consola de salida",
+        Text::HelpOverlayWindow => "Ventanas superpuestas",
+        Text::HelpToggleHelp => "Mostrar u ocultar esta ventana de Ayuda",
+        Text::HelpToggleStats => "Mostrar u ocultar la ventana de Estadísticas",
+        Text::HelpToggleOptions => "Mostrar u ocultar la ventana de Opciones",
+        Text::HelpToggleCameraPath => "Mostrar u ocultar la ventana de Trayectoria de Cámara",
+        Text::HelpToggleInspector => "Alternar el Inspector, que describe el vóxel pulsado",
+        Text::HelpGame => "Juego",
+        Text::HelpNewWorld => "Generar un nuevo mundo aleatorio y reiniciar el juego",
+        Text::HelpNextWorld => "Avanzar al mundo dejado al retroceder",
+        Text::HelpPreviousWorld => "Volver al mundo anterior y reiniciar el juego",
+        Text::HelpTogglePhotoMode => "Entrar o salir del modo foto, pausando el juego",
+        Text::HelpHidePhotoPanel => "Mostrar u ocultar la ventana del modo foto",
+        Text::HelpCaptureScreenshot => "Guardar una captura de alta resolución sin la interfaz",
+        Text::HelpToggleAutopilot => "Alternar el piloto automático, terminando la partida actual",
+        Text::HelpAddKeyframe => "Añadir un fotograma clave a la trayectoria",
+        Text::HelpPlayCameraPath => "Reproducir o detener la trayectoria de cámara",
+        Text::HelpSaveSpawnPoint => "Modo práctica: guardar aquí un punto de reaparición",
+        Text::HelpRespawn => "Modo práctica: volver al punto de reaparición",
+        Text::HelpFire => "Disparar un proyectil",
+        Text::HelpNextSong => "Reproducir la siguiente canción de la carpeta de música",
+        Text::HelpPreviousSong => "Reproducir la canción anterior de la carpeta de música",
+        Text::HelpCapturePanorama => "Guardar un panorama de 360° del entorno",
+        Text::HelpToggleRecording => "Iniciar o detener la grabación de un vídeo",
+        Text::HelpToggleSpectator => "Alternar el modo espectador, pilotando una cámara de director",
+        Text::HelpFlight => "Vuelo",
+        Text::HelpPitchDown => "Cabeceo hacia abajo",
+        Text::HelpPitchUp => "Cabeceo hacia arriba",
+        Text::HelpRollLeft => "Alabeo a la izquierda",
+        Text::HelpRollRight => "Alabeo a la derecha",
+        Text::HelpYawLeft => "Guiñada a la izquierda",
+        Text::HelpYawRight => "Guiñada a la derecha",
+        Text::HelpBoost => "Impulso",
+        Text::HelpScoring => "Puntuación",
+        Text::HelpScoringDepth => "Los portales a {} niveles de profundidad dan {}, y {} más por cada nivel más profundo",
+        Text::HelpScoringPortalBonus => "Cada portal da {} de más",
+        Text::HelpScoringDeepestBonus => "Los portales más profundos, a {} niveles, dan {} de más",
+        Text::HelpScoringRings => "Cada anillo da {}",
+        Text::HelpScoringPar => "Terminar un nivel bajo el par da 1 por cada {} segundos de sobra",
+        Text::HelpScoringMultiplier => "Todos los puntos se multiplican por {}",
+        Text::HelpScoringOff => "Este modo no lleva la puntuación",
+
+        Text::RunTitle => "Partida",
+        Text::RunScore => "Puntuación: {}",
+        Text::RunLevel => "Nivel: {}",
+        Text::RunRings => "Anillos: {} / {}",
+        Text::RunFuel => "Combustible",
+        Text::RunTime => "Tiempo: {}s",
+        Text::RunTimeLeft => "Tiempo restante: {}s",
+        Text::RunPar => "Par: {}s ({}s)",
+        Text::RunLastLevel => "Último nivel: {}s ({}s)",
+
+        Text::TutorialTitle => "Tutorial",
+        Text::TutorialPitch => "Inclina el morro arriba y abajo con UP y DOWN, o con la palanca izquierda",
+        Text::TutorialRoll => "Alabea a izquierda y derecha con LEFT y RIGHT, o con la palanca izquierda",
+        Text::TutorialYaw => "Guiña a izquierda y derecha con {} y {}, o con los botones superiores",
+        Text::TutorialBoost => "Mantén SPACE, o el botón frontal inferior, para acelerar",
+        Text::TutorialFindPortal => "Vuela hacia los portales sobre la torre para llegar a tu primer mundo",
+        Text::TutorialSkip => "Saltar el tutorial",
+
+        Text::CameraPathTitle => "Trayectoria de Cámara",
+        Text::CameraPathSummary => "Fotogramas clave: {}, duración: {}s",
+        Text::CameraPathOtherWorld => "Esta trayectoria se creó en el mundo {}",
+        Text::CameraPathAddKeyframe => "Añadir fotograma clave",
+        Text::CameraPathPlay => "Reproducir",
+        Text::CameraPathStop => "Detener",
+        Text::CameraPathClear => "Borrar",
+        Text::CameraPathExport => "Exportar",
+        Text::CameraPathImport => "Importar",
+        Text::CameraPathLoop => "Repetir reproducción",
+        Text::CameraPathHideOverlay => "Ocultar la interfaz durante la reproducción",
+        Text::CameraPathKeyframe => "#{}, segundos hasta el siguiente:",
+
+        Text::PhotoTitle => "Modo Foto",
+        Text::PhotoHint => "Gira con los controles de vuelo y mantén el impulso para avanzar",
+        Text::PhotoExposure => "Exposición",
+        Text::PhotoAperture => "Apertura",
+        Text::PhotoFocusDistance => "Distancia de enfoque",
+        Text::PhotoAutofocus => "Enfoque automático",
+        Text::PhotoCapture => "Capturar",
+
+        Text::StatsTitle => "Estadísticas",
+        Text::StatsFlightTime => "Tiempo de vuelo",
+        Text::StatsDistanceFlown => "Distancia recorrida",
+        Text::StatsCrashes => "Choques",
+        Text::StatsPortalsTaken => "Portales cruzados",
+        Text::StatsBestScore => "Mejor puntuación",
+        Text::StatsFurthestLevel => "Nivel más lejano",
+        Text::StatsCampaignLevels => "Niveles de campaña completados",
+        Text::StatsAchievements => "Logros",
+
+        Text::InspectorTitle => "Inspector",
+        Text::InspectorHint => "Haz clic en el mundo para inspeccionar el primer vóxel bajo el cursor",
+        Text::InspectorNothingHit => "No se alcanzó ningún vóxel",
+        Text::InspectorIndex => "Índice",
+        Text::InspectorFlags => "Indicadores",
+        Text::InspectorDepth => "Profundidad",
+        Text::InspectorDistance => "Distancia",
+        Text::InspectorNormal => "Normal",
+        Text::InspectorColour => "Color",
+        Text::VoxelComplex => "compuesto",
+        Text::VoxelColour => "color",
+        Text::VoxelPortal => "portal",
+        Text::VoxelMirror => "espejo",
+        Text::VoxelReturnPortal => "portal de regreso",
+        Text::VoxelSpeedPortal => "portal de velocidad",
+        Text::VoxelWormhole => "agujero de gusano",
+        Text::VoxelUnknown => "desconocido",
+
+        Text::CampaignTitle => "Campaña",
+        Text::CampaignLevel => "Nivel {}",
+        Text::CampaignGoal => "Consigue {} puntos",
+        Text::CampaignGoalTimed => "Consigue {} puntos en {}s",
+        Text::CampaignPlay => "Jugar",
+        Text::CampaignRestart => "Reiniciar",
+        Text::CampaignLocked => "Completa el nivel anterior para desbloquear este",
+        Text::CampaignLevelComplete => "¡Nivel {} completado!",
+        Text::CrashTitle => "Informe de fallo",
+        Text::CrashMessage => {
+            "El juego falló durante la última ejecución. Se guardó un informe en:"
+        }
+        Text::CrashOpenReport => "Abrir informe",
+        Text::CrashDismiss => "Descartar",
+
+        Text::AchievementUnlocked => "¡Logro desbloqueado!",
+        Text::AchievementFirstPortal => "A través del espejo",
+        Text::AchievementFirstPortalDescription => "Cruza tu primer portal",
+        Text::AchievementDeepDive => "Inmersión profunda",
+        Text::AchievementDeepDiveDescription => "Cruza un portal a la mayor profundidad posible",
+        Text::AchievementMarathon => "Maratón",
+        Text::AchievementMarathonDescription => "Llega al nivel 10 en una sola partida",
+        Text::AchievementSteadyHand => "Pulso firme",
+        Text::AchievementSteadyHandDescription => {
+            "Vuela 5 minutos en una partida sin usar el impulso"
+        }
+        Text::AchievementHighRoller => "A lo grande",
+        Text::AchievementHighRollerDescription => "Consigue 50 puntos en una sola partida",
+        Text::AchievementFrequentFlyer => "Viajero frecuente",
+        Text::AchievementFrequentFlyerDescription => "Pasa una hora en vuelo",
+        Text::AchievementCrashTestDummy => "Maniquí de pruebas",
+        Text::AchievementCrashTestDummyDescription => "Choca 100 veces",
+    }
+}
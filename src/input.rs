@@ -0,0 +1,272 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use winit::event::VirtualKeyCode;
+
+use crate::game::{GamepadResponse, LookDirection};
+
+// Where an action came from. Each source keeps its own steering and boost, so that releasing a key doesn't cancel a
+// stick held at the same time.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Source {
+    Keyboard,
+    Gamepad,
+    Touch,
+    Script,
+    Autopilot,
+}
+const SOURCE_COUNT: usize = 5;
+
+// The sources which fly the camera during a run. Scripts, such as replays and benchmarks, stand in for the player.
+pub const PLAYER_SOURCES: [Source; 4] = [
+    Source::Keyboard,
+    Source::Gamepad,
+    Source::Touch,
+    Source::Script,
+];
+
+// The axes the camera turns about.
+#[derive(Clone, Copy, PartialEq)]
+pub enum FlightAxis {
+    Roll,
+    Pitch,
+    Yaw,
+}
+
+// Game-level inputs, translated from the keyboard, controllers, and touchscreen, or produced by the autopilot and
+// scripts.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    // Steer about an axis in [-1, 1], where positive values roll left, pitch up, and yaw right.
+    Steer {
+        source: Source,
+        axis: FlightAxis,
+        value: f32,
+    },
+    // Hold or release boost.
+    Boost {
+        source: Source,
+        held: bool,
+    },
+    // Flip boost on or off when boosting is toggled rather than held.
+    ToggleBoost,
+    // Scale the flight speed with a throttle in [0, 1], or stop doing so.
+    Throttle(Option<f32>),
+    // Turn the view relative to the flight direction.
+    Look(LookDirection),
+    // Let go of everything held by a source, such as when a controller is disconnected.
+    Release(Source),
+}
+
+// The steering and boost of every source, built up from the actions applied to it.
+// Actions translated from window and controller events are queued, then applied together once per frame.
+#[derive(Default)]
+pub struct Input {
+    steering: [[f32; 3]; SOURCE_COUNT],
+    boost: [bool; SOURCE_COUNT],
+    pub throttle: Option<f32>,
+    pub look: LookDirection,
+    queue: Vec<Action>,
+}
+
+// The flight keys held down, translated into steering for the keyboard source.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Default)]
+pub struct Keyboard {
+    up: bool,
+    down: bool,
+    left: bool,
+    right: bool,
+    a: bool,
+    d: bool,
+}
+
+// The raw gamepad axes which steer the camera, before their response curves are applied.
+#[derive(Default)]
+pub struct GamepadState {
+    pub left_stick: [f32; 2],
+    pub yaw: SharedAxis,
+}
+
+// Helper type for tracking an axis value when one or two buttons control the result.
+pub enum SharedAxis {
+    Single(f32),
+    Split(f32, f32),
+}
+
+impl Input {
+    // Queue an action to be applied with the next frame.
+    pub fn push(&mut self, action: Action) {
+        self.queue.push(action);
+    }
+
+    // Take the actions queued since the last frame.
+    pub fn take_queue(&mut self) -> Vec<Action> {
+        std::mem::take(&mut self.queue)
+    }
+
+    // Apply an action right away, returning whether it was the player steering or boosting, which starts a run.
+    // Toggling boost changes the options, so it is left to the game state.
+    pub fn apply(&mut self, action: Action) -> bool {
+        match action {
+            Action::Steer {
+                source,
+                axis,
+                value,
+            } => {
+                self.steering[source as usize][axis as usize] = value.clamp(-1., 1.);
+                source != Source::Autopilot && value != 0.
+            }
+            Action::Boost { source, held } => {
+                self.boost[source as usize] = held;
+                source != Source::Autopilot && held
+            }
+            Action::Throttle(throttle) => {
+                self.throttle = throttle;
+                false
+            }
+            Action::Look(look) => {
+                self.look = look;
+                false
+            }
+            Action::Release(source) => {
+                self.steering[source as usize] = [0.; 3];
+                self.boost[source as usize] = false;
+                false
+            }
+            Action::ToggleBoost => false,
+        }
+    }
+
+    // The combined steering of the given sources as roll, pitch, and yaw.
+    pub fn steering(&self, sources: &[Source]) -> (f32, f32, f32) {
+        let sum = |axis: FlightAxis| {
+            sources
+                .iter()
+                .map(|&source| self.steering[source as usize][axis as usize])
+                .sum::<f32>()
+                .clamp(-1., 1.)
+        };
+        (
+            sum(FlightAxis::Roll),
+            sum(FlightAxis::Pitch),
+            sum(FlightAxis::Yaw),
+        )
+    }
+
+    // Whether the player is holding boost with any source.
+    pub fn boost_held(&self) -> bool {
+        PLAYER_SOURCES
+            .iter()
+            .any(|&source| self.boost[source as usize])
+    }
+}
+
+impl Keyboard {
+    // Translate a flight key into an action, or none for keys which aren't flight controls.
+    pub fn translate(
+        &mut self,
+        keycode: VirtualKeyCode,
+        pressed: bool,
+        invert_y: bool,
+    ) -> Option<Action> {
+        let held = match keycode {
+            VirtualKeyCode::Up => &mut self.up,
+            VirtualKeyCode::Down => &mut self.down,
+            VirtualKeyCode::Left => &mut self.left,
+            VirtualKeyCode::Right => &mut self.right,
+            VirtualKeyCode::A => &mut self.a,
+            VirtualKeyCode::D => &mut self.d,
+            VirtualKeyCode::Space => {
+                return Some(Action::Boost {
+                    source: Source::Keyboard,
+                    held: pressed,
+                })
+            }
+            _ => return None,
+        };
+        *held = pressed;
+
+        let (axis, value) = match keycode {
+            VirtualKeyCode::Up | VirtualKeyCode::Down => (
+                FlightAxis::Pitch,
+                pitch_sign(invert_y) * (f32::from(self.up) - f32::from(self.down)),
+            ),
+            VirtualKeyCode::Left | VirtualKeyCode::Right => (
+                FlightAxis::Roll,
+                f32::from(self.left) - f32::from(self.right),
+            ),
+            _ => (FlightAxis::Yaw, f32::from(self.d) - f32::from(self.a)),
+        };
+        Some(Action::Steer {
+            source: Source::Keyboard,
+            axis,
+            value,
+        })
+    }
+}
+
+impl GamepadState {
+    // The combined value of the yaw inputs, whether an axis or a pair of buttons.
+    pub fn raw_yaw(&self) -> f32 {
+        match self.yaw {
+            SharedAxis::Single(value) => value,
+            SharedAxis::Split(left, right) => right - left,
+        }
+    }
+
+    // Translate the raw axes into steering through their response curves.
+    // A pair of yaw buttons is either fully on or off, so it skips the curve.
+    pub fn steering(&self, response: &GamepadResponse, invert_y: bool) -> [Action; 3] {
+        let steer = |axis, value| Action::Steer {
+            source: Source::Gamepad,
+            axis,
+            value,
+        };
+        [
+            steer(FlightAxis::Roll, -response.roll.apply(self.left_stick[0])),
+            steer(
+                FlightAxis::Pitch,
+                pitch_sign(invert_y) * response.pitch.apply(self.left_stick[1]),
+            ),
+            steer(
+                FlightAxis::Yaw,
+                match self.yaw {
+                    SharedAxis::Single(value) => response.yaw.apply(value),
+                    SharedAxis::Split(left, right) => right - left,
+                },
+            ),
+        ]
+    }
+}
+
+// Make managaing the gamepad state easier with default axis value and type.
+impl Default for SharedAxis {
+    fn default() -> Self {
+        Self::Single(0.)
+    }
+}
+
+// Pushing forward on a stick or pressing up pitches down, unless the Y axis is inverted.
+pub fn pitch_sign(invert_y: bool) -> f32 {
+    if invert_y {
+        1.
+    } else {
+        -1.
+    }
+}
@@ -0,0 +1,55 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::Rotation;
+
+use crate::game;
+use crate::voxels::{self, VoxelCompact};
+
+// A debugging tool for checking what the generator produced, which reports the voxel under a clicked pixel.
+#[derive(Default)]
+pub struct Inspector {
+    pub inspected: Option<Inspection>,
+}
+
+// The voxel found under a clicked pixel, copied so that it outlives the world it came from.
+pub struct Inspection {
+    pub hit: voxels::Hit,
+    pub voxel: VoxelCompact,
+}
+
+impl Inspector {
+    // Cast a ray from the camera through the given pixel of a window of the given size, matching the ray that the
+    // shader marches for that pixel, and inspect the first voxel it hits.
+    pub fn inspect(
+        &mut self,
+        game: &game::State,
+        octree: &[VoxelCompact],
+        window_size: [f32; 2],
+        pixel: [f32; 2],
+    ) {
+        let lens = game.lens(window_size[0] / window_size[1]);
+        let view = [0, 1].map(|i| 2. * pixel[i] / window_size[i] - 1.);
+        let direction = game.camera_quaternion.rotate_vector(lens.ray(view));
+        self.inspected =
+            voxels::raycast(game.camera_position, direction, octree).map(|hit| Inspection {
+                hit,
+                voxel: octree[hit.index as usize],
+            });
+    }
+}
@@ -0,0 +1,105 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use winit::event::VirtualKeyCode;
+
+// Keys which are read by their position on the keyboard rather than the letter they type, so that the yaw keys sit
+// beside each other on any layout.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PositionalKey {
+    YawLeft,
+    YawRight,
+}
+
+// The names the current layout gives to the positional keys, learned as they are pressed.
+#[derive(Default)]
+pub struct KeyLabels {
+    yaw_left: Option<String>,
+    yaw_right: Option<String>,
+}
+
+impl PositionalKey {
+    pub const ALL: [PositionalKey; 2] = [PositionalKey::YawLeft, PositionalKey::YawRight];
+
+    // The key in this position on a QWERTY layout, which the rest of the app reads it as.
+    pub fn qwerty(self) -> VirtualKeyCode {
+        match self {
+            PositionalKey::YawLeft => VirtualKeyCode::A,
+            PositionalKey::YawRight => VirtualKeyCode::D,
+        }
+    }
+
+    // The scancode of the key in this position, which macOS numbers differently from the PC scancodes used by
+    // Windows and Linux.
+    fn scancode(self) -> u32 {
+        #[cfg(target_os = "macos")]
+        match self {
+            PositionalKey::YawLeft => 0x00,
+            PositionalKey::YawRight => 0x02,
+        }
+        #[cfg(not(target_os = "macos"))]
+        match self {
+            PositionalKey::YawLeft => 0x1E,
+            PositionalKey::YawRight => 0x20,
+        }
+    }
+
+    // The positional key with the given scancode, if any.
+    pub fn from_scancode(scancode: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.scancode() == scancode)
+    }
+}
+
+impl KeyLabels {
+    // Remember what the layout calls a positional key when it is pressed.
+    pub fn observe(&mut self, scancode: u32, keycode: VirtualKeyCode) {
+        let label = match PositionalKey::from_scancode(scancode) {
+            Some(PositionalKey::YawLeft) => &mut self.yaw_left,
+            Some(PositionalKey::YawRight) => &mut self.yaw_right,
+            None => return,
+        };
+        *label = Some(format!("{keycode:?}").to_lowercase());
+    }
+
+    // The name of the positional key on the current layout, or its QWERTY name until it has been pressed.
+    pub fn label(&self, key: PositionalKey) -> &str {
+        match key {
+            PositionalKey::YawLeft => self.yaw_left.as_deref().unwrap_or("a"),
+            PositionalKey::YawRight => self.yaw_right.as_deref().unwrap_or("d"),
+        }
+    }
+}
+
+// Translate a key press so that the positional keys are read by where they are rather than what they type.
+// Letters which the positional keys type on QWERTY, but which the layout puts elsewhere, are ignored.
+// Browsers already report keys by position, so they are read as is.
+pub fn positional(scancode: u32, keycode: VirtualKeyCode) -> Option<VirtualKeyCode> {
+    if cfg!(target_arch = "wasm32") {
+        return Some(keycode);
+    }
+    match PositionalKey::from_scancode(scancode) {
+        Some(key) => Some(key.qwerty()),
+        None if PositionalKey::ALL
+            .into_iter()
+            .any(|key| key.qwerty() == keycode) =>
+        {
+            None
+        }
+        None => Some(keycode),
+    }
+}
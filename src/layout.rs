@@ -0,0 +1,111 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::BTreeMap;
+
+use egui::{Color32, Context, Id, Pos2, Visuals};
+
+// Seconds the overlay takes to fade in or out when hidden automatically.
+const FADE_DURATION: f32 = 0.5;
+
+// The windows whose positions are remembered across sessions.
+pub const SAVED_WINDOWS: [&str; 3] = ["run_window", "options_window", "help_window"];
+
+// Where each saved window was last left, in points from the top-left of the window, keyed by its id.
+pub type WindowPositions = BTreeMap<String, [f32; 2]>;
+
+// Windows which open where they were last left, if they have been seen before.
+pub trait SavedPosition {
+    fn saved_position(self, id: &str, positions: &WindowPositions) -> Self;
+}
+
+impl SavedPosition for egui::Window<'_> {
+    fn saved_position(self, id: &str, positions: &WindowPositions) -> Self {
+        match positions.get(id) {
+            Some(&position) => self.default_pos(Pos2::from(position)),
+            None => self,
+        }
+    }
+}
+
+// Remember where the saved windows are, after they have been drawn this frame.
+pub fn remember(ctx: &Context, positions: &mut WindowPositions) {
+    for id in SAVED_WINDOWS {
+        if let Some(rect) = ctx.memory(|memory| memory.area_rect(Id::new(id))) {
+            positions.insert(id.to_owned(), rect.min.into());
+        }
+    }
+}
+
+// Forget where every window was left, returning them to where they first open.
+pub fn reset(ctx: &Context, positions: &mut WindowPositions) {
+    ctx.memory_mut(egui::Memory::reset_areas);
+    positions.clear();
+}
+
+// Fade the overlay towards being shown or hidden, returning its opacity. The colours of the windows and
+// widgets are faded from the given visuals.
+pub fn fade(ctx: &Context, shown: bool, visuals: Visuals) -> f32 {
+    let opacity = ctx.animate_bool_with_time(Id::new("overlay_fade"), shown, FADE_DURATION);
+    let visuals = faded_visuals(visuals, opacity);
+    if ctx.style().visuals != visuals {
+        ctx.set_visuals(visuals);
+    }
+    opacity
+}
+
+// The visuals with every colour made more transparent.
+fn faded_visuals(mut visuals: Visuals, opacity: f32) -> Visuals {
+    if opacity >= 1. {
+        return visuals;
+    }
+    let fade = |color: &mut Color32| *color = color.gamma_multiply(opacity);
+    if let Some(color) = &mut visuals.override_text_color {
+        fade(color);
+    }
+    for widget in [
+        &mut visuals.widgets.noninteractive,
+        &mut visuals.widgets.inactive,
+        &mut visuals.widgets.hovered,
+        &mut visuals.widgets.active,
+        &mut visuals.widgets.open,
+    ] {
+        fade(&mut widget.bg_fill);
+        fade(&mut widget.weak_bg_fill);
+        fade(&mut widget.bg_stroke.color);
+        fade(&mut widget.fg_stroke.color);
+    }
+    for color in [
+        &mut visuals.selection.bg_fill,
+        &mut visuals.selection.stroke.color,
+        &mut visuals.hyperlink_color,
+        &mut visuals.faint_bg_color,
+        &mut visuals.extreme_bg_color,
+        &mut visuals.code_bg_color,
+        &mut visuals.warn_fg_color,
+        &mut visuals.error_fg_color,
+        &mut visuals.window_shadow.color,
+        &mut visuals.window_fill,
+        &mut visuals.window_stroke.color,
+        &mut visuals.panel_fill,
+        &mut visuals.popup_shadow.color,
+    ] {
+        fade(color);
+    }
+    visuals
+}
@@ -0,0 +1,125 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::VecDeque;
+
+use bytemuck::{Pod, Zeroable};
+use cgmath::Vector3;
+
+use crate::theme::Theme;
+use crate::voxels::{VoxelCompact, CELL_CENTERS, NULL_VOXEL_INDEX};
+
+// The number of lights the shader reads. Must match `maximumLights` in `ray_march_voxels.frag`.
+pub const MAXIMUM_LIGHTS: usize = 16;
+
+// How many voxels are visited while looking for portals. The octree is recursive, so the search is
+// breadth-first and bounded, finding the largest portals first.
+const MAXIMUM_SEARCHED_VOXELS: usize = 1 << 16;
+
+// Portal lights reach a few times the size of their voxel, brighter than the portal's own colour.
+const PORTAL_GLOW_RANGE: f32 = 4.;
+const PORTAL_GLOW_INTENSITY: f32 = 1.5;
+
+// A light in the world. Directional lights have a `w` of 0 and shine along the direction `position`
+// points towards. Point lights have a `w` of 1 and fade out at the range stored in the `w` of `colour`.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+pub struct Light {
+    pub position: [f32; 4],
+    pub colour: [f32; 4],
+}
+
+// The lights of a world and the colours of its sky, laid out as the `Lights` uniform block in
+// `ray_march_voxels.frag`. They are found once each time a world is generated, and don't cast shadows.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct WorldLights {
+    pub lights: [Light; MAXIMUM_LIGHTS],
+    pub sky_colour: [f32; 4],
+    pub ground_colour: [f32; 4],
+    pub fog_colour: [f32; 4],
+    pub count: u32,
+    _padding: [u32; 3],
+}
+
+// The direction towards the sun, which circles the world over time and is the only light to cast shadows.
+pub fn sun_direction(time: f32) -> Vector3<f32> {
+    let delta = time / -20.;
+    Vector3::new(0.9165 * delta.sin(), 0.4, 0.9165 * delta.cos())
+}
+
+impl WorldLights {
+    // Add a glowing point light at each of the largest portals in the octree, under the theme's sky.
+    pub fn discover(octree: &[VoxelCompact], theme: &Theme) -> Self {
+        let mut lights = WorldLights {
+            sky_colour: theme.sky_colour,
+            ground_colour: theme.ground_colour,
+            fog_colour: theme.fog_colour,
+            ..WorldLights::zeroed()
+        };
+        if octree.is_empty() {
+            return lights;
+        }
+
+        let mut queue = VecDeque::from([(0u32, Vector3::new(0., 0., 0.), 1f32)]);
+        let mut searched = 0;
+        while let Some((index, center, half_size)) = queue.pop_front() {
+            searched += 1;
+            if searched > MAXIMUM_SEARCHED_VOXELS {
+                break;
+            }
+
+            let voxel = &octree[index as usize];
+            match voxel.flags {
+                0 => {
+                    for (&child, offset) in voxel.children.iter().zip(CELL_CENTERS) {
+                        if child != NULL_VOXEL_INDEX {
+                            queue.push_back((child, center + offset * half_size, 0.5 * half_size));
+                        }
+                    }
+                }
+                2 | 4..=6 => {
+                    let [r, g, b, _] = voxel.average_colour;
+                    lights.push(Light {
+                        position: [center.x, center.y, center.z, 1.],
+                        colour: [
+                            PORTAL_GLOW_INTENSITY * r,
+                            PORTAL_GLOW_INTENSITY * g,
+                            PORTAL_GLOW_INTENSITY * b,
+                            PORTAL_GLOW_RANGE * half_size,
+                        ],
+                    });
+                    if lights.count as usize == MAXIMUM_LIGHTS {
+                        break;
+                    }
+                }
+                _ => (),
+            }
+        }
+        log::debug!(target: "lighting", "Discovered lights={}", lights.count);
+        lights
+    }
+
+    // Add a light, ignoring it if the array is already full.
+    fn push(&mut self, light: Light) {
+        if let Some(slot) = self.lights.get_mut(self.count as usize) {
+            *slot = light;
+            self.count += 1;
+        }
+    }
+}
@@ -0,0 +1,113 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+    io::{self, Write},
+    net::TcpStream,
+    sync::mpsc,
+    thread,
+};
+
+// The address that LiveSplit Server listens on unless it has been changed in LiveSplit.
+pub const DEFAULT_ADDRESS: &str = "localhost:16834";
+
+// Connection state of the LiveSplit client, shown in the Options window.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Status {
+    Connecting,
+    Connected,
+    Disconnected,
+}
+
+// Messages sent from the connection thread to the app.
+enum ClientEvent {
+    Connected,
+    Disconnected(String),
+}
+
+// Drives a LiveSplit Server's timer over TCP from a background thread, so that a slow connection never stalls
+// a frame. Commands sent before the connection is made are written once it is.
+pub struct LiveSplitClient {
+    pub address: String,
+    pub status: Status,
+    commands: mpsc::Sender<String>,
+    events: mpsc::Receiver<ClientEvent>,
+}
+
+impl LiveSplitClient {
+    // Connect to the LiveSplit Server at the given address.
+    pub fn new(address: &str) -> Self {
+        let address = address.trim().to_owned();
+        let (commands, command_receiver) = mpsc::channel();
+        let (sender, events) = mpsc::channel();
+
+        let thread_address = address.clone();
+        thread::spawn(move || {
+            if let Err(e) = forward(&thread_address, &command_receiver, &sender) {
+                // The receiver may already be gone if the client was dropped.
+                let _ = sender.send(ClientEvent::Disconnected(e.to_string()));
+            }
+        });
+
+        Self {
+            address,
+            status: Status::Connecting,
+            commands,
+            events,
+        }
+    }
+
+    // Queue a command for the server, such as `split` or `setgametime 12.5`.
+    pub fn send(&self, command: impl Into<String>) {
+        // Commands are dropped once the connection is lost, which the status already shows.
+        let _ = self.commands.send(command.into());
+    }
+
+    // Update the status from the connection thread.
+    pub fn poll(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                ClientEvent::Connected => {
+                    self.status = Status::Connected;
+                    log::info!(target: "livesplit", "Connected to LiveSplit Server address={}", self.address);
+                }
+                ClientEvent::Disconnected(reason) => {
+                    self.status = Status::Disconnected;
+                    log::warn!(target: "livesplit", "LiveSplit Server disconnected reason=\"{reason}\"");
+                }
+            }
+        }
+    }
+}
+
+// Write each command to the server as a line until the client is dropped, which closes the command channel.
+fn forward(
+    address: &str,
+    commands: &mpsc::Receiver<String>,
+    sender: &mpsc::Sender<ClientEvent>,
+) -> io::Result<()> {
+    let mut stream = TcpStream::connect(address)?;
+    stream.set_nodelay(true)?;
+    if sender.send(ClientEvent::Connected).is_err() {
+        return Ok(());
+    }
+    while let Ok(command) = commands.recv() {
+        write!(stream, "{command}\r\n")?;
+    }
+    Ok(())
+}
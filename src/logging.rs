@@ -0,0 +1,225 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+// Logging constants.
+const LOG_FILE_NAME: &str = "log.txt";
+const MAX_LOG_FILE_SIZE: u64 = 1024 * 1024;
+const ROTATED_LOG_FILE_COUNT: u32 = 3;
+const RECENT_LINE_COUNT: usize = 64;
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+// Logger which writes every record to both the console and a size-rotated file in the app directory.
+struct Logger {
+    level: LevelFilter,
+    file: Mutex<Option<RotatingFile>>,
+    recent: Mutex<VecDeque<String>>,
+}
+
+// A log file which is moved aside once it grows past `MAX_LOG_FILE_SIZE`.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+// Install the global logger. Verbose runs also record debug messages.
+pub fn init(verbose: bool) {
+    let level = if verbose {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    };
+
+    // A missing log file shouldn't prevent the game from running, so only report the failure.
+    let path = crate::voxel_flight_simulator::app_data_dir().join(LOG_FILE_NAME);
+    let file = match RotatingFile::open(path) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("Failed to open log file: {e}");
+            None
+        }
+    };
+
+    let logger = LOGGER.get_or_init(|| Logger {
+        level,
+        file: Mutex::new(file),
+        recent: Mutex::new(VecDeque::with_capacity(RECENT_LINE_COUNT)),
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+// Get the most recently logged lines, oldest first. Doesn't block so that it is safe to use while panicking.
+pub fn recent_lines() -> Vec<String> {
+    LOGGER
+        .get()
+        .and_then(|logger| logger.recent.try_lock().ok())
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "{} {:<5} {}: {}\n",
+            format_timestamp(SystemTime::now()),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+
+        // Warnings and errors go to stderr so they stand out in the console.
+        #[cfg(not(target_arch = "wasm32"))]
+        if record.level() <= Level::Warn {
+            eprint!("{line}");
+        } else {
+            print!("{line}");
+        }
+
+        // Browsers discard the standard streams, so use the page's console instead.
+        #[cfg(target_arch = "wasm32")]
+        {
+            let message = line.trim_end().into();
+            if record.level() <= Level::Warn {
+                web_sys::console::warn_1(&message);
+            } else {
+                web_sys::console::log_1(&message);
+            }
+        }
+
+        if let Ok(mut guard) = self.file.lock() {
+            if let Some(file) = guard.as_mut() {
+                if let Err(e) = file.write_line(&line) {
+                    eprintln!("Couldn't write to file: {e}");
+                }
+            }
+        }
+
+        // Remember the latest lines for crash reports.
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == RECENT_LINE_COUNT {
+                recent.pop_front();
+            }
+            recent.push_back(line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut guard) = self.file.lock() {
+            if let Some(file) = guard.as_mut() {
+                let _ = file.file.flush();
+            }
+        }
+    }
+}
+
+impl RotatingFile {
+    // Open a log file for appending, rotating it first if it is already too large.
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        let size = fs::metadata(&path).map_or(0, |m| m.len());
+        let mut file = Self {
+            file: Self::open_append(&path)?,
+            path,
+            size,
+        };
+        if file.size >= MAX_LOG_FILE_SIZE {
+            file.rotate()?;
+        }
+        Ok(file)
+    }
+
+    fn open_append(path: &Path) -> std::io::Result<File> {
+        fs::OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    // Append a line to the file, rotating when the size limit is reached.
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        if self.size >= MAX_LOG_FILE_SIZE {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    // Shift `log.txt` to `log.1.txt`, `log.1.txt` to `log.2.txt`, etc., dropping the oldest file.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let rotated_path = |i: u32| self.path.with_extension(format!("{i}.txt"));
+        for i in (1..ROTATED_LOG_FILE_COUNT).rev() {
+            let from = rotated_path(i);
+            if from.exists() {
+                fs::rename(&from, rotated_path(i + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(1))?;
+
+        self.file = Self::open_append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+// Format a time as an ISO-8601 UTC timestamp with millisecond precision.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (hour, minute, second) = ((seconds / 3600) % 24, (seconds / 60) % 60, seconds % 60);
+
+    // Convert days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html.
+    #[allow(clippy::cast_possible_wrap)]
+    let z = (seconds / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}.{:03}Z",
+        since_epoch.subsec_millis()
+    )
+}
@@ -21,19 +21,120 @@
 
 use voxel_flight_simulator::App;
 use winit::{
-    event::{Event, KeyboardInput, WindowEvent},
-    event_loop::ControlFlow,
+    event::{ElementState, Event, KeyboardInput, MouseButton, WindowEvent},
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
 };
 
+// Browsers only offer WebGPU, which is provided by the wgpu backend.
+#[cfg(all(target_arch = "wasm32", not(feature = "wgpu")))]
+compile_error!("Web builds require the `wgpu` feature.");
+
+// Steam only runs on desktops.
+#[cfg(all(target_arch = "wasm32", feature = "steam"))]
+compile_error!("Web builds can't use the `steam` feature.");
+
+mod achievements;
+mod ai;
+#[cfg(not(target_arch = "wasm32"))]
+mod audio;
+mod autopilot;
+#[cfg(not(target_arch = "wasm32"))]
+mod backup;
+mod campaign;
+mod carving;
+mod cinematic;
+mod cli;
+mod controllers;
+mod crash;
+mod display;
+#[cfg(not(target_arch = "wasm32"))]
+mod frame_graph;
 mod game;
+mod graphics;
+mod haptics;
+mod hazards;
+#[cfg(not(target_arch = "wasm32"))]
 mod helens;
+mod history;
+mod i18n;
+mod input;
+mod inspector;
+mod keys;
+mod layout;
+mod lighting;
+mod livesplit;
+mod logging;
+mod mixer;
+mod modes;
+mod overlay_theme;
+mod palette;
+mod particles;
+mod persistence;
+mod photo;
+#[cfg(not(target_arch = "wasm32"))]
+mod plugins;
+mod popups;
+mod portal_values;
+mod practice;
+mod preview;
+mod profiles;
+mod projectiles;
+mod projection;
+#[cfg(not(target_arch = "wasm32"))]
+mod recording;
+#[cfg(not(target_arch = "wasm32"))]
+mod remote;
+mod renderer;
+mod resolution;
+mod rings;
+#[cfg(not(target_arch = "wasm32"))]
+mod screenshot;
+mod seed;
+mod spectator;
+mod speedrun;
+mod stats;
+#[cfg(feature = "steam")]
+mod steam;
+#[cfg(not(target_arch = "wasm32"))]
+mod stream_files;
+mod theme;
+mod touch;
+mod trail;
+mod tutorial;
+mod twitch;
+#[cfg(not(target_arch = "wasm32"))]
+mod updates;
 mod voxel_flight_simulator;
 mod voxels;
+#[cfg(feature = "wgpu")]
+mod wgpu_backend;
+mod wind;
+#[cfg(feature = "xr")]
+mod xr;
 
 fn main() {
+    // Parse command-line arguments and start logging before anything else can fail.
+    let args = cli::Args::parse();
+    logging::init(args.verbose);
+    crash::install_panic_hook();
+
     // Initialize the app window, engine, and game state.
-    let (mut app, event_loop, mut gui, mut window_manager) = App::new();
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let (app, event_loop) = pollster::block_on(App::new(args));
+        run(app, event_loop);
+    }
 
+    // Browsers can't block while the GPU is set up, so the app starts once setup completes.
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(async move {
+        let (app, event_loop) = App::new(args).await;
+        run(app, event_loop);
+    });
+}
+
+// Run the event loop until the app exits.
+fn run(mut app: App, event_loop: EventLoop<()>) {
     // Load icon from file resources.
     let icon = {
         // The data below is read at compile time from the file.
@@ -56,32 +157,56 @@ fn main() {
         ) {
             Ok(icon) => Some(icon),
             Err(e) => {
-                println!("Failed to parse icon: {e:?}");
+                log::warn!("Failed to parse icon: {e:?}");
                 None
             }
         }
     };
 
     // Apply icon to the window.
-    window_manager
-        .get_primary_window()
-        .unwrap()
-        .set_window_icon(icon);
-
-    // Run event loop until app exits.
-    event_loop.run(move |event, _, control_flow| {
-        let window_size = window_manager.get_primary_renderer().unwrap().window_size();
-        if window_size.contains(&0.0f32) {
+    app.renderer.window().set_window_icon(icon);
+
+    let event_handler = move |event: Event<()>,
+                              window_target: &EventLoopWindowTarget<()>,
+                              control_flow: &mut ControlFlow| {
+        // Save state before the app exits, even when the window is minimized.
+        if let Event::LoopDestroyed = event {
+            #[cfg(not(target_arch = "wasm32"))]
+            app.stop_recording();
+            app.save_on_exit();
+            return;
+        }
+
+        // Keep the exit request from being replaced by the frame-rate limiter.
+        if let ControlFlow::ExitWithCode(_) = control_flow {
+            return;
+        }
+
+        if app.renderer.window_size().contains(&0.0f32) {
             return;
         }
         match event {
+            // The map window only needs to follow its size, and closing it leaves the game running.
+            Event::WindowEvent { window_id, event } if app.renderer.is_map_window(window_id) => {
+                match event {
+                    WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
+                        app.renderer.resize_map();
+                    }
+                    WindowEvent::CloseRequested => app.map_window = false,
+                    WindowEvent::Focused(focused) => {
+                        // Keep rendering at full rate and the game running while the map is looked at.
+                        app.set_focused(focused);
+                    }
+                    _ => (),
+                }
+            }
             Event::WindowEvent { event, .. } => {
                 // Update the egui with our events so the UI can work!
-                let pass_events_to_game = !gui.update(&event);
+                let pass_events_to_game = !app.renderer.update_gui(&event);
                 match event {
                     WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
                         // Make app aware of the new window size.
-                        app.resize(&mut window_manager);
+                        app.renderer.resize();
                     }
                     WindowEvent::CloseRequested => {
                         // The window has been instructed to close.
@@ -91,30 +216,69 @@ fn main() {
                         input:
                             KeyboardInput {
                                 state,
+                                scancode,
                                 virtual_keycode: Some(keycode),
                                 ..
                             },
                         ..
                     } => {
                         if pass_events_to_game {
-                            app.handle_keyboard_inputs(
-                                keycode,
-                                state,
-                                &mut window_manager,
-                                control_flow,
-                            );
+                            app.handle_keyboard_inputs(keycode, scancode, state, control_flow);
+                        }
+                    }
+                    WindowEvent::CursorMoved { position, .. } => {
+                        app.overlay.cursor_position = position.into();
+                        app.overlay.last_cursor_movement = web_time::Instant::now();
+                    }
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    } => {
+                        if pass_events_to_game {
+                            app.inspect_cursor();
                         }
                     }
-                    WindowEvent::CursorMoved { .. } => {
-                        app.overlay.last_cursor_movement = std::time::Instant::now();
+                    WindowEvent::Touch(touch) => app.handle_touch(&touch, !pass_events_to_game),
+                    WindowEvent::Focused(focused) => {
+                        // Throttle rendering, and optionally pause the game, while the window is in the background.
+                        app.set_focused(focused);
                     }
                     _ => (),
                 }
             }
 
-            // Update the app state and render a frame.
-            Event::MainEventsCleared => app.tock_frame(&mut gui, &mut window_manager, window_size),
+            // Update the app state and render a frame, unless the frame rate is limited and it's too soon.
+            Event::MainEventsCleared => match app.time_until_next_frame() {
+                Some(wait) => *control_flow = wait_for(wait),
+                None => {
+                    *control_flow = ControlFlow::Poll;
+                    app.update_map_window(window_target);
+                    app.update_multisampling(window_target);
+                    app.tock_frame();
+                }
+            },
+
             _ => (),
         }
-    });
+    };
+
+    // Browsers drive the event loop themselves, so there the handler is only registered.
+    #[cfg(not(target_arch = "wasm32"))]
+    event_loop.run(event_handler);
+    #[cfg(target_arch = "wasm32")]
+    winit::platform::web::EventLoopExtWebSys::spawn(event_loop, event_handler);
+}
+
+// Sleep until the next frame is due instead of spinning.
+#[cfg(not(target_arch = "wasm32"))]
+fn wait_for(duration: std::time::Duration) -> ControlFlow {
+    ControlFlow::WaitUntil(std::time::Instant::now() + duration)
+}
+
+// Winit's web timers use their own clock type, so keep polling and let frames be skipped instead.
+// Browsers already throttle pages in the background.
+#[cfg(target_arch = "wasm32")]
+fn wait_for(_duration: std::time::Duration) -> ControlFlow {
+    ControlFlow::Poll
 }
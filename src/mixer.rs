@@ -0,0 +1,71 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+pub const VOLUME_RANGE: std::ops::RangeInclusive<f32> = 0.0..=1.;
+pub const DEFAULT_MUSIC_VOLUME: f32 = 0.5;
+pub const DEFAULT_EFFECTS_VOLUME: f32 = 0.8;
+
+// Volumes of the music and sound effects, each scaled by the master volume. Music and effects are each off when
+// they have no volume.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct AudioMixer {
+    pub master: f32,
+    pub music: Option<f32>,
+    pub effects: Option<f32>,
+    pub mute_on_focus_loss: bool,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        Self {
+            master: 1.,
+            music: None,
+            effects: Some(DEFAULT_EFFECTS_VOLUME),
+            mute_on_focus_loss: true,
+        }
+    }
+}
+
+impl AudioMixer {
+    // Whether anything is to be played, so that audio output is needed.
+    pub fn is_enabled(&self) -> bool {
+        self.music.is_some() || self.effects.is_some()
+    }
+
+    // The volume to play music at, silenced while the window is in the background if the options ask to be.
+    pub fn music_volume(&self, focused: bool) -> f32 {
+        self.music.map_or(0., |volume| self.scale(volume, focused))
+    }
+
+    // The volume to play sound effects at, silenced the same way as music.
+    pub fn effects_volume(&self, focused: bool) -> f32 {
+        self.effects
+            .map_or(0., |volume| self.scale(volume, focused))
+    }
+
+    fn scale(&self, volume: f32, focused: bool) -> f32 {
+        if self.mute_on_focus_loss && !focused {
+            0.
+        } else {
+            self.master * volume
+        }
+    }
+}
@@ -0,0 +1,247 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::Deserialize;
+
+use crate::game::{self, GameMode, Options, Run, ScoringRules};
+use crate::theme::WorldTheme;
+use crate::voxels::{DifficultyBand, Symmetry, WorldGenerator};
+
+// Custom mode constants.
+#[cfg(not(target_arch = "wasm32"))]
+const MODE_DIRECTORY: &str = "modes";
+#[cfg(not(target_arch = "wasm32"))]
+const MODE_EXTENSION: &str = "toml";
+
+// What happens when the camera flies into a voxel during a run.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq)]
+pub enum CollisionRule {
+    // The run ends and the camera returns to the start.
+    #[default]
+    EndRun,
+    // The camera returns to the world's saved spawn point, keeping the run going.
+    Respawn,
+    // The camera backs away from the obstacle and turns around.
+    TurnAround,
+}
+
+// How the time of a run is kept.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq)]
+pub enum TimerRule {
+    // The time since the run started is shown.
+    #[default]
+    Elapsed,
+    // No time is shown.
+    Hidden,
+    // The time left of the mode's time limit is shown, and the run ends once it runs out.
+    Countdown,
+}
+
+// The rules a run is played by, from a built-in game mode or a mode file.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ModeRules {
+    pub collision: CollisionRule,
+    // Whether score is kept, along with statistics, achievements, rings, and timers.
+    pub scoring: bool,
+    pub point_multiplier: u32,
+    pub speed_factor: f32,
+    pub sun_rate: f32,
+    pub timer: TimerRule,
+    // Seconds, for countdown timers.
+    pub time_limit: f32,
+}
+
+// Options for generating worlds, replacing the current options when a mode is chosen.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct GenerationRules {
+    pub generator: Option<WorldGenerator>,
+    pub theme: Option<WorldTheme>,
+    pub symmetry: Option<Symmetry>,
+    pub difficulty_band: Option<DifficultyBand>,
+}
+
+// A mode described by a file in the modes folder, named by its file unless it gives a name.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct CustomMode {
+    pub name: String,
+    #[serde(flatten)]
+    pub rules: ModeRules,
+    pub generation: GenerationRules,
+}
+
+// The custom modes found in the modes folder when the game started.
+#[derive(Default)]
+pub struct CustomModes {
+    pub modes: Vec<CustomMode>,
+}
+
+impl ModeRules {
+    const STANDARD: Self = Self {
+        collision: CollisionRule::EndRun,
+        scoring: true,
+        point_multiplier: 1,
+        speed_factor: 1.,
+        sun_rate: 1.,
+        timer: TimerRule::Elapsed,
+        time_limit: 0.,
+    };
+
+    // How points are scored by the mode's runs. Easy worlds have no bonus for the deepest portals, and every
+    // portal in hard worlds scores an extra point.
+    pub fn scoring(&self, difficulty_band: Option<DifficultyBand>) -> ScoringRules {
+        let standard = ScoringRules::default();
+        ScoringRules {
+            deepest_bonus: match difficulty_band {
+                Some(DifficultyBand::Easy) => 0,
+                _ => standard.deepest_bonus,
+            },
+            portal_bonus: u32::from(difficulty_band == Some(DifficultyBand::Hard)),
+            multiplier: self.point_multiplier,
+            ..standard
+        }
+    }
+
+    // Seconds left before a countdown ends the run, if the run has one and has started.
+    pub fn time_left(&self, run: &Run) -> Option<f32> {
+        let start = run.start.filter(|_| self.timer == TimerRule::Countdown)?;
+        Some(self.time_limit - start.elapsed().as_secs_f32())
+    }
+}
+
+impl Default for ModeRules {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+impl GameMode {
+    // The rules of the built-in mode.
+    pub fn rules(self) -> ModeRules {
+        match self {
+            GameMode::Standard | GameMode::Campaign => ModeRules::STANDARD,
+            GameMode::Practice => ModeRules {
+                collision: CollisionRule::Respawn,
+                ..ModeRules::STANDARD
+            },
+            GameMode::Zen => ModeRules {
+                collision: CollisionRule::TurnAround,
+                scoring: false,
+                speed_factor: game::ZEN_SPEED_FACTOR,
+                sun_rate: game::ZEN_SUN_RATE,
+                timer: TimerRule::Hidden,
+                ..ModeRules::STANDARD
+            },
+        }
+    }
+}
+
+impl GenerationRules {
+    // Use the mode's generation options in place of the current ones.
+    pub fn apply(&self, options: &mut Options) {
+        if let Some(generator) = self.generator {
+            options.generator = generator;
+        }
+        if let Some(theme) = self.theme {
+            options.theme = theme;
+        }
+        if let Some(symmetry) = self.symmetry {
+            options.symmetry = symmetry;
+        }
+        if self.difficulty_band.is_some() {
+            options.difficulty_band = self.difficulty_band;
+        }
+    }
+}
+
+impl CustomModes {
+    // Load every mode file in the modes folder, skipping any that are invalid.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        let directory = directory();
+        let entries = match std::fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!(target: "modes", "Failed to read the modes folder error=\"{e}\"");
+                return Self::default();
+            }
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(MODE_EXTENSION))
+            .collect();
+        paths.sort();
+
+        let mut modes = Vec::new();
+        for path in paths {
+            let mode = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| {
+                    toml::from_str::<CustomMode>(&contents).map_err(|e| e.to_string())
+                });
+            match mode {
+                Ok(mut mode) => {
+                    if mode.name.trim().is_empty() {
+                        mode.name = path
+                            .file_stem()
+                            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+                    }
+                    log::info!(target: "modes", "Loaded custom mode name={}", mode.name);
+                    modes.push(mode);
+                }
+                Err(e) => {
+                    log::warn!(target: "modes", "Ignoring invalid mode path=\"{}\": {e}", path.display());
+                }
+            }
+        }
+        Self { modes }
+    }
+
+    // Browsers have no folder to load modes from, so only the built-in modes are offered.
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        Self::default()
+    }
+
+    // The custom mode of the given name, if it was loaded.
+    pub fn get(&self, name: &str) -> Option<&CustomMode> {
+        self.modes.iter().find(|mode| mode.name == name)
+    }
+
+    // The rules of the mode chosen in the options. A custom mode which is no longer found falls back to the
+    // built-in mode.
+    pub fn rules(&self, options: &Options) -> ModeRules {
+        options
+            .custom_mode
+            .as_deref()
+            .and_then(|name| self.get(name))
+            .map_or_else(|| options.mode.rules(), |mode| mode.rules)
+    }
+}
+
+// The folder custom modes are loaded from.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn directory() -> std::path::PathBuf {
+    let directory = crate::voxel_flight_simulator::app_data_dir().join(MODE_DIRECTORY);
+    if let Err(e) = std::fs::create_dir_all(&directory) {
+        log::warn!(target: "modes", "Failed to create the modes folder error=\"{e}\"");
+    }
+    directory
+}
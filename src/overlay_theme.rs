@@ -0,0 +1,108 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use egui::{Color32, Stroke, Visuals};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Text;
+
+// The accent colour first offered when choosing one, an orange that stands out in every theme.
+pub const DEFAULT_ACCENT: [u8; 3] = [255, 140, 0];
+
+// The look of the overlay's windows and widgets.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+pub enum OverlayTheme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl OverlayTheme {
+    pub const ALL: [OverlayTheme; 3] = [
+        OverlayTheme::Dark,
+        OverlayTheme::Light,
+        OverlayTheme::HighContrast,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            OverlayTheme::Dark => Text::OverlayThemeDark,
+            OverlayTheme::Light => Text::OverlayThemeLight,
+            OverlayTheme::HighContrast => Text::OverlayThemeHighContrast,
+        }
+    }
+
+    // The visuals of the theme, with selections, links, and hovered widgets in the accent colour if one is chosen.
+    pub fn visuals(self, accent: Option<[u8; 3]>) -> Visuals {
+        let mut visuals = match self {
+            OverlayTheme::Dark => Visuals::dark(),
+            OverlayTheme::Light => Visuals::light(),
+            OverlayTheme::HighContrast => high_contrast(),
+        };
+        if let Some([r, g, b]) = accent {
+            let accent = Color32::from_rgb(r, g, b);
+            visuals.selection.bg_fill = accent;
+            visuals.selection.stroke.color = contrasting(accent);
+            visuals.hyperlink_color = accent;
+            visuals.widgets.hovered.bg_stroke.color = accent;
+            visuals.widgets.active.bg_stroke.color = accent;
+        }
+        visuals
+    }
+}
+
+// Black windows with white text and outlines, and yellow selections.
+fn high_contrast() -> Visuals {
+    let mut visuals = Visuals::dark();
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.window_fill = Color32::BLACK;
+    visuals.panel_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::from_gray(24);
+    visuals.window_stroke = Stroke::new(2., Color32::WHITE);
+    for widget in [
+        &mut visuals.widgets.noninteractive,
+        &mut visuals.widgets.inactive,
+        &mut visuals.widgets.hovered,
+        &mut visuals.widgets.active,
+        &mut visuals.widgets.open,
+    ] {
+        widget.bg_stroke = Stroke::new(1.5, Color32::WHITE);
+        widget.fg_stroke.color = Color32::WHITE;
+    }
+    visuals.widgets.inactive.bg_fill = Color32::BLACK;
+    visuals.widgets.inactive.weak_bg_fill = Color32::BLACK;
+    visuals.widgets.hovered.bg_fill = Color32::from_gray(48);
+    visuals.widgets.hovered.weak_bg_fill = Color32::from_gray(48);
+    visuals.selection.bg_fill = Color32::YELLOW;
+    visuals.selection.stroke.color = Color32::BLACK;
+    visuals.hyperlink_color = Color32::from_rgb(0, 255, 255);
+    visuals
+}
+
+// Black or white, whichever stands out more against the given colour.
+fn contrasting(color: Color32) -> Color32 {
+    let luminance =
+        0.299 * f32::from(color.r()) + 0.587 * f32::from(color.g()) + 0.114 * f32::from(color.b());
+    if luminance > 140. {
+        Color32::BLACK
+    } else {
+        Color32::WHITE
+    }
+}
@@ -0,0 +1,92 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::{Matrix3, Vector4};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Text;
+
+// Colour-vision deficiencies that the world palette can be adjusted for.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+pub enum ColorblindMode {
+    #[default]
+    Off,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl ColorblindMode {
+    pub const ALL: [ColorblindMode; 4] = [
+        ColorblindMode::Off,
+        ColorblindMode::Deuteranopia,
+        ColorblindMode::Protanopia,
+        ColorblindMode::Tritanopia,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            ColorblindMode::Off => Text::ColorblindOff,
+            ColorblindMode::Deuteranopia => Text::ColorblindDeuteranopia,
+            ColorblindMode::Protanopia => Text::ColorblindProtanopia,
+            ColorblindMode::Tritanopia => Text::ColorblindTritanopia,
+        }
+    }
+
+    // Remap a generated voxel colour so that the colour differences lost to the deficiency are
+    // shifted onto channels which remain distinguishable (daltonization).
+    pub fn remap(self, colour: Vector4<f32>) -> Vector4<f32> {
+        let Some((simulate, shift)) = self.matrices() else {
+            return colour;
+        };
+        let rgb = colour.truncate();
+        let error = rgb - simulate * rgb;
+        let corrected = rgb + shift * error;
+        corrected.map(|c| c.clamp(0., 1.)).extend(colour.w)
+    }
+
+    // The colour that portals pulse towards, with the strength of the pulse in the last component.
+    pub fn portal_highlight(self) -> [f32; 4] {
+        match self {
+            ColorblindMode::Off => [0., 0., 0., 1.],
+            ColorblindMode::Deuteranopia | ColorblindMode::Protanopia => [1., 0.85, 0.1, 0.85],
+            ColorblindMode::Tritanopia => [1., 0.2, 0.45, 0.85],
+        }
+    }
+
+    // Matrices simulating the deficiency and redistributing the lost colour information.
+    // Note that `cgmath` matrices are constructed column by column.
+    fn matrices(self) -> Option<(Matrix3<f32>, Matrix3<f32>)> {
+        const RED_GREEN_SHIFT: Matrix3<f32> = Matrix3::new(0., 0.7, 0.7, 0., 1., 0., 0., 0., 1.);
+        match self {
+            ColorblindMode::Off => None,
+            ColorblindMode::Deuteranopia => Some((
+                Matrix3::new(0.625, 0.7, 0., 0.375, 0.3, 0.3, 0., 0., 0.7),
+                RED_GREEN_SHIFT,
+            )),
+            ColorblindMode::Protanopia => Some((
+                Matrix3::new(0.567, 0.558, 0., 0.433, 0.442, 0.242, 0., 0., 0.758),
+                RED_GREEN_SHIFT,
+            )),
+            ColorblindMode::Tritanopia => Some((
+                Matrix3::new(0.95, 0., 0., 0.05, 0.433, 0.475, 0., 0.567, 0.525),
+                Matrix3::new(1., 0., 0., 0., 1., 0., 0.7, 0.7, 0.),
+            )),
+        }
+    }
+}
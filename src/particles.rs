@@ -0,0 +1,247 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bytemuck::{Pod, Zeroable};
+use cgmath::{InnerSpace, Quaternion, Rotation, Vector3};
+use rand::Rng;
+
+use crate::game::DEFAULT_CAMERA_SPEED;
+
+// The most particles alive at once, which bounds the buffer uploaded each frame.
+pub const MAXIMUM_PARTICLES: usize = 1024;
+
+// Dust emitted per second while flying at the default camera speed, which grows in proportion to the speed.
+const DUST_RATE: f32 = 90.;
+
+// Sizes, distances, and speeds of particles are relative to the camera's speed, so that they suit the size of the
+// surrounding space.
+const DUST_SIZE: f32 = 0.01;
+const DUST_SPREAD: f32 = 0.6;
+const DUST_LIFETIME: f32 = 1.2;
+const DUST_COLOUR: [f32; 4] = [0.9, 0.9, 0.85, 0.35];
+
+// Sparks thrown out in front of the camera after a crash.
+const SPARK_COUNT: usize = 96;
+const SPARK_SIZE: f32 = 0.02;
+const SPARK_SPEED: f32 = 1.5;
+const SPARK_LIFETIME: f32 = 0.9;
+const SPARK_COLOUR: [f32; 4] = [1., 0.55, 0.15, 0.9];
+
+// Fewer sparks are thrown where a projectile strikes the world.
+const IMPACT_SPARK_COUNT: usize = 32;
+
+// A ring of particles spinning around the camera's heading after it takes a portal.
+const SWIRL_COUNT: usize = 96;
+const SWIRL_SIZE: f32 = 0.015;
+const SWIRL_RADIUS: f32 = 0.5;
+const SWIRL_SPEED: f32 = 1.5;
+const SWIRL_LIFETIME: f32 = 1.1;
+const SWIRL_COLOUR: [f32; 4] = [0.75, 0.55, 1., 0.8];
+
+// How quickly particles slow down in the air.
+const PARTICLE_DRAG: f32 = 2.;
+
+// Seconds of motion relative to the camera which are drawn as each particle's streak.
+const STREAK_SECONDS: f32 = 0.04;
+
+// A particle as read by the particle shaders, laid out as the `Particle` struct in `particles.vert`.
+// The streak runs back from the position, along the particle's motion relative to the camera.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct ParticleInstance {
+    pub position: [f32; 3],
+    pub size: f32,
+    pub streak: [f32; 3],
+    pub _padding: f32,
+    pub colour: [f32; 4],
+}
+
+// A particle moving freely through the world, which fades out over its lifetime.
+struct Particle {
+    position: Vector3<f32>,
+    velocity: Vector3<f32>,
+    size: f32,
+    colour: [f32; 4],
+    age: f32,
+    lifetime: f32,
+}
+
+// The dust streaking past the camera and the bursts of particles following crashes and portals. They are simulated
+// here and drawn over the scene by the renderer.
+pub struct Particles {
+    particles: Vec<Particle>,
+    camera_velocity: Vector3<f32>,
+    dust_owed: f32,
+}
+
+impl Default for Particles {
+    fn default() -> Self {
+        Self {
+            particles: Vec::new(),
+            camera_velocity: Vector3::new(0., 0., 0.),
+            dust_owed: 0.,
+        }
+    }
+}
+
+// A random direction, uniformly distributed over the sphere.
+fn random_direction(rng: &mut impl Rng) -> Vector3<f32> {
+    let z = rng.gen_range(-1_f32..=1.);
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    let radius = (1. - z * z).sqrt();
+    Vector3::new(radius * angle.cos(), radius * angle.sin(), z)
+}
+
+impl Particles {
+    // Age and move the particles, removing those which have faded out.
+    pub fn update(&mut self, delta_time: f32) {
+        let drag = (-PARTICLE_DRAG * delta_time).exp();
+        for particle in &mut self.particles {
+            particle.age += delta_time;
+            particle.position += delta_time * particle.velocity;
+            particle.velocity *= drag;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    // Scatter dust ahead of the camera, more of it the faster the camera flies, so that it streaks past.
+    // The camera's velocity is kept to draw each particle's motion relative to it.
+    pub fn emit_dust(
+        &mut self,
+        camera_position: Vector3<f32>,
+        camera_quaternion: Quaternion<f32>,
+        camera_velocity: Vector3<f32>,
+        camera_speed: f32,
+        delta_time: f32,
+    ) {
+        self.camera_velocity = camera_velocity;
+        self.dust_owed +=
+            DUST_RATE * delta_time * camera_velocity.magnitude() / DEFAULT_CAMERA_SPEED;
+        let mut rng = rand::thread_rng();
+        while self.dust_owed >= 1. {
+            self.dust_owed -= 1.;
+            let offset = Vector3::new(
+                DUST_SPREAD * rng.gen_range(-1_f32..=1.),
+                DUST_SPREAD * rng.gen_range(-1_f32..=1.),
+                rng.gen_range(0.5..=1.5),
+            );
+            self.spawn(Particle {
+                position: camera_position + camera_speed * camera_quaternion.rotate_vector(offset),
+                velocity: Vector3::new(0., 0., 0.),
+                size: DUST_SIZE * camera_speed,
+                colour: DUST_COLOUR,
+                age: 0.,
+                lifetime: DUST_LIFETIME,
+            });
+        }
+    }
+
+    // Throw sparks out from just ahead of the camera, following a crash.
+    pub fn burst(
+        &mut self,
+        camera_position: Vector3<f32>,
+        camera_quaternion: Quaternion<f32>,
+        camera_speed: f32,
+    ) {
+        let centre = camera_position
+            + camera_quaternion.rotate_vector(Vector3::new(0., 0., 0.5 * camera_speed));
+        self.sparks(centre, SPARK_COUNT, camera_speed);
+    }
+
+    // Throw a few sparks out from where a projectile struck the world.
+    pub fn impact(&mut self, position: Vector3<f32>, camera_speed: f32) {
+        self.sparks(position, IMPACT_SPARK_COUNT, camera_speed);
+    }
+
+    // Spin a ring of particles around the camera's heading, following a portal.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn swirl(
+        &mut self,
+        camera_position: Vector3<f32>,
+        camera_quaternion: Quaternion<f32>,
+        camera_speed: f32,
+    ) {
+        let mut rng = rand::thread_rng();
+        for i in 0..SWIRL_COUNT {
+            let angle = std::f32::consts::TAU * i as f32 / SWIRL_COUNT as f32;
+            let (sin, cos) = angle.sin_cos();
+            let offset = Vector3::new(
+                SWIRL_RADIUS * cos,
+                SWIRL_RADIUS * sin,
+                rng.gen_range(0.3..=1.2),
+            );
+            let tangent = Vector3::new(-sin, cos, 0.);
+            self.spawn(Particle {
+                position: camera_position + camera_speed * camera_quaternion.rotate_vector(offset),
+                velocity: SWIRL_SPEED * camera_speed * camera_quaternion.rotate_vector(tangent),
+                size: SWIRL_SIZE * camera_speed,
+                colour: SWIRL_COLOUR,
+                age: 0.,
+                lifetime: SWIRL_LIFETIME,
+            });
+        }
+    }
+
+    // Remove every particle, such as when leaving a world.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.dust_owed = 0.;
+    }
+
+    // The particles to draw, fading out as they age.
+    pub fn instances(&self) -> Vec<ParticleInstance> {
+        self.particles
+            .iter()
+            .map(|particle| {
+                let [r, g, b, a] = particle.colour;
+                let fade = 1. - particle.age / particle.lifetime;
+                ParticleInstance {
+                    position: particle.position.into(),
+                    size: particle.size,
+                    streak: (STREAK_SECONDS * (particle.velocity - self.camera_velocity)).into(),
+                    _padding: 0.,
+                    colour: [r, g, b, a * fade],
+                }
+            })
+            .collect()
+    }
+
+    // Throw sparks out in every direction from a point.
+    fn sparks(&mut self, centre: Vector3<f32>, count: usize, camera_speed: f32) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..count {
+            let speed = SPARK_SPEED * camera_speed * rng.gen_range(0.3..=1.);
+            self.spawn(Particle {
+                position: centre,
+                velocity: speed * random_direction(&mut rng),
+                size: SPARK_SIZE * camera_speed,
+                colour: SPARK_COLOUR,
+                age: 0.,
+                lifetime: SPARK_LIFETIME * rng.gen_range(0.6..=1.),
+            });
+        }
+    }
+
+    // Add a particle, unless there are already as many as can be drawn.
+    fn spawn(&mut self, particle: Particle) {
+        if self.particles.len() < MAXIMUM_PARTICLES {
+            self.particles.push(particle);
+        }
+    }
+}
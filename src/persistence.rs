@@ -0,0 +1,132 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::sync::Mutex;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, io::ErrorKind};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::voxel_flight_simulator::app_data_dir;
+
+// Each named profile keeps its files in a directory of its own.
+const PROFILE_DIRECTORY: &str = "profiles";
+
+// The profile whose files are used, or `None` for the default profile, whose files are kept at the top level.
+static PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+// Use the files of the given profile from now on.
+pub fn set_profile(profile: Option<String>) {
+    if let Ok(mut current) = PROFILE.lock() {
+        *current = profile;
+    }
+}
+
+// The name a file belonging to the current profile is stored under.
+pub fn profile_file(file_name: &str) -> String {
+    match PROFILE.lock().ok().as_deref() {
+        Some(Some(profile)) => format!("{PROFILE_DIRECTORY}/{profile}/{file_name}"),
+        _ => file_name.to_owned(),
+    }
+}
+
+// The version of the format of the stored files, which is recorded with exported data. Increase it, with a migration
+// in `migrate`, whenever a file changes in a way that can't be loaded as it was.
+#[cfg(not(target_arch = "wasm32"))]
+pub const DATA_VERSION: u32 = 1;
+
+// Bring the contents of a stored file up to date from the data version it was written with.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn migrate(version: u32, file_name: &str, contents: String) -> Result<String, String> {
+    match version {
+        DATA_VERSION => Ok(contents),
+        _ => Err(format!(
+            "{file_name} has data version {version}, but this build only reads version {DATA_VERSION}"
+        )),
+    }
+}
+
+// Load a TOML file from the app directory, using the default value if it is missing or invalid.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    let path = app_data_dir().join(file_name);
+    match fs::read_to_string(&path) {
+        Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!(target: "persistence", "Ignoring invalid file path=\"{}\": {e}", path.display());
+            T::default()
+        }),
+        Err(e) if e.kind() == ErrorKind::NotFound => T::default(),
+        Err(e) => {
+            log::warn!(target: "persistence", "Failed to read file path=\"{}\": {e}", path.display());
+            T::default()
+        }
+    }
+}
+
+// Save a value as a TOML file in the app directory. The file is replaced atomically so that a crash
+// mid-write can't corrupt existing data.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save<T: Serialize>(file_name: &str, value: &T) {
+    let path = app_data_dir().join(file_name);
+    let temp_path = path.with_extension("toml.tmp");
+    let result = toml::to_string_pretty(value)
+        .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))
+        .and_then(|contents| {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&temp_path, contents)
+        })
+        .and_then(|()| fs::rename(&temp_path, &path));
+    if let Err(e) = result {
+        log::warn!(target: "persistence", "Failed to save file path=\"{}\": {e}", path.display());
+    }
+}
+
+// Browsers have no file system, so web builds keep each file's contents in the page's local storage.
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+// Load a TOML value from local storage, using the default value if it is missing or invalid.
+#[cfg(target_arch = "wasm32")]
+pub fn load<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    let Some(contents) =
+        local_storage().and_then(|storage| storage.get_item(file_name).ok().flatten())
+    else {
+        return T::default();
+    };
+    toml::from_str(&contents).unwrap_or_else(|e| {
+        log::warn!(target: "persistence", "Ignoring invalid stored key=\"{file_name}\": {e}");
+        T::default()
+    })
+}
+
+// Save a value as TOML in local storage.
+#[cfg(target_arch = "wasm32")]
+pub fn save<T: Serialize>(file_name: &str, value: &T) {
+    let saved = toml::to_string_pretty(value)
+        .ok()
+        .zip(local_storage())
+        .is_some_and(|(contents, storage)| storage.set_item(file_name, &contents).is_ok());
+    if !saved {
+        log::warn!(target: "persistence", "Failed to save key=\"{file_name}\"");
+    }
+}
@@ -0,0 +1,97 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::{Quaternion, Rotation, Vector3};
+use web_time::Instant;
+
+use crate::game;
+use crate::input;
+use crate::voxels::{self, VoxelCompact};
+
+// Photo mode constants.
+pub const EXPOSURE_RANGE: std::ops::RangeInclusive<f32> = -3.0..=3.0;
+pub const APERTURE_RANGE: std::ops::RangeInclusive<f32> = 0.0..=0.05;
+pub const FOCUS_DISTANCE_RANGE: std::ops::RangeInclusive<f32> = 0.001..=10.;
+
+// A paused game where the camera can be moved freely to frame a shot.
+// The camera and run are restored on exit, as if no time had passed.
+pub struct PhotoMode {
+    pub aperture: f32,
+    pub exposure: f32,
+    pub focus_distance: f32,
+    pub is_panel_visible: bool,
+    pub time: f32,
+    camera_position: Vector3<f32>,
+    camera_quaternion: Quaternion<f32>,
+    camera_speed: f32,
+    entered: Instant,
+}
+
+impl PhotoMode {
+    // Pause the game, freezing the world at the given shader time.
+    pub fn enter(game: &game::State, octree: &[VoxelCompact], time: f32) -> Self {
+        let mut photo = Self {
+            aperture: 0.,
+            exposure: 0.,
+            focus_distance: 1.,
+            is_panel_visible: true,
+            time,
+            camera_position: game.camera_position,
+            camera_quaternion: game.camera_quaternion,
+            camera_speed: game.camera_speed,
+            entered: Instant::now(),
+        };
+        photo.autofocus(game, octree);
+        photo
+    }
+
+    // Return the camera to where it was framed from and resume the run.
+    pub fn exit(self, game: &mut game::State) {
+        game.camera_position = self.camera_position;
+        game.camera_quaternion = self.camera_quaternion;
+        game.camera_speed = self.camera_speed;
+        if let Some(start) = &mut game.run.start {
+            *start += self.entered.elapsed();
+        }
+    }
+
+    // Move the free camera, which turns with the flight controls and only moves forward while boosting.
+    // The camera passes through voxels and portals.
+    pub fn fly(game: &mut game::State, delta_time: f32) {
+        let inputs = game.input.steering(&input::PLAYER_SOURCES);
+        game.turn_camera(delta_time, inputs);
+        if game.input.boost_held() {
+            game.camera_position += game.camera_quaternion.rotate_vector(Vector3::new(
+                0.,
+                0.,
+                delta_time * game.camera_speed,
+            ));
+        }
+    }
+
+    // Focus on the first voxel in the center of the view, if any are close enough.
+    // Mirrors count as surfaces, so the focus lands on a mirror rather than on its reflection.
+    pub fn autofocus(&mut self, game: &game::State, octree: &[VoxelCompact]) {
+        let direction = game.camera_quaternion.rotate_vector(Vector3::unit_z());
+        if let Some(hit) = voxels::raycast(game.camera_position, direction, octree)
+            .filter(|hit| hit.distance <= *FOCUS_DISTANCE_RANGE.end())
+        {
+            self.focus_distance = hit.distance.max(*FOCUS_DISTANCE_RANGE.start());
+        }
+    }
+}
@@ -0,0 +1,331 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+    cell::{Cell, RefCell},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use mlua::{Function, HookTriggers, IntoLuaMulti, Lua, LuaOptions, StdLib, Value};
+use web_time::Instant;
+
+use crate::game::{self, Options};
+use crate::voxel_flight_simulator::app_data_dir;
+use crate::wind;
+
+// Plugin constants.
+const PLUGIN_DIRECTORY: &str = "plugins";
+const PLUGIN_EXTENSION: &str = "lua";
+const MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+const HOOK_TIME_LIMIT: Duration = Duration::from_millis(50);
+const TIME_CHECK_INSTRUCTIONS: u32 = 10_000;
+const DEFAULT_HUD_SECONDS: f32 = 3.;
+
+// The events plugins can hook by defining global functions of the same name.
+#[derive(Clone, Copy)]
+pub enum Hook {
+    // `on_world_generated(seed)`, with the seed as a string, since Lua's integers can't hold every seed.
+    WorldGenerated(u64),
+    // `on_portal(level, score)`, after the portal's points are gained.
+    Portal { level: u32, score: u32 },
+    // `on_collision(score)`, with the score of the run that ended.
+    Collision { score: u32 },
+    // `on_frame(dt)`, with the seconds since the last frame.
+    Frame(f32),
+}
+
+// What plugins have asked of the game, applied once their hooks have returned.
+enum Request {
+    SetOption(String, Value<'static>),
+    Hud(String, f32),
+    Regenerate(Option<String>),
+}
+
+// A script loaded from the plugins folder, with its own Lua state. Scripts can only reach the `game` table and the
+// table, string, math, and utf8 libraries, without the base functions which load code, so they can't touch files or
+// run programs.
+struct Plugin {
+    name: String,
+    lua: Lua,
+    deadline: Rc<Cell<Option<Instant>>>,
+}
+
+// Text shown on the HUD by a plugin, and when it disappears.
+struct HudText {
+    text: String,
+    expires: Instant,
+}
+
+// The plugins loaded from the plugins folder, along with the requests they have made and the text they show.
+#[derive(Default)]
+pub struct Plugins {
+    plugins: Vec<Plugin>,
+    requests: Rc<RefCell<Vec<Request>>>,
+    hud: Vec<HudText>,
+}
+
+impl Plugins {
+    // Load every script in the plugins folder, skipping any that fail to run.
+    pub fn load() -> Self {
+        let mut plugins = Self::default();
+        let entries = match std::fs::read_dir(directory()) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!(target: "plugins", "Failed to read the plugins folder error=\"{e}\"");
+                return plugins;
+            }
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(PLUGIN_EXTENSION))
+            .collect();
+        paths.sort();
+        for path in paths {
+            match Plugin::load(&path, &plugins.requests) {
+                Ok(plugin) => {
+                    log::info!(target: "plugins", "Loaded plugin name={}", plugin.name);
+                    plugins.plugins.push(plugin);
+                }
+                Err(e) => {
+                    log::warn!(target: "plugins", "Failed to load plugin path=\"{}\" error=\"{e}\"", path.display());
+                }
+            }
+        }
+        plugins
+    }
+
+    // Call the hook in every plugin which defines it. A plugin whose hook fails is unloaded, so that a broken script
+    // doesn't fill the log every frame.
+    pub fn call(&mut self, hook: Hook) {
+        self.plugins.retain(|plugin| match plugin.call(hook) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!(target: "plugins", "Unloaded plugin name={} error=\"{e}\"", plugin.name);
+                false
+            }
+        });
+    }
+
+    // Change the options as plugins have asked, returning a world regeneration if one was requested, with its seed
+    // if one was given.
+    pub fn apply_requests(&mut self, options: &mut Options) -> Option<Option<String>> {
+        let mut regenerate = None;
+        for request in self.requests.borrow_mut().drain(..) {
+            match request {
+                Request::SetOption(name, value) => {
+                    if let Err(e) = set_option(options, &name, &value) {
+                        log::warn!(target: "plugins", "Ignored option name={name} error=\"{e}\"");
+                    }
+                }
+                Request::Hud(text, seconds) => self.hud.push(HudText {
+                    text,
+                    expires: Instant::now() + Duration::from_secs_f32(seconds.clamp(0., 60.)),
+                }),
+                Request::Regenerate(seed) => regenerate = Some(seed),
+            }
+        }
+        self.hud.retain(|hud| hud.expires > Instant::now());
+        regenerate
+    }
+
+    // Draw the text plugins have put on the HUD, below the top of the screen.
+    pub fn show_hud(&self, ctx: &egui::Context) {
+        if self.hud.is_empty() {
+            return;
+        }
+        egui::Area::new("plugin_hud")
+            .anchor(egui::Align2::CENTER_TOP, [0., 48.])
+            .interactable(false)
+            .show(ctx, |ui| {
+                for hud in &self.hud {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.label(&hud.text);
+                    });
+                }
+            });
+    }
+
+    // Getters.
+    pub fn has_hud(&self) -> bool {
+        !self.hud.is_empty()
+    }
+}
+
+impl Plugin {
+    // Run a script, giving it the `game` table through which it makes requests.
+    fn load(path: &Path, requests: &Rc<RefCell<Vec<Request>>>) -> mlua::Result<Self> {
+        let name = path
+            .file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+        let lua = Lua::new_with(
+            StdLib::TABLE | StdLib::STRING | StdLib::MATH | StdLib::UTF8,
+            LuaOptions::new(),
+        )?;
+        lua.set_memory_limit(MEMORY_LIMIT)?;
+
+        // The base library is always opened, so remove its functions which read files or load code from strings.
+        for function in ["dofile", "loadfile", "load"] {
+            lua.globals().set(function, Value::Nil)?;
+        }
+
+        // Stop scripts which run for too long, such as in an endless loop.
+        let deadline = Rc::new(Cell::new(None));
+        let hook_deadline = deadline.clone();
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(TIME_CHECK_INSTRUCTIONS),
+            move |_, _| match hook_deadline.get() {
+                Some(deadline) if Instant::now() > deadline => Err(mlua::Error::RuntimeError(
+                    "the script took too long".to_owned(),
+                )),
+                _ => Ok(()),
+            },
+        );
+
+        let game = lua.create_table()?;
+        let queue = requests.clone();
+        game.set(
+            "set_option",
+            lua.create_function(move |_, (name, value): (String, Value)| {
+                // Only plain values are accepted, which can outlive the call. NaN would pass through clamping into
+                // the options, so only finite numbers are.
+                let value = match value {
+                    Value::Nil => Value::Nil,
+                    Value::Boolean(value) => Value::Boolean(value),
+                    Value::Integer(value) => Value::Integer(value),
+                    Value::Number(value) if value.is_finite() => Value::Number(value),
+                    Value::Number(_) => {
+                        return Err(mlua::Error::external("expected a finite number"))
+                    }
+                    _ => return Err(mlua::Error::external("expected a boolean, number, or nil")),
+                };
+                queue.borrow_mut().push(Request::SetOption(name, value));
+                Ok(())
+            })?,
+        )?;
+        let queue = requests.clone();
+        game.set(
+            "hud",
+            lua.create_function(move |_, (text, seconds): (String, Option<f32>)| {
+                let seconds = seconds.unwrap_or(DEFAULT_HUD_SECONDS);
+                if !seconds.is_finite() {
+                    return Err(mlua::Error::external("expected a finite number of seconds"));
+                }
+                queue.borrow_mut().push(Request::Hud(text, seconds));
+                Ok(())
+            })?,
+        )?;
+        let queue = requests.clone();
+        game.set(
+            "regenerate",
+            lua.create_function(move |_, seed: Option<String>| {
+                queue.borrow_mut().push(Request::Regenerate(seed));
+                Ok(())
+            })?,
+        )?;
+        lua.globals().set("game", game)?;
+
+        let source = std::fs::read_to_string(path).map_err(mlua::Error::external)?;
+        let plugin = Self {
+            name,
+            lua,
+            deadline,
+        };
+        plugin.run(|lua| lua.load(&source).set_name(&plugin.name).exec())?;
+        Ok(plugin)
+    }
+
+    // Call the function of a hook, if the script defined one.
+    fn call(&self, hook: Hook) -> mlua::Result<()> {
+        match hook {
+            Hook::WorldGenerated(seed) => {
+                self.call_function("on_world_generated", seed.to_string())
+            }
+            Hook::Portal { level, score } => self.call_function("on_portal", (level, score)),
+            Hook::Collision { score } => self.call_function("on_collision", score),
+            Hook::Frame(delta_time) => self.call_function("on_frame", delta_time),
+        }
+    }
+
+    fn call_function<'lua, A: IntoLuaMulti<'lua>>(
+        &'lua self,
+        name: &str,
+        args: A,
+    ) -> mlua::Result<()> {
+        let Some(function) = self.lua.globals().get::<_, Option<Function>>(name)? else {
+            return Ok(());
+        };
+        self.run(|_| function.call(args))
+    }
+
+    // Run part of the script, stopping it if it runs past the time limit.
+    fn run<T>(&self, f: impl FnOnce(&Lua) -> mlua::Result<T>) -> mlua::Result<T> {
+        self.deadline.set(Some(Instant::now() + HOOK_TIME_LIMIT));
+        let result = f(&self.lua);
+        self.deadline.set(None);
+        result
+    }
+}
+
+// The folder plugins are loaded from.
+pub fn directory() -> PathBuf {
+    let directory = app_data_dir().join(PLUGIN_DIRECTORY);
+    if let Err(e) = std::fs::create_dir_all(&directory) {
+        log::warn!(target: "plugins", "Failed to create the plugins folder error=\"{e}\"");
+    }
+    directory
+}
+
+// Set one of the options plugins may change, by its name in the settings file.
+fn set_option(options: &mut Options, name: &str, value: &Value) -> Result<(), String> {
+    let flag = || value.as_boolean().ok_or("expected a boolean");
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    let number = || match *value {
+        Value::Integer(number) => Ok(number as f32),
+        Value::Number(number) if number.is_finite() => Ok(number as f32),
+        _ => Err("expected a finite number"),
+    };
+    match name {
+        "chase" => options.chase = flag()?,
+        "destructible_voxels" => options.destructible_voxels = flag()?,
+        "fov" => options.fov = number()?.clamp(*game::FOV_RANGE.start(), *game::FOV_RANGE.end()),
+        "fuel_boost" => options.fuel_boost = flag()?,
+        "minimap" => options.minimap = flag()?,
+        "particles" => options.particles = flag()?,
+        "speed_lines" => {
+            options.speed_lines = match value {
+                Value::Nil => None,
+                _ => Some(number()?.clamp(
+                    *game::SPEED_LINES_RANGE.start(),
+                    *game::SPEED_LINES_RANGE.end(),
+                )),
+            }
+        }
+        "trail" => options.trail = flag()?,
+        "wind" => {
+            options.wind = match value {
+                Value::Nil => None,
+                _ => Some(number()?.clamp(*wind::WIND_RANGE.start(), *wind::WIND_RANGE.end())),
+            }
+        }
+        "wingman" => options.wingman = flag()?,
+        _ => return Err("unknown option".to_owned()),
+    }
+    Ok(())
+}
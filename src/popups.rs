@@ -0,0 +1,98 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use egui::{Align2, Color32, Context, FontId};
+
+use crate::i18n::{Language, Text};
+
+// Seconds that each popup floats for, and how far it rises in points of the overlay.
+const POPUP_SECONDS: f32 = 1.2;
+const POPUP_RISE: f32 = 80.;
+
+const POINTS_COLOUR: Color32 = Color32::from_rgb(255, 220, 90);
+const LEVEL_COLOUR: Color32 = Color32::from_rgb(220, 230, 255);
+
+// Points gained by taking a portal and the level reached, and how long ago.
+struct Popup {
+    points: u32,
+    level: u32,
+    age: f32,
+}
+
+// Text floating up from the centre of the view after each portal, showing the points gained and the level reached.
+// Popups are drawn on their own layer over the world, beneath any windows.
+#[derive(Default)]
+pub struct ScorePopups {
+    popups: Vec<Popup>,
+}
+
+impl ScorePopups {
+    // Show the points gained by a portal, if any, and the level reached.
+    pub fn push(&mut self, points: u32, level: u32) {
+        self.popups.push(Popup {
+            points,
+            level,
+            age: 0.,
+        });
+    }
+
+    // Age the popups, removing those which have faded out.
+    pub fn update(&mut self, delta_time: f32) {
+        for popup in &mut self.popups {
+            popup.age += delta_time;
+        }
+        self.popups.retain(|popup| popup.age < POPUP_SECONDS);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.popups.is_empty()
+    }
+
+    // Draw each popup rising and fading out above the centre of the view.
+    pub fn draw(&self, ctx: &Context, lang: Language) {
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("score_popups"),
+        ));
+        let centre = ctx.screen_rect().center();
+        for popup in &self.popups {
+            let progress = popup.age / POPUP_SECONDS;
+            let position = centre - egui::vec2(0., POPUP_RISE * progress);
+            let fade = |colour: Color32| colour.gamma_multiply(1. - progress * progress);
+
+            // Points pop in larger, then settle to their size.
+            let size = 36. * (1. + 0.4 * (1. - (8. * progress).min(1.)));
+            if popup.points > 0 {
+                painter.text(
+                    position,
+                    Align2::CENTER_BOTTOM,
+                    format!("+{}", popup.points),
+                    FontId::proportional(size),
+                    fade(POINTS_COLOUR),
+                );
+            }
+            painter.text(
+                position,
+                Align2::CENTER_TOP,
+                lang.tr_args(Text::RunLevel, &[&popup.level]),
+                FontId::proportional(18.),
+                fade(LEVEL_COLOUR),
+            );
+        }
+    }
+}
@@ -0,0 +1,101 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::{InnerSpace, Quaternion, Rotation, Vector3};
+use egui::{Align2, Color32, Context, FontId};
+
+use crate::game::ScoringRules;
+use crate::projection::Lens;
+use crate::voxels::{self, NearbyPortal, PortalClass, VoxelCompact};
+
+// How far around the camera portals are labelled, and the smallest portals labelled, relative to the size of the
+// empty voxel the camera is in.
+const LABEL_DISTANCE: f32 = 6.;
+const SMALLEST_LABELLED: f32 = 1. / 16.;
+
+const VALUE_COLOUR: Color32 = Color32::from_rgb(255, 220, 90);
+
+// The point values of the scoring portals near the camera, so that a deeper portal can be sought out.
+#[derive(Default)]
+pub struct PortalValues {
+    portals: Vec<NearbyPortal>,
+}
+
+impl PortalValues {
+    // Find the scoring portals near the camera, given the scale of the empty voxel it is in.
+    pub fn update(&mut self, camera_position: Vector3<f32>, scale: f32, octree: &[VoxelCompact]) {
+        let size = scale.recip();
+        self.portals = voxels::portals_near(
+            camera_position,
+            LABEL_DISTANCE * size,
+            SMALLEST_LABELLED * size,
+            octree,
+        );
+        self.portals
+            .retain(|portal| portal.class == PortalClass::Score);
+    }
+
+    // Forget the portals found, such as when they are no longer shown.
+    pub fn clear(&mut self) {
+        self.portals.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.portals.is_empty()
+    }
+
+    // Draw the points each portal is worth at its goal, projected the way the shader projects the world. Portals
+    // whose goal isn't the first thing seen in their direction are hidden.
+    pub fn draw(
+        &self,
+        ctx: &Context,
+        camera_position: Vector3<f32>,
+        camera_quaternion: Quaternion<f32>,
+        lens: Lens,
+        octree: &[VoxelCompact],
+        scoring: &ScoringRules,
+    ) {
+        let screen = ctx.screen_rect();
+        let inverse = camera_quaternion.invert();
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("portal_values"),
+        ));
+        for portal in &self.portals {
+            let offset = portal.center - camera_position;
+            let view = inverse.rotate_vector(offset);
+            let distance = offset.magnitude();
+            let Some((centre, _)) = lens.to_screen(screen, view, 0.) else {
+                continue;
+            };
+            if distance <= f32::EPSILON
+                || voxels::raycast(camera_position, offset / distance, octree)
+                    .is_none_or(|hit| hit.index != portal.index)
+            {
+                continue;
+            }
+            painter.text(
+                centre,
+                Align2::CENTER_CENTER,
+                format!("+{}", scoring.portal_points(portal.depth)),
+                FontId::proportional(20.),
+                VALUE_COLOUR,
+            );
+        }
+    }
+}
@@ -0,0 +1,83 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::BTreeMap;
+
+use cgmath::{Quaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+// Practice constants.
+const SPAWN_POINTS_FILE_NAME: &str = "spawn_points.toml";
+
+// Where to return to after a crash in practice mode, and which way to face.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct SpawnPoint {
+    position: [f32; 3],
+    orientation: [f32; 4],
+}
+
+// The spawn point saved in each world, by the world's seed. Seeds are kept as text since TOML keys must be strings.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SpawnPoints {
+    points: BTreeMap<String, SpawnPoint>,
+}
+
+impl SpawnPoint {
+    pub fn new(position: Vector3<f32>, orientation: Quaternion<f32>) -> Self {
+        Self {
+            position: position.into(),
+            orientation: orientation.into(),
+        }
+    }
+
+    // Getters.
+    pub fn position(&self) -> Vector3<f32> {
+        self.position.into()
+    }
+    pub fn orientation(&self) -> Quaternion<f32> {
+        self.orientation.into()
+    }
+}
+
+impl SpawnPoints {
+    // Load the current profile's spawn points, starting with none if there are none.
+    pub fn load() -> Self {
+        persistence::load(&persistence::profile_file(SPAWN_POINTS_FILE_NAME))
+    }
+
+    // The spawn point saved in the world of the given seed, if any.
+    pub fn get(&self, seed: u64) -> Option<SpawnPoint> {
+        self.points.get(&seed.to_string()).copied()
+    }
+
+    // Save the spawn point of the world of the given seed, replacing any before it.
+    pub fn set(&mut self, seed: u64, point: SpawnPoint) {
+        self.points.insert(seed.to_string(), point);
+        persistence::save(&persistence::profile_file(SPAWN_POINTS_FILE_NAME), self);
+    }
+
+    // Forget the spawn point of the world of the given seed.
+    pub fn remove(&mut self, seed: u64) {
+        if self.points.remove(&seed.to_string()).is_some() {
+            persistence::save(&persistence::profile_file(SPAWN_POINTS_FILE_NAME), self);
+        }
+    }
+}
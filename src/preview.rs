@@ -0,0 +1,116 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::sync::mpsc;
+
+use cgmath::{InnerSpace, Rotation, Vector3};
+
+use crate::game;
+use crate::lighting;
+use crate::voxel_flight_simulator::generate_random_world;
+use crate::voxels::{self, VoxelCompact};
+
+// Size in pixels of world previews, which are ray-marched on the CPU.
+const PREVIEW_SIZE: [usize; 2] = [192, 108];
+
+// Brightness of voxels facing away from the sun in previews.
+const PREVIEW_AMBIENT: f32 = 0.35;
+
+// A small picture of the world that a seed would generate, drawn away from the live world so that seeds can be
+// browsed before committing to one.
+#[derive(Default)]
+pub struct WorldPreview {
+    seed: Option<u64>,
+    receiver: Option<mpsc::Receiver<egui::ColorImage>>,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl WorldPreview {
+    // Start drawing the world that the seed would generate with the given options, on a worker thread where
+    // threads are available. A picture still being drawn for another seed is discarded.
+    pub fn request(&mut self, seed: u64, options: &game::Options) {
+        if self.seed == Some(seed) {
+            return;
+        }
+        self.seed = Some(seed);
+
+        let (sender, receiver) = mpsc::channel();
+        let options = options.clone();
+        let draw = move || {
+            let (octree, _) =
+                generate_random_world(&mut voxels::RandomOctreeHelper::new(seed), &options);
+            sender.send(draw_world(&octree, &options)).ok();
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(draw);
+        #[cfg(target_arch = "wasm32")]
+        draw();
+        self.receiver = Some(receiver);
+    }
+
+    // The texture of the latest picture, uploading it once it has been drawn.
+    pub fn texture(&mut self, ctx: &egui::Context) -> Option<&egui::TextureHandle> {
+        if let Some(image) = self.receiver.as_ref().and_then(|r| r.try_recv().ok()) {
+            self.texture =
+                Some(ctx.load_texture("world_preview", image, egui::TextureOptions::NEAREST));
+            self.receiver = None;
+        }
+        self.texture.as_ref()
+    }
+
+    // Forget the preview, such as once its world has been entered.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+// Ray-march the world from where the camera starts in it, with the chosen field of view, lighting voxels by the sun and
+// showing the theme's sky where rays miss.
+#[allow(clippy::cast_precision_loss)]
+fn draw_world(octree: &[VoxelCompact], options: &game::Options) -> egui::ColorImage {
+    let [width, height] = PREVIEW_SIZE;
+    let fov_y = (0.5 * options.fov.to_radians()).tan();
+    let fov_x = fov_y * width as f32 / height as f32;
+    let sun = lighting::sun_direction(0.).normalize();
+    let sky = options.theme.theme().sky_colour;
+    let (position, orientation) = game::world_start(octree);
+
+    let pixels = (0..width * height)
+        .map(|i| {
+            let view = [(i % width, width), (i / width, height)]
+                .map(|(pixel, size)| 2. * (pixel as f32 + 0.5) / size as f32 - 1.);
+            let direction = orientation
+                .rotate_vector(Vector3::new(view[0] * fov_x, -view[1] * fov_y, 1.).normalize());
+            let [r, g, b, _] = match voxels::raycast(position, direction, octree) {
+                Some(hit) => {
+                    let light =
+                        PREVIEW_AMBIENT + (1. - PREVIEW_AMBIENT) * hit.normal.dot(sun).max(0.);
+                    octree[hit.index as usize]
+                        .average_colour
+                        .map(|channel| channel * light)
+                }
+                None => sky,
+            };
+            egui::Rgba::from_rgb(r, g, b).into()
+        })
+        .collect();
+    egui::ColorImage {
+        size: PREVIEW_SIZE,
+        pixels,
+    }
+}
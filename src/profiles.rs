@@ -0,0 +1,90 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+// The list of profiles is shared by every profile.
+const PROFILES_FILE_NAME: &str = "profiles.toml";
+const MAX_NAME_LENGTH: usize = 32;
+
+// The named profiles of the players sharing this machine, and which was played last.
+// Options, statistics, and achievements are kept separately for each profile.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Profiles {
+    pub names: Vec<String>,
+    pub current: Option<String>,
+}
+
+impl Profiles {
+    // Load the list of profiles, switching to the given profile or else the one played last.
+    pub fn load(profile: Option<&str>) -> Self {
+        let mut profiles: Self = persistence::load(PROFILES_FILE_NAME);
+
+        // The file may have been edited or imported, so names which couldn't have been typed are dropped before any
+        // of them become directories.
+        profiles.names.retain(|name| {
+            let valid = is_sanitized(name);
+            if !valid {
+                log::warn!(target: "profiles", "Ignored invalid profile name={name:?}");
+            }
+            valid
+        });
+        let current = profiles.current.take().filter(|name| is_sanitized(name));
+        let profile = profile.map_or(current, sanitize);
+        profiles.select(profile);
+        profiles
+    }
+
+    // Use the files of the given profile, adding it to the list if it is new, or the default profile for `None`.
+    pub fn select(&mut self, profile: Option<String>) {
+        if let Some(name) = &profile {
+            if !self.exists(name) {
+                self.names.push(name.clone());
+                self.names.sort_unstable();
+            }
+        }
+        log::info!(target: "profiles", "Selected profile={profile:?}");
+        persistence::set_profile(profile.clone());
+        self.current = profile;
+        persistence::save(PROFILES_FILE_NAME, self);
+    }
+
+    // Whether a profile of the given name has been played.
+    pub fn exists(&self, name: &str) -> bool {
+        self.names.iter().any(|existing| existing == name)
+    }
+}
+
+// Turn a name typed by the player into one which is safe to use as a directory name, or `None` if nothing is left.
+pub fn sanitize(name: &str) -> Option<String> {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        .take(MAX_NAME_LENGTH)
+        .collect();
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_owned())
+}
+
+// Whether a name is already safe to use as a directory name, left as it is by `sanitize`.
+fn is_sanitized(name: &str) -> bool {
+    sanitize(name).as_deref() == Some(name)
+}
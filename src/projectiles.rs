@@ -0,0 +1,148 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::{InnerSpace, Quaternion, Rotation, Vector3};
+use egui::{Color32, Context, Stroke};
+
+use crate::game::State;
+use crate::voxels::{self, Intersection, TraversalCache, VoxelCompact};
+
+// Speed of a projectile, relative to the camera's speed when it was fired.
+const PROJECTILE_SPEED: f32 = 6.;
+
+// Seconds a projectile flies before it fades, which sets the range at which voxels and portals can be hit.
+const PROJECTILE_LIFETIME: f32 = 1.5;
+
+// Most projectiles in flight at once. Firing more replaces the oldest.
+const MAXIMUM_PROJECTILES: usize = 32;
+
+// Seconds of flight that each tracer spans behind its projectile.
+const TRACER_SECONDS: f32 = 0.06;
+const TRACER_COLOUR: Color32 = Color32::from_rgb(255, 200, 90);
+
+struct Projectile {
+    position: Vector3<f32>,
+    velocity: Vector3<f32>,
+    age: f32,
+}
+
+// Where a projectile struck the world, as a point just inside the voxel hit, and what it would be to fly into it.
+pub struct Impact {
+    pub position: Vector3<f32>,
+    pub intersection: Intersection,
+}
+
+// Projectiles fired from the camera, traced through the octree as they fly.
+#[derive(Default)]
+pub struct Projectiles {
+    projectiles: Vec<Projectile>,
+    cache: TraversalCache,
+}
+
+impl Projectiles {
+    // Fire a projectile straight ahead of the camera.
+    pub fn fire(
+        &mut self,
+        camera_position: Vector3<f32>,
+        camera_quaternion: Quaternion<f32>,
+        camera_speed: f32,
+    ) {
+        if self.projectiles.len() == MAXIMUM_PROJECTILES {
+            self.projectiles.remove(0);
+        }
+        let direction = camera_quaternion.rotate_vector(Vector3::unit_z());
+        self.projectiles.push(Projectile {
+            position: camera_position,
+            velocity: PROJECTILE_SPEED * camera_speed * direction,
+            age: 0.,
+        });
+    }
+
+    // Move the projectiles along, returning what each that struck the world hit. Projectiles which hit something
+    // or fly out of range are removed.
+    pub fn update(&mut self, delta_time: f32, octree: &[VoxelCompact]) -> Vec<Impact> {
+        let mut impacts = Vec::new();
+        let cache = &mut self.cache;
+        self.projectiles.retain_mut(|projectile| {
+            projectile.age += delta_time;
+            let speed = projectile.velocity.magnitude();
+            let direction = projectile.velocity / speed;
+            let step = speed * delta_time;
+            match voxels::raycast(projectile.position, direction, octree) {
+                Some(hit) if hit.distance <= step => {
+                    // Step halfway into the voxel from the surface that was hit.
+                    #[allow(clippy::cast_possible_wrap)]
+                    let half_size = 0.5f32.powi(hit.depth as i32);
+                    let position = projectile.position + direction * hit.distance
+                        - 0.5 * half_size * hit.normal;
+                    let intersection =
+                        voxels::octree_scale_and_collision_of_point(position, octree, cache);
+                    impacts.push(Impact {
+                        position,
+                        intersection,
+                    });
+                    false
+                }
+                _ => {
+                    projectile.position += direction * step;
+                    projectile.age < PROJECTILE_LIFETIME
+                }
+            }
+        });
+        impacts
+    }
+
+    // Remove every projectile, such as when the world changes.
+    pub fn clear(&mut self) {
+        self.projectiles.clear();
+        self.cache.clear();
+    }
+
+    // Whether no projectiles are in flight.
+    pub fn is_empty(&self) -> bool {
+        self.projectiles.is_empty()
+    }
+
+    // Draw a tracer behind each projectile, fading as it nears the end of its range.
+    pub fn draw(&self, ctx: &Context, game: &State, time: f32) {
+        let camera_position = game.camera_position;
+        let camera_quaternion = game.shaken_camera_quaternion(time);
+        let screen = ctx.screen_rect();
+        let lens = game.lens(screen.aspect_ratio());
+        let inverse = camera_quaternion.invert();
+        let painter = ctx.layer_painter(egui::LayerId::background());
+        let near = 0.01 * game.camera_speed;
+        for projectile in &self.projectiles {
+            let tail =
+                projectile.position - projectile.velocity * TRACER_SECONDS.min(projectile.age);
+            let head_view = inverse.rotate_vector(projectile.position - camera_position);
+            let tail_view = inverse.rotate_vector(tail - camera_position);
+            let (Some((head_point, _)), Some((tail_point, _))) = (
+                lens.to_screen(screen, head_view, near),
+                lens.to_screen(screen, tail_view, near),
+            ) else {
+                continue;
+            };
+            let fade = 1. - projectile.age / PROJECTILE_LIFETIME;
+            painter.line_segment(
+                [tail_point, head_point],
+                Stroke::new(2., TRACER_COLOUR.gamma_multiply(fade)),
+            );
+        }
+    }
+}
@@ -0,0 +1,170 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::f32::consts::PI;
+
+use cgmath::{InnerSpace, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Text;
+
+// How the view maps directions around the camera onto the window. The value is passed to the shaders, which
+// match `direction` and `project`.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+#[repr(u32)]
+pub enum Projection {
+    // Straight lines stay straight, but the edges of a wide view are stretched.
+    #[default]
+    Rectilinear,
+
+    // The view is wrapped around a cylinder, so that columns are evenly spaced in angle.
+    Cylindrical,
+
+    // The cylinder is seen from its far side, keeping vertical lines and lines through the centre straight while
+    // stretching the edges far less than a rectilinear view.
+    Panini,
+}
+
+// The widest that each projection can see either side of the centre, in radians.
+const MAXIMUM_RECTILINEAR_HALF_ANGLE: f32 = 85. * PI / 180.;
+const MAXIMUM_PANINI_HALF_ANGLE: f32 = 170. * PI / 180.;
+
+// The projection of a window, and the tangents of half its fields of view at the centre of the view.
+#[derive(Clone, Copy)]
+pub struct Lens {
+    pub projection: Projection,
+    pub fov_x: f32,
+    pub fov_y: f32,
+}
+
+impl Projection {
+    pub const ALL: [Projection; 3] = [
+        Projection::Rectilinear,
+        Projection::Cylindrical,
+        Projection::Panini,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            Projection::Rectilinear => Text::ProjectionRectilinear,
+            Projection::Cylindrical => Text::ProjectionCylindrical,
+            Projection::Panini => Text::ProjectionPanini,
+        }
+    }
+
+    // The camera-space direction through a point of the view, measured in tangents at the centre of the view with
+    // +Y up.
+    pub fn direction(self, x: f32, y: f32) -> Vector3<f32> {
+        match self {
+            Projection::Rectilinear => Vector3::new(x, y, 1.),
+            Projection::Cylindrical => Vector3::new(x.sin(), y, x.cos()),
+            Projection::Panini => {
+                let angle = 2. * (0.5 * x).atan();
+                Vector3::new(angle.sin(), 0.5 * y * (1. + angle.cos()), angle.cos())
+            }
+        }
+        .normalize()
+    }
+
+    // The point of the view where a camera-space point is seen, and the depth that sizes shrink with there, which
+    // isn't positive where the projection can't show the point. This is the inverse of `direction`.
+    pub fn project(self, v: Vector3<f32>) -> Vector3<f32> {
+        match self {
+            Projection::Rectilinear => Vector3::new(v.x / v.z, v.y / v.z, v.z),
+            Projection::Cylindrical => {
+                let r = v.x.hypot(v.z);
+                Vector3::new(v.x.atan2(v.z), v.y / r, r)
+            }
+            Projection::Panini => {
+                let depth = 0.5 * (v.x.hypot(v.z) + v.z);
+                Vector3::new(v.x / depth, v.y / depth, depth)
+            }
+        }
+    }
+
+    // The angle seen either side of the centre of the view out to the given tangent.
+    fn half_angle(self, tangent: f32) -> f32 {
+        match self {
+            Projection::Rectilinear => tangent.atan(),
+            Projection::Cylindrical => tangent,
+            Projection::Panini => 2. * (0.5 * tangent).atan(),
+        }
+    }
+
+    // The tangent at which the view sees the given angle either side of its centre, as far as the projection can.
+    fn tangent(self, half_angle: f32) -> f32 {
+        match self {
+            Projection::Rectilinear => half_angle.min(MAXIMUM_RECTILINEAR_HALF_ANGLE).tan(),
+            Projection::Cylindrical => half_angle.min(PI),
+            Projection::Panini => 2. * (0.5 * half_angle.min(MAXIMUM_PANINI_HALF_ANGLE)).tan(),
+        }
+    }
+}
+
+impl Lens {
+    // The given projection of a window of the given aspect ratio, narrowing the vertical field of view when needed
+    // to keep the horizontal field of view within the cap, in degrees.
+    pub fn new(
+        projection: Projection,
+        fov_y: f32,
+        aspect_ratio: f32,
+        horizontal_fov_cap: Option<f32>,
+    ) -> Self {
+        let mut fov_x = aspect_ratio * fov_y;
+        if let Some(cap) = horizontal_fov_cap {
+            let half_angle = 0.5 * cap.to_radians();
+            if projection.half_angle(fov_x) > half_angle {
+                fov_x = projection.tangent(half_angle);
+            }
+        }
+        Lens {
+            projection,
+            fov_x,
+            fov_y: fov_x / aspect_ratio,
+        }
+    }
+
+    // Where a camera-space point is seen on the given screen, and the depth that sizes shrink with there. Points
+    // nearer than the given depth are left out, as are points the projection can't show.
+    pub fn to_screen(
+        &self,
+        screen: egui::Rect,
+        view: Vector3<f32>,
+        near: f32,
+    ) -> Option<(egui::Pos2, f32)> {
+        let point = self.projection.project(view);
+        (point.z > near.max(0.)).then(|| {
+            let offset = egui::vec2(
+                0.5 * screen.width() * point.x / self.fov_x,
+                -0.5 * screen.height() * point.y / self.fov_y,
+            );
+            (screen.center() + offset, point.z)
+        })
+    }
+
+    // The size in pixels on the given screen of a length seen at the given depth.
+    pub fn pixels(&self, screen: egui::Rect, length: f32, depth: f32) -> f32 {
+        0.5 * screen.height() * length / (depth * self.fov_y)
+    }
+
+    // The camera-space direction through a point of the view, from -1 to 1 across and down it.
+    pub fn ray(&self, view: [f32; 2]) -> Vector3<f32> {
+        self.projection
+            .direction(view[0] * self.fov_x, -view[1] * self.fov_y)
+    }
+}
@@ -0,0 +1,186 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread::JoinHandle,
+};
+
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::game::VideoFormat;
+use crate::renderer::{CapturedFrame, PixelOrder};
+use crate::voxel_flight_simulator::app_data_dir;
+
+// Recording constants.
+const RECORDING_DIRECTORY: &str = "recordings";
+const QUEUED_FRAME_LIMIT: usize = 8;
+
+// Records captured frames to a video file. Frames are encoded by `ffmpeg` on a worker thread, so that
+// encoding doesn't slow down rendering.
+pub struct Recorder {
+    dropped_frames: u32,
+    fps: u32,
+    path: PathBuf,
+    sender: SyncSender<CapturedFrame>,
+    worker: JoinHandle<()>,
+}
+
+impl Recorder {
+    // Start recording to a new file in the app directory, at a constant frame rate.
+    pub fn start(format: VideoFormat, fps: u32) -> std::io::Result<Self> {
+        let directory = app_data_dir().join(RECORDING_DIRECTORY);
+        std::fs::create_dir_all(&directory)?;
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = directory.join(format!("recording_{seconds}.{}", format.extension()));
+
+        let (sender, receiver) = mpsc::sync_channel(QUEUED_FRAME_LIMIT);
+        let worker = {
+            let path = path.clone();
+            std::thread::Builder::new()
+                .name("recording".to_string())
+                .spawn(move || encode(&receiver, &path, format, fps))?
+        };
+        log::info!(target: "recording", "Started recording path=\"{}\"", path.display());
+
+        Ok(Recorder {
+            dropped_frames: 0,
+            fps,
+            path,
+            sender,
+            worker,
+        })
+    }
+
+    // The frame rate of the video, which rendering is limited to while recording.
+    pub fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    // Queue a frame for encoding, dropping it if the encoder has fallen too far behind.
+    pub fn push(&mut self, frame: CapturedFrame) {
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(frame) {
+            self.dropped_frames += 1;
+        }
+    }
+
+    // Finish encoding the queued frames and return the path of the video.
+    pub fn finish(self) -> PathBuf {
+        drop(self.sender);
+        if self.worker.join().is_err() {
+            log::error!(target: "recording", "The encoder thread panicked");
+        }
+        log::info!(
+            target: "recording",
+            "Finished recording path=\"{}\" dropped_frames={}",
+            self.path.display(),
+            self.dropped_frames,
+        );
+        self.path
+    }
+}
+
+// Pipe raw frames into `ffmpeg` until the recording is finished. The encoder starts with the size of the
+// first frame, and later frames of a different size are skipped.
+fn encode(receiver: &Receiver<CapturedFrame>, path: &Path, format: VideoFormat, fps: u32) {
+    let mut encoder: Option<(Child, ChildStdin, [u32; 2])> = None;
+    for frame in receiver {
+        let size = [frame.width, frame.height];
+        if encoder.is_none() {
+            match spawn_ffmpeg(path, format, fps, &frame) {
+                Ok(mut child) => {
+                    let stdin = child.stdin.take().expect("ffmpeg stdin is piped");
+                    encoder = Some((child, stdin, size));
+                }
+                Err(e) => {
+                    log::error!(target: "recording", "Failed to start ffmpeg, is it installed? {e}");
+                    return;
+                }
+            }
+        }
+        let Some((_, stdin, encoder_size)) = &mut encoder else {
+            return;
+        };
+        if *encoder_size != size {
+            continue;
+        }
+        if let Err(e) = stdin.write_all(&frame.pixels) {
+            log::error!(target: "recording", "Failed to write frame to ffmpeg: {e}");
+            break;
+        }
+    }
+
+    // Close the input so that ffmpeg finishes the file.
+    if let Some((mut child, stdin, _)) = encoder {
+        drop(stdin);
+        match child.wait() {
+            Ok(status) if status.success() => {}
+            Ok(status) => log::error!(target: "recording", "ffmpeg exited with {status}"),
+            Err(e) => log::error!(target: "recording", "Failed to wait for ffmpeg: {e}"),
+        }
+    }
+}
+
+fn spawn_ffmpeg(
+    path: &Path,
+    format: VideoFormat,
+    fps: u32,
+    frame: &CapturedFrame,
+) -> std::io::Result<Child> {
+    let pixel_format = match frame.order {
+        PixelOrder::Rgba => "rgba",
+        PixelOrder::Bgra => "bgra",
+    };
+    let codec: &[&str] = match format {
+        VideoFormat::Mp4 => &["-c:v", "libx264", "-preset", "veryfast", "-crf", "18"],
+        VideoFormat::WebM => &[
+            "-c:v",
+            "libvpx-vp9",
+            "-deadline",
+            "realtime",
+            "-crf",
+            "30",
+            "-b:v",
+            "0",
+        ],
+    };
+    Command::new("ffmpeg")
+        .args([
+            "-loglevel",
+            "error",
+            "-y",
+            "-f",
+            "rawvideo",
+            "-pixel_format",
+            pixel_format,
+        ])
+        .args(["-video_size", &format!("{}x{}", frame.width, frame.height)])
+        .args(["-framerate", &fps.to_string(), "-i", "-"])
+        .args(codec)
+        .args(["-pix_fmt", "yuv420p"])
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+}
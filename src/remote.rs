@@ -0,0 +1,246 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+// Remote control constants.
+pub const DEFAULT_PORT: u16 = 16835;
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_BODY_LENGTH: usize = 1024;
+
+// Content types a web page can send without the browser asking the server first, which commands must not use.
+const SIMPLE_CONTENT_TYPES: [&str; 3] = [
+    "application/x-www-form-urlencoded",
+    "multipart/form-data",
+    "text/plain",
+];
+
+// Commands sent to the game over HTTP.
+pub enum Command {
+    // `POST /seed` with the seed, a number or any phrase, as the body.
+    Seed(String),
+    // `POST /regen` to generate a new random world.
+    Regenerate,
+}
+
+// The state of the game reported by `GET /status`.
+pub struct Status {
+    pub seed: u64,
+    pub level: u32,
+    pub score: u32,
+    pub run_time: Option<f32>,
+    pub paused: bool,
+    pub position: [f32; 3],
+}
+
+// A small HTTP server on the local machine, run on a background thread, which reports the game's state as JSON and
+// accepts commands, for dashboards, stream bots, and automated tests.
+pub struct RemoteControl {
+    pub port: u16,
+    commands: mpsc::Receiver<Command>,
+    status: Arc<Mutex<String>>,
+    running: Arc<AtomicBool>,
+}
+
+impl RemoteControl {
+    // Listen on the given port of the loopback address, so that only programs on this machine can connect.
+    pub fn start(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+        let (sender, commands) = mpsc::channel();
+        let status = Arc::new(Mutex::new(String::from("{}")));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread_status = status.clone();
+        let thread_running = running.clone();
+        thread::spawn(move || {
+            while thread_running.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if let Err(e) = respond(stream, port, &sender, &thread_status) {
+                            log::warn!(target: "remote", "Failed to answer request error=\"{e}\"");
+                        }
+                    }
+                    // Waiting between checks lets the thread notice when it should stop.
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_INTERVAL);
+                    }
+                    Err(e) => {
+                        log::warn!(target: "remote", "Stopped listening error=\"{e}\"");
+                        return;
+                    }
+                }
+            }
+        });
+
+        log::info!(target: "remote", "Listening for remote control port={port}");
+        Ok(Self {
+            port,
+            commands,
+            status,
+            running,
+        })
+    }
+
+    // Publish the current state of the game, and take the commands received since the last frame.
+    pub fn update(&self, status: &Status) -> Vec<Command> {
+        if let Ok(mut json) = self.status.lock() {
+            *json = status.to_json();
+        }
+        self.commands.try_iter().collect()
+    }
+}
+
+// Ask the server thread to stop when remote control is turned off.
+impl Drop for RemoteControl {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Status {
+    // The status as a JSON object. The seed is a string, since JavaScript numbers can't hold every seed exactly, and
+    // numbers which aren't finite are null, since JSON can't hold them.
+    fn to_json(&self) -> String {
+        let run_time = self
+            .run_time
+            .filter(|seconds| seconds.is_finite())
+            .map_or_else(|| "null".to_owned(), |seconds| format!("{seconds:.3}"));
+        let [x, y, z] = self.position.map(json_number);
+        format!(
+            "{{\"seed\":\"{}\",\"level\":{},\"score\":{},\"run_time\":{run_time},\"paused\":{},\"position\":[{x},{y},{z}]}}",
+            self.seed, self.level, self.score, self.paused
+        )
+    }
+}
+
+// A number as JSON, which is null if it isn't finite.
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_owned()
+    }
+}
+
+// Read a single request and write its response, closing the connection afterwards.
+// Requests from web pages are refused, so that a page open in a browser can't read the status or send commands:
+// the host must be this machine, as it isn't when a page rebinds its own domain to the loopback address, browsers'
+// cross-origin requests carry an `Origin`, and commands need a content type which browsers ask the server about first.
+fn respond(
+    stream: TcpStream,
+    port: u16,
+    sender: &mpsc::Sender<Command>,
+    status: &Mutex<String>,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    // Read the request line and headers, keeping only those deciding whether and how to answer.
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    let mut content_type = None;
+    let mut host = None;
+    let mut has_origin = false;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim().to_ascii_lowercase();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.parse().unwrap_or(0),
+                "content-type" => content_type = Some(value),
+                "host" => host = Some(value),
+                "origin" => has_origin = true,
+                _ => {}
+            }
+        }
+    }
+    let mut body = vec![0; content_length.min(MAX_BODY_LENGTH)];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8_lossy(&body);
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let local_hosts = [format!("127.0.0.1:{port}"), format!("localhost:{port}")];
+    let is_local = host.is_some_and(|host| local_hosts.contains(&host));
+    let is_simple = content_type.as_deref().is_none_or(|content_type| {
+        let essence = content_type.split(';').next().unwrap_or("").trim();
+        SIMPLE_CONTENT_TYPES.contains(&essence)
+    });
+    let (code, response) = match (method, path) {
+        _ if !is_local || has_origin => (
+            "403 Forbidden",
+            String::from("{\"error\":\"only local programs may connect\"}"),
+        ),
+        ("POST", _) if is_simple => (
+            "415 Unsupported Media Type",
+            String::from(
+                "{\"error\":\"commands need a content type such as application/octet-stream\"}",
+            ),
+        ),
+        ("GET", "/status") => (
+            "200 OK",
+            status
+                .lock()
+                .map_or_else(|_| String::from("{}"), |json| json.clone()),
+        ),
+        ("POST", "/seed") if !body.trim().is_empty() => {
+            send(sender, Command::Seed(body.trim().to_owned()))
+        }
+        ("POST", "/seed") => (
+            "400 Bad Request",
+            String::from("{\"error\":\"missing seed\"}"),
+        ),
+        ("POST", "/regen") => send(sender, Command::Regenerate),
+        _ => ("404 Not Found", String::from("{\"error\":\"not found\"}")),
+    };
+
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 {code}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+        response.len()
+    )?;
+    stream.flush()
+}
+
+// Pass a command on to the game.
+fn send(sender: &mpsc::Sender<Command>, command: Command) -> (&'static str, String) {
+    match sender.send(command) {
+        Ok(()) => ("202 Accepted", String::from("{\"ok\":true}")),
+        Err(_) => (
+            "503 Service Unavailable",
+            String::from("{\"error\":\"game closed\"}"),
+        ),
+    }
+}
@@ -0,0 +1,415 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use bytemuck::{Pod, Zeroable};
+use cgmath::{Quaternion, Rotation, Vector3};
+use winit::{
+    event::WindowEvent,
+    event_loop::{EventLoop, EventLoopWindowTarget},
+    window::{Window, WindowId},
+};
+
+use crate::hazards::WorldHazards;
+use crate::lighting::WorldLights;
+use crate::particles::ParticleInstance;
+use crate::trail::TrailVertex;
+use crate::voxels::VoxelCompact;
+
+// Graphics APIs the app can render with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    Vulkan,
+    Wgpu,
+}
+
+// Per-frame values read by the ray-marching shader and the speed-lines pass drawn over it, which also
+// flashes the view after a crash or a portal.
+// The layout must begin with the `Push` block in `ray_march_voxels.frag`, and be a multiple of 16 bytes
+// as uniform buffers require.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct FrameUniforms {
+    pub camera_position: [f32; 3],
+    pub time: f32,
+    pub camera_quaternion: [f32; 4],
+    pub light_dir: [f32; 3],
+    pub aspect_ratio: f32,
+    pub portal_highlight: [f32; 4],
+    pub fov_y: f32,
+    pub exposure: f32,
+    pub focus_distance: f32,
+    pub aperture: f32,
+    pub shadow_quality: u32,
+    pub ambient_occlusion: u32,
+    pub traversal: u32,
+    pub temporal: u32,
+    pub previous_camera_quaternion: [f32; 4],
+    pub previous_camera_position: [f32; 3],
+    pub view: u32,
+    pub speed_lines: f32,
+    pub crash_flash: f32,
+    pub score_flash: f32,
+    pub _padding: f32,
+}
+
+// Bits of `FrameUniforms::traversal` from these shifts hold the deepest level of the octree marched and the steps
+// marched before the fog is opaque, and those below them the bounces allowed off mirrors.
+pub const TRAVERSAL_DEPTH_SHIFT: u32 = 8;
+pub const TRAVERSAL_STEPS_SHIFT: u32 = 16;
+
+// Bit of `FrameUniforms::temporal` which reuses the previous frame's colours where the world is still visible.
+pub const TEMPORAL_REPROJECTION: u32 = 1;
+
+// Bit of `FrameUniforms::temporal` which ray-marches half of the pixels each frame in a checkerboard pattern,
+// reconstructing the rest from their neighbours.
+pub const TEMPORAL_CHECKERBOARD: u32 = 2;
+
+// Bits of `FrameUniforms::temporal` above this shift count frames, choosing which pixels must be ray-marched.
+pub const TEMPORAL_FRAME_SHIFT: u32 = 8;
+
+// Bits of `FrameUniforms::view` from these shifts hold the stereo mode and the projection, and those below them the
+// debug view.
+pub const VIEW_STEREO_SHIFT: u32 = 8;
+pub const VIEW_PROJECTION_SHIFT: u32 = 16;
+pub const VIEW_DEBUG_MASK: u32 = (1 << VIEW_STEREO_SHIFT) - 1;
+
+// The camera which rendered the frame kept for temporal reprojection.
+#[derive(Clone, Copy)]
+pub struct SceneHistory {
+    pub camera_position: [f32; 3],
+    pub camera_quaternion: [f32; 4],
+}
+
+// Values read by the octree-map shader, which draws both the map window and the minimap. The map shows `scale`
+// units of the world either side of `centre`, seen from above, and is ray-marched down from the camera's height
+// rather than sliced through it when `ray_march` is set.
+// The layout must match the `Push` block in `octree_map.frag`, and be a multiple of 16 bytes.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct MapUniforms {
+    pub camera_quaternion: [f32; 4],
+    pub camera_position: [f32; 3],
+    pub aspect_ratio: f32,
+    pub centre: [f32; 2],
+    pub scale: f32,
+    pub ray_march: u32,
+}
+
+// Units of the world shown either side of the camera by the minimap.
+const MINIMAP_SCALE: f32 = 0.35;
+
+// The minimap is only ray-marched again once in this many frames.
+pub const MINIMAP_FRAME_INTERVAL: u32 = 4;
+
+// Where an eye of a VR headset is relative to where the head started, in metres, how it is turned, and the tangents
+// of the horizontal and vertical half-angles it sees. All are in the camera space of the game, which looks along +Z
+// with +X right and +Y up.
+#[derive(Clone, Copy)]
+pub struct EyeView {
+    pub position: [f32; 3],
+    pub orientation: [f32; 4],
+    pub tan_half_fov: [f32; 2],
+}
+
+// Channel order of captured pixels, which follows the format of the window's images.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PixelOrder {
+    Rgba,
+    Bgra,
+}
+
+// A rendered frame copied back to host memory, as tightly packed rows of 8-bit pixels.
+pub struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub order: PixelOrder,
+    pub pixels: Vec<u8>,
+}
+
+// The window, overlay, and GPU resources of a rendering backend.
+pub trait Renderer {
+    // The window being rendered to.
+    fn window(&self) -> &Window;
+
+    // Names of the GPUs which can be chosen at startup.
+    fn gpu_names(&self) -> &[String];
+
+    // The size of the window's drawable area in pixels.
+    fn window_size(&self) -> [f32; 2];
+
+    // Handle changes in window size.
+    fn resize(&mut self);
+
+    // Pass a window event to the overlay, returning whether the overlay consumed it.
+    fn update_gui(&mut self, event: &WindowEvent) -> bool;
+
+    // Begin a new overlay frame, which will be drawn over the next rendered frame.
+    fn begin_gui(&mut self) -> egui::Context;
+
+    // Replace the voxel-octree being rendered, and the lights and hazards placed in it.
+    fn upload_world(
+        &mut self,
+        octree: &[VoxelCompact],
+        lights: &WorldLights,
+        hazards: &WorldHazards,
+    );
+
+    // Overwrite the uploaded voxel-octree from the given index onwards, such as after a voxel is carved out of it.
+    fn update_voxels(&mut self, first: usize, voxels: &[VoxelCompact]);
+
+    // Replace the particles drawn over the next frame. Backends which can't draw particles ignore them.
+    fn upload_particles(&mut self, particles: &[ParticleInstance]);
+
+    // Replace the trail drawn behind the camera over the next frame. Backends which can't draw it ignore it.
+    fn upload_trail(&mut self, trail: &[TrailVertex]);
+
+    // Render the world at a fraction of the window's resolution, which is scaled up to fill the window.
+    fn set_render_scale(&mut self, scale: f32);
+
+    // Render and present a frame, including the overlay if one was begun.
+    fn render_frame(&mut self, uniforms: &FrameUniforms);
+
+    // Start or stop copying each rendered frame back to host memory. Returns false if the window's
+    // images can't be captured.
+    fn set_frame_capture(&mut self, enabled: bool) -> bool;
+
+    // Take the captured frames which have finished copying, oldest first.
+    fn take_captured_frames(&mut self) -> Vec<CapturedFrame>;
+
+    // Save the compiled pipelines so the next launch can reuse them. Backends without a pipeline cache do nothing.
+    fn save_pipeline_cache(&self);
+
+    // Render the world without the overlay to an image of the given size, waiting for it to be copied
+    // back to host memory. The window is unaffected.
+    fn render_offscreen(
+        &mut self,
+        uniforms: &FrameUniforms,
+        size: [u32; 2],
+    ) -> Option<CapturedFrame>;
+
+    // Open a second window with the given title, showing a top-down map of the octree at the camera's height,
+    // or close it when no title is given. Returns false if the backend can't open one.
+    fn set_map_window(
+        &mut self,
+        window_target: &EventLoopWindowTarget<()>,
+        title: Option<&str>,
+    ) -> bool;
+
+    // Whether the given window is the map window.
+    fn is_map_window(&self, id: WindowId) -> bool;
+
+    // Multisample frames with up to the given samples, recreating the passes drawn with them when the count changes.
+    // Returns false if the backend can't multisample.
+    fn set_multisampling(
+        &mut self,
+        window_target: &EventLoopWindowTarget<()>,
+        samples: u32,
+    ) -> bool;
+
+    // Handle changes in the map window's size.
+    fn resize_map(&mut self);
+
+    // Get a square texture of the given size in pixels for the overlay to show the minimap in. The minimap is
+    // ray-marched into it along with the next frame when it is new or due to be redrawn.
+    fn minimap(&mut self, size: u32) -> Option<egui::TextureId>;
+
+    // Wait for the VR headset's next frame and begin it, returning the view of each eye. Returns none when there is
+    // no headset, or the frame needn't be rendered.
+    fn begin_xr_frame(&mut self) -> Option<[EyeView; 2]>;
+
+    // Render each eye of the headset frame that was begun, and show them in the headset.
+    fn end_xr_frame(&mut self, eyes: &[FrameUniforms; 2]);
+}
+
+// The size in pixels that the world is rendered at for a frame of the given size, at least one pixel.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub fn scaled_extent(extent: [u32; 2], scale: f32) -> [u32; 2] {
+    extent.map(|x| ((x as f32 * scale).round() as u32).max(1))
+}
+
+impl FrameUniforms {
+    // Complete the temporal values for the given frame, rendered after the given history. Reprojection is
+    // turned off when there is no history to reuse.
+    pub fn reprojecting(mut self, history: Option<SceneHistory>, frame: u32) -> Self {
+        self.temporal =
+            (self.temporal & ((1 << TEMPORAL_FRAME_SHIFT) - 1)) | (frame << TEMPORAL_FRAME_SHIFT);
+        match history {
+            Some(history) => {
+                self.previous_camera_position = history.camera_position;
+                self.previous_camera_quaternion = history.camera_quaternion;
+            }
+            None => self.temporal &= !TEMPORAL_REPROJECTION,
+        }
+        self
+    }
+
+    // Whether half of the pixels are ray-marched into an image half as wide, then resolved to fill the scene.
+    pub fn checkerboard(&self) -> bool {
+        self.temporal & TEMPORAL_CHECKERBOARD != 0
+    }
+
+    // The frame seen by a headset eye, moving and turning the camera by the eye's pose at the given number of world
+    // units to a metre. Speed lines and flashes are left out, as they are uncomfortable in a headset, and the headset's
+    // own eyes and lenses take the place of a stereo mode and projection.
+    pub fn eye(&self, eye: &EyeView, units_per_metre: f32) -> Self {
+        let camera = Quaternion::from(self.camera_quaternion);
+        let position = Vector3::from(self.camera_position)
+            + camera.rotate_vector(Vector3::from(eye.position) * units_per_metre);
+        FrameUniforms {
+            camera_position: position.into(),
+            camera_quaternion: (camera * Quaternion::from(eye.orientation)).into(),
+            aspect_ratio: eye.tan_half_fov[0] / eye.tan_half_fov[1],
+            fov_y: eye.tan_half_fov[1],
+            temporal: 0,
+            view: self.view & VIEW_DEBUG_MASK,
+            speed_lines: 0.,
+            crash_flash: 0.,
+            score_flash: 0.,
+            ..*self
+        }
+    }
+
+    // The camera of this frame, to be reused by the next.
+    pub fn history(&self) -> SceneHistory {
+        SceneHistory {
+            camera_position: self.camera_position,
+            camera_quaternion: self.camera_quaternion,
+        }
+    }
+}
+
+// The size in pixels of the image that half of a scene of the given size is ray-marched into when rendering
+// in a checkerboard pattern.
+pub fn checkerboard_extent([width, height]: [u32; 2]) -> [u32; 2] {
+    [width.div_ceil(2), height]
+}
+
+impl MapUniforms {
+    // The whole world sliced at the camera's height, for a window of the given aspect ratio.
+    pub fn window(uniforms: &FrameUniforms, aspect_ratio: f32) -> Self {
+        MapUniforms {
+            camera_quaternion: uniforms.camera_quaternion,
+            camera_position: uniforms.camera_position,
+            aspect_ratio,
+            centre: [0.; 2],
+            scale: 1.,
+            ray_march: 0,
+        }
+    }
+
+    // The world around the camera, seen from above down to the first voxel below, for the square minimap.
+    pub fn minimap(uniforms: &FrameUniforms) -> Self {
+        MapUniforms {
+            camera_quaternion: uniforms.camera_quaternion,
+            camera_position: uniforms.camera_position,
+            aspect_ratio: 1.,
+            centre: [uniforms.camera_position[0], uniforms.camera_position[2]],
+            scale: MINIMAP_SCALE,
+            ray_march: 1,
+        }
+    }
+}
+
+impl Backend {
+    // Parse a backend name given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "vulkan" => Some(Backend::Vulkan),
+            "wgpu" => Some(Backend::Wgpu),
+            _ => None,
+        }
+    }
+
+    // Prefer Vulkan, falling back to wgpu when no Vulkan driver can be loaded.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn detect() -> Self {
+        match vulkano::VulkanLibrary::new() {
+            Ok(_) => Backend::Vulkan,
+            Err(e) if cfg!(feature = "wgpu") => {
+                log::warn!(target: "gpu", "Vulkan is unavailable, falling back to wgpu: {e}");
+                Backend::Wgpu
+            }
+            Err(_) => Backend::Vulkan,
+        }
+    }
+
+    // Browsers only offer WebGPU.
+    #[cfg(target_arch = "wasm32")]
+    pub fn detect() -> Self {
+        Backend::Wgpu
+    }
+}
+
+// Create the window and renderer for the requested backend, starting with the given world, its lights, and its
+// hazards.
+// The named GPU is preferred when it is available, and the frame is multisampled with up to the given samples.
+// A VR headset is also rendered to when asked for, which only the Vulkan backend can do.
+#[allow(clippy::too_many_arguments)]
+pub async fn create(
+    backend: Backend,
+    event_loop: &EventLoop<()>,
+    title: &str,
+    octree: &[VoxelCompact],
+    lights: &WorldLights,
+    hazards: &WorldHazards,
+    gpu: Option<&str>,
+    samples: u32,
+    vr: bool,
+) -> Box<dyn Renderer> {
+    log::info!(target: "gpu", "Using backend={backend:?}");
+    if vr && backend == Backend::Wgpu {
+        log::error!(target: "xr", "VR is only available with the Vulkan backend");
+    }
+    match backend {
+        #[cfg(not(target_arch = "wasm32"))]
+        Backend::Vulkan => Box::new(crate::helens::VulkanRenderer::new(
+            event_loop, title, octree, lights, hazards, gpu, samples, vr,
+        )),
+
+        #[cfg(feature = "wgpu")]
+        Backend::Wgpu => Box::new(
+            crate::wgpu_backend::WgpuRenderer::new(
+                event_loop, title, octree, lights, hazards, gpu, samples,
+            )
+            .await,
+        ),
+
+        #[cfg(target_arch = "wasm32")]
+        Backend::Vulkan => {
+            log::error!(target: "gpu", "Vulkan isn't available in a browser, using wgpu");
+            Box::new(
+                crate::wgpu_backend::WgpuRenderer::new(
+                    event_loop, title, octree, lights, hazards, gpu, samples,
+                )
+                .await,
+            )
+        }
+
+        #[cfg(not(feature = "wgpu"))]
+        Backend::Wgpu => {
+            log::error!(target: "gpu", "This build doesn't include the wgpu backend, using Vulkan");
+            Box::new(crate::helens::VulkanRenderer::new(
+                event_loop, title, octree, lights, hazards, gpu, samples, vr,
+            ))
+        }
+    }
+}
@@ -0,0 +1,86 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// The lowest fraction of the window's resolution that the world is rendered at.
+pub const MINIMUM_RENDER_SCALE: f32 = 0.5;
+
+// Seconds between changes to the render scale, so that each change has time to show in the frame times.
+const ADJUSTMENT_INTERVAL: f32 = 0.5;
+
+// The render scale moves in steps of this size, so that the pipelines aren't recreated for tiny changes.
+const SCALE_STEP: f32 = 0.05;
+
+// The resolution is only raised while frames take less than this fraction of the budget.
+const HEADROOM: f32 = 0.8;
+
+// Lowers the resolution the world is rendered at while frames take longer than the target frame rate
+// allows, and raises it again once there is time to spare.
+pub struct DynamicResolution {
+    frame_times: Vec<f32>,
+    scale: f32,
+    since_adjustment: f32,
+}
+
+impl Default for DynamicResolution {
+    fn default() -> Self {
+        Self {
+            frame_times: Vec::new(),
+            scale: 1.,
+            since_adjustment: 0.,
+        }
+    }
+}
+
+impl DynamicResolution {
+    // The fraction of the window's resolution that the world should be rendered at.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    // Record the time taken by the last frame, adjusting the render scale to fit frames within the time
+    // allowed by the target frame rate. The median frame time is used so that single hitches, such as
+    // generating a world, are ignored.
+    pub fn update(&mut self, frame_time: f32, target_fps: u32) {
+        self.frame_times.push(frame_time);
+        self.since_adjustment += frame_time;
+        if self.since_adjustment < ADJUSTMENT_INTERVAL {
+            return;
+        }
+        self.since_adjustment = 0.;
+        self.frame_times.sort_by(f32::total_cmp);
+        let median = self.frame_times[self.frame_times.len() / 2];
+        self.frame_times.clear();
+
+        #[allow(clippy::cast_precision_loss)]
+        let budget = (target_fps as f32).recip();
+        if median > budget {
+            // Ray marching costs roughly the same for each pixel, so time grows with the square of the scale.
+            let ideal = self.scale * (budget / median).sqrt();
+            let stepped = (ideal / SCALE_STEP).floor() * SCALE_STEP;
+            self.scale = stepped.min(self.scale - SCALE_STEP);
+        } else if median < HEADROOM * budget {
+            self.scale += SCALE_STEP;
+        }
+        self.scale = self.scale.clamp(MINIMUM_RENDER_SCALE, 1.);
+    }
+
+    // Return to the full resolution, forgetting previous frame times.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
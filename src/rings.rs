@@ -0,0 +1,154 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::{InnerSpace, Quaternion, Rotation, Vector3};
+use egui::{Color32, Context, Stroke};
+
+use crate::projection::Lens;
+use crate::voxels::{self, RandomOctreeHelper, TraversalCache, VoxelCompact};
+
+// The most rings placed in each world.
+const MAXIMUM_RINGS: usize = 12;
+
+// Points sampled when probing the world for open space to place rings in.
+const PLACEMENT_SAMPLES: u32 = 256;
+
+// Only empty voxels at least this large, relative to the root voxel, are open enough to hold a ring.
+const MINIMUM_OPEN_HALF_SIZE: f32 = 1. / 32.;
+
+// Radius of a ring relative to the half-size of the empty voxel it is placed in.
+const RING_SIZE: f32 = 0.3;
+
+const RING_COLOUR: Color32 = Color32::from_rgb(255, 210, 60);
+
+// Bonus points for each ring collected during a run.
+pub const RING_POINTS: u32 = 2;
+
+// A ring floating in the centre of an empty voxel, collected by flying through it.
+struct Ring {
+    position: Vector3<f32>,
+    radius: f32,
+    collected: bool,
+}
+
+// The rings of the current world, which are drawn over the rendered world wherever no voxel hides them.
+#[derive(Default)]
+pub struct Rings {
+    rings: Vec<Ring>,
+    cache: TraversalCache,
+}
+
+impl Rings {
+    // Place rings in the largest empty voxels found by probing the world at random points. The same seed places
+    // the same rings.
+    pub fn place(random: &mut RandomOctreeHelper, octree: &[VoxelCompact]) -> Self {
+        let mut cache = TraversalCache::default();
+        let mut rings: Vec<Ring> = Vec::new();
+        for _ in 0..PLACEMENT_SAMPLES {
+            if rings.len() == MAXIMUM_RINGS {
+                break;
+            }
+            let position = Vector3::new(
+                2. * random.samplef() - 1.,
+                2. * random.samplef() - 1.,
+                2. * random.samplef() - 1.,
+            );
+            let Some((center, half_size)) = voxels::empty_voxel_at(position, octree, &mut cache)
+            else {
+                continue;
+            };
+            if half_size >= MINIMUM_OPEN_HALF_SIZE && rings.iter().all(|r| r.position != center) {
+                rings.push(Ring {
+                    position: center,
+                    radius: RING_SIZE * half_size,
+                    collected: false,
+                });
+            }
+        }
+        Rings { rings, cache }
+    }
+
+    // Collect the rings that the camera is inside of, returning how many were collected.
+    pub fn collect(&mut self, position: Vector3<f32>) -> u32 {
+        let mut count = 0;
+        for ring in self.rings.iter_mut().filter(|ring| !ring.collected) {
+            if (ring.position - position).magnitude2() < ring.radius * ring.radius {
+                ring.collected = true;
+                count += 1;
+            }
+        }
+        count
+    }
+
+    // Put back every ring for a new run.
+    pub fn reset(&mut self) {
+        for ring in &mut self.rings {
+            ring.collected = false;
+        }
+    }
+
+    pub fn collected(&self) -> usize {
+        self.rings.iter().filter(|ring| ring.collected).count()
+    }
+
+    pub fn total(&self) -> usize {
+        self.rings.len()
+    }
+
+    // Whether any rings are left to be collected, and so need drawing.
+    pub fn any_remaining(&self) -> bool {
+        self.rings.iter().any(|ring| !ring.collected)
+    }
+
+    // Draw the rings left to collect behind any windows, projected the way the shader projects the world. Rings
+    // behind a voxel as seen from the camera are hidden.
+    pub fn draw(
+        &mut self,
+        ctx: &Context,
+        camera_position: Vector3<f32>,
+        camera_quaternion: Quaternion<f32>,
+        lens: Lens,
+        octree: &[VoxelCompact],
+    ) {
+        let screen = ctx.screen_rect();
+        let inverse = camera_quaternion.invert();
+        let painter = ctx.layer_painter(egui::LayerId::background());
+        for ring in self.rings.iter().filter(|ring| !ring.collected) {
+            let view = inverse.rotate_vector(ring.position - camera_position);
+            let Some((centre, depth)) = lens.to_screen(screen, view, ring.radius) else {
+                continue;
+            };
+            if voxels::first_hit_along_segment(
+                camera_position,
+                ring.position,
+                octree,
+                &mut self.cache,
+            )
+            .is_some()
+            {
+                continue;
+            }
+            let radius = lens.pixels(screen, ring.radius, depth);
+            painter.circle_stroke(
+                centre,
+                radius,
+                Stroke::new((0.2 * radius).clamp(1., 6.), RING_COLOUR),
+            );
+        }
+    }
+}
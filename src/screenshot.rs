@@ -0,0 +1,144 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use cgmath::{Deg, Matrix, Matrix3, Quaternion, Rotation3, Vector3};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::renderer::{CapturedFrame, PixelOrder};
+use crate::voxel_flight_simulator::app_data_dir;
+
+// Screenshot constants.
+const SCREENSHOT_DIRECTORY: &str = "screenshots";
+
+// Rotations from camera space to each face of a cube map around the camera, named by the direction
+// the face looks relative to the camera.
+pub fn cube_faces() -> [(&'static str, Quaternion<f32>); 6] {
+    [
+        ("front", Quaternion::from_angle_y(Deg(0.))),
+        ("right", Quaternion::from_angle_y(Deg(90.))),
+        ("back", Quaternion::from_angle_y(Deg(180.))),
+        ("left", Quaternion::from_angle_y(Deg(-90.))),
+        ("up", Quaternion::from_angle_x(Deg(-90.))),
+        ("down", Quaternion::from_angle_x(Deg(90.))),
+    ]
+}
+
+// Create a path for a new image in the screenshots folder of the app directory.
+pub fn new_path(name: &str) -> std::io::Result<PathBuf> {
+    let directory = app_data_dir().join(SCREENSHOT_DIRECTORY);
+    std::fs::create_dir_all(&directory)?;
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    Ok(directory.join(format!("{name}_{seconds}.png")))
+}
+
+// Save a captured frame as a PNG image.
+pub fn save_png(frame: &CapturedFrame, path: &Path) -> std::io::Result<()> {
+    let mut pixels = frame.pixels.clone();
+    if frame.order == PixelOrder::Bgra {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        frame.width,
+        frame.height,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(std::io::Error::other)
+}
+
+// Project the faces of a cube map, rendered with the rotations of `cube_faces`, to an equirectangular
+// panorama. The centre of the panorama looks forward from the camera.
+pub fn equirectangular(faces: &[CapturedFrame], width: u32, height: u32) -> CapturedFrame {
+    // Camera-space to face-space rotation of each face.
+    let rotations: Vec<Matrix3<f32>> = cube_faces()
+        .iter()
+        .map(|(_, rotation)| Matrix3::from(*rotation).transpose())
+        .collect();
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    for row in 0..height {
+        #[allow(clippy::cast_precision_loss)]
+        let latitude =
+            std::f32::consts::FRAC_PI_2 - (row as f32 + 0.5) / height as f32 * std::f32::consts::PI;
+        for column in 0..width {
+            #[allow(clippy::cast_precision_loss)]
+            let longitude =
+                (column as f32 + 0.5) / width as f32 * std::f32::consts::TAU - std::f32::consts::PI;
+            let direction = Vector3::new(
+                latitude.cos() * longitude.sin(),
+                latitude.sin(),
+                latitude.cos() * longitude.cos(),
+            );
+
+            // Sample the face which the direction points most directly into.
+            let (face, local) = rotations
+                .iter()
+                .map(|rotation| rotation * direction)
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.z.total_cmp(&b.z))
+                .unwrap();
+            pixels.extend_from_slice(&sample(&faces[face], local.x / local.z, -local.y / local.z));
+        }
+    }
+
+    CapturedFrame {
+        width,
+        height,
+        order: faces[0].order,
+        pixels,
+    }
+}
+
+// Bilinearly sample a frame at coordinates from -1 to 1 across its width and height.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn sample(frame: &CapturedFrame, x: f32, y: f32) -> [u8; 4] {
+    let (width, height) = (frame.width as usize, frame.height as usize);
+    let x = ((x + 1.) * 0.5 * width as f32 - 0.5).clamp(0., (width - 1) as f32);
+    let y = ((y + 1.) * 0.5 * height as f32 - 0.5).clamp(0., (height - 1) as f32);
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x.fract(), y.fract());
+
+    let texel =
+        |x: usize, y: usize, channel: usize| f32::from(frame.pixels[(y * width + x) * 4 + channel]);
+    std::array::from_fn(|channel| {
+        let top = texel(x0, y0, channel) * (1. - fx) + texel(x1, y0, channel) * fx;
+        let bottom = texel(x0, y1, channel) * (1. - fx) + texel(x1, y1, channel) * fx;
+        (top * (1. - fy) + bottom * fy).round() as u8
+    })
+}
@@ -0,0 +1,80 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// World seeds can be written as numbers or as any other text, such as "purple dragon", which is hashed to a number.
+// The hash must never change so that phrases shared between players and versions keep leading to the same world.
+
+// The seed written as the given text, which is the number itself when the text is one.
+pub fn from_text(text: &str) -> u64 {
+    let text = text.trim();
+    text.parse().unwrap_or_else(|_| sip_hash(text.as_bytes()))
+}
+
+// Whether the given text is a phrase rather than a seed's number.
+pub fn is_phrase(text: &str) -> bool {
+    text.trim().parse::<u64>().is_err()
+}
+
+// SipHash-2-4 with a key of zero. The standard library's hashers aren't guaranteed to stay the same between
+// releases, so the algorithm is written out here.
+fn sip_hash(bytes: &[u8]) -> u64 {
+    fn round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+    fn compress(v: &mut [u64; 4], word: u64) {
+        v[3] ^= word;
+        round(v);
+        round(v);
+        v[0] ^= word;
+    }
+
+    let mut v = [
+        0x736f_6d65_7073_6575,
+        0x646f_7261_6e64_6f6d,
+        0x6c79_6765_6e65_7261,
+        0x7465_6462_7974_6573,
+    ];
+    let words = bytes.chunks_exact(8);
+    let remainder = words.remainder();
+    for word in words {
+        let mut le_bytes = [0; 8];
+        le_bytes.copy_from_slice(word);
+        compress(&mut v, u64::from_le_bytes(le_bytes));
+    }
+
+    // The last word holds the remaining bytes and the lowest byte of the length.
+    let mut last = [0; 8];
+    last[..remainder.len()].copy_from_slice(remainder);
+    last[7] = bytes.len().to_le_bytes()[0];
+    compress(&mut v, u64::from_le_bytes(last));
+
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
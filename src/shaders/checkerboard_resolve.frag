@@ -0,0 +1,75 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+#version 450
+#extension GL_EXT_samplerless_texture_functions : require
+precision highp float;
+
+layout (location = 0) in vec2 coord;
+layout (location = 0) out vec4 fragColor;
+layout (location = 1) out float hitDistance;
+
+// Half of the scene's pixels, ray-marched into images half as wide as the scene. Which of each pair of neighbouring
+// pixels was marched alternates between rows and frames, matching `ray_march_voxels.frag`.
+layout (set = 0, binding = 0) uniform texture2D marchedColour;
+layout (set = 0, binding = 1) uniform texture2D marchedDistance;
+
+#ifdef WGPU
+layout (set = 0, binding = 2) uniform Push {
+#else
+layout (push_constant) uniform Push {
+#endif
+	uint frame;
+} push;
+
+// Read the marched pixel standing in for a pixel of the scene, clamping to the edges of the image.
+void marched(ivec2 pixel, out vec4 colour, out float surfaceDistance) {
+	ivec2 size = textureSize(marchedColour, 0);
+	ivec2 p = clamp(ivec2(pixel.x >> 1, pixel.y), ivec2(0), size - 1);
+	colour = texelFetch(marchedColour, p, 0);
+	surfaceDistance = texelFetch(marchedDistance, p, 0).r;
+}
+
+void main(void) {
+	ivec2 pixel = ivec2(gl_FragCoord.xy);
+	if ((pixel.x & 1) == ((pixel.y + int(push.frame)) & 1)) {
+		marched(pixel, fragColor, hitDistance);
+		return;
+	}
+
+	// The four neighbours of a skipped pixel were all ray-marched. Interpolate along the pair whose surfaces are closest
+	// in distance, so that edges stay sharp instead of blurring across them.
+	vec4 left, right, up, down;
+	float leftDistance, rightDistance, upDistance, downDistance;
+	marched(pixel - ivec2(1, 0), left, leftDistance);
+	marched(pixel + ivec2(1, 0), right, rightDistance);
+	marched(pixel - ivec2(0, 1), up, upDistance);
+	marched(pixel + ivec2(0, 1), down, downDistance);
+	float horizontal = abs(leftDistance - rightDistance);
+	float vertical = abs(upDistance - downDistance);
+	if (horizontal < 0.5*vertical) {
+		fragColor = 0.5*(left + right);
+		hitDistance = 0.5*(leftDistance + rightDistance);
+	} else if (vertical < 0.5*horizontal) {
+		fragColor = 0.5*(up + down);
+		hitDistance = 0.5*(upDistance + downDistance);
+	} else {
+		fragColor = 0.25*(left + right + up + down);
+		hitDistance = 0.25*(leftDistance + rightDistance + upDistance + downDistance);
+	}
+}
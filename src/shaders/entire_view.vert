@@ -0,0 +1,34 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+#version 450
+layout (location = 0) out vec2 coord;
+vec2 quad[4] = vec2[] (
+	vec2(-1.0, -1.0),
+	vec2(-1.0,  1.0),
+	vec2( 1.0, -1.0),
+	vec2( 1.0,  1.0)
+);
+void main() {
+	gl_Position = vec4(quad[gl_VertexIndex], 0.0, 1.0);
+	coord = quad[gl_VertexIndex];
+#ifdef WGPU
+	// The Y-axis of clip space points up in wgpu, but down in Vulkan.
+	gl_Position.y = -gl_Position.y;
+#endif
+}
@@ -0,0 +1,150 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+#version 450
+precision highp float;
+layout (location = 0) in vec2 coord;
+
+layout (location = 0) out vec4 fragColor;
+
+// The wgpu backend passes the map's values in a uniform buffer, since push constants aren't available everywhere.
+#ifdef WGPU
+layout (set = 0, binding = 1) uniform Push {
+#else
+layout (push_constant) uniform Push {
+#endif
+	vec4 camera_quaternion;
+	vec3 camera_position;
+	float aspect_ratio;
+	vec2 centre;
+	float scale;
+	uint ray_march;
+} push;
+
+struct Voxel {
+	vec4 averageColour;
+	uint ftl;
+	uint ftr;
+	uint fbl;
+	uint fbr;
+	uint btl;
+	uint btr;
+	uint bbl;
+	uint bbr;
+	uint vtype;
+};
+const uint emptyVoxel = 0xFFFFFFFF;
+
+layout(set = 0, binding = 0) readonly buffer VoxelOctree {
+	Voxel voxels[];
+} voxelOctree;
+
+const int maxDepth = 15;
+const int maxDescentSteps = 64;
+const vec3 emptyColour = vec3(0.03, 0.035, 0.05);
+const vec3 outsideColour = vec3(0.0);
+const vec3 playerColour = vec3(1.0, 0.95, 0.3);
+
+// Size of the player's marker and the line showing their heading, relative to the area shown.
+const float markerRadius = 0.025;
+const float headingLength = 0.1;
+const float lineWidth = 0.006;
+
+vec3 rotateByQuaternion(vec3 v, vec4 q) {
+	vec3 temp = cross(q.xyz, cross(q.xyz, v) + q.w * v);
+	return v + temp+temp;
+}
+
+// Find the voxel containing a point in the root voxel, descending until a voxel without children is reached.
+// The depth reached is returned for outlining the smaller voxels.
+uint voxelAt(vec3 p, out int depth) {
+	uint index = 0;
+	for(depth = 0; depth < maxDepth; depth++) {
+		if(index == emptyVoxel || voxelOctree.voxels[index].vtype != 0) break;
+		Voxel voxel = voxelOctree.voxels[index];
+		if(p.x > 0.0) {
+			if(p.y > 0.0) {
+				index = p.z > 0.0 ? voxel.btr : voxel.ftr;
+			} else {
+				index = p.z > 0.0 ? voxel.bbr : voxel.fbr;
+			}
+		} else {
+			if(p.y > 0.0) {
+				index = p.z > 0.0 ? voxel.btl : voxel.ftl;
+			} else {
+				index = p.z > 0.0 ? voxel.bbl : voxel.fbl;
+			}
+		}
+		p = 2.0*p - (2.0*vec3(greaterThan(p, vec3(0.0))) - 1.0);
+	}
+	return index;
+}
+
+// Distance from a point to the line segment between two others.
+float segmentDistance(vec2 p, vec2 a, vec2 b) {
+	vec2 ab = b - a;
+	float t = clamp(dot(p - a, ab) / dot(ab, ab), 0.0, 1.0);
+	return length(p - a - t*ab);
+}
+
+// Draw the octree seen from above with +Z up the view, either sliced through the player's height or ray-marched
+// down from it to the first voxel below.
+void main() {
+	vec2 uv = coord * vec2(max(push.aspect_ratio, 1.0), max(1.0 / push.aspect_ratio, 1.0));
+	vec2 position = push.centre + push.scale*vec2(uv.x, -uv.y);
+	vec3 colour = outsideColour;
+	if(all(lessThanEqual(abs(position), vec2(1.0)))) {
+		int depth;
+		float height = clamp(push.camera_position.y, -1.0, 1.0);
+		uint index = voxelAt(vec3(position.x, height, position.y), depth);
+
+		// Step down through the bottom of each empty voxel, so that voxels further below appear darker.
+		if(push.ray_march != 0) {
+			for(int i = 0; i < maxDescentSteps && index == emptyVoxel; i++) {
+				float size = exp2(float(1 - depth));
+				height = floor((height + 1.0)/size)*size - 1.0 - 0.001*size;
+				if(height <= -1.0) break;
+				index = voxelAt(vec3(position.x, height, position.y), depth);
+			}
+		}
+		if(index == emptyVoxel) {
+			colour = emptyColour;
+		} else {
+			float drop = push.camera_position.y - height;
+			colour = voxelOctree.voxels[index].averageColour.rgb * mix(1.0, 0.35, clamp(drop, 0.0, 1.0));
+		}
+
+		// Darken the edges of voxels so that neighbours of the same colour can be told apart.
+		vec2 cell = fract((position + 1.0) * exp2(float(depth - 1)));
+		float edge = min(min(cell.x, 1.0 - cell.x), min(cell.y, 1.0 - cell.y)) / exp2(float(depth - 1));
+		colour *= mix(0.6, 1.0, smoothstep(0.0, 0.004*push.scale, edge));
+	}
+
+	// Mark the player, with a line in the direction they are flying.
+	vec2 player = push.camera_position.xz;
+	vec3 forward = rotateByQuaternion(vec3(0.0, 0.0, 1.0), push.camera_quaternion);
+	vec2 heading = length(forward.xz) > 0.0 ? normalize(forward.xz) : vec2(0.0);
+	float marker = min(
+		length(position - player) - markerRadius*push.scale,
+		segmentDistance(position, player, player + headingLength*push.scale*heading) - lineWidth*push.scale);
+	marker /= push.scale;
+	colour = mix(colour, vec3(0.0), smoothstep(0.006, 0.0, marker - 0.004));
+	colour = mix(colour, playerColour, smoothstep(0.003, 0.0, marker));
+
+	fragColor = vec4(colour, 1.0);
+}
@@ -0,0 +1,30 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+#version 450
+precision highp float;
+layout (location = 0) in vec2 coord;
+layout (location = 1) in vec4 colour;
+
+layout (location = 0) out vec4 fragColor;
+
+// Particles are blended additively, softening towards the sides of each streak.
+void main(void) {
+	float falloff = 1.0 - coord.y*coord.y;
+	fragColor = vec4(colour.rgb, colour.a * falloff);
+}
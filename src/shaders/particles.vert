@@ -0,0 +1,110 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+#version 450
+precision highp float;
+
+// A particle of `ParticleInstance` in `particles.rs`. The streak runs back from the position.
+struct Particle {
+	vec3 position;
+	float size;
+	vec3 streak;
+	float padding;
+	vec4 colour;
+};
+
+layout (set = 0, binding = 0) readonly buffer Particles {
+	Particle particles[];
+};
+
+layout (push_constant) uniform Push {
+	vec4 camera_quaternion;
+	vec3 camera_position;
+	float aspect_ratio;
+	float fov_y;
+	uint projection;
+} push;
+
+layout (location = 0) out vec2 coord;
+layout (location = 1) out vec4 colour;
+
+// Corners of the quad drawn for each particle, along its streak then across it.
+vec2 quad[4] = vec2[] (
+	vec2(-1.0, -1.0),
+	vec2(-1.0,  1.0),
+	vec2( 1.0, -1.0),
+	vec2( 1.0,  1.0)
+);
+
+// Particles closer to the camera than this aren't drawn.
+const float nearDistance = 1e-5;
+
+vec3 rotateByQuaternion(vec3 v, vec4 q) {
+	vec3 temp = cross(q.xyz, cross(q.xyz, v) + q.w * v);
+	return v + temp+temp;
+}
+
+// Projections of the view, matching `Projection` in `projection.rs`.
+const uint projectionCylindrical = 1;
+const uint projectionPanini = 2;
+
+// The point of the view where a camera-space point is seen, measured in tangents at the centre of the view, and the
+// depth that sizes shrink with there. This matches `projectView` in `ray_march_voxels.frag`.
+vec3 projectView(vec3 v) {
+	if (push.projection == projectionCylindrical) {
+		float r = length(v.xz);
+		return vec3(atan(v.x, v.z), v.y / r, r);
+	}
+	if (push.projection == projectionPanini) {
+		float depth = 0.5*(length(v.xz) + v.z);
+		return vec3(v.xy / depth, depth);
+	}
+	return vec3(v.xy / v.z, v.z);
+}
+
+// Scale a projected point onto the view, matching the rays cast by `ray_march_voxels.frag`.
+// The horizontal axis is scaled by the aspect ratio so that distances are the same in both directions.
+vec2 toView(vec3 p) {
+	return vec2(p.x, -p.y) / push.fov_y;
+}
+
+void main() {
+	Particle particle = particles[gl_InstanceIndex];
+	vec4 inverse = vec4(-push.camera_quaternion.xyz, push.camera_quaternion.w);
+	vec3 head = projectView(rotateByQuaternion(particle.position - push.camera_position, inverse));
+	vec3 tail = projectView(rotateByQuaternion(particle.position - particle.streak - push.camera_position, inverse));
+	coord = quad[gl_VertexIndex];
+	colour = particle.colour;
+
+	// Particles behind the camera are moved outside of the view to be clipped.
+	if (min(head.z, tail.z) < nearDistance) {
+		gl_Position = vec4(0.0, 0.0, 2.0, 1.0);
+		return;
+	}
+
+	// Stretch a quad from the tail to the head, rounded off by the fragment shader.
+	vec2 a = toView(tail);
+	vec2 b = toView(head);
+	float width = particle.size / (head.z * push.fov_y);
+	vec2 along = b - a;
+	vec2 direction = dot(along, along) > 1e-12 ? normalize(along) : vec2(1.0, 0.0);
+	vec2 across = vec2(-direction.y, direction.x);
+	vec2 end = coord.x < 0.0 ? a - width*direction : b + width*direction;
+	vec2 p = end + coord.y*width*across;
+	gl_Position = vec4(p.x / push.aspect_ratio, p.y, 0.0, 1.0);
+}
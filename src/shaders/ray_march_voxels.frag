@@ -21,15 +21,45 @@ precision highp float;
 layout (location = 0) in vec2 coord;
 
 layout (location = 0) out vec4 fragColor;
+layout (location = 1) out float hitDistance;
 
+// The wgpu backend passes per-frame values in a uniform buffer, since push constants aren't available everywhere.
+#ifdef WGPU
+layout (set = 0, binding = 1) uniform Push {
+#else
 layout (push_constant) uniform Push {
+#endif
 	vec3 camera_position;
 	float time;
 	vec4 camera_quaternion;
 	vec3 light_dir;
 	float aspect_ratio;
+	vec4 portal_highlight;
+	float fov_y;
+	float exposure;
+	float focus_distance;
+	float aperture;
+	uint shadow_quality;
+	uint ambient_occlusion;
+	uint traversal;
+	uint temporal;
+	vec4 previous_camera_quaternion;
+	vec3 previous_camera_position;
+	uint view;
 } push;
 
+// The previous frame's colour and distance to the first surface seen by each pixel, which temporal reprojection reuses.
+#ifdef WGPU
+layout (set = 1, binding = 0) uniform texture2D previousColourTexture;
+layout (set = 1, binding = 1) uniform texture2D previousDistanceTexture;
+layout (set = 1, binding = 2) uniform sampler historySampler;
+#define previousColour sampler2D(previousColourTexture, historySampler)
+#define previousDistance sampler2D(previousDistanceTexture, historySampler)
+#else
+layout (set = 1, binding = 0) uniform sampler2D previousColour;
+layout (set = 1, binding = 1) uniform sampler2D previousDistance;
+#endif
+
 struct Voxel {
 	vec4 averageColour;
 	uint ftl;
@@ -44,15 +74,59 @@ struct Voxel {
 };
 const uint emptyVoxel = 0xFFFFFFFF;
 
+// Types of voxel leading to another world, matching `VoxelType` in `voxels.rs`.
+const uint portalVoxel = 2;
+const uint returnPortalVoxel = 4;
+const uint speedPortalVoxel = 5;
+const uint wormholeVoxel = 6;
+bool isPortal(uint vtype) {
+	return vtype == portalVoxel || (vtype >= returnPortalVoxel && vtype <= wormholeVoxel);
+}
+
+// The colour that each class of portal pulses towards. Each is a different hue of the chosen highlight, so that
+// colour-blind palettes still tell them apart.
+vec3 portalHighlight(uint vtype) {
+	vec3 highlight = push.portal_highlight.rgb;
+	switch(vtype) {
+	case returnPortalVoxel: return 1.0 - highlight;
+	case speedPortalVoxel: return highlight.gbr;
+	case wormholeVoxel: return highlight.brg;
+	default: return highlight;
+	}
+}
+
 layout(set = 0, binding = 0) readonly buffer VoxelOctree {   
 	Voxel voxels[];
 } voxelOctree;
 
+// Lights found in the world when it was generated, such as the glow of portals, and the colours of its theme's sky.
+// Directional lights have a `w` of 0, and point lights store their range in the `w` of their colour.
+const uint maximumLights = 16;
+struct Light {
+	vec4 position;
+	vec4 colour;
+};
+layout(set = 0, binding = 2) uniform Lights {
+	Light lights[maximumLights];
+	vec4 skyColour;
+	vec4 groundColour;
+	vec4 fogColour;
+	uint count;
+} worldLights;
+
 const float pi = 3.14159265358;
 const float e = 2.718281828;
-const int maxIterations = 35;
-const float maxIterationsF = float(maxIterations);
-const int globalMaxDepth = 15;
+
+// `push.traversal` keeps the bounces allowed off mirrors in its lowest byte, the deepest level of the octree marched in
+// the next, and the steps marched before the fog is opaque above them, all chosen by the graphics settings.
+const uint traversalDepthShift = 8;
+const uint traversalStepsShift = 16;
+const uint traversalFieldMask = 0xFFu;
+#define maxIterations int((push.traversal >> traversalStepsShift) & traversalFieldMask)
+#define maxIterationsF float(maxIterations)
+#define globalMaxDepth int((push.traversal >> traversalDepthShift) & traversalFieldMask)
+#define reflectionBounces int(push.traversal & traversalFieldMask)
+
 const float epsilon = 0.005;
 const float unitEpsilon = 1.001;
 const vec3 dirX = vec3(1.0, 0.0, 0.0);
@@ -65,9 +139,6 @@ const vec3 negDirZ = vec3(0.0, 0.0, -1.0);
 const float goalRadiusSquared = 0.75;
 
 // Phong lighting
-const vec4 fogColour = vec4(0.42, 0.525, 0.45, 1.0);
-const vec4 skyColour = vec4(0.08, 0.2, 0.75, 1.0);
-const vec4 groundColour = vec4(0.2, 0.08, 0.08, 1.0);
 const float ambientStrength = 0.5;
 const vec3 lightColor = vec3(0.85);
 const vec3 ambientLight = ambientStrength * lightColor;
@@ -221,9 +292,18 @@ float goalVoxelTraversal(inout vec3 p, vec3 d) {
 	return -1.0;
 }
 
+// Shadow qualities, matching `ShadowQuality` in the game options.
+const uint shadowsOff = 0;
+const uint shadowsSoft = 2;
+
+// Soft shadows treat the shadow ray as a cone, darkened where the empty cells it crosses are narrow relative to its width.
+const float softShadowSharpness = 12.0;
 float castShadowRay(vec3 p, vec3 d, vec3 invD, int maxDepth) {
+	if(push.shadow_quality == shadowsOff) return 1.0;
 	if(!projectToRootVoxel(p, d, invD)) return 1.0;
 
+	vec3 start = p;
+	float light = 1.0;
 	int i = 0;
 	do {
 		vec3 s = p;
@@ -232,11 +312,17 @@ float castShadowRay(vec3 p, vec3 d, vec3 invD, int maxDepth) {
 
 		// Is empty or filled?
 		if(index == emptyVoxel) {
+			if(push.shadow_quality == shadowsSoft) {
+				float travelled = length(p - start);
+				if(travelled > 0.0) {
+					light = min(light, softShadowSharpness * scale / travelled);
+				}
+			}
 			float t = escapeCubeDistance(s, d, invD) * scale;
 			p += t * d;
 		} else {
 			Voxel voxel = voxelOctree.voxels[index];
-			if(voxel.vtype == 2) {
+			if(isPortal(voxel.vtype)) {
 				// We are in a goal voxel! Traverse and check for hit
 				float t = goalVoxelTraversal(s, d);
 				if(t >= 0.0) {
@@ -250,7 +336,7 @@ float castShadowRay(vec3 p, vec3 d, vec3 invD, int maxDepth) {
 				return 0.0;
 			}
 		}
-		if(!insideCube(p)) return 1.0;
+		if(!insideCube(p)) return smoothstep(0.0, 1.0, light);
 	} while(++i < maxIterations);
 	return 0.0;
 }
@@ -259,18 +345,61 @@ const float maxBrightness = 1.3;
 const float maxBrightnessR2 = maxBrightness*maxBrightness;
 vec4 scaleColor(float si, vec4 col) {
 	float temp = 1.0 - si/maxIterationsF;
-	return mix(fogColour, col, temp);
+	return mix(worldLights.fogColour, col, temp);
 }
 
 vec3 gradient;
-vec3 phongLighting(vec3 c, float shadow) {
+
+// Diffuse light from the world's lights, which don't cast shadows. Point lights fade smoothly to nothing at their range.
+vec3 worldLighting(vec3 p) {
+	vec3 diffuse = vec3(0.0);
+	for(uint i = 0; i < min(worldLights.count, maximumLights); ++i) {
+		Light light = worldLights.lights[i];
+		if(light.position.w == 0.0) {
+			diffuse += max(dot(gradient, light.position.xyz), 0.0) * light.colour.rgb;
+		} else {
+			vec3 l = light.position.xyz - p;
+			float falloff = max(1.0 - dot(l, l) / (light.colour.a * light.colour.a), 0.0);
+			diffuse += max(dot(gradient, normalize(l)), 0.0) * falloff * falloff * light.colour.rgb;
+		}
+	}
+	return diffuse;
+}
+
+vec3 phongLighting(vec3 p, vec3 c, float shadow, float occlusion) {
 	vec3 diffuse = max(dot(gradient, push.light_dir), 0.0) * lightColor;
-	return (ambientLight + diffuse * shadow) * c;
+	return (ambientLight * occlusion + diffuse * shadow + worldLighting(p)) * c;
+}
+
+// Ambient occlusion probes the cells diagonally above a face, at two distances relative to the voxel's size.
+// Each probe which lands in a filled voxel darkens the ambient light, so crevices and inside corners are shaded.
+const float occlusionStrength = 0.6;
+float ambientOcclusion(vec3 p, vec3 n, float scale, int maxDepth) {
+	if(push.ambient_occlusion == 0) return 1.0;
+
+	// Axis-aligned normals are perpendicular to their own permutations.
+	vec3 t1 = n.yzx;
+	vec3 t2 = n.zxy;
+	float occluded = 0.0;
+	for(int k = 1; k <= 2; ++k) {
+		float h = 0.6 * float(k) * scale;
+		vec3 probes[4] = vec3[4](n + t1, n - t1, n + t2, n - t2);
+		for(int j = 0; j < 4; ++j) {
+			vec3 q = p + h * probes[j];
+			if(!insideCube(q)) continue;
+			float probeScale = 1.0;
+			if(voxelIndex(q, probeScale, maxDepth) != emptyVoxel) {
+				// Nearer probes matter more.
+				occluded += 1.0 / float(k);
+			}
+		}
+	}
+	return 1.0 - occlusionStrength * occluded / 6.0;
 }
 
 vec4 escapeColour(vec3 d) {
 	float temp = dot(dirY, d);
-	vec4 groundSkyColour = mix(groundColour, skyColour, (sqrt(abs(temp))*sign(temp) + 1.0)/2.0);
+	vec4 groundSkyColour = mix(worldLights.groundColour, worldLights.skyColour, (sqrt(abs(temp))*sign(temp) + 1.0)/2.0);
 	return mix(groundSkyColour, vec4(lightColor, 1.0), clamp(64.0*dot(d, push.light_dir) - 63.0, 0.0, 1.0));
 }
 
@@ -318,10 +447,31 @@ float cloudDensity(mat2 m, float cloudSeed, vec3 p) {
 	return density;
 }
 
+// Distance to the first surface hit by the last ray cast, before any reflection. Escaping rays are given a far distance.
+const float farDistance = 64.0;
+float firstHitDistance;
+
+// What the debug views show of the first surface hit: the steps taken to reach it, the scale and type of its voxel,
+// and its normal. Escaping rays count every step taken and have no voxel.
+int firstHitSteps;
+float firstHitScale;
+uint firstHitType;
+vec3 firstHitNormal;
+void recordFirstHit(float surfaceDistance, int steps, float scale, uint vtype) {
+	firstHitDistance = surfaceDistance;
+	firstHitSteps = steps;
+	firstHitScale = scale;
+	firstHitType = vtype;
+	firstHitNormal = gradient;
+}
+
 const float minTravel = 0.000005;
 vec4 castVoxelRay(vec3 p, vec3 d) {
 	// Remember source position of cast
 	vec3 origin = p;
+	firstHitDistance = farDistance;
+	firstHitSteps = 0;
+	firstHitType = emptyVoxel;
 
 	// Reset gradient
 	gradient = vec3(0.0);
@@ -362,7 +512,7 @@ vec4 castVoxelRay(vec3 p, vec3 d) {
 			}
 		} else {
 			Voxel voxel = voxelOctree.voxels[index];
-			if(voxel.vtype == 2) {
+			if(isPortal(voxel.vtype)) {
 				// We are in a goal voxel! Traverse and check for hit
 				float t = goalVoxelTraversal(s, d);
 				float r2 = dot(s, s);
@@ -371,15 +521,16 @@ vec4 castVoxelRay(vec3 p, vec3 d) {
 					t *= scale;
 					p += t * d;
 					gradient = normalize(s);
+					if(reflections == 0) recordFirstHit(length(p - origin), i, scale, voxel.vtype);
 
 					// Apply portal coloring.
 					float colTemp = sin(7.0*push.time + 1.25*s.x + 1.5*s.y - 1.5*s.z);
 					float colTemp2 = cos(8.0*push.time - 2.5*s.x * 3.0*s.y * 2.0*s.z);
 					colTemp = (colTemp + colTemp2) / 2.0;
 					vec3 portalCol = voxel.averageColour.xyz;
-					portalCol = mix(portalCol, vec3(0.0), min(colTemp, tan(8.0*push.time - 12.0*(dot(s, d)))));
+					portalCol = mix(portalCol, portalHighlight(voxel.vtype), push.portal_highlight.a * min(colTemp, tan(8.0*push.time - 12.0*(dot(s, d)))));
 
-					col += col + col + col + vec4(phongLighting(portalCol, castShadowRay(p, push.light_dir, 1.0 / push.light_dir, maxDepth)), 1.0);
+					col += col + col + col + vec4(phongLighting(p, portalCol, castShadowRay(p, push.light_dir, 1.0 / push.light_dir, maxDepth), 1.0), 1.0);
 
 					return scaleColor(i, col/col.w);
 				} else {
@@ -402,6 +553,7 @@ vec4 castVoxelRay(vec3 p, vec3 d) {
 				gradient = cubeNorm(s);
 				vec3 t = projectToOutsideDistance(s);
 				p += t*scale;
+				if(reflections == 0) recordFirstHit(length(p - origin), i, scale, voxel.vtype);
 
 				// Cloud end density.
 				float endDensity = cloudDensity(m, cloudSeed, p);
@@ -412,7 +564,7 @@ vec4 castVoxelRay(vec3 p, vec3 d) {
 					col = mix(col, vec4(1.0), accCloudDensity);
 				}
 
-				if(voxel.vtype == 3 && reflections < 2) {
+				if(voxel.vtype == 3 && reflections < reflectionBounces) {
 					// We have hit a mirror voxel. Reflect and continue
 					reflections += 1;
 					d -= 2.0*dot(d, gradient)*gradient;
@@ -429,28 +581,264 @@ vec4 castVoxelRay(vec3 p, vec3 d) {
 					mirrorFuzz += 0.0078125*iq_inspired_noise(uv, 0);
 					mirrorFuzz *= 0.16;
 
-					col += col + col + col + vec4(vec3(mirrorFuzz) + phongLighting(voxel.averageColour.xyz, castShadowRay(p, push.light_dir, 1.0 / push.light_dir, maxDepth)), 1.0);
+					col += col + col + col + vec4(vec3(mirrorFuzz) + phongLighting(p, voxel.averageColour.xyz, castShadowRay(p, push.light_dir, 1.0 / push.light_dir, maxDepth), ambientOcclusion(p, gradient, scale, maxDepth)), 1.0);
 				} else {
 					vec3 t = abs(s);
 					float borderOutline = pow(min(abs(t.x - t.y), min(abs(t.x - t.z), abs(t.y - t.z))), 0.16);
-					col += col + col + col + vec4(borderOutline*phongLighting(voxel.averageColour.xyz, castShadowRay(p, push.light_dir, 1.0 / push.light_dir, maxDepth)), 1.0);
+					col += col + col + col + vec4(borderOutline*phongLighting(p, voxel.averageColour.xyz, castShadowRay(p, push.light_dir, 1.0 / push.light_dir, maxDepth), ambientOcclusion(p, gradient, scale, maxDepth)), 1.0);
 
 					return scaleColor(i, col/col.w);
 				}
 			}
 		}
 	} while(++i < maxIterations && insideCube(p));
+	if(reflections == 0) firstHitSteps = i;
 	col += col + col + col + escapeColour(d);
 	return scaleColor(i, col/col.w);
 }
 
-const float fov = (pi/1.75) / 2.0;
-const float fovY = sin(fov);
-float fovX = push.aspect_ratio * fovY;
-void main(void) {
-	vec3 direction = normalize(vec3(coord.x*fovX, -coord.y*fovY, 1.0));
-	direction = rotateByQuaternion(direction, push.camera_quaternion);
+// Hazards moving through open space, placed when the world was generated. Each is a cube whose centre follows
+// `centre + cos(angle)*a + sin(angle)*b`, matching `Hazard` in `hazards.rs`.
+const uint maximumHazards = 6;
+struct Hazard {
+	vec4 centre;
+	vec4 a;
+	vec4 b;
+};
+layout(set = 0, binding = 3) uniform Hazards {
+	Hazard hazards[maximumHazards];
+	uint count;
+} worldHazards;
+
+const vec3 hazardColour = vec3(1.0, 0.18, 0.08);
+
+// Cast a ray through the world and its hazards, drawing whichever is hit first. Hazards only hide what is behind
+// them, so they aren't seen in mirrors and don't cast shadows.
+vec4 castRay(vec3 p, vec3 d) {
+	vec4 col = castVoxelRay(p, d);
+	float nearest = firstHitDistance;
+	vec3 normal = -d;
+	vec3 invD = 1.0 / d;
+	for(uint i = 0; i < worldHazards.count; i++) {
+		Hazard hazard = worldHazards.hazards[i];
+		float angle = hazard.a.w*push.time + hazard.b.w;
+		vec3 centre = hazard.centre.xyz + cos(angle)*hazard.a.xyz + sin(angle)*hazard.b.xyz;
+		vec3 t0 = (centre - hazard.centre.w - p) * invD;
+		vec3 t1 = (centre + hazard.centre.w - p) * invD;
+		vec3 tMin = min(t0, t1);
+		vec3 tMax = max(t0, t1);
+		float tNear = max(max(tMin.x, tMin.y), tMin.z);
+		float tFar = min(min(tMax.x, tMax.y), tMax.z);
+		if(tNear <= tFar && tNear > 0.0 && tNear < nearest) {
+			// The face entered last is the one hit.
+			nearest = tNear;
+			normal = -sign(d) * step(tMin.yzx, tMin) * step(tMin.zxy, tMin);
+		}
+	}
+	if(nearest < firstHitDistance) {
+		// Hazards glow with a pulse, and their faces are lit by the sun.
+		firstHitDistance = nearest;
+		float pulse = 0.75 + 0.25*sin(6.0*push.time);
+		col = vec4(hazardColour * (pulse + 0.4*max(dot(normal, push.light_dir), 0.0)), 1.0);
+	}
+	return col;
+}
+
+// Temporal reprojection flags, matching those in `renderer.rs`. The upper bits count frames.
+const uint temporalReprojection = 1;
+const uint temporalCheckerboard = 2;
+const uint temporalFrameShift = 8;
+
+// `push.view` keeps the debug view in its lowest byte, the stereo mode in the next, and the projection above them.
+const uint viewStereoShift = 8;
+const uint viewProjectionShift = 16;
+const uint viewFieldMask = 0xFFu;
+
+// Projections of the view, matching `Projection` in `projection.rs`.
+const uint projectionCylindrical = 1;
+const uint projectionPanini = 2;
+
+// The camera-space direction through a point of the view, measured in tangents at the centre of the view.
+vec3 viewDirection(vec2 p) {
+	uint projection = push.view >> viewProjectionShift;
+	if (projection == projectionCylindrical) {
+		return normalize(vec3(sin(p.x), p.y, cos(p.x)));
+	}
+	if (projection == projectionPanini) {
+		// The view is wrapped around a cylinder which is seen from its far side.
+		float angle = 2.0*atan(0.5*p.x);
+		return normalize(vec3(sin(angle), 0.5*p.y*(1.0 + cos(angle)), cos(angle)));
+	}
+	return normalize(vec3(p, 1.0));
+}
+
+// The point of the view where a camera-space point is seen, and the depth that sizes shrink with there, which isn't
+// positive where the projection can't show the point. This is the inverse of `viewDirection`.
+vec3 projectView(vec3 v) {
+	uint projection = push.view >> viewProjectionShift;
+	if (projection == projectionCylindrical) {
+		float r = length(v.xz);
+		return vec3(atan(v.x, v.z), v.y / r, r);
+	}
+	if (projection == projectionPanini) {
+		float depth = 0.5*(length(v.xz) + v.z);
+		return vec3(v.xy / depth, depth);
+	}
+	return vec3(v.xy / v.z, v.z);
+}
+
+// Project a point into the previous frame, returning false when it was outside the previous view.
+bool projectToPreviousFrame(vec3 point, float fovX, float fovY, out vec2 uv) {
+	vec4 inverse = vec4(-push.previous_camera_quaternion.xyz, push.previous_camera_quaternion.w);
+	vec3 local = projectView(rotateByQuaternion(point - push.previous_camera_position, inverse));
+	if(local.z <= 0.0) return false;
+	uv = 0.5*vec2(local.x / fovX, -local.y / fovY) + 0.5;
+	return all(greaterThanEqual(uv, vec2(0.0))) && all(lessThanEqual(uv, vec2(1.0)));
+}
+
+// Reuse the previous frame's colour when the surface it saw lies along this pixel's ray.
+// The point is first guessed from the previous distance at this pixel, then checked against the distance the previous frame
+// saw where that guess projects. Points off the ray by more than about a pixel have been disoccluded and must be marched.
+bool reproject(vec2 uv, vec3 d, float fovX, float fovY, out vec4 colour, out float surfaceDistance) {
+	vec3 guess = push.camera_position + d * texture(previousDistance, uv).r;
+	vec2 previousUv;
+	if(!projectToPreviousFrame(guess, fovX, fovY, previousUv)) return false;
+
+	vec2 previousCoord = 2.0*previousUv - 1.0;
+	vec3 previousDirection = viewDirection(vec2(previousCoord.x*fovX, -previousCoord.y*fovY));
+	vec3 point = push.previous_camera_position
+		+ rotateByQuaternion(previousDirection, push.previous_camera_quaternion) * texture(previousDistance, previousUv).r;
+	vec3 toPoint = point - push.camera_position;
+	surfaceDistance = length(toPoint);
+	float pixelAngle = 2.0 * fovY / float(textureSize(previousDistance, 0).y);
+	if(dot(d, toPoint) <= 0.0 || length(cross(d, toPoint)) > pixelAngle * surfaceDistance) return false;
+
+	colour = texture(previousColour, previousUv);
+	return true;
+}
+
+// Stereo modes, matching `StereoMode` in the game.
+const uint stereoAnaglyph = 1;
+const uint stereoSideBySide = 2;
+
+// Each eye sits this fraction of the convergence distance to either side of the camera, the stereographer's 1/30 rule.
+const float eyeSeparation = 1.0/30.0;
+
+// Debug views, matching `DebugView` in the game.
+const uint debugSteps = 1;
+const uint debugDepth = 2;
+const uint debugNormals = 3;
+const uint debugVoxelType = 4;
+
+// Colour the first surface hit by the last ray cast to show how it was found instead of how it is lit.
+const vec3 debugEscapeColour = vec3(0.1);
+vec3 debugColour(uint debugView) {
+	if(debugView == debugSteps) {
+		// Few steps are blue, through green, to red at the iteration limit.
+		float t = float(firstHitSteps) / maxIterationsF;
+		return vec3(smoothstep(0.5, 1.0, t), sin(pi*t), 1.0 - smoothstep(0.0, 0.5, t));
+	}
+	if(firstHitType == emptyVoxel) return debugEscapeColour;
+	if(debugView == debugDepth) {
+		// Each level of the octree cycles to a distinct hue.
+		float depth = round(-log2(firstHitScale));
+		return 0.5 + 0.5*cos(2.0*pi*(depth/6.0 + vec3(0.0, 0.33, 0.67)));
+	}
+	if(debugView == debugNormals) return 0.5*firstHitNormal + 0.5;
+
+	// Voxels subdivided past the depth limit are grey, solid voxels white, portals magenta, mirrors cyan, return
+	// portals orange, speed portals yellow, and wormholes blue.
+	const vec3 typeColours[7] = vec3[7](vec3(0.5), vec3(1.0), vec3(1.0, 0.2, 1.0), vec3(0.2, 1.0, 1.0), vec3(1.0, 0.55, 0.1),
+		vec3(1.0, 1.0, 0.2), vec3(0.25, 0.35, 1.0));
+	return typeColours[min(firstHitType, 6u)] * (0.6 + 0.4*abs(dot(firstHitNormal, vec3(0.36, 0.48, 0.8))));
+}
+
+// Depth-of-field averages rays from points spread across a lens, all passing through the same point on the focal plane.
+const int dofSamples = 8;
+const float goldenAngle = 2.39996323;
+
+// Render what is seen in a camera-space direction from the camera, or from an eye the given number of half-separations
+// to its right, and where the first surface seen is.
+vec4 render(vec3 direction, float eye, vec2 uv, float fovX, float fovY, uvec2 pixel, uint frame, uint debugView) {
 	vec3 pos = push.camera_position;
+	if (eye != 0.0) {
+		// The eye stays pointed at the point the camera sees at the convergence distance, which is the focus distance.
+		// Measuring it along the ray rather than forward keeps the eyes converging at the wide edges of the view.
+		vec3 offset = vec3(0.5*eye*eyeSeparation*push.focus_distance, 0.0, 0.0);
+		direction = normalize(direction * push.focus_distance - offset);
+		pos += rotateByQuaternion(offset, push.camera_quaternion);
+	}
+
+	if (debugView != 0) {
+		// Debug views show the surfaces themselves, so neither reprojection, depth-of-field, nor exposure apply.
+		castVoxelRay(pos, rotateByQuaternion(direction, push.camera_quaternion));
+		hitDistance = firstHitDistance;
+		return vec4(debugColour(debugView), 1.0);
+	}
+	vec4 colour;
+	if (push.aperture <= 0.0) {
+		vec3 d = rotateByQuaternion(direction, push.camera_quaternion);
+
+		// Each pixel of a 2x2 block is ray-marched in turn every fourth frame, so reprojected colours are never long out of date.
+		bool refresh = ((pixel.x & 1u) | ((pixel.y & 1u) << 1)) == (frame & 3u);
+		if ((push.temporal & temporalReprojection) != 0 && !refresh && reproject(uv, d, fovX, fovY, colour, hitDistance)) {
+			return colour;
+		}
+		colour = castRay(pos, d);
+	} else {
+		// The lens radius is relative to the focus distance, so that the blur looks the same at any scale.
+		vec3 focus = direction * (push.focus_distance / direction.z);
+		float lensRadius = push.aperture * push.focus_distance;
+		float angle = 2.0*pi*fract(sin(dot(coord, vec2(12.9898, 78.233))) * 43758.5453);
+		colour = vec4(0.0);
+		for (int i = 0; i < dofSamples; i++) {
+			float r = lensRadius * sqrt((float(i) + 0.5) / float(dofSamples));
+			float a = angle + float(i)*goldenAngle;
+			vec3 lens = vec3(r*cos(a), r*sin(a), 0.0);
+			colour += castRay(
+				pos + rotateByQuaternion(lens, push.camera_quaternion),
+				rotateByQuaternion(normalize(focus - lens), push.camera_quaternion));
+		}
+		colour /= float(dofSamples);
+	}
+	hitDistance = firstHitDistance;
+
+	// Exposure is measured in stops.
+	colour.rgb *= exp2(push.exposure);
+	return colour;
+}
 
-	fragColor = castVoxelRay(pos, direction);
-}
\ No newline at end of file
+void main(void) {
+	uvec2 pixel = uvec2(gl_FragCoord.xy);
+	uint frame = push.temporal >> temporalFrameShift;
+	vec2 uv = 0.5*coord + 0.5;
+	if ((push.temporal & temporalCheckerboard) != 0) {
+		// Only one of each pair of neighbouring pixels is ray-marched, into an image half as wide as the scene.
+		// Which one alternates between rows and frames, matching `checkerboard_resolve.frag`.
+		uv.x = (float(2u*pixel.x + ((pixel.y + frame) & 1u)) + 0.5) / float(textureSize(previousColour, 0).x);
+		frame >>= 1;
+	}
+	uint debugView = push.view & viewFieldMask;
+	uint stereo = (push.view >> viewStereoShift) & viewFieldMask;
+	float eye = 0.0;
+	if (stereo == stereoSideBySide) {
+		// The left eye is squeezed into the left half of the view and the right eye into the right half, as 3D
+		// displays expect.
+		eye = uv.x < 0.5 ? -1.0 : 1.0;
+		uv.x = fract(2.0*uv.x);
+	}
+	vec2 viewCoord = 2.0*uv - 1.0;
+
+	float fovY = push.fov_y;
+	float fovX = push.aspect_ratio * fovY;
+	vec3 direction = viewDirection(vec2(viewCoord.x*fovX, -viewCoord.y*fovY));
+
+	if (stereo == stereoAnaglyph) {
+		// Both eyes are rendered, the left seen through the red filter of the glasses and the right through cyan.
+		vec4 left = render(direction, -1.0, uv, fovX, fovY, pixel, frame, debugView);
+		fragColor = render(direction, 1.0, uv, fovX, fovY, pixel, frame, debugView);
+		fragColor.r = left.r;
+	} else {
+		fragColor = render(direction, eye, uv, fovX, fovY, pixel, frame, debugView);
+	}
+}
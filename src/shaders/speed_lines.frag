@@ -0,0 +1,96 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+
+#version 450
+precision highp float;
+layout (location = 0) in vec2 coord;
+
+layout (location = 0) out vec4 fragColor;
+
+// The scene is read from a texture written by the previous render pass, which may be smaller than the view
+// when rendering at a reduced resolution. Bilinear filtering scales it up to fill the view.
+#ifdef WGPU
+layout (set = 0, binding = 0) uniform texture2D sceneTexture;
+layout (set = 0, binding = 1) uniform sampler sceneSampler;
+layout (set = 0, binding = 2) uniform Push {
+#else
+layout (set = 0, binding = 0) uniform sampler2D scene;
+layout (push_constant) uniform Push {
+#endif
+	float time;
+	float aspect_ratio;
+	float strength;
+	float flash;
+	float score_flash;
+} push;
+
+const float pi = 3.14159265358979;
+const float laneCount = 96.0;
+
+float hash(float n) {
+	return fract(sin(n) * 43758.5453);
+}
+
+// Draw streaks racing outwards from the centre of the view, over a darkening vignette.
+vec3 speedLines(vec3 col, vec2 p, float radius, float edge) {
+	// Split the view into thin lanes radiating from the centre. Half of the lanes carry a streak.
+	float lanePosition = (atan(p.y, p.x) / (2.0*pi) + 0.5) * laneCount;
+	float lane = floor(lanePosition);
+	float seed = hash(lane);
+	float travel = fract(0.6*radius - push.time*(1.0 + seed) + 7.0*seed);
+	float streak = (1.0 - smoothstep(0.0, 0.4, travel))
+		* (1.0 - smoothstep(0.0, 0.3, abs(fract(lanePosition) - 0.5)))
+		* step(0.5, hash(lane + 17.0));
+
+	col = mix(col, vec3(1.0), 0.6 * push.strength * streak * edge);
+	return col * (1.0 - 0.35 * push.strength * edge * edge);
+}
+
+vec4 sampleScene(vec2 uv) {
+#ifdef WGPU
+	return texture(sampler2D(sceneTexture, sceneSampler), uv);
+#else
+	return texture(scene, uv);
+#endif
+}
+
+void main(void) {
+	vec2 uv = 0.5*coord + 0.5;
+	vec4 col = sampleScene(uv);
+
+	// Split the colour channels outwards from the centre after taking a portal, brightening the view briefly.
+	if (push.score_flash > 0.0) {
+		vec2 shift = 0.015 * push.score_flash * coord;
+		col.r = sampleScene(uv + shift).r;
+		col.b = sampleScene(uv - shift).b;
+		col.rgb += 0.12 * push.score_flash * vec3(1.0, 0.9, 0.6);
+	}
+
+	// Effects only appear towards the edges, keeping the centre of the view clear.
+	vec2 p = vec2(coord.x * push.aspect_ratio, coord.y);
+	float radius = length(p);
+	float edge = smoothstep(0.35, 1.2, radius);
+	if (push.strength > 0.0) {
+		col.rgb = speedLines(col.rgb, p, radius, edge);
+	}
+
+	// Flash the view red after a crash, most strongly around the edges.
+	col.rgb = mix(col.rgb, vec3(0.8, 0.05, 0.05), push.flash * (0.4 + 0.6*edge));
+	fragColor = col;
+}
@@ -0,0 +1,90 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+#version 450
+precision highp float;
+
+// A point of `TrailVertex` in `trail.rs`.
+struct TrailPoint {
+	vec3 position;
+	float width;
+	vec4 colour;
+};
+
+layout (set = 0, binding = 0) readonly buffer Trail {
+	TrailPoint points[];
+};
+
+layout (push_constant) uniform Push {
+	vec4 camera_quaternion;
+	vec3 camera_position;
+	float aspect_ratio;
+	float fov_y;
+	uint projection;
+} push;
+
+layout (location = 0) out float across;
+layout (location = 1) out vec4 colour;
+
+// Parts of the trail closer to the camera than this are clipped.
+const float nearDistance = 1e-5;
+
+vec3 rotateByQuaternion(vec3 v, vec4 q) {
+	vec3 temp = cross(q.xyz, cross(q.xyz, v) + q.w * v);
+	return v + temp+temp;
+}
+
+// Projections of the view, matching `Projection` in `projection.rs`.
+const uint projectionCylindrical = 1;
+const uint projectionPanini = 2;
+
+// The point of the view where a camera-space point is seen, measured in tangents at the centre of the view, and the
+// depth that sizes shrink with there. This matches `projectView` in `ray_march_voxels.frag`.
+vec3 projectView(vec3 v) {
+	if (push.projection == projectionCylindrical) {
+		float r = length(v.xz);
+		return vec3(atan(v.x, v.z), v.y / r, r);
+	}
+	if (push.projection == projectionPanini) {
+		float depth = 0.5*(length(v.xz) + v.z);
+		return vec3(v.xy / depth, depth);
+	}
+	return vec3(v.xy / v.z, v.z);
+}
+
+// Each point of the trail is drawn as a pair of vertices on either side of it, forming a ribbon which faces the
+// camera. The ribbon is projected to clip space so that parts of it behind the camera are clipped.
+void main() {
+	int index = gl_VertexIndex / 2;
+	across = float(gl_VertexIndex & 1) * 2.0 - 1.0;
+	TrailPoint point = points[index];
+	colour = point.colour;
+
+	vec4 inverse = vec4(-push.camera_quaternion.xyz, push.camera_quaternion.w);
+	vec3 previous = points[max(index - 1, 0)].position;
+	vec3 next = points[min(index + 1, points.length() - 1)].position;
+	vec3 p = rotateByQuaternion(point.position - push.camera_position, inverse);
+	vec3 tangent = rotateByQuaternion(next - previous, inverse);
+	vec3 side = cross(tangent, p);
+	side = dot(side, side) > 1e-30 ? normalize(side) : vec3(1.0, 0.0, 0.0);
+	p += across * point.width * side;
+
+	// Match the rays cast by `ray_march_voxels.frag`, with the horizontal axis scaled by the aspect ratio.
+	vec3 v = projectView(p);
+	gl_Position = vec4(v.x*v.z / (push.fov_y * push.aspect_ratio), -v.y*v.z / push.fov_y, v.z - nearDistance, v.z);
+}
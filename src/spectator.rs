@@ -0,0 +1,135 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::{InnerSpace, Quaternion, Rotation, Vector3};
+use egui::{Color32, Context, Stroke};
+
+use crate::game;
+use crate::input;
+use crate::voxels::{self, VoxelCompact};
+
+// Where the director camera is placed behind and above the plane, as seconds of flight at the plane's speed.
+const FOLLOW_BEHIND: f32 = 3.;
+const FOLLOW_ABOVE: f32 = 0.75;
+
+// The plane has been moved rather than flown, such as through a portal, when it travels further than this many
+// frames of flight in one.
+const JUMP_FRAMES: f32 = 8.;
+
+// How much faster than the plane the director camera moves, so that it can catch up.
+const CATCH_UP_FACTOR: f32 = 1.5;
+
+const MARKER_RADIUS: f32 = 10.;
+const MARKER_COLOUR: Color32 = Color32::from_rgb(255, 140, 60);
+
+// A director camera flown with the flight controls while the plane flies itself, for recording trailers.
+// The plane's camera is left to the autopilot or a camera path, and is shown as a marker.
+pub struct Spectator {
+    pub position: Vector3<f32>,
+    pub quaternion: Quaternion<f32>,
+    plane_position: Vector3<f32>,
+}
+
+impl Spectator {
+    // Start behind the plane, looking along its flight.
+    pub fn enter(game: &game::State) -> Self {
+        let mut spectator = Self {
+            position: game.camera_position,
+            quaternion: game.camera_quaternion,
+            plane_position: game.camera_position,
+        };
+        spectator.follow(game);
+        spectator
+    }
+
+    // Move back behind the plane.
+    pub fn follow(&mut self, game: &game::State) {
+        let offset = Vector3::new(0., FOLLOW_ABOVE, -FOLLOW_BEHIND) * game.camera_speed;
+        self.position = game.camera_position + game.camera_quaternion.rotate_vector(offset);
+        self.quaternion = game.camera_quaternion;
+    }
+
+    // Turn the director camera with the flight controls, moving it forward while boosting at a little more than the
+    // plane's speed. It passes through voxels and portals, and jumps back behind the plane whenever the plane is
+    // moved rather than flown.
+    pub fn fly(&mut self, game: &game::State, delta_time: f32) {
+        let moved = (game.camera_position - self.plane_position).magnitude();
+        self.plane_position = game.camera_position;
+        if moved > JUMP_FRAMES * game.camera_speed * delta_time.max(f32::EPSILON) {
+            self.follow(game);
+            return;
+        }
+
+        let inputs = game.input.steering(&input::PLAYER_SOURCES);
+        self.quaternion = game::turned(self.quaternion, delta_time, inputs);
+        if game.input.boost_held() {
+            self.position += self.quaternion.rotate_vector(Vector3::new(
+                0.,
+                0.,
+                delta_time * CATCH_UP_FACTOR * game.camera_speed,
+            ));
+        }
+    }
+
+    // Draw the plane as a ring with a tick along its heading, projected the way the shader projects the world. The
+    // marker is faded while voxels hide the plane, so that it can still be found.
+    pub fn draw_marker(&self, ctx: &Context, game: &game::State, octree: &[VoxelCompact]) {
+        let screen = ctx.screen_rect();
+        let lens = game.lens(screen.aspect_ratio());
+        let inverse = self.quaternion.invert();
+        let project = |point: Vector3<f32>| {
+            lens.to_screen(screen, inverse.rotate_vector(point - self.position), 0.)
+                .map(|(point, _)| point)
+        };
+        let Some(centre) = project(game.camera_position) else {
+            return;
+        };
+
+        let offset = game.camera_position - self.position;
+        let distance = offset.magnitude();
+        let hidden = distance > f32::EPSILON
+            && voxels::raycast(self.position, offset / distance, octree)
+                .is_some_and(|hit| hit.distance < distance);
+        let colour = if hidden {
+            MARKER_COLOUR.gamma_multiply(0.35)
+        } else {
+            MARKER_COLOUR
+        };
+
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("spectator_marker"),
+        ));
+        let stroke = Stroke::new(2., colour);
+        painter.circle_stroke(centre, MARKER_RADIUS, stroke);
+        let ahead = game.camera_position
+            + game.camera_quaternion.rotate_vector(Vector3::unit_z()) * game.camera_speed;
+        if let Some(heading) = project(ahead).map(|ahead| ahead - centre) {
+            if heading.length() > f32::EPSILON {
+                let direction = heading.normalized();
+                painter.line_segment(
+                    [
+                        centre + direction * MARKER_RADIUS,
+                        centre + direction * 2.5 * MARKER_RADIUS,
+                    ],
+                    stroke,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::fmt::Write;
+
+use egui::{Align2, Color32, Context, RichText};
+use web_time::Duration;
+
+use crate::game::Run;
+use crate::i18n::{Language, Text};
+use crate::livesplit::LiveSplitClient;
+
+// Colours of a split's difference from the personal best, when ahead of or behind it.
+const AHEAD_COLOUR: Color32 = Color32::from_rgb(90, 220, 120);
+const BEHIND_COLOUR: Color32 = Color32::from_rgb(235, 90, 90);
+
+// Times a run with a split for each level reached, measured from the start of the run. The best run of the session,
+// reaching the furthest level or the same level soonest, is kept to compare splits against and to export.
+#[derive(Default)]
+pub struct SpeedrunTimer {
+    splits: Vec<Duration>,
+    personal_best: Vec<Duration>,
+    attempts: u32,
+    running: bool,
+
+    // Mirrors the timer onto a LiveSplit Server, when connected.
+    pub livesplit: Option<LiveSplitClient>,
+}
+
+impl SpeedrunTimer {
+    // Follow the run starting and ending, starting or resetting the LiveSplit timer with it.
+    pub fn update(&mut self, run: &Run) {
+        if let Some(livesplit) = &mut self.livesplit {
+            livesplit.poll();
+        }
+
+        match (self.running, run.start.is_some()) {
+            (false, true) => {
+                self.running = true;
+                self.attempts += 1;
+                self.splits.clear();
+                self.send("starttimer");
+                self.send("initgametime");
+            }
+            (true, false) => {
+                self.running = false;
+                if is_better(&self.splits, &self.personal_best) {
+                    self.personal_best = self.splits.clone();
+                }
+                self.send("reset");
+            }
+            _ => (),
+        }
+    }
+
+    // Record a split for the level just reached. The game time LiveSplit shows is set to the run's own time, which
+    // leaves out any time spent paused.
+    pub fn split(&mut self, run: &Run) {
+        let Some(start) = run.start else {
+            return;
+        };
+        let time = start.elapsed();
+        self.splits.push(time);
+        self.send(format!("setgametime {:.3}", time.as_secs_f64()));
+        self.send("split");
+    }
+
+    // Show the run's time at the top of the view, and the last split with its difference from the personal best.
+    pub fn draw(&self, ctx: &Context, run: &Run, lang: Language) {
+        let Some(start) = run.start else {
+            return;
+        };
+        egui::Area::new("speedrun_timer")
+            .anchor(Align2::CENTER_TOP, [0., 8.])
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(
+                            RichText::new(format_time(start.elapsed()))
+                                .monospace()
+                                .size(28.)
+                                .strong(),
+                        );
+                        let Some(&last) = self.splits.last() else {
+                            return;
+                        };
+                        let level = self.splits.len();
+                        ui.horizontal(|ui| {
+                            ui.label(lang.tr_args(Text::SpeedrunSplit, &[&level]));
+                            ui.monospace(format_time(last));
+                            if let Some(&best) = self.personal_best.get(level - 1) {
+                                let (sign, difference, colour) = if last <= best {
+                                    ('-', best - last, AHEAD_COLOUR)
+                                } else {
+                                    ('+', last - best, BEHIND_COLOUR)
+                                };
+                                ui.label(
+                                    RichText::new(format!("{sign}{:.2}", difference.as_secs_f32()))
+                                        .monospace()
+                                        .color(colour),
+                                );
+                            }
+                        });
+                    });
+                });
+            });
+    }
+
+    // The personal best as a LiveSplit splits file, with a segment for each level. The best run so far is used
+    // if no run has finished yet.
+    pub fn to_lss(&self, lang: Language) -> String {
+        let splits = if self.personal_best.is_empty() {
+            &self.splits
+        } else {
+            &self.personal_best
+        };
+        let mut lss =
+            String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Run version=\"1.7.0\">\n");
+        lss.push_str("  <GameIcon />\n  <GameName>Voxel Flight Simulator</GameName>\n");
+        lss.push_str("  <CategoryName>Any%</CategoryName>\n  <Offset>00:00:00</Offset>\n");
+        let _ = writeln!(lss, "  <AttemptCount>{}</AttemptCount>", self.attempts);
+        lss.push_str("  <AttemptHistory />\n  <Segments>\n");
+        let mut previous = Duration::ZERO;
+        for (i, &split) in splits.iter().enumerate() {
+            let name = lang.tr_args(Text::SpeedrunSplit, &[&(i + 1)]);
+            let (split_time, segment_time) = (lss_time(split), lss_time(split - previous));
+            previous = split;
+            let _ = write!(
+                lss,
+                "    <Segment>\n      <Name>{name}</Name>\n      <Icon />\n      <SplitTimes>\n        \
+                 <SplitTime name=\"Personal Best\">\n          <RealTime>{split_time}</RealTime>\n          \
+                 <GameTime>{split_time}</GameTime>\n        </SplitTime>\n      </SplitTimes>\n      \
+                 <BestSegmentTime>\n        <RealTime>{segment_time}</RealTime>\n        \
+                 <GameTime>{segment_time}</GameTime>\n      </BestSegmentTime>\n      <SegmentHistory />\n    \
+                 </Segment>\n"
+            );
+        }
+        lss.push_str("  </Segments>\n  <AutoSplitterSettings />\n</Run>\n");
+        lss
+    }
+
+    // Whether there are any splits to export.
+    pub fn has_splits(&self) -> bool {
+        !self.splits.is_empty() || !self.personal_best.is_empty()
+    }
+
+    // Send a command to the LiveSplit Server, if connected.
+    fn send(&self, command: impl Into<String>) {
+        if let Some(livesplit) = &self.livesplit {
+            livesplit.send(command);
+        }
+    }
+}
+
+// Whether a run's splits beat the best, by reaching a further level, or the same level sooner.
+fn is_better(splits: &[Duration], best: &[Duration]) -> bool {
+    match splits.len().cmp(&best.len()) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => splits.last() < best.last(),
+    }
+}
+
+// Show a time as minutes, seconds, and hundredths, with hours when needed.
+fn format_time(time: Duration) -> String {
+    let centiseconds = time.as_millis() / 10;
+    let (hours, minutes, seconds) = (
+        centiseconds / 360_000,
+        centiseconds / 6000 % 60,
+        centiseconds / 100 % 60,
+    );
+    if hours > 0 {
+        format!(
+            "{hours}:{minutes:02}:{seconds:02}.{:02}",
+            centiseconds % 100
+        )
+    } else {
+        format!("{minutes}:{seconds:02}.{:02}", centiseconds % 100)
+    }
+}
+
+// Write a time the way LiveSplit stores it, with seven decimal places of seconds.
+fn lss_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}.{:07}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        time.subsec_nanos() / 100
+    )
+}
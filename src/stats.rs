@@ -0,0 +1,88 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Run;
+use crate::persistence;
+
+const STATS_FILE_NAME: &str = "stats.toml";
+
+// Lifetime statistics across every session.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Stats {
+    pub flight_time: f64,
+    pub distance_flown: f64,
+    pub crashes: u32,
+    pub portals_taken: u32,
+    pub best_score: u32,
+    pub furthest_level: u32,
+    // Campaign levels are completed in order, so this is also the index of the furthest level unlocked.
+    pub campaign_levels: u32,
+}
+
+impl Stats {
+    // Load the stored statistics, starting fresh if there are none.
+    pub fn load() -> Self {
+        persistence::load(&persistence::profile_file(STATS_FILE_NAME))
+    }
+
+    // Store the statistics in the app directory.
+    pub fn save(&self) {
+        persistence::save(&persistence::profile_file(STATS_FILE_NAME), self);
+    }
+
+    // Accumulate a frame of flight.
+    pub fn record_flight(&mut self, delta_time: f32, distance: f32) {
+        self.flight_time += f64::from(delta_time);
+        self.distance_flown += f64::from(distance);
+    }
+
+    // Record a run ending in a collision.
+    pub fn record_crash(&mut self) {
+        self.crashes += 1;
+        self.save();
+    }
+
+    // Record a portal being taken, given the state of the run after gaining its points.
+    pub fn record_portal(&mut self, run: &Run) {
+        self.portals_taken += 1;
+        self.best_score = self.best_score.max(run.points);
+        self.furthest_level = self.furthest_level.max(run.level);
+        self.save();
+    }
+
+    // Record a campaign level being completed, unlocking the next.
+    pub fn record_campaign_level(&mut self, level: u32) {
+        self.campaign_levels = self.campaign_levels.max(level + 1);
+        self.save();
+    }
+}
+
+// Format a duration in seconds as hours, minutes, and seconds.
+pub fn format_duration(seconds: f64) -> String {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let total = seconds.max(0.) as u64;
+    let (hours, minutes, seconds) = (total / 3600, (total / 60) % 60, total % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m {seconds:02}s")
+    } else {
+        format!("{minutes}m {seconds:02}s")
+    }
+}
@@ -0,0 +1,171 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::BTreeSet;
+
+use steamworks::{Client, ClientManager, SingleClient};
+
+use crate::achievements::{Achievement, Achievements};
+
+// Names from the Steam Input action manifest, `res/steam_input_manifest.vdf`.
+const ACTION_SET: &str = "Flight";
+const STEER_ACTION: &str = "Steer";
+const YAW_LEFT_ACTION: &str = "YawLeft";
+const YAW_RIGHT_ACTION: &str = "YawRight";
+const BOOST_ACTION: &str = "Boost";
+const FIRE_ACTION: &str = "Fire";
+
+// The name each achievement is given in the Steamworks app's stats and achievements.
+fn api_name(achievement: Achievement) -> &'static str {
+    match achievement {
+        Achievement::FirstPortal => "FIRST_PORTAL",
+        Achievement::DeepDive => "DEEP_DIVE",
+        Achievement::Marathon => "MARATHON",
+        Achievement::SteadyHand => "STEADY_HAND",
+        Achievement::HighRoller => "HIGH_ROLLER",
+        Achievement::FrequentFlyer => "FREQUENT_FLYER",
+        Achievement::CrashTestDummy => "CRASH_TEST_DUMMY",
+    }
+}
+
+// The handles of the flight actions, looked up once Steam Input has loaded the action manifest.
+struct ActionHandles {
+    set: u64,
+    steer: u64,
+    yaw_left: u64,
+    yaw_right: u64,
+    boost: u64,
+    fire: u64,
+}
+
+// The state of a Steam Input controller for one frame.
+pub struct ControllerFrame {
+    pub steer: [f32; 2],
+    pub yaw: [f32; 2],
+    // Whether boost was pressed or released since the last frame.
+    pub boost: Option<bool>,
+    pub fire: bool,
+}
+
+// A connection to the running Steam client, used for achievements, rich presence, and Steam Input.
+pub struct Steam {
+    client: Client<ClientManager>,
+    single: SingleClient<ClientManager>,
+    actions: Option<ActionHandles>,
+    reported: BTreeSet<Achievement>,
+    presence: Option<(u64, u32)>,
+    boost_held: bool,
+    fire_held: bool,
+}
+
+impl Steam {
+    // Connect to Steam, which is only possible when the game was launched through it or beside a `steam_appid.txt`.
+    pub fn init() -> Option<Self> {
+        let (client, single) = match Client::init() {
+            Ok(client) => client,
+            Err(e) => {
+                log::warn!(target: "steam", "Failed to connect to Steam error=\"{e}\"");
+                return None;
+            }
+        };
+        client.user_stats().request_current_stats();
+        let input = client.input();
+        let actions = input.init(false).then(|| ActionHandles {
+            set: input.get_action_set_handle(ACTION_SET),
+            steer: input.get_analog_action_handle(STEER_ACTION),
+            yaw_left: input.get_analog_action_handle(YAW_LEFT_ACTION),
+            yaw_right: input.get_analog_action_handle(YAW_RIGHT_ACTION),
+            boost: input.get_digital_action_handle(BOOST_ACTION),
+            fire: input.get_digital_action_handle(FIRE_ACTION),
+        });
+        if actions.is_none() {
+            log::warn!(target: "steam", "Failed to initialize Steam Input");
+        }
+        log::info!(target: "steam", "Connected to Steam");
+        Some(Self {
+            client,
+            single,
+            actions,
+            reported: BTreeSet::new(),
+            presence: None,
+            boost_held: false,
+            fire_held: false,
+        })
+    }
+
+    // Handle Steam's callbacks, and unlock on Steam any achievements it hasn't been told about.
+    pub fn update(&mut self, achievements: &Achievements) {
+        self.single.run_callbacks();
+
+        let user_stats = self.client.user_stats();
+        let mut unlocked_any = false;
+        for achievement in Achievement::ALL {
+            if achievements.is_unlocked(achievement) && self.reported.insert(achievement) {
+                if user_stats.achievement(api_name(achievement)).set().is_err() {
+                    log::warn!(target: "steam", "Failed to unlock achievement={achievement:?}");
+                }
+                unlocked_any = true;
+            }
+        }
+        if unlocked_any && user_stats.store_stats().is_err() {
+            log::warn!(target: "steam", "Failed to store stats");
+        }
+    }
+
+    // Show friends the world being flown and the run's score.
+    pub fn set_presence(&mut self, seed: u64, score: u32) {
+        if self.presence == Some((seed, score)) {
+            return;
+        }
+        self.presence = Some((seed, score));
+
+        let friends = self.client.friends();
+        let seed = seed.to_string();
+        let score = score.to_string();
+        friends.set_rich_presence("seed", Some(&seed));
+        friends.set_rich_presence("score", Some(&score));
+        friends.set_rich_presence("steam_display", Some("#Flying"));
+        friends.set_rich_presence("status", Some(&format!("Seed {seed}, score {score}")));
+    }
+
+    // Read the first controller connected through Steam Input, if any.
+    pub fn controller(&mut self) -> Option<ControllerFrame> {
+        let actions = self.actions.as_ref()?;
+        let input = self.client.input();
+        input.run_frame();
+        let handle = *input.get_connected_controllers().first()?;
+        input.activate_action_set_handle(handle, actions.set);
+
+        let steer = input.get_analog_action_data(handle, actions.steer);
+        let yaw_left = input.get_analog_action_data(handle, actions.yaw_left);
+        let yaw_right = input.get_analog_action_data(handle, actions.yaw_right);
+        let boost = input.get_digital_action_data(handle, actions.boost).bState;
+        let fire = input.get_digital_action_data(handle, actions.fire).bState;
+
+        let boost_changed = (boost != self.boost_held).then_some(boost);
+        let fire_pressed = fire && !self.fire_held;
+        self.boost_held = boost;
+        self.fire_held = fire;
+        Some(ControllerFrame {
+            steer: [steer.x, steer.y],
+            yaw: [yaw_left.x, yaw_right.x],
+            boost: boost_changed,
+            fire: fire_pressed,
+        })
+    }
+}
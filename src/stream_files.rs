@@ -0,0 +1,110 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{fs, path::PathBuf, time::Duration};
+
+use web_time::Instant;
+
+use crate::voxel_flight_simulator::app_data_dir;
+
+// Stream file constants.
+const STREAM_DIRECTORY: &str = "stream";
+const WRITE_INTERVAL: Duration = Duration::from_millis(500);
+
+// The run information shared with streaming software.
+#[derive(Clone, PartialEq)]
+pub struct StreamInfo {
+    pub score: u32,
+    pub level: u32,
+    pub seed: u64,
+    pub run_time: Option<f32>,
+}
+
+// Writes the current run to small text files, and a JSON file of them all, for streaming software such as OBS to
+// show as text sources. Files are only written a couple of times a second, and only when something has changed.
+#[derive(Default)]
+pub struct StreamFiles {
+    last_write: Option<Instant>,
+    frames: u32,
+    written: Option<(StreamInfo, u32)>,
+}
+
+impl StreamFiles {
+    // Count a frame, writing the files if it is time to.
+    pub fn update(&mut self, info: StreamInfo) {
+        self.frames += 1;
+        let now = Instant::now();
+        let Some(last_write) = self.last_write else {
+            self.last_write = Some(now);
+            return;
+        };
+        let elapsed = now - last_write;
+        if elapsed < WRITE_INTERVAL {
+            return;
+        }
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let fps = (self.frames as f32 / elapsed.as_secs_f32()).round() as u32;
+        self.last_write = Some(now);
+        self.frames = 0;
+        let state = (info, fps);
+        if self.written.as_ref() == Some(&state) {
+            return;
+        }
+        if let Err(e) = write_files(&state.0, fps) {
+            log::warn!(target: "stream", "Failed to write stream files error=\"{e}\"");
+        }
+        self.written = Some(state);
+    }
+}
+
+// The folder the stream files are written to.
+pub fn directory() -> PathBuf {
+    app_data_dir().join(STREAM_DIRECTORY)
+}
+
+// Write each value to its own file, replacing the old files atomically so that they are never read half-written.
+fn write_files(info: &StreamInfo, fps: u32) -> std::io::Result<()> {
+    let directory = directory();
+    fs::create_dir_all(&directory)?;
+    let run_time = info
+        .run_time
+        .map_or_else(String::new, |seconds| format!("{seconds:.1}"));
+    let json = format!(
+        "{{\"score\":{},\"level\":{},\"seed\":\"{}\",\"run_time\":{},\"fps\":{fps}}}\n",
+        info.score,
+        info.level,
+        info.seed,
+        info.run_time
+            .map_or_else(|| "null".to_owned(), |seconds| format!("{seconds:.3}")),
+    );
+    for (name, contents) in [
+        ("score.txt", info.score.to_string()),
+        ("level.txt", info.level.to_string()),
+        ("seed.txt", info.seed.to_string()),
+        ("run_time.txt", run_time),
+        ("fps.txt", fps.to_string()),
+        ("stream.json", json),
+    ] {
+        let path = directory.join(name);
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, contents)?;
+        fs::rename(&temp_path, &path)?;
+    }
+    Ok(())
+}
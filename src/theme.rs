@@ -0,0 +1,139 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Text;
+
+// Named styles of world, each changing how worlds are generated and the sky they are seen under.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum WorldTheme {
+    #[default]
+    Classic,
+    Canyon,
+    City,
+    Nebula,
+}
+
+// The parameters of a theme, read by world generation and by the sky in `ray_march_voxels.frag`.
+pub struct Theme {
+    // Chance that a sub-voxel is left empty. Leaves become likelier than this below the first depths.
+    pub empty_chance: f32,
+
+    // Chance that a sub-voxel is not a recursive reference, which falls with depth.
+    pub branch_chance: f32,
+
+    // Relative weights of colour, portal, and mirror leaves deep enough to hold portals, and of colour and
+    // mirror leaves above them.
+    pub deep_leaf_weights: [usize; 3],
+    pub shallow_leaf_weights: [usize; 2],
+
+    // Each channel of a leaf's colour is drawn uniformly between these bounds.
+    pub colour_low: [f32; 3],
+    pub colour_high: [f32; 3],
+
+    // Colours of the sky above and the ground below the horizon, and of the fog that distant voxels fade into.
+    pub sky_colour: [f32; 4],
+    pub ground_colour: [f32; 4],
+    pub fog_colour: [f32; 4],
+}
+
+impl WorldTheme {
+    pub const ALL: [WorldTheme; 4] = [
+        WorldTheme::Classic,
+        WorldTheme::Canyon,
+        WorldTheme::City,
+        WorldTheme::Nebula,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            WorldTheme::Classic => Text::ThemeClassic,
+            WorldTheme::Canyon => Text::ThemeCanyon,
+            WorldTheme::City => Text::ThemeCity,
+            WorldTheme::Nebula => Text::ThemeNebula,
+        }
+    }
+
+    // Parse a theme name given on the command line or in a link.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "classic" => Some(WorldTheme::Classic),
+            "canyon" => Some(WorldTheme::Canyon),
+            "city" => Some(WorldTheme::City),
+            "nebula" => Some(WorldTheme::Nebula),
+            _ => None,
+        }
+    }
+
+    pub fn theme(self) -> Theme {
+        match self {
+            // The original worlds: sparse, brightly coloured, under a blue sky.
+            WorldTheme::Classic => Theme {
+                empty_chance: 0.45,
+                branch_chance: 0.825,
+                deep_leaf_weights: [21, 2, 2],
+                shallow_leaf_weights: [9, 1],
+                colour_low: [0.; 3],
+                colour_high: [1.; 3],
+                sky_colour: [0.08, 0.2, 0.75, 1.],
+                ground_colour: [0.2, 0.08, 0.08, 1.],
+                fog_colour: [0.42, 0.525, 0.45, 1.],
+            },
+
+            // Dense walls of red rock with few mirrors, under a dusty sky.
+            WorldTheme::Canyon => Theme {
+                empty_chance: 0.4,
+                branch_chance: 0.8,
+                deep_leaf_weights: [24, 2, 1],
+                shallow_leaf_weights: [19, 1],
+                colour_low: [0.45, 0.18, 0.06],
+                colour_high: [0.95, 0.55, 0.3],
+                sky_colour: [0.55, 0.35, 0.25, 1.],
+                ground_colour: [0.3, 0.12, 0.05, 1.],
+                fog_colour: [0.7, 0.5, 0.35, 1.],
+            },
+
+            // Grey towers with many glass-like mirrors, under a dusk sky.
+            WorldTheme::City => Theme {
+                empty_chance: 0.5,
+                branch_chance: 0.85,
+                deep_leaf_weights: [14, 2, 6],
+                shallow_leaf_weights: [5, 2],
+                colour_low: [0.3, 0.32, 0.36],
+                colour_high: [0.8, 0.82, 0.88],
+                sky_colour: [0.25, 0.2, 0.45, 1.],
+                ground_colour: [0.08, 0.08, 0.1, 1.],
+                fog_colour: [0.4, 0.38, 0.5, 1.],
+            },
+
+            // Open space scattered with glowing purple clouds and more portals, under a dark sky.
+            WorldTheme::Nebula => Theme {
+                empty_chance: 0.5,
+                branch_chance: 0.85,
+                deep_leaf_weights: [16, 4, 3],
+                shallow_leaf_weights: [8, 1],
+                colour_low: [0.25, 0.05, 0.35],
+                colour_high: [1., 0.55, 1.],
+                sky_colour: [0.02, 0., 0.08, 1.],
+                ground_colour: [0.1, 0.02, 0.12, 1.],
+                fog_colour: [0.3, 0.12, 0.4, 1.],
+            },
+        }
+    }
+}
@@ -0,0 +1,199 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use egui::{Align2, Color32, Context, FontId, Stroke};
+use winit::event::{Touch, TouchPhase};
+
+use crate::i18n::{Language, Text};
+use crate::input::{self, Action, FlightAxis, Source};
+
+// Sizes and places of the on-screen controls, in fractions of the window height.
+const STICK_RADIUS: f32 = 0.12;
+const STICK_REST: [f32; 2] = [0.2, 0.75];
+const BOOST_RADIUS: f32 = 0.09;
+const BOOST_INSET: [f32; 2] = [0.18, 0.22];
+
+const CONTROL_COLOUR: Color32 = Color32::from_rgba_premultiplied(60, 60, 60, 60);
+const ACTIVE_COLOUR: Color32 = Color32::from_rgba_premultiplied(140, 140, 140, 140);
+
+// A finger steering with the virtual stick, which is centred wherever the finger first touched.
+struct Stick {
+    id: u64,
+    origin: [f32; 2],
+    position: [f32; 2],
+}
+
+// A virtual stick and boost button for touchscreens, shown once the screen has been touched.
+// Positions are measured in window heights from the top-left so that the controls stay round on any aspect ratio.
+#[derive(Default)]
+pub struct TouchControls {
+    stick: Option<Stick>,
+    boost: Option<u64>,
+    aspect_ratio: f32,
+    touched: bool,
+}
+
+impl TouchControls {
+    // Track a finger touching the window. Touches which begin over the overlay are left to it.
+    pub fn handle(&mut self, touch: &Touch, window_size: [f32; 2], over_overlay: bool) {
+        #[allow(clippy::cast_possible_truncation)]
+        let position = [
+            touch.location.x as f32 / window_size[1],
+            touch.location.y as f32 / window_size[1],
+        ];
+        self.aspect_ratio = window_size[0] / window_size[1];
+        match touch.phase {
+            TouchPhase::Started if !over_overlay => {
+                self.touched = true;
+                if distance(position, self.boost_centre()) <= BOOST_RADIUS {
+                    self.boost = Some(touch.id);
+                } else if self.stick.is_none() {
+                    self.stick = Some(Stick {
+                        id: touch.id,
+                        origin: position,
+                        position,
+                    });
+                }
+            }
+            TouchPhase::Moved => {
+                if let Some(stick) = self.stick.as_mut().filter(|stick| stick.id == touch.id) {
+                    stick.position = position;
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if self.boost == Some(touch.id) {
+                    self.boost = None;
+                }
+                if self
+                    .stick
+                    .as_ref()
+                    .is_some_and(|stick| stick.id == touch.id)
+                {
+                    self.stick = None;
+                }
+            }
+            TouchPhase::Started => (),
+        }
+    }
+
+    // The stick deflection, with its y axis pointing up like a gamepad's.
+    fn deflection(&self) -> [f32; 2] {
+        self.stick.as_ref().map_or([0.; 2], |stick| {
+            let offset = [
+                stick.position[0] - stick.origin[0],
+                stick.position[1] - stick.origin[1],
+            ];
+            let scale = 1. / distance(offset, [0.; 2]).max(STICK_RADIUS);
+            [offset[0] * scale, -offset[1] * scale]
+        })
+    }
+
+    // Translate the stick and boost button into steering and boosting.
+    pub fn actions(&self, invert_y: bool) -> [Action; 3] {
+        let [x, y] = self.deflection();
+        [
+            Action::Steer {
+                source: Source::Touch,
+                axis: FlightAxis::Roll,
+                value: -x,
+            },
+            Action::Steer {
+                source: Source::Touch,
+                axis: FlightAxis::Pitch,
+                value: input::pitch_sign(invert_y) * y,
+            },
+            Action::Boost {
+                source: Source::Touch,
+                held: self.boost.is_some(),
+            },
+        ]
+    }
+
+    // Draw the stick and boost button over the world, beneath any windows.
+    pub fn draw(&self, ctx: &Context, lang: Language) {
+        if !self.touched {
+            return;
+        }
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("touch_controls"),
+        ));
+        let screen = ctx.screen_rect();
+        let to_screen = |[x, y]: [f32; 2]| screen.min + egui::vec2(x, y) * screen.height();
+
+        // The stick's base follows the finger's first touch, and the knob follows the finger within the base.
+        let (origin, knob) = self
+            .stick
+            .as_ref()
+            .map_or((STICK_REST, STICK_REST), |stick| {
+                let [x, y] = self.deflection();
+                (
+                    stick.origin,
+                    [
+                        stick.origin[0] + STICK_RADIUS * x,
+                        stick.origin[1] - STICK_RADIUS * y,
+                    ],
+                )
+            });
+        let colour = if self.stick.is_some() {
+            ACTIVE_COLOUR
+        } else {
+            CONTROL_COLOUR
+        };
+        painter.circle_stroke(
+            to_screen(origin),
+            STICK_RADIUS * screen.height(),
+            Stroke::new(2., colour),
+        );
+        painter.circle_filled(
+            to_screen(knob),
+            0.4 * STICK_RADIUS * screen.height(),
+            colour,
+        );
+
+        let colour = if self.boost.is_some() {
+            ACTIVE_COLOUR
+        } else {
+            CONTROL_COLOUR
+        };
+        let boost_centre = to_screen(self.boost_centre());
+        painter.circle_filled(boost_centre, BOOST_RADIUS * screen.height(), colour);
+        painter.text(
+            boost_centre,
+            Align2::CENTER_CENTER,
+            lang.tr(Text::TouchBoost),
+            FontId::proportional(0.3 * BOOST_RADIUS * screen.height()),
+            Color32::WHITE,
+        );
+    }
+
+    // The boost button sits in the bottom-right corner.
+    fn boost_centre(&self) -> [f32; 2] {
+        [self.aspect_ratio - BOOST_INSET[0], 1. - BOOST_INSET[1]]
+    }
+
+    // Getters.
+    pub fn is_visible(&self) -> bool {
+        self.touched
+    }
+}
+
+// Distance between two points.
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    (a[0] - b[0]).hypot(a[1] - b[1])
+}
@@ -0,0 +1,115 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::VecDeque;
+
+use bytemuck::{Pod, Zeroable};
+use cgmath::{InnerSpace, Vector3};
+
+// The most points kept along the trail, which bounds the buffer uploaded each frame.
+const MAXIMUM_TRAIL_POINTS: usize = 512;
+
+// Seconds that each point of the trail lasts before fading away.
+const TRAIL_SECONDS: f32 = 4.;
+
+// Spacing between points and the half-width of the trail, relative to the camera's speed when each point was
+// recorded so that the trail suits the size of the surrounding space.
+const TRAIL_SPACING: f32 = 0.05;
+const TRAIL_WIDTH: f32 = 0.02;
+
+const TRAIL_COLOUR: [f32; 4] = [0.85, 0.9, 1., 0.6];
+
+// A point of the trail as read by the trail shaders, laid out as the `TrailPoint` struct in `trail.vert`.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct TrailVertex {
+    pub position: [f32; 3],
+    pub width: f32,
+    pub colour: [f32; 4],
+}
+
+// A recorded position of the camera, the trail's width there, and when it was recorded.
+struct TrailPoint {
+    position: Vector3<f32>,
+    width: f32,
+    time: f32,
+}
+
+// The contrail left behind the camera, a ribbon through its recent positions which fades with age.
+#[derive(Default)]
+pub struct Trail {
+    points: VecDeque<TrailPoint>,
+    clock: f32,
+}
+
+impl Trail {
+    // Advance the trail's clock, dropping the points which have faded away.
+    pub fn update(&mut self, delta_time: f32) {
+        self.clock += delta_time;
+        while self
+            .points
+            .front()
+            .is_some_and(|point| self.clock - point.time > TRAIL_SECONDS)
+        {
+            self.points.pop_front();
+        }
+    }
+
+    // Record the camera's position once it is far enough from the last point recorded.
+    pub fn record(&mut self, position: Vector3<f32>, camera_speed: f32) {
+        let spacing = TRAIL_SPACING * camera_speed;
+        if self
+            .points
+            .back()
+            .is_some_and(|point| (position - point.position).magnitude2() < spacing * spacing)
+        {
+            return;
+        }
+        if self.points.len() == MAXIMUM_TRAIL_POINTS {
+            self.points.pop_front();
+        }
+        self.points.push_back(TrailPoint {
+            position,
+            width: TRAIL_WIDTH * camera_speed,
+            time: self.clock,
+        });
+    }
+
+    // Forget the trail, such as when the camera jumps to a new position.
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    // The points of the trail to draw, oldest first, fading out as they age.
+    pub fn vertices(&self) -> Vec<TrailVertex> {
+        let [r, g, b, a] = TRAIL_COLOUR;
+        self.points
+            .iter()
+            .map(|point| TrailVertex {
+                position: point.position.into(),
+                width: point.width,
+                colour: [
+                    r,
+                    g,
+                    b,
+                    a * (1. - (self.clock - point.time) / TRAIL_SECONDS),
+                ],
+            })
+            .collect()
+    }
+}
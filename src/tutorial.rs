@@ -0,0 +1,107 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::i18n::{Language, Text};
+use crate::input::{Input, PLAYER_SOURCES};
+use crate::keys::{KeyLabels, PositionalKey};
+
+// Seconds that each control must be used for before the tutorial moves on.
+const STEP_SECONDS: f32 = 1.5;
+
+// The steps of the tutorial, in the order they are taught.
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Step {
+    #[default]
+    Pitch,
+    Roll,
+    Yaw,
+    Boost,
+    FindPortal,
+}
+
+// A guided first flight which teaches each control in turn, then asks for the portal of the tutorial world to be
+// found. The tutorial ends once the tutorial world is left, however that happens.
+#[derive(Default)]
+pub struct Tutorial {
+    step: Step,
+    progress: f32,
+}
+
+impl Step {
+    fn next(self) -> Self {
+        match self {
+            Step::Pitch => Step::Roll,
+            Step::Roll => Step::Yaw,
+            Step::Yaw => Step::Boost,
+            Step::Boost | Step::FindPortal => Step::FindPortal,
+        }
+    }
+}
+
+impl Tutorial {
+    // Advance the current step while the player is using its control.
+    pub fn update(&mut self, input: &Input, delta_time: f32) {
+        let (roll, pitch, yaw) = input.steering(&PLAYER_SOURCES);
+        let active = match self.step {
+            Step::Pitch => pitch != 0.,
+            Step::Roll => roll != 0.,
+            Step::Yaw => yaw != 0.,
+            Step::Boost => input.boost_held(),
+            Step::FindPortal => false,
+        };
+        if active {
+            self.progress += delta_time / STEP_SECONDS;
+            if self.progress >= 1. {
+                self.step = self.step.next();
+                self.progress = 0.;
+            }
+        }
+    }
+
+    // Prompt for the current step at the bottom of the view, with its progress. Returns whether the player asked
+    // to skip the rest of the tutorial.
+    pub fn draw(&self, ctx: &egui::Context, key_labels: &KeyLabels, lang: Language) -> bool {
+        let prompt = match self.step {
+            Step::Pitch => lang.tr(Text::TutorialPitch).to_owned(),
+            Step::Roll => lang.tr(Text::TutorialRoll).to_owned(),
+            Step::Yaw => lang.tr_args(
+                Text::TutorialYaw,
+                &[
+                    &key_labels.label(PositionalKey::YawLeft),
+                    &key_labels.label(PositionalKey::YawRight),
+                ],
+            ),
+            Step::Boost => lang.tr(Text::TutorialBoost).to_owned(),
+            Step::FindPortal => lang.tr(Text::TutorialFindPortal).to_owned(),
+        };
+        let mut skip = false;
+        egui::Area::new("tutorial")
+            .anchor(egui::Align2::CENTER_BOTTOM, [0., -48.])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new(lang.tr(Text::TutorialTitle)).small());
+                    ui.heading(prompt);
+                    if self.step != Step::FindPortal {
+                        ui.add(egui::ProgressBar::new(self.progress));
+                    }
+                    skip = ui.button(lang.tr(Text::TutorialSkip)).clicked();
+                });
+            });
+        skip
+    }
+}
@@ -0,0 +1,233 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+// Twitch chat constants.
+const TWITCH_IRC_ADDRESS: &str = "irc.chat.twitch.tv:6667";
+const ANONYMOUS_NICK: &str = "justinfan31415";
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+pub const VOTE_WINDOW: Duration = Duration::from_secs(30);
+pub const REGENERATION_COOLDOWN: Duration = Duration::from_secs(90);
+
+// The world changes that chat can vote for.
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub enum Vote {
+    Seed(u64),
+    Random,
+    Hazard,
+}
+
+// Connection state of the chat listener, shown in the Options window.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Status {
+    Connecting,
+    Connected,
+    Disconnected,
+}
+
+// Messages sent from the chat thread to the app.
+enum ChatEvent {
+    Connected,
+    Vote { user: String, vote: Vote },
+    Disconnected(String),
+}
+
+// Listens to a Twitch channel's chat on a background thread and tallies world votes.
+pub struct ChatListener {
+    pub channel: String,
+    pub status: Status,
+    events: mpsc::Receiver<ChatEvent>,
+    running: Arc<AtomicBool>,
+    votes: HashMap<String, (Vote, u64)>,
+    votes_cast: u64,
+    vote_start: Option<Instant>,
+    last_regeneration: Option<Instant>,
+}
+
+impl ChatListener {
+    // Connect anonymously to the chat of the given channel.
+    pub fn new(channel: &str) -> Self {
+        let channel = channel.trim().trim_start_matches('#').to_lowercase();
+        let (sender, events) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread_channel = channel.clone();
+        let thread_running = running.clone();
+        thread::spawn(move || {
+            if let Err(e) = listen(&thread_channel, &sender, &thread_running) {
+                // The receiver may already be gone if the listener was dropped.
+                let _ = sender.send(ChatEvent::Disconnected(e.to_string()));
+            }
+        });
+
+        Self {
+            channel,
+            status: Status::Connecting,
+            events,
+            running,
+            votes: HashMap::new(),
+            votes_cast: 0,
+            vote_start: None,
+            last_regeneration: None,
+        }
+    }
+
+    // Process any new chat messages and return the winning vote once voting has closed.
+    pub fn poll(&mut self) -> Option<Vote> {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                ChatEvent::Connected => {
+                    self.status = Status::Connected;
+                    log::info!(target: "twitch", "Connected to chat channel=#{}", self.channel);
+                }
+                ChatEvent::Vote { user, vote } => {
+                    // Each viewer gets a single vote, replaced by their latest command. Votes are numbered in the
+                    // order they were cast to break ties.
+                    self.votes.insert(user, (vote, self.votes_cast));
+                    self.votes_cast += 1;
+                    self.vote_start.get_or_insert_with(Instant::now);
+                }
+                ChatEvent::Disconnected(reason) => {
+                    self.status = Status::Disconnected;
+                    log::warn!(target: "twitch", "Chat disconnected reason=\"{reason}\"");
+                }
+            }
+        }
+
+        // Only close the vote once the window has passed and the world hasn't changed too recently.
+        let window_closed = self
+            .vote_start
+            .is_some_and(|start| start.elapsed() >= VOTE_WINDOW);
+        let cooled_down = self
+            .last_regeneration
+            .is_none_or(|last| last.elapsed() >= REGENERATION_COOLDOWN);
+        if !window_closed || !cooled_down {
+            return None;
+        }
+
+        // Tally the votes and pick the most popular choice, or of those tied, the one voted for first.
+        let mut tally = HashMap::<Vote, (u32, u64)>::new();
+        for &(vote, order) in self.votes.values() {
+            let (count, first) = tally.entry(vote).or_insert((0, order));
+            *count += 1;
+            *first = (*first).min(order);
+        }
+        let winner = tally
+            .into_iter()
+            .max_by_key(|&(_, (count, first))| (count, std::cmp::Reverse(first)))
+            .map(|(vote, _)| vote);
+
+        self.votes.clear();
+        self.vote_start = None;
+        self.last_regeneration = Some(Instant::now());
+        winner
+    }
+
+    // Getters.
+    pub fn pending_votes(&self) -> usize {
+        self.votes.len()
+    }
+    pub fn seconds_until_close(&self) -> Option<u64> {
+        self.vote_start
+            .map(|start| VOTE_WINDOW.saturating_sub(start.elapsed()).as_secs())
+    }
+}
+
+// Ask the chat thread to stop when the listener is no longer needed.
+impl Drop for ChatListener {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+// Read the IRC stream of a channel and forward any votes until asked to stop.
+fn listen(channel: &str, sender: &mpsc::Sender<ChatEvent>, running: &AtomicBool) -> io::Result<()> {
+    let mut stream = TcpStream::connect(TWITCH_IRC_ADDRESS)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    write!(stream, "NICK {ANONYMOUS_NICK}\r\nJOIN #{channel}\r\n")?;
+    if sender.send(ChatEvent::Connected).is_err() {
+        return Ok(());
+    }
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    while running.load(Ordering::Relaxed) {
+        match reader.read_line(&mut line) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Connection closed by server",
+                ))
+            }
+            Ok(_) => {
+                let message = line.trim_end();
+                if let Some(server) = message.strip_prefix("PING ") {
+                    // Keep the connection alive.
+                    write!(stream, "PONG {server}\r\n")?;
+                } else if let Some((user, vote)) = parse_privmsg(message)
+                    .and_then(|(user, text)| parse_vote(text).map(|vote| (user, vote)))
+                {
+                    if sender.send(ChatEvent::Vote { user, vote }).is_err() {
+                        return Ok(());
+                    }
+                }
+                line.clear();
+            }
+
+            // Timeouts let us periodically check whether we should stop. Partial lines are kept.
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+// Get the user and text of a chat message, e.g. `:user!user@user.tmi.twitch.tv PRIVMSG #channel :text`.
+fn parse_privmsg(message: &str) -> Option<(String, &str)> {
+    let (prefix, rest) = message.strip_prefix(':')?.split_once(' ')?;
+    let user = prefix.split('!').next()?;
+    let (_, text) = rest.strip_prefix("PRIVMSG ")?.split_once(" :")?;
+    Some((user.to_string(), text))
+}
+
+// Chat commands are `!seed <number>` to vote for a specific world, `!regen` for a random one, and `!hazard` to add a
+// hazard to the current world.
+fn parse_vote(text: &str) -> Option<Vote> {
+    let mut words = text.split_whitespace();
+    match words.next()? {
+        "!regen" => Some(Vote::Random),
+        "!hazard" => Some(Vote::Hazard),
+        "!seed" => words.next()?.parse().ok().map(Vote::Seed),
+        _ => None,
+    }
+}
@@ -0,0 +1,148 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::i18n::{Language, Text};
+use crate::voxel_flight_simulator::open_path;
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/ryco117/voxel_flight_simulator/releases/latest";
+
+// Release pages offered for download must be on the project's GitHub page.
+const RELEASE_PAGE_PREFIX: &str = "https://github.com/ryco117/voxel_flight_simulator/";
+
+// How long to wait for the releases API before giving up until the next launch.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// The fields of a GitHub release needed to offer it.
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+}
+
+// Asks GitHub for the latest release on a background thread, offering it in a banner when it is newer than this
+// build.
+pub struct UpdateChecker {
+    receiver: Receiver<Release>,
+    release: Option<Release>,
+    dismissed: bool,
+}
+
+impl UpdateChecker {
+    // Start checking for a newer release.
+    pub fn start() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || match latest_release() {
+            Ok(release) if is_newer(&release.tag_name, env!("CARGO_PKG_VERSION")) => {
+                log::info!(target: "updates", "Newer release found version={}", release.tag_name);
+                // The receiver may already be gone if checking was turned off.
+                let _ = sender.send(release);
+            }
+            Ok(_) => log::info!(target: "updates", "This is the latest release"),
+            Err(e) => log::warn!(target: "updates", "Failed to check for updates error=\"{e}\""),
+        });
+        Self {
+            receiver,
+            release: None,
+            dismissed: false,
+        }
+    }
+
+    // Take the result of the check once it arrives.
+    pub fn update(&mut self) {
+        if let Ok(release) = self.receiver.try_recv() {
+            self.release = Some(release);
+        }
+    }
+
+    // Draw a banner at the top of the screen offering the newer release, until it is dismissed.
+    pub fn show(&mut self, ctx: &egui::Context, language: Language) {
+        let Some(release) = self.release.as_ref().filter(|_| !self.dismissed) else {
+            return;
+        };
+        egui::Area::new("update_banner")
+            .anchor(egui::Align2::CENTER_TOP, [0., 8.])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(language.tr_args(Text::UpdateAvailable, &[&release.tag_name]));
+                        // A page elsewhere is only shown, rather than handed to the system to open.
+                        if !is_release_page(&release.html_url) {
+                            ui.label(&release.html_url);
+                        } else if ui.button(language.tr(Text::UpdateDownload)).clicked() {
+                            if let Err(e) = open_path(std::path::Path::new(&release.html_url)) {
+                                log::warn!(target: "updates", "Failed to open the release error=\"{e}\"");
+                            }
+                        }
+                        if ui.button("✖").clicked() {
+                            self.dismissed = true;
+                        }
+                    });
+                });
+            });
+    }
+
+    // Whether the banner is being shown.
+    pub fn is_visible(&self) -> bool {
+        self.release.is_some() && !self.dismissed
+    }
+}
+
+// Fetch the latest release from the GitHub releases API.
+fn latest_release() -> Result<Release, String> {
+    let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+    agent
+        .get(LATEST_RELEASE_URL)
+        .set(
+            "User-Agent",
+            concat!("voxel_flight_simulator/", env!("CARGO_PKG_VERSION")),
+        )
+        .set("Accept", "application/vnd.github+json")
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_json()
+        .map_err(|e| e.to_string())
+}
+
+// Whether a URL is a page of the project on GitHub, made only of characters which can't be read as more than a URL
+// when passed to the system's opener.
+fn is_release_page(url: &str) -> bool {
+    url.strip_prefix(RELEASE_PAGE_PREFIX).is_some_and(|rest| {
+        rest.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '.' | '_' | '~'))
+    })
+}
+
+// Whether a release's version tag, such as `v1.2.0`, is newer than the given version of this build.
+fn is_newer(tag: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Vec<u32> {
+        version
+            .trim()
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    parse(tag) > parse(current)
+}
@@ -16,31 +16,88 @@
     along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
 */
 
-use std::{fs::File, path, sync::Arc, time::Instant};
+use std::path;
 
-use crate::game::{self, HoldOrToggle, Run, SharedAxis};
-use crate::helens::{self, Allocators};
-use crate::voxels::{self, VoxelCompact};
-use cgmath::{Quaternion, Rad, Rotation, Rotation3, Vector3};
-use egui::Context;
-use egui_winit_vulkano::{Gui, GuiConfig};
-use vulkano::buffer::Buffer;
-use vulkano::memory::allocator::{AllocationCreateInfo, MemoryTypeFilter};
-use vulkano::{
-    buffer::{BufferCreateInfo, BufferUsage, Subbuffer},
-    command_buffer::SecondaryAutoCommandBuffer,
-    descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
-    pipeline::{graphics::viewport::Viewport, GraphicsPipeline, Pipeline},
+use crate::achievements::{self, Achievements};
+use crate::ai::Drones;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::audio::{self, Audio, Effect};
+use crate::autopilot::Autopilot;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::backup;
+use crate::campaign::{self, Campaign, Outcome};
+use crate::carving::{Carve, Carvings};
+use crate::cinematic::{self, CameraPath};
+use crate::cli;
+use crate::controllers::{self, ControllerToasts};
+use crate::display::{self, FullscreenMode};
+use crate::game::{
+    self, CollisionHull, FlightModel, GameMode, HoldOrToggle, Run, RunEvent, ScoringRules,
+    ShadowQuality, StereoMode,
 };
-use vulkano_util::{
-    context::{VulkanoConfig, VulkanoContext},
-    renderer::VulkanoWindowRenderer,
-    window::{VulkanoWindows, WindowDescriptor},
+use crate::graphics::{self, GraphicsPreset};
+use crate::haptics::{self, Haptics, Rumble};
+use crate::hazards::WorldHazards;
+use crate::history::{WorldHistory, WorldRecord};
+use crate::i18n::{Language, Text};
+use crate::input::{self, Action, GamepadState, Source};
+use crate::inspector::Inspector;
+use crate::keys::{self, KeyLabels, PositionalKey};
+use crate::layout::{self, SavedPosition};
+use crate::lighting::{self, WorldLights};
+use crate::livesplit::{self, LiveSplitClient};
+use crate::mixer;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::modes;
+use crate::modes::{CollisionRule, CustomModes, ModeRules, TimerRule};
+use crate::overlay_theme::{self, OverlayTheme};
+use crate::palette::ColorblindMode;
+use crate::particles::Particles;
+use crate::photo::{self, PhotoMode};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::plugins::{self, Hook, Plugins};
+use crate::popups::ScorePopups;
+use crate::portal_values::PortalValues;
+use crate::practice::{SpawnPoint, SpawnPoints};
+use crate::preview::WorldPreview;
+use crate::profiles::{self, Profiles};
+use crate::projectiles::{Impact, Projectiles};
+use crate::projection::Projection;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::recording::Recorder;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::remote::{self, RemoteControl};
+use crate::renderer::{self, Backend, FrameUniforms, Renderer};
+use crate::resolution::DynamicResolution;
+use crate::rings::Rings;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::screenshot;
+use crate::seed;
+use crate::spectator::Spectator;
+use crate::speedrun::SpeedrunTimer;
+use crate::stats::{self, Stats};
+#[cfg(feature = "steam")]
+use crate::steam::{self, Steam};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::stream_files::{self, StreamFiles, StreamInfo};
+use crate::theme::WorldTheme;
+use crate::touch::TouchControls;
+use crate::trail::Trail;
+use crate::tutorial::Tutorial;
+use crate::twitch::{self, ChatListener};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::updates::UpdateChecker;
+use crate::voxels::{
+    self, graph, heightmap, tutorial, voxelize, DifficultyBand, PortalClass, Symmetry,
+    VoxelCompact, WorldGenerator,
 };
+use crate::wind::{self, Wind};
+use cgmath::{Deg, InnerSpace, Quaternion, Rotation, Rotation3, Vector3};
+use egui::Context;
+use web_time::{Duration, Instant};
 use winit::{
     event::{ElementState, VirtualKeyCode},
-    event_loop::{ControlFlow, EventLoop},
-    window::Fullscreen,
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
 };
 
 #[cfg(all(not(debug_assertions), target_os = "windows"))]
@@ -50,33 +107,139 @@ use companion_console::ConsoleState;
 const TITLE: &str = "voxel_flight_simulator";
 const SHOW_OVERLAY_AT_LAUNCH: bool = true;
 const CAMERA_BOOST_FACTOR: f32 = 3.5;
+const CURSOR_WAIT_TO_HIDE_DURATION: f32 = 2.;
+const BACKGROUND_FPS_LIMIT: u32 = 10;
+const COLLISION_HULL_SIZE: f32 = 0.012;
+const MINIMAP_SIZE: f32 = 160.;
+const MINIMAP_MARGIN: f32 = 12.;
+
+// Seconds of flight ahead of the camera in which obstacles rumble the controller.
+const PROXIMITY_WARNING_SECONDS: f32 = 0.6;
+
+// How far an axis must move from centre to be assigned to the H.O.T.A.S. control awaiting one.
+const HOTAS_ASSIGN_THRESHOLD: f32 = 0.5;
 
-pub struct LogFile(File);
+// The most worlds generated in search of one in the chosen difficulty band.
+const DIFFICULTY_ATTEMPTS: u32 = 32;
+
+// The most worlds that return portals can lead back through.
+const MAXIMUM_WORLD_STACK: usize = 64;
+#[cfg(not(target_arch = "wasm32"))]
+const RECORDING_FPS: u32 = 60;
+#[cfg(not(target_arch = "wasm32"))]
+const PANORAMA_FACE_SIZE: u32 = 2048;
+#[cfg(not(target_arch = "wasm32"))]
+const SCREENSHOT_SCALE: f32 = 2.;
+#[cfg(not(target_arch = "wasm32"))]
+const MAXIMUM_SCREENSHOT_SIZE: f32 = 8192.;
+
+// Seconds of flight at the run's speed for each metre the head moves in VR, so that the world looks the same size at
+// any speed.
+const HEADSET_SECONDS_PER_METRE: f32 = 0.2;
 
 pub struct Overlay {
+    pub is_camera_path_visible: bool,
+    pub is_campaign_visible: bool,
     pub is_options_visible: bool,
     pub is_help_visible: bool,
+    pub is_stats_visible: bool,
+    pub cursor_position: [f32; 2],
     pub last_cursor_movement: Instant,
+    pub livesplit_address: String,
+    pub new_profile: String,
+    pub pending_crash_report: Option<path::PathBuf>,
+    pub reset_layout: bool,
     pub seed_string: String,
+    pub twitch_channel: String,
+}
+
+// A world loaded from a file or built for the tutorial instead of generated, which is built again whenever the
+// world is regenerated.
+pub enum ImportedWorld {
+    Graph(graph::Graph),
+    Model(voxelize::Model),
+    Terrain(heightmap::Heightmap),
+    Tutorial,
+}
+
+// Where a world was left through a portal, so that its return portal can lead back to the same place.
+pub struct PortalExit {
+    pub seed: u64,
+    pub position: Vector3<f32>,
+    pub orientation: Quaternion<f32>,
 }
 
 pub struct App {
+    pub achievements: Achievements,
     pub app_start_time: Instant,
-    pub descriptor_set: Arc<PersistentDescriptorSet>,
-    pub engine: crate::helens::Engine,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub audio: Option<Audio>,
+    pub autopilot: Autopilot,
+    pub camera_path: CameraPath,
+    pub campaign: Campaign,
+    pub carvings: Carvings,
+    pub chat: Option<ChatListener>,
+    pub controller_toasts: ControllerToasts,
+    pub custom_modes: CustomModes,
+    pub debug_view: game::DebugView,
+    pub difficulty: f32,
+    pub drones: Drones,
     pub game: crate::game::State,
+    pub haptics: Haptics,
+    pub hazards: WorldHazards,
+    pub history: WorldHistory,
+    pub hotas_listening: Option<game::HotasControl>,
+    pub imported_world: Option<ImportedWorld>,
+    pub inspector: Option<Inspector>,
+    pub key_labels: KeyLabels,
     pub last_draw_time: Option<Instant>,
-    pub log_file: LogFile,
+    pub map_window: bool,
+    pub octree: Vec<VoxelCompact>,
+    pub octree_cache: voxels::TraversalCache,
+    pub hull_caches: Vec<voxels::TraversalCache>,
     pub overlay: Overlay,
+    pub particles: Particles,
+    pub paused_since: Option<Instant>,
+    pub photo: Option<PhotoMode>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub plugins: Option<Plugins>,
+    pub portal_values: PortalValues,
+    pub preview: WorldPreview,
+    pub profiles: Profiles,
+    pub projectiles: Projectiles,
     pub random: voxels::RandomOctreeHelper,
-    pub voxel_buffer: Subbuffer<[VoxelCompact]>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub recorder: Option<Recorder>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub remote: Option<RemoteControl>,
+    pub renderer: Box<dyn Renderer>,
+    pub resolution: DynamicResolution,
+    pub rings: Rings,
+    pub samples: u32,
+    pub score_popups: ScorePopups,
+    pub spectator: Option<Spectator>,
+    pub speedrun: SpeedrunTimer,
+    pub stats: Stats,
+    #[cfg(feature = "steam")]
+    pub steam: Option<Steam>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub stream_files: StreamFiles,
+    pub touch: TouchControls,
+    pub trail: Trail,
+    pub tutorial: Option<Tutorial>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub updates: Option<UpdateChecker>,
+    pub wind: Wind,
+    pub window_focused: bool,
+    pub world_stack: Vec<PortalExit>,
 
     #[cfg(all(not(debug_assertions), target_os = "windows"))]
     pub console: ConsoleState,
 }
 
 impl App {
-    pub fn new() -> (Self, EventLoop<()>, Gui, VulkanoWindows) {
+    // Setup is asynchronous because browsers can't block while waiting for the GPU.
+    pub async fn new(args: cli::Args) -> (Self, EventLoop<()>) {
         // Create a console window for debugging.
         #[cfg(all(not(debug_assertions), target_os = "windows"))]
         let console = ConsoleState::new(false).expect("Could not allocate a console window.");
@@ -84,122 +247,520 @@ impl App {
         // Winit event loop.
         let event_loop = EventLoop::new();
 
-        // Get Vulkano context.
-        let context = VulkanoContext::new(VulkanoConfig::default());
-
-        // Vulkano windows (create one).
-        let mut window_manager = VulkanoWindows::default();
-        window_manager.create_window(
-            &event_loop,
-            &context,
-            &WindowDescriptor {
-                title: TITLE.to_string(),
-                present_mode: vulkano::swapchain::PresentMode::Mailbox,
-                ..WindowDescriptor::default()
-            },
-            |_| {},
-        );
-        let renderer = window_manager.get_primary_renderer().unwrap();
-
-        // Get the image format that will be used by the swapchain and is acceptable for the window surface.
-        let image_format = renderer.swapchain_format();
-
-        // Initialize standalone engine.
-        let engine = helens::Engine::new(
-            renderer.graphics_queue(),
-            image_format,
-            Viewport {
-                offset: [0.; 2],
-                extent: renderer.window_size(),
-                depth_range: 0.0..=1.,
-            },
-        );
-
-        // Create the RNG to be used for voxel-world generation.
-        let mut random = voxels::RandomOctreeHelper::default();
-
-        // Create GUI manager that will render as a subpass of our render pass.
-        let gui = Gui::new_with_subpass(
-            &event_loop,
-            renderer.surface(),
-            renderer.graphics_queue(),
-            engine.gui_pass(),
-            renderer.swapchain_format(),
-            GuiConfig::default(),
-        );
+        // Create the RNG to be used for voxel-world generation, starting from the requested seed if given.
+        let seed_given = args.seed.is_some();
+        let mut random = args
+            .seed
+            .as_deref()
+            .map_or_else(voxels::RandomOctreeHelper::default, |text| {
+                voxels::RandomOctreeHelper::new(seed::from_text(text))
+            });
 
         // Create manager for the GUI overlay and state.
         let overlay = {
             Overlay {
+                is_camera_path_visible: false,
+                is_campaign_visible: false,
                 is_options_visible: SHOW_OVERLAY_AT_LAUNCH,
                 is_help_visible: SHOW_OVERLAY_AT_LAUNCH,
+                is_stats_visible: false,
+                cursor_position: [0.; 2],
                 last_cursor_movement: Instant::now(),
-                seed_string: random.get_seed().to_string(),
+                livesplit_address: livesplit::DEFAULT_ADDRESS.to_owned(),
+                new_profile: String::new(),
+                pending_crash_report: crate::crash::take_pending_report(),
+                reset_layout: false,
+                seed_string: args.seed.map_or_else(
+                    || random.get_seed().to_string(),
+                    |text| text.trim().to_owned(),
+                ),
+                twitch_channel: String::new(),
             }
         };
 
-        // Create a log file for app convenience.
-        let mut log_file = LogFile::default();
+        // Choose whose options, statistics, and achievements to load, by name on the command line or else the
+        // profile played last.
+        let profiles = Profiles::load(args.profile.as_deref());
 
         // Initialize storage buffer with random voxel-octree data.
-        let (descriptor_set, voxel_buffer) = create_random_world(
-            engine.allocators(),
-            engine.pipeline(),
-            &mut random,
-            &mut log_file,
-        );
+        // Create an initial game state using the stored options.
+        // A theme given on the command line or in a link replaces the stored one.
+        let mut game_state = game::State {
+            options: game::Options::load(),
+            spawn_points: SpawnPoints::load(),
+            ..game::State::default()
+        };
+        if let Some(theme) = args.theme {
+            game_state.options.theme = theme;
+        }
 
-        // Create an initial game state.
-        let game_state = game::State::default();
+        // Load the model or world file given on the command line, falling back to generated worlds if it can't be
+        // read.
+        let imported_world = args
+            .model
+            .as_deref()
+            .and_then(|path| {
+                let depth = args.model_depth.unwrap_or(voxelize::DEFAULT_MODEL_DEPTH);
+                voxelize::Model::load(path, depth)
+                    .map(ImportedWorld::Model)
+                    .map_err(|e| {
+                        log::error!(target: "world", "Failed to load model {}: {e}", path.display());
+                    })
+                    .ok()
+            })
+            .or_else(|| {
+                let path = args.world.as_deref()?;
+                graph::Graph::load(path)
+                    .map(ImportedWorld::Graph)
+                    .map_err(|e| {
+                        log::error!(target: "world", "Failed to load world {}: {e}", path.display());
+                    })
+                    .ok()
+            });
+
+        // Teach the controls in the tutorial world on the first launch, unless a particular world was asked for.
+        // Players who have taken portals before this tutorial existed are assumed not to need it.
+        let stats = Stats::load();
+        let tutorial = (imported_world.is_none()
+            && !seed_given
+            && !game_state.options.tutorial_complete
+            && stats.portals_taken == 0)
+            .then(Tutorial::default);
+        let imported_world = if tutorial.is_some() {
+            Some(ImportedWorld::Tutorial)
+        } else {
+            imported_world
+        };
+
+        // Generate the first world and create the window and renderer to display it.
+        // A GPU named on the command line takes precedence over the stored option.
+        let carvings = Carvings::load();
+        let mut octree = create_world(&mut random, &game_state.options, imported_world.as_ref());
+        if imported_world.is_none() {
+            carvings.apply(random.get_seed(), &mut octree);
+        }
+        let difficulty = voxels::difficulty(&octree);
+        game_state.place_start(&octree);
+        game_state.reset_camera();
+        let rings = Rings::place(&mut random, &octree);
+        let hazards = WorldHazards::place(&mut random, &octree);
+        let wind = Wind::new(&mut random);
+        let backend = args.backend.unwrap_or_else(Backend::detect);
+        let gpu = args.gpu.as_deref().or(game_state.options.gpu.as_deref());
+        let lights = WorldLights::discover(&octree, &game_state.options.theme.theme());
+        let samples = game_state.options.multisampling.samples();
+        let renderer = renderer::create(
+            backend,
+            &event_loop,
+            TITLE,
+            &octree,
+            &lights,
+            &hazards,
+            gpu,
+            samples,
+            args.vr,
+        )
+        .await;
+        #[cfg(not(target_arch = "wasm32"))]
+        let plugins = game_state.options.plugins.then(|| {
+            let mut plugins = Plugins::load();
+            plugins.call(Hook::WorldGenerated(random.get_seed()));
+            plugins
+        });
+        #[cfg(not(target_arch = "wasm32"))]
+        let remote = game_state
+            .options
+            .remote_control
+            .and_then(|port| start_remote_control(port, &mut game_state.options));
+        #[cfg(not(target_arch = "wasm32"))]
+        let updates = (game_state.options.check_for_updates && !game_state.options.offline)
+            .then(UpdateChecker::start);
+        let mut history = WorldHistory::default();
+        if imported_world.is_none() {
+            history.visit(WorldRecord::new(random.get_seed(), &game_state.options));
+        }
 
         (
             App {
+                achievements: Achievements::load(),
                 app_start_time: Instant::now(),
-                descriptor_set,
-                engine,
+                #[cfg(not(target_arch = "wasm32"))]
+                audio: None,
+                autopilot: Autopilot::default(),
+                camera_path: CameraPath::load(),
+                campaign: Campaign::default(),
+                carvings,
+                chat: None,
+                controller_toasts: ControllerToasts::default(),
+                custom_modes: CustomModes::load(),
+                debug_view: game::DebugView::default(),
+                difficulty,
+                drones: Drones::default(),
                 game: game_state,
+                haptics: Haptics::default(),
+                hazards,
+                history,
+                hotas_listening: None,
+                imported_world,
+                inspector: None,
+                key_labels: KeyLabels::default(),
                 last_draw_time: None,
-                log_file,
+                map_window: false,
+                octree,
+                octree_cache: voxels::TraversalCache::default(),
+                hull_caches: Vec::new(),
                 overlay,
+                particles: Particles::default(),
+                paused_since: None,
+                photo: None,
+                #[cfg(not(target_arch = "wasm32"))]
+                plugins,
+                portal_values: PortalValues::default(),
+                preview: WorldPreview::default(),
+                profiles,
+                projectiles: Projectiles::default(),
                 random,
-                voxel_buffer,
+                #[cfg(not(target_arch = "wasm32"))]
+                recorder: None,
+                #[cfg(not(target_arch = "wasm32"))]
+                remote,
+                renderer,
+                resolution: DynamicResolution::default(),
+                rings,
+                samples,
+                score_popups: ScorePopups::default(),
+                spectator: None,
+                speedrun: SpeedrunTimer::default(),
+                stats,
+                #[cfg(feature = "steam")]
+                steam: Steam::init(),
+                #[cfg(not(target_arch = "wasm32"))]
+                stream_files: StreamFiles::default(),
+                touch: TouchControls::default(),
+                trail: Trail::default(),
+                tutorial,
+                #[cfg(not(target_arch = "wasm32"))]
+                updates,
+                wind,
+                window_focused: true,
+                world_stack: Vec::new(),
 
                 #[cfg(all(not(debug_assertions), target_os = "windows"))]
                 console,
             },
             event_loop,
-            gui,
-            window_manager,
         )
     }
 
+    // Replace the world with a newly generated one, forgetting the worlds that return portals lead back to.
     pub fn new_random_world(&mut self, world_seed: u64) {
+        self.world_stack.clear();
+        self.enter_world(world_seed);
+    }
+
+    // Generate the world of the given seed and start at its beginning.
+    fn enter_world(&mut self, world_seed: u64) {
         // Ensure that creating a new world always requires updating to a new seed.
         self.random.set_seed(world_seed);
 
         // Update the overlay with the new seed.
         self.overlay.seed_string = world_seed.to_string();
 
-        // Create GPU buffer and descriptor set for new world.
-        let (descriptor_set, voxel_buffer) = create_random_world(
-            self.engine.allocators(),
-            self.engine.pipeline(),
-            &mut self.random,
-            &mut self.log_file,
-        );
-        self.descriptor_set = descriptor_set;
-        self.voxel_buffer = voxel_buffer;
+        // Leave an imported world for a generated one.
+        self.imported_world = None;
+
+        // Particles belong to the world they were emitted in.
+        self.particles.clear();
+
+        // Generate the new world and upload it to the GPU.
+        self.regenerate_world();
 
         // Reset the camera since we never enter a new world at a non-start orientation.
+        if let Some(photo) = self.photo.take() {
+            photo.exit(&mut self.game);
+        }
         self.game.reset_camera();
     }
 
-    pub fn tock_frame(
-        &mut self,
-        gui: &mut Gui,
-        window_manager: &mut VulkanoWindows,
-        window_size: [f32; 2],
-    ) {
+    // Return to the previous world in the history, or forward to the next, restarting the run in it.
+    fn step_world_history(&mut self, forward: bool) {
+        let record = if forward {
+            self.history.forward()
+        } else {
+            self.history.back()
+        };
+        let Some(record) = record else {
+            return;
+        };
+        record.apply(&mut self.game.options);
+        self.game.run = Run::default();
+        self.new_random_world(record.seed);
+    }
+
+    // Save everything kept for the current profile, then play as the given profile from a new run.
+    // A new profile starts with the current options, and the world is generated again with the profile's options.
+    fn switch_profile(&mut self, profile: Option<String>) {
+        self.save_on_exit();
+        let is_new = profile
+            .as_deref()
+            .is_some_and(|name| !self.profiles.exists(name));
+        self.profiles.select(profile);
+        if is_new {
+            self.game.options.save();
+        } else {
+            self.game.options = game::Options::load();
+        }
+        self.game.spawn_points = SpawnPoints::load();
+        self.stats = Stats::load();
+        self.achievements = Achievements::load();
+        self.camera_path = CameraPath::load();
+        self.carvings = Carvings::load();
+        self.game.run = Run::default();
+        self.new_random_world(self.random.get_seed());
+    }
+
+    // Save the current data, then export the data of every profile to a zip archive chosen by the player.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_data(&self) {
+        let lang = self.game.options.language;
+        let Some(path) = rfd::FileDialog::new()
+            .set_title(lang.tr(Text::ExportData))
+            .set_file_name("voxel_flight_simulator_data.zip")
+            .add_filter("Zip", &["zip"])
+            .save_file()
+        else {
+            return;
+        };
+        self.save_on_exit();
+        match backup::export(&path) {
+            Ok(count) => {
+                log::info!(target: "backup", "Exported data path=\"{}\" file_count={count}", path.display());
+            }
+            Err(e) => log::error!(target: "backup", "Failed to export data: {e}"),
+        }
+    }
+
+    // Replace the data of every profile with an exported zip archive chosen by the player, then load it as if the
+    // game was launched again, starting a new run.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_data(&mut self) {
+        let lang = self.game.options.language;
+        let Some(path) = rfd::FileDialog::new()
+            .set_title(lang.tr(Text::ImportData))
+            .add_filter("Zip", &["zip"])
+            .pick_file()
+        else {
+            return;
+        };
+        match backup::import(&path) {
+            Ok(count) => {
+                log::info!(target: "backup", "Imported data path=\"{}\" file_count={count}", path.display());
+                self.profiles = Profiles::load(None);
+                self.game.options = game::Options::load();
+                self.game.spawn_points = SpawnPoints::load();
+                self.stats = Stats::load();
+                self.achievements = Achievements::load();
+                self.camera_path = CameraPath::load();
+                self.carvings = Carvings::load();
+                self.game.run = Run::default();
+                self.new_random_world(self.random.get_seed());
+            }
+            Err(e) => log::error!(target: "backup", "Failed to import data: {e}"),
+        }
+    }
+
+    // Restart the run in the tutorial world, teaching the controls from the beginning.
+    fn start_tutorial(&mut self) {
+        self.tutorial = Some(Tutorial::default());
+        self.imported_world = Some(ImportedWorld::Tutorial);
+        self.world_stack.clear();
+        self.game.run = Run::default();
+        self.regenerate_world();
+        if let Some(photo) = self.photo.take() {
+            photo.exit(&mut self.game);
+        }
+        self.game.reset_camera();
+    }
+
+    // End the tutorial so that it isn't offered again.
+    fn finish_tutorial(&mut self) {
+        self.tutorial = None;
+        self.game.options.tutorial_complete = true;
+    }
+
+    // Ask for a heightmap and an optional colour map, then restart the run over the terrain made from them.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_image(&mut self) {
+        let lang = self.game.options.language;
+        let pick = |title| {
+            rfd::FileDialog::new()
+                .set_title(lang.tr(title))
+                .add_filter("PNG", &["png"])
+                .pick_file()
+        };
+        let Some(path) = pick(Text::ChooseHeightmap) else {
+            return;
+        };
+        let colour_path = pick(Text::ChooseColourMap);
+        match heightmap::Heightmap::load(&path, colour_path.as_deref()) {
+            Ok(heightmap) => self.fly_imported_world(ImportedWorld::Terrain(heightmap)),
+            Err(e) => {
+                log::error!(target: "world", "Failed to import image {}: {e}", path.display());
+            }
+        }
+    }
+
+    // Ask for a world file, then restart the run in the hand-authored world it describes.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_world_file(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title(self.game.options.language.tr(Text::ChooseWorldFile))
+            .add_filter("TOML", &["toml"])
+            .pick_file()
+        else {
+            return;
+        };
+        match graph::Graph::load(&path) {
+            Ok(graph) => self.fly_imported_world(ImportedWorld::Graph(graph)),
+            Err(e) => {
+                log::error!(target: "world", "Failed to import world {}: {e}", path.display());
+            }
+        }
+    }
+
+    // Restart the run in an imported world.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn fly_imported_world(&mut self, world: ImportedWorld) {
+        self.imported_world = Some(world);
+        self.game.run = Run::default();
+        self.regenerate_world();
+        if let Some(photo) = self.photo.take() {
+            photo.exit(&mut self.game);
+        }
+        self.game.reset_camera();
+    }
+
+    // Regenerate the current world from its seed, or build the imported world again, such as after the palette has
+    // changed.
+    // The layout is unchanged, so the camera and run are left as they are.
+    fn regenerate_world(&mut self) {
+        self.random.set_seed(self.random.get_seed());
+        let options = &self.game.options;
+        self.octree = create_world(&mut self.random, options, self.imported_world.as_ref());
+        if self.imported_world.is_none() {
+            self.carvings
+                .apply(self.random.get_seed(), &mut self.octree);
+        }
+        self.difficulty = voxels::difficulty(&self.octree);
+        if !self.world_stack.is_empty() {
+            voxels::add_return_portal(&mut self.octree);
+        }
+        self.rings = Rings::place(&mut self.random, &self.octree);
+        self.hazards = WorldHazards::place(&mut self.random, &self.octree);
+        self.wind = Wind::new(&mut self.random);
+        if self.imported_world.is_none() {
+            self.history
+                .visit(WorldRecord::new(self.random.get_seed(), options));
+        }
+        self.preview.clear();
+        self.octree_cache.clear();
+        self.hull_caches.clear();
+        self.projectiles.clear();
+        self.drones.reset();
+        self.autopilot.reset();
+        self.renderer.upload_world(
+            &self.octree,
+            &WorldLights::discover(&self.octree, &options.theme.theme()),
+            &self.hazards,
+        );
+        self.game.place_start(&self.octree);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.call_plugins(Hook::WorldGenerated(self.random.get_seed()));
+    }
+
+    // Time to wait before the next frame is due, if the frame rate is currently limited.
+    pub fn time_until_next_frame(&self) -> Option<Duration> {
+        let fps_limit = self.fps_limit()?;
+        let frame_time = Duration::from_secs_f64(1. / f64::from(fps_limit));
+        frame_time.checked_sub(self.last_draw_time?.elapsed())
+    }
+
+    // Seconds of game time since the app started, which stand still while the game is paused.
+    fn game_time(&self) -> f32 {
+        self.paused_since
+            .unwrap_or_else(Instant::now)
+            .duration_since(self.app_start_time)
+            .as_secs_f32()
+    }
+
+    // Track whether any of the app's windows has focus, pausing the game while none do if the options ask to.
+    // Resuming moves the clocks forward by the time spent paused, so that neither the run timer nor the world
+    // advances in the background.
+    pub fn set_focused(&mut self, focused: bool) {
+        self.window_focused = focused;
+        if !focused {
+            if self.game.options.pause_on_focus_loss {
+                self.paused_since.get_or_insert_with(Instant::now);
+            }
+            return;
+        }
+        if let Some(paused_since) = self.paused_since.take() {
+            let paused = paused_since.elapsed();
+            self.app_start_time += paused;
+            // Photo mode already moves the run timer forward by its whole duration on exit.
+            if self.photo.is_none() {
+                if let Some(start) = &mut self.game.run.start {
+                    *start += paused;
+                }
+            }
+        }
+    }
+
+    // The current frame rate limit, if any.
+    // Rendering is throttled while the window is in the background, in addition to any chosen limit.
+    // While recording, frames are limited to the frame rate of the video.
+    fn fps_limit(&self) -> Option<u32> {
+        let fps_limit = match (self.window_focused, self.game.options.fps_limit) {
+            (true, fps_limit) => fps_limit,
+            (false, fps_limit) => Some(fps_limit.map_or(BACKGROUND_FPS_LIMIT, |limit| {
+                limit.min(BACKGROUND_FPS_LIMIT)
+            })),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(recorder) = &self.recorder {
+            return Some(fps_limit.map_or(recorder.fps(), |limit| limit.min(recorder.fps())));
+        }
+        fps_limit
+    }
+
+    // Open or close the map window to match the request, giving up if the renderer can't show one.
+    pub fn update_map_window(&mut self, window_target: &EventLoopWindowTarget<()>) {
+        let title = self.map_window.then(|| {
+            let lang = self.game.options.language;
+            format!("{TITLE} - {}", lang.tr(Text::MapWindowTitle))
+        });
+        if !self
+            .renderer
+            .set_map_window(window_target, title.as_deref())
+        {
+            log::error!(target: "gpu", "The map window is only available with the Vulkan backend");
+            self.map_window = false;
+        }
+    }
+
+    // Multisample frames with the samples chosen in the options once they change.
+    pub fn update_multisampling(&mut self, window_target: &EventLoopWindowTarget<()>) {
+        let samples = self.game.options.multisampling.samples();
+        if samples == self.samples {
+            return;
+        }
+        self.samples = samples;
+        if !self.renderer.set_multisampling(window_target, samples) {
+            log::warn!(target: "gpu", "Multisampling is only available with the Vulkan backend");
+        }
+    }
+
+    pub fn tock_frame(&mut self) {
         // Update frame-render timing.
         let delta_time = if let Some(instant) = self.last_draw_time {
             instant.elapsed()
@@ -210,63 +771,570 @@ impl App {
         self.last_draw_time = Some(Instant::now());
 
         // Update window cursor visibility.
-        if let Some(window) = window_manager.get_primary_window() {
-            const CURSOR_WAIT_TO_HIDE_DURATION: f32 = 2.;
-            window.set_cursor_visible(
-                self.overlay.last_cursor_movement.elapsed().as_secs_f32()
-                    < CURSOR_WAIT_TO_HIDE_DURATION,
-            );
+        self.renderer.window().set_cursor_visible(
+            self.overlay.last_cursor_movement.elapsed().as_secs_f32()
+                < CURSOR_WAIT_TO_HIDE_DURATION,
+        );
+
+        // Report the game's state and follow any commands sent by remote control, even while paused.
+        #[cfg(not(target_arch = "wasm32"))]
+        self.handle_remote_commands();
+
+        // While paused, only the overlay is updated and the world is drawn as it was left.
+        if self.paused_since.is_some() {
+            self.update_overlay();
+            let uniforms = self.frame_uniforms();
+            self.renderer.render_frame(&uniforms);
+            return;
         }
 
-        // Update gamepad state.
+        // Update gamepad state, then apply the actions translated from every input since the last frame.
+        // The player steering or boosting ends the wait for input.
         self.handle_controller_inputs();
+        let started = self.game.apply_queued_actions();
+        self.game
+            .run
+            .ensure_running_if(started && self.accepts_flight_input());
+
+        // Apply the result of any closed chat vote.
+        self.handle_chat_votes();
+
+        // Update camera state, following the camera path while it plays or flying freely in photo mode.
+        if self.photo.is_some() {
+            PhotoMode::fly(&mut self.game, delta_time);
+        } else if self.camera_path.is_playing() {
+            if let Some((position, orientation)) = self.camera_path.advance(delta_time) {
+                self.game.camera_position = position;
+                self.game.camera_quaternion = orientation;
+                self.game.align_velocity();
+            }
+        } else {
+            self.update_player_state(delta_time);
+        }
+        if let Some(spectator) = &mut self.spectator {
+            spectator.fly(&self.game, delta_time);
+        }
+
+        // Fly the projectiles fired on through the world, reacting to whatever they strike. They are paused in photo
+        // mode. A portal struck leaves the world, so the rest of the impacts no longer apply.
+        if self.photo.is_none() {
+            for impact in self.projectiles.update(delta_time, &self.octree) {
+                let portal = matches!(
+                    impact.intersection,
+                    voxels::Intersection::Portal { .. } | voxels::Intersection::ReturnPortal
+                );
+                self.handle_impact(impact);
+                if portal {
+                    break;
+                }
+            }
+        }
+
+        // Fly the drones after the camera, also paused in photo mode.
+        if self.photo.is_none() {
+            let scoring = self.rules().scoring;
+            self.drones
+                .update(&self.game, scoring, delta_time, &self.octree);
+        }
+
+        // Carry the particles showing the wind along with it, except while the world is paused in photo mode.
+        if self.game.options.wind.is_none() {
+            self.wind.clear_particles();
+        } else if self.photo.is_none() {
+            let time = self.game_time();
+            self.wind
+                .update_particles(&self.game, time, delta_time, &self.octree);
+        }
+
+        // Move the particles and upload them to be drawn over the next frame. They are paused in photo mode, and left
+        // out of stereo views since they are drawn from the centre of view rather than from each eye.
+        let stereo = self.game.options.stereo != StereoMode::Off;
+        if self.game.options.particles && !stereo {
+            if self.photo.is_none() {
+                self.particles.update(delta_time);
+            }
+            self.renderer.upload_particles(&self.particles.instances());
+        } else {
+            self.particles.clear();
+        }
+
+        // Fade the trail behind the camera and upload it to be drawn, also paused in photo mode and left out of stereo.
+        if self.game.options.trail && !stereo {
+            if self.photo.is_none() {
+                self.trail.update(delta_time);
+            }
+            self.renderer.upload_trail(&self.trail.vertices());
+        } else {
+            self.trail.clear();
+        }
+
+        // Play the world's music, which intensifies with the camera's speed, and the sound effects.
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_audio();
+
+        // A countdown ends the run once its time runs out.
+        if self
+            .rules()
+            .time_left(&self.game.run)
+            .is_some_and(|left| left <= 0.)
+        {
+            log::info!(target: "run", "Time ran out score={}", self.game.run.points);
+            self.game.run = Run::default();
+            self.game.reset_camera();
+            self.rings.reset();
+            self.drones.reset();
+            self.trail.clear();
+        }
+
+        // Complete the campaign level once its goal is reached, or try it again when the run ends short of it.
+        match self.campaign.update(&self.game.run) {
+            Some(Outcome::Completed(level)) => {
+                #[allow(clippy::cast_possible_truncation)]
+                self.stats.record_campaign_level(level as u32);
+                self.overlay.is_campaign_visible = true;
+                log::info!(target: "campaign", "Campaign level completed level={}", level + 1);
+            }
+            Some(Outcome::Failed(level)) => self.start_campaign_level(level),
+            None => {}
+        }
+        let rules = self.rules();
+
+        // Follow runs starting and ending with the speedrun timer, which splits on each portal taken.
+        // Modes that don't keep score have no timer.
+        let speedrun_timer = self.game.options.speedrun_timer && rules.scoring;
+        if speedrun_timer {
+            self.speedrun.update(&self.game.run);
+        }
+
+        // React to what happened during the run this frame.
+        for event in std::mem::take(&mut self.game.events) {
+            match event {
+                RunEvent::PortalTaken { points, level } => {
+                    if speedrun_timer {
+                        self.speedrun.split(&self.game.run);
+                    }
+                    if rules.scoring {
+                        self.game.begin_score_flash();
+                        self.score_popups.push(points, level);
+                    }
+                    self.haptics.play(&mut self.game, Rumble::Portal);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        self.play_effect(Effect::Portal);
+                        self.call_plugins(Hook::Portal {
+                            level,
+                            score: self.game.run.points,
+                        });
+                    }
+                }
+                RunEvent::Crashed => {
+                    self.haptics.play(&mut self.game, Rumble::Collision);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    self.play_effect(Effect::Crash);
+                }
+                RunEvent::BoostStarted => self.haptics.play(&mut self.game, Rumble::Boost),
+                RunEvent::ObstacleAhead { closeness } => {
+                    self.haptics
+                        .play(&mut self.game, Rumble::Proximity(closeness));
+                }
+            }
+        }
+
+        // Let plugins act on the frame, then carry out what they asked for.
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_plugins(delta_time);
+
+        // Let notifications of controllers changing expire, and release finished rumbles.
+        self.controller_toasts.update();
+        self.haptics.update(delta_time);
+
+        // Unlock any achievements earned this frame, and ease the effects of boosting, crashing, and scoring.
+        // Both are paused in photo mode, and achievements can't be earned in modes that don't keep score.
+        if self.photo.is_none() {
+            self.game.update_boost_blend(delta_time);
+            self.game.update_crash_feedback(delta_time);
+            self.game.update_score_flash(delta_time);
+            self.score_popups.update(delta_time);
+            if rules.scoring {
+                self.achievements.update(&achievements::Progress {
+                    run: &self.game.run,
+                    stats: &self.stats,
+                    boosting: self.game.is_boosting(),
+                    delta_time,
+                    ends_on_crash: rules.collision == CollisionRule::EndRun,
+                });
+            }
+        }
+
+        // Share achievements and the world being flown with Steam.
+        #[cfg(feature = "steam")]
+        if let Some(steam) = &mut self.steam {
+            steam.update(&self.achievements);
+            steam.set_presence(self.random.get_seed(), self.game.run.points);
+        }
+
+        // Share the run with streaming software through files in the app directory.
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.game.options.stream_files {
+            self.stream_files.update(StreamInfo {
+                score: self.game.run.points,
+                level: self.game.run.level,
+                seed: self.random.get_seed(),
+                run_time: self
+                    .game
+                    .run
+                    .start
+                    .map(|start| start.elapsed().as_secs_f32()),
+            });
+        }
 
-        // Update camera state.
-        self.update_player_state(delta_time);
+        // Move the tutorial on as each control is tried, ending it once its world has been left in any way.
+        if self.tutorial.is_some() && !matches!(self.imported_world, Some(ImportedWorld::Tutorial))
+        {
+            self.finish_tutorial();
+        }
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.update(&self.game.input, delta_time);
+        }
+
+        // Lower the resolution while frames are too slow for the target frame rate, which can't be above
+        // the frame rate limit. The resolution is held while the window is in the background.
+        if let Some(target_fps) = self.game.options.dynamic_resolution {
+            if self.window_focused {
+                let target_fps = self
+                    .fps_limit()
+                    .map_or(target_fps, |limit| limit.min(target_fps));
+                self.resolution.update(delta_time, target_fps);
+            }
+        } else {
+            self.resolution.reset();
+        }
+        self.renderer
+            .set_render_scale(self.game.options.graphics.render_scale * self.resolution.scale());
 
-        // Get secondary command buffer for rendering GUI.
-        let renderer = window_manager.get_primary_renderer_mut().unwrap();
-        let gui_command_buffer = self.create_updated_overlay(gui, renderer);
+        // Update the GUI state for this frame.
+        self.update_overlay();
 
         // Render main app with overlay from GUI.
-        let push_constants = {
-            fn light_dir(time: f32) -> Vector3<f32> {
-                let delta = time / -20.;
-                Vector3::new(0.9165 * delta.sin(), 0.4, 0.9165 * delta.cos())
-            }
-            let time = self.app_start_time.elapsed().as_secs_f32();
-            helens::ray_march_voxels_fs::Push {
-                aspect_ratio: window_size[0] / window_size[1],
-                time,
-                camera_position: self.game.camera_position.into(),
-                camera_quaternion: self.game.camera_quaternion.into(),
-                light_dir: light_dir(time).into(),
+        let uniforms = self.frame_uniforms();
+        self.renderer.render_frame(&uniforms);
+
+        // Render each eye of the VR headset, if there is one, from the camera moved and turned by the head.
+        if let Some(eyes) = self.renderer.begin_xr_frame() {
+            let units_per_metre = HEADSET_SECONDS_PER_METRE * self.game.run.camera_speed();
+            let eyes = eyes.map(|eye| uniforms.eye(&eye, units_per_metre));
+            self.renderer.end_xr_frame(&eyes);
+        }
+
+        // Pass any frames that have been copied back to the recording.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(recorder) = &mut self.recorder {
+            for frame in self.renderer.take_captured_frames() {
+                recorder.push(frame);
+            }
+        }
+    }
+
+    // The shader values for rendering the current view of the window.
+    fn frame_uniforms(&self) -> FrameUniforms {
+        let window_size = self.renderer.window_size();
+        let time = self
+            .photo
+            .as_ref()
+            .map_or_else(|| self.game_time(), |photo| photo.time);
+        let sun_time = time * self.rules().sun_rate;
+        // Stereo views converge on the focal plane, which is otherwise a fixed time of flight ahead.
+        let convergence = self.game.options.stereo_convergence * self.game.run.camera_speed();
+        let (exposure, focus_distance, aperture) =
+            self.photo.as_ref().map_or((0., convergence, 0.), |photo| {
+                (photo.exposure, photo.focus_distance, photo.aperture)
+            });
+        let (camera_position, camera_quaternion) = self.view_camera(time);
+        let effects = self.photo.is_none() && self.spectator.is_none();
+        let aspect_ratio = window_size[0] / window_size[1];
+        let lens = self.game.lens(aspect_ratio);
+        FrameUniforms {
+            aspect_ratio,
+            time,
+            camera_position: camera_position.into(),
+            camera_quaternion: camera_quaternion.into(),
+            light_dir: lighting::sun_direction(sun_time).into(),
+            portal_highlight: self.game.options.colorblind_mode.portal_highlight(),
+            fov_y: lens.fov_y,
+            exposure,
+            focus_distance,
+            aperture,
+            shadow_quality: self.game.options.graphics.shadow_quality as u32,
+            ambient_occlusion: self.game.options.graphics.ambient_occlusion.into(),
+            traversal: self.game.options.graphics.traversal(),
+            temporal: self.temporal_flags(),
+            previous_camera_quaternion: [0.; 4],
+            previous_camera_position: [0.; 3],
+            view: self.debug_view as u32
+                | (self.game.options.stereo as u32) << renderer::VIEW_STEREO_SHIFT
+                | (lens.projection as u32) << renderer::VIEW_PROJECTION_SHIFT,
+            speed_lines: if effects { self.game.speed_lines() } else { 0. },
+            crash_flash: if self.spectator.is_some() {
+                0.
+            } else {
+                self.game.crash_flash()
+            },
+            score_flash: if effects { self.game.score_flash } else { 0. },
+            _padding: 0.,
+        }
+    }
+
+    // The camera the window is rendered from, which is the director camera while spectating rather than the game's
+    // camera. Only the game's camera is shaken by crashes.
+    fn view_camera(&self, time: f32) -> (Vector3<f32>, Quaternion<f32>) {
+        match &self.spectator {
+            Some(spectator) => (spectator.position, spectator.quaternion),
+            None => (
+                self.game.camera_position,
+                self.game.shaken_camera_quaternion(time),
+            ),
+        }
+    }
+
+    // The temporal rendering options in effect, which photo mode, debug views, and stereo modes turn off to render
+    // every pixel afresh.
+    fn temporal_flags(&self) -> u32 {
+        if self.photo.is_some()
+            || self.debug_view != game::DebugView::Off
+            || self.game.options.stereo != StereoMode::Off
+        {
+            return 0;
+        }
+        let options = &self.game.options;
+        let mut flags = 0;
+        if options.temporal_reprojection {
+            flags |= renderer::TEMPORAL_REPROJECTION;
+        }
+        if options.checkerboard_rendering {
+            flags |= renderer::TEMPORAL_CHECKERBOARD;
+        }
+        flags
+    }
+
+    // Render the current view without the overlay at a higher resolution than the window, and save it in
+    // the background.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn capture_screenshot(&mut self) {
+        let [width, height] = self.renderer.window_size();
+        let scale = SCREENSHOT_SCALE.min(MAXIMUM_SCREENSHOT_SIZE / width.max(height));
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let size = [(width * scale) as u32, (height * scale) as u32];
+        if size.contains(&0) {
+            return;
+        }
+        let Some(frame) = self.renderer.render_offscreen(&self.frame_uniforms(), size) else {
+            log::error!(target: "screenshot", "Failed to render screenshot");
+            return;
+        };
+
+        std::thread::spawn(move || {
+            let save = || -> std::io::Result<path::PathBuf> {
+                let path = screenshot::new_path("screenshot")?;
+                screenshot::save_png(&frame, &path)?;
+                Ok(path)
+            };
+            match save() {
+                Ok(path) => {
+                    log::info!(target: "screenshot", "Saved screenshot path=\"{}\"", path.display());
+                }
+                Err(e) => log::error!(target: "screenshot", "Failed to save screenshot: {e}"),
+            }
+        });
+    }
+
+    // Render a 360° panorama around the camera. The six cube faces are saved as a skybox alongside an
+    // equirectangular image, which is projected and encoded in the background because it is slow.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn capture_panorama(&mut self) {
+        let uniforms = self.frame_uniforms();
+        let orientation = self
+            .spectator
+            .as_ref()
+            .map_or(self.game.camera_quaternion, |spectator| {
+                spectator.quaternion
+            });
+        let mut faces = Vec::new();
+        for (_, rotation) in screenshot::cube_faces() {
+            let face_uniforms = FrameUniforms {
+                camera_quaternion: (orientation * rotation).into(),
+                aspect_ratio: 1.,
+                fov_y: 1.,
+                view: uniforms.view & renderer::VIEW_DEBUG_MASK,
+                speed_lines: 0.,
+                crash_flash: 0.,
+                score_flash: 0.,
+                ..uniforms
+            };
+            let Some(face) = self
+                .renderer
+                .render_offscreen(&face_uniforms, [PANORAMA_FACE_SIZE; 2])
+            else {
+                log::error!(target: "screenshot", "Failed to render panorama");
+                return;
+            };
+            faces.push(face);
+        }
+
+        std::thread::spawn(move || {
+            let save = || -> std::io::Result<path::PathBuf> {
+                let path = screenshot::new_path("panorama")?;
+                let panorama = screenshot::equirectangular(
+                    &faces,
+                    4 * PANORAMA_FACE_SIZE,
+                    2 * PANORAMA_FACE_SIZE,
+                );
+                screenshot::save_png(&panorama, &path)?;
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                for ((name, _), face) in screenshot::cube_faces().iter().zip(&faces) {
+                    screenshot::save_png(face, &path.with_file_name(format!("{stem}_{name}.png")))?;
+                }
+                Ok(path)
+            };
+            match save() {
+                Ok(path) => {
+                    log::info!(target: "screenshot", "Saved panorama path=\"{}\"", path.display());
+                }
+                Err(e) => log::error!(target: "screenshot", "Failed to save panorama: {e}"),
+            }
+        });
+    }
+
+    // Start or stop the audio output as the mixer asks, keeping the music following the world, level, and camera's
+    // speed. Music and sound effects are turned off in the options if there is no audio device to play them on.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_audio(&mut self) {
+        let mixer = self.game.options.audio;
+        if !mixer.is_enabled() {
+            self.audio = None;
+            return;
+        }
+        if self.audio.is_none() {
+            match Audio::new() {
+                Ok(audio) => self.audio = Some(audio),
+                Err(e) => {
+                    log::warn!(target: "audio", "Failed to start audio error=\"{e}\"");
+                    self.game.options.audio.music = None;
+                    self.game.options.audio.effects = None;
+                    return;
+                }
             }
+        }
+        if let Some(audio) = &mut self.audio {
+            let focused = self.window_focused;
+            audio.update(
+                self.random.get_seed(),
+                self.game.run.level,
+                self.game.camera_speed,
+                mixer.music.map(|_| mixer.music_volume(focused)),
+                mixer.effects_volume(focused),
+            );
+        }
+    }
+
+    // Call a hook of the loaded plugins.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn call_plugins(&mut self, hook: Hook) {
+        if let Some(plugins) = &mut self.plugins {
+            plugins.call(hook);
+        }
+    }
+
+    // Call the frame hook of the loaded plugins, then change the options and regenerate the world as they asked.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_plugins(&mut self, delta_time: f32) {
+        let Some(plugins) = &mut self.plugins else {
+            return;
         };
-        let after_future = self.engine.render_frame(
-            renderer,
-            gui_command_buffer,
-            push_constants,
-            self.descriptor_set.clone(),
-        );
+        plugins.call(Hook::Frame(delta_time));
+        if let Some(seed) = plugins.apply_requests(&mut self.game.options) {
+            let seed = seed.map_or_else(
+                || random_world_seed(&self.game.options),
+                |text| seed::from_text(&text),
+            );
+            log::info!(target: "plugins", "Plugin regenerated the world seed={seed}");
+            self.game.run = Run::default();
+            self.new_random_world(seed);
+        }
+    }
+
+    // Play a sound effect, when there is audio output.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn play_effect(&self, effect: Effect) {
+        if let Some(audio) = &self.audio {
+            audio.play_effect(effect);
+        }
+    }
+
+    // Start or stop recording a video of the rendered frames, including the overlay.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn toggle_recording(&mut self) {
+        if self.recorder.is_some() {
+            self.stop_recording();
+            return;
+        }
 
-        // Present swapchain.
-        renderer.present(after_future, true);
+        if !self.renderer.set_frame_capture(true) {
+            log::error!(target: "recording", "Frames can't be captured from this window");
+            return;
+        }
+        let fps = self
+            .game
+            .options
+            .fps_limit
+            .map_or(RECORDING_FPS, |limit| limit.min(RECORDING_FPS));
+        match Recorder::start(self.game.options.video_format, fps) {
+            Ok(recorder) => {
+                self.recorder = Some(recorder);
+                self.renderer.window().set_title(&format!("{TITLE} - REC"));
+            }
+            Err(e) => {
+                self.renderer.set_frame_capture(false);
+                log::error!(target: "recording", "Failed to start recording: {e}");
+            }
+        }
     }
 
     pub fn handle_keyboard_inputs(
         &mut self,
         keycode: VirtualKeyCode,
+        scancode: u32,
         state: ElementState,
-        window_manager: &mut VulkanoWindows,
         control_flow: &mut ControlFlow,
     ) {
-        let mut game_starting_event = false;
-        match state {
-            ElementState::Pressed => match keycode {
+        // Read the yaw keys by their position, remembering what the layout calls them for the Help window.
+        self.key_labels.observe(scancode, keycode);
+        let Some(keycode) = keys::positional(scancode, keycode) else {
+            return;
+        };
+
+        // Flight keys become actions, which start a run when the next frame applies them.
+        let pressed = state == ElementState::Pressed;
+        if let Some(action) =
+            self.game
+                .keyboard
+                .translate(keycode, pressed, self.game.options.invert_y)
+        {
+            if pressed
+                && keycode == VirtualKeyCode::Space
+                && self.photo.is_none()
+                && self.spectator.is_none()
+            {
+                self.game.input.push(Action::ToggleBoost);
+            }
+            self.game.input.push(action);
+            return;
+        }
+
+        if pressed {
+            match keycode {
                 VirtualKeyCode::Escape => {
                     // If fullscreen then leave fullscreen, else exit the app.
-                    let window = window_manager.get_primary_window().unwrap();
+                    let window = self.renderer.window();
                     match window.fullscreen() {
                         None => *control_flow = ControlFlow::Exit,
                         Some(_) => window.set_fullscreen(None),
@@ -276,17 +1344,34 @@ impl App {
                     // Show the Help window.
                     self.overlay.is_help_visible = !self.overlay.is_help_visible;
                 }
+                VirtualKeyCode::F2 => {
+                    // Show the Statistics window.
+                    self.overlay.is_stats_visible = !self.overlay.is_stats_visible;
+                }
+                VirtualKeyCode::F3 => self.step_world_history(false),
+                VirtualKeyCode::F4 => self.step_world_history(true),
                 VirtualKeyCode::F5 => {
-                    use rand::Rng;
                     self.game.run = Run::default();
-                    self.new_random_world(rand::thread_rng().gen());
+                    self.new_random_world(random_world_seed(&self.game.options));
                 }
+                VirtualKeyCode::F6 => self.toggle_photo_mode(),
+                #[cfg(not(target_arch = "wasm32"))]
+                VirtualKeyCode::F7 => self.capture_screenshot(),
+                #[cfg(not(target_arch = "wasm32"))]
+                VirtualKeyCode::F8 => self.capture_panorama(),
+                #[cfg(not(target_arch = "wasm32"))]
+                VirtualKeyCode::F9 => self.toggle_recording(),
+                VirtualKeyCode::F10 => self.toggle_spectator(),
                 VirtualKeyCode::F11 => {
-                    // Toggle fullscreen.
-                    let window = window_manager.get_primary_window().unwrap();
+                    // Toggle fullscreen, on the monitor and in the mode chosen in the options.
+                    let window = self.renderer.window();
                     match window.fullscreen() {
                         None => {
-                            window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+                            window.set_fullscreen(Some(display::fullscreen(
+                                window,
+                                self.game.options.monitor.as_deref(),
+                                &self.game.options.fullscreen,
+                            )));
                         }
                         Some(_) => {
                             window.set_fullscreen(None);
@@ -297,39 +1382,62 @@ impl App {
                     // Toggle Options window visibility.
                     self.overlay.is_options_visible = !self.overlay.is_options_visible;
                 }
-
-                // Camera controls.
-                VirtualKeyCode::Up => {
-                    self.game.keyboard.up = true;
-                    game_starting_event = true;
+                #[cfg(not(target_arch = "wasm32"))]
+                VirtualKeyCode::M => self.map_window = !self.map_window,
+                VirtualKeyCode::C => {
+                    // Toggle Camera Path window visibility.
+                    self.overlay.is_camera_path_visible = !self.overlay.is_camera_path_visible;
+                }
+                VirtualKeyCode::K => {
+                    self.camera_path.add_keyframe(
+                        self.game.camera_position,
+                        self.game.camera_quaternion,
+                        self.random.get_seed(),
+                    );
+                }
+                VirtualKeyCode::V => self.toggle_camera_path_playback(),
+                VirtualKeyCode::H => {
+                    // Toggle the photo mode panel so that the shot can be seen uncovered.
+                    if let Some(photo) = &mut self.photo {
+                        photo.is_panel_visible = !photo.is_panel_visible;
+                    }
                 }
-                VirtualKeyCode::Down => {
-                    self.game.keyboard.down = true;
-                    game_starting_event = true;
+                VirtualKeyCode::I => {
+                    // Toggle the world inspection tool.
+                    self.inspector = match self.inspector {
+                        Some(_) => None,
+                        None => Some(Inspector::default()),
+                    };
                 }
-                VirtualKeyCode::Left => {
-                    self.game.keyboard.left = true;
-                    game_starting_event = true;
+                VirtualKeyCode::N if self.rules().collision == CollisionRule::Respawn => {
+                    self.save_spawn_point()
                 }
-                VirtualKeyCode::Right => {
-                    self.game.keyboard.right = true;
-                    game_starting_event = true;
+                VirtualKeyCode::F => self.fire(),
+                #[cfg(not(target_arch = "wasm32"))]
+                VirtualKeyCode::Period => {
+                    if let Some(audio) = &mut self.audio {
+                        audio.skip(1);
+                    }
                 }
-                VirtualKeyCode::Space => {
-                    self.game.keyboard.space = true;
-                    match &mut self.game.options.camera_boost {
-                        HoldOrToggle::Toggle(t) => *t = !*t,
-                        HoldOrToggle::Hold => (),
+                #[cfg(not(target_arch = "wasm32"))]
+                VirtualKeyCode::Comma => {
+                    if let Some(audio) = &mut self.audio {
+                        audio.skip(-1);
                     }
-                    game_starting_event = true;
                 }
-                VirtualKeyCode::A => {
-                    self.game.keyboard.a = true;
-                    game_starting_event = true;
+                VirtualKeyCode::R => {
+                    if let Some(point) = self.practice_spawn_point() {
+                        self.game.respawn(point);
+                        self.trail.clear();
+                    }
                 }
-                VirtualKeyCode::D => {
-                    self.game.keyboard.d = true;
-                    game_starting_event = true;
+                VirtualKeyCode::P => {
+                    // Toggle the autopilot. Handing over control ends the current run, and the run can't
+                    // start again until control is taken back.
+                    self.autopilot.enabled = !self.autopilot.enabled;
+                    if self.autopilot.enabled {
+                        self.game.run = Run::default();
+                    }
                 }
 
                 // Toggle Windows console visibility.
@@ -342,75 +1450,173 @@ impl App {
                     }
                 }
                 _ => (),
-            },
-            ElementState::Released => match keycode {
-                // Camera controls.
-                VirtualKeyCode::Up => {
-                    self.game.keyboard.up = false;
-                }
-                VirtualKeyCode::Down => {
-                    self.game.keyboard.down = false;
-                }
-                VirtualKeyCode::Left => {
-                    self.game.keyboard.left = false;
-                }
-                VirtualKeyCode::Right => {
-                    self.game.keyboard.right = false;
-                }
-                VirtualKeyCode::Space => {
-                    self.game.keyboard.space = false;
-                }
-                VirtualKeyCode::A => {
-                    self.game.keyboard.a = false;
-                }
-                VirtualKeyCode::D => {
-                    self.game.keyboard.d = false;
-                }
-                _ => (),
-            },
+            }
         }
+    }
 
-        // If we processed any game-starting events, we're no longer waiting for input.
-        self.game.run.ensure_running_if(game_starting_event);
+    // Steer and boost with the on-screen controls.
+    pub fn handle_touch(&mut self, touch: &winit::event::Touch, over_overlay: bool) {
+        self.touch
+            .handle(touch, self.renderer.window_size(), over_overlay);
+        for action in self.touch.actions(self.game.options.invert_y) {
+            self.game.input.push(action);
+        }
     }
 
     fn handle_controller_inputs(&mut self) {
-        // Default to handling no events.
-        let mut processed = false;
+        // Steam Input takes over from reading controllers directly while it has one connected, and Steam's virtual
+        // controller would otherwise be read twice.
+        #[cfg(feature = "steam")]
+        if let Some(frame) = self.steam.as_mut().and_then(Steam::controller) {
+            while self.game.gilrs.next_event().is_some() {}
+            self.steam_controller_inputs(frame);
+            return;
+        }
+
+        // Whether the raw axes changed, so that their steering needs translating again.
+        let mut steered = false;
 
         // Process all queued events.
         while let Some(event) = self.game.gilrs.next_event() {
             use gilrs::ev::EventType;
-            use SharedAxis::{Single, Split};
+            use input::SharedAxis::{Single, Split};
+
+            // Notify the player of controllers coming and going, releasing any inputs held by a lost controller.
+            match event.event {
+                EventType::Connected => {
+                    let name = self.game.gilrs.gamepad(event.id).name().to_owned();
+                    self.controller_toasts.push(&name, true);
+                    continue;
+                }
+                EventType::Disconnected => {
+                    let name = self.game.gilrs.gamepad(event.id).name().to_owned();
+                    self.controller_toasts.push(&name, false);
+                    self.game.gamepad = GamepadState::default();
+                    for action in [
+                        Action::Release(Source::Gamepad),
+                        Action::Throttle(None),
+                        Action::Look(game::LookDirection::Forward),
+                    ] {
+                        self.game.input.push(action);
+                    }
+                    continue;
+                }
+                _ => (),
+            }
+
+            // Only read the chosen controller when several are connected.
+            if !controllers::accepts(
+                &self.game.gilrs,
+                event.id,
+                self.game.options.controller.as_deref(),
+            ) {
+                continue;
+            }
             match event.event {
+                // Wait for an axis to move far enough while assigning it to a H.O.T.A.S. control.
+                EventType::AxisChanged(_, val, code)
+                    if self.hotas_listening.is_some() && val.abs() > HOTAS_ASSIGN_THRESHOLD =>
+                {
+                    if let Some(control) = self.hotas_listening.take() {
+                        *self.game.options.hotas_axes.get_mut(control) = Some(code.into_u32());
+                    }
+                }
+                EventType::AxisChanged(_, val, code)
+                    if self.game.options.hotas_mode
+                        && self
+                            .game
+                            .options
+                            .hotas_axes
+                            .control(code.into_u32())
+                            .is_some() =>
+                {
+                    let axes = self.game.options.hotas_axes;
+                    match axes.control(code.into_u32()) {
+                        Some(game::HotasControl::Pitch) => self.game.gamepad.left_stick[1] = val,
+                        Some(game::HotasControl::Roll) => self.game.gamepad.left_stick[0] = val,
+                        Some(game::HotasControl::Yaw) => self.game.gamepad.yaw = Single(val),
+                        Some(game::HotasControl::Throttle) => {
+                            // Throttles rest at one end of the axis, so map the whole axis onto [0, 1].
+                            let throttle = 0.5 * (val + 1.);
+                            self.game
+                                .input
+                                .push(Action::Throttle(Some(if axes.invert_throttle {
+                                    1. - throttle
+                                } else {
+                                    throttle
+                                })));
+                        }
+                        None => (),
+                    }
+                    steered = true;
+                }
                 EventType::AxisChanged(axis, val, _) => match axis {
-                    gilrs::Axis::LeftStickX => {
+                    gilrs::Axis::LeftStickX
+                        if self.unassigned_hotas_axis(game::HotasControl::Roll) =>
+                    {
                         self.game.gamepad.left_stick[0] = val;
-                        processed = true;
+                        steered = true;
                     }
-                    gilrs::Axis::LeftStickY => {
+                    gilrs::Axis::LeftStickY
+                        if self.unassigned_hotas_axis(game::HotasControl::Pitch) =>
+                    {
                         self.game.gamepad.left_stick[1] = val;
-                        processed = true;
+                        steered = true;
+                    }
+                    // The hat switch may report as a pair of axes.
+                    gilrs::Axis::DPadX | gilrs::Axis::DPadY if self.game.options.hotas_mode => {
+                        self.game
+                            .input
+                            .push(Action::Look(game::LookDirection::from_hat_axis(axis, val)));
                     }
                     _ => (),
                 },
+                EventType::ButtonPressed(
+                    button @ (gilrs::Button::DPadUp
+                    | gilrs::Button::DPadDown
+                    | gilrs::Button::DPadLeft
+                    | gilrs::Button::DPadRight),
+                    _,
+                ) if self.game.options.hotas_mode => {
+                    self.game.input.push(Action::Look(match button {
+                        gilrs::Button::DPadUp => game::LookDirection::Up,
+                        gilrs::Button::DPadDown => game::LookDirection::Back,
+                        gilrs::Button::DPadLeft => game::LookDirection::Left,
+                        _ => game::LookDirection::Right,
+                    }));
+                }
+                EventType::ButtonReleased(
+                    gilrs::Button::DPadUp
+                    | gilrs::Button::DPadDown
+                    | gilrs::Button::DPadLeft
+                    | gilrs::Button::DPadRight,
+                    _,
+                ) => self
+                    .game
+                    .input
+                    .push(Action::Look(game::LookDirection::Forward)),
                 EventType::ButtonPressed(gilrs::Button::South, _) => {
-                    self.game.gamepad.south_button = true;
-                    match &mut self.game.options.camera_boost {
-                        HoldOrToggle::Toggle(t) => *t = !*t,
-                        HoldOrToggle::Hold => (),
+                    if self.photo.is_none() {
+                        self.game.input.push(Action::ToggleBoost);
                     }
-                    processed = true;
+                    self.game.input.push(Action::Boost {
+                        source: Source::Gamepad,
+                        held: true,
+                    });
                 }
+                EventType::ButtonPressed(gilrs::Button::West, _) => self.fire(),
                 EventType::ButtonReleased(gilrs::Button::South, _) => {
-                    self.game.gamepad.south_button = false;
-                    processed = true;
+                    self.game.input.push(Action::Boost {
+                        source: Source::Gamepad,
+                        held: false,
+                    });
                 }
                 EventType::ButtonChanged(gilrs::Button::RightTrigger2, val, _)
-                    if self.game.options.hotas_mode =>
+                    if self.game.options.hotas_mode
+                        && self.game.options.hotas_axes.yaw.is_none() =>
                 {
                     self.game.gamepad.yaw = Single(val + val - 1.);
-                    processed = true;
+                    steered = true;
                 }
                 EventType::ButtonPressed(gilrs::Button::LeftTrigger, _)
                     if !self.game.options.hotas_mode =>
@@ -420,7 +1626,7 @@ impl App {
                     } else {
                         Split(1., 0.)
                     };
-                    processed = true;
+                    steered = true;
                 }
                 EventType::ButtonReleased(gilrs::Button::LeftTrigger, _)
                     if !self.game.options.hotas_mode =>
@@ -430,7 +1636,7 @@ impl App {
                     } else {
                         Split(0., 0.)
                     };
-                    processed = true;
+                    steered = true;
                 }
                 EventType::ButtonPressed(gilrs::Button::RightTrigger, _)
                     if !self.game.options.hotas_mode =>
@@ -440,7 +1646,7 @@ impl App {
                     } else {
                         Split(0., 1.)
                     };
-                    processed = true;
+                    steered = true;
                 }
                 EventType::ButtonReleased(gilrs::Button::RightTrigger, _)
                     if !self.game.options.hotas_mode =>
@@ -450,357 +1656,2908 @@ impl App {
                     } else {
                         Split(0., 0.)
                     };
-                    processed = true;
+                    steered = true;
                 }
                 _ => (),
             }
         }
 
-        // If we processed any events, we're no longer waiting for input.
-        self.game.run.ensure_running_if(processed);
+        // Translate the raw axes into steering through their response curves.
+        if steered {
+            for action in self.game.gamepad.steering(
+                &self.game.options.gamepad_response,
+                self.game.options.invert_y,
+            ) {
+                self.game.input.push(action);
+            }
+        }
     }
 
-    // Update state for the player/camera and their run.
-    fn update_player_state(&mut self, delta_time: f32) {
-        match self.game.run.start {
-            None => {
-                // Apply demo camera controls until we get input.
-                const DEMO_SPEED: f32 = 0.05;
-                let demo_rotation = Quaternion::from_angle_y(Rad(delta_time * DEMO_SPEED));
-                self.game.camera_position = demo_rotation.rotate_vector(self.game.camera_position);
-                self.game.camera_quaternion = demo_rotation * self.game.camera_quaternion;
-            }
-            Some(_) => {
-                use voxels::Intersection;
-                let intersection = voxels::octree_scale_and_collision_of_point(
-                    self.game.camera_position,
-                    &self.voxel_buffer.read().unwrap(),
-                );
-                match intersection {
-                    Intersection::Empty(scale) => {
-                        const SMOOTHING_INCREASE_FACTOR: f32 = -0.12;
-                        const SMOOTHING_DECREASE_FACTOR: f32 = -1.4;
-                        const SCALING_FACTOR: f32 = 0.7;
-                        const ROLL_SPEED: f32 = 2.;
-                        const PITCH_SPEED: f32 = 1.25;
-                        const YAW_SPEED: f32 = 0.5;
-
-                        self.game.camera_position += if self.game.keyboard.space
-                            || self.game.gamepad.south_button
-                            || self.game.options.camera_boost.into()
-                        {
-                            CAMERA_BOOST_FACTOR
-                        } else {
-                            1.
-                        } * self.game.camera_quaternion.rotate_vector(
-                            Vector3::new(0., 0., delta_time * self.game.camera_speed),
-                        );
+    // Steer, boost, and fire with a controller read through Steam Input.
+    #[cfg(feature = "steam")]
+    fn steam_controller_inputs(&mut self, frame: steam::ControllerFrame) {
+        self.game.gamepad.left_stick = frame.steer;
+        self.game.gamepad.yaw = input::SharedAxis::Split(frame.yaw[0], frame.yaw[1]);
+        if let Some(held) = frame.boost {
+            if held && self.photo.is_none() {
+                self.game.input.push(Action::ToggleBoost);
+            }
+            self.game.input.push(Action::Boost {
+                source: Source::Gamepad,
+                held,
+            });
+        }
+        if frame.fire {
+            self.fire();
+        }
+        for action in self.game.gamepad.steering(
+            &self.game.options.gamepad_response,
+            self.game.options.invert_y,
+        ) {
+            self.game.input.push(action);
+        }
+    }
 
-                        // Use exponential smoothing to make the camera speed change with scale.
-                        let target_speed = game::DEFAULT_CAMERA_SPEED / scale.powf(SCALING_FACTOR);
-                        let smooth = |factor: f32| 1. - (factor * delta_time).exp();
-                        self.game.camera_speed += if target_speed > self.game.camera_speed {
-                            smooth(SMOOTHING_INCREASE_FACTOR)
-                        } else {
-                            smooth(SMOOTHING_DECREASE_FACTOR)
-                        } * (target_speed - self.game.camera_speed);
-
-                        let roll = (f32::from(self.game.keyboard.left)
-                            - f32::from(self.game.keyboard.right)
-                            - self.game.gamepad.left_stick[0])
-                            .clamp(-1., 1.);
-                        let pitch = (f32::from(self.game.keyboard.up)
-                            - f32::from(self.game.keyboard.down)
-                            + self.game.gamepad.left_stick[1])
-                            .clamp(-1., 1.)
-                            * if self.game.options.invert_y { 1. } else { -1. };
-                        let yaw = (f32::from(self.game.keyboard.d)
-                            - f32::from(self.game.keyboard.a)
-                            + match self.game.gamepad.yaw {
-                                SharedAxis::Single(value) => value,
-                                SharedAxis::Split(left, right) => right - left,
-                            })
-                        .clamp(-1., 1.);
-
-                        self.game.camera_quaternion = self.game.camera_quaternion
-                            * Quaternion::from_angle_z(Rad(delta_time * ROLL_SPEED * roll))
-                            * Quaternion::from_angle_x(Rad(delta_time * PITCH_SPEED * pitch))
-                            * Quaternion::from_angle_y(Rad(delta_time * YAW_SPEED * yaw));
-                    }
-                    Intersection::Collision => {
-                        self.game.reset_camera();
-                        self.game.run = Run::default();
-                    }
-                    Intersection::Portal { depth, index } => {
-                        let points_gained =
-                            u32::from(depth == voxels::MAXIMUM_GOAL_DEPTH) + depth + 1
-                                - voxels::MINIMUM_GOAL_DEPTH;
-                        self.game.run.points += points_gained;
-                        self.game.run.level += 1;
+    // Whether the default input should drive the given control, which is always the case outside of H.O.T.A.S. mode.
+    fn unassigned_hotas_axis(&self, control: game::HotasControl) -> bool {
+        !self.game.options.hotas_mode || self.game.options.hotas_axes.get(control).is_none()
+    }
 
-                        // Log the state of the run after taking the portal and gaining points.
-                        self.log_file.log(format!("{:.3}s: Portal depth: {depth}, +{points_gained}, Score: {}, Levels: {}\n",
-                            self.app_start_time.elapsed().as_secs_f32(),
-                            self.game.run.points,
-                            self.game.run.level,
-                        ).as_str());
+    // Whether flight inputs can start a run, rather than the camera being flown by the autopilot, a camera path,
+    // or in photo mode, or the inputs flying the director camera while spectating.
+    fn accepts_flight_input(&self) -> bool {
+        !self.autopilot.enabled
+            && !self.camera_path.is_playing()
+            && self.photo.is_none()
+            && self.spectator.is_none()
+    }
 
-                        // Use the portal taken to seed the RNG for the next world.
-                        self.new_random_world(self.random.get_seed() + u64::from(index));
-                    }
-                }
-            }
+    // Inspect the voxel under the cursor, if the inspection tool is open.
+    pub fn inspect_cursor(&mut self) {
+        if let Some(inspector) = &mut self.inspector {
+            inspector.inspect(
+                &self.game,
+                &self.octree,
+                self.renderer.window_size(),
+                self.overlay.cursor_position,
+            );
         }
     }
 
-    // Options window helper.
-    fn options_window(&mut self, ctx: &Context) {
-        // Copy the current visibility state to a temporary variable.
-        // This is needed to avoid a borrow conflict on `app`.
-        let mut is_options_visible = self.overlay.is_options_visible;
+    // Enter or leave photo mode, which pauses the game and stops any camera path playback.
+    fn toggle_photo_mode(&mut self) {
+        if let Some(photo) = self.photo.take() {
+            photo.exit(&mut self.game);
+            return;
+        }
+        self.camera_path.stop();
+        self.spectator = None;
+        self.photo = Some(PhotoMode::enter(&self.game, &self.octree, self.game_time()));
+    }
 
-        // Create an Egui window that starts closed.
-        egui::Window::new("Options")
-            .default_open(false)
-            .open(&mut is_options_visible)
-            .show(ctx, |ui| {
-                // Create a toggle for reading inputs as a gamepad or H.O.T.A.S.
-                ui.checkbox(
-                    &mut self.game.options.hotas_mode,
-                    "Treat gamepad as H.O.T.A.S. stick",
-                );
+    // Enter or leave spectator mode, where the flight controls fly a director camera while the plane flies itself.
+    // Spectating ends the current run, and no run can start until it is left.
+    fn toggle_spectator(&mut self) {
+        if self.spectator.take().is_some() {
+            return;
+        }
+        if let Some(photo) = self.photo.take() {
+            photo.exit(&mut self.game);
+        }
+        self.game.run = Run::default();
+        self.spectator = Some(Spectator::enter(&self.game));
+    }
 
-                // Create an option to either hold or toggle for boost.
-                let mut b = self.game.options.camera_boost != HoldOrToggle::Hold;
-                if ui.checkbox(&mut b, "Toggle boost").changed() {
-                    self.game.options.camera_boost = match self.game.options.camera_boost {
-                        HoldOrToggle::Hold => HoldOrToggle::Toggle(false),
-                        HoldOrToggle::Toggle(_) => HoldOrToggle::Hold,
-                    };
+    // Start or stop playing the camera path. Playback ends the current run and switches to the path's world.
+    fn toggle_camera_path_playback(&mut self) {
+        if self.camera_path.is_playing() {
+            self.camera_path.stop();
+            return;
+        }
+        if !self.camera_path.can_play() || self.photo.is_some() {
+            return;
+        }
+        self.game.run = Run::default();
+        if let Some(seed) = self.camera_path.seed {
+            if seed != self.random.get_seed() {
+                self.new_random_world(seed);
+            }
+        }
+        self.camera_path.play();
+    }
+
+    // Regenerate the world, or add a hazard to it, when Twitch chat has voted for it.
+    fn handle_chat_votes(&mut self) {
+        let Some(chat) = &mut self.chat else {
+            return;
+        };
+        if let Some(vote) = chat.poll() {
+            let seed = match vote {
+                twitch::Vote::Seed(seed) => seed,
+                twitch::Vote::Random => random_world_seed(&self.game.options),
+                twitch::Vote::Hazard => {
+                    self.add_hazard();
+                    return;
                 }
+            };
+            log::info!(target: "twitch", "Chat voted for seed={seed}");
+            self.game.run = Run::default();
+            self.new_random_world(seed);
+        }
+    }
 
-                // Allow user to view, edit, and set the world seed.
-                ui.horizontal(|ui| {
-                    ui.text_edit_singleline(&mut self.overlay.seed_string);
-                    if ui.button("Set seed").clicked() {
-                        if let Ok(seed) = self.overlay.seed_string.parse::<u64>() {
-                            self.game.run = Run::default();
-                            self.new_random_world(seed);
-                        }
-                    }
-                });
+    // Add a hazard to the current world, re-uploading the world so the shader draws it.
+    fn add_hazard(&mut self) {
+        if !self.hazards.add(&mut self.random, &self.octree) {
+            log::info!(target: "twitch", "Chat voted for a hazard, but there is no room for another");
+            return;
+        }
+        log::info!(target: "twitch", "Chat voted for a hazard count={}", self.hazards.count);
+        self.renderer.upload_world(
+            &self.octree,
+            &WorldLights::discover(&self.octree, &self.game.options.theme.theme()),
+            &self.hazards,
+        );
+    }
 
-                // Create an option to choose whether the Y axis is inverted.
-                ui.checkbox(&mut self.game.options.invert_y, "Inverted Y-Axis");
-            });
+    // Publish the game's state to remote control, and change worlds as it commands.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn handle_remote_commands(&mut self) {
+        let Some(remote) = &self.remote else {
+            return;
+        };
+        let commands = remote.update(&remote::Status {
+            seed: self.random.get_seed(),
+            level: self.game.run.level,
+            score: self.game.run.points,
+            run_time: self
+                .game
+                .run
+                .start
+                .map(|start| start.elapsed().as_secs_f32()),
+            paused: self.paused_since.is_some(),
+            position: self.game.camera_position.into(),
+        });
+        for command in commands {
+            let seed = match command {
+                remote::Command::Seed(text) => seed::from_text(&text),
+                remote::Command::Regenerate => random_world_seed(&self.game.options),
+            };
+            log::info!(target: "remote", "Remote control chose seed={seed}");
+            self.game.run = Run::default();
+            self.new_random_world(seed);
+        }
+    }
 
-        // Update the self with the new visibility state.
-        self.overlay.is_options_visible = is_options_visible;
+    // Test the points of the collision hull against the octree. The hull shrinks with the scale of the
+    // surrounding voxels, the same way the camera slows down.
+    fn hull_collides(&mut self, scale: f32) -> bool {
+        let points = self.game.options.collision_hull.points();
+        self.hull_caches
+            .resize_with(points.len(), voxels::TraversalCache::default);
+        let size = COLLISION_HULL_SIZE / scale;
+        points
+            .iter()
+            .zip(&mut self.hull_caches)
+            .any(|(&offset, cache)| {
+                let point = self.game.camera_position
+                    + self.game.camera_quaternion.rotate_vector(offset * size);
+                matches!(
+                    voxels::octree_scale_and_collision_of_point(point, &self.octree, cache),
+                    voxels::Intersection::Collision
+                )
+            })
     }
 
-    // Help window helper.
-    fn help_window(ctx: &Context, is_help_visible: &mut bool) {
-        // Helper enum for creating a grid of controls. Each entry is a row in the grid.
-        enum HelpWindowEntry {
-            Title(&'static str),
-            Item(&'static str, &'static str),
-            Empty(),
+    // Test the camera against the octree, treating the collision hull or a hazard touching it as a collision.
+    fn camera_intersection(&mut self) -> voxels::Intersection {
+        use voxels::Intersection;
+        let intersection = voxels::octree_scale_and_collision_of_point(
+            self.game.camera_position,
+            &self.octree,
+            &mut self.octree_cache,
+        );
+        if let Intersection::Empty(scale) = intersection {
+            let time = self.game_time();
+            if self.hull_collides(scale) || self.hazards.collides(self.game.camera_position, time) {
+                return Intersection::Collision;
+            }
         }
-        use HelpWindowEntry::{Empty, Item, Title};
+        intersection
+    }
 
-        // Create an Egui window that starts closed.
-        egui::Window::new("Help")
-            .default_open(false)
-            .open(is_help_visible)
-            .show(ctx, |ui| {
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    // Describe the controls-help layout.
-                    let controls_list = [
-                        Title("App-Window"),
-                        Item("F11", "Toggle window fullscreen"),
-                        Item(
-                            "ESC",
-                            "If fullscreen, then enter windowed mode. Else, close the application",
-                        ),
-                        #[cfg(all(not(debug_assertions), target_os = "windows"))]
-                        Item(
-                            "ENTER",
-                            "Toggle the visibility of the output command prompt",
-                        ),
-                        Empty(),
-                        Title("Overlay-Window"),
-                        Item("F1", "Toggle showing this Help window"),
-                        Item("o", "Toggle showing the Options window"),
-                        Empty(),
-                        Title("Game"),
-                        Item("F5", "Generate a new random world and reset game"),
-                        Empty(),
-                        Title("Flight"),
-                        Item("UP", "Pitch down"),
-                        Item("DOWN", "Pitch up"),
-                        Item("LEFT", "Roll left"),
-                        Item("RIGHT", "Roll right"),
-                        Item("a", "Yaw left"),
-                        Item("d", "Yaw right"),
-                        Item("SPACE", "Boost"),
-                    ];
+    // Carve the voxel containing a point out of the world, remembering it with generated worlds and updating the
+    // renderer's copy of the octree. Returns whether there was a voxel to carve.
+    fn carve(&mut self, position: Vector3<f32>) -> bool {
+        let Some(carve) = Carve::at(position, &mut self.octree) else {
+            return false;
+        };
+        let parent = carve.parent();
+        self.renderer
+            .update_voxels(parent, &self.octree[parent..=parent]);
+        if self.imported_world.is_none() {
+            self.carvings.record(self.random.get_seed(), carve);
+        }
+        if self.game.options.particles {
+            self.particles.impact(position, self.game.camera_speed);
+        }
+        true
+    }
 
-                    // Grid of controls, showing the buttons and their corresponding actions.
-                    egui::Grid::new("scheme_index_grid").show(ui, |ui| {
-                        for entry in controls_list {
-                            match entry {
-                                Empty() => {}
-                                Item(key, desc) => {
-                                    ui.vertical_centered(|ui| {
-                                        ui.label(egui::RichText::new(key).monospace().strong())
-                                    });
-                                    ui.label(desc);
-                                }
-                                Title(title) => {
-                                    // Include a separator in the first row and the title in the second.
-                                    ui.separator();
-                                    ui.heading(title);
-                                }
-                            }
-                            ui.end_row();
-                        }
-                    });
-                });
-            });
+    // Carve away the voxels that the camera and its collision hull have flown into. Returns whether any were.
+    fn carve_collisions(&mut self) -> bool {
+        let mut carved = self.carve(self.game.camera_position);
+        let intersection = voxels::octree_scale_and_collision_of_point(
+            self.game.camera_position,
+            &self.octree,
+            &mut self.octree_cache,
+        );
+        if let voxels::Intersection::Empty(scale) = intersection {
+            let size = COLLISION_HULL_SIZE / scale;
+            for &offset in self.game.options.collision_hull.points() {
+                let point = self.game.camera_position
+                    + self.game.camera_quaternion.rotate_vector(offset * size);
+                carved |= self.carve(point);
+            }
+        }
+        carved
     }
 
-    // Update the internal GUI state and return an optional command buffer to draw the overlay.
-    fn create_updated_overlay(
-        &mut self,
-        gui: &mut Gui,
-        renderer: &mut VulkanoWindowRenderer,
-    ) -> Option<Arc<SecondaryAutoCommandBuffer>> {
-        // If no window should be shown, then don't draw anything.
-        if !self.overlay.is_options_visible
-            && !self.overlay.is_help_visible
-            && self.game.run.start.is_none()
+    // Fire a projectile ahead of the camera.
+    fn fire(&mut self) {
+        if self.photo.is_some() {
+            return;
+        }
+        self.projectiles.fire(
+            self.game.camera_position,
+            self.game.camera_quaternion,
+            self.game.camera_speed,
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        self.play_effect(Effect::Fire);
+    }
+
+    // React to a projectile striking the world. Voxels hit are carved away when voxels are destructible, and
+    // otherwise marked by sparks. Portals hit are taken as though the camera had flown into them.
+    fn handle_impact(&mut self, impact: Impact) {
+        use voxels::Intersection;
+        match impact.intersection {
+            Intersection::Portal { .. } | Intersection::ReturnPortal => {
+                self.take_portal_intersection(impact.intersection);
+            }
+            Intersection::Collision
+                if self.game.options.destructible_voxels && self.carve(impact.position) => {}
+            Intersection::Collision | Intersection::Empty(_) => {
+                if self.game.options.particles {
+                    self.particles
+                        .impact(impact.position, self.game.camera_speed);
+                }
+            }
+        }
+    }
+
+    // Update state for the player/camera and their run.
+    fn update_player_state(&mut self, delta_time: f32) {
+        use voxels::Intersection;
+
+        // Settle how the run is scored once it starts.
+        if self.game.run.start.is_none() {
+            self.game.run.scoring = self.rules().scoring(self.game.options.difficulty_band);
+        }
+        let mut intersection = self.camera_intersection();
+
+        // Voxels flown into during a run are carved away instead of crashing the camera, when voxels are destructible.
+        if matches!(intersection, Intersection::Collision)
+            && self.game.options.destructible_voxels
+            && self.game.run.start.is_some()
+            && self.carve_collisions()
         {
-            return None;
+            intersection = self.camera_intersection();
         }
 
-        // Update the GUI state.
-        gui.immediate_ui(|gui| {
-            let ctx = gui.context();
+        // Being caught by the pursuer in chase mode ends the run just like a crash.
+        if self
+            .drones
+            .caught(self.game.camera_position, self.game.camera_speed)
+        {
+            intersection = Intersection::Collision;
+        }
+        match intersection {
+            Intersection::Empty(scale) => {
+                const SMOOTHING_INCREASE_FACTOR: f32 = -0.12;
+                const SMOOTHING_DECREASE_FACTOR: f32 = -1.4;
+                const SCALING_FACTOR: f32 = 0.7;
+
+                // Find the portals near enough to have their point values shown.
+                if self.game.options.portal_values && self.rules().scoring {
+                    self.portal_values
+                        .update(self.game.camera_position, scale, &self.octree);
+                } else {
+                    self.portal_values.clear();
+                }
+
+                self.game.burn_fuel(delta_time);
+                let boosting = self.game.is_boosting();
+                if boosting && !self.game.boosting && self.game.run.start.is_some() {
+                    self.game.events.push(RunEvent::BoostStarted);
+                }
+                self.game.boosting = boosting;
+                let boost_factor = if boosting { CAMERA_BOOST_FACTOR } else { 1. };
+                let mut target_speed = self.game.run.camera_speed() / scale.powf(SCALING_FACTOR);
+                if self.game.run.start.is_some() {
+                    target_speed *= self.game.throttle_factor();
+                }
+                target_speed *= self.rules().speed_factor;
+                let previous_position = self.game.camera_position;
+                self.game.open_position = previous_position;
+
+                // The advanced flight model only applies to the player, so the autopilot always flies arcade.
+                let advanced = self.game.options.flight_model == FlightModel::Advanced
+                    && self.game.run.start.is_some();
+                let distance = if advanced {
+                    self.game
+                        .fly_advanced(delta_time, target_speed, boost_factor)
+                } else {
+                    let distance = boost_factor * delta_time * self.game.camera_speed;
+                    self.game.camera_position += self
+                        .game
+                        .camera_quaternion
+                        .rotate_vector(Vector3::new(0., 0., distance));
+                    distance
+                };
+
+                // The wind carries the camera along with it.
+                let time = self.game_time();
+                if self.game.options.wind.is_some() {
+                    self.game.camera_position += Wind::drift_speed(&self.game)
+                        * delta_time
+                        * self.wind.velocity(self.game.camera_position, time);
+                }
+
+                // Stop at the first voxel crossed this frame so that fast movement can't tunnel through it.
+                // The collision or portal is then handled by the next frame's test.
+                if let Some(hit) = voxels::first_hit_along_segment(
+                    previous_position,
+                    self.game.camera_position,
+                    &self.octree,
+                    &mut self.octree_cache,
+                ) {
+                    self.game.camera_position = hit;
+                }
+                // Leave a trail behind the camera.
+                if self.game.options.trail {
+                    self.trail
+                        .record(self.game.camera_position, self.game.camera_speed);
+                }
+
+                // Dust streaks past the camera, more of it the faster the camera flies.
+                if self.game.options.particles {
+                    self.particles.emit_dust(
+                        self.game.camera_position,
+                        self.game.camera_quaternion,
+                        (self.game.camera_position - previous_position)
+                            / delta_time.max(f32::EPSILON),
+                        self.game.camera_speed,
+                        delta_time,
+                    );
+                }
+                if self.game.run.start.is_some() {
+                    self.stats.record_flight(delta_time, distance);
+
+                    // Warn through the controller of obstacles which the camera will reach soon.
+                    if self.game.options.haptics.enabled && self.game.options.haptics.proximity > 0.
+                    {
+                        let warning_distance =
+                            PROXIMITY_WARNING_SECONDS * boost_factor * self.game.camera_speed;
+                        let ahead = self.game.camera_position
+                            + self.game.camera_quaternion.rotate_vector(Vector3::new(
+                                0.,
+                                0.,
+                                warning_distance,
+                            ));
+                        if let Some(hit) = voxels::first_hit_along_segment(
+                            self.game.camera_position,
+                            ahead,
+                            &self.octree,
+                            &mut self.octree_cache,
+                        ) {
+                            let closeness = 1.
+                                - (hit - self.game.camera_position).magnitude()
+                                    / warning_distance.max(f32::EPSILON);
+                            self.game.events.push(RunEvent::ObstacleAhead { closeness });
+                        }
+                    }
+
+                    // Flying through rings gains bonus points.
+                    let collected = self.rings.collect(self.game.camera_position);
+                    if collected > 0 {
+                        self.game.run.points += self.game.run.scoring.ring_points(collected);
+                        #[allow(clippy::cast_precision_loss)]
+                        let fuel = game::RING_FUEL * collected as f32;
+                        self.game.run.refuel(fuel);
+                        log::info!(
+                            target: "run",
+                            "Rings collected count={collected} score={}",
+                            self.game.run.points,
+                        );
+                    }
+                }
+
+                // Use exponential smoothing to make the camera speed change with scale.
+                if !advanced {
+                    let smooth = |factor: f32| 1. - (factor * delta_time).exp();
+                    self.game.camera_speed += if target_speed > self.game.camera_speed {
+                        smooth(SMOOTHING_INCREASE_FACTOR)
+                    } else {
+                        smooth(SMOOTHING_DECREASE_FACTOR)
+                    } * (target_speed - self.game.camera_speed);
+                }
 
-            // Create a window for setting options.
-            self.options_window(&ctx);
+                // The autopilot flies whenever a run isn't in progress, such as the demo before any input.
+                // It steers through actions of its own, turning the camera the same way the player does.
+                let sources: &[Source] = if self.game.run.start.is_some() {
+                    &input::PLAYER_SOURCES
+                } else {
+                    let steering = self.autopilot.steer(
+                        self.game.camera_position,
+                        self.game.camera_quaternion,
+                        self.game.camera_speed,
+                        delta_time,
+                        &self.octree,
+                    );
+                    for (axis, value) in [
+                        (input::FlightAxis::Roll, 0.),
+                        (input::FlightAxis::Pitch, steering.pitch),
+                        (input::FlightAxis::Yaw, steering.yaw),
+                    ] {
+                        self.game.apply_action(Action::Steer {
+                            source: Source::Autopilot,
+                            axis,
+                            value,
+                        });
+                    }
+                    &[Source::Autopilot]
+                };
+                let inputs = self.game.input.steering(sources);
+                self.game.turn_camera(delta_time, inputs);
+
+                // Turbulence gently rocks the camera, more so in stronger wind.
+                if let Some(strength) = self.game.options.wind {
+                    let (roll, pitch, yaw) = self.wind.turbulence(self.game.camera_position, time);
+                    let amount = strength * wind::WIND_TURBULENCE;
+                    self.game
+                        .turn_camera(delta_time, (amount * roll, amount * pitch, amount * yaw));
+                }
+                if !advanced {
+                    self.game.align_velocity();
+                }
+            }
+            Intersection::Collision if self.rules().collision == CollisionRule::TurnAround => {
+                // Flights like zen never crash. The camera backs away from the obstacle and turns around instead.
+                self.game.turn_around();
+                self.trail.clear();
+            }
+            Intersection::Collision => {
+                if self.game.run.start.is_some() {
+                    self.stats.record_crash();
+                    self.game.begin_crash_feedback();
+                    self.game.events.push(RunEvent::Crashed);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    self.call_plugins(Hook::Collision {
+                        score: self.game.run.points,
+                    });
+                }
 
-            // Create a window for describing the controls.
-            Self::help_window(&ctx, &mut self.overlay.is_help_visible);
+                // In practice mode, a crash returns to the world's spawn point without ending the run.
+                if let Some(point) = self.practice_spawn_point() {
+                    self.game.respawn(point);
+                } else {
+                    self.game.run = Run::default();
+                    self.game.reset_camera();
+                    self.rings.reset();
+                }
+                self.drones.reset();
+                self.trail.clear();
+                if self.game.options.particles {
+                    self.particles.burst(
+                        self.game.camera_position,
+                        self.game.camera_quaternion,
+                        self.game.camera_speed,
+                    );
+                }
+            }
+            Intersection::Portal { .. } | Intersection::ReturnPortal => {
+                self.take_portal_intersection(intersection);
+            }
+        }
+    }
 
-            // Optionally, create a window for showing run information.
-            if let Some(start_time) = self.game.run.start {
-                egui::Window::new("Run").show(&ctx, |ui| {
-                    ui.heading(format!("Score: {}", self.game.run.points));
-                    ui.label(format!("Level: {}", self.game.run.level));
-                    ui.label(format!("Time: {:.3}s", start_time.elapsed().as_secs_f32()));
+    // Take the portal of an intersection, scoring it when a run is in progress.
+    fn take_portal_intersection(&mut self, intersection: voxels::Intersection) {
+        use voxels::Intersection;
+        match intersection {
+            Intersection::Portal {
+                class: PortalClass::Wormhole,
+                ..
+            } => self.take_wormhole(),
+            Intersection::Portal {
+                class: PortalClass::Speed,
+                depth,
+                index,
+            } if self.game.run.start.is_some() => {
+                self.game.run.speed_portals += 1;
+                self.game.run.finish_level(game::par_time(self.difficulty));
+                self.game.run.level += 1;
+                self.game.run.refuel(game::PORTAL_FUEL);
+                self.record_portal();
+                self.game.events.push(RunEvent::PortalTaken {
+                    points: 0,
+                    level: self.game.run.level,
+                });
+                log::info!(
+                    target: "run",
+                    "Speed portal taken app_time={:.3}s depth={depth} speed_portals={} level={}",
+                    self.app_start_time.elapsed().as_secs_f32(),
+                    self.game.run.speed_portals,
+                    self.game.run.level,
+                );
+                self.take_portal(depth, index);
+            }
+            Intersection::Portal { depth, index, .. } if self.game.run.start.is_some() => {
+                // Finishing the level under its par time scores a bonus on top of the portal's points.
+                let level_time = self.game.run.finish_level(game::par_time(self.difficulty));
+                let time_bonus = self.game.run.scoring.time_bonus(level_time);
+                let points_gained = self.game.run.scoring.portal_points(depth) + time_bonus;
+                self.game.run.points += points_gained;
+                self.game.run.level += 1;
+                self.game.run.refuel(game::PORTAL_FUEL);
+                self.game.run.deepest_portal = self.game.run.deepest_portal.max(Some(depth));
+                self.record_portal();
+                self.game.events.push(RunEvent::PortalTaken {
+                    points: points_gained,
+                    level: self.game.run.level,
                 });
+
+                // Log the state of the run after taking the portal and gaining points.
+                log::info!(
+                    target: "run",
+                    "Portal taken app_time={:.3}s depth={depth} level_time={:.3}s par={:.1}s time_bonus={time_bonus} points_gained={points_gained} score={} level={}",
+                    self.app_start_time.elapsed().as_secs_f32(),
+                    level_time.time,
+                    level_time.par,
+                    self.game.run.points,
+                    self.game.run.level,
+                );
+
+                // Use the portal taken to seed the RNG for the next world.
+                self.take_portal(depth, index);
             }
-        });
+            Intersection::Portal { depth, index, .. } => {
+                // Portals taken by the autopilot don't score, but still lead to the next world.
+                self.take_portal(depth, index);
+            }
+            Intersection::ReturnPortal => self.take_return_portal(),
+            Intersection::Empty(_) | Intersection::Collision => return,
+        }
 
-        // Return a command buffer to draw the GUI.
-        Some(gui.draw_on_subpass_image(renderer.swapchain_image_size()))
+        // The trail doesn't follow the camera through portals.
+        self.trail.clear();
+
+        // Swirl particles around the camera as it comes out of a portal.
+        if self.game.options.particles {
+            self.particles.swirl(
+                self.game.camera_position,
+                self.game.camera_quaternion,
+                self.game.camera_speed,
+            );
+        }
     }
 
-    // Handle changes in window size.
-    pub fn resize(&mut self, window_manager: &mut VulkanoWindows) {
-        // Notify the window manager to recreate the swapchain next draw.
-        window_manager.get_primary_renderer_mut().unwrap().resize();
+    // Enter the world that the portal at the given depth and index leads to, remembering where this world was
+    // left. Imported worlds can't be generated again, so there is no way back to them.
+    fn take_portal(&mut self, depth: u32, index: u32) {
+        let seed = self.random.get_seed().wrapping_add(u64::from(index));
+        if self.imported_world.is_some() {
+            self.new_random_world(seed);
+            return;
+        }
 
-        // Recreate the pipeline with the new viewport.
-        self.engine.recreate_pipeline(Viewport {
-            offset: [0.; 2],
-            extent: window_manager.get_primary_renderer().unwrap().window_size(),
-            depth_range: 0.0..=1.,
+        // Return a little before the portal, so as not to fall straight back into it.
+        #[allow(clippy::cast_possible_wrap)]
+        let voxel_size = 2_f32.powi(-(depth as i32));
+        let orientation = self.game.camera_quaternion;
+        let position =
+            self.game.camera_position - orientation.rotate_vector(Vector3::unit_z()) * voxel_size;
+        if self.world_stack.len() == MAXIMUM_WORLD_STACK {
+            self.world_stack.remove(0);
+        }
+        self.world_stack.push(PortalExit {
+            seed: self.random.get_seed(),
+            position,
+            orientation,
         });
+        self.enter_world(seed);
     }
-}
 
-fn create_random_world(
-    allocators: &Allocators,
-    pipeline: &Arc<GraphicsPipeline>,
-    random: &mut voxels::RandomOctreeHelper,
-    log_file: &mut LogFile,
-) -> (Arc<PersistentDescriptorSet>, Subbuffer<[VoxelCompact]>) {
-    // Generate a random voxel-octree.
-    let (voxel_octree, stats) = voxels::generate_recursive_voxel_octree(random, 256, 10);
-    log_file.log(
-        format!(
-            "Seed: {:?}, Voxel Count: {:?}, Portal Count: {:?}\n",
-            random.get_seed(),
-            stats.voxel_count,
-            stats.goal_count
-        )
-        .as_str(),
-    );
+    // Play by the rules of a custom mode, starting a new world generated the way it describes.
+    fn choose_custom_mode(&mut self, name: String) {
+        if let Some(mode) = self.custom_modes.get(&name) {
+            mode.generation.apply(&mut self.game.options);
+            log::info!(target: "modes", "Custom mode chosen name={name}");
+            self.game.options.custom_mode = Some(name);
+            self.game.run = Run::default();
+            self.new_random_world(self.random.get_seed());
+        }
+    }
 
-    // Upload the voxel-octree to the GPU.
-    let storage_usage: BufferCreateInfo = BufferCreateInfo {
-        usage: BufferUsage::STORAGE_BUFFER,
-        ..Default::default()
-    };
-    let memory_usage = AllocationCreateInfo {
-        memory_type_filter: MemoryTypeFilter::HOST_SEQUENTIAL_WRITE
-            | MemoryTypeFilter::PREFER_DEVICE,
-        ..Default::default()
-    };
-    let buffer = Buffer::from_iter(
-        allocators.memory.clone(),
-        storage_usage,
-        memory_usage,
-        voxel_octree,
-    )
-    .expect("Failed to create voxel buffer.");
-
-    (
-        // Create a descriptor set for the voxel buffer data.
-        PersistentDescriptorSet::new(
-            &allocators.descriptor_set,
-            pipeline.layout().set_layouts().get(0).unwrap().clone(),
-            [WriteDescriptorSet::buffer(0, buffer.clone())],
-            [],
-        )
-        .expect("Failed to create voxel buffer descriptor set."),
-        buffer,
-    )
-}
+    // The rules of the chosen mode. Timed campaign levels count down their time limit.
+    fn rules(&self) -> ModeRules {
+        let mut rules = self.custom_modes.rules(&self.game.options);
+        if let Some(time_limit) = self.campaign.time_limit() {
+            rules.timer = TimerRule::Countdown;
+            rules.time_limit = time_limit;
+        }
+        rules
+    }
 
-// Simple helper to write logs to an app file and stdout.
-impl LogFile {
-    // Open a log file in the app directory.
-    pub fn default() -> Self {
-        // Get a reasonable path for the log file.
-        let file_path = if let Some(p) = dirs::data_local_dir() {
-            let dir = p.join(path::Path::new("voxel_flight_simulator"));
-            if !dir.exists() {
-                std::fs::create_dir_all(&dir).expect("Failed to create app directory.");
-            }
-            dir.join("log.txt")
-        } else {
-            path::PathBuf::from("voxel_flight_simulator_log.txt")
+    // Start a run in the world of a campaign level, generated with the level's options.
+    fn start_campaign_level(&mut self, level: usize) {
+        let Some(definition) = campaign::LEVELS.get(level) else {
+            return;
         };
+        self.game.options.mode = GameMode::Campaign;
+        self.game.options.custom_mode = None;
+        self.game.options.theme = definition.theme;
+        self.game.options.generator = definition.generator;
+        self.game.options.symmetry = definition.symmetry;
+        self.campaign.start(level);
+        self.game.run = Run::default();
+        self.new_random_world(definition.seed);
+        log::info!(target: "campaign", "Campaign level started level={}", level + 1);
+    }
 
-        // Open the log file.
-        Self(
-            std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(file_path)
-                .expect("Failed to open log file."),
-        )
+    // Count a portal taken towards the lifetime statistics, which modes that don't keep score don't.
+    fn record_portal(&mut self) {
+        if self.rules().scoring {
+            self.stats.record_portal(&self.game.run);
+        }
+    }
+
+    // The spawn point of the current world, when practising.
+    fn practice_spawn_point(&self) -> Option<SpawnPoint> {
+        if self.rules().collision != CollisionRule::Respawn {
+            return None;
+        }
+        self.game.spawn_points.get(self.random.get_seed())
+    }
+
+    // Save where the camera is, and which way it faces, as the spawn point of the current world.
+    fn save_spawn_point(&mut self) {
+        let seed = self.random.get_seed();
+        let point = SpawnPoint::new(self.game.camera_position, self.game.camera_quaternion);
+        self.game.spawn_points.set(seed, point);
+        log::info!(
+            target: "practice",
+            "Spawn point saved seed={seed} position={:?}",
+            self.game.camera_position
+        );
     }
 
-    // Write a message to the log file and stdout.
-    pub fn log(&mut self, msg: &str) {
-        use std::io::Write;
-        print!("{msg}");
-        if let Err(e) = write!(self.0, "{msg}") {
-            eprintln!("Couldn't write to file: {e}");
+    // Move the camera to an open point elsewhere in the world, keeping its heading and speed. If no open point can
+    // be found the camera starts the world again instead.
+    fn take_wormhole(&mut self) {
+        match voxels::wormhole_destination(&mut rand::thread_rng(), &self.octree) {
+            Some(position) => self.game.camera_position = position,
+            None => self.game.reset_camera(),
         }
+        self.autopilot.reset();
+    }
+
+    // Go back to the world most recently left through a portal, facing away from the portal that was taken.
+    fn take_return_portal(&mut self) {
+        let Some(exit) = self.world_stack.pop() else {
+            return;
+        };
+        log::info!(target: "run", "Return portal taken seed={}", exit.seed);
+        self.enter_world(exit.seed);
+        self.game.camera_position = exit.position;
+        self.game.camera_quaternion = exit.orientation * Quaternion::from_angle_y(Deg(180.));
+        self.game.align_velocity();
     }
+
+    // Options window helper.
+    fn options_window(&mut self, ctx: &Context) {
+        // Copy the current visibility state to a temporary variable.
+        // This is needed to avoid a borrow conflict on `app`.
+        let mut is_options_visible = self.overlay.is_options_visible;
+        let lang = self.game.options.language;
+
+        // Create an Egui window that starts closed, where it was last left.
+        egui::Window::new(lang.tr(Text::OptionsTitle))
+            .id(egui::Id::new("options_window"))
+            .default_open(false)
+            .saved_position("options_window", &self.game.options.window_positions)
+            .open(&mut is_options_visible)
+            .show(ctx, |ui| {
+                self.profile_options(ui, lang);
+                ui.separator();
+                self.controls_options(ui, lang);
+                self.gameplay_options(ui, lang);
+                #[cfg(not(target_arch = "wasm32"))]
+                self.audio_options(ui, lang);
+                self.world_options(ui, lang);
+                self.accessibility_options(ui, lang);
+                self.generation_options(ui, lang);
+                self.graphics_options(ui, lang);
+                self.effects_options(ui, lang);
+                self.overlay_options(ui, lang);
+                self.performance_options(ui, lang);
+                self.flight_options(ui, lang);
+                self.display_options(ui, lang);
+                self.speedrun_timer_options(ui, lang);
+                #[cfg(not(target_arch = "wasm32"))]
+                self.network_options(ui, lang);
+                #[cfg(not(target_arch = "wasm32"))]
+                self.streaming_options(ui, lang);
+            });
+
+        // Browsers don't report when the page is closed, so save as soon as the options are closed.
+        #[cfg(target_arch = "wasm32")]
+        if self.overlay.is_options_visible && !is_options_visible {
+            self.save_on_exit();
+        }
+
+        // Update the self with the new visibility state.
+        self.overlay.is_options_visible = is_options_visible;
+    }
+
+    // Options for choosing, adding, exporting, and importing profiles.
+    fn profile_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Choose whose options, statistics, and achievements are in use, or add a profile by name.
+        let default = lang.tr(Text::ProfileDefault);
+        let mut profile = self.profiles.current.clone();
+        egui::ComboBox::new("profile_combo_box", lang.tr(Text::OptionProfile))
+            .selected_text(profile.as_deref().unwrap_or(default))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut profile, None, default);
+                for name in &self.profiles.names {
+                    ui.selectable_value(&mut profile, Some(name.clone()), name);
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.overlay.new_profile)
+                    .hint_text(lang.tr(Text::ProfileNameHint)),
+            );
+            let new_profile = profiles::sanitize(&self.overlay.new_profile);
+            if ui
+                .add_enabled(
+                    new_profile.is_some(),
+                    egui::Button::new(lang.tr(Text::ProfileAdd)),
+                )
+                .clicked()
+            {
+                profile = new_profile;
+                self.overlay.new_profile.clear();
+            }
+        });
+        if profile != self.profiles.current {
+            self.switch_profile(profile);
+        }
+
+        // Bundle every profile's data into a single file, or restore it from one. Browsers can't save files.
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.horizontal(|ui| {
+            if ui.button(lang.tr(Text::ExportData)).clicked() {
+                self.export_data();
+            }
+            if ui.button(lang.tr(Text::ImportData)).clicked() {
+                self.import_data();
+            }
+        });
+    }
+
+    // Options for controllers, H.O.T.A.S. axes, rumble, and boosting.
+    fn controls_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Choose which controller to read when several are connected.
+        let controller_names: Vec<String> = self
+            .game
+            .gilrs
+            .gamepads()
+            .map(|(_, gamepad)| gamepad.name().to_owned())
+            .collect();
+        if controller_names.len() > 1 || self.game.options.controller.is_some() {
+            let any = lang.tr(Text::ControllerAny);
+            egui::ComboBox::new("controller_combo_box", lang.tr(Text::OptionController))
+                .selected_text(self.game.options.controller.as_deref().unwrap_or(any))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.game.options.controller, None, any);
+                    for name in controller_names {
+                        ui.selectable_value(
+                            &mut self.game.options.controller,
+                            Some(name.clone()),
+                            name,
+                        );
+                    }
+                });
+        }
+
+        // Create a toggle for reading inputs as a gamepad or H.O.T.A.S.
+        ui.checkbox(
+            &mut self.game.options.hotas_mode,
+            lang.tr(Text::OptionHotasMode),
+        );
+
+        // Assign the axes of a H.O.T.A.S. to each control, either from a list of the connected axes or by
+        // moving the axis after pressing assign.
+        if self.game.options.hotas_mode {
+            let mut connected_axes = Vec::new();
+            for (_, gamepad) in self.game.gilrs.gamepads() {
+                for (code, data) in gamepad.state().axes() {
+                    let name = match gamepad.axis_or_btn_name(code) {
+                        Some(gilrs::ev::AxisOrBtn::Axis(axis)) => format!("{axis:?}"),
+                        Some(gilrs::ev::AxisOrBtn::Btn(button)) => format!("{button:?}"),
+                        None => format!("#{code}"),
+                    };
+                    connected_axes.push((
+                        code.into_u32(),
+                        format!("{name} ({:+.2}) - {}", data.value(), gamepad.name()),
+                    ));
+                }
+            }
+            ui.collapsing(lang.tr(Text::OptionHotasAxes), |ui| {
+                egui::Grid::new("hotas_axes").show(ui, |ui| {
+                    for control in game::HotasAxes::CONTROLS {
+                        ui.label(lang.tr(match control {
+                            game::HotasControl::Pitch => Text::AxisPitch,
+                            game::HotasControl::Roll => Text::AxisRoll,
+                            game::HotasControl::Yaw => Text::AxisRudder,
+                            game::HotasControl::Throttle => Text::AxisThrottle,
+                        }));
+                        let assigned = self.game.options.hotas_axes.get_mut(control);
+                        let selected = assigned.map_or_else(
+                            || lang.tr(Text::AxisDefault).to_owned(),
+                            |code| {
+                                connected_axes
+                                    .iter()
+                                    .find(|(c, _)| *c == code)
+                                    .map_or_else(|| format!("#{code}"), |(_, name)| name.clone())
+                            },
+                        );
+                        egui::ComboBox::from_id_source(("hotas_axis", control as u8))
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(assigned, None, lang.tr(Text::AxisDefault));
+                                for (code, name) in &connected_axes {
+                                    ui.selectable_value(assigned, Some(*code), name.as_str());
+                                }
+                            });
+                        let listening = self.hotas_listening == Some(control);
+                        if ui
+                            .selectable_label(
+                                listening,
+                                lang.tr(if listening {
+                                    Text::HotasListening
+                                } else {
+                                    Text::HotasAssign
+                                }),
+                            )
+                            .clicked()
+                        {
+                            self.hotas_listening = if listening { None } else { Some(control) };
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.checkbox(
+                    &mut self.game.options.hotas_axes.invert_throttle,
+                    lang.tr(Text::OptionInvertThrottle),
+                );
+            });
+        }
+
+        // Tune the deadzone, sensitivity, and curve of each stick axis while watching the live input.
+        let raw_yaw = self.game.gamepad.raw_yaw();
+        ui.collapsing(lang.tr(Text::OptionGamepadResponse), |ui| {
+            let response = &mut self.game.options.gamepad_response;
+            Self::axis_response_ui(
+                ui,
+                lang.tr(Text::AxisRoll),
+                &mut response.roll,
+                self.game.gamepad.left_stick[0],
+                lang,
+            );
+            Self::axis_response_ui(
+                ui,
+                lang.tr(Text::AxisPitch),
+                &mut response.pitch,
+                self.game.gamepad.left_stick[1],
+                lang,
+            );
+            Self::axis_response_ui(ui, lang.tr(Text::AxisYaw), &mut response.yaw, raw_yaw, lang);
+            if ui.button(lang.tr(Text::ResetDefaults)).clicked() {
+                *response = game::GamepadResponse::default();
+            }
+        });
+
+        // Rumble the controller on crashes, portals, boosting, and obstacles ahead.
+        ui.collapsing(lang.tr(Text::OptionRumble), |ui| {
+            let rumble = &mut self.game.options.haptics;
+            ui.checkbox(&mut rumble.enabled, lang.tr(Text::OptionRumbleEnabled));
+            ui.add_enabled_ui(rumble.enabled, |ui| {
+                for (intensity, text) in [
+                    (&mut rumble.collision, Text::RumbleCollision),
+                    (&mut rumble.portal, Text::RumblePortal),
+                    (&mut rumble.boost, Text::RumbleBoost),
+                    (&mut rumble.proximity, Text::RumbleProximity),
+                ] {
+                    ui.add(
+                        egui::Slider::new(intensity, haptics::INTENSITY_RANGE).text(lang.tr(text)),
+                    );
+                }
+            });
+        });
+
+        // Create an option to either hold or toggle for boost.
+        let mut b = self.game.options.camera_boost != HoldOrToggle::Hold;
+        if ui
+            .checkbox(&mut b, lang.tr(Text::OptionToggleBoost))
+            .changed()
+        {
+            self.game.options.camera_boost = match self.game.options.camera_boost {
+                HoldOrToggle::Hold => HoldOrToggle::Toggle(false),
+                HoldOrToggle::Toggle(_) => HoldOrToggle::Hold,
+            };
+        }
+
+        // Create an option to limit boosting with a fuel meter, refilled by portals and rings.
+        ui.checkbox(
+            &mut self.game.options.fuel_boost,
+            lang.tr(Text::OptionFuelBoost),
+        );
+    }
+
+    // Options for the game mode, carving, drones, and wind.
+    fn gameplay_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Choose whether crashes end the run, return to a saved spawn point, or are avoided altogether in
+        // a relaxed flight. Custom modes from the modes folder follow, and start a new world of their own.
+        let mut chosen_mode = None;
+        ui.horizontal(|ui| {
+            let custom_mode = self
+                .game
+                .options
+                .custom_mode
+                .as_deref()
+                .and_then(|name| self.custom_modes.get(name));
+            let selected_text = custom_mode.map_or_else(
+                || lang.tr(self.game.options.mode.name()).to_owned(),
+                |mode| mode.name.clone(),
+            );
+            egui::ComboBox::new("game_mode_combo_box", lang.tr(Text::OptionGameMode))
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for mode in GameMode::ALL {
+                        let selected = custom_mode.is_none() && self.game.options.mode == mode;
+                        if ui
+                            .selectable_label(selected, lang.tr(mode.name()))
+                            .clicked()
+                        {
+                            self.game.options.mode = mode;
+                            self.game.options.custom_mode = None;
+                            self.overlay.is_campaign_visible |= mode == GameMode::Campaign;
+                        }
+                    }
+                    if !self.custom_modes.modes.is_empty() {
+                        ui.separator();
+                    }
+                    for mode in &self.custom_modes.modes {
+                        let selected = custom_mode.is_some_and(|m| m.name == mode.name);
+                        if ui.selectable_label(selected, &mode.name).clicked() && !selected {
+                            chosen_mode = Some(mode.name.clone());
+                        }
+                    }
+                });
+            let seed = self.random.get_seed();
+            if self.rules().collision == CollisionRule::Respawn
+                && ui
+                    .add_enabled(
+                        self.game.spawn_points.get(seed).is_some(),
+                        egui::Button::new(lang.tr(Text::PracticeClearSpawnPoint)),
+                    )
+                    .clicked()
+            {
+                self.game.spawn_points.remove(seed);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button(lang.tr(Text::OptionOpenModesFolder)).clicked() {
+                if let Err(e) = open_path(&modes::directory()) {
+                    log::warn!(target: "modes", "Failed to open the modes folder error=\"{e}\"");
+                }
+            }
+        });
+        if let Some(name) = chosen_mode {
+            self.choose_custom_mode(name);
+        }
+        if self.game.options.mode != GameMode::Campaign || self.game.options.custom_mode.is_some() {
+            self.campaign.stop();
+        }
+
+        // Carve voxels away by flying into them or firing at them, or restore the world's carved voxels.
+        ui.horizontal(|ui| {
+            ui.checkbox(
+                &mut self.game.options.destructible_voxels,
+                lang.tr(Text::OptionDestructibleVoxels),
+            );
+            let seed = self.random.get_seed();
+            if self.imported_world.is_none()
+                && ui
+                    .add_enabled(
+                        self.carvings.any(seed),
+                        egui::Button::new(lang.tr(Text::OptionRestoreCarvedVoxels)),
+                    )
+                    .clicked()
+            {
+                self.carvings.remove(seed);
+                self.regenerate_world();
+            }
+        });
+
+        // Fly with a wingman drone, or flee a pursuer which ends the run if it catches the camera.
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.game.options.wingman, lang.tr(Text::OptionWingman));
+            ui.checkbox(&mut self.game.options.chase, lang.tr(Text::OptionChase));
+        });
+
+        // Optionally blow the camera around with wind and turbulence, at an adjustable strength.
+        ui.horizontal(|ui| {
+            let mut enabled = self.game.options.wind.is_some();
+            if ui
+                .checkbox(&mut enabled, lang.tr(Text::OptionWind))
+                .changed()
+            {
+                self.game.options.wind = enabled.then_some(wind::DEFAULT_WIND);
+            }
+            if let Some(strength) = &mut self.game.options.wind {
+                ui.add(egui::Slider::new(strength, wind::WIND_RANGE));
+            }
+        });
+    }
+
+    // Options for the volumes of music and sound effects, which browsers don't play.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn audio_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Mix the volumes of the music and sound effects, optionally muting them while the window is in the
+        // background.
+        ui.collapsing(lang.tr(Text::OptionAudio), |ui| {
+            let audio = &mut self.game.options.audio;
+            ui.add(
+                egui::Slider::new(&mut audio.master, mixer::VOLUME_RANGE)
+                    .text(lang.tr(Text::OptionMasterVolume)),
+            );
+            for (volume, default, text) in [
+                (
+                    &mut audio.music,
+                    mixer::DEFAULT_MUSIC_VOLUME,
+                    Text::OptionMusic,
+                ),
+                (
+                    &mut audio.effects,
+                    mixer::DEFAULT_EFFECTS_VOLUME,
+                    Text::OptionSoundEffects,
+                ),
+            ] {
+                ui.horizontal(|ui| {
+                    let mut enabled = volume.is_some();
+                    if ui.checkbox(&mut enabled, lang.tr(text)).changed() {
+                        *volume = enabled.then_some(default);
+                    }
+                    if let Some(volume) = volume {
+                        ui.add(egui::Slider::new(volume, mixer::VOLUME_RANGE));
+                    }
+                });
+            }
+            ui.checkbox(
+                &mut audio.mute_on_focus_loss,
+                lang.tr(Text::OptionMuteOnFocusLoss),
+            );
+            if ui.button(lang.tr(Text::OptionOpenMusicFolder)).clicked() {
+                if let Err(e) = open_path(&audio::music_dir()) {
+                    log::warn!(target: "audio", "Failed to open the music folder error=\"{e}\"");
+                }
+            }
+        });
+    }
+
+    // Options for the world's seed and difficulty, previewing and revisiting worlds, and importing them.
+    fn world_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Allow user to view, edit, and set the world seed, which may be a number or any phrase.
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.overlay.seed_string);
+            if ui.button(lang.tr(Text::OptionSetSeed)).clicked() {
+                let phrase = self.overlay.seed_string.trim().to_owned();
+                self.game.run = Run::default();
+                self.new_random_world(seed::from_text(&phrase));
+                self.overlay.seed_string = phrase;
+            }
+        });
+        if seed::is_phrase(&self.overlay.seed_string) {
+            ui.label(lang.tr_args(
+                Text::OptionSeedNumber,
+                &[&seed::from_text(&self.overlay.seed_string)],
+            ));
+        }
+        ui.label(lang.tr_args(
+            Text::WorldDifficulty,
+            &[
+                &format!("{:.1}", self.difficulty),
+                &lang.tr(DifficultyBand::of(self.difficulty).name()),
+            ],
+        ));
+
+        // Re-roll new random worlds until one falls in the chosen difficulty band.
+        let band_name = self
+            .game
+            .options
+            .difficulty_band
+            .map_or(Text::DifficultyAny, DifficultyBand::name);
+        egui::ComboBox::new(
+            "difficulty_band_combo_box",
+            lang.tr(Text::OptionDifficultyBand),
+        )
+        .selected_text(lang.tr(band_name))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(
+                &mut self.game.options.difficulty_band,
+                None,
+                lang.tr(Text::DifficultyAny),
+            );
+            for band in DifficultyBand::ALL {
+                ui.selectable_value(
+                    &mut self.game.options.difficulty_band,
+                    Some(band),
+                    lang.tr(band.name()),
+                );
+            }
+        });
+
+        // Preview the world of an edited seed, which is only entered once the seed is set.
+        let seed = seed::from_text(&self.overlay.seed_string);
+        if seed != self.random.get_seed() {
+            self.preview.request(seed, &self.game.options);
+            if let Some(texture) = self.preview.texture(ui.ctx()) {
+                ui.image((texture.id(), texture.size_vec2()));
+            }
+        }
+
+        // Return to worlds left by generating new ones.
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    self.history.can_go_back(),
+                    egui::Button::new(lang.tr(Text::OptionPreviousWorld)),
+                )
+                .clicked()
+            {
+                self.step_world_history(false);
+            }
+            if ui
+                .add_enabled(
+                    self.history.can_go_forward(),
+                    egui::Button::new(lang.tr(Text::OptionNextWorld)),
+                )
+                .clicked()
+            {
+                self.step_world_history(true);
+            }
+        });
+
+        // Fly over terrain made from a heightmap instead of a generated world.
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button(lang.tr(Text::OptionImportImage)).clicked() {
+            self.import_image();
+        }
+
+        // Fly through a hand-authored world described by a file.
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button(lang.tr(Text::OptionImportWorld)).clicked() {
+            self.import_world_file();
+        }
+
+        // Fly the tutorial again from its start.
+        if ui.button(lang.tr(Text::OptionReplayTutorial)).clicked() {
+            self.start_tutorial();
+        }
+    }
+
+    // Options for inverting the Y axis, the overlay's language, and colour-blind palettes.
+    fn accessibility_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Create an option to choose whether the Y axis is inverted.
+        ui.checkbox(
+            &mut self.game.options.invert_y,
+            lang.tr(Text::OptionInvertY),
+        );
+
+        // Choose the language of the overlay.
+        egui::ComboBox::new("language_combo_box", lang.tr(Text::OptionLanguage))
+            .selected_text(lang.native_name())
+            .show_ui(ui, |ui| {
+                for language in Language::ALL {
+                    ui.selectable_value(
+                        &mut self.game.options.language,
+                        language,
+                        language.native_name(),
+                    );
+                }
+            });
+
+        // Choose a palette adjusted for colour-blindness.
+        let colorblind_mode = self.game.options.colorblind_mode;
+        egui::ComboBox::new("colorblind_combo_box", lang.tr(Text::OptionColorblindMode))
+            .selected_text(lang.tr(colorblind_mode.name()))
+            .show_ui(ui, |ui| {
+                for mode in ColorblindMode::ALL {
+                    ui.selectable_value(
+                        &mut self.game.options.colorblind_mode,
+                        mode,
+                        lang.tr(mode.name()),
+                    );
+                }
+            });
+        if self.game.options.colorblind_mode != colorblind_mode {
+            self.regenerate_world();
+        }
+    }
+
+    // Options for how worlds are generated, which restart the current world when changed.
+    fn generation_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Choose the theme of generated worlds, which restarts the current world in the new theme.
+        let theme = self.game.options.theme;
+        egui::ComboBox::new("theme_combo_box", lang.tr(Text::OptionTheme))
+            .selected_text(lang.tr(theme.name()))
+            .show_ui(ui, |ui| {
+                for theme in WorldTheme::ALL {
+                    ui.selectable_value(&mut self.game.options.theme, theme, lang.tr(theme.name()));
+                }
+            });
+
+        // Choose how worlds are generated, which also restarts the current world.
+        let generator = self.game.options.generator;
+        egui::ComboBox::new("generator_combo_box", lang.tr(Text::OptionGenerator))
+            .selected_text(lang.tr(generator.name()))
+            .show_ui(ui, |ui| {
+                for generator in WorldGenerator::ALL {
+                    ui.selectable_value(
+                        &mut self.game.options.generator,
+                        generator,
+                        lang.tr(generator.name()),
+                    );
+                }
+            });
+
+        // Choose a symmetry for every voxel of generated worlds, which also restarts the current world.
+        let symmetry = self.game.options.symmetry;
+        egui::ComboBox::new("symmetry_combo_box", lang.tr(Text::OptionSymmetry))
+            .selected_text(lang.tr(symmetry.name()))
+            .show_ui(ui, |ui| {
+                for symmetry in Symmetry::ALL {
+                    ui.selectable_value(
+                        &mut self.game.options.symmetry,
+                        symmetry,
+                        lang.tr(symmetry.name()),
+                    );
+                }
+            });
+        if self.game.options.theme != theme
+            || self.game.options.generator != generator
+            || self.game.options.symmetry != symmetry
+        {
+            self.game.run = Run::default();
+            self.new_random_world(self.random.get_seed());
+        }
+    }
+
+    // Options for the field of view, projection, graphics quality, and stereo output.
+    fn graphics_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Choose the vertical field of view, and whether boosting widens it.
+        ui.add(
+            egui::Slider::new(&mut self.game.options.fov, game::FOV_RANGE)
+                .suffix("°")
+                .text(lang.tr(Text::OptionFov)),
+        );
+        ui.checkbox(
+            &mut self.game.options.dynamic_fov,
+            lang.tr(Text::OptionDynamicFov),
+        );
+
+        // Choose how the view is projected, and optionally narrow it to keep ultrawide screens usable.
+        egui::ComboBox::new("projection_combo_box", lang.tr(Text::OptionProjection))
+            .selected_text(lang.tr(self.game.options.projection.name()))
+            .show_ui(ui, |ui| {
+                for projection in Projection::ALL {
+                    ui.selectable_value(
+                        &mut self.game.options.projection,
+                        projection,
+                        lang.tr(projection.name()),
+                    );
+                }
+            })
+            .response
+            .on_hover_text(lang.tr(Text::ProjectionHint));
+        ui.horizontal(|ui| {
+            let mut enabled = self.game.options.horizontal_fov_cap.is_some();
+            if ui
+                .checkbox(&mut enabled, lang.tr(Text::OptionHorizontalFovCap))
+                .changed()
+            {
+                self.game.options.horizontal_fov_cap =
+                    enabled.then_some(game::DEFAULT_HORIZONTAL_FOV_CAP);
+            }
+            if let Some(cap) = &mut self.game.options.horizontal_fov_cap {
+                ui.add(egui::Slider::new(cap, game::HORIZONTAL_FOV_CAP_RANGE).suffix("°"));
+            }
+        });
+
+        // Choose all of the graphics settings at once from a preset, which shows as custom once any is changed.
+        let settings = &mut self.game.options.graphics;
+        let preset = settings.preset();
+        egui::ComboBox::new(
+            "graphics_preset_combo_box",
+            lang.tr(Text::OptionGraphicsPreset),
+        )
+        .selected_text(lang.tr(preset.map_or(Text::GraphicsCustom, GraphicsPreset::name)))
+        .show_ui(ui, |ui| {
+            for option in GraphicsPreset::ALL {
+                if ui
+                    .selectable_label(preset == Some(option), lang.tr(option.name()))
+                    .clicked()
+                {
+                    *settings = option.settings();
+                }
+            }
+        });
+
+        // Render the world at a fraction of the window's resolution.
+        ui.add(
+            egui::Slider::new(&mut settings.render_scale, graphics::RENDER_SCALE_RANGE)
+                .text(lang.tr(Text::OptionRenderScale)),
+        );
+
+        // Choose how shadows are cast, trading their softness for performance.
+        egui::ComboBox::new(
+            "shadow_quality_combo_box",
+            lang.tr(Text::OptionShadowQuality),
+        )
+        .selected_text(lang.tr(settings.shadow_quality.name()))
+        .show_ui(ui, |ui| {
+            for quality in ShadowQuality::ALL {
+                ui.selectable_value(
+                    &mut settings.shadow_quality,
+                    quality,
+                    lang.tr(quality.name()),
+                );
+            }
+        });
+
+        // Shade crevices and inside corners to give the structures depth.
+        ui.checkbox(
+            &mut settings.ambient_occlusion,
+            lang.tr(Text::OptionAmbientOcclusion),
+        );
+
+        // Limit how many times rays bounce off mirror voxels. Each bounce continues the march and casts another
+        // shadow ray.
+        ui.add(
+            egui::Slider::new(
+                &mut settings.reflection_bounces,
+                graphics::REFLECTION_BOUNCES_RANGE,
+            )
+            .text(lang.tr(Text::OptionReflectionBounces)),
+        )
+        .on_hover_text(lang.tr(Text::ReflectionBouncesCost));
+
+        // Limit how far rays march before the fog hides them, and how finely they resolve nearby voxels.
+        ui.add(
+            egui::Slider::new(&mut settings.fog_distance, graphics::FOG_DISTANCE_RANGE)
+                .text(lang.tr(Text::OptionFogDistance)),
+        )
+        .on_hover_text(lang.tr(Text::FogDistanceHint));
+        ui.add(
+            egui::Slider::new(&mut settings.maximum_depth, graphics::MAXIMUM_DEPTH_RANGE)
+                .text(lang.tr(Text::OptionMaximumDepth)),
+        )
+        .on_hover_text(lang.tr(Text::MaximumDepthHint));
+
+        // Split the view between two eyes for anaglyph glasses or 3D displays, converging at a chosen distance.
+        egui::ComboBox::new("stereo_combo_box", lang.tr(Text::OptionStereo))
+            .selected_text(lang.tr(self.game.options.stereo.name()))
+            .show_ui(ui, |ui| {
+                for mode in StereoMode::ALL {
+                    ui.selectable_value(&mut self.game.options.stereo, mode, lang.tr(mode.name()));
+                }
+            });
+        if self.game.options.stereo != StereoMode::Off {
+            ui.add(
+                egui::Slider::new(
+                    &mut self.game.options.stereo_convergence,
+                    game::STEREO_CONVERGENCE_RANGE,
+                )
+                .text(lang.tr(Text::OptionStereoConvergence)),
+            )
+            .on_hover_text(lang.tr(Text::StereoConvergenceHint));
+        }
+    }
+
+    // Options for the effects drawn over the world, and the minimap.
+    fn effects_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Scale the shake and flash that follow a crash, or turn them off.
+        ui.add(
+            egui::Slider::new(
+                &mut self.game.options.crash_feedback,
+                game::CRASH_FEEDBACK_RANGE,
+            )
+            .text(lang.tr(Text::OptionCrashFeedback)),
+        );
+
+        // Optionally draw speed lines around the edges of the view while boosting.
+        ui.horizontal(|ui| {
+            let mut enabled = self.game.options.speed_lines.is_some();
+            if ui
+                .checkbox(&mut enabled, lang.tr(Text::OptionSpeedLines))
+                .changed()
+            {
+                self.game.options.speed_lines = enabled.then_some(game::DEFAULT_SPEED_LINES);
+            }
+            if let Some(intensity) = &mut self.game.options.speed_lines {
+                ui.add(egui::Slider::new(intensity, game::SPEED_LINES_RANGE));
+            }
+        });
+
+        // Optionally draw dust, sparks after crashes, and swirls after portals.
+        ui.checkbox(
+            &mut self.game.options.particles,
+            lang.tr(Text::OptionParticles),
+        );
+
+        // Optionally leave a trail behind the camera, seen when looking back or in photo mode.
+        ui.checkbox(&mut self.game.options.trail, lang.tr(Text::OptionTrail));
+
+        // Optionally show the world below the camera in a corner of the view.
+        ui.checkbox(&mut self.game.options.minimap, lang.tr(Text::OptionMinimap));
+
+        // Create an option to label nearby portals with the points they are worth.
+        ui.checkbox(
+            &mut self.game.options.portal_values,
+            lang.tr(Text::OptionPortalValues),
+        );
+    }
+
+    // Options for the overlay's scale, look, and windows.
+    fn overlay_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Scale the overlay to suit the display.
+        ui.add(
+            egui::Slider::new(&mut self.game.options.ui_scale, game::UI_SCALE_RANGE)
+                .text(lang.tr(Text::OptionUiScale)),
+        );
+
+        // Choose the look of the overlay, and optionally a colour to highlight selections with.
+        egui::ComboBox::new("overlay_theme_combo_box", lang.tr(Text::OptionOverlayTheme))
+            .selected_text(lang.tr(self.game.options.overlay_theme.name()))
+            .show_ui(ui, |ui| {
+                for theme in OverlayTheme::ALL {
+                    ui.selectable_value(
+                        &mut self.game.options.overlay_theme,
+                        theme,
+                        lang.tr(theme.name()),
+                    );
+                }
+            });
+        ui.horizontal(|ui| {
+            let mut accented = self.game.options.overlay_accent.is_some();
+            if ui
+                .checkbox(&mut accented, lang.tr(Text::OptionAccentColour))
+                .changed()
+            {
+                self.game.options.overlay_accent =
+                    accented.then_some(overlay_theme::DEFAULT_ACCENT);
+            }
+            if let Some(accent) = &mut self.game.options.overlay_accent {
+                ui.color_edit_button_srgb(accent);
+            }
+        });
+
+        // Optionally fade the overlay out while the cursor is still, and return the windows to where they
+        // first open.
+        ui.horizontal(|ui| {
+            let mut auto_hide = self.game.options.overlay_auto_hide.is_some();
+            if ui
+                .checkbox(&mut auto_hide, lang.tr(Text::OptionOverlayAutoHide))
+                .changed()
+            {
+                self.game.options.overlay_auto_hide =
+                    auto_hide.then_some(game::DEFAULT_OVERLAY_AUTO_HIDE);
+            }
+            if let Some(delay) = &mut self.game.options.overlay_auto_hide {
+                ui.add(egui::Slider::new(delay, game::OVERLAY_AUTO_HIDE_RANGE).suffix(" s"));
+            }
+        });
+        if ui.button(lang.tr(Text::ResetWindowLayout)).clicked() {
+            self.overlay.reset_layout = true;
+        }
+    }
+
+    // Options for the frame rate and the cost of rendering each frame.
+    fn performance_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Optionally stop the game clock while the window is in the background.
+        ui.checkbox(
+            &mut self.game.options.pause_on_focus_loss,
+            lang.tr(Text::OptionPauseOnFocusLoss),
+        );
+
+        // Optionally cap the frame rate to reduce GPU load.
+        ui.horizontal(|ui| {
+            let mut limited = self.game.options.fps_limit.is_some();
+            if ui
+                .checkbox(&mut limited, lang.tr(Text::OptionFpsLimit))
+                .changed()
+            {
+                self.game.options.fps_limit = limited.then_some(game::DEFAULT_FPS_LIMIT);
+            }
+            if let Some(fps_limit) = &mut self.game.options.fps_limit {
+                ui.add(egui::Slider::new(fps_limit, game::FPS_LIMIT_RANGE).suffix(" FPS"));
+            }
+        });
+
+        // Optionally lower the resolution to hold a target frame rate.
+        ui.horizontal(|ui| {
+            let mut adaptive = self.game.options.dynamic_resolution.is_some();
+            if ui
+                .checkbox(&mut adaptive, lang.tr(Text::OptionDynamicResolution))
+                .changed()
+            {
+                self.game.options.dynamic_resolution = adaptive.then_some(game::DEFAULT_FPS_LIMIT);
+            }
+            if let Some(target_fps) = &mut self.game.options.dynamic_resolution {
+                ui.add(egui::Slider::new(target_fps, game::FPS_LIMIT_RANGE).suffix(" FPS"));
+            }
+        });
+        if self.game.options.dynamic_resolution.is_some() {
+            ui.label(
+                lang.tr_args(
+                    Text::RenderScale,
+                    &[
+                        &(self.game.options.graphics.render_scale * self.resolution.scale() * 100.)
+                            .round(),
+                    ],
+                ),
+            );
+        }
+
+        // Reuse the previous frame where the same surfaces are still in view, only ray-marching the rest.
+        ui.checkbox(
+            &mut self.game.options.temporal_reprojection,
+            lang.tr(Text::OptionTemporalReprojection),
+        )
+        .on_hover_text(lang.tr(Text::TemporalReprojectionHint));
+
+        // Ray-march half of the pixels each frame and fill in the rest, for integrated GPUs.
+        ui.checkbox(
+            &mut self.game.options.checkerboard_rendering,
+            lang.tr(Text::OptionCheckerboardRendering),
+        )
+        .on_hover_text(lang.tr(Text::CheckerboardRenderingHint));
+    }
+
+    // Options for collision testing and the flight model.
+    fn flight_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Choose which points around the camera are tested for collisions.
+        egui::ComboBox::new(
+            "collision_hull_combo_box",
+            lang.tr(Text::OptionCollisionHull),
+        )
+        .selected_text(lang.tr(self.game.options.collision_hull.name()))
+        .show_ui(ui, |ui| {
+            for hull in CollisionHull::ALL {
+                ui.selectable_value(
+                    &mut self.game.options.collision_hull,
+                    hull,
+                    lang.tr(hull.name()),
+                );
+            }
+        });
+
+        // Choose between arcade flight and flight with momentum, gravity, lift, and stalls.
+        egui::ComboBox::new("flight_model_combo_box", lang.tr(Text::OptionFlightModel))
+            .selected_text(lang.tr(self.game.options.flight_model.name()))
+            .show_ui(ui, |ui| {
+                for model in FlightModel::ALL {
+                    ui.selectable_value(
+                        &mut self.game.options.flight_model,
+                        model,
+                        lang.tr(model.name()),
+                    );
+                }
+            });
+    }
+
+    // Options for debug views, fullscreen, anti-aliasing, the GPU, and recording.
+    fn display_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Show how the world is traversed instead of how it is lit, to diagnose generation and traversal.
+        egui::ComboBox::new("debug_view_combo_box", lang.tr(Text::OptionDebugView))
+            .selected_text(lang.tr(self.debug_view.name()))
+            .show_ui(ui, |ui| {
+                for view in game::DebugView::ALL {
+                    ui.selectable_value(&mut self.debug_view, view, lang.tr(view.name()));
+                }
+            });
+
+        // Choose the monitor to go fullscreen on, between borderless and exclusive fullscreen, and the video mode
+        // of the latter.
+        // Browsers only allow borderless fullscreen.
+        #[cfg(not(target_arch = "wasm32"))]
+        self.fullscreen_options(ui, lang);
+
+        // Choose how many samples are taken of each pixel.
+        egui::ComboBox::new(
+            "multisampling_combo_box",
+            lang.tr(Text::OptionMultisampling),
+        )
+        .selected_text(self.game.options.multisampling.label(lang))
+        .show_ui(ui, |ui| {
+            for multisampling in game::Multisampling::ALL {
+                ui.selectable_value(
+                    &mut self.game.options.multisampling,
+                    multisampling,
+                    multisampling.label(lang),
+                );
+            }
+        });
+
+        // Choose the GPU used from the next launch, when there is more than one.
+        let gpu_names = self.renderer.gpu_names();
+        if gpu_names.len() > 1 {
+            let automatic = lang.tr(Text::GpuAutomatic);
+            egui::ComboBox::new("gpu_combo_box", lang.tr(Text::OptionGpu))
+                .selected_text(self.game.options.gpu.as_deref().unwrap_or(automatic))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.game.options.gpu, None, automatic);
+                    for name in gpu_names {
+                        ui.selectable_value(&mut self.game.options.gpu, Some(name.clone()), name);
+                    }
+                });
+        }
+
+        // Choose the container of recorded videos. Browsers can't record.
+        #[cfg(not(target_arch = "wasm32"))]
+        egui::ComboBox::new("video_format_combo_box", lang.tr(Text::OptionVideoFormat))
+            .selected_text(self.game.options.video_format.name())
+            .show_ui(ui, |ui| {
+                for format in game::VideoFormat::ALL {
+                    ui.selectable_value(&mut self.game.options.video_format, format, format.name());
+                }
+            });
+    }
+
+    // Options for the speedrun timer and its splits.
+    fn speedrun_timer_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Time runs with a split for each level, which can be exported for LiveSplit or sent to a running
+        // LiveSplit Server. Turning the timer off forgets the session's splits.
+        ui.separator();
+        if ui
+            .checkbox(
+                &mut self.game.options.speedrun_timer,
+                lang.tr(Text::OptionSpeedrunTimer),
+            )
+            .changed()
+        {
+            self.speedrun = SpeedrunTimer::default();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.game.options.speedrun_timer {
+            self.speedrun_options(ui, lang);
+        }
+    }
+
+    // Options for checking for updates and staying offline, which browsers don't use.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn network_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Checking for updates is opt-in, and offline mode keeps the game from connecting to the internet.
+        ui.separator();
+        let options = &mut self.game.options;
+        let mut network_changed = ui
+            .add_enabled(
+                !options.offline,
+                egui::Checkbox::new(
+                    &mut options.check_for_updates,
+                    lang.tr(Text::OptionCheckForUpdates),
+                ),
+            )
+            .changed();
+        network_changed |= ui
+            .checkbox(&mut options.offline, lang.tr(Text::OptionOffline))
+            .changed();
+        if network_changed {
+            if options.offline {
+                self.updates = None;
+                self.chat = None;
+            } else if options.check_for_updates && self.updates.is_none() {
+                self.updates = Some(UpdateChecker::start());
+            } else if !options.check_for_updates {
+                self.updates = None;
+            }
+        }
+    }
+
+    // Options for stream files, plugins, remote control, and Twitch chat, which browsers can't use.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn streaming_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        // Write the run to files for streaming software to show. Browsers can't write files.
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.checkbox(
+                &mut self.game.options.stream_files,
+                lang.tr(Text::OptionStreamFiles),
+            );
+            if ui.button(lang.tr(Text::OptionOpenStreamFolder)).clicked() {
+                let directory = stream_files::directory();
+                let opened =
+                    std::fs::create_dir_all(&directory).and_then(|()| open_path(&directory));
+                if let Err(e) = opened {
+                    log::warn!(target: "stream", "Failed to open the stream folder error=\"{e}\"");
+                }
+            }
+        });
+
+        // Run Lua scripts from the plugins folder, loading them again when asked. Browsers can't run plugins.
+        ui.separator();
+        ui.horizontal(|ui| {
+            let toggled = ui
+                .checkbox(
+                    &mut self.game.options.plugins,
+                    lang.tr(Text::OptionPlugins),
+                )
+                .changed();
+            let reload = ui
+                .add_enabled(
+                    self.game.options.plugins,
+                    egui::Button::new(lang.tr(Text::ReloadPlugins)),
+                )
+                .clicked();
+            if toggled || reload {
+                self.plugins = self.game.options.plugins.then(Plugins::load);
+            }
+            if ui.button(lang.tr(Text::OptionOpenPluginsFolder)).clicked() {
+                if let Err(e) = open_path(&plugins::directory()) {
+                    log::warn!(target: "plugins", "Failed to open the plugins folder error=\"{e}\"");
+                }
+            }
+        });
+
+        // Serve the game's state and accept commands over HTTP on the local machine. Browsers can't listen
+        // for connections.
+        ui.separator();
+        let options = &mut self.game.options;
+        let mut enabled = options.remote_control.is_some();
+        let mut port = options.remote_control.unwrap_or(remote::DEFAULT_PORT);
+        let editing = ui
+            .horizontal(|ui| {
+                ui.checkbox(&mut enabled, lang.tr(Text::OptionRemoteControl));
+                let port_edit = ui.add_enabled(
+                    enabled,
+                    egui::DragValue::new(&mut port).prefix(lang.tr(Text::RemoteControlPort)),
+                );
+                port_edit.dragged() || port_edit.has_focus()
+            })
+            .inner;
+        options.remote_control = enabled.then_some(port);
+
+        // Move the server to the chosen port once it is no longer being edited.
+        let serving = self.remote.as_ref().map(|remote| remote.port);
+        if !editing && serving != options.remote_control {
+            self.remote = None;
+            self.remote = options
+                .remote_control
+                .and_then(|port| start_remote_control(port, options));
+        }
+
+        // Allow Twitch chat to vote on the next world. Browsers can't open the chat connection.
+        ui.separator();
+        ui.horizontal(|ui| {
+            let mut chat_enabled = self.chat.is_some();
+            ui.add_enabled_ui(
+                !self.game.options.offline
+                    && (chat_enabled || !self.overlay.twitch_channel.trim().is_empty()),
+                |ui| {
+                    if ui
+                        .checkbox(&mut chat_enabled, lang.tr(Text::TwitchVoting))
+                        .changed()
+                    {
+                        self.chat =
+                            chat_enabled.then(|| ChatListener::new(&self.overlay.twitch_channel));
+                    }
+                },
+            );
+            ui.add_enabled(
+                self.chat.is_none(),
+                egui::TextEdit::singleline(&mut self.overlay.twitch_channel)
+                    .hint_text(lang.tr(Text::TwitchChannelHint)),
+            );
+        });
+        if let Some(chat) = &self.chat {
+            ui.label(match chat.status {
+                twitch::Status::Connecting => {
+                    lang.tr_args(Text::TwitchConnecting, &[&chat.channel])
+                }
+                twitch::Status::Connected => match chat.seconds_until_close() {
+                    Some(seconds) => {
+                        lang.tr_args(Text::TwitchVotesPending, &[&chat.pending_votes(), &seconds])
+                    }
+                    None => lang.tr(Text::TwitchWaiting).to_string(),
+                },
+                twitch::Status::Disconnected => lang.tr(Text::TwitchDisconnected).to_string(),
+            });
+        }
+    }
+
+    // Options for exporting splits and connecting to LiveSplit Server, which browsers can't do.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn speedrun_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        if ui
+            .add_enabled(
+                self.speedrun.has_splits(),
+                egui::Button::new(lang.tr(Text::SpeedrunExportSplits)),
+            )
+            .clicked()
+        {
+            let path = rfd::FileDialog::new()
+                .set_title(lang.tr(Text::SpeedrunExportSplits))
+                .set_file_name("splits.lss")
+                .add_filter("LiveSplit", &["lss"])
+                .save_file();
+            if let Some(path) = path {
+                match std::fs::write(&path, self.speedrun.to_lss(lang)) {
+                    Ok(()) => {
+                        log::info!(target: "speedrun", "Exported splits path=\"{}\"", path.display());
+                    }
+                    Err(e) => log::error!(target: "speedrun", "Failed to export splits: {e}"),
+                }
+            }
+        }
+
+        ui.horizontal(|ui| {
+            let mut connected = self.speedrun.livesplit.is_some();
+            ui.add_enabled_ui(
+                connected || !self.overlay.livesplit_address.trim().is_empty(),
+                |ui| {
+                    if ui
+                        .checkbox(&mut connected, lang.tr(Text::LiveSplitServer))
+                        .changed()
+                    {
+                        self.speedrun.livesplit = connected
+                            .then(|| LiveSplitClient::new(&self.overlay.livesplit_address));
+                    }
+                },
+            );
+            ui.add_enabled(
+                self.speedrun.livesplit.is_none(),
+                egui::TextEdit::singleline(&mut self.overlay.livesplit_address)
+                    .hint_text(lang.tr(Text::LiveSplitAddressHint)),
+            );
+        });
+        if let Some(livesplit) = &self.speedrun.livesplit {
+            ui.label(match livesplit.status {
+                livesplit::Status::Connecting => {
+                    lang.tr_args(Text::LiveSplitConnecting, &[&livesplit.address])
+                }
+                livesplit::Status::Connected => {
+                    lang.tr_args(Text::LiveSplitConnected, &[&livesplit.address])
+                }
+                livesplit::Status::Disconnected => lang.tr(Text::LiveSplitDisconnected).to_string(),
+            });
+        }
+    }
+
+    // Options for how the window fills the screen, applied right away when already fullscreen.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn fullscreen_options(&mut self, ui: &mut egui::Ui, lang: Language) {
+        let window = self.renderer.window();
+        let options = &mut self.game.options;
+        let previous = (options.monitor.clone(), options.fullscreen.clone());
+
+        // List the monitors when there is a choice, or a chosen one to go back to.
+        let monitors = display::monitor_names(window);
+        if monitors.len() > 1 || options.monitor.is_some() {
+            let selected = options
+                .monitor
+                .clone()
+                .unwrap_or_else(|| lang.tr(Text::MonitorCurrent).to_owned());
+            egui::ComboBox::new("monitor_combo_box", lang.tr(Text::OptionMonitor))
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut options.monitor, None, lang.tr(Text::MonitorCurrent));
+                    for name in monitors {
+                        let label = name.clone();
+                        ui.selectable_value(&mut options.monitor, Some(name), label);
+                    }
+                });
+        }
+
+        let mut exclusive = matches!(options.fullscreen, FullscreenMode::Exclusive(_));
+        let exclusive_changed = ui
+            .checkbox(&mut exclusive, lang.tr(Text::OptionExclusiveFullscreen))
+            .changed();
+        if exclusive_changed || (exclusive && options.monitor != previous.0) {
+            // Start from the best mode of the chosen monitor.
+            options.fullscreen = if exclusive {
+                display::display_modes(window, options.monitor.as_deref())
+                    .into_iter()
+                    .next()
+                    .map_or(FullscreenMode::Borderless, FullscreenMode::Exclusive)
+            } else {
+                FullscreenMode::Borderless
+            };
+        }
+
+        if let FullscreenMode::Exclusive(mode) = &mut options.fullscreen {
+            let modes = display::display_modes(window, options.monitor.as_deref());
+            egui::ComboBox::new("video_mode_combo_box", lang.tr(Text::OptionVideoMode))
+                .selected_text(mode.label())
+                .show_ui(ui, |ui| {
+                    for option in modes {
+                        let label = option.label();
+                        ui.selectable_value(mode, option, label);
+                    }
+                });
+        }
+
+        // Switch to the new monitor or mode straight away if the window is already fullscreen.
+        if (options.monitor.clone(), options.fullscreen.clone()) != previous
+            && window.fullscreen().is_some()
+        {
+            window.set_fullscreen(Some(display::fullscreen(
+                window,
+                options.monitor.as_deref(),
+                &options.fullscreen,
+            )));
+        }
+    }
+
+    // Draw the response curve of one gamepad axis beside sliders to edit it.
+    // The dot marks where the stick currently is along the curve.
+    fn axis_response_ui(
+        ui: &mut egui::Ui,
+        name: &str,
+        response: &mut game::AxisResponse,
+        raw: f32,
+        lang: Language,
+    ) {
+        const PLOT_SIZE: f32 = 96.;
+        const CURVE_SEGMENTS: usize = 48;
+        ui.label(name);
+        ui.horizontal(|ui| {
+            let (rect, _) =
+                ui.allocate_exact_size(egui::Vec2::splat(PLOT_SIZE), egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            let visuals = ui.visuals();
+            painter.rect_filled(rect, 2., visuals.extreme_bg_color);
+            let axis_stroke = egui::Stroke::new(1., visuals.weak_text_color());
+            painter.line_segment([rect.center_top(), rect.center_bottom()], axis_stroke);
+            painter.line_segment([rect.left_center(), rect.right_center()], axis_stroke);
+
+            // Map an input and output pair in [-1, 1] onto the plot, with positive output upward.
+            let to_screen =
+                |x: f32, y: f32| rect.center() + egui::vec2(x, -y) * (0.5 * PLOT_SIZE - 2.);
+            #[allow(clippy::cast_precision_loss)]
+            let curve = (0..=CURVE_SEGMENTS)
+                .map(|i| {
+                    let x = 2. * i as f32 / CURVE_SEGMENTS as f32 - 1.;
+                    to_screen(x, response.apply(x))
+                })
+                .collect();
+            painter.add(egui::Shape::line(
+                curve,
+                egui::Stroke::new(1.5, visuals.text_color()),
+            ));
+            painter.circle_filled(
+                to_screen(raw.clamp(-1., 1.), response.apply(raw)),
+                3.5,
+                visuals.selection.bg_fill,
+            );
+
+            ui.vertical(|ui| {
+                ui.add(
+                    egui::Slider::new(&mut response.deadzone, game::DEADZONE_RANGE)
+                        .text(lang.tr(Text::OptionDeadzone)),
+                );
+                ui.add(
+                    egui::Slider::new(&mut response.sensitivity, game::SENSITIVITY_RANGE)
+                        .text(lang.tr(Text::OptionSensitivity)),
+                );
+                ui.add(
+                    egui::Slider::new(&mut response.exponent, game::RESPONSE_EXPONENT_RANGE)
+                        .text(lang.tr(Text::OptionCurve)),
+                );
+            });
+        });
+    }
+
+    // Help window helper.
+    fn help_window(
+        ctx: &Context,
+        is_help_visible: &mut bool,
+        key_labels: &KeyLabels,
+        scoring: Option<&ScoringRules>,
+        window_positions: &layout::WindowPositions,
+        lang: Language,
+    ) {
+        // Helper enum for creating a grid of controls. Each entry is a row in the grid.
+        enum HelpWindowEntry<'a> {
+            Title(Text),
+            Item(&'a str, Text),
+            Empty(),
+        }
+        use HelpWindowEntry::{Empty, Item, Title};
+
+        // Create an Egui window that starts closed, where it was last left.
+        egui::Window::new(lang.tr(Text::HelpTitle))
+            .id(egui::Id::new("help_window"))
+            .default_open(false)
+            .saved_position("help_window", window_positions)
+            .open(is_help_visible)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    // Describe the controls-help layout.
+                    let controls_list = [
+                        Title(Text::HelpAppWindow),
+                        Item("F11", Text::HelpToggleFullscreen),
+                        Item("ESC", Text::HelpEscape),
+                        #[cfg(not(target_arch = "wasm32"))]
+                        Item("m", Text::HelpToggleMap),
+                        #[cfg(all(not(debug_assertions), target_os = "windows"))]
+                        Item("ENTER", Text::HelpToggleConsole),
+                        Empty(),
+                        Title(Text::HelpOverlayWindow),
+                        Item("F1", Text::HelpToggleHelp),
+                        Item("F2", Text::HelpToggleStats),
+                        Item("o", Text::HelpToggleOptions),
+                        Item("c", Text::HelpToggleCameraPath),
+                        Item("i", Text::HelpToggleInspector),
+                        Empty(),
+                        Title(Text::HelpGame),
+                        Item("F3", Text::HelpPreviousWorld),
+                        Item("F4", Text::HelpNextWorld),
+                        Item("F5", Text::HelpNewWorld),
+                        Item("F6", Text::HelpTogglePhotoMode),
+                        Item("h", Text::HelpHidePhotoPanel),
+                        #[cfg(not(target_arch = "wasm32"))]
+                        Item("F7", Text::HelpCaptureScreenshot),
+                        #[cfg(not(target_arch = "wasm32"))]
+                        Item("F8", Text::HelpCapturePanorama),
+                        #[cfg(not(target_arch = "wasm32"))]
+                        Item("F9", Text::HelpToggleRecording),
+                        Item("F10", Text::HelpToggleSpectator),
+                        Item("p", Text::HelpToggleAutopilot),
+                        Item("k", Text::HelpAddKeyframe),
+                        Item("v", Text::HelpPlayCameraPath),
+                        Item("n", Text::HelpSaveSpawnPoint),
+                        Item("r", Text::HelpRespawn),
+                        Item("f", Text::HelpFire),
+                        Item(".", Text::HelpNextSong),
+                        Item(",", Text::HelpPreviousSong),
+                        Empty(),
+                        Title(Text::HelpFlight),
+                        Item("UP", Text::HelpPitchDown),
+                        Item("DOWN", Text::HelpPitchUp),
+                        Item("LEFT", Text::HelpRollLeft),
+                        Item("RIGHT", Text::HelpRollRight),
+                        Item(key_labels.label(PositionalKey::YawLeft), Text::HelpYawLeft),
+                        Item(
+                            key_labels.label(PositionalKey::YawRight),
+                            Text::HelpYawRight,
+                        ),
+                        Item("SPACE", Text::HelpBoost),
+                    ];
+
+                    // Grid of controls, showing the buttons and their corresponding actions.
+                    egui::Grid::new("scheme_index_grid").show(ui, |ui| {
+                        for entry in controls_list {
+                            match entry {
+                                Empty() => {}
+                                Item(key, desc) => {
+                                    ui.vertical_centered(|ui| {
+                                        ui.label(egui::RichText::new(key).monospace().strong())
+                                    });
+                                    ui.label(lang.tr(desc));
+                                }
+                                Title(title) => {
+                                    // Include a separator in the first row and the title in the second.
+                                    ui.separator();
+                                    ui.heading(lang.tr(title));
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                    // Describe how the run is scored, which changes with the mode and difficulty.
+                    ui.separator();
+                    ui.heading(lang.tr(Text::HelpScoring));
+                    let Some(scoring) = scoring else {
+                        ui.label(lang.tr(Text::HelpScoringOff));
+                        return;
+                    };
+                    ui.label(lang.tr_args(
+                        Text::HelpScoringDepth,
+                        &[
+                            &scoring.minimum_depth,
+                            &scoring.points_per_depth,
+                            &scoring.points_per_depth,
+                        ],
+                    ));
+                    if scoring.portal_bonus > 0 {
+                        ui.label(
+                            lang.tr_args(Text::HelpScoringPortalBonus, &[&scoring.portal_bonus]),
+                        );
+                    }
+                    if scoring.deepest_bonus > 0 {
+                        ui.label(lang.tr_args(
+                            Text::HelpScoringDeepestBonus,
+                            &[&voxels::MAXIMUM_GOAL_DEPTH, &scoring.deepest_bonus],
+                        ));
+                    }
+                    ui.label(lang.tr_args(Text::HelpScoringRings, &[&scoring.ring_points]));
+                    if scoring.par_bonus_seconds > 0. {
+                        ui.label(lang.tr_args(Text::HelpScoringPar, &[&scoring.par_bonus_seconds]));
+                    }
+                    if scoring.multiplier != 1 {
+                        ui.label(lang.tr_args(Text::HelpScoringMultiplier, &[&scoring.multiplier]));
+                    }
+                });
+            });
+    }
+
+    // Statistics window helper.
+    fn stats_window(
+        ctx: &Context,
+        stats: &Stats,
+        achievements: &Achievements,
+        is_stats_visible: &mut bool,
+        lang: Language,
+    ) {
+        egui::Window::new(lang.tr(Text::StatsTitle))
+            .id(egui::Id::new("stats_window"))
+            .open(is_stats_visible)
+            .show(ctx, |ui| {
+                egui::Grid::new("stats_grid").show(ui, |ui| {
+                    let rows = [
+                        (
+                            Text::StatsFlightTime,
+                            stats::format_duration(stats.flight_time),
+                        ),
+                        (
+                            Text::StatsDistanceFlown,
+                            format!("{:.2}", stats.distance_flown),
+                        ),
+                        (Text::StatsCrashes, stats.crashes.to_string()),
+                        (Text::StatsPortalsTaken, stats.portals_taken.to_string()),
+                        (Text::StatsBestScore, stats.best_score.to_string()),
+                        (Text::StatsFurthestLevel, stats.furthest_level.to_string()),
+                        (Text::StatsCampaignLevels, stats.campaign_levels.to_string()),
+                    ];
+                    for (name, value) in rows {
+                        ui.label(lang.tr(name));
+                        ui.label(egui::RichText::new(value).monospace());
+                        ui.end_row();
+                    }
+                });
+
+                ui.separator();
+                ui.heading(lang.tr(Text::StatsAchievements));
+                achievements.show_list(ui, lang);
+            });
+    }
+
+    // Camera path window helper.
+    fn camera_path_window(&mut self, ctx: &Context) {
+        let mut is_camera_path_visible = self.overlay.is_camera_path_visible;
+        let lang = self.game.options.language;
+        egui::Window::new(lang.tr(Text::CameraPathTitle))
+            .id(egui::Id::new("camera_path_window"))
+            .open(&mut is_camera_path_visible)
+            .show(ctx, |ui| {
+                ui.label(lang.tr_args(
+                    Text::CameraPathSummary,
+                    &[
+                        &self.camera_path.keyframes.len(),
+                        &format!("{:.1}", self.camera_path.total_duration()),
+                    ],
+                ));
+                if let Some(seed) = self.camera_path.seed {
+                    if seed != self.random.get_seed() && !self.camera_path.keyframes.is_empty() {
+                        ui.label(lang.tr_args(Text::CameraPathOtherWorld, &[&seed]));
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button(lang.tr(Text::CameraPathAddKeyframe)).clicked() {
+                        self.camera_path.add_keyframe(
+                            self.game.camera_position,
+                            self.game.camera_quaternion,
+                            self.random.get_seed(),
+                        );
+                    }
+                    let play = if self.camera_path.is_playing() {
+                        Text::CameraPathStop
+                    } else {
+                        Text::CameraPathPlay
+                    };
+                    if ui
+                        .add_enabled(
+                            self.camera_path.can_play(),
+                            egui::Button::new(lang.tr(play)),
+                        )
+                        .clicked()
+                    {
+                        self.toggle_camera_path_playback();
+                    }
+                    if ui.button(lang.tr(Text::CameraPathClear)).clicked() {
+                        self.camera_path.stop();
+                        self.camera_path.keyframes.clear();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button(lang.tr(Text::CameraPathExport)).clicked() {
+                        self.camera_path.save();
+                    }
+                    if ui.button(lang.tr(Text::CameraPathImport)).clicked() {
+                        self.camera_path = CameraPath::load();
+                    }
+                });
+                ui.checkbox(&mut self.camera_path.looping, lang.tr(Text::CameraPathLoop));
+                ui.checkbox(
+                    &mut self.camera_path.hide_overlay,
+                    lang.tr(Text::CameraPathHideOverlay),
+                );
+
+                // List the keyframes with the time taken to reach the next, except for the last keyframe.
+                ui.separator();
+                let mut removed = None;
+                let last = self.camera_path.keyframes.len().saturating_sub(1);
+                egui::ScrollArea::vertical()
+                    .max_height(200.)
+                    .show(ui, |ui| {
+                        egui::Grid::new("keyframe_grid").show(ui, |ui| {
+                            for (i, keyframe) in self.camera_path.keyframes.iter_mut().enumerate() {
+                                ui.label(lang.tr_args(Text::CameraPathKeyframe, &[&(i + 1)]));
+                                if i < last {
+                                    ui.add(
+                                        egui::DragValue::new(&mut keyframe.duration)
+                                            .clamp_range(cinematic::KEYFRAME_DURATION_RANGE)
+                                            .speed(0.05),
+                                    );
+                                } else {
+                                    ui.label("-");
+                                }
+                                if ui.small_button("x").clicked() {
+                                    removed = Some(i);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                if let Some(i) = removed {
+                    self.camera_path.stop();
+                    self.camera_path.keyframes.remove(i);
+                }
+            });
+        self.overlay.is_camera_path_visible = is_camera_path_visible;
+    }
+
+    // Photo mode window helper.
+    fn photo_window(&mut self, ctx: &Context) {
+        let Some(photo) = &mut self.photo else {
+            return;
+        };
+        let lang = self.game.options.language;
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut capture = false;
+        egui::Window::new(lang.tr(Text::PhotoTitle))
+            .id(egui::Id::new("photo_window"))
+            .show(ctx, |ui| {
+                ui.label(lang.tr(Text::PhotoHint));
+                ui.add(
+                    egui::Slider::new(&mut photo.exposure, photo::EXPOSURE_RANGE)
+                        .text(lang.tr(Text::PhotoExposure)),
+                );
+                ui.add(
+                    egui::Slider::new(&mut photo.aperture, photo::APERTURE_RANGE)
+                        .text(lang.tr(Text::PhotoAperture)),
+                );
+                ui.add_enabled(
+                    photo.aperture > 0.,
+                    egui::Slider::new(&mut photo.focus_distance, photo::FOCUS_DISTANCE_RANGE)
+                        .logarithmic(true)
+                        .text(lang.tr(Text::PhotoFocusDistance)),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button(lang.tr(Text::PhotoAutofocus)).clicked() {
+                        photo.autofocus(&self.game, &self.octree);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui.button(lang.tr(Text::PhotoCapture)).clicked() {
+                        capture = true;
+                    }
+                });
+            });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if capture {
+            self.capture_screenshot();
+        }
+    }
+
+    // World inspection window helper, describing the voxel last clicked on.
+    fn inspector_window(&mut self, ctx: &Context) {
+        let Some(inspector) = &self.inspector else {
+            return;
+        };
+        let lang = self.game.options.language;
+        let mut is_open = true;
+        egui::Window::new(lang.tr(Text::InspectorTitle))
+            .id(egui::Id::new("inspector_window"))
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                ui.label(lang.tr(Text::InspectorHint));
+                let Some(inspection) = &inspector.inspected else {
+                    ui.label(lang.tr(Text::InspectorNothingHit));
+                    return;
+                };
+                let voxel = &inspection.voxel;
+                let type_name = match voxel.flags {
+                    0 => Text::VoxelComplex,
+                    1 => Text::VoxelColour,
+                    2 => Text::VoxelPortal,
+                    3 => Text::VoxelMirror,
+                    4 => Text::VoxelReturnPortal,
+                    5 => Text::VoxelSpeedPortal,
+                    6 => Text::VoxelWormhole,
+                    _ => Text::VoxelUnknown,
+                };
+                egui::Grid::new("inspector_grid").show(ui, |ui| {
+                    let rows = [
+                        (Text::InspectorIndex, inspection.hit.index.to_string()),
+                        (
+                            Text::InspectorFlags,
+                            format!("{} ({})", voxel.flags, lang.tr(type_name)),
+                        ),
+                        (Text::InspectorDepth, inspection.hit.depth.to_string()),
+                        (
+                            Text::InspectorDistance,
+                            format!("{:.4}", inspection.hit.distance),
+                        ),
+                        (
+                            Text::InspectorNormal,
+                            format!(
+                                "{:.2}, {:.2}, {:.2}",
+                                inspection.hit.normal.x,
+                                inspection.hit.normal.y,
+                                inspection.hit.normal.z
+                            ),
+                        ),
+                    ];
+                    for (name, value) in rows {
+                        ui.label(lang.tr(name));
+                        ui.label(egui::RichText::new(value).monospace());
+                        ui.end_row();
+                    }
+
+                    let [r, g, b, a] = voxel.average_colour;
+                    ui.label(lang.tr(Text::InspectorColour));
+                    ui.horizontal(|ui| {
+                        egui::color_picker::show_color(
+                            ui,
+                            egui::Rgba::from_rgba_unmultiplied(r, g, b, a),
+                            egui::Vec2::splat(ui.spacing().interact_size.y),
+                        );
+                        ui.label(
+                            egui::RichText::new(format!("{r:.3}, {g:.3}, {b:.3}, {a:.3}"))
+                                .monospace(),
+                        );
+                    });
+                    ui.end_row();
+                });
+            });
+
+        if !is_open {
+            self.inspector = None;
+        }
+    }
+
+    // Crash report window helper.
+    fn crash_report_window(
+        ctx: &Context,
+        pending_crash_report: &mut Option<path::PathBuf>,
+        lang: Language,
+    ) {
+        let Some(report_path) = pending_crash_report.as_deref() else {
+            return;
+        };
+
+        let mut dismissed = false;
+        egui::Window::new(lang.tr(Text::CrashTitle))
+            .id(egui::Id::new("crash_report_window"))
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(lang.tr(Text::CrashMessage));
+                ui.monospace(report_path.display().to_string());
+                ui.horizontal(|ui| {
+                    if ui.button(lang.tr(Text::CrashOpenReport)).clicked() {
+                        if let Err(e) = open_path(report_path) {
+                            log::error!("Failed to open crash report: {e}");
+                        }
+                        dismissed = true;
+                    }
+                    if ui.button(lang.tr(Text::CrashDismiss)).clicked() {
+                        dismissed = true;
+                    }
+                });
+            });
+
+        if dismissed {
+            *pending_crash_report = None;
+        }
+    }
+
+    // Minimap helper, drawn in the bottom-right corner under any windows. Its texture is rendered at the size
+    // it is shown.
+    fn minimap(&mut self, ctx: &Context) {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let size = (MINIMAP_SIZE * ctx.pixels_per_point()).round() as u32;
+        let Some(texture) = self.renderer.minimap(size) else {
+            return;
+        };
+        egui::Area::new(egui::Id::new("minimap"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-MINIMAP_MARGIN; 2])
+            .order(egui::Order::Background)
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.image((texture, egui::Vec2::splat(MINIMAP_SIZE)));
+            });
+    }
+
+    // The visuals of the chosen overlay theme and accent colour.
+    fn overlay_visuals(&self) -> egui::Visuals {
+        self.game
+            .options
+            .overlay_theme
+            .visuals(self.game.options.overlay_accent)
+    }
+
+    // Update the internal GUI state so that the overlay is drawn with the next frame.
+    fn update_overlay(&mut self) {
+        // If no window should be shown, then don't draw anything.
+        // The overlay can be hidden while a camera path plays so that it doesn't appear in recordings.
+        if self.camera_path.is_playing() && self.camera_path.hide_overlay {
+            return;
+        }
+
+        // Photo mode only shows its own window, which can also be hidden to see the whole shot.
+        if let Some(photo) = &self.photo {
+            if photo.is_panel_visible {
+                let ctx = self.renderer.begin_gui();
+                if !ctx.is_using_pointer() {
+                    ctx.set_zoom_factor(self.game.options.ui_scale);
+                }
+                layout::fade(&ctx, true, self.overlay_visuals());
+                self.photo_window(&ctx);
+            }
+            return;
+        }
+
+        // Spectator mode only marks where the plane is, keeping the view clear for recording.
+        if let Some(spectator) = &self.spectator {
+            let ctx = self.renderer.begin_gui();
+            spectator.draw_marker(&ctx, &self.game, &self.octree);
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let song_toast = self.audio.as_ref().is_some_and(Audio::has_toast);
+        #[cfg(target_arch = "wasm32")]
+        let song_toast = false;
+        #[cfg(not(target_arch = "wasm32"))]
+        let update_banner = self.updates.as_mut().is_some_and(|updates| {
+            updates.update();
+            updates.is_visible()
+        });
+        #[cfg(target_arch = "wasm32")]
+        let update_banner = false;
+        #[cfg(not(target_arch = "wasm32"))]
+        let plugin_hud = self.plugins.as_ref().is_some_and(Plugins::has_hud);
+        #[cfg(target_arch = "wasm32")]
+        let plugin_hud = false;
+        if !self.overlay.is_options_visible
+            && !self.overlay.is_camera_path_visible
+            && !self.overlay.is_help_visible
+            && !self.overlay.is_stats_visible
+            && !self.overlay.is_campaign_visible
+            && !self.achievements.has_toasts()
+            && self.overlay.pending_crash_report.is_none()
+            && self.game.run.start.is_none()
+            && !self.game.options.minimap
+            && self.inspector.is_none()
+            && !self.rings.any_remaining()
+            && self.game.options.wind.is_none()
+            && self.score_popups.is_empty()
+            && self.portal_values.is_empty()
+            && self.projectiles.is_empty()
+            && self.drones.is_empty()
+            && self.controller_toasts.is_empty()
+            && !song_toast
+            && !update_banner
+            && !plugin_hud
+            && !self.touch.is_visible()
+            && self.tutorial.is_none()
+        {
+            return;
+        }
+
+        // Update the GUI state.
+        let ctx = self.renderer.begin_gui();
+        let lang = self.game.options.language;
+
+        // Apply the UI scale, waiting until any drag ends so that the slider doesn't move under the cursor.
+        if !ctx.is_using_pointer() {
+            ctx.set_zoom_factor(self.game.options.ui_scale);
+        }
+
+        // Return the windows to where they first open before any are drawn, when asked to in the options.
+        if std::mem::take(&mut self.overlay.reset_layout) {
+            layout::reset(&ctx, &mut self.game.options.window_positions);
+        }
+
+        // Show the virtual stick and boost button once the screen has been touched.
+        self.touch.draw(&ctx, lang);
+
+        // Prompt for the next control to learn, even while the rest of the overlay is hidden. Skipping the
+        // tutorial leaves for a new world.
+        let skip_tutorial = self
+            .tutorial
+            .as_ref()
+            .is_some_and(|tutorial| tutorial.draw(&ctx, &self.key_labels, lang));
+        if skip_tutorial {
+            self.finish_tutorial();
+            self.new_random_world(random_world_seed(&self.game.options));
+        }
+
+        // Fade the rest of the overlay out once the cursor has been still for long enough, if the options ask to.
+        let shown =
+            self.game.options.overlay_auto_hide.is_none_or(|delay| {
+                self.overlay.last_cursor_movement.elapsed().as_secs_f32() < delay
+            });
+
+        // Show the tracers of the projectiles in flight and the drones, which are part of the flight rather than the
+        // overlay.
+        let time = self.game_time();
+        self.projectiles.draw(&ctx, &self.game, time);
+        self.drones.draw(&ctx, &self.game, time, &self.octree);
+
+        let visuals = self.overlay_visuals();
+        if layout::fade(&ctx, shown, visuals) <= 0. {
+            return;
+        }
+
+        // Show the world below the camera from above.
+        if self.game.options.minimap {
+            self.minimap(&ctx);
+        }
+
+        // Show the rings left to collect. Modes that don't keep score hide everything to do with scoring.
+        let rules = self.rules();
+        if rules.scoring {
+            self.rings.draw(
+                &ctx,
+                self.game.camera_position,
+                self.game.shaken_camera_quaternion(time),
+                self.game.lens(ctx.screen_rect().aspect_ratio()),
+                &self.octree,
+            );
+        }
+
+        // Show what the portals near the camera are worth.
+        if rules.scoring {
+            self.portal_values.draw(
+                &ctx,
+                self.game.camera_position,
+                self.game.shaken_camera_quaternion(time),
+                self.game.lens(ctx.screen_rect().aspect_ratio()),
+                &self.octree,
+                &self.game.run.scoring,
+            );
+        }
+
+        // Show the particles drifting with the wind.
+        self.wind.draw_particles(&ctx, &self.game, time);
+
+        // Show the points and level gained by recent portals.
+        self.score_popups.draw(&ctx, lang);
+
+        // Show the speedrun timer and the last split.
+        if self.game.options.speedrun_timer && rules.scoring {
+            self.speedrun.draw(&ctx, &self.game.run, lang);
+        }
+
+        // Create a window for setting options.
+        self.options_window(&ctx);
+
+        // Create a window for editing the camera path.
+        self.camera_path_window(&ctx);
+
+        // Create a window for describing the controls.
+        Self::help_window(
+            &ctx,
+            &mut self.overlay.is_help_visible,
+            &self.key_labels,
+            rules.scoring.then_some(&self.game.run.scoring),
+            &self.game.options.window_positions,
+            lang,
+        );
+
+        // Create a window for choosing campaign levels.
+        let mut is_campaign_visible = self.overlay.is_campaign_visible;
+        let level = self
+            .campaign
+            .show_window(&ctx, &mut is_campaign_visible, &self.stats, lang);
+        self.overlay.is_campaign_visible = is_campaign_visible;
+        if let Some(level) = level {
+            self.start_campaign_level(level);
+        }
+
+        // Create a window for lifetime statistics.
+        Self::stats_window(
+            &ctx,
+            &self.stats,
+            &self.achievements,
+            &mut self.overlay.is_stats_visible,
+            lang,
+        );
+
+        // Describe the voxel last clicked on while inspecting the world.
+        self.inspector_window(&ctx);
+
+        // Notify the player of newly unlocked achievements.
+        self.achievements.show_toasts(&ctx, lang);
+
+        // Notify the player of controllers being connected or disconnected.
+        self.controller_toasts.show(&ctx, lang);
+
+        // Name each new song played from the music folder.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(audio) = &self.audio {
+            audio.show_toast(&ctx, lang);
+        }
+
+        // Show any text plugins have put on the HUD.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(plugins) = &self.plugins {
+            plugins.show_hud(&ctx);
+        }
+
+        // Offer a newer release when one was found.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(updates) = &mut self.updates {
+            updates.show(&ctx, lang);
+        }
+
+        // Offer to open the report of a crash from the previous launch.
+        Self::crash_report_window(&ctx, &mut self.overlay.pending_crash_report, lang);
+
+        // Optionally, create a window for showing run information.
+        if let Some(start_time) = self.game.run.start.filter(|_| rules.scoring) {
+            egui::Window::new(lang.tr(Text::RunTitle))
+                .id(egui::Id::new("run_window"))
+                .saved_position("run_window", &self.game.options.window_positions)
+                .show(&ctx, |ui| {
+                    ui.heading(lang.tr_args(Text::RunScore, &[&self.game.run.points]));
+                    ui.label(lang.tr_args(Text::RunLevel, &[&self.game.run.level]));
+                    ui.label(lang.tr_args(
+                        Text::RunRings,
+                        &[&self.rings.collected(), &self.rings.total()],
+                    ));
+                    if self.game.options.fuel_boost {
+                        ui.add(
+                            egui::ProgressBar::new(self.game.run.fuel())
+                                .text(lang.tr(Text::RunFuel)),
+                        );
+                    }
+                    match rules.timer {
+                        TimerRule::Elapsed => {
+                            ui.label(lang.tr_args(
+                                Text::RunTime,
+                                &[&format!("{:.3}", start_time.elapsed().as_secs_f32())],
+                            ));
+                        }
+                        TimerRule::Countdown => {
+                            let left = rules.time_left(&self.game.run).unwrap_or_default();
+                            ui.label(
+                                lang.tr_args(Text::RunTimeLeft, &[&format!("{:.3}", left.max(0.))]),
+                            );
+                        }
+                        TimerRule::Hidden => {}
+                    }
+
+                    // Compare the current level's time to its par, and show how the last level went.
+                    if let Some(level_elapsed) = self.game.run.level_elapsed() {
+                        let par = game::par_time(self.difficulty);
+                        ui.label(lang.tr_args(
+                            Text::RunPar,
+                            &[
+                                &format!("{par:.1}"),
+                                &format!("{:+.1}", level_elapsed - par),
+                            ],
+                        ));
+                    }
+                    if let Some(last) = self.game.run.level_times.last() {
+                        ui.label(lang.tr_args(
+                            Text::RunLastLevel,
+                            &[
+                                &format!("{:.1}", last.time),
+                                &format!("{:+.1}", last.time - last.par),
+                            ],
+                        ));
+                    }
+                });
+        }
+
+        // Remember where the windows were dragged to, for them to open there after a restart.
+        layout::remember(&ctx, &mut self.game.options.window_positions);
+    }
+
+    // Stop any recording, waiting for the video file to be finished.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stop_recording(&mut self) {
+        let Some(mut recorder) = self.recorder.take() else {
+            return;
+        };
+        self.renderer.set_frame_capture(false);
+        for frame in self.renderer.take_captured_frames() {
+            recorder.push(frame);
+        }
+        recorder.finish();
+        self.renderer.window().set_title(TITLE);
+    }
+
+    // Persist any state that is only saved on exit.
+    pub fn save_on_exit(&self) {
+        self.game.options.save();
+        self.stats.save();
+        self.renderer.save_pipeline_cache();
+    }
+}
+
+// Start serving remote control on the given port, turning it off in the options if the port can't be used.
+#[cfg(not(target_arch = "wasm32"))]
+fn start_remote_control(port: u16, options: &mut game::Options) -> Option<RemoteControl> {
+    RemoteControl::start(port)
+        .map_err(|e| {
+            log::warn!(target: "remote", "Failed to start remote control port={port} error=\"{e}\"");
+            options.remote_control = None;
+        })
+        .ok()
+}
+
+// Build the imported world if there is one, or else generate a random world.
+fn create_world(
+    random: &mut voxels::RandomOctreeHelper,
+    options: &game::Options,
+    imported_world: Option<&ImportedWorld>,
+) -> Vec<VoxelCompact> {
+    let palette = options.colorblind_mode;
+    let theme = options.theme.theme();
+    let (voxel_octree, stats) = match imported_world {
+        None => return create_random_world(random, options),
+        Some(ImportedWorld::Graph(graph)) => graph::world(graph, palette),
+        Some(ImportedWorld::Model(model)) => voxelize::voxelize(random, palette, &theme, model, 10),
+        Some(ImportedWorld::Terrain(heightmap)) => {
+            heightmap::terrain(random, palette, &theme, heightmap, 10)
+        }
+        Some(ImportedWorld::Tutorial) => tutorial::world(random, palette, &theme),
+    };
+    log::info!(
+        target: "world",
+        "World imported voxel_count={} portal_count={}",
+        stats.voxel_count,
+        stats.goal_count
+    );
+
+    voxel_octree
+}
+
+// Generate a world from the RNG's seed, with the generator, palette, theme, and symmetry chosen in the options.
+fn create_random_world(
+    random: &mut voxels::RandomOctreeHelper,
+    options: &game::Options,
+) -> Vec<VoxelCompact> {
+    let (voxel_octree, stats) = generate_random_world(random, options);
+    crate::crash::set_seed(random.get_seed());
+    log::info!(
+        target: "world",
+        "World generated seed={} generator={:?} theme={:?} symmetry={:?} voxel_count={} portal_count={}",
+        random.get_seed(),
+        options.generator,
+        options.theme,
+        options.symmetry,
+        stats.voxel_count,
+        stats.goal_count
+    );
+
+    voxel_octree
+}
+
+// Pick the seed of a new random world, re-rolling until the world's difficulty falls in the band chosen in the
+// options, if any.
+fn random_world_seed(options: &game::Options) -> u64 {
+    use rand::Rng;
+    let mut random = rand::thread_rng();
+    let mut seed = random.gen();
+    if let Some(band) = options.difficulty_band {
+        for _ in 0..DIFFICULTY_ATTEMPTS {
+            let (octree, _) =
+                generate_random_world(&mut voxels::RandomOctreeHelper::new(seed), options);
+            if DifficultyBand::of(voxels::difficulty(&octree)) == band {
+                return seed;
+            }
+            seed = random.gen();
+        }
+        log::warn!(target: "world", "No world found in difficulty band band={band:?} attempts={DIFFICULTY_ATTEMPTS}");
+    }
+    seed
+}
+
+// Generate a random voxel-octree without recording it as the current world, such as for a preview.
+pub fn generate_random_world(
+    random: &mut voxels::RandomOctreeHelper,
+    options: &game::Options,
+) -> (Vec<VoxelCompact>, voxels::OctreeStats) {
+    let theme = options.theme.theme();
+    let (mut octree, stats) = match options.generator {
+        WorldGenerator::Recursive => voxels::generate_recursive_voxel_octree(
+            random,
+            options.colorblind_mode,
+            &theme,
+            options.symmetry,
+            256,
+            10,
+        ),
+        WorldGenerator::Tiles => {
+            voxels::generate_tiled_voxel_octree(random, options.colorblind_mode, &theme, 256, 10)
+        }
+    };
+    voxels::assign_portal_classes(random, &mut octree);
+    (octree, stats)
+}
+
+// Get the directory for app data, creating it if necessary. Falls back to the working directory.
+pub fn app_data_dir() -> path::PathBuf {
+    if let Some(p) = dirs::data_local_dir() {
+        let dir = p.join(path::Path::new("voxel_flight_simulator"));
+        if dir.exists() || std::fs::create_dir_all(&dir).is_ok() {
+            return dir;
+        }
+    }
+    path::PathBuf::from(".")
+}
+
+// Open a file or directory with the default application of the OS.
+pub fn open_path(path: &path::Path) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    command.arg(path).spawn().map(|_| ())
 }
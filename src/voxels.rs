@@ -23,6 +23,16 @@ use fast_loaded_dice_roller::FairCoin;
 use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use fast_loaded_dice_roller as fldr;
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Text;
+use crate::palette::ColorblindMode;
+use crate::theme::Theme;
+
+pub mod graph;
+pub mod heightmap;
+pub mod tutorial;
+pub mod voxelize;
 
 // The types of reference that a voxel can have to its child voxels.
 enum GraphRef {
@@ -38,6 +48,22 @@ enum VoxelType {
     Colour,
     Portal,
     Mirror,
+    // A portal back to the world that was left through a portal.
+    ReturnPortal,
+    // Portals of the other classes, which are made from scoring portals once a world has been generated.
+    SpeedPortal,
+    Wormhole,
+}
+
+// What taking a portal does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PortalClass {
+    // Leads to the next world and scores points.
+    Score,
+    // Leads to the next world and raises the camera's speed for the rest of the run.
+    Speed,
+    // Moves the camera somewhere else in the same world.
+    Wormhole,
 }
 
 struct Voxel {
@@ -47,11 +73,57 @@ struct Voxel {
     pub id: u32,
 }
 
+// Symmetries that generated voxels can be forced to have, copying sub-voxels onto one another instead of
+// rolling each independently. The largest voxels are made symmetric, giving cathedral-like structures.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Symmetry {
+    #[default]
+    Off,
+    // Each voxel's right half reflects its left half.
+    Mirror,
+    // Each voxel looks the same after a quarter turn about the vertical axis.
+    Rotational,
+}
+
+// The ways that worlds can be generated.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum WorldGenerator {
+    // Voxels rolled at random, recursing into copies of their ancestors.
+    #[default]
+    Recursive,
+    // Hand-authored tiles placed so that neighbouring tiles join up.
+    Tiles,
+}
+
+// Bands of difficulty rating that newly generated random worlds can be re-rolled until they fall in.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DifficultyBand {
+    Easy,
+    Medium,
+    Hard,
+}
+
 pub const MINIMUM_GOAL_DEPTH: u32 = 6;
 pub const MAXIMUM_VOXEL_DEPTH: u32 = 15;
 pub const MAXIMUM_GOAL_DEPTH: u32 = MAXIMUM_VOXEL_DEPTH - 1;
 pub const NULL_VOXEL_INDEX: u32 = 0xFFFF_FFFF;
 
+// Only voxels this close to the root are made symmetric, since each level of symmetry multiplies the copies below it.
+const SYMMETRIC_DEPTHS: u32 = 2;
+
+// The center of each sub-voxel (cell) relative to the parent, in the order of `VoxelCompact::children`.
+// The order here must be aligned with the subvoxel-order in the `ray_march_voxels.frag` shader.
+pub const CELL_CENTERS: [Vector3<f32>; 8] = [
+    Vector3::new(-0.5, 0.5, -0.5),
+    Vector3::new(0.5, 0.5, -0.5),
+    Vector3::new(-0.5, -0.5, -0.5),
+    Vector3::new(0.5, -0.5, -0.5),
+    Vector3::new(-0.5, 0.5, 0.5),
+    Vector3::new(0.5, 0.5, 0.5),
+    Vector3::new(-0.5, -0.5, 0.5),
+    Vector3::new(0.5, -0.5, 0.5),
+];
+
 const LEAF_VOXEL: Voxel = Voxel {
     average_colour: Vector4::new(0., 0., 0., 0.),
     children: arr![GraphRef::Recurse(0); 8],
@@ -69,6 +141,21 @@ pub struct VoxelCompact {
 }
 
 impl VoxelCompact {
+    // Whether the voxel is a portal leading to another world, either forward or back.
+    pub fn is_portal(&self) -> bool {
+        self.flags == VoxelType::ReturnPortal as u32 || self.portal_class().is_some()
+    }
+
+    // The class of a portal leading forward, or none if the voxel isn't one.
+    pub fn portal_class(&self) -> Option<PortalClass> {
+        match self.flags {
+            f if f == VoxelType::Portal as u32 => Some(PortalClass::Score),
+            f if f == VoxelType::SpeedPortal as u32 => Some(PortalClass::Speed),
+            f if f == VoxelType::Wormhole as u32 => Some(PortalClass::Wormhole),
+            _ => None,
+        }
+    }
+
     pub fn new(average_colour: [f32; 4], children: [u32; 8], flags: u32) -> Self {
         VoxelCompact {
             average_colour,
@@ -84,21 +171,17 @@ fn compact_octree_from_root(root_voxel: Voxel, voxel_count: u32) -> Vec<VoxelCom
         pub voxel: Box<Voxel>,
         pub parent_list: Vec<u32>,
     }
-    fn breadth_first_search_octree(
-        mut acc: Vec<VoxelCompact>,
-        mut to_explore: Vec<BfsVoxel>,
-        voxel_count: u32,
-    ) -> Vec<VoxelCompact> {
-        // If there are no more voxels to explore, return the accumulator.
-        if to_explore.is_empty() {
-            // Ensure that we have explored all voxels before leaving.
-            assert_eq!(voxel_count as usize, acc.len());
-            return acc;
-        }
 
-        // Get the next voxel and its parent list.
-        let BfsVoxel { voxel, parent_list } = to_explore.pop().unwrap();
+    // Initialize an empty array of compact voxel data.
+    let mut acc = Vec::<VoxelCompact>::with_capacity(voxel_count as usize);
+    acc.resize_with(voxel_count as usize, VoxelCompact::default);
 
+    // Explore the voxels in a loop rather than by recursion, since imported worlds can hold millions of them.
+    let mut to_explore = vec![BfsVoxel {
+        voxel: Box::new(root_voxel),
+        parent_list: vec![],
+    }];
+    while let Some(BfsVoxel { voxel, parent_list }) = to_explore.pop() {
         // Use the voxel count and our knowledge that there is no voxel with ID 1 to get the index of the voxel.
         assert!(voxel.id <= voxel_count);
         let self_index = voxel_count - voxel.id;
@@ -139,23 +222,14 @@ fn compact_octree_from_root(root_voxel: Voxel, voxel_count: u32) -> Vec<VoxelCom
             VoxelCompact::new(voxel.average_colour.into(), children, voxel.vtype as u32);
         *acc.get_mut(self_index as usize).unwrap() = compact_voxel;
 
-        // Maintain a sorted list of voxels to explore.
-        bfs.append(&mut to_explore);
-
-        // Continue the breadth-first search.
-        breadth_first_search_octree(acc, bfs, voxel_count)
+        // Add the new voxels to those left to explore. Their order doesn't matter since every voxel's index is
+        // known from its ID, and appending to the shorter list keeps wide octrees quick to compact.
+        to_explore.append(&mut bfs);
     }
 
-    // Initialize an empty array of compact voxel data.
-    let mut voxel_array = Vec::<VoxelCompact>::with_capacity(voxel_count as usize);
-    voxel_array.resize_with(voxel_count as usize, VoxelCompact::default);
-
-    let to_explore = vec![BfsVoxel {
-        voxel: Box::new(root_voxel),
-        parent_list: vec![],
-    }];
-
-    breadth_first_search_octree(voxel_array, to_explore, voxel_count)
+    // Ensure that we have explored all voxels before leaving.
+    assert_eq!(voxel_count as usize, acc.len());
+    acc
 }
 
 #[derive(Default)]
@@ -168,29 +242,59 @@ pub struct OctreeStats {
 // and voxel types.
 pub struct RandomOctreeHelper {
     fair_coin: fldr::rand::RngCoin<StdRng>,
-    leaf_after_portal_depth: fldr::Generator,
-    leaf_before_portal_depth: fldr::Generator,
     seed: u64,
 }
 
-// Generate a random voxel-octree stored in a contiguous array.
+// How the leaves of a world are coloured and which types they are sampled from, according to its theme.
+struct LeafStyle<'a> {
+    palette: ColorblindMode,
+    theme: &'a Theme,
+    deep_leaves: fldr::Generator,
+    shallow_leaves: fldr::Generator,
+}
+
+impl<'a> LeafStyle<'a> {
+    fn new(palette: ColorblindMode, theme: &'a Theme) -> Self {
+        LeafStyle {
+            palette,
+            theme,
+            deep_leaves: fldr::Generator::new(&theme.deep_leaf_weights),
+            shallow_leaves: fldr::Generator::new(&theme.shallow_leaf_weights),
+        }
+    }
+
+    // Generate a random voxel-colour within the theme's bounds.
+    fn random_colour(&self, random: &mut RandomOctreeHelper) -> Vector4<f32> {
+        let [low, high] = [self.theme.colour_low, self.theme.colour_high];
+        let mut channel = |i: usize| low[i] + (high[i] - low[i]) * random.samplef();
+        self.palette
+            .remap(Vector4::new(channel(0), channel(1), channel(2), 1.))
+    }
+}
+
+// Generate a random voxel-octree stored in a contiguous array, shaped and coloured by the given theme and
+// with the given symmetry. Leaf colours are remapped by the given colour-blind mode, which doesn't affect the
+// sequence of random samples.
 pub fn generate_recursive_voxel_octree(
     random: &mut RandomOctreeHelper,
+    palette: ColorblindMode,
+    theme: &Theme,
+    symmetry: Symmetry,
     desired_voxel_count: u32,
     desired_portal_count: u32,
 ) -> (Vec<VoxelCompact>, OctreeStats) {
-    // Helper to generate a random voxel-colour.
-    fn random_colour(random: &mut RandomOctreeHelper) -> Vector4<f32> {
-        Vector4::new(random.samplef(), random.samplef(), random.samplef(), 1.)
-    }
-
     // Helper to generate a random leaf-voxel.
-    fn random_leaf(random: &mut RandomOctreeHelper, depth: u32, stats: &mut OctreeStats) -> Voxel {
-        let colour = random_colour(random);
+    fn random_leaf(
+        random: &mut RandomOctreeHelper,
+        style: &mut LeafStyle,
+        depth: u32,
+        stats: &mut OctreeStats,
+    ) -> Voxel {
+        let colour = style.random_colour(random);
         stats.voxel_count += 1;
         let id = stats.voxel_count;
 
-        match random.sample_leaf(depth >= MINIMUM_GOAL_DEPTH) {
+        match random.sample_leaf(style, depth >= MINIMUM_GOAL_DEPTH) {
             VoxelType::Colour => Voxel {
                 average_colour: colour,
                 id,
@@ -211,7 +315,10 @@ pub fn generate_recursive_voxel_octree(
                 id,
                 ..LEAF_VOXEL
             },
-            VoxelType::Complex => unreachable!(),
+            VoxelType::Complex
+            | VoxelType::ReturnPortal
+            | VoxelType::SpeedPortal
+            | VoxelType::Wormhole => unreachable!(),
         }
     }
 
@@ -226,9 +333,12 @@ pub fn generate_recursive_voxel_octree(
     // Recursively form a graph of voxels in a depth-first manner.
     fn roll_voxel_graph(
         random: &mut RandomOctreeHelper,
+        style: &mut LeafStyle,
+        symmetry: Symmetry,
         depth: u32,
         stats: &mut OctreeStats,
     ) -> Voxel {
+        let (empty_chance, branch_chance) = (style.theme.empty_chance, style.theme.branch_chance);
         let mut sum_colour = Vector4::zero();
         let mut sum_count: f32 = 0.;
         let mut pop_node_option = |colour: &mut Vector4<f32>| -> GraphRef {
@@ -236,19 +346,19 @@ pub fn generate_recursive_voxel_octree(
             // TODO: Change the moving target function to better approach the desired voxel count. Current is tuned for 256.
             #[allow(clippy::cast_precision_loss)]
             let moving_target = 1. / (0.65 * depth as f32 + 1.);
-            if random_type < 0.45 {
-                // 45% chance of empty node
+            if random_type < empty_chance {
+                // The theme's chance of an empty node, 45% for the classic theme.
                 GraphRef::Empty
-            } else if random_type < 0.45_f32.powf(moving_target) {
+            } else if random_type < empty_chance.powf(moving_target) {
                 // Next most likely is a leaf node, but not at the first depths.
-                let v = random_leaf(random, depth, stats);
+                let v = random_leaf(random, style, depth, stats);
                 *colour += v.average_colour;
                 sum_count += 1.;
 
                 GraphRef::Ref(Box::new(v))
-            } else if random_type < 0.825_f32.powf(moving_target.powf(0.625)) {
+            } else if random_type < branch_chance.powf(moving_target.powf(0.625)) {
                 // Next most likely is a non-recursive voxel, however, should be less likely at latter depths.
-                let v = roll_voxel_graph(random, depth + 1, stats);
+                let v = roll_voxel_graph(random, style, symmetry, depth + 1, stats);
                 *colour += v.average_colour;
                 sum_count += 1.;
 
@@ -258,8 +368,17 @@ pub fn generate_recursive_voxel_octree(
             }
         };
 
-        // Build a random voxel for each sub-voxel.
-        let children = arr![pop_node_option(&mut sum_colour); 8];
+        // Build a random voxel for each sub-voxel which isn't copied from another by the symmetry.
+        let own_symmetry = if depth < SYMMETRIC_DEPTHS {
+            symmetry
+        } else {
+            Symmetry::Off
+        };
+        let mut children = arr![GraphRef::Empty; 8];
+        for &i in own_symmetry.rolled_cells() {
+            children[i] = pop_node_option(&mut sum_colour);
+        }
+        own_symmetry.complete(&mut children, stats);
 
         // Ensure that the sum count is never still 0.
         sum_count = sum_count.max(1.);
@@ -273,10 +392,11 @@ pub fn generate_recursive_voxel_octree(
         }
     }
 
+    let mut style = LeafStyle::new(palette, theme);
     loop {
         // Loop through random graphs until one satisfies all conditions.
         let mut stats = OctreeStats::default();
-        let v = roll_voxel_graph(random, 0, &mut stats);
+        let v = roll_voxel_graph(random, &mut style, symmetry, 0, &mut stats);
 
         // If we have generated enough voxels, compactify the octree into an array and return it.
         if stats.voxel_count >= desired_voxel_count && stats.goal_count >= desired_portal_count {
@@ -285,100 +405,1207 @@ pub fn generate_recursive_voxel_octree(
     }
 }
 
+// The depth of the grid that tiles are placed on, which has `TILE_GRID_SIZE` cells along each axis.
+const TILE_GRID_DEPTH: u32 = 4;
+const TILE_GRID_SIZE: usize = 1 << TILE_GRID_DEPTH;
+
+// The cells that a portal is nested through inside a tile's cell, placing it near the bottom of the cell and
+// deep enough to score like the portals of recursive worlds.
+const TILE_PORTAL_PATH: [usize; 2] = [3, 6];
+
+// Something placed in one of a tile's empty cells, which doesn't affect which tiles can neighbour it.
+#[derive(Clone, Copy, PartialEq)]
+enum TileFeature {
+    None,
+    // A portal at the bottom of the cell.
+    Portal(usize),
+    // A miniature copy of the whole world.
+    Recursion(usize),
+}
+
+// A hand-authored tile for the tiled generator. The bits of `cells` mark which of its sub-voxels are filled,
+// and its material chooses their colour.
+#[derive(Clone, Copy, PartialEq)]
+struct Tile {
+    cells: u8,
+    weight: f32,
+    material: usize,
+    feature: TileFeature,
+}
+
+// The tiles that worlds are built from, before they are rotated and flipped to form the full set.
+// Cells are ordered as in `CELL_CENTERS`, with the lowest bit being the top-front-left cell.
+const BASE_TILES: [Tile; 11] = {
+    const FLOOR: u8 = 0b1100_1100;
+    [
+        // Empty space, whose weight is scaled by the theme's chance of empty voxels.
+        Tile::new(0, 10., 0, TileFeature::None),
+        // Solid rock.
+        Tile::new(0xFF, 1., 0, TileFeature::None),
+        // A floor, and the edge of a floor.
+        Tile::new(FLOOR, 3., 1, TileFeature::None),
+        Tile::new(0b0100_0100, 1., 1, TileFeature::None),
+        // A wall, and a floor meeting a wall.
+        Tile::new(0b0101_0101, 2., 2, TileFeature::None),
+        Tile::new(FLOOR | 0b0001_0001, 1., 2, TileFeature::None),
+        // A pillar, a beam, and a block ending either.
+        Tile::new(0b0000_0101, 1., 3, TileFeature::None),
+        Tile::new(0b0000_0011, 1., 3, TileFeature::None),
+        Tile::new(0b0000_0001, 0.5, 3, TileFeature::None),
+        // A portal standing on a floor, and a miniature copy of the world standing on a floor.
+        Tile::new(FLOOR, 0.5, 1, TileFeature::Portal(0)),
+        Tile::new(FLOOR, 0.1, 1, TileFeature::Recursion(5)),
+    ]
+};
+
+// The number of materials that tiles are coloured with.
+const TILE_MATERIALS: usize = 4;
+
+// Generate a voxel-octree from hand-authored tiles placed on a grid so that the faces of neighbouring tiles
+// match, giving more architectural worlds than random rolls. Tiles are coloured by the theme, and a few hold
+// portals or miniature copies of the whole world.
+pub fn generate_tiled_voxel_octree(
+    random: &mut RandomOctreeHelper,
+    palette: ColorblindMode,
+    theme: &Theme,
+    desired_voxel_count: u32,
+    desired_portal_count: u32,
+) -> (Vec<VoxelCompact>, OctreeStats) {
+    let mut style = LeafStyle::new(palette, theme);
+    let tiles = Tile::variants(theme);
+    loop {
+        // Loop through tilings until one satisfies all conditions.
+        let grid = collapse_tile_grid(random, &tiles);
+        let materials = [(); TILE_MATERIALS].map(|()| style.random_colour(random));
+        let mut stats = OctreeStats::default();
+        let root = build_tiled_voxel(
+            random,
+            &mut style,
+            &TileGrid {
+                tiles: &tiles,
+                grid: &grid,
+                materials: &materials,
+            },
+            0,
+            [0; 3],
+            &mut stats,
+        );
+        if stats.voxel_count >= desired_voxel_count && stats.goal_count >= desired_portal_count {
+            if let GraphRef::Ref(root) = root {
+                return (compact_octree_from_root(*root, stats.voxel_count), stats);
+            }
+        }
+    }
+}
+
+// A grid of chosen tiles, indexed by `tile_grid_index`, and the colours of their materials.
+struct TileGrid<'a> {
+    tiles: &'a [Tile],
+    grid: &'a [usize],
+    materials: &'a [Vector4<f32>; TILE_MATERIALS],
+}
+
+impl Tile {
+    const fn new(cells: u8, weight: f32, material: usize, feature: TileFeature) -> Self {
+        Tile {
+            cells,
+            weight,
+            material,
+            feature,
+        }
+    }
+
+    // Every distinct rotation about the vertical axis and vertical flip of the base tiles. Each base tile's
+    // weight is shared between its variants.
+    fn variants(theme: &Theme) -> Vec<Tile> {
+        let mut tiles = Vec::new();
+        for (i, base) in BASE_TILES.iter().enumerate() {
+            let mut variants = Vec::new();
+            let mut tile = *base;
+            for _ in 0..2 {
+                for _ in 0..4 {
+                    if !variants.contains(&tile) {
+                        variants.push(tile);
+                    }
+                    tile = tile.permuted(rotate_cell);
+                }
+                tile = tile.permuted(flip_cell);
+            }
+
+            #[allow(clippy::cast_precision_loss)]
+            let weight = if i == 0 {
+                base.weight * theme.empty_chance
+            } else {
+                base.weight
+            } / variants.len() as f32;
+            tiles.extend(variants.into_iter().map(|tile| Tile { weight, ..tile }));
+        }
+        assert!(tiles.len() <= 64, "Tile choices must fit in a 64-bit set");
+        tiles
+    }
+
+    // This tile with its cells rearranged by the given permutation.
+    fn permuted(self, permutation: fn(usize) -> usize) -> Tile {
+        let cells = (0..8)
+            .filter(|&i| self.cells & (1 << i) != 0)
+            .fold(0, |cells, i| cells | (1 << permutation(i)));
+        let feature = match self.feature {
+            TileFeature::None => TileFeature::None,
+            TileFeature::Portal(i) => TileFeature::Portal(permutation(i)),
+            TileFeature::Recursion(i) => TileFeature::Recursion(permutation(i)),
+        };
+        Tile {
+            cells,
+            feature,
+            ..self
+        }
+    }
+
+    // The pattern of filled cells on the face of this tile towards the positive or negative end of an axis.
+    // Tiles can neighbour each other when the patterns of the faces they share are equal.
+    fn face(self, axis: usize, positive: bool) -> u8 {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        (0..8)
+            .filter(|&i| self.cells & (1 << i) != 0)
+            .map(tile_cell_position)
+            .filter(|p| (p[axis] == 1) == positive)
+            .fold(0, |pattern, p| pattern | (1 << (p[u] * 2 + p[v])))
+    }
+}
+
+// The position of a cell within its parent, as 0 or 1 along each axis with Y pointing up.
+fn tile_cell_position(i: usize) -> [usize; 3] {
+    [i & 1, 1 - ((i >> 1) & 1), (i >> 2) & 1]
+}
+
+// The index of a cell of the tile grid.
+fn tile_grid_index([x, y, z]: [usize; 3]) -> usize {
+    x + TILE_GRID_SIZE * (y + TILE_GRID_SIZE * z)
+}
+
+// Choose a tile for every cell of the grid so that the faces of neighbouring tiles match, in the manner of
+// wave-function collapse: the cell with the fewest remaining choices is decided next, narrowing the choices of
+// its neighbours in turn. Cells left without a choice by conflicting neighbours are made empty.
+fn collapse_tile_grid(random: &mut RandomOctreeHelper, tiles: &[Tile]) -> Vec<usize> {
+    const DIRECTIONS: [(usize, bool); 6] = [
+        (0, false),
+        (0, true),
+        (1, false),
+        (1, true),
+        (2, false),
+        (2, true),
+    ];
+
+    // For each tile and direction, the set of tiles which may be placed next to it in that direction.
+    let compatible: Vec<[u64; 6]> = tiles
+        .iter()
+        .map(|a| {
+            DIRECTIONS.map(|(axis, positive)| {
+                (0..tiles.len())
+                    .filter(|&j| a.face(axis, positive) == tiles[j].face(axis, !positive))
+                    .fold(0, |set, j| set | (1 << j))
+            })
+        })
+        .collect();
+    let tile_set = |mut set: u64| {
+        std::iter::from_fn(move || {
+            (set != 0).then(|| {
+                let j = set.trailing_zeros() as usize;
+                set &= set - 1;
+                j
+            })
+        })
+    };
+
+    let cell_count = TILE_GRID_SIZE.pow(3);
+    let mut choices = vec![u64::MAX >> (64 - tiles.len()); cell_count];
+
+    // Visit the cells in a random order when looking for the fewest choices, so that ties are broken fairly.
+    let mut order: Vec<usize> = (0..cell_count).collect();
+    for i in (1..cell_count).rev() {
+        let j = random.sample_index(i + 1);
+        order.swap(i, j);
+    }
+
+    let mut stack = Vec::new();
+    loop {
+        // Find the undecided cell with the fewest choices, stopping early at the fewest possible.
+        let mut next = None;
+        let mut fewest = u32::MAX;
+        for &cell in &order {
+            let count = choices[cell].count_ones();
+            if count > 1 && count < fewest {
+                (next, fewest) = (Some(cell), count);
+                if count == 2 {
+                    break;
+                }
+            }
+        }
+        let Some(cell) = next else {
+            break;
+        };
+
+        // Decide the cell's tile by weight.
+        let total: f32 = tile_set(choices[cell]).map(|j| tiles[j].weight).sum();
+        let mut roll = random.samplef() * total;
+        let chosen = tile_set(choices[cell])
+            .find(|&j| {
+                roll -= tiles[j].weight;
+                roll < 0.
+            })
+            .unwrap_or_else(|| tile_set(choices[cell]).last().unwrap_or_default());
+        choices[cell] = 1 << chosen;
+
+        // Narrow the choices of the neighbours, and of their neighbours when theirs change.
+        stack.push(cell);
+        while let Some(cell) = stack.pop() {
+            let position = [
+                cell % TILE_GRID_SIZE,
+                cell / TILE_GRID_SIZE % TILE_GRID_SIZE,
+                cell / (TILE_GRID_SIZE * TILE_GRID_SIZE),
+            ];
+            for (direction, &(axis, positive)) in DIRECTIONS.iter().enumerate() {
+                let mut neighbour = position;
+                match (positive, neighbour[axis]) {
+                    (false, 0) => continue,
+                    (true, p) if p + 1 == TILE_GRID_SIZE => continue,
+                    (false, _) => neighbour[axis] -= 1,
+                    (true, _) => neighbour[axis] += 1,
+                }
+                let neighbour = tile_grid_index(neighbour);
+                let allowed =
+                    tile_set(choices[cell]).fold(0, |set, j| set | compatible[j][direction]);
+                let narrowed = choices[neighbour] & allowed;
+                if narrowed != choices[neighbour] {
+                    choices[neighbour] = narrowed;
+                    if narrowed != 0 {
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+    }
+
+    // The empty tile is the first, which is also used where no tile could be chosen.
+    choices
+        .into_iter()
+        .map(|set| tile_set(set).next().unwrap_or(0))
+        .collect()
+}
+
+// Build the voxel covering the tiles below the grid cell at the given depth and position, made of the same
+// cells as the grid at that depth.
+fn build_tiled_voxel(
+    random: &mut RandomOctreeHelper,
+    style: &mut LeafStyle,
+    tiling: &TileGrid,
+    depth: u32,
+    position: [usize; 3],
+    stats: &mut OctreeStats,
+) -> GraphRef {
+    let mut children = arr![GraphRef::Empty; 8];
+    if depth == TILE_GRID_DEPTH {
+        let tile = tiling.tiles[tiling.grid[tile_grid_index(position)]];
+        for (i, child) in children.iter_mut().enumerate() {
+            if tile.cells & (1 << i) != 0 {
+                stats.voxel_count += 1;
+                *child = GraphRef::Ref(Box::new(Voxel {
+                    average_colour: tiling.materials[tile.material],
+                    vtype: random.sample_leaf(style, false),
+                    id: stats.voxel_count,
+                    ..LEAF_VOXEL
+                }));
+            }
+        }
+        match tile.feature {
+            TileFeature::None => (),
+            TileFeature::Portal(i) => {
+                stats.goal_count += 1;
+                stats.voxel_count += 1;
+                let mut portal = GraphRef::Ref(Box::new(Voxel {
+                    average_colour: style.random_colour(random),
+                    vtype: VoxelType::Portal,
+                    id: stats.voxel_count,
+                    ..LEAF_VOXEL
+                }));
+                for &cell in TILE_PORTAL_PATH.iter().rev() {
+                    let mut nested = arr![GraphRef::Empty; 8];
+                    nested[cell] = portal;
+                    portal = complex_voxel(nested, stats);
+                }
+                children[i] = portal;
+            }
+
+            // The tile's voxel is as deep as the grid, so the root is that many parents up.
+            TileFeature::Recursion(i) => children[i] = GraphRef::Recurse(TILE_GRID_DEPTH),
+        }
+    } else {
+        for (i, child) in children.iter_mut().enumerate() {
+            let offset = tile_cell_position(i);
+            *child = build_tiled_voxel(
+                random,
+                style,
+                tiling,
+                depth + 1,
+                [0, 1, 2].map(|axis| 2 * position[axis] + offset[axis]),
+                stats,
+            );
+        }
+    }
+    complex_voxel(children, stats)
+}
+
+// A complex voxel of the given sub-voxels, coloured by their average, or nothing if they are all empty.
+fn complex_voxel(children: [GraphRef; 8], stats: &mut OctreeStats) -> GraphRef {
+    if children
+        .iter()
+        .all(|child| matches!(child, GraphRef::Empty))
+    {
+        return GraphRef::Empty;
+    }
+    let (sum_colour, sum_count) = children
+        .iter()
+        .filter_map(|child| match child {
+            GraphRef::Ref(voxel) => Some(voxel.average_colour),
+            _ => None,
+        })
+        .fold((Vector4::zero(), 0.), |(sum, count), colour| {
+            (sum + colour, count + 1.)
+        });
+    stats.voxel_count += 1;
+    GraphRef::Ref(Box::new(Voxel {
+        average_colour: sum_colour / f32::max(sum_count, 1.),
+        children,
+        vtype: VoxelType::Complex,
+        id: stats.voxel_count,
+    }))
+}
+
+// Squared radius of the spherical goal inside a portal voxel, relative to the voxel's size.
+const GOAL_RADIUS_SQUARED: f32 = 0.75;
+
 pub enum Intersection {
     Empty(f32),
     Collision,
-    Portal { depth: u32, index: u32 },
+    Portal {
+        class: PortalClass,
+        depth: u32,
+        index: u32,
+    },
+    ReturnPortal,
+}
+
+// The branch voxels visited by the last octree query. The camera moves very little between frames, so
+// queries resume from the deepest cached voxel that still contains the point instead of from the root.
+#[derive(Default)]
+pub struct TraversalCache {
+    path: Vec<CachedCell>,
+}
+
+// A voxel visited during traversal and the region of the root it covers.
+#[derive(Clone, Copy)]
+struct CachedCell {
+    center: Vector3<f32>,
+    half_size: f32,
+    index: u32,
+}
+
+impl TraversalCache {
+    // Forget the cached path, which must be done whenever the octree changes.
+    pub fn clear(&mut self) {
+        self.path.clear();
+    }
+}
+
+impl CachedCell {
+    const ROOT: CachedCell = CachedCell {
+        center: Vector3::new(0., 0., 0.),
+        half_size: 1.,
+        index: 0,
+    };
+
+    fn contains(&self, p: Vector3<f32>) -> bool {
+        let d = p - self.center;
+        d.x.abs() <= self.half_size && d.y.abs() <= self.half_size && d.z.abs() <= self.half_size
+    }
+
+    // Distance along a ray from a point inside the voxel to where the ray leaves it.
+    fn exit_distance(&self, p: Vector3<f32>, direction: Vector3<f32>) -> f32 {
+        let axis = |p: f32, d: f32, c: f32| {
+            if d > 0. {
+                (c + self.half_size - p) / d
+            } else if d < 0. {
+                (c - self.half_size - p) / d
+            } else {
+                f32::INFINITY
+            }
+        };
+        axis(p.x, direction.x, self.center.x)
+            .min(axis(p.y, direction.y, self.center.y))
+            .min(axis(p.z, direction.z, self.center.z))
+            .max(0.)
+    }
+
+    // Distance along a ray from a point outside the voxel to where the ray enters it, if it does.
+    fn entry_distance(&self, p: Vector3<f32>, direction: Vector3<f32>) -> Option<f32> {
+        let mut near = 0f32;
+        let mut far = f32::INFINITY;
+        for i in 0..3 {
+            let (min, max) = (
+                self.center[i] - self.half_size,
+                self.center[i] + self.half_size,
+            );
+            if direction[i] == 0. {
+                if p[i] < min || p[i] > max {
+                    return None;
+                }
+            } else {
+                let (t0, t1) = ((min - p[i]) / direction[i], (max - p[i]) / direction[i]);
+                near = near.max(t0.min(t1));
+                far = far.min(t0.max(t1));
+            }
+        }
+        (near <= far).then_some(near)
+    }
+
+    // Distance along a ray from a point in this portal voxel to where the ray touches its spherical goal, if it does.
+    fn goal_distance(&self, p: Vector3<f32>, direction: Vector3<f32>) -> Option<f32> {
+        let offset = p - self.center;
+        let c = offset.magnitude2() - GOAL_RADIUS_SQUARED * self.half_size * self.half_size;
+        if c <= 0. {
+            return Some(0.);
+        }
+        let b = offset.dot(direction);
+        let discriminant = b * b - c;
+        (b < 0. && discriminant >= 0.).then(|| -b - discriminant.sqrt())
+    }
+
+    // The outward normal of the face of this voxel nearest to a point inside it.
+    fn face_normal(&self, p: Vector3<f32>) -> Vector3<f32> {
+        let d = p - self.center;
+        let axis = (0..3)
+            .max_by(|&a, &b| d[a].abs().total_cmp(&d[b].abs()))
+            .unwrap_or(0);
+        let mut normal = Vector3::zero();
+        normal[axis] = d[axis].signum();
+        normal
+    }
+
+    // The sub-voxel containing a point inside this voxel, given this voxel's children.
+    fn child(&self, p: Vector3<f32>, children: &[u32; 8]) -> CachedCell {
+        let cell_index = self.child_index(p);
+        CachedCell {
+            center: self.center + CELL_CENTERS[cell_index] * self.half_size,
+            half_size: self.half_size * 0.5,
+            index: children[cell_index],
+        }
+    }
+
+    // Which of this voxel's sub-voxels contains a point inside it.
+    fn child_index(&self, p: Vector3<f32>) -> usize {
+        // Determine which sub-voxel the point is in by assigning a bit to each axis and
+        // setting its value depending on which side of the axis the point is on.
+        let p = p - self.center;
+        (usize::from(p.z > 0.) << 2) + (usize::from(p.y <= 0.) << 1) + usize::from(p.x > 0.)
+    }
 }
 
 // Determine where in the octree a point is, and whether it is colliding with a voxel.
 pub fn octree_scale_and_collision_of_point(
     position: Vector3<f32>,
     octree: &[VoxelCompact],
+    cache: &mut TraversalCache,
 ) -> Intersection {
-    const GOAL_RADIUS_SQUARED: f32 = 0.75;
-    fn search_graph(
-        scale: f32,
-        iter: u32,
-        p: Vector3<f32>,
-        index: u32,
-        octree: &[VoxelCompact],
-    ) -> Intersection {
-        if iter > MAXIMUM_VOXEL_DEPTH {
-            Intersection::Collision
-        } else if index == NULL_VOXEL_INDEX {
-            Intersection::Empty(scale)
+    if !CachedCell::ROOT.contains(position) {
+        // If the point is outside the root voxel then there cannot be an intersection.
+        cache.clear();
+        return Intersection::Empty(1.);
+    }
+    traverse(position, octree, cache).0
+}
+
+// Chance of each scoring portal being turned into a speed portal, and into a wormhole.
+const SPEED_PORTAL_CHANCE: f32 = 0.15;
+const WORMHOLE_CHANCE: f32 = 0.15;
+
+// Turn some of a generated world's scoring portals into speed portals and wormholes. The rolls are made after the
+// world's layout, so the same seed still gives the same layout. The portal with the highest index is always left
+// scoring, so that every world can still be scored in.
+pub fn assign_portal_classes(random: &mut RandomOctreeHelper, octree: &mut [VoxelCompact]) {
+    let mut portals = octree
+        .iter_mut()
+        .filter(|voxel| voxel.flags == VoxelType::Portal as u32);
+    portals.next_back();
+    for portal in portals {
+        let roll = random.samplef();
+        if roll < SPEED_PORTAL_CHANCE {
+            portal.flags = VoxelType::SpeedPortal as u32;
+        } else if roll < SPEED_PORTAL_CHANCE + WORMHOLE_CHANCE {
+            portal.flags = VoxelType::Wormhole as u32;
+        }
+    }
+}
+
+// The centre and half-size of the empty voxel containing a point inside the root voxel, or none if the point isn't
+// in one. Points in the empty corners of portal voxels don't count, since the portal fills their centre.
+pub fn empty_voxel_at(
+    position: Vector3<f32>,
+    octree: &[VoxelCompact],
+    cache: &mut TraversalCache,
+) -> Option<(Vector3<f32>, f32)> {
+    match traverse(position, octree, cache) {
+        (Intersection::Empty(_), cell) if cell.index == NULL_VOXEL_INDEX => {
+            Some((cell.center, cell.half_size))
+        }
+        _ => None,
+    }
+}
+
+// Find an open point in the world for a wormhole to lead to, preferring the largest empty voxel among those
+// sampled. Returns none if no sampled point was empty.
+pub fn wormhole_destination(
+    random: &mut impl Rng,
+    octree: &[VoxelCompact],
+) -> Option<Vector3<f32>> {
+    const SAMPLES: u32 = 64;
+
+    let mut cache = TraversalCache::default();
+    let mut best: Option<(Vector3<f32>, f32)> = None;
+    for _ in 0..SAMPLES {
+        let position = Vector3::new(
+            random.gen_range(-1. ..1.),
+            random.gen_range(-1. ..1.),
+            random.gen_range(-1. ..1.),
+        );
+        if let Some((center, half_size)) = empty_voxel_at(position, octree, &mut cache) {
+            if !matches!(best, Some((_, best_size)) if half_size <= best_size) {
+                // Arrive at the centre of the empty voxel, as far from its neighbours as possible.
+                best = Some((center, half_size));
+            }
+        }
+    }
+    best.map(|(position, _)| position)
+}
+
+// Turn one of the world's portals into a return portal, leaving the others to lead forward. The portal with the
+// highest index is chosen so that the same one leads back each time the world is generated. Worlds with a single
+// portal are left unchanged so that they can still be left forward.
+pub fn add_return_portal(octree: &mut [VoxelCompact]) {
+    let mut portals = octree
+        .iter_mut()
+        .filter(|voxel| voxel.flags == VoxelType::Portal as u32);
+    if let (Some(portal), Some(_)) = (portals.next_back(), portals.next()) {
+        portal.flags = VoxelType::ReturnPortal as u32;
+    }
+}
+
+// The most portals found near a point, and voxels searched to find them.
+const NEARBY_PORTALS: usize = 16;
+const NEARBY_SEARCHED_VOXELS: usize = 1 << 12;
+
+// A portal leading forward, found near a point.
+pub struct NearbyPortal {
+    pub center: Vector3<f32>,
+    pub class: PortalClass,
+    pub depth: u32,
+    pub index: u32,
+}
+
+// The portals leading forward within the given distance of a point, no smaller than the given half-size,
+// found breadth-first from the root.
+pub fn portals_near(
+    position: Vector3<f32>,
+    distance: f32,
+    minimum_half_size: f32,
+    octree: &[VoxelCompact],
+) -> Vec<NearbyPortal> {
+    let mut portals = Vec::new();
+    let mut queue = std::collections::VecDeque::from([CachedCell::ROOT]);
+    let mut searched = 0;
+    while let Some(cell) = queue.pop_front() {
+        searched += 1;
+        if searched > NEARBY_SEARCHED_VOXELS || portals.len() == NEARBY_PORTALS {
+            break;
+        }
+        let Some(voxel) = octree.get(cell.index as usize) else {
+            continue;
+        };
+        if let Some(class) = voxel.portal_class() {
+            // The depth is counted the way `traverse` counts it when the portal is taken.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            portals.push(NearbyPortal {
+                center: cell.center,
+                class,
+                depth: (-cell.half_size.log2() - 1.).max(0.) as u32,
+                index: cell.index,
+            });
+        } else if voxel.flags == VoxelType::Complex as u32
+            && 0.5 * cell.half_size >= minimum_half_size
+        {
+            for (&index, offset) in voxel.children.iter().zip(CELL_CENTERS) {
+                let child = CachedCell {
+                    center: cell.center + offset * cell.half_size,
+                    half_size: 0.5 * cell.half_size,
+                    index,
+                };
+
+                // Only descend into voxels which reach within the distance of the point.
+                let d = (position - child.center).map(|c| (c.abs() - child.half_size).max(0.));
+                if index != NULL_VOXEL_INDEX && d.magnitude2() <= distance * distance {
+                    queue.push_back(child);
+                }
+            }
+        }
+    }
+    portals
+}
+
+// Number of points probed to estimate how much of a world is open space.
+const DIFFICULTY_PROBES: u32 = 512;
+
+// How many levels below the shallowest goals the portals must be on average, how much of the world must be closed,
+// and how many of its leaves must be mirrors, for each part of the difficulty rating to be at its hardest.
+const HARDEST_PORTAL_DEPTH: f32 = 6.;
+const HARDEST_CLOSED_FRACTION: f32 = 0.4;
+const HARDEST_MIRROR_FRACTION: f32 = 0.2;
+
+// Rate how hard a world is to fly from 0 to 10, from how deep its portals are, how little of it is open space,
+// and how many of its leaves are mirrors. The probes are seeded the same way each time, so a world's rating
+// doesn't change when it's regenerated.
+#[allow(clippy::cast_precision_loss)]
+pub fn difficulty(octree: &[VoxelCompact]) -> f32 {
+    // Deeper portals are smaller, and further from the open space near the root.
+    let depths = portal_depths(octree);
+    let depth = if depths.is_empty() {
+        1.
+    } else {
+        let mean = depths.iter().sum::<u32>() as f32 / depths.len() as f32;
+        ((mean - MINIMUM_GOAL_DEPTH as f32) / HARDEST_PORTAL_DEPTH).clamp(0., 1.)
+    };
+
+    // Estimate the fraction of the world that's closed by probing random points within it.
+    let mut random = StdRng::seed_from_u64(0);
+    let mut cache = TraversalCache::default();
+    let open = (0..DIFFICULTY_PROBES)
+        .filter(|_| {
+            let position = Vector3::new(
+                random.gen_range(-1. ..1.),
+                random.gen_range(-1. ..1.),
+                random.gen_range(-1. ..1.),
+            );
+            matches!(
+                octree_scale_and_collision_of_point(position, octree, &mut cache),
+                Intersection::Empty(_)
+            )
+        })
+        .count();
+    let closed = 1. - open as f32 / DIFFICULTY_PROBES as f32;
+    let closed = (closed / HARDEST_CLOSED_FRACTION).min(1.);
+
+    // Mirrors make the world look larger than it is, hiding its walls.
+    let is_mirror = |voxel: &&VoxelCompact| voxel.flags == VoxelType::Mirror as u32;
+    let leaves = octree
+        .iter()
+        .filter(|voxel| voxel.flags == VoxelType::Colour as u32 || is_mirror(voxel))
+        .count();
+    let mirrors = octree.iter().filter(is_mirror).count() as f32 / leaves.max(1) as f32;
+    let mirrors = (mirrors / HARDEST_MIRROR_FRACTION).min(1.);
+
+    10. * (0.5 * depth + 0.35 * closed + 0.15 * mirrors)
+}
+
+// The shallowest depth at which each portal leading forward can be reached from the root.
+fn portal_depths(octree: &[VoxelCompact]) -> Vec<u32> {
+    let mut visited = vec![false; octree.len()];
+    let mut depths = Vec::new();
+    let mut level = vec![0];
+    for depth in 0..=MAXIMUM_VOXEL_DEPTH {
+        let mut next = Vec::new();
+        for index in level {
+            let Some(voxel) = octree.get(index as usize) else {
+                continue;
+            };
+            if std::mem::replace(&mut visited[index as usize], true) {
+                continue;
+            }
+            if voxel.portal_class().is_some() {
+                depths.push(depth);
+            } else if voxel.flags == VoxelType::Complex as u32 {
+                next.extend(
+                    voxel
+                        .children
+                        .iter()
+                        .copied()
+                        .filter(|&child| child != NULL_VOXEL_INDEX),
+                );
+            }
+        }
+        level = next;
+    }
+    depths
+}
+
+// Carve the colour or mirror voxel containing a point out of the octree, by emptying its parent's reference to it.
+// Returns the index of the parent, which of its children was emptied, and the index of the carved voxel. Voxels are
+// shared between the repeats of recursive worlds, so every repeat of the parent loses the voxel as well.
+pub fn carve_leaf(
+    position: Vector3<f32>,
+    octree: &mut [VoxelCompact],
+) -> Option<(u32, usize, u32)> {
+    if !CachedCell::ROOT.contains(position) {
+        return None;
+    }
+    let mut cell = CachedCell::ROOT;
+    for _ in 0..MAXIMUM_VOXEL_DEPTH {
+        let parent = octree.get(cell.index as usize)?;
+        let child = cell.child(position, &parent.children);
+        let cell_index = cell.child_index(position);
+        match octree.get(child.index as usize)?.flags {
+            f if f == VoxelType::Complex as u32 => cell = child,
+            f if f == VoxelType::Colour as u32 || f == VoxelType::Mirror as u32 => {
+                octree[cell.index as usize].children[cell_index] = NULL_VOXEL_INDEX;
+                return Some((cell.index, cell_index, child.index));
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+// Find the first point along a segment which isn't empty space, so that fast movement can't pass through
+// thin voxels between frames. The segment skips across each empty voxel it enters, except for portal
+// voxels which are crossed in smaller steps to find their spherical goal.
+pub fn first_hit_along_segment(
+    start: Vector3<f32>,
+    end: Vector3<f32>,
+    octree: &[VoxelCompact],
+    cache: &mut TraversalCache,
+) -> Option<Vector3<f32>> {
+    const MAXIMUM_STEPS: u32 = 256;
+    const PORTAL_STEP: f32 = 0.25;
+
+    let length = (end - start).magnitude();
+    if length <= 0. {
+        return None;
+    }
+    let direction = (end - start) / length;
+
+    let mut distance = 0.;
+    for _ in 0..MAXIMUM_STEPS {
+        let position = start + direction * distance;
+        let step = if CachedCell::ROOT.contains(position) {
+            let (intersection, cell) = traverse(position, octree, cache);
+            if !matches!(intersection, Intersection::Empty(_)) {
+                return Some(position);
+            }
+            let exit = cell.exit_distance(position, direction);
+            if cell.index != NULL_VOXEL_INDEX && octree[cell.index as usize].is_portal() {
+                exit.min(PORTAL_STEP * cell.half_size)
+            } else {
+                exit
+            }
         } else {
-            let voxel = octree[index as usize];
-            if voxel.flags == 1 || voxel.flags == 4 {
-                Intersection::Collision
-            } else if voxel.flags == 2 {
-                if p.dot(p) <= GOAL_RADIUS_SQUARED {
-                    // Subtract 1 from depth since this function asserts the root as depth zero, others do not.
-                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-                    Intersection::Portal {
-                        depth: (scale.log2() - 1.).max(0.) as u32,
-                        index,
+            // Skip ahead to where the segment enters the root voxel, if it does.
+            CachedCell::ROOT.entry_distance(position, direction)?
+        };
+
+        // Step just past the boundary so that the next sample is in the neighbouring voxel.
+        distance += step + f32::EPSILON.max(step * 1e-4);
+        if distance >= length {
+            return None;
+        }
+    }
+    None
+}
+
+// The first voxel hit by a ray cast through the octree. The depth counts levels below the root voxel, and the
+// normal faces out of the surface that was hit, or back along the ray if it started inside the voxel.
+#[derive(Clone, Copy)]
+pub struct Hit {
+    pub distance: f32,
+    pub normal: Vector3<f32>,
+    pub depth: u32,
+    pub index: u32,
+}
+
+// Cast a ray through the octree the way the ray-marching shader does, finding the first colour or mirror voxel
+// it enters, or the first portal whose spherical goal it touches. The direction must be normalized.
+// Unlike `first_hit_along_segment`, the ray isn't limited in length and needs no cache, so it suits one-off
+// queries such as those of tools.
+pub fn raycast(
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    octree: &[VoxelCompact],
+) -> Option<Hit> {
+    const MAXIMUM_STEPS: u32 = 1024;
+
+    let mut distance = 0.;
+    let mut boundary = 0.;
+    for _ in 0..MAXIMUM_STEPS {
+        let position = origin + direction * distance;
+        let step = if CachedCell::ROOT.contains(position) {
+            let (cell, depth) = leaf_containing(position, octree);
+            let exit = cell.exit_distance(position, direction);
+            if cell.index == NULL_VOXEL_INDEX {
+                exit
+            } else if octree[cell.index as usize].is_portal() {
+                match cell.goal_distance(position, direction) {
+                    Some(t) if t <= exit => {
+                        let normal = if distance + t > 0. {
+                            (position + direction * t - cell.center).normalize()
+                        } else {
+                            -direction
+                        };
+                        return Some(Hit {
+                            distance: distance + t,
+                            normal,
+                            depth,
+                            index: cell.index,
+                        });
                     }
-                } else {
-                    Intersection::Empty(scale)
+                    _ => exit,
                 }
             } else {
-                // The center of each sub-voxel (cell) relative to the parent.
-                // The order here must be aligned with the subvoxel-order in the `ray_march_voxels.frag` shader.
-                const CELL_CENTERS: [Vector3<f32>; 8] = [
-                    Vector3::new(-0.5, 0.5, -0.5),
-                    Vector3::new(0.5, 0.5, -0.5),
-                    Vector3::new(-0.5, -0.5, -0.5),
-                    Vector3::new(0.5, -0.5, -0.5),
-                    Vector3::new(-0.5, 0.5, 0.5),
-                    Vector3::new(0.5, 0.5, 0.5),
-                    Vector3::new(-0.5, -0.5, 0.5),
-                    Vector3::new(0.5, -0.5, 0.5),
-                ];
-
-                // Determine which sub-voxel the point is in by assigning a bit to each axis and
-                // setting its value depending on which side of the axis the point is on.
-                let cell_index = (usize::from(p.z > 0.) << 2)
-                    + (usize::from(p.y <= 0.) << 1)
-                    + usize::from(p.x > 0.);
-
-                // Rescale the point to be relative to the sub-voxel and recurse.
-                search_graph(
-                    scale + scale,
-                    iter + 1,
-                    2. * (p - CELL_CENTERS[cell_index]),
-                    voxel.children[cell_index],
-                    octree,
-                )
+                return Some(Hit {
+                    distance,
+                    normal: if distance > 0. {
+                        // The boundary crossed, rather than the sample just past it, tells which face was entered
+                        // when the ray passes close to an edge.
+                        cell.face_normal(origin + direction * boundary)
+                    } else {
+                        -direction
+                    },
+                    depth,
+                    index: cell.index,
+                });
+            }
+        } else {
+            // Skip ahead to where the ray enters the root voxel, if it does.
+            CachedCell::ROOT.entry_distance(position, direction)?
+        };
+
+        // Step just past the boundary so that the next sample is in the neighbouring voxel.
+        boundary = distance + step;
+        distance = boundary + f32::EPSILON.max(step * 1e-4);
+    }
+    None
+}
+
+// How far past the root's face the way into the world must be clear for a start outside the root, relative to the
+// root's half-size.
+const SPAWN_CLEARANCE: f32 = 0.25;
+
+// Number of points probed for roomy empty voxels to start in, and how many of the largest are tried.
+const SPAWN_PROBES: u32 = 256;
+const SPAWN_CANDIDATES: usize = 8;
+
+// The most portals considered for a start to face, and voxels searched to find them.
+const SPAWN_PORTALS: usize = 32;
+const SPAWN_SEARCHED_VOXELS: usize = 1 << 16;
+
+// Whether a ray from outside the root passes well into the world before meeting a colour or mirror voxel.
+pub fn approach_is_clear(
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    octree: &[VoxelCompact],
+) -> bool {
+    let Some(entry) = CachedCell::ROOT.entry_distance(origin, direction) else {
+        return true;
+    };
+    raycast(origin, direction, octree).is_none_or(|hit| {
+        octree[hit.index as usize].is_portal() || hit.distance >= entry + SPAWN_CLEARANCE
+    })
+}
+
+// Find a roomy start inside the world, in one of the largest empty voxels found by probing, facing a portal that
+// can be seen from it. Returns the position and the normalized direction to face, or none if no probe found an empty voxel. The probes
+// are seeded the same way each time, so a world always has the same start.
+pub fn find_spawn(octree: &[VoxelCompact]) -> Option<(Vector3<f32>, Vector3<f32>)> {
+    let mut random = StdRng::seed_from_u64(0);
+    let mut cache = TraversalCache::default();
+    let mut candidates: Vec<(Vector3<f32>, f32)> = Vec::new();
+    for _ in 0..SPAWN_PROBES {
+        let position = Vector3::new(
+            random.gen_range(-1. ..1.),
+            random.gen_range(-1. ..1.),
+            random.gen_range(-1. ..1.),
+        );
+        if let Some(open) = empty_voxel_at(position, octree, &mut cache) {
+            if candidates.iter().all(|(center, _)| *center != open.0) {
+                candidates.push(open);
             }
         }
     }
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    candidates.truncate(SPAWN_CANDIDATES);
 
-    if position.x.abs() > 1. || position.y.abs() > 1. || position.z.abs() > 1. {
-        // If the point is outside the root voxel then there cannot be an intersection.
-        Intersection::Empty(1.)
-    } else {
-        search_graph(1., 0, position, 0, octree)
+    // Face the nearest portal whose goal is the first thing seen in its direction.
+    let portals = portal_centers(octree);
+    for &(center, _) in &candidates {
+        let mut nearest: Vec<_> = portals.iter().collect();
+        nearest.sort_by(|a, b| {
+            (a.1 - center)
+                .magnitude2()
+                .total_cmp(&(b.1 - center).magnitude2())
+        });
+        for &(index, portal) in nearest {
+            let direction = (portal - center).normalize();
+            if raycast(center, direction, octree).is_some_and(|hit| hit.index == index) {
+                return Some((center, direction));
+            }
+        }
     }
+
+    // Otherwise look along whichever axis is clear the furthest.
+    let &(center, _) = candidates.first()?;
+    let clearance =
+        |direction| raycast(center, direction, octree).map_or(f32::INFINITY, |hit| hit.distance);
+    let direction = [
+        Vector3::unit_x(),
+        -Vector3::unit_x(),
+        Vector3::unit_y(),
+        -Vector3::unit_y(),
+        Vector3::unit_z(),
+        -Vector3::unit_z(),
+    ]
+    .into_iter()
+    .max_by(|&a, &b| clearance(a).total_cmp(&clearance(b)))?;
+    Some((center, direction))
 }
 
-impl RandomOctreeHelper {
-    const AFTER_PORTAL_DISTRIBUTION: [usize; 3] = [21, 2, 2];
-    const BEFORE_PORTAL_DISTRIBUTION: [usize; 2] = [9, 1];
+// The index and centre of the shallowest portals leading forward, found breadth-first from the root.
+fn portal_centers(octree: &[VoxelCompact]) -> Vec<(u32, Vector3<f32>)> {
+    let mut portals = Vec::new();
+    let mut queue = std::collections::VecDeque::from([CachedCell::ROOT]);
+    let mut searched = 0;
+    while let Some(cell) = queue.pop_front() {
+        searched += 1;
+        if searched > SPAWN_SEARCHED_VOXELS || portals.len() == SPAWN_PORTALS {
+            break;
+        }
+        let Some(voxel) = octree.get(cell.index as usize) else {
+            continue;
+        };
+        if voxel.portal_class().is_some() {
+            portals.push((cell.index, cell.center));
+        } else if voxel.flags == VoxelType::Complex as u32 {
+            for (&index, offset) in voxel.children.iter().zip(CELL_CENTERS) {
+                if index != NULL_VOXEL_INDEX {
+                    queue.push_back(CachedCell {
+                        center: cell.center + offset * cell.half_size,
+                        half_size: 0.5 * cell.half_size,
+                        index,
+                    });
+                }
+            }
+        }
+    }
+    portals
+}
 
+// Descend from the root to the voxel containing the point, which must be inside the root, stopping at the first
+// voxel which isn't subdivided as the shader does. Returns the voxel and its depth below the root.
+fn leaf_containing(position: Vector3<f32>, octree: &[VoxelCompact]) -> (CachedCell, u32) {
+    let mut cell = CachedCell::ROOT;
+    for depth in 0..MAXIMUM_VOXEL_DEPTH {
+        if cell.index == NULL_VOXEL_INDEX || octree[cell.index as usize].flags != 0 {
+            return (cell, depth);
+        }
+        cell = cell.child(position, &octree[cell.index as usize].children);
+    }
+    (cell, MAXIMUM_VOXEL_DEPTH)
+}
+
+// Traverse the octree from the deepest cached voxel containing the point, which must be inside the root.
+// Returns the intersection at the point and the voxel it was found in.
+fn traverse(
+    position: Vector3<f32>,
+    octree: &[VoxelCompact],
+    cache: &mut TraversalCache,
+) -> (Intersection, CachedCell) {
+    // Resume from the deepest cached voxel containing the point.
+    let shared_depth = cache
+        .path
+        .iter()
+        .take_while(|cell| cell.contains(position))
+        .count();
+    let mut cell = shared_depth
+        .checked_sub(1)
+        .map_or(CachedCell::ROOT, |depth| cache.path[depth]);
+    cache.path.truncate(shared_depth.saturating_sub(1));
+
+    loop {
+        // Each level of depth halves the size of a voxel, relative to the root.
+        let scale = cell.half_size.recip();
+        if cache.path.len() > MAXIMUM_VOXEL_DEPTH as usize {
+            return (Intersection::Collision, cell);
+        }
+        if cell.index == NULL_VOXEL_INDEX {
+            return (Intersection::Empty(scale), cell);
+        }
+
+        // The point relative to the current voxel, in the range [-1, 1].
+        let p = (position - cell.center) / cell.half_size;
+        let voxel = octree[cell.index as usize];
+        if voxel.flags == 1 {
+            return (Intersection::Collision, cell);
+        } else if voxel.is_portal() {
+            let intersection = if p.dot(p) > GOAL_RADIUS_SQUARED {
+                Intersection::Empty(scale)
+            } else if voxel.flags == VoxelType::ReturnPortal as u32 {
+                Intersection::ReturnPortal
+            } else {
+                // Subtract 1 from depth since this function asserts the root as depth zero, others do not.
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                Intersection::Portal {
+                    class: voxel.portal_class().unwrap_or(PortalClass::Score),
+                    depth: (scale.log2() - 1.).max(0.) as u32,
+                    index: cell.index,
+                }
+            };
+            return (intersection, cell);
+        }
+
+        // Descend into the sub-voxel, remembering the path for the next query.
+        cache.path.push(cell);
+        cell = cell.child(position, &voxel.children);
+    }
+}
+
+impl Voxel {
+    // Copy this voxel with its sub-voxels rearranged by the given permutation of cells, at every depth.
+    // Each copied voxel is given a new ID, and counted in the statistics.
+    fn permuted(&self, permutation: fn(usize) -> usize, stats: &mut OctreeStats) -> Voxel {
+        let mut children = arr![GraphRef::Empty; 8];
+        for (i, child) in self.children.iter().enumerate() {
+            children[permutation(i)] = child.permuted(permutation, stats);
+        }
+        if let VoxelType::Portal = self.vtype {
+            stats.goal_count += 1;
+        }
+        stats.voxel_count += 1;
+        Voxel {
+            average_colour: self.average_colour,
+            children,
+            vtype: self.vtype,
+            id: stats.voxel_count,
+        }
+    }
+}
+
+impl GraphRef {
+    // Copy the referenced sub-graph as `Voxel::permuted` does. References to parents are kept as they are.
+    fn permuted(&self, permutation: fn(usize) -> usize, stats: &mut OctreeStats) -> GraphRef {
+        match self {
+            GraphRef::Ref(voxel) => GraphRef::Ref(Box::new(voxel.permuted(permutation, stats))),
+            GraphRef::Recurse(n) => GraphRef::Recurse(*n),
+            GraphRef::Empty => GraphRef::Empty,
+        }
+    }
+}
+
+impl WorldGenerator {
+    pub const ALL: [WorldGenerator; 2] = [WorldGenerator::Recursive, WorldGenerator::Tiles];
+
+    pub fn name(self) -> Text {
+        match self {
+            WorldGenerator::Recursive => Text::GeneratorRecursive,
+            WorldGenerator::Tiles => Text::GeneratorTiles,
+        }
+    }
+}
+
+impl DifficultyBand {
+    pub const ALL: [DifficultyBand; 3] = [
+        DifficultyBand::Easy,
+        DifficultyBand::Medium,
+        DifficultyBand::Hard,
+    ];
+
+    pub fn name(self) -> Text {
+        match self {
+            DifficultyBand::Easy => Text::DifficultyEasy,
+            DifficultyBand::Medium => Text::DifficultyMedium,
+            DifficultyBand::Hard => Text::DifficultyHard,
+        }
+    }
+
+    // The band that a difficulty rating falls in.
+    pub fn of(rating: f32) -> Self {
+        if rating < 3.5 {
+            DifficultyBand::Easy
+        } else if rating < 4.5 {
+            DifficultyBand::Medium
+        } else {
+            DifficultyBand::Hard
+        }
+    }
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 3] = [Symmetry::Off, Symmetry::Mirror, Symmetry::Rotational];
+
+    pub fn name(self) -> Text {
+        match self {
+            Symmetry::Off => Text::SymmetryOff,
+            Symmetry::Mirror => Text::SymmetryMirror,
+            Symmetry::Rotational => Text::SymmetryRotational,
+        }
+    }
+
+    // The cells whose sub-voxels are rolled, from which the symmetry copies the rest.
+    fn rolled_cells(self) -> &'static [usize] {
+        match self {
+            Symmetry::Off => &[0, 1, 2, 3, 4, 5, 6, 7],
+            Symmetry::Mirror => &[0, 2, 4, 6],
+            Symmetry::Rotational => &[0, 2],
+        }
+    }
+
+    // Fill in the cells that weren't rolled by copying the rolled sub-voxels.
+    fn complete(self, children: &mut [GraphRef; 8], stats: &mut OctreeStats) {
+        match self {
+            Symmetry::Off => (),
+            Symmetry::Mirror => {
+                for &i in self.rolled_cells() {
+                    children[mirror_cell(i)] = children[i].permuted(mirror_cell, stats);
+                }
+            }
+            Symmetry::Rotational => {
+                // Each layer of four cells is one rolled cell followed by three quarter turns of it.
+                for &i in self.rolled_cells() {
+                    let mut cell = i;
+                    for _ in 0..3 {
+                        children[rotate_cell(cell)] = children[cell].permuted(rotate_cell, stats);
+                        cell = rotate_cell(cell);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The cell reflected across the plane `x = 0`, swapping left and right.
+fn mirror_cell(i: usize) -> usize {
+    i ^ 1
+}
+
+// The cell reflected across the plane `y = 0`, swapping top and bottom.
+fn flip_cell(i: usize) -> usize {
+    i ^ 2
+}
+
+// The cell reached by a quarter turn about the vertical axis, taking +Z to +X and +X to -Z.
+fn rotate_cell(i: usize) -> usize {
+    let (right, back) = (i & 1, (i >> 2) & 1);
+    (i & 2) | back | ((1 - right) << 2)
+}
+
+impl RandomOctreeHelper {
     // Create a new instance for generating random values for voxel generation.
     pub fn new(seed: u64) -> Self {
-        let leaf_after_portal_depth = fldr::Generator::new(&Self::AFTER_PORTAL_DISTRIBUTION);
-        let leaf_before_portal_depth = fldr::Generator::new(&Self::BEFORE_PORTAL_DISTRIBUTION);
         let fair_coin = fldr::rand::RngCoin::<StdRng>::new(StdRng::seed_from_u64(seed));
-
-        Self {
-            fair_coin,
-            leaf_after_portal_depth,
-            leaf_before_portal_depth,
-            seed,
-        }
+        Self { fair_coin, seed }
     }
 
     // Default to a simple time based seed and create instance.
@@ -397,15 +1624,25 @@ impl RandomOctreeHelper {
         f
     }
 
-    // Sample a random voxel type depending on the depth of the voxel.
-    fn sample_leaf(&mut self, depth_reached: bool) -> VoxelType {
+    // Sample a random index uniformly below the given count, which must be positive.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn sample_index(&mut self, count: usize) -> usize {
+        ((self.samplef() * count as f32) as usize).min(count - 1)
+    }
+
+    // Sample a random voxel type depending on the depth of the voxel, weighted by the theme.
+    fn sample_leaf(&mut self, style: &mut LeafStyle, depth_reached: bool) -> VoxelType {
         if depth_reached {
             const LEAVES: [VoxelType; 3] =
                 [VoxelType::Colour, VoxelType::Portal, VoxelType::Mirror];
-            LEAVES[self.leaf_after_portal_depth.sample(&mut self.fair_coin)]
+            LEAVES[style.deep_leaves.sample(&mut self.fair_coin)]
         } else {
             const LEAVES: [VoxelType; 2] = [VoxelType::Colour, VoxelType::Mirror];
-            LEAVES[self.leaf_before_portal_depth.sample(&mut self.fair_coin)]
+            LEAVES[style.shallow_leaves.sample(&mut self.fair_coin)]
         }
     }
 
@@ -421,3 +1658,150 @@ impl RandomOctreeHelper {
         self.seed
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // How far either side of a hit the point queries are made, and how many points along a ray are queried.
+    const EPSILON: f32 = 1e-3;
+    const SAMPLES: u32 = 256;
+
+    // A small world: a colour voxel filling the root's (-x, +y, -z) cell, a portal in its (+x, +y, -z) cell, and a
+    // colour voxel in the (-x, +y, -z) cell of the branch filling the root's (+x, -y, +z) cell. The rest is empty.
+    fn small_octree() -> Vec<VoxelCompact> {
+        let leaf =
+            |flags: VoxelType| VoxelCompact::new([1.; 4], [NULL_VOXEL_INDEX; 8], flags as u32);
+        let mut root = [NULL_VOXEL_INDEX; 8];
+        root[0] = 1;
+        root[1] = 2;
+        root[7] = 3;
+        let mut branch = [NULL_VOXEL_INDEX; 8];
+        branch[0] = 4;
+        vec![
+            VoxelCompact::new([1.; 4], root, VoxelType::Complex as u32),
+            leaf(VoxelType::Colour),
+            leaf(VoxelType::Portal),
+            VoxelCompact::new([1.; 4], branch, VoxelType::Complex as u32),
+            leaf(VoxelType::Colour),
+        ]
+    }
+
+    // Cast a ray and check the hit against point queries: every point before it must be empty, and the point just
+    // past it must be in the voxel that was hit, at the same scale. A miss must only pass through empty points.
+    fn raycast_agrees_with_points(
+        octree: &[VoxelCompact],
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+    ) -> Option<Hit> {
+        let direction = direction.normalize();
+        let hit = raycast(origin, direction, octree);
+        let mut cache = TraversalCache::default();
+        let clear_distance = hit.map_or(4., |hit| hit.distance - EPSILON);
+        for i in 0..SAMPLES {
+            let point = origin + direction * (clear_distance * i as f32 / SAMPLES as f32);
+            assert!(
+                matches!(
+                    octree_scale_and_collision_of_point(point, octree, &mut cache),
+                    Intersection::Empty(_)
+                ),
+                "point {point:?} before the hit isn't empty",
+            );
+        }
+
+        if let Some(hit) = hit {
+            let point = origin + direction * (hit.distance + EPSILON);
+            match traverse(point, octree, &mut cache) {
+                (Intersection::Collision, cell) => {
+                    assert_eq!(cell.index, hit.index);
+                    assert_eq!(cell.half_size, 0.5f32.powi(hit.depth as i32));
+                }
+                (Intersection::Portal { index, .. }, _) => assert_eq!(index, hit.index),
+                _ => panic!("point {point:?} past the hit isn't in a voxel"),
+            }
+        }
+        hit
+    }
+
+    #[test]
+    fn raycast_hits_shallow_voxel() {
+        let octree = small_octree();
+        let hit =
+            raycast_agrees_with_points(&octree, Vector3::new(-2., 0.5, -0.5), Vector3::unit_x())
+                .expect("ray missed the colour voxel");
+        assert_eq!(hit.index, 1);
+        assert_eq!(hit.depth, 1);
+        assert!((hit.distance - 1.).abs() < EPSILON);
+        assert_eq!(hit.normal, -Vector3::unit_x());
+    }
+
+    #[test]
+    fn raycast_hits_deep_voxel() {
+        let octree = small_octree();
+        let origin = Vector3::new(0.25, -0.25, -2.);
+        let hit = raycast_agrees_with_points(&octree, origin, Vector3::unit_z())
+            .expect("ray missed the deep colour voxel");
+        assert_eq!(hit.index, 4);
+        assert_eq!(hit.depth, 2);
+        assert!((hit.distance - 2.).abs() < EPSILON);
+        assert_eq!(hit.normal, -Vector3::unit_z());
+
+        // The empty voxel just before the hit is one of the root's cells.
+        let before = origin + Vector3::unit_z() * (hit.distance - EPSILON);
+        let mut cache = TraversalCache::default();
+        assert!(matches!(
+            octree_scale_and_collision_of_point(before, &octree, &mut cache),
+            Intersection::Empty(scale) if scale == 2.
+        ));
+    }
+
+    #[test]
+    fn raycast_hits_portal_goal() {
+        let octree = small_octree();
+        let hit =
+            raycast_agrees_with_points(&octree, Vector3::new(0.5, 0.5, -2.), Vector3::unit_z())
+                .expect("ray missed the portal's goal");
+        assert_eq!(hit.index, 2);
+        let goal_radius = GOAL_RADIUS_SQUARED.sqrt() * 0.5;
+        assert!((hit.distance - (1.5 - goal_radius)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn raycast_misses() {
+        let octree = small_octree();
+
+        // Through empty cells only, and pointing away from the root.
+        let empty =
+            raycast_agrees_with_points(&octree, Vector3::new(-0.5, -0.5, -2.), Vector3::unit_z());
+        assert!(empty.is_none());
+        let away =
+            raycast_agrees_with_points(&octree, Vector3::new(-2., 0.5, -0.5), -Vector3::unit_x());
+        assert!(away.is_none());
+    }
+
+    #[test]
+    fn raycast_grazes_faces() {
+        let octree = small_octree();
+
+        // Along the bottom face of the colour voxel, which belongs to the empty cell below it.
+        let along =
+            raycast_agrees_with_points(&octree, Vector3::new(-2., 0., -0.5), Vector3::unit_x());
+        assert!(along.is_none());
+
+        // Just above that face, hitting the colour voxel's side.
+        let above =
+            raycast_agrees_with_points(&octree, Vector3::new(-2., 1e-4, -0.5), Vector3::unit_x())
+                .expect("ray missed the colour voxel");
+        assert_eq!(above.normal, -Vector3::unit_x());
+
+        // Rising shallowly from below, entering through the bottom face.
+        let rising = raycast_agrees_with_points(
+            &octree,
+            Vector3::new(-2., -0.1, -0.5),
+            Vector3::new(1., 0.1 / 1.5, 0.),
+        )
+        .expect("ray missed the colour voxel");
+        assert_eq!(rising.index, 1);
+        assert_eq!(rising.normal, -Vector3::unit_y());
+    }
+}
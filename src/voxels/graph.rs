@@ -0,0 +1,217 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{collections::HashMap, io, path::Path};
+
+use arr_macro::arr;
+use cgmath::{Vector4, Zero};
+use serde::Deserialize;
+
+use super::{
+    compact_octree_from_root, GraphRef, OctreeStats, Voxel, VoxelCompact, VoxelType, LEAF_VOXEL,
+    MAXIMUM_VOXEL_DEPTH, MINIMUM_GOAL_DEPTH,
+};
+use crate::palette::ColorblindMode;
+
+// Hand-authored graphs are expanded into a tree of voxels, which shared nodes could make enormous.
+const MAXIMUM_GRAPH_VOXELS: u32 = 1 << 22;
+
+// The types of node a world file can describe.
+#[derive(Clone, Copy, Deserialize, PartialEq)]
+enum NodeType {
+    // A voxel divided into eight sub-voxels.
+    Complex,
+    Colour,
+    Mirror,
+    Portal,
+}
+
+// A named node of a world file. Nodes with children are complex unless given a type, and others are coloured.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Node {
+    #[serde(rename = "type")]
+    kind: Option<NodeType>,
+    colour: Option<[f32; 3]>,
+    // Names of the nodes in each cell, in the order of `CELL_CENTERS`. An empty name leaves the cell empty,
+    // and naming a node's own ancestor links back to it, recursing forever.
+    children: Option<[String; 8]>,
+}
+
+// A world file, describing a voxel graph from its root node.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Description {
+    root: String,
+    nodes: HashMap<String, Node>,
+}
+
+// A hand-authored world, checked and compacted when loaded.
+pub struct Graph {
+    octree: Vec<VoxelCompact>,
+    stats: OctreeStats,
+}
+
+impl Graph {
+    // Load a world file, failing with a description of the first problem found in it.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    // Check a world description and compact it into an octree.
+    fn parse(contents: &str) -> Result<Self, String> {
+        let description: Description = toml::from_str(contents).map_err(|e| e.to_string())?;
+        let mut stats = OctreeStats::default();
+        let root = description.expand(&description.root, &mut Vec::new(), &mut stats)?;
+        Ok(Graph {
+            octree: compact_octree_from_root(root, stats.voxel_count),
+            stats,
+        })
+    }
+}
+
+impl Description {
+    // Expand the named node into a tree of voxels below the given path of ancestors, giving each voxel an ID
+    // after those of its descendants so that the root has the largest.
+    fn expand<'a>(
+        &'a self,
+        name: &'a str,
+        path: &mut Vec<&'a str>,
+        stats: &mut OctreeStats,
+    ) -> Result<Voxel, String> {
+        let node = self
+            .nodes
+            .get(name)
+            .ok_or_else(|| format!("No node is named \"{name}\""))?;
+        let depth = path.len();
+        if depth > MAXIMUM_VOXEL_DEPTH as usize {
+            return Err(format!(
+                "Node \"{name}\" is deeper than {MAXIMUM_VOXEL_DEPTH} levels"
+            ));
+        }
+        let kind = node.kind.unwrap_or(if node.children.is_some() {
+            NodeType::Complex
+        } else {
+            NodeType::Colour
+        });
+        let colour = node
+            .colour
+            .map(|[r, g, b]| {
+                if [r, g, b].iter().all(|c| (0. ..=1.).contains(c)) {
+                    Ok(Vector4::new(r, g, b, 1.))
+                } else {
+                    Err(format!("Node \"{name}\" has a colour outside 0 to 1"))
+                }
+            })
+            .transpose()?;
+
+        let voxel = match (kind, &node.children) {
+            (NodeType::Complex, Some(names)) => {
+                path.push(name);
+                let mut children = arr![GraphRef::Empty; 8];
+                for (child, child_name) in children.iter_mut().zip(names) {
+                    if child_name.is_empty() {
+                        continue;
+                    }
+                    *child = match path.iter().rposition(|&n| n == child_name) {
+                        #[allow(clippy::cast_possible_truncation)]
+                        Some(i) => GraphRef::Recurse((path.len() - 1 - i) as u32),
+                        None => GraphRef::Ref(Box::new(self.expand(child_name, path, stats)?)),
+                    };
+                }
+                path.pop();
+
+                // Without a colour of its own, the voxel is seen from afar as the average of its sub-voxels.
+                let average_colour = colour.unwrap_or_else(|| {
+                    let (sum, count) = children
+                        .iter()
+                        .filter_map(|child| match child {
+                            GraphRef::Ref(voxel) => Some(voxel.average_colour),
+                            _ => None,
+                        })
+                        .fold((Vector4::zero(), 0.), |(sum, count), colour| {
+                            (sum + colour, count + 1.)
+                        });
+                    sum / f32::max(count, 1.)
+                });
+                Voxel {
+                    average_colour,
+                    children,
+                    vtype: VoxelType::Complex,
+                    id: 0,
+                }
+            }
+            (NodeType::Complex, None) => {
+                return Err(format!("Node \"{name}\" is complex but has no children"));
+            }
+            (_, Some(_)) => return Err(format!("Node \"{name}\" is a leaf but has children")),
+            (leaf, None) => {
+                let average_colour =
+                    colour.ok_or_else(|| format!("Node \"{name}\" is a leaf without a colour"))?;
+                let vtype = match leaf {
+                    NodeType::Portal if depth < MINIMUM_GOAL_DEPTH as usize => {
+                        return Err(format!(
+                            "Portal \"{name}\" is shallower than {MINIMUM_GOAL_DEPTH} levels"
+                        ));
+                    }
+                    NodeType::Portal => {
+                        stats.goal_count += 1;
+                        VoxelType::Portal
+                    }
+                    NodeType::Mirror => VoxelType::Mirror,
+                    _ => VoxelType::Colour,
+                };
+                Voxel {
+                    average_colour,
+                    vtype,
+                    id: 0,
+                    ..LEAF_VOXEL
+                }
+            }
+        };
+
+        stats.voxel_count += 1;
+        if stats.voxel_count > MAXIMUM_GRAPH_VOXELS {
+            return Err(format!(
+                "The world expands to more than {MAXIMUM_GRAPH_VOXELS} voxels"
+            ));
+        }
+        Ok(Voxel {
+            id: stats.voxel_count,
+            ..voxel
+        })
+    }
+}
+
+// Build a hand-authored world, with its colours remapped by the given colour-blind mode.
+pub fn world(graph: &Graph, palette: ColorblindMode) -> (Vec<VoxelCompact>, OctreeStats) {
+    let octree = graph
+        .octree
+        .iter()
+        .map(|voxel| {
+            let colour = palette.remap(voxel.average_colour.into());
+            VoxelCompact::new(colour.into(), voxel.children, voxel.flags)
+        })
+        .collect();
+    let stats = OctreeStats {
+        goal_count: graph.stats.goal_count,
+        voxel_count: graph.stats.voxel_count,
+    };
+    (octree, stats)
+}
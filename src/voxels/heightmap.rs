@@ -0,0 +1,135 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{collections::HashMap, io, path::Path};
+
+use cgmath::{Vector2, Vector3};
+
+use super::voxelize::{load_png, octree_from_leaves, Texture};
+use super::{OctreeStats, RandomOctreeHelper, VoxelCompact};
+use crate::palette::ColorblindMode;
+use crate::theme::Theme;
+
+// The depth that heightmaps are turned into terrain at, giving a column of voxels for each of 256 by 256 points.
+const TERRAIN_DEPTH: u32 = 8;
+
+// The fraction of the world's height that the highest terrain reaches, leaving room for portals above it.
+const TERRAIN_HEIGHT: f32 = 0.6;
+
+// A grayscale heightmap, where lighter is higher, and an optional colour map of the same area.
+pub struct Heightmap {
+    heights: Texture,
+    colours: Option<Texture>,
+}
+
+impl Heightmap {
+    // Load a heightmap and colour map from PNG images.
+    pub fn load(path: &Path, colour_path: Option<&Path>) -> io::Result<Self> {
+        Ok(Heightmap {
+            heights: load_png(path)?,
+            colours: colour_path.map(load_png).transpose()?,
+        })
+    }
+}
+
+// Turn a heightmap into terrain across the bottom of the world, laid out like a map with its top furthest from
+// the starting camera. Without a colour map, the terrain is coloured from the theme's lowest to highest colours
+// by altitude. Colours are remapped by the given colour-blind mode, and portals coloured by the theme float at
+// random above the terrain.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub fn terrain(
+    random: &mut RandomOctreeHelper,
+    palette: ColorblindMode,
+    theme: &Theme,
+    heightmap: &Heightmap,
+    desired_portal_count: u32,
+) -> (Vec<VoxelCompact>, OctreeStats) {
+    let grid_size = 1 << TERRAIN_DEPTH;
+    let uv = |x: usize, z: usize| {
+        Vector2::new(
+            (x as f32 + 0.5) / grid_size as f32,
+            1. - (z as f32 + 0.5) / grid_size as f32,
+        )
+    };
+
+    // The number of voxels in each column, at least one. Points beyond the edges have none.
+    let top_voxels = grid_size as f32 * TERRAIN_HEIGHT;
+    let heights: Vec<usize> = (0..grid_size * grid_size)
+        .map(|i| {
+            let value = heightmap.heights.sample(uv(i % grid_size, i / grid_size));
+            1 + ((value.x + value.y + value.z) / 3. * (top_voxels - 1.)) as usize
+        })
+        .collect();
+    let height = |x: usize, z: usize| {
+        if x < grid_size && z < grid_size {
+            heights[x + z * grid_size]
+        } else {
+            0
+        }
+    };
+
+    // Fill each column from its top down to the top of its lowest neighbour, so that cliffs are solid without
+    // filling in the ground below them.
+    let (low, high) = (
+        Vector3::from(theme.colour_low),
+        Vector3::from(theme.colour_high),
+    );
+    let mut leaves = HashMap::new();
+    for z in 0..grid_size {
+        for x in 0..grid_size {
+            let top = height(x, z);
+            let bottom = [
+                height(x.wrapping_sub(1), z),
+                height(x + 1, z),
+                height(x, z.wrapping_sub(1)),
+                height(x, z + 1),
+            ]
+            .into_iter()
+            .min()
+            .unwrap_or_default()
+            .min(top - 1);
+            let colour = match &heightmap.colours {
+                Some(colours) => colours.sample(uv(x, z)),
+                None => (low + (high - low) * (top as f32 / top_voxels)).extend(1.),
+            };
+            for y in bottom..top {
+                leaves.insert([x, y, z], colour);
+            }
+        }
+    }
+
+    // Float portals at random points at least a voxel above the terrain.
+    let mut portals = Vec::new();
+    while portals.len() < desired_portal_count as usize {
+        let (x, z) = (
+            random.sample_index(grid_size),
+            random.sample_index(grid_size),
+        );
+        let lowest = height(x, z) + 1;
+        let portal = [x, lowest + random.sample_index(grid_size - lowest), z];
+        if !portals.contains(&portal) {
+            portals.push(portal);
+        }
+    }
+
+    octree_from_leaves(random, palette, theme, TERRAIN_DEPTH, leaves, &portals)
+}
@@ -0,0 +1,80 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::HashMap;
+
+use cgmath::Vector3;
+
+use super::voxelize::octree_from_leaves;
+use super::{OctreeStats, RandomOctreeHelper, VoxelCompact, MINIMUM_GOAL_DEPTH};
+use crate::palette::ColorblindMode;
+use crate::theme::Theme;
+
+// The depth that the tutorial world is built at, the shallowest which can hold portals.
+const TUTORIAL_DEPTH: u32 = MINIMUM_GOAL_DEPTH;
+
+// The tower holding the portal, ahead and to the side of the starting camera so that reaching it takes a turn.
+// Its footprint is centred on the given column, and its top is a little below the starting altitude.
+const TOWER_COLUMN: [usize; 2] = [44, 44];
+const TOWER_HALF_WIDTH: usize = 1;
+const TOWER_TOP: usize = 36;
+
+// Squares of the checkered floor are this many voxels wide, giving a sense of speed and height.
+const FLOOR_SQUARE: usize = 8;
+
+// A small world for learning to fly in: a checkered floor and a single tower with a cluster of portals on top,
+// which leads on to the first generated world. Colours come from the theme and are remapped by the given
+// colour-blind mode.
+pub fn world(
+    random: &mut RandomOctreeHelper,
+    palette: ColorblindMode,
+    theme: &Theme,
+) -> (Vec<VoxelCompact>, OctreeStats) {
+    let grid_size = 1 << TUTORIAL_DEPTH;
+    let (low, high) = (
+        Vector3::from(theme.colour_low),
+        Vector3::from(theme.colour_high),
+    );
+    let mut leaves = HashMap::new();
+
+    // Alternate the floor's squares between the theme's lowest colour and a blend towards its highest.
+    for z in 0..grid_size {
+        for x in 0..grid_size {
+            let light = (x / FLOOR_SQUARE + z / FLOOR_SQUARE) & 1 == 0;
+            let colour = if light { low + (high - low) * 0.5 } else { low };
+            leaves.insert([x, 0, z], colour.extend(1.));
+        }
+    }
+
+    // Raise the tower from the floor in the theme's highest colour, and fill the space above it with portals.
+    let [column_x, column_z] = TOWER_COLUMN;
+    let footprint = |centre: usize| centre - TOWER_HALF_WIDTH..=centre + TOWER_HALF_WIDTH;
+    let mut portals = Vec::new();
+    for z in footprint(column_z) {
+        for x in footprint(column_x) {
+            for y in 1..TOWER_TOP {
+                leaves.insert([x, y, z], high.extend(1.));
+            }
+            for y in footprint(TOWER_TOP + TOWER_HALF_WIDTH) {
+                portals.push([x, y, z]);
+            }
+        }
+    }
+
+    octree_from_leaves(random, palette, theme, TUTORIAL_DEPTH, leaves, &portals)
+}
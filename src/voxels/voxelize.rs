@@ -0,0 +1,641 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+};
+
+use arr_macro::arr;
+use cgmath::{ElementWise, InnerSpace, Matrix4, SquareMatrix, Vector2, Vector3, Vector4, Zero};
+
+use super::{
+    compact_octree_from_root, complex_voxel, tile_cell_position, GraphRef, LeafStyle, OctreeStats,
+    RandomOctreeHelper, Voxel, VoxelCompact, VoxelType, LEAF_VOXEL,
+};
+use crate::palette::ColorblindMode;
+use crate::theme::Theme;
+
+// The depths that models can be voxelized at. Portals are placed at the same depth, so it must be deep enough
+// for them to score.
+pub const MODEL_DEPTHS: std::ops::RangeInclusive<u32> = 7..=9;
+pub const DEFAULT_MODEL_DEPTH: u32 = 8;
+
+// The fraction of the world's width that a model is scaled to fill.
+const MODEL_FILL: f32 = 0.9;
+
+const WHITE: Vector4<f32> = Vector4::new(1., 1., 1., 1.);
+
+// A texture's pixels, in rows from the top.
+pub(super) struct Texture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vector4<f32>>,
+}
+
+// The colour of a surface, multiplied by its texture if it has one.
+struct Material {
+    colour: Vector4<f32>,
+    texture: Option<Texture>,
+}
+
+struct Triangle {
+    positions: [Vector3<f32>; 3],
+    colours: [Vector4<f32>; 3],
+    uvs: [Vector2<f32>; 3],
+    material: usize,
+}
+
+// The triangles of a model and their materials, the first of which is plain white.
+pub struct Mesh {
+    triangles: Vec<Triangle>,
+    materials: Vec<Material>,
+}
+
+// A model loaded to be flown through, and the depth to voxelize it at.
+pub struct Model {
+    pub mesh: Mesh,
+    pub depth: u32,
+}
+
+impl Model {
+    // Load an OBJ or glTF model, chosen by the file's extension. The depth is clamped to `MODEL_DEPTHS`.
+    pub fn load(path: &Path, depth: u32) -> io::Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let mesh = match extension.as_str() {
+            "obj" => load_obj(path)?,
+            "gltf" | "glb" => load_gltf(path)?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Models must be OBJ or glTF files",
+                ))
+            }
+        };
+        if mesh.triangles.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The model has no triangles",
+            ));
+        }
+        Ok(Model {
+            mesh,
+            depth: depth.clamp(*MODEL_DEPTHS.start(), *MODEL_DEPTHS.end()),
+        })
+    }
+}
+
+impl Default for Mesh {
+    fn default() -> Self {
+        Mesh {
+            triangles: Vec::new(),
+            materials: vec![Material {
+                colour: WHITE,
+                texture: None,
+            }],
+        }
+    }
+}
+
+impl Mesh {
+    // The colour of a triangle at the given barycentric coordinates.
+    fn colour(&self, triangle: &Triangle, weights: Vector3<f32>) -> Vector4<f32> {
+        let material = &self.materials[triangle.material];
+        let colour = (triangle.colours[0] * weights.x
+            + triangle.colours[1] * weights.y
+            + triangle.colours[2] * weights.z)
+            .mul_element_wise(material.colour);
+        match &material.texture {
+            Some(texture) => colour.mul_element_wise(texture.sample(
+                triangle.uvs[0] * weights.x
+                    + triangle.uvs[1] * weights.y
+                    + triangle.uvs[2] * weights.z,
+            )),
+            None => colour,
+        }
+    }
+}
+
+impl Texture {
+    // Read a texture from 8-bit pixels with the given number of channels, ignoring transparency.
+    fn from_bytes(width: usize, height: usize, channels: usize, bytes: &[u8]) -> Self {
+        let pixels = bytes
+            .chunks_exact(channels)
+            .map(|pixel| {
+                let value = |i: usize| f32::from(pixel[i]) / 255.;
+                if channels < 3 {
+                    Vector4::new(value(0), value(0), value(0), 1.)
+                } else {
+                    Vector4::new(value(0), value(1), value(2), 1.)
+                }
+            })
+            .collect();
+        Texture {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // The nearest pixel to the given texture coordinates, repeating the texture outside of `[0, 1)`.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub(super) fn sample(&self, uv: Vector2<f32>) -> Vector4<f32> {
+        if self.pixels.len() < self.width * self.height || self.width == 0 || self.height == 0 {
+            return WHITE;
+        }
+        let x = ((uv.x.rem_euclid(1.) * self.width as f32) as usize).min(self.width - 1);
+        let y = ((uv.y.rem_euclid(1.) * self.height as f32) as usize).min(self.height - 1);
+        self.pixels[y * self.width + x]
+    }
+}
+
+// Load a Wavefront OBJ model, with the diffuse colours and PNG textures of its MTL materials, and any vertex
+// colours written after the vertex positions.
+fn load_obj(path: &Path) -> io::Result<Mesh> {
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let mut mesh = Mesh::default();
+    let mut material_names = HashMap::new();
+    let mut material = 0;
+    let (mut positions, mut colours, mut uvs) = (Vec::new(), Vec::new(), Vec::new());
+    for line in std::fs::read_to_string(path)?.lines() {
+        let (keyword, rest) = split_keyword(line);
+        let numbers: Vec<f32> = rest
+            .split_whitespace()
+            .filter_map(|word| word.parse().ok())
+            .collect();
+        match keyword {
+            "v" if numbers.len() >= 3 => {
+                positions.push(Vector3::new(numbers[0], numbers[1], numbers[2]));
+                colours.push(if numbers.len() >= 6 {
+                    Vector4::new(numbers[3], numbers[4], numbers[5], 1.)
+                } else {
+                    WHITE
+                });
+            }
+
+            // Texture rows are stored from the top, but OBJ texture coordinates count from the bottom.
+            "vt" if numbers.len() >= 2 => uvs.push(Vector2::new(numbers[0], 1. - numbers[1])),
+
+            // Faces with more than three corners are split into a fan of triangles.
+            "f" => {
+                let corners = rest
+                    .split_whitespace()
+                    .map(|corner| obj_corner(corner, positions.len(), uvs.len()))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, format!("Invalid face: {line}"))
+                    })?;
+                for i in 1..corners.len().saturating_sub(1) {
+                    let corners = [corners[0], corners[i], corners[i + 1]];
+                    mesh.triangles.push(Triangle {
+                        positions: corners.map(|(position, _)| positions[position]),
+                        colours: corners.map(|(position, _)| colours[position]),
+                        uvs: corners.map(|(_, uv)| uv.map_or(Vector2::zero(), |uv| uvs[uv])),
+                        material,
+                    });
+                }
+            }
+
+            // A model whose materials can't be read is still loaded, in white.
+            "mtllib" => {
+                let mtl_path = directory.join(rest);
+                if let Err(e) = load_mtl(&mtl_path, &mut mesh.materials, &mut material_names) {
+                    log::warn!(target: "world", "Failed to load materials {}: {e}", mtl_path.display());
+                }
+            }
+            "usemtl" => material = material_names.get(rest).copied().unwrap_or(0),
+            _ => (),
+        }
+    }
+    Ok(mesh)
+}
+
+// Split the keyword from the start of a line of an OBJ or MTL file.
+fn split_keyword(line: &str) -> (&str, &str) {
+    let line = line.trim();
+    let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    (keyword, rest.trim())
+}
+
+// The position and texture-coordinate indices of a face's corner, written as `v`, `v/vt`, `v//vn`, or
+// `v/vt/vn`. Indices count from one, or back from the latest when negative.
+fn obj_corner(
+    corner: &str,
+    position_count: usize,
+    uv_count: usize,
+) -> Option<(usize, Option<usize>)> {
+    let resolve = |index: &str, count: usize| {
+        let index: isize = index.parse().ok()?;
+        let resolved = if index < 0 {
+            count.checked_sub(index.unsigned_abs())?
+        } else {
+            usize::try_from(index).ok()?.checked_sub(1)?
+        };
+        (resolved < count).then_some(resolved)
+    };
+    let mut indices = corner.split('/');
+    let position = resolve(indices.next()?, position_count)?;
+    let uv = match indices.next() {
+        None | Some("") => None,
+        Some(index) => Some(resolve(index, uv_count)?),
+    };
+    Some((position, uv))
+}
+
+// Add the materials of an MTL file, with their diffuse colours and PNG textures.
+fn load_mtl(
+    path: &Path,
+    materials: &mut Vec<Material>,
+    names: &mut HashMap<String, usize>,
+) -> io::Result<()> {
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let mut current = None;
+    for line in std::fs::read_to_string(path)?.lines() {
+        let (keyword, rest) = split_keyword(line);
+        match (keyword, current) {
+            ("newmtl", _) => {
+                names.insert(rest.to_owned(), materials.len());
+                current = Some(materials.len());
+                materials.push(Material {
+                    colour: WHITE,
+                    texture: None,
+                });
+            }
+            ("Kd", Some(i)) => {
+                let rgb: Vec<f32> = rest
+                    .split_whitespace()
+                    .filter_map(|word| word.parse().ok())
+                    .collect();
+                if let [r, g, b, ..] = rgb[..] {
+                    materials[i].colour = Vector4::new(r, g, b, 1.);
+                }
+            }
+
+            // Options may come before the texture's file name, which is last.
+            ("map_Kd", Some(i)) => {
+                let file = rest.split_whitespace().last().unwrap_or_default();
+                match load_png(&directory.join(file)) {
+                    Ok(texture) => materials[i].texture = Some(texture),
+                    Err(e) => log::warn!(target: "world", "Failed to load texture {file}: {e}"),
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+// Load a PNG image as a texture.
+pub(super) fn load_png(path: &Path) -> io::Result<Texture> {
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(io::Error::other)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(io::Error::other)?;
+    Ok(Texture::from_bytes(
+        info.width as usize,
+        info.height as usize,
+        info.color_type.samples(),
+        &buffer[..info.buffer_size()],
+    ))
+}
+
+// Load the default scene of a glTF model, with its vertex colours and the base colours and 8-bit textures of
+// its materials.
+fn load_gltf(path: &Path) -> io::Result<Mesh> {
+    let (document, buffers, images) = gltf::import(path).map_err(io::Error::other)?;
+    let mut mesh = Mesh::default();
+
+    // The model's materials follow the default one.
+    for material in document.materials() {
+        let pbr = material.pbr_metallic_roughness();
+        let texture = pbr.base_color_texture().and_then(|info| {
+            let image = images.get(info.texture().source().index())?;
+            let channels = match image.format {
+                gltf::image::Format::R8 => 1,
+                gltf::image::Format::R8G8 => 2,
+                gltf::image::Format::R8G8B8 => 3,
+                gltf::image::Format::R8G8B8A8 => 4,
+                _ => return None,
+            };
+            Some(Texture::from_bytes(
+                image.width as usize,
+                image.height as usize,
+                channels,
+                &image.pixels,
+            ))
+        });
+        mesh.materials.push(Material {
+            colour: pbr.base_color_factor().into(),
+            texture,
+        });
+    }
+
+    let Some(scene) = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    else {
+        return Ok(mesh);
+    };
+    let mut nodes: Vec<_> = scene
+        .nodes()
+        .map(|node| (node, Matrix4::identity()))
+        .collect();
+    while let Some((node, parent_transform)) = nodes.pop() {
+        let transform = parent_transform * Matrix4::from(node.transform().matrix());
+        nodes.extend(node.children().map(|child| (child, transform)));
+        let Some(node_mesh) = node.mesh() else {
+            continue;
+        };
+        for primitive in node_mesh
+            .primitives()
+            .filter(|primitive| primitive.mode() == gltf::mesh::Mode::Triangles)
+        {
+            let reader = primitive.reader(|buffer| Some(buffers[buffer.index()].0.as_slice()));
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let positions: Vec<Vector3<f32>> = positions
+                .map(|position| (transform * Vector3::from(position).extend(1.)).truncate())
+                .collect();
+            let colours: Vec<Vector4<f32>> = reader
+                .read_colors(0)
+                .map(|colours| colours.into_rgba_f32().map(Vector4::from).collect())
+                .unwrap_or_default();
+            let uvs: Vec<Vector2<f32>> = reader
+                .read_tex_coords(0)
+                .map(|uvs| uvs.into_f32().map(Vector2::from).collect())
+                .unwrap_or_default();
+            let indices: Vec<usize> = reader.read_indices().map_or_else(
+                || (0..positions.len()).collect(),
+                |indices| indices.into_u32().map(|i| i as usize).collect(),
+            );
+            let material = primitive.material().index().map_or(0, |i| i + 1);
+            for corners in indices.chunks_exact(3) {
+                if corners.iter().any(|&i| i >= positions.len()) {
+                    continue;
+                }
+                mesh.triangles.push(Triangle {
+                    positions: [0, 1, 2].map(|i| positions[corners[i]]),
+                    colours: [0, 1, 2].map(|i| colours.get(corners[i]).copied().unwrap_or(WHITE)),
+                    uvs: [0, 1, 2].map(|i| uvs.get(corners[i]).copied().unwrap_or(Vector2::zero())),
+                    material,
+                });
+            }
+        }
+    }
+    Ok(mesh)
+}
+
+// Voxelize the surface of a model into a voxel-octree, scaled to fill the world and turned so that its front
+// faces the starting camera. Each voxel takes the average colour of the triangles passing through it, remapped
+// by the given colour-blind mode, and portals coloured by the theme are stood on the model at random.
+pub fn voxelize(
+    random: &mut RandomOctreeHelper,
+    palette: ColorblindMode,
+    theme: &Theme,
+    model: &Model,
+    desired_portal_count: u32,
+) -> (Vec<VoxelCompact>, OctreeStats) {
+    // Fit the model's bounds inside the world, turning it half a turn about the vertical axis.
+    let (min, max) = model
+        .mesh
+        .triangles
+        .iter()
+        .flat_map(|triangle| triangle.positions)
+        .fold(
+            (Vector3::from([f32::MAX; 3]), Vector3::from([f32::MIN; 3])),
+            |(min, max), p| {
+                (
+                    Vector3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                    Vector3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+                )
+            },
+        );
+    let (centre, size) = ((min + max) / 2., max - min);
+    let scale = 2. * MODEL_FILL / size.x.max(size.y).max(size.z).max(f32::EPSILON);
+    let positions: Vec<[Vector3<f32>; 3]> = model
+        .mesh
+        .triangles
+        .iter()
+        .map(|triangle| {
+            triangle.positions.map(|p| {
+                let p = (p - centre) * scale;
+                Vector3::new(-p.x, p.y, -p.z)
+            })
+        })
+        .collect();
+
+    // Find the leaves that the triangles pass through, and their colours.
+    let mut leaves = HashMap::new();
+    let triangles: Vec<usize> = (0..positions.len()).collect();
+    surface_leaves(
+        &model.mesh,
+        &positions,
+        &triangles,
+        model.depth,
+        0,
+        [0; 3],
+        &mut leaves,
+    );
+
+    // Stand portals on leaves with room above them, choosing from the leaves in a repeatable order.
+    let grid_size = 1 << model.depth;
+    let is_clear = |[x, y, z]: [usize; 3]| y < grid_size && !leaves.contains_key(&[x, y, z]);
+    let mut candidates: Vec<[usize; 3]> = leaves
+        .keys()
+        .copied()
+        .filter(|&[x, y, z]| is_clear([x, y + 1, z]) && is_clear([x, y + 2, z]))
+        .collect();
+    candidates.sort_unstable();
+    let mut portals = Vec::new();
+    while portals.len() < desired_portal_count as usize && !candidates.is_empty() {
+        let [x, y, z] = candidates.swap_remove(random.sample_index(candidates.len()));
+        portals.push([x, y + 1, z]);
+    }
+
+    octree_from_leaves(random, palette, theme, model.depth, leaves, &portals)
+}
+
+// Build a voxel-octree from leaves at the given depth, with their colours remapped by the given colour-blind
+// mode, and portals coloured by the theme at the given positions.
+pub(super) fn octree_from_leaves(
+    random: &mut RandomOctreeHelper,
+    palette: ColorblindMode,
+    theme: &Theme,
+    depth: u32,
+    leaves: HashMap<[usize; 3], Vector4<f32>>,
+    portals: &[[usize; 3]],
+) -> (Vec<VoxelCompact>, OctreeStats) {
+    // Create the leaves, then group each level's voxels under their parents up to the root.
+    let style = LeafStyle::new(palette, theme);
+    let mut stats = OctreeStats::default();
+    let mut level = HashMap::new();
+    for (position, colour) in leaves {
+        stats.voxel_count += 1;
+        let voxel = Voxel {
+            average_colour: palette.remap(colour),
+            vtype: VoxelType::Colour,
+            id: stats.voxel_count,
+            ..LEAF_VOXEL
+        };
+        level.insert(position, GraphRef::Ref(Box::new(voxel)));
+    }
+    for &position in portals {
+        stats.voxel_count += 1;
+        stats.goal_count += 1;
+        let voxel = Voxel {
+            average_colour: style.random_colour(random),
+            vtype: VoxelType::Portal,
+            id: stats.voxel_count,
+            ..LEAF_VOXEL
+        };
+        level.insert(position, GraphRef::Ref(Box::new(voxel)));
+    }
+    for _ in 0..depth {
+        let mut parents = HashMap::new();
+        for ([x, y, z], voxel) in level {
+            let cell = (x & 1) | ((1 - (y & 1)) << 1) | ((z & 1) << 2);
+            parents
+                .entry([x / 2, y / 2, z / 2])
+                .or_insert_with(|| arr![GraphRef::Empty; 8])[cell] = voxel;
+        }
+        level = parents
+            .into_iter()
+            .map(|(position, children)| (position, complex_voxel(children, &mut stats)))
+            .collect();
+    }
+
+    // A world without leaves, such as a model with invalid positions, becomes a single voxel.
+    let root = match level.remove(&[0; 3]) {
+        Some(GraphRef::Ref(root)) => *root,
+        _ => {
+            stats = OctreeStats {
+                goal_count: 0,
+                voxel_count: 1,
+            };
+            Voxel {
+                average_colour: palette.remap(WHITE),
+                id: 1,
+                ..LEAF_VOXEL
+            }
+        }
+    };
+    (compact_octree_from_root(root, stats.voxel_count), stats)
+}
+
+// Collect the leaves at the given depth that the given triangles pass through, below the voxel at the given
+// depth and grid position, with the average colour of those triangles.
+fn surface_leaves(
+    mesh: &Mesh,
+    positions: &[[Vector3<f32>; 3]],
+    triangles: &[usize],
+    leaf_depth: u32,
+    depth: u32,
+    position: [usize; 3],
+    leaves: &mut HashMap<[usize; 3], Vector4<f32>>,
+) {
+    #[allow(clippy::cast_precision_loss)]
+    let half_size = 1. / (1 << depth) as f32;
+    #[allow(clippy::cast_precision_loss)]
+    let centre = Vector3::from(position.map(|x| (2 * x + 1) as f32 * half_size - 1.));
+    let triangles: Vec<usize> = triangles
+        .iter()
+        .copied()
+        .filter(|&i| triangle_overlaps_box(&positions[i], centre, half_size))
+        .collect();
+    if triangles.is_empty() {
+        return;
+    }
+
+    if depth == leaf_depth {
+        let sum = triangles.iter().fold(Vector4::zero(), |sum, &i| {
+            sum + mesh.colour(&mesh.triangles[i], barycentric(&positions[i], centre))
+        });
+        #[allow(clippy::cast_precision_loss)]
+        leaves.insert(position, sum / triangles.len() as f32);
+        return;
+    }
+    for i in 0..8 {
+        let offset = tile_cell_position(i);
+        surface_leaves(
+            mesh,
+            positions,
+            &triangles,
+            leaf_depth,
+            depth + 1,
+            [0, 1, 2].map(|axis| 2 * position[axis] + offset[axis]),
+            leaves,
+        );
+    }
+}
+
+// Whether a triangle overlaps the cube with the given centre and half-size, by the separating axis theorem.
+fn triangle_overlaps_box(
+    triangle: &[Vector3<f32>; 3],
+    centre: Vector3<f32>,
+    half_size: f32,
+) -> bool {
+    let v = triangle.map(|p| p - centre);
+    let separates = |axis: Vector3<f32>| {
+        let radius = half_size * (axis.x.abs() + axis.y.abs() + axis.z.abs());
+        let projections = v.map(|p| axis.dot(p));
+        projections.iter().all(|&p| p > radius) || projections.iter().all(|&p| p < -radius)
+    };
+
+    // The cube's face normals, the triangle's normal, and the cross products of the cube's edges with the
+    // triangle's.
+    let edges = [v[1] - v[0], v[2] - v[1], v[0] - v[2]];
+    let axes = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+    !(axes.into_iter().any(separates)
+        || separates(edges[0].cross(edges[1]))
+        || edges
+            .into_iter()
+            .any(|edge| axes.into_iter().any(|axis| separates(axis.cross(edge)))))
+}
+
+// The barycentric coordinates of the point on a triangle's plane nearest to the given point, clamped to lie
+// within the triangle.
+fn barycentric(triangle: &[Vector3<f32>; 3], point: Vector3<f32>) -> Vector3<f32> {
+    let (v0, v1, v2) = (
+        triangle[1] - triangle[0],
+        triangle[2] - triangle[0],
+        point - triangle[0],
+    );
+    let (d00, d01, d11) = (v0.dot(v0), v0.dot(v1), v1.dot(v1));
+    let (d20, d21) = (v2.dot(v0), v2.dot(v1));
+    let denominator = d00 * d11 - d01 * d01;
+    if denominator.abs() <= f32::EPSILON * d00 * d11 {
+        return Vector3::new(1., 1., 1.) / 3.;
+    }
+    let v = (d11 * d20 - d01 * d21) / denominator;
+    let w = (d00 * d21 - d01 * d20) / denominator;
+    let weights = Vector3::new(1. - v - w, v, w).map(|x| x.max(0.));
+    weights / (weights.x + weights.y + weights.z)
+}
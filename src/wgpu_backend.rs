@@ -0,0 +1,1393 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use egui_wgpu::renderer::ScreenDescriptor;
+use winit::{
+    event::WindowEvent,
+    event_loop::{EventLoop, EventLoopWindowTarget},
+    window::{Window, WindowBuilder, WindowId},
+};
+
+use crate::hazards::WorldHazards;
+use crate::lighting::WorldLights;
+use crate::particles::ParticleInstance;
+use crate::renderer::{
+    self, CapturedFrame, EyeView, FrameUniforms, MapUniforms, PixelOrder, Renderer, SceneHistory,
+};
+use crate::trail::TrailVertex;
+use crate::voxels::VoxelCompact;
+
+const CAPTURE_BUFFER_COUNT: usize = 3;
+
+// SPIR-V compiled by the build script from the same GLSL as the Vulkan backend, with `WGPU` defined.
+const ENTIRE_VIEW_VS: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/entire_view.vert.spv"));
+const OCTREE_MAP_FS: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/octree_map.frag.spv"));
+const RAY_MARCH_VOXELS_FS: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/ray_march_voxels.frag.spv"));
+const CHECKERBOARD_RESOLVE_FS: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/checkerboard_resolve.frag.spv"));
+const SPEED_LINES_FS: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/speed_lines.frag.spv"));
+
+// Format of the distance to the surface seen by each pixel, kept for temporal reprojection.
+const DISTANCE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+
+// wgpu implementation of the app renderer, for platforms where Vulkan is unavailable.
+pub struct WgpuRenderer {
+    // The surface must be dropped before the window it draws to.
+    surface: wgpu::Surface,
+    window: Window,
+
+    bind_group: wgpu::BindGroup,
+    bind_group_layout: wgpu::BindGroupLayout,
+    capture: FrameCapture,
+    checkerboard: Option<CheckerboardTarget>,
+    config: wgpu::SurfaceConfiguration,
+    device: wgpu::Device,
+    frame: u32,
+    gpu_names: Vec<String>,
+    hazards_buffer: wgpu::Buffer,
+    history: Option<SceneHistory>,
+    history_layout: wgpu::BindGroupLayout,
+    history_sampler: wgpu::Sampler,
+    lights_buffer: wgpu::Buffer,
+    map_buffer: wgpu::Buffer,
+    map_layout: wgpu::BindGroupLayout,
+    map_pipeline: wgpu::RenderPipeline,
+    minimap: Option<Minimap>,
+    pipeline: wgpu::RenderPipeline,
+    queue: wgpu::Queue,
+    render_scale: f32,
+    resolve_buffer: wgpu::Buffer,
+    resolve_layout: wgpu::BindGroupLayout,
+    resolve_pipeline: wgpu::RenderPipeline,
+    scenes: [SceneTarget; 2],
+    scene_sampler: wgpu::Sampler,
+    speed_lines_buffer: wgpu::Buffer,
+    speed_lines_layout: wgpu::BindGroupLayout,
+    speed_lines_pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    voxel_buffer: wgpu::Buffer,
+
+    egui_ctx: egui::Context,
+    egui_renderer: egui_wgpu::Renderer,
+    egui_winit: egui_winit::State,
+    gui_active: bool,
+}
+
+// Buffers that rendered frames are copied into when capturing. Each frame is read back once its buffer
+// has been mapped, so that capturing doesn't stall rendering.
+struct FrameCapture {
+    enabled: bool,
+    next: usize,
+    order: Option<PixelOrder>,
+    ready: Vec<CapturedFrame>,
+    slots: [Option<CaptureSlot>; CAPTURE_BUFFER_COUNT],
+}
+
+// A capture buffer, the size of the frame copied into it, and whether a frame is waiting to be read back.
+// Rows in the buffer are padded to the alignment wgpu requires for copies.
+struct CaptureSlot {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_row: u32,
+    mapped: Arc<AtomicBool>,
+    pending: bool,
+}
+
+// The texture that the scene is rendered to before the speed-lines pass reads it, and the bind group reading it.
+// The distance to the surface seen by each pixel is rendered alongside, and both are read through the history
+// bind group by the next frame to reproject.
+struct SceneTarget {
+    view: wgpu::TextureView,
+    distance_view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    history_bind_group: wgpu::BindGroup,
+}
+
+// The textures that half of the scene is ray-marched into when rendering in a checkerboard pattern, and the
+// bind group reading them for the resolve pass to fill the scene from.
+struct CheckerboardTarget {
+    view: wgpu::TextureView,
+    distance_view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+// The texture the minimap is ray-marched into, the bind group reading the voxel-octree for it, the overlay's
+// handle to the texture, and whether it is due to be redrawn.
+struct Minimap {
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    size: u32,
+    texture: egui::TextureId,
+    redraw: bool,
+}
+
+// Values read by the checkerboard-resolve shader, matching its `Push` block padded to 16 bytes.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ResolveUniforms {
+    frame: u32,
+    _padding: [u32; 3],
+}
+
+// Values read by the speed-lines shader, matching its `Push` block.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SpeedLinesUniforms {
+    time: f32,
+    aspect_ratio: f32,
+    strength: f32,
+    flash: f32,
+    score_flash: f32,
+    _padding: [f32; 3],
+}
+
+// The tessellated overlay of a frame and the textures to free once it has been drawn.
+struct GuiFrame {
+    paint_jobs: Vec<egui::ClippedPrimitive>,
+    screen: ScreenDescriptor,
+    free: Vec<egui::TextureId>,
+}
+
+impl WgpuRenderer {
+    // Setup is asynchronous because browsers can't block while waiting for the GPU.
+    pub async fn new(
+        event_loop: &EventLoop<()>,
+        title: &str,
+        octree: &[VoxelCompact],
+        lights: &WorldLights,
+        hazards: &WorldHazards,
+        gpu: Option<&str>,
+        samples: u32,
+    ) -> Self {
+        if samples > 1 {
+            log::warn!(target: "gpu", "Multisampling is only available with the Vulkan backend");
+        }
+        let window = WindowBuilder::new()
+            .with_title(title)
+            .build(event_loop)
+            .expect("Failed to create window.");
+
+        // In a browser the window is a canvas, which must be added to the page and sized to fill it.
+        #[cfg(target_arch = "wasm32")]
+        attach_canvas(&window);
+
+        // Create a surface for the window using whichever graphics API is available.
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        // SAFETY: The surface is dropped before the window, which is stored alongside it.
+        let surface =
+            unsafe { instance.create_surface(&window) }.expect("Failed to create surface.");
+
+        // List the adapters which can present to the surface. Browsers only expose the one they choose.
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut adapters: Vec<_> = instance
+            .enumerate_adapters(wgpu::Backends::all())
+            .filter(|adapter| adapter.is_surface_supported(&surface))
+            .collect();
+        #[cfg(target_arch = "wasm32")]
+        let mut adapters = Vec::new();
+        let gpu_names = adapters
+            .iter()
+            .map(|adapter| adapter.get_info().name)
+            .collect();
+
+        // Use the requested adapter if available, otherwise prefer a high-performance one.
+        let requested = gpu.and_then(|gpu| {
+            let index = adapters
+                .iter()
+                .position(|adapter| adapter.get_info().name == gpu);
+            if index.is_none() {
+                log::warn!(target: "gpu", "Requested GPU is unavailable name=\"{gpu}\"");
+            }
+            index.map(|i| adapters.swap_remove(i))
+        });
+        let adapter = match requested {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::HighPerformance,
+                    compatible_surface: Some(&surface),
+                    force_fallback_adapter: false,
+                })
+                .await
+                .expect("Failed to find a suitable GPU adapter."),
+        };
+        let adapter_info = adapter.get_info();
+        log::info!(
+            target: "gpu",
+            "Using device name=\"{}\" api={:?}",
+            adapter_info.name,
+            adapter_info.backend
+        );
+        crate::crash::set_device_name(&adapter_info.name);
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: None,
+                    features: wgpu::Features::empty(),
+                    limits: wgpu::Limits::default(),
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create device.");
+
+        // Configure the surface, preferring the same present mode as the Vulkan backend.
+        // Frames can only be captured when the surface allows copying from its textures.
+        let capabilities = surface.get_capabilities(&adapter);
+        let size = window.inner_size();
+        let capturable = capabilities.usages.contains(wgpu::TextureUsages::COPY_SRC);
+        let config = wgpu::SurfaceConfiguration {
+            usage: if capturable {
+                wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC
+            } else {
+                wgpu::TextureUsages::RENDER_ATTACHMENT
+            },
+            format: capabilities.formats[0],
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: if capabilities
+                .present_modes
+                .contains(&wgpu::PresentMode::Mailbox)
+            {
+                wgpu::PresentMode::Mailbox
+            } else {
+                wgpu::PresentMode::Fifo
+            },
+            alpha_mode: capabilities.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&device, &config);
+
+        // Create the ray-marching pipeline with the voxel-octree, frame uniforms, world lights, and world hazards
+        // bound to set 0, and the previous frame bound to set 1.
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("voxel_bind_group_layout"),
+            entries: &[
+                buffer_layout_entry(0, wgpu::BufferBindingType::Storage { read_only: true }),
+                buffer_layout_entry(1, wgpu::BufferBindingType::Uniform),
+                buffer_layout_entry(2, wgpu::BufferBindingType::Uniform),
+                buffer_layout_entry(3, wgpu::BufferBindingType::Uniform),
+            ],
+        });
+        let history_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("history_bind_group_layout"),
+            entries: &[
+                texture_layout_entry(0, false),
+                texture_layout_entry(1, false),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::NonFiltering),
+                    count: None,
+                },
+            ],
+        });
+        let pipeline = create_pipeline(
+            &device,
+            &[&bind_group_layout, &history_layout],
+            &[config.format, DISTANCE_FORMAT],
+            "app_pipeline",
+            RAY_MARCH_VOXELS_FS,
+        );
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("frame_uniforms"),
+            size: std::mem::size_of::<FrameUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let lights_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("world_lights"),
+            size: std::mem::size_of::<WorldLights>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&lights_buffer, 0, bytemuck::bytes_of(lights));
+        let hazards_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("world_hazards"),
+            size: std::mem::size_of::<WorldHazards>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&hazards_buffer, 0, bytemuck::bytes_of(hazards));
+        let voxel_buffer = create_voxel_buffer(&device, octree.len());
+        queue.write_buffer(&voxel_buffer, 0, bytemuck::cast_slice(octree));
+        let bind_group = create_bind_group(
+            &device,
+            &bind_group_layout,
+            &voxel_buffer,
+            &uniform_buffer,
+            &lights_buffer,
+            &hazards_buffer,
+        );
+
+        // Create the pipeline which fills the pixels skipped when ray-marching in a checkerboard pattern.
+        let resolve_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("resolve_bind_group_layout"),
+            entries: &[
+                texture_layout_entry(0, false),
+                texture_layout_entry(1, false),
+                buffer_layout_entry(2, wgpu::BufferBindingType::Uniform),
+            ],
+        });
+        let resolve_pipeline = create_pipeline(
+            &device,
+            &[&resolve_layout],
+            &[config.format, DISTANCE_FORMAT],
+            "resolve_pipeline",
+            CHECKERBOARD_RESOLVE_FS,
+        );
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("resolve_uniforms"),
+            size: std::mem::size_of::<ResolveUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Create the pipeline which draws a top-down map of the voxel-octree into the minimap.
+        let map_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("map_bind_group_layout"),
+            entries: &[
+                buffer_layout_entry(0, wgpu::BufferBindingType::Storage { read_only: true }),
+                buffer_layout_entry(1, wgpu::BufferBindingType::Uniform),
+            ],
+        });
+        let map_pipeline = create_pipeline(
+            &device,
+            &[&map_layout],
+            &[config.format],
+            "map_pipeline",
+            OCTREE_MAP_FS,
+        );
+        let map_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("map_uniforms"),
+            size: std::mem::size_of::<MapUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Create the speed-lines pipeline, which scales the scene up from its texture to fill the window.
+        let speed_lines_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("speed_lines_bind_group_layout"),
+                entries: &[
+                    texture_layout_entry(0, true),
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    buffer_layout_entry(2, wgpu::BufferBindingType::Uniform),
+                ],
+            });
+        let speed_lines_pipeline = create_pipeline(
+            &device,
+            &[&speed_lines_layout],
+            &[config.format],
+            "speed_lines_pipeline",
+            SPEED_LINES_FS,
+        );
+        let speed_lines_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("speed_lines_uniforms"),
+            size: std::mem::size_of::<SpeedLinesUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let scene_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("scene_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..wgpu::SamplerDescriptor::default()
+        });
+
+        // The previous frame is reprojected pixel by pixel, so it isn't filtered.
+        let history_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("history_sampler"),
+            ..wgpu::SamplerDescriptor::default()
+        });
+        let scenes = [(); 2].map(|()| {
+            create_scene_target(
+                &device,
+                [&speed_lines_layout, &history_layout],
+                [&scene_sampler, &history_sampler],
+                &speed_lines_buffer,
+                config.format,
+                [config.width, config.height],
+            )
+        });
+
+        // Create the overlay state and renderer.
+        #[allow(clippy::cast_possible_truncation)]
+        let egui_winit = egui_winit::State::new(
+            egui::ViewportId::ROOT,
+            &window,
+            Some(window.scale_factor() as f32),
+            Some(device.limits().max_texture_dimension_2d as usize),
+        );
+        let egui_renderer = egui_wgpu::Renderer::new(&device, config.format, None, 1);
+
+        WgpuRenderer {
+            surface,
+            window,
+            bind_group,
+            bind_group_layout,
+            capture: FrameCapture::new(capturable, config.format),
+            checkerboard: None,
+            config,
+            device,
+            frame: 0,
+            gpu_names,
+            hazards_buffer,
+            history: None,
+            history_layout,
+            history_sampler,
+            lights_buffer,
+            map_buffer,
+            map_layout,
+            map_pipeline,
+            minimap: None,
+            pipeline,
+            queue,
+            render_scale: 1.,
+            resolve_buffer,
+            resolve_layout,
+            resolve_pipeline,
+            scenes,
+            scene_sampler,
+            speed_lines_buffer,
+            speed_lines_layout,
+            speed_lines_pipeline,
+            uniform_buffer,
+            voxel_buffer,
+            egui_ctx: egui::Context::default(),
+            egui_renderer,
+            egui_winit,
+            gui_active: false,
+        }
+    }
+
+    // End the overlay frame and prepare it for drawing.
+    fn end_gui(&mut self) -> GuiFrame {
+        let output = self.egui_ctx.end_frame();
+        self.egui_winit.handle_platform_output(
+            &self.window,
+            &self.egui_ctx,
+            output.platform_output,
+        );
+
+        // Upload new textures now, so that they aren't lost if this frame can't be drawn.
+        for (id, delta) in &output.textures_delta.set {
+            self.egui_renderer
+                .update_texture(&self.device, &self.queue, *id, delta);
+        }
+
+        GuiFrame {
+            paint_jobs: self
+                .egui_ctx
+                .tessellate(output.shapes, output.pixels_per_point),
+            screen: ScreenDescriptor {
+                size_in_pixels: [self.config.width, self.config.height],
+                pixels_per_point: output.pixels_per_point,
+            },
+            free: output.textures_delta.free,
+        }
+    }
+
+    // The size in pixels that the scene is ray-marched at.
+    fn scene_size(&self) -> [u32; 2] {
+        renderer::scaled_extent([self.config.width, self.config.height], self.render_scale)
+    }
+
+    // Recreate the textures the scene is rendered to, after the window or render scale has changed.
+    // The previous frame no longer matches the new textures.
+    fn recreate_scene(&mut self) {
+        self.scenes = [(); 2].map(|()| self.create_scene_target(self.scene_size()));
+        self.history = None;
+        self.checkerboard = None;
+    }
+
+    // Create the textures half of the scene is ray-marched into when rendering in a checkerboard pattern, unless
+    // they already exist.
+    fn prepare_checkerboard(&mut self) {
+        if self.checkerboard.is_some() {
+            return;
+        }
+        let size = renderer::checkerboard_extent(self.scene_size());
+        let view = create_texture_view(&self.device, "checkerboard", self.config.format, size);
+        let distance_view =
+            create_texture_view(&self.device, "checkerboard_distance", DISTANCE_FORMAT, size);
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("resolve_bind_group"),
+            layout: &self.resolve_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&distance_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.resolve_buffer.as_entire_binding(),
+                },
+            ],
+        });
+        self.checkerboard = Some(CheckerboardTarget {
+            view,
+            distance_view,
+            bind_group,
+        });
+    }
+
+    // Create a texture of the given size for the scene to be rendered to.
+    fn create_scene_target(&self, size: [u32; 2]) -> SceneTarget {
+        create_scene_target(
+            &self.device,
+            [&self.speed_lines_layout, &self.history_layout],
+            [&self.scene_sampler, &self.history_sampler],
+            &self.speed_lines_buffer,
+            self.config.format,
+            size,
+        )
+    }
+
+    // Copy the per-frame values into the uniform buffers of both passes.
+    fn write_uniforms(&self, uniforms: &FrameUniforms) {
+        self.queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(uniforms));
+        let speed_lines = SpeedLinesUniforms {
+            time: uniforms.time,
+            aspect_ratio: uniforms.aspect_ratio,
+            strength: uniforms.speed_lines,
+            flash: uniforms.crash_flash,
+            score_flash: uniforms.score_flash,
+            _padding: [0.; 3],
+        };
+        self.queue.write_buffer(
+            &self.speed_lines_buffer,
+            0,
+            bytemuck::bytes_of(&speed_lines),
+        );
+        let resolve = ResolveUniforms {
+            frame: uniforms.temporal >> renderer::TEMPORAL_FRAME_SHIFT,
+            _padding: [0; 3],
+        };
+        self.queue
+            .write_buffer(&self.resolve_buffer, 0, bytemuck::bytes_of(&resolve));
+    }
+
+    // Ray-march the scene into its texture, reading the previous frame, then begin the pass which draws it into
+    // the view with speed lines. The returned pass can draw anything else over the frame.
+    // In a checkerboard pattern, half of the scene is ray-marched into textures half as wide, then resolved to
+    // fill the scene.
+    fn begin_frame_passes<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        [scene, previous]: [&'a SceneTarget; 2],
+        checkerboard: Option<&'a CheckerboardTarget>,
+        view: &'a wgpu::TextureView,
+    ) -> wgpu::RenderPass<'a> {
+        {
+            let targets = match checkerboard {
+                Some(target) => [&target.view, &target.distance_view],
+                None => [&scene.view, &scene.distance_view],
+            };
+            let mut pass = begin_view_pass(encoder, "app", &targets);
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.set_bind_group(1, &previous.history_bind_group, &[]);
+            pass.draw(0..4, 0..1);
+        }
+        if let Some(target) = checkerboard {
+            let mut pass = begin_view_pass(
+                encoder,
+                "checkerboard_resolve",
+                &[&scene.view, &scene.distance_view],
+            );
+            pass.set_pipeline(&self.resolve_pipeline);
+            pass.set_bind_group(0, &target.bind_group, &[]);
+            pass.draw(0..4, 0..1);
+        }
+        let mut pass = begin_view_pass(encoder, "speed_lines_with_overlay", &[view]);
+        pass.set_pipeline(&self.speed_lines_pipeline);
+        pass.set_bind_group(0, &scene.bind_group, &[]);
+        pass.draw(0..4, 0..1);
+        pass
+    }
+}
+
+impl Renderer for WgpuRenderer {
+    fn window(&self) -> &Window {
+        &self.window
+    }
+
+    fn gpu_names(&self) -> &[String] {
+        &self.gpu_names
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn window_size(&self) -> [f32; 2] {
+        let size = self.window.inner_size();
+        [size.width as f32, size.height as f32]
+    }
+
+    fn resize(&mut self) {
+        let size = self.window.inner_size();
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        self.config.width = size.width;
+        self.config.height = size.height;
+        self.surface.configure(&self.device, &self.config);
+        self.recreate_scene();
+    }
+
+    fn update_gui(&mut self, event: &WindowEvent) -> bool {
+        self.egui_winit
+            .on_window_event(&self.egui_ctx, event)
+            .consumed
+    }
+
+    fn begin_gui(&mut self) -> egui::Context {
+        let raw_input = self.egui_winit.take_egui_input(&self.window);
+        self.egui_ctx.begin_frame(raw_input);
+        self.gui_active = true;
+        self.egui_ctx.clone()
+    }
+
+    fn upload_world(
+        &mut self,
+        octree: &[VoxelCompact],
+        lights: &WorldLights,
+        hazards: &WorldHazards,
+    ) {
+        // Reuse the voxel buffer when the new world fits, only rebinding after a reallocation.
+        let contents: &[u8] = bytemuck::cast_slice(octree);
+        if contents.len() as u64 > self.voxel_buffer.size() {
+            self.voxel_buffer = create_voxel_buffer(&self.device, octree.len());
+            self.bind_group = create_bind_group(
+                &self.device,
+                &self.bind_group_layout,
+                &self.voxel_buffer,
+                &self.uniform_buffer,
+                &self.lights_buffer,
+                &self.hazards_buffer,
+            );
+            if let Some(minimap) = &mut self.minimap {
+                minimap.bind_group = create_map_bind_group(
+                    &self.device,
+                    &self.map_layout,
+                    &self.voxel_buffer,
+                    &self.map_buffer,
+                );
+            }
+        }
+        self.queue.write_buffer(&self.voxel_buffer, 0, contents);
+        self.queue
+            .write_buffer(&self.lights_buffer, 0, bytemuck::bytes_of(lights));
+        self.queue
+            .write_buffer(&self.hazards_buffer, 0, bytemuck::bytes_of(hazards));
+
+        // The previous frame shows a different world.
+        self.history = None;
+    }
+
+    fn update_voxels(&mut self, first: usize, voxels: &[VoxelCompact]) {
+        let offset = (first * std::mem::size_of::<VoxelCompact>()) as u64;
+        self.queue
+            .write_buffer(&self.voxel_buffer, offset, bytemuck::cast_slice(voxels));
+    }
+
+    // Only the Vulkan backend draws particles and the trail.
+    fn upload_particles(&mut self, _: &[ParticleInstance]) {}
+
+    fn upload_trail(&mut self, _: &[TrailVertex]) {}
+
+    fn set_render_scale(&mut self, scale: f32) {
+        let previous = self.scene_size();
+        self.render_scale = scale;
+        if self.scene_size() != previous {
+            self.recreate_scene();
+        }
+    }
+
+    fn set_frame_capture(&mut self, enabled: bool) -> bool {
+        if enabled && self.capture.order.is_none() {
+            return false;
+        }
+        self.capture.enabled = enabled;
+        true
+    }
+
+    fn take_captured_frames(&mut self) -> Vec<CapturedFrame> {
+        self.capture.take(&self.device)
+    }
+
+    fn save_pipeline_cache(&self) {}
+
+    fn render_offscreen(
+        &mut self,
+        uniforms: &FrameUniforms,
+        size: [u32; 2],
+    ) -> Option<CapturedFrame> {
+        let order = self.capture.order?;
+        let [width, height] = size;
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("offscreen_frame"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // Offscreen frames ray-march every pixel without reprojecting, so their history is a placeholder.
+        let scene = self.create_scene_target(size);
+        let history = self.create_scene_target([1, 1]);
+        self.write_uniforms(&FrameUniforms {
+            temporal: 0,
+            ..*uniforms
+        });
+
+        // Draw the app alone, then copy the frame into a buffer that can be mapped.
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        drop(self.begin_frame_passes(&mut encoder, [&scene, &history], None, &view));
+        let mut slot = CaptureSlot::new(&self.device, width, height);
+        slot.copy_from(&mut encoder, &texture);
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        slot.map();
+        self.device.poll(wgpu::Maintain::Wait);
+        slot.read(order)
+    }
+
+    // Only the Vulkan backend draws the octree map.
+    fn set_map_window(&mut self, _: &EventLoopWindowTarget<()>, title: Option<&str>) -> bool {
+        title.is_none()
+    }
+
+    // Only the Vulkan backend multisamples.
+    fn set_multisampling(&mut self, _: &EventLoopWindowTarget<()>, samples: u32) -> bool {
+        samples <= 1
+    }
+
+    fn is_map_window(&self, _: WindowId) -> bool {
+        false
+    }
+
+    fn resize_map(&mut self) {}
+
+    fn minimap(&mut self, size: u32) -> Option<egui::TextureId> {
+        if let Some(minimap) = self.minimap.as_mut().filter(|minimap| minimap.size == size) {
+            minimap.redraw |= self.frame % renderer::MINIMAP_FRAME_INTERVAL == 0;
+            return Some(minimap.texture);
+        }
+
+        // Replace the minimap and its texture at the new size.
+        if let Some(minimap) = self.minimap.take() {
+            self.egui_renderer.free_texture(&minimap.texture);
+        }
+        let view = create_texture_view(&self.device, "minimap", self.config.format, [size; 2]);
+        let texture = self.egui_renderer.register_native_texture(
+            &self.device,
+            &view,
+            wgpu::FilterMode::Linear,
+        );
+        self.minimap = Some(Minimap {
+            bind_group: create_map_bind_group(
+                &self.device,
+                &self.map_layout,
+                &self.voxel_buffer,
+                &self.map_buffer,
+            ),
+            view,
+            size,
+            texture,
+            redraw: true,
+        });
+        Some(texture)
+    }
+
+    // VR is only rendered with Vulkan.
+    fn begin_xr_frame(&mut self) -> Option<[EyeView; 2]> {
+        None
+    }
+
+    fn end_xr_frame(&mut self, _eyes: &[FrameUniforms; 2]) {}
+
+    fn render_frame(&mut self, uniforms: &FrameUniforms) {
+        let gui = std::mem::take(&mut self.gui_active).then(|| self.end_gui());
+
+        // Acquire the next image, reconfiguring the surface when it no longer matches the window.
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => Some(frame),
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.resize();
+                None
+            }
+            Err(e) => {
+                log::warn!(target: "gpu", "Failed to acquire surface texture: {e}");
+                None
+            }
+        };
+
+        if let Some(frame) = frame {
+            // Render into the scene texture which held the frame before last, reprojecting the last frame.
+            self.scenes.swap(0, 1);
+            let uniforms = uniforms.reprojecting(self.history, self.frame);
+            self.history = Some(uniforms.history());
+            self.frame = self.frame.wrapping_add(1);
+            self.write_uniforms(&uniforms);
+            if uniforms.checkerboard() {
+                self.prepare_checkerboard();
+            }
+            let view = frame
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            let mut encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+
+            // Upload the overlay geometry before the render pass begins.
+            let gui_command_buffers = match &gui {
+                Some(gui) => self.egui_renderer.update_buffers(
+                    &self.device,
+                    &self.queue,
+                    &mut encoder,
+                    &gui.paint_jobs,
+                    &gui.screen,
+                ),
+                None => Vec::new(),
+            };
+
+            // Ray-march the minimap first when it is due, so that the overlay over this frame shows it.
+            if let Some(minimap) = self.minimap.as_mut().filter(|minimap| minimap.redraw) {
+                minimap.redraw = false;
+                self.queue.write_buffer(
+                    &self.map_buffer,
+                    0,
+                    bytemuck::bytes_of(&MapUniforms::minimap(&uniforms)),
+                );
+                let mut pass = begin_view_pass(&mut encoder, "minimap", &[&minimap.view]);
+                pass.set_pipeline(&self.map_pipeline);
+                pass.set_bind_group(0, &minimap.bind_group, &[]);
+                pass.draw(0..4, 0..1);
+            }
+
+            // Draw the app with speed lines, then the overlay on top.
+            {
+                let [scene, previous] = &self.scenes;
+                let checkerboard = self
+                    .checkerboard
+                    .as_ref()
+                    .filter(|_| uniforms.checkerboard());
+                let mut pass =
+                    self.begin_frame_passes(&mut encoder, [scene, previous], checkerboard, &view);
+                if let Some(gui) = &gui {
+                    self.egui_renderer
+                        .render(&mut pass, &gui.paint_jobs, &gui.screen);
+                }
+            }
+
+            // Copy the finished frame out if capturing.
+            let capture_slot = self.capture.begin(
+                &self.device,
+                &mut encoder,
+                &frame.texture,
+                self.config.width,
+                self.config.height,
+            );
+
+            self.queue.submit(
+                gui_command_buffers
+                    .into_iter()
+                    .chain(std::iter::once(encoder.finish())),
+            );
+            if let Some(slot) = capture_slot {
+                self.capture.end(slot);
+            }
+            frame.present();
+        }
+
+        // Release the overlay textures that are no longer used.
+        if let Some(gui) = gui {
+            for id in &gui.free {
+                self.egui_renderer.free_texture(id);
+            }
+        }
+    }
+}
+
+impl FrameCapture {
+    fn new(capturable: bool, format: wgpu::TextureFormat) -> Self {
+        let order = match format {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {
+                Some(PixelOrder::Rgba)
+            }
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+                Some(PixelOrder::Bgra)
+            }
+            _ => None,
+        };
+        FrameCapture {
+            enabled: false,
+            next: 0,
+            order: order.filter(|_| capturable),
+            ready: Vec::new(),
+            slots: Default::default(),
+        }
+    }
+
+    // Record a copy of the frame into the next buffer, if capturing, and return the buffer's index.
+    // A frame still waiting in the buffer is read back first, and the buffer is recreated when the
+    // frame size has changed.
+    fn begin(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        width: u32,
+        height: u32,
+    ) -> Option<usize> {
+        if !self.enabled {
+            return None;
+        }
+        let order = self.order?;
+        let slot = &mut self.slots[self.next];
+        if slot.as_ref().is_some_and(|slot| slot.pending) {
+            device.poll(wgpu::Maintain::Wait);
+        }
+        if let Some(frame) = slot.as_mut().and_then(|slot| slot.read(order)) {
+            self.ready.push(frame);
+        }
+        if let Some(stale) = slot.as_mut().filter(|slot| slot.pending) {
+            // The buffer failed to map, so the frame in it is lost.
+            stale.pending = false;
+        }
+        if slot
+            .as_ref()
+            .map_or(true, |slot| slot.width != width || slot.height != height)
+        {
+            *slot = Some(CaptureSlot::new(device, width, height));
+        }
+
+        slot.as_ref()?.copy_from(encoder, texture);
+        Some(self.next)
+    }
+
+    // Map the buffer once the copy recorded by `begin` has been submitted.
+    fn end(&mut self, index: usize) {
+        if let Some(slot) = &mut self.slots[index] {
+            slot.map();
+        }
+        self.next = (index + 1) % CAPTURE_BUFFER_COUNT;
+    }
+
+    // Read back the frames whose buffers have been mapped, stopping at the first unmapped one to keep them in order.
+    fn take(&mut self, device: &wgpu::Device) -> Vec<CapturedFrame> {
+        let mut frames = std::mem::take(&mut self.ready);
+        let Some(order) = self.order else {
+            return frames;
+        };
+        device.poll(wgpu::Maintain::Poll);
+        for i in 0..CAPTURE_BUFFER_COUNT {
+            let Some(slot) = &mut self.slots[(self.next + i) % CAPTURE_BUFFER_COUNT] else {
+                continue;
+            };
+            if !slot.pending {
+                continue;
+            }
+            match slot.read(order) {
+                Some(frame) => frames.push(frame),
+                None => break,
+            }
+        }
+        frames
+    }
+}
+
+impl CaptureSlot {
+    fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let padded_row = (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        CaptureSlot {
+            buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("frame_capture"),
+                size: u64::from(padded_row) * u64::from(height),
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            width,
+            height,
+            padded_row,
+            mapped: Arc::new(AtomicBool::new(false)),
+            pending: false,
+        }
+    }
+
+    // Record a copy of a texture of the buffer's size into the buffer.
+    fn copy_from(&self, encoder: &mut wgpu::CommandEncoder, texture: &wgpu::Texture) {
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    // Start mapping the buffer once the copy into it has been submitted.
+    fn map(&mut self) {
+        let mapped = self.mapped.clone();
+        self.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                mapped.store(result.is_ok(), Ordering::Release);
+            });
+        self.pending = true;
+    }
+
+    // Read back the frame in the buffer if it has been mapped, removing the padding from each row.
+    fn read(&mut self, order: PixelOrder) -> Option<CapturedFrame> {
+        if !self.pending || !self.mapped.swap(false, Ordering::Acquire) {
+            return None;
+        }
+        let row = self.width as usize * 4;
+        let mut pixels = Vec::with_capacity(row * self.height as usize);
+        for padded_row in self
+            .buffer
+            .slice(..)
+            .get_mapped_range()
+            .chunks_exact(self.padded_row as usize)
+        {
+            pixels.extend_from_slice(&padded_row[..row]);
+        }
+        self.buffer.unmap();
+        self.pending = false;
+        Some(CapturedFrame {
+            width: self.width,
+            height: self.height,
+            order,
+            pixels,
+        })
+    }
+}
+
+// Describe a buffer visible to the fragment shader.
+fn buffer_layout_entry(binding: u32, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+// Describe a 2D float texture visible to the fragment shader.
+fn texture_layout_entry(binding: u32, filterable: bool) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+// Begin a render pass which clears the views and draws into them.
+fn begin_view_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    label: &str,
+    views: &[&'a wgpu::TextureView],
+) -> wgpu::RenderPass<'a> {
+    let color_attachments: Vec<_> = views
+        .iter()
+        .map(|&view| {
+            Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })
+        })
+        .collect();
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &color_attachments,
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    })
+}
+
+// Create a pipeline which draws a quad over the entire view with the given fragment shader, writing to
+// targets of the given formats.
+fn create_pipeline(
+    device: &wgpu::Device,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    formats: &[wgpu::TextureFormat],
+    label: &str,
+    fragment_shader: &[u8],
+) -> wgpu::RenderPipeline {
+    let vs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("entire_view_vs"),
+        source: wgpu::util::make_spirv(ENTIRE_VIEW_VS),
+    });
+    let fs = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(label),
+        source: wgpu::util::make_spirv(fragment_shader),
+    });
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(label),
+        bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+    let targets: Vec<_> = formats
+        .iter()
+        .map(|&format| {
+            Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+        })
+        .collect();
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &vs,
+            entry_point: "main",
+            buffers: &[],
+        },
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+            ..wgpu::PrimitiveState::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        fragment: Some(wgpu::FragmentState {
+            module: &fs,
+            entry_point: "main",
+            targets: &targets,
+        }),
+        multiview: None,
+    })
+}
+
+// Upload a voxel-octree to the GPU and bind it with the frame uniforms.
+// Create a storage buffer with room for at least `voxel_count` voxels. Capacity grows in powers of two
+// so that slightly larger worlds can reuse the buffer.
+fn create_voxel_buffer(device: &wgpu::Device, voxel_count: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("voxel_octree"),
+        size: (voxel_count.next_power_of_two() * std::mem::size_of::<VoxelCompact>()) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    voxel_buffer: &wgpu::Buffer,
+    uniform_buffer: &wgpu::Buffer,
+    lights_buffer: &wgpu::Buffer,
+    hazards_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("voxel_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: voxel_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: lights_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: hazards_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+// Bind the voxel-octree and the map's uniforms for the octree-map shader.
+fn create_map_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    voxel_buffer: &wgpu::Buffer,
+    map_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("map_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: voxel_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: map_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+// Create a 2D texture of the given size to be rendered to and then read, and a view of all of it.
+fn create_texture_view(
+    device: &wgpu::Device,
+    label: &str,
+    format: wgpu::TextureFormat,
+    [width, height]: [u32; 2],
+) -> wgpu::TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+// Create textures of the given size for the scene and its distances to be rendered to, bound for the
+// speed-lines pass and the next frame to read.
+fn create_scene_target(
+    device: &wgpu::Device,
+    [layout, history_layout]: [&wgpu::BindGroupLayout; 2],
+    [sampler, history_sampler]: [&wgpu::Sampler; 2],
+    speed_lines_buffer: &wgpu::Buffer,
+    format: wgpu::TextureFormat,
+    size: [u32; 2],
+) -> SceneTarget {
+    let view = create_texture_view(device, "scene", format, size);
+    let distance_view = create_texture_view(device, "scene_distance", DISTANCE_FORMAT, size);
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("speed_lines_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: speed_lines_buffer.as_entire_binding(),
+            },
+        ],
+    });
+    let history_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("history_bind_group"),
+        layout: history_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&distance_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(history_sampler),
+            },
+        ],
+    });
+    SceneTarget {
+        view,
+        distance_view,
+        bind_group,
+        history_bind_group,
+    }
+}
+
+// Add the window's canvas to the page body and size it to the browser window.
+#[cfg(target_arch = "wasm32")]
+fn attach_canvas(window: &Window) {
+    use winit::platform::web::WindowExtWebSys;
+
+    let browser = web_sys::window().expect("Failed to access the browser window.");
+    browser
+        .document()
+        .and_then(|document| document.body())
+        .and_then(|body| body.append_child(&window.canvas()).ok())
+        .expect("Failed to add the canvas to the page.");
+
+    let width = browser.inner_width().ok().and_then(|w| w.as_f64());
+    let height = browser.inner_height().ok().and_then(|h| h.as_f64());
+    if let (Some(width), Some(height)) = (width, height) {
+        window.set_inner_size(winit::dpi::LogicalSize::new(width, height));
+    }
+}
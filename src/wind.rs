@@ -0,0 +1,202 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use cgmath::{InnerSpace, Rotation, Vector3};
+use egui::{Color32, Context, Stroke};
+use rand::Rng;
+
+use crate::game::State;
+use crate::voxels::{self, RandomOctreeHelper, TraversalCache, VoxelCompact};
+
+// Sine waves summed to form the potential whose curl is the wind. More waves give a less regular pattern.
+const WAVE_COUNT: usize = 4;
+
+// Radians per unit of the world of each wave, so that the wind changes a few times across the root voxel.
+const WAVE_FREQUENCY: f32 = 3.;
+
+// Radians per second that each wave drifts by, so that the wind changes slowly over time.
+const WAVE_SPEED: f32 = 0.4;
+
+// The turbulence felt by the camera samples the wind at this many times its frequency and speed.
+const TURBULENCE_DETAIL: f32 = 4.;
+
+// Speed of the wind at full strength, relative to the camera's speed.
+pub const WIND_DRIFT: f32 = 0.35;
+
+// Fraction of a full roll, pitch, and yaw input which turbulence can apply at full strength.
+pub const WIND_TURBULENCE: f32 = 0.15;
+
+pub const WIND_RANGE: std::ops::RangeInclusive<f32> = 0.0..=1.;
+pub const DEFAULT_WIND: f32 = 0.5;
+
+// Particles drifting with the wind around the camera, and how far away they may drift before being replaced,
+// in seconds of flight at the camera's speed.
+const PARTICLE_COUNT: usize = 48;
+const PARTICLE_RANGE: f32 = 1.5;
+
+// Length of each particle's streak in seconds of wind.
+const STREAK_SECONDS: f32 = 1.;
+
+// A wave of the potential, whose curl contributes `amplitude * cos(frequency . p + phase + speed * t)` along
+// `axis`.
+struct Wave {
+    frequency: Vector3<f32>,
+    axis: Vector3<f32>,
+    phase: f32,
+    speed: f32,
+}
+
+// A speck of dust carried by the wind, drawn only while it is in an empty voxel.
+struct Particle {
+    position: Vector3<f32>,
+    visible: bool,
+}
+
+// The wind of the current world, a divergence-free flow seeded by the world seed, and the particles showing it.
+#[derive(Default)]
+pub struct Wind {
+    waves: Vec<Wave>,
+    particles: Vec<Particle>,
+    cache: TraversalCache,
+}
+
+// A random vector with each component in the range [-1, 1].
+fn random_vector(mut sample: impl FnMut() -> f32) -> Vector3<f32> {
+    Vector3::new(2. * sample() - 1., 2. * sample() - 1., 2. * sample() - 1.)
+}
+
+impl Wind {
+    // Choose the waves of the wind from the world's RNG, so that the same seed blows the same wind.
+    pub fn new(random: &mut RandomOctreeHelper) -> Self {
+        let waves = (0..WAVE_COUNT)
+            .map(|_| {
+                let direction = random_vector(|| random.samplef()).normalize();
+                let frequency = WAVE_FREQUENCY * direction;
+                let other = random_vector(|| random.samplef());
+
+                // The curl of a wave is perpendicular to its frequency, so only that part of the axis matters.
+                let axis = frequency.cross(other - direction * direction.dot(other));
+                Wave {
+                    frequency,
+                    axis: axis / axis.magnitude().max(f32::EPSILON),
+                    phase: std::f32::consts::TAU * random.samplef(),
+                    speed: WAVE_SPEED * (0.5 + random.samplef()),
+                }
+            })
+            .collect();
+        Wind {
+            waves,
+            particles: Vec::new(),
+            cache: TraversalCache::default(),
+        }
+    }
+
+    // The wind at a point and time, the curl of a sum of sine waves, with a magnitude of about one.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn velocity(&self, position: Vector3<f32>, time: f32) -> Vector3<f32> {
+        let normalization = 1. / (self.waves.len().max(1) as f32).sqrt();
+        self.waves
+            .iter()
+            .map(|wave| {
+                normalization
+                    * (wave.frequency.dot(position) + wave.phase + wave.speed * time).cos()
+                    * wave.axis
+            })
+            .sum()
+    }
+
+    // Roll, pitch, and yaw disturbances in the range of about [-1, 1], from finer and faster detail of the wind.
+    pub fn turbulence(&self, position: Vector3<f32>, time: f32) -> (f32, f32, f32) {
+        let gust = self.velocity(TURBULENCE_DETAIL * position, TURBULENCE_DETAIL * time);
+        (gust.x, gust.y, gust.z)
+    }
+
+    // Speed of the wind in the world, which follows the camera's speed so that it is felt in any size of space.
+    pub fn drift_speed(game: &State) -> f32 {
+        game.options.wind.unwrap_or(0.) * WIND_DRIFT * game.camera_speed
+    }
+
+    // Carry the particles along with the wind, replacing those which drift too far from the camera with new ones
+    // ahead of it. Each particle is visible while it is in an empty voxel.
+    pub fn update_particles(
+        &mut self,
+        game: &State,
+        time: f32,
+        delta_time: f32,
+        octree: &[VoxelCompact],
+    ) {
+        let drift_speed = Self::drift_speed(game);
+        let range = PARTICLE_RANGE * game.camera_speed;
+        let forward = game.camera_quaternion.rotate_vector(Vector3::unit_z());
+        let mut rng = rand::thread_rng();
+        while self.particles.len() < PARTICLE_COUNT {
+            self.particles.push(Particle {
+                position: game.camera_position + range * random_vector(|| rng.gen()),
+                visible: false,
+            });
+        }
+        for i in 0..self.particles.len() {
+            let mut position = self.particles[i].position;
+            position += drift_speed * delta_time * self.velocity(position, time);
+            if (position - game.camera_position).magnitude2() > range * range {
+                position =
+                    game.camera_position + 0.5 * range * (forward + random_vector(|| rng.gen()));
+            }
+            let visible = voxels::empty_voxel_at(position, octree, &mut self.cache).is_some();
+            self.particles[i] = Particle { position, visible };
+        }
+    }
+
+    // Remove the particles, such as when the wind is turned off.
+    pub fn clear_particles(&mut self) {
+        self.particles.clear();
+    }
+
+    // Draw the visible particles behind any windows as short streaks along the wind, projected the way the shader
+    // projects the world. Nearer particles are drawn brighter.
+    pub fn draw_particles(&self, ctx: &Context, game: &State, time: f32) {
+        let camera_position = game.camera_position;
+        let camera_quaternion = game.shaken_camera_quaternion(time);
+        let drift_speed = Self::drift_speed(game);
+        let range = PARTICLE_RANGE * game.camera_speed;
+        let screen = ctx.screen_rect();
+        let lens = game.lens(screen.aspect_ratio());
+        let inverse = camera_quaternion.invert();
+        let painter = ctx.layer_painter(egui::LayerId::background());
+        for particle in self.particles.iter().filter(|particle| particle.visible) {
+            let tail = particle.position
+                - STREAK_SECONDS * drift_speed * self.velocity(particle.position, time);
+            let head_view = inverse.rotate_vector(particle.position - camera_position);
+            let tail_view = inverse.rotate_vector(tail - camera_position);
+            let near = 0.05 * range;
+            let (Some((head_point, _)), Some((tail_point, _))) = (
+                lens.to_screen(screen, head_view, near),
+                lens.to_screen(screen, tail_view, near),
+            ) else {
+                continue;
+            };
+            let fade = 1. - (head_view.magnitude() / range).min(1.);
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let alpha = (160. * fade) as u8;
+            painter.line_segment(
+                [tail_point, head_point],
+                Stroke::new(1.5, Color32::from_white_alpha(alpha)),
+            );
+        }
+    }
+}
@@ -0,0 +1,403 @@
+/*
+    voxel_flight_simulator - A simple game where you fly around randomly generated, recursive, voxel worlds.
+    Copyright (C) 2023 Ryan Andersen
+
+    voxel_flight_simulator is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    voxel_flight_simulator is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with voxel_flight_simulator. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use openxr as xr;
+
+use crate::renderer::EyeView;
+
+const VIEW_CONFIGURATION: xr::ViewConfigurationType = xr::ViewConfigurationType::PRIMARY_STEREO;
+
+// An OpenXR runtime with a head-mounted display, found before Vulkan is created so that Vulkan can be created with
+// the extensions the runtime needs, on the GPU the headset is plugged into.
+#[derive(Clone)]
+pub struct Headset {
+    instance: xr::Instance,
+    system: xr::SystemId,
+}
+
+// Raw handles of the Vulkan objects the headset's session renders with.
+pub struct VulkanHandles {
+    pub instance: u64,
+    pub physical_device: u64,
+    pub device: u64,
+    pub queue_family_index: u32,
+    pub queue_index: u32,
+}
+
+// A running session with the headset, and a swapchain for each eye which frames are rendered into.
+pub struct Session {
+    instance: xr::Instance,
+    session: xr::Session<xr::Vulkan>,
+    frame_waiter: xr::FrameWaiter,
+    frame_stream: xr::FrameStream<xr::Vulkan>,
+    space: xr::Space,
+    swapchains: [xr::Swapchain<xr::Vulkan>; 2],
+    images: [Vec<u64>; 2],
+    extent: [u32; 2],
+    events: xr::EventDataBuffer,
+    running: bool,
+    ended: bool,
+    frame: Option<Frame>,
+}
+
+// A headset frame which has begun, the views its eyes are rendered with once located, and how many eyes have been
+// rendered.
+struct Frame {
+    display_time: xr::Time,
+    views: Option<[xr::View; 2]>,
+    released: usize,
+}
+
+impl Headset {
+    // Load the OpenXR runtime and find a head-mounted display which can be rendered to with Vulkan.
+    pub fn new() -> Result<Self, String> {
+        let entry = unsafe { xr::Entry::load() }.map_err(|e| e.to_string())?;
+        let available = entry.enumerate_extensions().map_err(|e| e.to_string())?;
+        if !available.khr_vulkan_enable {
+            return Err("The OpenXR runtime doesn't support Vulkan".to_string());
+        }
+        let mut extensions = xr::ExtensionSet::default();
+        extensions.khr_vulkan_enable = true;
+        let instance = entry
+            .create_instance(
+                &xr::ApplicationInfo {
+                    application_name: "voxel_flight_simulator",
+                    application_version: 0,
+                    engine_name: "voxel_flight_simulator",
+                    engine_version: 0,
+                },
+                &extensions,
+                &[],
+            )
+            .map_err(|e| e.to_string())?;
+        let system = instance
+            .system(xr::FormFactor::HEAD_MOUNTED_DISPLAY)
+            .map_err(|e| e.to_string())?;
+
+        // The runtime must be asked which Vulkan versions it supports before a session can be created.
+        instance
+            .graphics_requirements::<xr::Vulkan>(system)
+            .map_err(|e| e.to_string())?;
+        let properties = instance
+            .system_properties(system)
+            .map_err(|e| e.to_string())?;
+        log::info!(target: "xr", "Using headset name=\"{}\"", properties.system_name);
+        Ok(Self { instance, system })
+    }
+
+    // The Vulkan instance extensions the runtime needs.
+    pub fn instance_extensions(&self) -> Vec<String> {
+        split_extensions(self.instance.vulkan_legacy_instance_extensions(self.system))
+    }
+
+    // The Vulkan device extensions the runtime needs.
+    pub fn device_extensions(&self) -> Vec<String> {
+        split_extensions(self.instance.vulkan_legacy_device_extensions(self.system))
+    }
+
+    // Whether the physical device with the given handle is the one the headset is plugged into.
+    pub fn is_graphics_device(&self, vulkan_instance: u64, physical_device: u64) -> bool {
+        let device = unsafe {
+            self.instance
+                .vulkan_graphics_device(self.system, vulkan_instance as usize as _)
+        };
+        device.is_ok_and(|device| device as usize as u64 == physical_device)
+    }
+
+    // Start a session rendering with the given Vulkan objects, with a swapchain for each eye in the given format.
+    //
+    // # Safety
+    // The handles must be of live Vulkan objects created with the extensions the runtime needs, which outlive the
+    // session.
+    pub unsafe fn start(self, vulkan: &VulkanHandles, format: u32) -> Result<Session, String> {
+        let (session, frame_waiter, frame_stream) = self
+            .instance
+            .create_session::<xr::Vulkan>(
+                self.system,
+                &xr::vulkan::SessionCreateInfo {
+                    instance: vulkan.instance as usize as _,
+                    physical_device: vulkan.physical_device as usize as _,
+                    device: vulkan.device as usize as _,
+                    queue_family_index: vulkan.queue_family_index,
+                    queue_index: vulkan.queue_index,
+                },
+            )
+            .map_err(|e| e.to_string())?;
+        let space = session
+            .create_reference_space(xr::ReferenceSpaceType::LOCAL, xr::Posef::IDENTITY)
+            .map_err(|e| e.to_string())?;
+
+        // Both eyes are rendered at the size the runtime recommends for the first.
+        let views = self
+            .instance
+            .enumerate_view_configuration_views(self.system, VIEW_CONFIGURATION)
+            .map_err(|e| e.to_string())?;
+        let view = views.first().ok_or("The headset has no views")?;
+        let extent = [
+            view.recommended_image_rect_width,
+            view.recommended_image_rect_height,
+        ];
+        let formats = session
+            .enumerate_swapchain_formats()
+            .map_err(|e| e.to_string())?;
+        if !formats.contains(&format) {
+            return Err(format!("The headset can't show images in format={format}"));
+        }
+        let create_swapchain = || {
+            session.create_swapchain(&xr::SwapchainCreateInfo {
+                create_flags: xr::SwapchainCreateFlags::EMPTY,
+                usage_flags: xr::SwapchainUsageFlags::COLOR_ATTACHMENT,
+                format,
+                sample_count: 1,
+                width: extent[0],
+                height: extent[1],
+                face_count: 1,
+                array_size: 1,
+                mip_count: 1,
+            })
+        };
+        let swapchains = [
+            create_swapchain().map_err(|e| e.to_string())?,
+            create_swapchain().map_err(|e| e.to_string())?,
+        ];
+        let images = [
+            swapchains[0]
+                .enumerate_images()
+                .map_err(|e| e.to_string())?,
+            swapchains[1]
+                .enumerate_images()
+                .map_err(|e| e.to_string())?,
+        ];
+
+        Ok(Session {
+            instance: self.instance,
+            session,
+            frame_waiter,
+            frame_stream,
+            space,
+            swapchains,
+            images,
+            extent,
+            events: xr::EventDataBuffer::new(),
+            running: false,
+            ended: false,
+            frame: None,
+        })
+    }
+}
+
+impl Session {
+    // The size in pixels of each eye's images.
+    pub fn extent(&self) -> [u32; 2] {
+        self.extent
+    }
+
+    // Raw handles of the images in an eye's swapchain.
+    pub fn images(&self, eye: usize) -> &[u64] {
+        &self.images[eye]
+    }
+
+    // Whether the runtime has ended the session, such as when the headset is lost, after which it can't be
+    // rendered to.
+    pub fn has_ended(&self) -> bool {
+        self.ended
+    }
+
+    // Follow the session's state, then wait for the headset's next frame and begin it. Returns the view of each eye
+    // when the frame should be rendered, which must then be ended with `end_frame`.
+    pub fn begin_frame(&mut self) -> Option<[EyeView; 2]> {
+        if let Err(e) = self.handle_events() {
+            log::error!(target: "xr", "Failed to follow the VR session: {e}");
+            self.ended = true;
+        }
+        if !self.running || self.ended {
+            return None;
+        }
+        match self.wait_and_locate() {
+            Ok(views) => views,
+            Err(e) => {
+                log::error!(target: "xr", "Failed to begin a VR frame: {e}");
+                None
+            }
+        }
+    }
+
+    // Acquire the image of an eye's swapchain to render the current frame into, waiting until it is free.
+    pub fn acquire_image(&mut self, eye: usize) -> Option<usize> {
+        let swapchain = &mut self.swapchains[eye];
+        let index = swapchain
+            .acquire_image()
+            .and_then(|index| swapchain.wait_image(xr::Duration::INFINITE).map(|()| index));
+        match index {
+            Ok(index) => Some(index as usize),
+            Err(e) => {
+                log::error!(target: "xr", "Failed to acquire a VR image: {e}");
+                None
+            }
+        }
+    }
+
+    // Hand an eye's image back to the headset once its frame has finished rendering.
+    pub fn release_image(&mut self, eye: usize) {
+        match self.swapchains[eye].release_image() {
+            Ok(()) => {
+                if let Some(frame) = &mut self.frame {
+                    frame.released += 1;
+                }
+            }
+            Err(e) => log::error!(target: "xr", "Failed to release a VR image: {e}"),
+        }
+    }
+
+    // End the current frame, showing it in the headset if both eyes were rendered.
+    pub fn end_frame(&mut self) {
+        let Some(frame) = self.frame.take() else {
+            return;
+        };
+        let result = match frame.views.filter(|_| frame.released == 2) {
+            Some(views) => {
+                let rect = xr::Rect2Di {
+                    offset: xr::Offset2Di { x: 0, y: 0 },
+                    extent: xr::Extent2Di {
+                        width: i32::try_from(self.extent[0]).unwrap_or(i32::MAX),
+                        height: i32::try_from(self.extent[1]).unwrap_or(i32::MAX),
+                    },
+                };
+                let views = [0, 1].map(|eye| {
+                    xr::CompositionLayerProjectionView::new()
+                        .pose(views[eye].pose)
+                        .fov(views[eye].fov)
+                        .sub_image(
+                            xr::SwapchainSubImage::new()
+                                .swapchain(&self.swapchains[eye])
+                                .image_array_index(0)
+                                .image_rect(rect),
+                        )
+                });
+                self.frame_stream.end(
+                    frame.display_time,
+                    xr::EnvironmentBlendMode::OPAQUE,
+                    &[&xr::CompositionLayerProjection::new()
+                        .space(&self.space)
+                        .views(&views)],
+                )
+            }
+            None => {
+                self.frame_stream
+                    .end(frame.display_time, xr::EnvironmentBlendMode::OPAQUE, &[])
+            }
+        };
+        if let Err(e) = result {
+            log::error!(target: "xr", "Failed to end a VR frame: {e}");
+        }
+    }
+
+    // Begin and end the session as the runtime asks, noting when it has ended for good.
+    fn handle_events(&mut self) -> Result<(), xr::sys::Result> {
+        while let Some(event) = self.instance.poll_event(&mut self.events)? {
+            match event {
+                xr::Event::SessionStateChanged(change) => match change.state() {
+                    xr::SessionState::READY => {
+                        self.session.begin(VIEW_CONFIGURATION)?;
+                        self.running = true;
+                    }
+                    xr::SessionState::STOPPING => {
+                        self.session.end()?;
+                        self.running = false;
+                    }
+                    xr::SessionState::EXITING | xr::SessionState::LOSS_PENDING => {
+                        self.running = false;
+                        self.ended = true;
+                    }
+                    _ => {}
+                },
+                xr::Event::InstanceLossPending(_) => {
+                    self.running = false;
+                    self.ended = true;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Wait for the next frame and begin it, finding where each eye will be when it is shown. Frames the runtime
+    // doesn't need rendered are ended straight away.
+    fn wait_and_locate(&mut self) -> Result<Option<[EyeView; 2]>, xr::sys::Result> {
+        let state = self.frame_waiter.wait()?;
+        self.frame_stream.begin()?;
+        self.frame = Some(Frame {
+            display_time: state.predicted_display_time,
+            views: None,
+            released: 0,
+        });
+        if !state.should_render {
+            self.end_frame();
+            return Ok(None);
+        }
+        let (_, views) = self.session.locate_views(
+            VIEW_CONFIGURATION,
+            state.predicted_display_time,
+            &self.space,
+        )?;
+        let (Some(&left), Some(&right)) = (views.first(), views.get(1)) else {
+            self.end_frame();
+            return Ok(None);
+        };
+
+        // The ray-marching shader's frustum is symmetric, so each eye is rendered with the widest half-angles of its
+        // view, and the headset is told the wider view that was rendered.
+        let views = [left, right].map(|mut view| {
+            let x = view.fov.angle_left.abs().max(view.fov.angle_right.abs());
+            let y = view.fov.angle_up.abs().max(view.fov.angle_down.abs());
+            view.fov = xr::Fovf {
+                angle_left: -x,
+                angle_right: x,
+                angle_up: y,
+                angle_down: -y,
+            };
+            view
+        });
+        if let Some(frame) = &mut self.frame {
+            frame.views = Some(views);
+        }
+        Ok(Some(views.map(|view| eye_view(&view))))
+    }
+}
+
+// An eye's view in the camera space of the game, which looks along +Z rather than OpenXR's -Z, mirroring the Z axis.
+fn eye_view(view: &xr::View) -> EyeView {
+    let orientation = view.pose.orientation;
+    let position = view.pose.position;
+    EyeView {
+        position: [position.x, position.y, -position.z],
+        orientation: [-orientation.x, -orientation.y, orientation.z, orientation.w],
+        tan_half_fov: [view.fov.angle_right.tan(), view.fov.angle_up.tan()],
+    }
+}
+
+// Split the space-separated names of the Vulkan extensions the runtime needs, needing none if it can't say.
+fn split_extensions(names: Result<String, xr::sys::Result>) -> Vec<String> {
+    match names {
+        Ok(names) => names.split_whitespace().map(str::to_string).collect(),
+        Err(e) => {
+            log::error!(target: "xr", "Failed to list the Vulkan extensions for VR: {e}");
+            Vec::new()
+        }
+    }
+}